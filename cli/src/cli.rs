@@ -17,6 +17,8 @@
 //! Polkadot CLI library.
 
 use clap::Parser;
+use polkadot_node_core_dispute_coordinator::ParticipationOrdering;
+use std::path::PathBuf;
 
 #[allow(missing_docs)]
 #[derive(Debug, Parser)]
@@ -59,6 +61,12 @@ pub enum Subcommand {
 	/// capabilities of running a validator.
 	HostPerfCheck,
 
+	/// Replays a validation dump written by a node started with `--validation-dump-dir`.
+	ReplayValidation(ReplayValidationCmd),
+
+	/// Inspect the availability store, and export or import candidate data and chunks.
+	AvailabilityStore(AvailabilityStoreCmd),
+
 	/// Try some command against runtime state.
 	#[cfg(feature = "try-runtime")]
 	TryRuntime(try_runtime_cli::TryRuntimeCmd),
@@ -84,6 +92,83 @@ pub struct ValidationWorkerCommand {
 	/// Calling node implementation version
 	#[arg(long)]
 	pub node_impl_version: String,
+	/// Restrict the worker with the seccomp syscall allowlist.
+	#[arg(long)]
+	pub enable_seccomp: bool,
+	/// Move the worker into its own namespaces, with an empty root that only contains the given
+	/// artifacts directory.
+	#[arg(long)]
+	pub sandbox_root: Option<String>,
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Parser)]
+pub struct ReplayValidationCmd {
+	/// The validation dump to replay.
+	pub dump: PathBuf,
+	/// The directory to keep the prepared artifacts in. Artifacts left in it by earlier replays
+	/// are reused. Defaults to a new temporary directory, so that the PVF is prepared from scratch.
+	#[arg(long)]
+	pub cache_path: Option<PathBuf>,
+	/// The executable to spawn the PVF workers from. Defaults to the current executable.
+	#[arg(long)]
+	pub program_path: Option<PathBuf>,
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Parser)]
+pub struct AvailabilityStoreCmd {
+	#[command(subcommand)]
+	pub subcommand: AvailabilityStoreSubcommand,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Parser)]
+pub enum AvailabilityStoreSubcommand {
+	/// List the stored candidates with their pruning state. The database is opened read-only.
+	List,
+
+	/// Export the available data of a candidate to a file. The database is opened read-only.
+	ExportData {
+		/// The hash of the candidate.
+		candidate_hash: String,
+		/// The file to write the available data to.
+		#[arg(long)]
+		output: PathBuf,
+	},
+
+	/// Export chunks of a candidate along with their merkle proofs, one file per chunk. The
+	/// database is opened read-only.
+	ExportChunks {
+		/// The hash of the candidate.
+		candidate_hash: String,
+		/// The index of a chunk to export. Can be given multiple times. Defaults to all stored
+		/// chunks.
+		#[arg(long = "index")]
+		indices: Vec<u32>,
+		/// The directory to write the chunks to, as `<candidate hash>-<index>.chunk`.
+		#[arg(long)]
+		output_dir: PathBuf,
+	},
+
+	/// Import exported available data and chunks. The node must not be running.
+	Import {
+		/// The erasure root of the candidate, from its receipt. The imported available data and
+		/// chunks are checked against it.
+		#[arg(long)]
+		erasure_root: String,
+		/// The exported files.
+		#[arg(required = true)]
+		files: Vec<PathBuf>,
+	},
 }
 
 #[allow(missing_docs)]
@@ -148,6 +233,87 @@ pub struct RunCmd {
 	/// **Dangerous!** Do not touch unless explicitly adviced to.
 	#[arg(long)]
 	pub overseer_channel_capacity_override: Option<usize>,
+
+	/// Write a dump of every candidate validation which does not conclude that the candidate
+	/// is valid to the given directory.
+	///
+	/// The dumps can be replayed with the `replay-validation` subcommand.
+	#[arg(long)]
+	pub validation_dump_dir: Option<PathBuf>,
+
+	/// How long to keep the available data of candidates which are not included in any block,
+	/// in seconds. Defaults to an hour.
+	#[arg(long, value_name = "SECONDS")]
+	pub av_store_keep_unfinalized: Option<u64>,
+
+	/// How long to keep the available data of candidates after the block they are included in
+	/// is finalized, in seconds. Defaults to 25 hours.
+	#[arg(long, value_name = "SECONDS")]
+	pub av_store_keep_finalized: Option<u64>,
+
+	/// The maximum size of the available data and chunks kept in the availability store, in MiB.
+	///
+	/// Once it is exceeded, the data of finalized candidates is evicted before its retention
+	/// period ends, least recently finalized first.
+	#[arg(long, value_name = "MiB")]
+	pub av_store_max_size: Option<u64>,
+
+	/// Never prune the available data of the given para once it is finalized. Can be given
+	/// multiple times.
+	#[arg(long, value_name = "PARA_ID")]
+	pub av_store_archive_para: Vec<u32>,
+
+	/// Downlink bandwidth availability chunk fetches may use, in Mbit/s. Defaults to 500.
+	#[arg(long, value_name = "MBIT/S")]
+	pub chunk_fetching_downlink: Option<u64>,
+
+	/// Uplink bandwidth availability chunk requests may use, in Mbit/s. Defaults to 500.
+	#[arg(long, value_name = "MBIT/S")]
+	pub chunk_fetching_uplink: Option<u64>,
+
+	/// How queued dispute participations are ordered. Defaults to the age of their relay parent.
+	#[arg(long, value_enum)]
+	pub dispute_participation_ordering: Option<ParticipationOrdering>,
+
+	/// How many best-effort dispute participations of a single session can be queued at the
+	/// same time. Defaults to half of the best-effort queue.
+	#[arg(long)]
+	pub dispute_participation_session_budget: Option<usize>,
+
+	/// Recover the available data of candidates with a large PoV from the systematic chunks
+	/// first, which avoids the cost of decoding, and only fall back to any chunks if that fails.
+	#[arg(long)]
+	pub systematic_chunks_recovery: bool,
+
+	/// Restrict the threads of the PVF workers that prepare and execute untrusted code with a
+	/// seccomp syscall allowlist. A worker making a forbidden syscall is killed and the
+	/// candidate is neither approved nor disputed.
+	///
+	/// Only supported on Linux.
+	#[arg(long)]
+	pub pvf_enable_seccomp: bool,
+
+	/// Spawn the PVF workers in their own namespaces and in cgroups created under the given
+	/// cgroup-v2 directory, which limit the memory and CPU a worker may use.
+	///
+	/// The directory must be delegated to the node with the `memory` and `cpu` controllers
+	/// available. Only supported on Linux.
+	#[arg(long, value_name = "PATH")]
+	pub pvf_sandbox_cgroup_root: Option<PathBuf>,
+
+	/// The memory, in MiB, a sandboxed PVF worker may use on top of what the executor parameters
+	/// of the session allow the PVF to use. It covers the worker binary and the compiled artifact.
+	///
+	/// Raise it if workers are reported to exceed the memory limit of their sandbox.
+	#[arg(long, value_name = "MIB", default_value_t = 512, requires = "pvf_sandbox_cgroup_root")]
+	pub pvf_sandbox_memory_overhead: u64,
+
+	/// Cover all relay VRF modulo approval assignments of a block with a single cert.
+	///
+	/// Such certs can only be checked by nodes which speak `v2` of the approval distribution
+	/// messages, so only enable this once most of the network has upgraded.
+	#[arg(long)]
+	pub enable_v2_assignments: bool,
 }
 
 #[allow(missing_docs)]
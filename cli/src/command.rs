@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{Cli, Subcommand};
+use crate::cli::{AvailabilityStoreCmd, AvailabilityStoreSubcommand, Cli, Subcommand};
 use frame_benchmarking_cli::{BenchmarkCmd, ExtrinsicFactory, SUBSTRATE_REFERENCE_HARDWARE};
 use futures::future::TryFutureExt;
 use log::info;
@@ -214,6 +214,104 @@ fn host_perf_check() -> Result<()> {
 	}
 }
 
+impl sc_cli::CliConfiguration for AvailabilityStoreCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&sc_cli::DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// Inspects the availability store, or exports from or imports into it.
+fn availability_store(
+	cmd: &AvailabilityStoreCmd,
+	database: &sc_service::config::DatabaseSource,
+) -> Result<()> {
+	use polkadot_node_core_av_store::offline::{self, ExportedItem};
+	use polkadot_primitives::{CandidateHash, ValidatorIndex};
+
+	let parse_candidate_hash = |candidate_hash: &str| {
+		candidate_hash.parse::<service::Hash>().map(CandidateHash).map_err(|err| {
+			Error::Other(format!("Invalid candidate hash {}: {}", candidate_hash, err))
+		})
+	};
+	let config = service::AVAILABILITY_CONFIG;
+
+	match &cmd.subcommand {
+		AvailabilityStoreSubcommand::List => {
+			let db = service::open_database_read_only(database)?;
+			for candidate in offline::list_candidates(&db, &config)? {
+				println!(
+					"{:?}: {:?}, prune at: {:?}, data available: {}, chunks: {}/{}",
+					candidate.candidate_hash,
+					candidate.state,
+					candidate.prune_at,
+					candidate.data_available,
+					candidate.chunks_stored.len(),
+					candidate.n_validators,
+				);
+			}
+		},
+		AvailabilityStoreSubcommand::ExportData { candidate_hash, output } => {
+			let candidate_hash = parse_candidate_hash(candidate_hash)?;
+			let db = service::open_database_read_only(database)?;
+			let item =
+				offline::export_available_data(&db, &config, candidate_hash)?.ok_or_else(|| {
+					Error::Other(format!("No available data of {:?} in the store", candidate_hash))
+				})?;
+			item.write(output).map_err(sc_cli::Error::from)?;
+			info!("Exported the available data of {:?} to {:?}", candidate_hash, output);
+		},
+		AvailabilityStoreSubcommand::ExportChunks { candidate_hash, indices, output_dir } => {
+			let candidate_hash = parse_candidate_hash(candidate_hash)?;
+			let db = service::open_database_read_only(database)?;
+			let indices = if indices.is_empty() {
+				offline::load_candidate(&db, &config, candidate_hash)?
+					.ok_or_else(|| Error::Other(format!("No {:?} in the store", candidate_hash)))?
+					.chunks_stored
+			} else {
+				indices.iter().copied().map(ValidatorIndex).collect()
+			};
+
+			std::fs::create_dir_all(output_dir).map_err(sc_cli::Error::from)?;
+			for index in indices {
+				let item = offline::export_chunk(&db, &config, candidate_hash, index)?.ok_or_else(
+					|| {
+						Error::Other(format!(
+							"No chunk {} of {:?} in the store",
+							index.0, candidate_hash
+						))
+					},
+				)?;
+				let path = output_dir.join(format!("{:?}-{}.chunk", candidate_hash, index.0));
+				item.write(&path).map_err(sc_cli::Error::from)?;
+				info!("Exported chunk {} of {:?} to {:?}", index.0, candidate_hash, path);
+			}
+		},
+		AvailabilityStoreSubcommand::Import { erasure_root, files } => {
+			let erasure_root = erasure_root.parse::<service::Hash>().map_err(|err| {
+				Error::Other(format!("Invalid erasure root {}: {}", erasure_root, err))
+			})?;
+			let db = service::open_database(database)?;
+			for path in files {
+				let item = ExportedItem::read(path).map_err(sc_cli::Error::from)?;
+				let candidate_hash = item.candidate_hash();
+				if !offline::import(&db, &config, item, erasure_root)? {
+					return Err(Error::Other(format!(
+						"{:?} does not fit {:?} in the store",
+						path, candidate_hash
+					)))
+				}
+				info!("Imported {:?} of {:?}", path, candidate_hash);
+			}
+		},
+	}
+
+	Ok(())
+}
+
 /// Launch a node, accepting arguments just like a regular node,
 /// accepts an alternative overseer generator, to adjust behavior
 /// for integration tests as needed.
@@ -274,6 +372,51 @@ where
 		None
 	};
 
+	let dispute_participation = {
+		use polkadot_node_core_dispute_coordinator::ParticipationConfig;
+
+		let default = ParticipationConfig::default();
+		ParticipationConfig {
+			ordering: cli.run.dispute_participation_ordering.unwrap_or(default.ordering),
+			best_effort_session_budget: cli
+				.run
+				.dispute_participation_session_budget
+				.unwrap_or(default.best_effort_session_budget),
+		}
+	};
+
+	let availability_pruning_policy = {
+		let default = polkadot_node_core_av_store::PruningPolicy::default();
+		polkadot_node_core_av_store::PruningPolicy {
+			keep_unfinalized_for: cli
+				.run
+				.av_store_keep_unfinalized
+				.map_or(default.keep_unfinalized_for, std::time::Duration::from_secs),
+			keep_finalized_for: cli
+				.run
+				.av_store_keep_finalized
+				.map_or(default.keep_finalized_for, std::time::Duration::from_secs),
+			max_total_size: cli.run.av_store_max_size.map(|mib| mib * 1024 * 1024),
+			archive_paras: cli.run.av_store_archive_para.iter().map(|&p| p.into()).collect(),
+		}
+	};
+
+	let chunk_fetching_budget = {
+		// 1 Mbit/s is 125_000 bytes per second.
+		let bytes_per_sec = |mbit: u64| mbit.saturating_mul(125_000);
+		let default = polkadot_availability_distribution::ChunkFetchingBudget::default();
+		polkadot_availability_distribution::ChunkFetchingBudget {
+			downlink_bytes_per_sec: cli
+				.run
+				.chunk_fetching_downlink
+				.map_or(default.downlink_bytes_per_sec, bytes_per_sec),
+			uplink_bytes_per_sec: cli
+				.run
+				.chunk_fetching_uplink
+				.map_or(default.uplink_bytes_per_sec, bytes_per_sec),
+		}
+	};
+
 	runner.run_node_until_exit(move |config| async move {
 		let hwbench = (!cli.run.no_hardware_benchmarks)
 			.then_some(config.database.path().map(|database_path| {
@@ -285,16 +428,39 @@ where
 		let database_source = config.database.clone();
 		let task_manager = service::build_full(
 			config,
-			service::IsCollator::No,
-			grandpa_pause,
-			enable_beefy,
-			jaeger_agent,
-			None,
-			false,
-			overseer_gen,
-			cli.run.overseer_channel_capacity_override,
-			maybe_malus_finality_delay,
-			hwbench,
+			service::NewFullParams {
+				is_collator: service::IsCollator::No,
+				grandpa_pause,
+				enable_beefy,
+				jaeger_agent,
+				telemetry_worker_handle: None,
+				program_path: None,
+				subsystems: service::SubsystemsParams {
+					validation_dump_dir: cli.run.validation_dump_dir,
+					availability_pruning_policy,
+					enable_v2_assignments: cli.run.enable_v2_assignments,
+					chunk_fetching_budget,
+					dispute_participation,
+					systematic_chunks_recovery: cli.run.systematic_chunks_recovery,
+					pvf_enable_seccomp: cli.run.pvf_enable_seccomp,
+					pvf_sandbox: cli.run.pvf_sandbox_cgroup_root.map(|cgroup_root| {
+						polkadot_node_core_candidate_validation::PvfSandboxConfig {
+							cgroup_root,
+							worker_memory_overhead: cli
+								.run
+								.pvf_sandbox_memory_overhead
+								.saturating_mul(1024 * 1024),
+						}
+					}),
+				},
+				overseer_enable_anyways: false,
+				overseer_gen,
+				overseer_message_channel_capacity_override: cli
+					.run
+					.overseer_channel_capacity_override,
+				malus_finality_delay: maybe_malus_finality_delay,
+				hwbench,
+			},
 		)
 		.map(|full| full.task_manager)?;
 
@@ -439,6 +605,8 @@ pub fn run() -> Result<()> {
 				polkadot_node_core_pvf_prepare_worker::worker_entrypoint(
 					&cmd.socket_path,
 					Some(&cmd.node_impl_version),
+					cmd.enable_seccomp,
+					cmd.sandbox_root.as_deref(),
 				);
 				Ok(())
 			}
@@ -461,6 +629,8 @@ pub fn run() -> Result<()> {
 				polkadot_node_core_pvf_execute_worker::worker_entrypoint(
 					&cmd.socket_path,
 					Some(&cmd.node_impl_version),
+					cmd.enable_seccomp,
+					cmd.sandbox_root.as_deref(),
 				);
 				Ok(())
 			}
@@ -566,6 +736,28 @@ pub fn run() -> Result<()> {
 
 			host_perf_check()
 		},
+		Some(Subcommand::ReplayValidation(cmd)) => {
+			use polkadot_node_core_candidate_validation::dump::{replay, ValidationDump};
+
+			let mut builder = sc_cli::LoggerBuilder::new("");
+			builder.with_colors(true);
+			builder.init()?;
+
+			let dump = ValidationDump::read(&cmd.dump).map_err(sc_cli::Error::from)?;
+			let program_path = match cmd.program_path {
+				Some(program_path) => program_path,
+				None => std::env::current_exe().map_err(sc_cli::Error::from)?,
+			};
+
+			let runtime = sc_cli::build_runtime().map_err(sc_cli::Error::from)?;
+			let report = runtime.block_on(replay(dump, cmd.cache_path, program_path))?;
+			println!("{}", report);
+			Ok(())
+		},
+		Some(Subcommand::AvailabilityStore(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			Ok(runner.sync_run(|config| availability_store(cmd, &config.database))?)
+		},
 		Some(Subcommand::Key(cmd)) => Ok(cmd.run(&cli)?),
 		#[cfg(feature = "try-runtime")]
 		Some(Subcommand::TryRuntime(cmd)) => {
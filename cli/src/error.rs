@@ -28,6 +28,9 @@ pub enum Error {
 	#[error(transparent)]
 	SubstrateTracing(#[from] sc_tracing::logging::Error),
 
+	#[error(transparent)]
+	AvailabilityStore(#[from] polkadot_node_core_av_store::Error),
+
 	#[error(transparent)]
 	#[cfg(feature = "hostperfcheck")]
 	PerfCheck(#[from] polkadot_performance_test::PerfCheckError),
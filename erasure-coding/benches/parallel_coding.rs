@@ -0,0 +1,105 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::time::Duration;
+
+const N_VALIDATORS: [usize; 6] = [10, 50, 100, 200, 500, 1000];
+
+const KB: usize = 1024;
+const MB: usize = 1024 * KB;
+
+fn chunks(n_validators: usize, pov: &Vec<u8>) -> Vec<Vec<u8>> {
+	polkadot_erasure_coding::obtain_chunks(n_validators, pov).unwrap()
+}
+
+fn construct_and_reconstruct_5mb_pov(c: &mut Criterion) {
+	let pov = vec![0xfe; 5 * MB];
+
+	for (name, parallel) in [("construct", false), ("construct_parallel", true)] {
+		let mut group = c.benchmark_group(name);
+		for n_validators in N_VALIDATORS {
+			let expected_root =
+				polkadot_erasure_coding::branches(&chunks(n_validators, &pov)).root();
+
+			group.throughput(Throughput::Bytes(pov.len() as u64));
+			group.bench_with_input(
+				BenchmarkId::from_parameter(n_validators),
+				&n_validators,
+				|b, &n| {
+					b.iter(|| {
+						let root = if parallel {
+							let chunks =
+								polkadot_erasure_coding::obtain_chunks_parallel(n, &pov).unwrap();
+							polkadot_erasure_coding::branches_parallel(&chunks).root()
+						} else {
+							polkadot_erasure_coding::branches(&chunks(n, &pov)).root()
+						};
+						assert_eq!(root, expected_root);
+					});
+				},
+			);
+		}
+		group.finish();
+	}
+
+	for (name, parallel) in [("reconstruct", false), ("reconstruct_parallel", true)] {
+		let mut group = c.benchmark_group(name);
+		for n_validators in N_VALIDATORS {
+			let all_chunks = chunks(n_validators, &pov);
+			let threshold = polkadot_erasure_coding::recovery_threshold(n_validators).unwrap();
+			// the last chunks, so that the systematic ones can not be used as they are.
+			let last_chunks: Vec<_> = all_chunks
+				.iter()
+				.enumerate()
+				.rev()
+				.take(threshold)
+				.map(|(i, c)| (&c[..], i))
+				.collect();
+
+			group.throughput(Throughput::Bytes(pov.len() as u64));
+			group.bench_with_input(
+				BenchmarkId::from_parameter(n_validators),
+				&n_validators,
+				|b, &n| {
+					b.iter(|| {
+						let _pov: Vec<u8> = if parallel {
+							polkadot_erasure_coding::reconstruct_parallel(n, last_chunks.clone())
+								.unwrap()
+						} else {
+							polkadot_erasure_coding::reconstruct(n, last_chunks.clone()).unwrap()
+						};
+					});
+				},
+			);
+		}
+		group.finish();
+	}
+}
+
+fn criterion_config() -> Criterion {
+	Criterion::default()
+		.sample_size(15)
+		.warm_up_time(Duration::from_millis(200))
+		.measurement_time(Duration::from_secs(3))
+}
+
+criterion_group!(
+	name = parallel_coding;
+	config = criterion_config();
+	targets = construct_and_reconstruct_5mb_pov,
+);
+criterion_main!(parallel_coding);
@@ -23,10 +23,20 @@
 //! Each of n validators stores their piece of data. We assume `n = 3f + k`, `0 < k ≤ 3`.
 //! f is the maximum number of faulty validators in the system.
 //! The data is coded so any f+1 chunks can be used to reconstruct the full data.
+//!
+//! The code is systematic: the first [`systematic_recovery_threshold`] chunks contain the encoded
+//! data itself, so the data can be recovered from exactly those chunks without any decoding, see
+//! [`reconstruct_from_systematic`].
+//!
+//! The code works on runs of [`systematic_recovery_threshold`] two-byte symbols, and every run
+//! yields one symbol of each chunk, independently of all the other runs. [`obtain_chunks_parallel`]
+//! and [`reconstruct_parallel`] make use of that by splitting the chunks into columns of symbols,
+//! which are coded on the rayon thread pool and concatenated afterwards.
 
 use parity_scale_codec::{Decode, Encode};
 use polkadot_node_primitives::{AvailableData, Proof};
 use polkadot_primitives::{BlakeTwo256, Hash as H256, HashT};
+use rayon::prelude::*;
 use sp_core::Blake2Hasher;
 use sp_trie::{
 	trie_types::{TrieDBBuilder, TrieDBMutBuilderV0 as TrieDBMutBuilder},
@@ -96,6 +106,20 @@ pub const fn recovery_threshold(n_validators: usize) -> Result<usize, Error> {
 	Ok(needed + 1)
 }
 
+/// Obtain the number of systematic chunks, i.e. the number of chunks at the start of the
+/// erasure-coding which together contain the whole encoded data.
+///
+/// This is the [`recovery_threshold`] rounded down to a power of two, so it never exceeds it.
+pub const fn systematic_recovery_threshold(n_validators: usize) -> Result<usize, Error> {
+	let threshold = match recovery_threshold(n_validators) {
+		Ok(threshold) => threshold,
+		Err(e) => return Err(e),
+	};
+
+	// the payload shard count of the code is the next lower power of two.
+	Ok(1 << (usize::BITS - 1 - threshold.leading_zeros()))
+}
+
 fn code_params(n_validators: usize) -> Result<CodeParams, Error> {
 	// we need to be able to reconstruct from 1/3 - eps
 
@@ -139,6 +163,66 @@ pub fn obtain_chunks<T: Encode>(n_validators: usize, data: &T) -> Result<Vec<Vec
 	Ok(shards.into_iter().map(|w: WrappedShard| w.into_inner()).collect())
 }
 
+/// Obtain erasure-coded chunks, one for each validator, using all threads of the rayon thread
+/// pool.
+///
+/// The chunks are identical to the ones returned by [`obtain_chunks`].
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn obtain_chunks_parallel<T: Encode>(
+	n_validators: usize,
+	data: &T,
+) -> Result<Vec<Vec<u8>>, Error> {
+	obtain_chunks_in_columns(n_validators, data, None)
+}
+
+/// Obtain the chunks by encoding columns of `symbols_per_column` symbols of every chunk in
+/// parallel. By default, there is one column per thread.
+fn obtain_chunks_in_columns<T: Encode>(
+	n_validators: usize,
+	data: &T,
+	symbols_per_column: Option<usize>,
+) -> Result<Vec<Vec<u8>>, Error> {
+	let params = code_params(n_validators)?;
+	let encoded = data.encode();
+
+	if encoded.is_empty() {
+		return Err(Error::BadPayload)
+	}
+
+	// every run of `2 * k` bytes of the payload yields one symbol of each chunk.
+	let run_len = 2 * systematic_recovery_threshold(n_validators)?;
+	let symbols_per_column = symbols_per_column
+		.unwrap_or_else(|| symbols_per_column_default((encoded.len() + run_len - 1) / run_len));
+
+	let columns: Vec<Vec<WrappedShard>> = encoded
+		.par_chunks(run_len * symbols_per_column)
+		.map(|column| {
+			params.make_encoder().encode::<WrappedShard>(column).expect(
+				"Column non-empty, shard sizes are uniform, and validator numbers checked; qed",
+			)
+		})
+		.collect();
+
+	let chunk_len = columns.iter().map(|column| AsRef::<[u8]>::as_ref(&column[0]).len()).sum();
+	Ok((0..n_validators)
+		.into_par_iter()
+		.map(|chunk_index| {
+			let mut chunk = Vec::with_capacity(chunk_len);
+			for column in &columns {
+				chunk.extend_from_slice(AsRef::<[u8]>::as_ref(&column[chunk_index]));
+			}
+			chunk
+		})
+		.collect())
+}
+
+// the number of symbols per column which yields one column per thread of the pool.
+fn symbols_per_column_default(chunk_symbols: usize) -> usize {
+	let n_columns = rayon::current_num_threads().max(1);
+	((chunk_symbols + n_columns - 1) / n_columns).max(1)
+}
+
 /// Reconstruct the v1 available data from a set of chunks.
 ///
 /// Provide an iterator containing chunk data and the corresponding index.
@@ -165,7 +249,84 @@ where
 	I: IntoIterator<Item = (&'a [u8], usize)>,
 {
 	let params = code_params(n_validators)?;
-	let mut received_shards: Vec<Option<WrappedShard>> = vec![None; n_validators];
+	let (received_chunks, _) = received_chunks(n_validators, chunks)?;
+	let received_shards = received_chunks
+		.into_iter()
+		.map(|chunk| chunk.map(|chunk_data| WrappedShard::new(chunk_data.to_vec())))
+		.collect();
+
+	let payload_bytes = params
+		.make_encoder()
+		.reconstruct(received_shards)
+		.map_err(reconstruction_error)?;
+
+	Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
+}
+
+/// Reconstruct decodable data from a set of chunks, using all threads of the rayon thread pool.
+///
+/// Takes the same input and yields the same result as [`reconstruct`].
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn reconstruct_parallel<'a, I: 'a, T: Decode>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<T, Error>
+where
+	I: IntoIterator<Item = (&'a [u8], usize)>,
+{
+	reconstruct_in_columns(n_validators, chunks, None)
+}
+
+/// Reconstruct the data by decoding columns of `symbols_per_column` symbols of every chunk in
+/// parallel. By default, there is one column per thread.
+fn reconstruct_in_columns<'a, I: 'a, T: Decode>(
+	n_validators: usize,
+	chunks: I,
+	symbols_per_column: Option<usize>,
+) -> Result<T, Error>
+where
+	I: IntoIterator<Item = (&'a [u8], usize)>,
+{
+	let params = code_params(n_validators)?;
+	let (received_chunks, shard_len) = received_chunks(n_validators, chunks)?;
+
+	// without any chunk there is nothing to split, and the decoder would ask for more shards.
+	let shard_len = shard_len.ok_or(Error::NotEnoughChunks)?;
+	let column_len =
+		2 * symbols_per_column.unwrap_or_else(|| symbols_per_column_default(shard_len / 2));
+	let n_columns = (shard_len + column_len - 1) / column_len;
+
+	let columns = (0..n_columns)
+		.into_par_iter()
+		.map(|column| {
+			let start = column * column_len;
+			let end = std::cmp::min(start + column_len, shard_len);
+			let received_shards = received_chunks
+				.iter()
+				.map(|chunk| {
+					chunk.map(|chunk_data| WrappedShard::new(chunk_data[start..end].to_vec()))
+				})
+				.collect();
+
+			params.make_encoder().reconstruct(received_shards).map_err(reconstruction_error)
+		})
+		.collect::<Result<Vec<Vec<u8>>, Error>>()?;
+	let payload_bytes = columns.concat();
+
+	Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
+}
+
+// place the chunks at their index and check that they are of uniform, even and non-zero length,
+// which is returned if there is any chunk.
+fn received_chunks<'a, I: 'a>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<(Vec<Option<&'a [u8]>>, Option<usize>), Error>
+where
+	I: IntoIterator<Item = (&'a [u8], usize)>,
+{
+	let mut received_chunks: Vec<Option<&[u8]>> = vec![None; n_validators];
 	let mut shard_len = None;
 	for (chunk_data, chunk_idx) in chunks.into_iter().take(n_validators) {
 		if chunk_idx >= n_validators {
@@ -182,24 +343,78 @@ where
 			return Err(Error::NonUniformChunks)
 		}
 
-		received_shards[chunk_idx] = Some(WrappedShard::new(chunk_data.to_vec()));
+		received_chunks[chunk_idx] = Some(chunk_data);
 	}
 
-	let res = params.make_encoder().reconstruct(received_shards);
+	Ok((received_chunks, shard_len))
+}
 
-	let payload_bytes = match res {
-		Err(e) => match e {
-			novelpoly::Error::NeedMoreShards { .. } => return Err(Error::NotEnoughChunks),
-			novelpoly::Error::ParamterMustBePowerOf2 { .. } => return Err(Error::UnevenLength),
-			novelpoly::Error::WantedShardCountTooHigh(_) => return Err(Error::TooManyValidators),
-			novelpoly::Error::WantedShardCountTooLow(_) => return Err(Error::NotEnoughValidators),
-			novelpoly::Error::PayloadSizeIsZero { .. } => return Err(Error::BadPayload),
-			novelpoly::Error::InconsistentShardLengths { .. } =>
-				return Err(Error::NonUniformChunks),
-			_ => return Err(Error::UnknownReconstruction),
-		},
-		Ok(payload_bytes) => payload_bytes,
-	};
+fn reconstruction_error(e: novelpoly::Error) -> Error {
+	match e {
+		novelpoly::Error::NeedMoreShards { .. } => Error::NotEnoughChunks,
+		novelpoly::Error::ParamterMustBePowerOf2 { .. } => Error::UnevenLength,
+		novelpoly::Error::WantedShardCountTooHigh(_) => Error::TooManyValidators,
+		novelpoly::Error::WantedShardCountTooLow(_) => Error::NotEnoughValidators,
+		novelpoly::Error::PayloadSizeIsZero { .. } => Error::BadPayload,
+		novelpoly::Error::InconsistentShardLengths { .. } => Error::NonUniformChunks,
+		_ => Error::UnknownReconstruction,
+	}
+}
+
+/// Reconstruct the v1 available data from the systematic chunks.
+///
+/// Provide an iterator containing the data of the first [`systematic_recovery_threshold`] chunks,
+/// in the order of their indices. If too few chunks are provided, recovery is not possible.
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn reconstruct_from_systematic_v1<'a, I: 'a>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<AvailableData, Error>
+where
+	I: IntoIterator<Item = &'a [u8]>,
+{
+	reconstruct_from_systematic(n_validators, chunks)
+}
+
+/// Reconstruct decodable data from the systematic chunks.
+///
+/// Provide an iterator containing the data of the first [`systematic_recovery_threshold`] chunks,
+/// in the order of their indices. If too few chunks are provided, recovery is not possible.
+///
+/// Unlike [`reconstruct`], this does not do any decoding: the systematic chunks are merely
+/// interleaved back into the encoded data.
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn reconstruct_from_systematic<'a, I: 'a, T: Decode>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<T, Error>
+where
+	I: IntoIterator<Item = &'a [u8]>,
+{
+	let k = systematic_recovery_threshold(n_validators)?;
+	let chunks: Vec<&[u8]> = chunks.into_iter().take(k).collect();
+	if chunks.len() < k {
+		return Err(Error::NotEnoughChunks)
+	}
+
+	let shard_len = chunks[0].len();
+	if shard_len % 2 != 0 {
+		return Err(Error::UnevenLength)
+	}
+	if shard_len == 0 || chunks.iter().any(|chunk| chunk.len() != shard_len) {
+		return Err(Error::NonUniformChunks)
+	}
+
+	// The encoder splits the payload into runs of `k` symbols of two bytes each, and the `i`-th
+	// symbol of every run ends up in the `i`-th chunk.
+	let mut payload_bytes = Vec::with_capacity(shard_len * k);
+	for symbol in (0..shard_len).step_by(2) {
+		for chunk in &chunks {
+			payload_bytes.extend_from_slice(&chunk[symbol..symbol + 2]);
+		}
+	}
 
 	Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
 }
@@ -255,15 +470,35 @@ pub fn branches<'a, I: 'a>(chunks: &'a [I]) -> Branches<'a, I>
 where
 	I: AsRef<[u8]>,
 {
+	let chunk_hashes = chunks.iter().map(|chunk| BlakeTwo256::hash(chunk.as_ref()));
+	branches_from_hashes(chunks, chunk_hashes)
+}
+
+/// Construct a trie from chunks of an erasure-coded value, hashing the chunks on all threads of
+/// the rayon thread pool.
+///
+/// The root and the merkle proofs are identical to the ones of [`branches`].
+pub fn branches_parallel<'a, I: 'a>(chunks: &'a [I]) -> Branches<'a, I>
+where
+	I: AsRef<[u8]> + Sync,
+{
+	let chunk_hashes: Vec<H256> =
+		chunks.par_iter().map(|chunk| BlakeTwo256::hash(chunk.as_ref())).collect();
+	branches_from_hashes(chunks, chunk_hashes)
+}
+
+fn branches_from_hashes<'a, I: 'a>(
+	chunks: &'a [I],
+	chunk_hashes: impl IntoIterator<Item = H256>,
+) -> Branches<'a, I> {
 	let mut trie_storage: MemoryDB<Blake2Hasher> = MemoryDB::default();
 	let mut root = H256::default();
 
 	// construct trie mapping each chunk's index to its hash.
 	{
 		let mut trie = TrieDBMutBuilder::new(&mut trie_storage, &mut root).build();
-		for (i, chunk) in chunks.as_ref().iter().enumerate() {
+		for (i, chunk_hash) in chunk_hashes.into_iter().enumerate() {
 			(i as u32).using_encoded(|encoded_index| {
-				let chunk_hash = BlakeTwo256::hash(chunk.as_ref());
 				trie.insert(encoded_index, chunk_hash.as_ref())
 					.expect("a fresh trie stored in memory cannot have errors loading nodes; qed");
 			})
@@ -348,6 +583,7 @@ impl<'a, I: Iterator<Item = &'a [u8]>> parity_scale_codec::Input for ShardInput<
 mod tests {
 	use super::*;
 	use polkadot_node_primitives::{AvailableData, BlockData, PoV};
+	use quickcheck::{quickcheck, TestResult};
 
 	// In order to adequately compute the number of entries in the Merkle
 	// trie, we must account for the fixed 16-ary trie structure.
@@ -379,12 +615,178 @@ mod tests {
 		assert_eq!(reconstructed, available_data);
 	}
 
+	#[test]
+	fn systematic_recovery_threshold_is_power_of_two() {
+		assert_eq!(systematic_recovery_threshold(1), Err(Error::NotEnoughValidators));
+		assert_eq!(systematic_recovery_threshold(2), Ok(1));
+		assert_eq!(systematic_recovery_threshold(10), Ok(4));
+		assert_eq!(systematic_recovery_threshold(13), Ok(4));
+		assert_eq!(systematic_recovery_threshold(16), Ok(4));
+		assert_eq!(systematic_recovery_threshold(300), Ok(64));
+		assert_eq!(systematic_recovery_threshold(1000), Ok(256));
+
+		for n_validators in 2..1000 {
+			let threshold = systematic_recovery_threshold(n_validators).unwrap();
+			assert!(threshold.is_power_of_two());
+			assert!(threshold <= recovery_threshold(n_validators).unwrap());
+		}
+	}
+
+	#[test]
+	fn systematic_round_trip_works() {
+		for n_validators in [2, 3, 10, 13, 100, 300, 1000] {
+			for pov_len in [1, 2, 255, 1000] {
+				let pov = PoV { block_data: BlockData((0..pov_len).map(|i| i as u8).collect()) };
+				let available_data =
+					AvailableData { pov: pov.into(), validation_data: Default::default() };
+				let chunks = obtain_chunks(n_validators, &available_data).unwrap();
+
+				let k = systematic_recovery_threshold(n_validators).unwrap();
+				let reconstructed: AvailableData = reconstruct_from_systematic_v1(
+					n_validators,
+					chunks.iter().take(k).map(|chunk| &chunk[..]),
+				)
+				.unwrap();
+
+				assert_eq!(reconstructed, available_data);
+			}
+		}
+	}
+
+	#[test]
+	fn systematic_reconstruction_needs_all_systematic_chunks() {
+		let pov = PoV { block_data: BlockData((0..255).collect()) };
+		let available_data = AvailableData { pov: pov.into(), validation_data: Default::default() };
+		let chunks = obtain_chunks(10, &available_data).unwrap();
+
+		let reconstructed: Result<AvailableData, _> =
+			reconstruct_from_systematic_v1(10, chunks.iter().take(3).map(|chunk| &chunk[..]));
+		assert_eq!(reconstructed, Err(Error::NotEnoughChunks));
+
+		let reconstructed: Result<AvailableData, _> = reconstruct_from_systematic_v1(
+			10,
+			[&chunks[0][..], &chunks[1][..], &chunks[2][..], &chunks[3][1..]],
+		);
+		assert_eq!(reconstructed, Err(Error::NonUniformChunks));
+	}
+
 	#[test]
 	fn reconstruct_does_not_panic_on_low_validator_count() {
 		let reconstructed = reconstruct_v1(1, [].iter().cloned());
 		assert_eq!(reconstructed, Err(Error::NotEnoughValidators));
 	}
 
+	// the validator count and the column width of the property tests, derived from arbitrary
+	// input. The widths are kept small, so that even short payloads are split into many columns.
+	fn validators_and_column(n_validators: u16, symbols_per_column: u8) -> (usize, usize) {
+		(2 + n_validators as usize % 999, 1 + symbols_per_column as usize % 16)
+	}
+
+	#[test]
+	fn parallel_chunks_are_identical() {
+		fn property(n_validators: u16, symbols_per_column: u8, data: Vec<u8>) -> TestResult {
+			let (n_validators, symbols_per_column) =
+				validators_and_column(n_validators, symbols_per_column);
+
+			let chunks = obtain_chunks(n_validators, &data);
+			TestResult::from_bool(
+				obtain_chunks_in_columns(n_validators, &data, Some(symbols_per_column)) == chunks &&
+					obtain_chunks_parallel(n_validators, &data) == chunks,
+			)
+		}
+
+		quickcheck(property as fn(u16, u8, Vec<u8>) -> TestResult);
+	}
+
+	#[test]
+	fn parallel_reconstruction_is_identical() {
+		fn property(
+			n_validators: u16,
+			symbols_per_column: u8,
+			data: Vec<u8>,
+			present: Vec<u16>,
+		) -> TestResult {
+			let (n_validators, symbols_per_column) =
+				validators_and_column(n_validators, symbols_per_column);
+
+			let chunks = obtain_chunks(n_validators, &data).unwrap();
+			// an arbitrary subset of the chunks, which may be too small for the reconstruction.
+			let mut present: Vec<usize> =
+				present.into_iter().map(|i| i as usize % n_validators).collect();
+			present.sort_unstable();
+			present.dedup();
+			let received: Vec<_> = present.iter().map(|&i| (&chunks[i][..], i)).collect();
+
+			let reconstructed: Result<Vec<u8>, _> = reconstruct(n_validators, received.clone());
+			if let Ok(ref reconstructed) = reconstructed {
+				if reconstructed != &data {
+					return TestResult::failed()
+				}
+			}
+
+			let in_columns: Result<Vec<u8>, _> =
+				reconstruct_in_columns(n_validators, received.clone(), Some(symbols_per_column));
+			let parallel: Result<Vec<u8>, _> = reconstruct_parallel(n_validators, received);
+			TestResult::from_bool(in_columns == reconstructed && parallel == reconstructed)
+		}
+
+		quickcheck(property as fn(u16, u8, Vec<u8>, Vec<u16>) -> TestResult);
+	}
+
+	#[test]
+	fn parallel_reconstruction_of_available_data_works() {
+		let pov = PoV { block_data: BlockData((0..100_000).map(|i| i as u8).collect()) };
+		let available_data = AvailableData { pov: pov.into(), validation_data: Default::default() };
+
+		for n_validators in [10, 100, 1000] {
+			let chunks = obtain_chunks_parallel(n_validators, &available_data).unwrap();
+			assert_eq!(chunks, obtain_chunks_v1(n_validators, &available_data).unwrap());
+
+			let threshold = recovery_threshold(n_validators).unwrap();
+			let reconstructed: AvailableData = reconstruct_parallel(
+				n_validators,
+				chunks.iter().enumerate().rev().take(threshold).map(|(i, c)| (&c[..], i)),
+			)
+			.unwrap();
+			assert_eq!(reconstructed, available_data);
+		}
+	}
+
+	#[test]
+	fn parallel_reconstruction_errors_are_identical() {
+		let chunks = obtain_chunks(10, &vec![1u8; 100]).unwrap();
+
+		for received in [
+			vec![],
+			vec![(&chunks[0][..], 0), (&chunks[1][..], 1)],
+			vec![(&chunks[0][..], 0), (&chunks[1][..], 10)],
+			vec![(&chunks[0][..], 0), (&chunks[1][1..], 1)],
+			vec![(&chunks[0][1..], 0)],
+		] {
+			let reconstructed: Result<Vec<u8>, _> = reconstruct(10, received.clone());
+			let parallel: Result<Vec<u8>, _> = reconstruct_parallel(10, received);
+			assert!(reconstructed.is_err());
+			assert_eq!(parallel, reconstructed);
+		}
+	}
+
+	#[test]
+	fn parallel_branches_are_identical() {
+		fn property(n_validators: u16, data: Vec<u8>) -> TestResult {
+			let (n_validators, _) = validators_and_column(n_validators, 0);
+			let chunks = match obtain_chunks(n_validators, &data) {
+				Ok(chunks) => chunks,
+				Err(_) => return TestResult::discard(),
+			};
+
+			let branches = branches(&chunks);
+			let parallel = branches_parallel(&chunks);
+			TestResult::from_bool(branches.root() == parallel.root() && branches.eq(parallel))
+		}
+
+		quickcheck(property as fn(u16, Vec<u8>) -> TestResult);
+	}
+
 	fn generate_trie_and_generate_proofs(magnitude: u32) {
 		let n_validators = 2_u32.pow(magnitude) as usize;
 		let pov = PoV { block_data: BlockData(vec![2; n_validators / KEY_INDEX_NIBBLE_SIZE]) };
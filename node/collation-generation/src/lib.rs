@@ -22,9 +22,9 @@
 //!
 //! * If there is no collation generation config, ignore.
 //! * Otherwise, for each `activated` head in the update:
-//!   * Determine if the para is scheduled on any core by fetching the `availability_cores` Runtime API.
+//!   * Determine which of the configured paras are scheduled on any core by fetching the `availability_cores` Runtime API.
 //!   * Use the Runtime API subsystem to fetch the full validation data.
-//!   * Invoke the `collator`, and use its outputs to produce a [`CandidateReceipt`], signed with the configuration's `key`.
+//!   * Invoke the para's `collator`, and use its outputs to produce a [`CandidateReceipt`], signed with the para's configured `key`.
 //!   * Dispatch a [`CollatorProtocolMessage::DistributeCollation`](receipt, pov)`.
 
 #![deny(missing_docs)]
@@ -61,7 +61,8 @@ const LOG_TARGET: &'static str = "parachain::collation-generation";
 
 /// Collation Generation Subsystem
 pub struct CollationGenerationSubsystem {
-	config: Option<Arc<CollationGenerationConfig>>,
+	/// One config per para we are collating on.
+	configs: Vec<Arc<CollationGenerationConfig>>,
 	metrics: Metrics,
 }
 
@@ -69,7 +70,7 @@ pub struct CollationGenerationSubsystem {
 impl CollationGenerationSubsystem {
 	/// Create a new instance of the `CollationGenerationSubsystem`.
 	pub fn new(metrics: Metrics) -> Self {
-		Self { config: None, metrics }
+		Self { configs: Vec::new(), metrics }
 	}
 
 	/// Run this subsystem
@@ -124,10 +125,10 @@ impl CollationGenerationSubsystem {
 				..
 			}))) => {
 				// follow the procedure from the guide
-				if let Some(config) = &self.config {
+				if !self.configs.is_empty() {
 					let metrics = self.metrics.clone();
 					if let Err(err) = handle_new_activations(
-						config.clone(),
+						self.configs.clone(),
 						activated.into_iter().map(|v| v.hash),
 						ctx,
 						metrics,
@@ -143,12 +144,18 @@ impl CollationGenerationSubsystem {
 			},
 			Ok(FromOrchestra::Signal(OverseerSignal::Conclude)) => true,
 			Ok(FromOrchestra::Communication {
-				msg: CollationGenerationMessage::Initialize(config),
+				msg: CollationGenerationMessage::Initialize(configs),
 			}) => {
-				if self.config.is_some() {
-					gum::error!(target: LOG_TARGET, "double initialization");
-				} else {
-					self.config = Some(Arc::new(config));
+				for config in configs {
+					if self.configs.iter().any(|c| c.para_id == config.para_id) {
+						gum::error!(
+							target: LOG_TARGET,
+							para_id = %config.para_id,
+							"double initialization",
+						);
+					} else {
+						self.configs.push(Arc::new(config));
+					}
 				}
 				false
 			},
@@ -181,7 +188,7 @@ impl<Context> CollationGenerationSubsystem {
 
 #[overseer::contextbounds(CollationGeneration, prefix = self::overseer)]
 async fn handle_new_activations<Context>(
-	config: Arc<CollationGenerationConfig>,
+	configs: Vec<Arc<CollationGenerationConfig>>,
 	activated: impl IntoIterator<Item = Hash>,
 	ctx: &mut Context,
 	metrics: Metrics,
@@ -229,17 +236,19 @@ async fn handle_new_activations<Context>(
 				},
 			};
 
-			if scheduled_core.para_id != config.para_id {
-				gum::trace!(
-					target: LOG_TARGET,
-					core_idx = %core_idx,
-					relay_parent = ?relay_parent,
-					our_para = %config.para_id,
-					their_para = %scheduled_core.para_id,
-					"core is not assigned to our para. Keep going.",
-				);
-				continue
-			}
+			let config = match configs.iter().find(|c| c.para_id == scheduled_core.para_id) {
+				Some(config) => config,
+				None => {
+					gum::trace!(
+						target: LOG_TARGET,
+						core_idx = %core_idx,
+						relay_parent = ?relay_parent,
+						their_para = %scheduled_core.para_id,
+						"core is not assigned to any of our paras. Keep going.",
+					);
+					continue
+				},
+			};
 
 			// we get validation data and validation code synchronously for each core instead of
 			// within the subtask loop, because we have only a single mutable handle to the
@@ -116,7 +116,7 @@ mod handle_new_activations {
 		let subsystem_activated_hashes = activated_hashes.clone();
 		subsystem_test_harness(overseer, |mut ctx| async move {
 			handle_new_activations(
-				test_config(123u32),
+				vec![test_config(123u32)],
 				subsystem_activated_hashes,
 				&mut ctx,
 				Metrics(None),
@@ -193,9 +193,15 @@ mod handle_new_activations {
 		let (tx, _rx) = mpsc::channel(0);
 
 		subsystem_test_harness(overseer, |mut ctx| async move {
-			handle_new_activations(test_config(16), activated_hashes, &mut ctx, Metrics(None), &tx)
-				.await
-				.unwrap();
+			handle_new_activations(
+				vec![test_config(16)],
+				activated_hashes,
+				&mut ctx,
+				Metrics(None),
+				&tx,
+			)
+			.await
+			.unwrap();
 		});
 
 		let requested_validation_data = Arc::try_unwrap(requested_validation_data)
@@ -209,6 +215,89 @@ mod handle_new_activations {
 		assert_eq!(requested_validation_data, vec![[4; 32].into()]);
 	}
 
+	#[test]
+	fn requests_validation_data_for_all_configured_paras() {
+		let activated_hashes: Vec<Hash> = vec![
+			Hash::repeat_byte(1),
+			Hash::repeat_byte(4),
+			Hash::repeat_byte(9),
+			Hash::repeat_byte(16),
+		];
+
+		let requested_validation_data = Arc::new(Mutex::new(Vec::new()));
+
+		let overseer_requested_validation_data = requested_validation_data.clone();
+		let overseer = |mut handle: TestSubsystemContextHandle<CollationGenerationMessage>| async move {
+			loop {
+				match handle.try_recv().await {
+					None => break,
+					Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						hash,
+						RuntimeApiRequest::AvailabilityCores(tx),
+					))) => {
+						tx.send(Ok(vec![
+							CoreState::Scheduled(scheduled_core_for(
+								(hash.as_fixed_bytes()[0] * 4) as u32,
+							)),
+							CoreState::Scheduled(scheduled_core_for(
+								(hash.as_fixed_bytes()[0] * 5) as u32,
+							)),
+						]))
+						.unwrap();
+					},
+					Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						hash,
+						RuntimeApiRequest::PersistedValidationData(
+							para_id,
+							_occupied_core_assumption,
+							tx,
+						),
+					))) => {
+						overseer_requested_validation_data.lock().await.push((hash, para_id));
+						tx.send(Ok(None)).unwrap();
+					},
+					Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						_hash,
+						RuntimeApiRequest::Validators(tx),
+					))) => {
+						tx.send(Ok(vec![dummy_validator(); 3])).unwrap();
+					},
+					Some(msg) => {
+						panic!("didn't expect any other overseer requests; got {:?}", msg)
+					},
+				}
+			}
+		};
+
+		let (tx, _rx) = mpsc::channel(0);
+
+		subsystem_test_harness(overseer, |mut ctx| async move {
+			handle_new_activations(
+				vec![test_config(16), test_config(45)],
+				activated_hashes,
+				&mut ctx,
+				Metrics(None),
+				&tx,
+			)
+			.await
+			.unwrap();
+		});
+
+		let mut requested_validation_data = Arc::try_unwrap(requested_validation_data)
+			.expect("overseer should have shut down by now")
+			.into_inner();
+		requested_validation_data.sort();
+
+		// para 16 is only scheduled at the 4 hash and para 45 only at the 9 hash.
+		assert_eq!(
+			requested_validation_data,
+			vec![
+				(Hash::repeat_byte(4), ParaId::from(16)),
+				(Hash::repeat_byte(9), ParaId::from(45)),
+			],
+		);
+	}
+
 	#[test]
 	fn sends_distribute_collation_message() {
 		let activated_hashes: Vec<Hash> = vec![
@@ -281,7 +370,7 @@ mod handle_new_activations {
 		let subsystem_sent_messages = sent_messages.clone();
 		subsystem_test_harness(overseer, |mut ctx| async move {
 			handle_new_activations(
-				subsystem_config,
+				vec![subsystem_config],
 				activated_hashes,
 				&mut ctx,
 				Metrics(None),
@@ -448,7 +537,7 @@ mod handle_new_activations {
 		let subsystem_sent_messages = sent_messages.clone();
 		subsystem_test_harness(overseer, |mut ctx| async move {
 			handle_new_activations(
-				subsystem_config,
+				vec![subsystem_config],
 				activated_hashes,
 				&mut ctx,
 				Metrics(None),
@@ -18,7 +18,8 @@
 
 use parity_scale_codec::{Decode, Encode};
 use polkadot_node_primitives::approval::{
-	self as approval_types, AssignmentCert, AssignmentCertKind, DelayTranche, RelayVRFStory,
+	self as approval_types, AssignmentCert, AssignmentCertKind, CoreBitfield, DelayTranche,
+	RelayVRFStory,
 };
 use polkadot_primitives::{
 	AssignmentId, AssignmentPair, CandidateHash, CoreIndex, GroupIndex, IndexedVec, SessionInfo,
@@ -105,6 +106,33 @@ fn relay_vrf_modulo_core(vrf_in_out: &VRFInOut, n_cores: u32) -> CoreIndex {
 	CoreIndex(random_core)
 }
 
+fn relay_vrf_modulo_compact_transcript(relay_vrf_story: RelayVRFStory) -> Transcript {
+	// A single VRF output yields all samples, so the sample number is not part of it.
+	let mut t = Transcript::new(approval_types::RELAY_VRF_MODULO_COMPACT_CONTEXT);
+	t.append_message(b"RC-VRF", &relay_vrf_story.0);
+	t
+}
+
+// All distinct cores picked by `num_samples` samples of a compact modulo VRF output, in
+// ascending order.
+fn relay_vrf_modulo_compact_cores(
+	vrf_in_out: &VRFInOut,
+	num_samples: u32,
+	n_cores: u32,
+) -> Vec<CoreIndex> {
+	let mut cores = (0..num_samples)
+		.map(|sample| {
+			let context =
+				[approval_types::CORE_RANDOMNESS_CONTEXT, &sample.to_le_bytes()[..]].concat();
+			let bytes: [u8; 4] = vrf_in_out.make_bytes(&context);
+			CoreIndex(u32::from_le_bytes(bytes) % n_cores)
+		})
+		.collect::<Vec<_>>();
+	cores.sort();
+	cores.dedup();
+	cores
+}
+
 fn relay_vrf_delay_transcript(relay_vrf_story: RelayVRFStory, core_index: CoreIndex) -> Transcript {
 	let mut t = Transcript::new(approval_types::RELAY_VRF_DELAY_CONTEXT);
 	t.append_message(b"RC-VRF", &relay_vrf_story.0);
@@ -133,6 +161,12 @@ fn assigned_core_transcript(core_index: CoreIndex) -> Transcript {
 	t
 }
 
+fn assigned_cores_transcript(core_bitfield: &CoreBitfield) -> Transcript {
+	let mut t = Transcript::new(approval_types::ASSIGNED_CORES_CONTEXT);
+	core_bitfield.using_encoded(|s| t.append_message(b"cores", s));
+	t
+}
+
 /// Information about the world assignments are being produced in.
 #[derive(Clone)]
 pub(crate) struct Config {
@@ -171,16 +205,17 @@ pub(crate) trait AssignmentCriteria {
 		relay_vrf_story: RelayVRFStory,
 		config: &Config,
 		leaving_cores: Vec<(CandidateHash, CoreIndex, GroupIndex)>,
+		enable_v2_assignments: bool,
 	) -> HashMap<CoreIndex, OurAssignment>;
 
 	fn check_assignment_cert(
 		&self,
-		claimed_core_index: CoreIndex,
+		claimed_cores: CoreBitfield,
 		validator_index: ValidatorIndex,
 		config: &Config,
 		relay_vrf_story: RelayVRFStory,
 		assignment: &AssignmentCert,
-		backing_group: GroupIndex,
+		backing_groups: Vec<GroupIndex>,
 	) -> Result<DelayTranche, InvalidAssignment>;
 }
 
@@ -193,26 +228,27 @@ impl AssignmentCriteria for RealAssignmentCriteria {
 		relay_vrf_story: RelayVRFStory,
 		config: &Config,
 		leaving_cores: Vec<(CandidateHash, CoreIndex, GroupIndex)>,
+		enable_v2_assignments: bool,
 	) -> HashMap<CoreIndex, OurAssignment> {
-		compute_assignments(keystore, relay_vrf_story, config, leaving_cores)
+		compute_assignments(keystore, relay_vrf_story, config, leaving_cores, enable_v2_assignments)
 	}
 
 	fn check_assignment_cert(
 		&self,
-		claimed_core_index: CoreIndex,
+		claimed_cores: CoreBitfield,
 		validator_index: ValidatorIndex,
 		config: &Config,
 		relay_vrf_story: RelayVRFStory,
 		assignment: &AssignmentCert,
-		backing_group: GroupIndex,
+		backing_groups: Vec<GroupIndex>,
 	) -> Result<DelayTranche, InvalidAssignment> {
 		check_assignment_cert(
-			claimed_core_index,
+			claimed_cores,
 			validator_index,
 			config,
 			relay_vrf_story,
 			assignment,
-			backing_group,
+			backing_groups,
 		)
 	}
 }
@@ -227,11 +263,15 @@ impl AssignmentCriteria for RealAssignmentCriteria {
 /// The idea is that most assignments are never triggered and fall by the wayside.
 ///
 /// This will not assign to anything the local validator was part of the backing group for.
+///
+/// With `enable_v2_assignments`, all `RelayVRFModulo` samples are covered by a single
+/// `RelayVRFModuloCompact` cert, which is returned for every core it claims.
 pub(crate) fn compute_assignments(
 	keystore: &LocalKeystore,
 	relay_vrf_story: RelayVRFStory,
 	config: &Config,
 	leaving_cores: impl IntoIterator<Item = (CandidateHash, CoreIndex, GroupIndex)> + Clone,
+	enable_v2_assignments: bool,
 ) -> HashMap<CoreIndex, OurAssignment> {
 	if config.n_cores == 0 ||
 		config.assignment_keys.is_empty() ||
@@ -290,14 +330,25 @@ pub(crate) fn compute_assignments(
 	let mut assignments = HashMap::new();
 
 	// First run `RelayVRFModulo` for each sample.
-	compute_relay_vrf_modulo_assignments(
-		&assignments_key,
-		index,
-		config,
-		relay_vrf_story.clone(),
-		leaving_cores.iter().cloned(),
-		&mut assignments,
-	);
+	if enable_v2_assignments {
+		compute_relay_vrf_modulo_compact_assignments(
+			&assignments_key,
+			index,
+			config,
+			relay_vrf_story.clone(),
+			leaving_cores.iter().cloned(),
+			&mut assignments,
+		);
+	} else {
+		compute_relay_vrf_modulo_assignments(
+			&assignments_key,
+			index,
+			config,
+			relay_vrf_story.clone(),
+			leaving_cores.iter().cloned(),
+			&mut assignments,
+		);
+	}
 
 	// Then run `RelayVRFDelay` once for the whole block.
 	compute_relay_vrf_delay_assignments(
@@ -373,6 +424,71 @@ fn compute_relay_vrf_modulo_assignments(
 	}
 }
 
+fn compute_relay_vrf_modulo_compact_assignments(
+	assignments_key: &schnorrkel::Keypair,
+	validator_index: ValidatorIndex,
+	config: &Config,
+	relay_vrf_story: RelayVRFStory,
+	leaving_cores: impl IntoIterator<Item = (CandidateHash, CoreIndex)> + Clone,
+	assignments: &mut HashMap<CoreIndex, OurAssignment>,
+) {
+	let mut assigned_cores = Vec::new();
+
+	let maybe_assignment = {
+		let assigned_cores = &mut assigned_cores;
+		assignments_key.vrf_sign_extra_after_check(
+			relay_vrf_modulo_compact_transcript(relay_vrf_story),
+			|vrf_in_out| {
+				*assigned_cores = relay_vrf_modulo_compact_cores(
+					&vrf_in_out,
+					config.relay_vrf_modulo_samples,
+					config.n_cores,
+				)
+				.into_iter()
+				.filter(|core| leaving_cores.clone().into_iter().any(|(_, c)| c == *core))
+				.collect();
+
+				CoreBitfield::new(assigned_cores.iter().copied())
+					.ok()
+					.map(|core_bitfield| assigned_cores_transcript(&core_bitfield))
+			},
+		)
+	};
+
+	if let Some((vrf_in_out, vrf_proof, _)) = maybe_assignment {
+		// Sanity: `assigned_cores` is non-empty here, otherwise the closure would have bailed.
+		let core_bitfield = match CoreBitfield::new(assigned_cores.iter().copied()) {
+			Ok(core_bitfield) => core_bitfield,
+			Err(_) => return,
+		};
+		let cert = AssignmentCert {
+			kind: AssignmentCertKind::RelayVRFModuloCompact { core_bitfield },
+			vrf: approval_types::VrfSignature {
+				output: approval_types::VrfOutput(vrf_in_out.to_output()),
+				proof: approval_types::VrfProof(vrf_proof),
+			},
+		};
+
+		gum::trace!(
+			target: LOG_TARGET,
+			?assigned_cores,
+			?validator_index,
+			tranche = 0,
+			"RelayVRFModuloCompact Assignment."
+		);
+
+		// The cert covers all cores, and like `RelayVRFModulo` it is tranche 0.
+		for core in assigned_cores {
+			assignments.entry(core).or_insert(OurAssignment {
+				cert: cert.clone(),
+				tranche: 0,
+				validator_index,
+				triggered: false,
+			});
+		}
+	}
+}
+
 fn compute_relay_vrf_delay_assignments(
 	assignments_key: &schnorrkel::Keypair,
 	validator_index: ValidatorIndex,
@@ -452,26 +568,31 @@ pub(crate) enum InvalidAssignmentReason {
 	VRFModuloOutputMismatch,
 	VRFDelayCoreIndexMismatch,
 	VRFDelayOutputMismatch,
+	InvalidClaimedCores,
 }
 
 /// Checks the crypto of an assignment cert. Failure conditions:
 ///   * Validator index out of bounds
 ///   * VRF signature check fails
-///   * VRF output doesn't match assigned core
-///   * Core is not covered by extra data in signature
+///   * VRF output doesn't match assigned cores
+///   * Cores are not covered by extra data in signature
 ///   * Core index out of bounds
 ///   * Sample is out of bounds
 ///   * Validator is present in backing group.
+///   * More than one core claimed by a cert covering a single core.
 ///
-/// This function does not check whether the core is actually a valid assignment or not. That should be done
-/// outside the scope of this function.
+/// `backing_groups` are the groups which backed the candidates on `claimed_cores`, in the
+/// order of the claimed cores.
+///
+/// This function does not check whether the cores are actually valid assignments or not. That
+/// should be done outside the scope of this function.
 pub(crate) fn check_assignment_cert(
-	claimed_core_index: CoreIndex,
+	claimed_cores: CoreBitfield,
 	validator_index: ValidatorIndex,
 	config: &Config,
 	relay_vrf_story: RelayVRFStory,
 	assignment: &AssignmentCert,
-	backing_group: GroupIndex,
+	backing_groups: Vec<GroupIndex>,
 ) -> Result<DelayTranche, InvalidAssignment> {
 	use InvalidAssignmentReason as Reason;
 
@@ -483,29 +604,61 @@ pub(crate) fn check_assignment_cert(
 	let public = schnorrkel::PublicKey::from_bytes(validator_public.as_slice())
 		.map_err(|_| InvalidAssignment(Reason::InvalidAssignmentKey))?;
 
-	if claimed_core_index.0 >= config.n_cores {
+	if claimed_cores.count_ones() == 0 || claimed_cores.count_ones() != backing_groups.len() {
+		return Err(InvalidAssignment(Reason::InvalidClaimedCores))
+	}
+
+	if claimed_cores.iter_ones().any(|core| core.0 >= config.n_cores) {
 		return Err(InvalidAssignment(Reason::CoreIndexOutOfBounds))
 	}
 
-	// Check that the validator was not part of the backing group
+	// Check that the validator was not part of the backing groups
 	// and not already assigned.
-	let is_in_backing =
-		is_in_backing_group(&config.validator_groups, validator_index, backing_group);
+	let is_in_backing = backing_groups
+		.iter()
+		.any(|group| is_in_backing_group(&config.validator_groups, validator_index, *group));
 
 	if is_in_backing {
 		return Err(InvalidAssignment(Reason::IsInBackingGroup))
 	}
 
 	let vrf_signature = &assignment.vrf;
-	match assignment.kind {
+	match &assignment.kind {
+		AssignmentCertKind::RelayVRFModuloCompact { core_bitfield } => {
+			if !core_bitfield.same_ones(&claimed_cores) {
+				return Err(InvalidAssignment(Reason::VRFModuloCoreIndexMismatch))
+			}
+
+			let (vrf_in_out, _) = public
+				.vrf_verify_extra(
+					relay_vrf_modulo_compact_transcript(relay_vrf_story),
+					&vrf_signature.output.0,
+					&vrf_signature.proof.0,
+					assigned_cores_transcript(core_bitfield),
+				)
+				.map_err(|_| InvalidAssignment(Reason::VRFModuloOutputMismatch))?;
+
+			// ensure that the `vrf_in_out` actually gives us all the claimed cores.
+			let assigned_cores = relay_vrf_modulo_compact_cores(
+				&vrf_in_out,
+				config.relay_vrf_modulo_samples,
+				config.n_cores,
+			);
+			if claimed_cores.iter_ones().all(|core| assigned_cores.contains(&core)) {
+				Ok(0)
+			} else {
+				Err(InvalidAssignment(Reason::VRFModuloCoreIndexMismatch))
+			}
+		},
 		AssignmentCertKind::RelayVRFModulo { sample } => {
-			if sample >= config.relay_vrf_modulo_samples {
+			let claimed_core_index = single_claimed_core(&claimed_cores)?;
+			if *sample >= config.relay_vrf_modulo_samples {
 				return Err(InvalidAssignment(Reason::SampleOutOfBounds))
 			}
 
 			let (vrf_in_out, _) = public
 				.vrf_verify_extra(
-					relay_vrf_modulo_transcript(relay_vrf_story, sample),
+					relay_vrf_modulo_transcript(relay_vrf_story, *sample),
 					&vrf_signature.output.0,
 					&vrf_signature.proof.0,
 					assigned_core_transcript(claimed_core_index),
@@ -520,13 +673,14 @@ pub(crate) fn check_assignment_cert(
 			}
 		},
 		AssignmentCertKind::RelayVRFDelay { core_index } => {
-			if core_index != claimed_core_index {
+			let claimed_core_index = single_claimed_core(&claimed_cores)?;
+			if *core_index != claimed_core_index {
 				return Err(InvalidAssignment(Reason::VRFDelayCoreIndexMismatch))
 			}
 
 			let (vrf_in_out, _) = public
 				.vrf_verify(
-					relay_vrf_delay_transcript(relay_vrf_story, core_index),
+					relay_vrf_delay_transcript(relay_vrf_story, *core_index),
 					&vrf_signature.output.0,
 					&vrf_signature.proof.0,
 				)
@@ -541,6 +695,14 @@ pub(crate) fn check_assignment_cert(
 	}
 }
 
+// Certs which are not compact cover exactly one core.
+fn single_claimed_core(claimed_cores: &CoreBitfield) -> Result<CoreIndex, InvalidAssignment> {
+	match claimed_cores.first_one() {
+		Some(core) if claimed_cores.count_ones() == 1 => Ok(core),
+		_ => Err(InvalidAssignment(InvalidAssignmentReason::InvalidClaimedCores)),
+	}
+}
+
 fn is_in_backing_group(
 	validator_groups: &IndexedVec<GroupIndex, Vec<ValidatorIndex>>,
 	validator: ValidatorIndex,
@@ -633,6 +795,7 @@ mod tests {
 				n_delay_tranches: 40,
 			},
 			vec![(c_a, CoreIndex(0), GroupIndex(1)), (c_b, CoreIndex(1), GroupIndex(0))],
+			false,
 		);
 
 		// Note that alice is in group 0, which was the backing group for core 1.
@@ -668,6 +831,7 @@ mod tests {
 				n_delay_tranches: 40,
 			},
 			vec![(c_a, CoreIndex(0), GroupIndex(0)), (c_b, CoreIndex(1), GroupIndex(1))],
+			false,
 		);
 
 		assert_eq!(assignments.len(), 1);
@@ -695,6 +859,7 @@ mod tests {
 				n_delay_tranches: 40,
 			},
 			vec![],
+			false,
 		);
 
 		assert!(assignments.is_empty());
@@ -715,6 +880,20 @@ mod tests {
 		n_cores: usize,
 		rotation_offset: usize,
 		f: impl Fn(&mut MutatedAssignment) -> Option<bool>, // None = skip
+	) {
+		check_mutated_assignments_with(n_validators, n_cores, rotation_offset, false, f)
+	}
+
+	// Like `check_mutated_assignments`, with compact certs if `enable_v2_assignments` is set.
+	//
+	// Compact certs are checked once for each core they cover, with the mutated core and group
+	// replacing the respective one of the claimed cores.
+	fn check_mutated_assignments_with(
+		n_validators: usize,
+		n_cores: usize,
+		rotation_offset: usize,
+		enable_v2_assignments: bool,
+		f: impl Fn(&mut MutatedAssignment) -> Option<bool>, // None = skip
 	) {
 		let keystore = make_keystore(&[Sr25519Keyring::Alice]);
 
@@ -746,10 +925,12 @@ mod tests {
 					)
 				})
 				.collect::<Vec<_>>(),
+			enable_v2_assignments,
 		);
 
 		let mut counted = 0;
 		for (core, assignment) in assignments {
+			let assignment_kind = assignment.cert.kind.clone();
 			let mut mutated = MutatedAssignment {
 				core,
 				group: group_for_core(core.0 as _),
@@ -766,13 +947,24 @@ mod tests {
 
 			counted += 1;
 
+			let mut claimed = match &assignment_kind {
+				AssignmentCertKind::RelayVRFModuloCompact { core_bitfield } => core_bitfield
+					.iter_ones()
+					.filter(|c| *c != core)
+					.map(|c| (c, group_for_core(c.0 as _)))
+					.collect(),
+				_ => Vec::new(),
+			};
+			claimed.push((mutated.core, mutated.group));
+			claimed.sort_by_key(|(c, _)| *c);
+
 			let is_good = check_assignment_cert(
-				mutated.core,
+				CoreBitfield::new(claimed.iter().map(|(c, _)| *c)).unwrap(),
 				mutated.val_index,
 				&mutated.config,
 				relay_vrf_story.clone(),
 				&mutated.cert,
-				mutated.group,
+				claimed.into_iter().map(|(_, g)| g).collect(),
 			)
 			.is_ok();
 
@@ -875,4 +1067,130 @@ mod tests {
 			}
 		});
 	}
+
+	#[test]
+	fn computed_compact_assignments_pass_checks() {
+		check_mutated_assignments_with(200, 100, 25, true, |m| {
+			assert!(!matches!(m.cert.kind, AssignmentCertKind::RelayVRFModulo { .. }));
+			Some(true)
+		});
+	}
+
+	#[test]
+	fn compact_assignments_cover_multiple_cores() {
+		let keystore = make_keystore(&[Sr25519Keyring::Alice]);
+		let n_cores = 100;
+		let config = Config {
+			assignment_keys: assignment_keys_plus_random(&[Sr25519Keyring::Alice], 199),
+			validator_groups: basic_groups(200, n_cores),
+			n_cores: n_cores as u32,
+			zeroth_delay_tranche_width: 10,
+			relay_vrf_modulo_samples: 6,
+			n_delay_tranches: 40,
+		};
+
+		let assignments = compute_assignments(
+			&keystore,
+			RelayVRFStory([42u8; 32]),
+			&config,
+			(0..n_cores)
+				.map(|i| {
+					(
+						CandidateHash(Hash::repeat_byte(i as u8)),
+						CoreIndex(i as u32),
+						GroupIndex(((i + 25) % n_cores) as _),
+					)
+				})
+				.collect::<Vec<_>>(),
+			true,
+		);
+
+		let compact = assignments
+			.iter()
+			.filter_map(|(core, assignment)| match &assignment.cert.kind {
+				AssignmentCertKind::RelayVRFModuloCompact { core_bitfield } => {
+					assert!(core_bitfield.bit_at(core));
+					assert_eq!(assignment.tranche, 0);
+					Some(core_bitfield.clone())
+				},
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+
+		// A single cert, shared by all cores it claims.
+		assert!(compact.len() > 1);
+		assert!(compact.iter().all(|bitfield| bitfield == &compact[0]));
+		assert_eq!(compact[0].count_ones(), compact.len());
+	}
+
+	#[test]
+	fn check_rejects_compact_bad_vrf() {
+		check_mutated_assignments_with(200, 100, 25, true, |m| {
+			match m.cert.kind.clone() {
+				AssignmentCertKind::RelayVRFModuloCompact { .. } => {
+					m.cert.vrf = garbage_vrf_signature();
+					Some(false)
+				},
+				_ => None, // skip everything else.
+			}
+		});
+	}
+
+	#[test]
+	fn check_rejects_compact_core_wrong() {
+		check_mutated_assignments_with(200, 100, 25, true, |m| {
+			match m.cert.kind.clone() {
+				AssignmentCertKind::RelayVRFModuloCompact { core_bitfield } => {
+					m.core = (1..100)
+						.map(|offset| CoreIndex((m.core.0 + offset) % 100))
+						.find(|core| !core_bitfield.bit_at(core))
+						.expect("cert does not cover all cores");
+					Some(false)
+				},
+				_ => None, // skip everything else.
+			}
+		});
+	}
+
+	#[test]
+	fn check_rejects_compact_in_backing_group() {
+		check_mutated_assignments_with(200, 100, 25, true, |m| {
+			match m.cert.kind.clone() {
+				AssignmentCertKind::RelayVRFModuloCompact { .. } => {
+					m.group = m.own_group;
+					Some(false)
+				},
+				_ => None, // skip everything else.
+			}
+		});
+	}
+
+	#[test]
+	fn check_rejects_multiple_cores_for_single_core_certs() {
+		let claimed = CoreBitfield::new(vec![CoreIndex(0), CoreIndex(1)]).unwrap();
+		let cert = AssignmentCert {
+			kind: AssignmentCertKind::RelayVRFDelay { core_index: CoreIndex(0) },
+			vrf: garbage_vrf_signature(),
+		};
+		let config = Config {
+			assignment_keys: assignment_keys(&[Sr25519Keyring::Alice, Sr25519Keyring::Bob]),
+			validator_groups: basic_groups(2, 2),
+			n_cores: 2,
+			zeroth_delay_tranche_width: 10,
+			relay_vrf_modulo_samples: 3,
+			n_delay_tranches: 40,
+		};
+
+		assert_eq!(
+			check_assignment_cert(
+				claimed,
+				ValidatorIndex(0),
+				&config,
+				RelayVRFStory([42u8; 32]),
+				&cert,
+				vec![GroupIndex(1), GroupIndex(1)],
+			),
+			Err(InvalidAssignment(InvalidAssignmentReason::InvalidClaimedCores)),
+		);
+	}
 }
@@ -79,6 +79,7 @@ struct ImportedBlockInfoEnv<'a> {
 	runtime_info: &'a mut RuntimeInfo,
 	assignment_criteria: &'a (dyn AssignmentCriteria + Send + Sync),
 	keystore: &'a LocalKeystore,
+	enable_v2_assignments: bool,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -234,6 +235,7 @@ async fn imported_block_info<Context>(
 								.iter()
 								.map(|(c_hash, _, core, group)| (*c_hash, *core, *group))
 								.collect(),
+							env.enable_v2_assignments,
 						);
 
 						(assignments, slot, relay_vrf)
@@ -381,6 +383,7 @@ pub(crate) async fn handle_new_head<Context, B: Backend>(
 				runtime_info: session_info_provider,
 				assignment_criteria: &*state.assignment_criteria,
 				keystore: &state.keystore,
+				enable_v2_assignments: state.enable_v2_assignments,
 			};
 
 			match imported_block_info(ctx, env, block_hash, &block_header, finalized_number).await {
@@ -633,6 +636,7 @@ pub(crate) mod tests {
 			clock: Box::new(MockClock::default()),
 			assignment_criteria: Box::new(MockAssignmentCriteria),
 			spans: HashMap::new(),
+			enable_v2_assignments: false,
 		}
 	}
 
@@ -659,19 +663,21 @@ pub(crate) mod tests {
 				polkadot_primitives::CoreIndex,
 				polkadot_primitives::GroupIndex,
 			)>,
+			_enable_v2_assignments: bool,
 		) -> HashMap<polkadot_primitives::CoreIndex, criteria::OurAssignment> {
 			HashMap::new()
 		}
 
 		fn check_assignment_cert(
 			&self,
-			_claimed_core_index: polkadot_primitives::CoreIndex,
+			_claimed_cores: polkadot_node_primitives::approval::CoreBitfield,
 			_validator_index: polkadot_primitives::ValidatorIndex,
 			_config: &criteria::Config,
 			_relay_vrf_story: polkadot_node_primitives::approval::RelayVRFStory,
 			_assignment: &polkadot_node_primitives::approval::AssignmentCert,
-			_backing_group: polkadot_primitives::GroupIndex,
-		) -> Result<polkadot_node_primitives::approval::DelayTranche, criteria::InvalidAssignment> {
+			_backing_groups: Vec<polkadot_primitives::GroupIndex>,
+		) -> Result<polkadot_node_primitives::approval::DelayTranche, criteria::InvalidAssignment>
+		{
 			Ok(0)
 		}
 	}
@@ -762,6 +768,7 @@ pub(crate) mod tests {
 					runtime_info: &mut runtime_info,
 					assignment_criteria: &MockAssignmentCriteria,
 					keystore: &LocalKeystore::in_memory(),
+					enable_v2_assignments: false,
 				};
 
 				let info =
@@ -885,6 +892,7 @@ pub(crate) mod tests {
 					runtime_info: &mut runtime_info,
 					assignment_criteria: &MockAssignmentCriteria,
 					keystore: &LocalKeystore::in_memory(),
+					enable_v2_assignments: false,
 				};
 
 				let info = imported_block_info(&mut ctx, env, hash, &header, &Some(4)).await;
@@ -1001,6 +1009,7 @@ pub(crate) mod tests {
 					runtime_info: &mut runtime_info,
 					assignment_criteria: &MockAssignmentCriteria,
 					keystore: &LocalKeystore::in_memory(),
+					enable_v2_assignments: false,
 				};
 
 				let info = imported_block_info(&mut ctx, env, hash, &header, &Some(6)).await;
@@ -1099,6 +1108,7 @@ pub(crate) mod tests {
 					runtime_info: &mut runtime_info,
 					assignment_criteria: &MockAssignmentCriteria,
 					keystore: &LocalKeystore::in_memory(),
+					enable_v2_assignments: false,
 				};
 
 				let info =
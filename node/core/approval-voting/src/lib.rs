@@ -25,7 +25,9 @@ use jaeger::{hash_to_trace_identifier, PerLeafSpan};
 use polkadot_node_jaeger as jaeger;
 use polkadot_node_primitives::{
 	approval::{
-		BlockApprovalMeta, DelayTranche, IndirectAssignmentCert, IndirectSignedApprovalVote,
+		AssignmentCert, AssignmentCertKind, BlockApprovalMeta, CandidateBitfield, CoreBitfield,
+		DelayTranche, IndirectAssignmentCert, IndirectSignedApprovalVote,
+		IndirectSignedApprovalVoteV2,
 	},
 	ValidationResult, DISPUTE_WINDOW,
 };
@@ -49,14 +51,15 @@ use polkadot_node_subsystem_util::{
 	TimeoutExt,
 };
 use polkadot_primitives::{
-	ApprovalVote, BlockNumber, CandidateHash, CandidateIndex, CandidateReceipt, DisputeStatement,
-	GroupIndex, Hash, PvfExecTimeoutKind, SessionIndex, SessionInfo, ValidDisputeStatementKind,
-	ValidatorId, ValidatorIndex, ValidatorPair, ValidatorSignature,
+	ApprovalVote, ApprovalVoteMultipleCandidates, BlockNumber, CandidateHash, CandidateIndex,
+	CandidateReceipt, GroupIndex, Hash, PvfExecTimeoutKind, SessionIndex, SessionInfo, ValidatorId,
+	ValidatorIndex, ValidatorPair, ValidatorSignature,
 };
 use sc_keystore::LocalKeystore;
 use sp_application_crypto::Pair;
 use sp_consensus::SyncOracle;
 use sp_consensus_slots::Slot;
+use sp_runtime::traits::AppVerify;
 
 use futures::{
 	channel::oneshot,
@@ -119,6 +122,11 @@ pub struct Config {
 	/// The slot duration of the consensus algorithm, in milliseconds. Should be evenly
 	/// divisible by 500.
 	pub slot_duration_millis: u64,
+	/// Whether to cover all `RelayVRFModulo` assignments of a block with a single cert.
+	///
+	/// Such certs can only be checked by nodes which speak `v2` of the approval distribution
+	/// messages, so this should only be enabled once most of the network has upgraded.
+	pub enable_v2_assignments: bool,
 }
 
 // The mode of the approval voting subsystem. It should start in a `Syncing` mode when it first
@@ -143,6 +151,7 @@ pub struct ApprovalVotingSubsystem {
 	keystore: Arc<LocalKeystore>,
 	db_config: DatabaseConfig,
 	slot_duration_millis: u64,
+	enable_v2_assignments: bool,
 	db: Arc<dyn Database>,
 	mode: Mode,
 	metrics: Metrics,
@@ -354,6 +363,7 @@ impl ApprovalVotingSubsystem {
 		ApprovalVotingSubsystem {
 			keystore,
 			slot_duration_millis: config.slot_duration_millis,
+			enable_v2_assignments: config.enable_v2_assignments,
 			db,
 			db_config: DatabaseConfig { col_approval_data: config.col_approval_data },
 			mode: Mode::Syncing(sync_oracle),
@@ -662,6 +672,7 @@ struct State {
 	clock: Box<dyn Clock + Send + Sync>,
 	assignment_criteria: Box<dyn AssignmentCriteria + Send + Sync>,
 	spans: HashMap<Hash, jaeger::PerLeafSpan>,
+	enable_v2_assignments: bool,
 }
 
 #[overseer::contextbounds(ApprovalVoting, prefix = self::overseer)]
@@ -731,7 +742,7 @@ enum Action {
 		indirect_cert: IndirectAssignmentCert,
 		assignment_tranche: DelayTranche,
 		relay_block_hash: Hash,
-		candidate_index: CandidateIndex,
+		claimed_candidate_indices: CandidateBitfield,
 		session: SessionIndex,
 		candidate: CandidateReceipt,
 		backing_group: GroupIndex,
@@ -763,6 +774,7 @@ where
 		clock,
 		assignment_criteria,
 		spans: HashMap::new(),
+		enable_v2_assignments: subsystem.enable_v2_assignments,
 	};
 
 	// `None` on start-up. Gets initialized/updated on leaf update
@@ -953,7 +965,7 @@ async fn handle_actions<Context>(
 				indirect_cert,
 				assignment_tranche,
 				relay_block_hash,
-				candidate_index,
+				claimed_candidate_indices,
 				session,
 				candidate,
 				backing_group,
@@ -979,7 +991,7 @@ async fn handle_actions<Context>(
 
 				ctx.send_unbounded_message(ApprovalDistributionMessage::DistributeAssignment(
 					indirect_cert,
-					candidate_index,
+					claimed_candidate_indices,
 				));
 
 				match approvals_cache.get(&candidate_hash) {
@@ -1089,6 +1101,8 @@ fn distribution_messages_for_activation(
 			session: block_entry.session(),
 		});
 
+		// Compact certs are shared by multiple candidates, but only need to be sent once.
+		let mut sent_certs = Vec::new();
 		for (i, (_, candidate_hash)) in block_entry.candidates().iter().enumerate() {
 			let _candidate_span =
 				distribution_message_span.child("candidate").with_candidate(*candidate_hash);
@@ -1108,37 +1122,33 @@ fn distribution_messages_for_activation(
 
 			match candidate_entry.approval_entry(&block_hash) {
 				Some(approval_entry) => {
-					match approval_entry.local_statements() {
-						(None, None) | (None, Some(_)) => {}, // second is impossible case.
-						(Some(assignment), None) => {
-							messages.push(ApprovalDistributionMessage::DistributeAssignment(
-								IndirectAssignmentCert {
-									block_hash,
-									validator: assignment.validator_index(),
-									cert: assignment.cert().clone(),
-								},
-								i as _,
-							));
-						},
-						(Some(assignment), Some(approval_sig)) => {
-							messages.push(ApprovalDistributionMessage::DistributeAssignment(
-								IndirectAssignmentCert {
-									block_hash,
-									validator: assignment.validator_index(),
-									cert: assignment.cert().clone(),
-								},
-								i as _,
-							));
+					let (assignment, approval_sig) = match approval_entry.local_statements() {
+						(None, None) | (None, Some(_)) => continue, // second is impossible case.
+						(Some(assignment), approval_sig) => (assignment, approval_sig),
+					};
+
+					if !sent_certs.contains(assignment.cert()) {
+						sent_certs.push(assignment.cert().clone());
+						messages.push(ApprovalDistributionMessage::DistributeAssignment(
+							IndirectAssignmentCert {
+								block_hash,
+								validator: assignment.validator_index(),
+								cert: assignment.cert().clone(),
+							},
+							claimed_candidates(&block_entry, assignment.cert(), i as _),
+						));
+					}
 
-							messages.push(ApprovalDistributionMessage::DistributeApproval(
-								IndirectSignedApprovalVote {
-									block_hash,
-									candidate_index: i as _,
-									validator: assignment.validator_index(),
-									signature: approval_sig,
-								},
-							))
-						},
+					if let Some(approval_sig) = approval_sig {
+						messages.push(ApprovalDistributionMessage::DistributeApproval(
+							IndirectSignedApprovalVote {
+								block_hash,
+								candidate_index: i as _,
+								validator: assignment.validator_index(),
+								signature: approval_sig,
+							}
+							.into(),
+						))
 					}
 				},
 				None => {
@@ -1157,6 +1167,28 @@ fn distribution_messages_for_activation(
 	Ok(messages)
 }
 
+// The candidates of the block claimed by our assignment `cert` for the candidate at
+// `candidate_index`. Compact certs claim the candidates on all of their cores.
+fn claimed_candidates(
+	block_entry: &BlockEntry,
+	cert: &AssignmentCert,
+	candidate_index: CandidateIndex,
+) -> CandidateBitfield {
+	match &cert.kind {
+		AssignmentCertKind::RelayVRFModuloCompact { core_bitfield } => CandidateBitfield::new(
+			block_entry
+				.candidates()
+				.iter()
+				.enumerate()
+				.filter(|(_, (core, _))| core_bitfield.bit_at(core))
+				.map(|(i, _)| i as CandidateIndex),
+		)
+		.unwrap_or_else(|_| candidate_index.into()),
+		AssignmentCertKind::RelayVRFModulo { .. } | AssignmentCertKind::RelayVRFDelay { .. } =>
+			candidate_index.into(),
+	}
+}
+
 // Handle an incoming signal from the overseer. Returns true if execution should conclude.
 #[overseer::contextbounds(ApprovalVoting, prefix = self::overseer)]
 async fn handle_from_overseer<Context>(
@@ -1255,14 +1287,14 @@ async fn handle_from_overseer<Context>(
 			vec![Action::Conclude]
 		},
 		FromOrchestra::Communication { msg } => match msg {
-			ApprovalVotingMessage::CheckAndImportAssignment(a, claimed_core, res) => {
+			ApprovalVotingMessage::CheckAndImportAssignment(a, claimed_candidate_indices, res) => {
 				let (check_outcome, actions) = check_and_import_assignment(
 					ctx.sender(),
 					state,
 					db,
 					session_info_provider,
 					a,
-					claimed_core,
+					claimed_candidate_indices,
 				)
 				.await?;
 				let _ = res.send(check_outcome);
@@ -1332,7 +1364,7 @@ async fn get_approval_signatures_for_candidate<Context>(
 	ctx: &mut Context,
 	db: &OverlayedBackend<'_, impl Backend>,
 	candidate_hash: CandidateHash,
-	tx: oneshot::Sender<HashMap<ValidatorIndex, ValidatorSignature>>,
+	tx: oneshot::Sender<HashMap<ValidatorIndex, (Vec<CandidateHash>, ValidatorSignature)>>,
 ) -> SubsystemResult<()> {
 	let send_votes = |votes| {
 		if let Err(_) = tx.send(votes) {
@@ -1358,6 +1390,8 @@ async fn get_approval_signatures_for_candidate<Context>(
 	let relay_hashes = entry.block_assignments.keys();
 
 	let mut candidate_indices = HashSet::new();
+	// Signatures may cover further candidates of the same block, which are reported by index.
+	let mut candidates_by_block = HashMap::new();
 	// Retrieve `CoreIndices`/`CandidateIndices` as required by approval-distribution:
 	for hash in relay_hashes {
 		let entry = match db.load_block_entry(hash)? {
@@ -1378,6 +1412,10 @@ async fn get_approval_signatures_for_candidate<Context>(
 				break
 			}
 		}
+		candidates_by_block.insert(
+			*hash,
+			entry.candidates().iter().map(|(_, c_hash)| *c_hash).collect::<Vec<_>>(),
+		);
 	}
 
 	let mut sender = ctx.sender().clone();
@@ -1401,7 +1439,20 @@ async fn get_approval_signatures_for_candidate<Context>(
 				target: LOG_TARGET,
 				"Request for approval signatures got cancelled by `approval-distribution`."
 			),
-			Some(Ok(votes)) => send_votes(votes),
+			Some(Ok(votes)) => {
+				let votes = votes
+					.into_iter()
+					.filter_map(|(validator_index, (hash, signed_indices, signature))| {
+						let candidates = candidates_by_block.get(&hash)?;
+						let signed_candidates = signed_indices
+							.into_iter()
+							.map(|i| candidates.get(i as usize).copied())
+							.collect::<Option<Vec<_>>>()?;
+						Some((validator_index, (signed_candidates, signature)))
+					})
+					.collect();
+				send_votes(votes)
+			},
 		}
 	};
 
@@ -1757,7 +1808,7 @@ async fn check_and_import_assignment<Sender>(
 	db: &mut OverlayedBackend<'_, impl Backend>,
 	session_info_provider: &mut RuntimeInfo,
 	assignment: IndirectAssignmentCert,
-	candidate_index: CandidateIndex,
+	candidate_indices: CandidateBitfield,
 ) -> SubsystemResult<(AssignmentCheckResult, Vec<Action>)>
 where
 	Sender: SubsystemSender<RuntimeApiMessage>,
@@ -1770,7 +1821,7 @@ where
 		.map(|span| span.child("check-and-import-assignment"))
 		.unwrap_or_else(|| jaeger::Span::new(assignment.block_hash, "check-and-import-assignment"))
 		.with_relay_parent(assignment.block_hash)
-		.with_uint_tag("candidate-index", candidate_index as u64)
+		.with_string_tag("candidate-indices", format!("{:?}", candidate_indices))
 		.with_stage(jaeger::Stage::ApprovalChecking);
 
 	let block_entry = match db.load_block_entry(&assignment.block_hash)? {
@@ -1802,41 +1853,35 @@ where
 			)),
 	};
 
-	let (claimed_core_index, assigned_candidate_hash) =
-		match block_entry.candidate(candidate_index as usize) {
-			Some((c, h)) => (*c, *h),
+	// The claimed cores and candidate entries, in the order of the claimed cores.
+	let mut claimed = Vec::with_capacity(candidate_indices.count_ones());
+	for candidate_index in candidate_indices.iter_ones() {
+		let (claimed_core_index, assigned_candidate_hash) =
+			match block_entry.candidate(candidate_index as usize) {
+				Some((c, h)) => (*c, *h),
+				None =>
+					return Ok((
+						AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidateIndex(
+							candidate_index,
+						)),
+						Vec::new(),
+					)), // no candidate at core.
+			};
+
+		let candidate_entry = match db.load_candidate_entry(&assigned_candidate_hash)? {
+			Some(c) => c,
 			None =>
 				return Ok((
-					AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidateIndex(
+					AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidate(
 						candidate_index,
+						assigned_candidate_hash,
 					)),
 					Vec::new(),
-				)), // no candidate at core.
-		};
-
-	check_and_import_assignment_span
-		.add_string_tag("candidate-hash", format!("{:?}", assigned_candidate_hash));
-	check_and_import_assignment_span.add_string_tag(
-		"traceID",
-		format!("{:?}", jaeger::hash_to_trace_identifier(assigned_candidate_hash.0)),
-	);
-
-	let mut candidate_entry = match db.load_candidate_entry(&assigned_candidate_hash)? {
-		Some(c) => c,
-		None =>
-			return Ok((
-				AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidate(
-					candidate_index,
-					assigned_candidate_hash,
 				)),
-				Vec::new(),
-			)),
-	};
+		};
 
-	let res = {
-		// import the assignment.
-		let approval_entry = match candidate_entry.approval_entry_mut(&assignment.block_hash) {
-			Some(a) => a,
+		let backing_group = match candidate_entry.approval_entry(&assignment.block_hash) {
+			Some(a) => a.backing_group(),
 			None =>
 				return Ok((
 					AssignmentCheckResult::Bad(AssignmentCheckError::Internal(
@@ -1847,78 +1892,129 @@ where
 				)),
 		};
 
-		let res = state.assignment_criteria.check_assignment_cert(
-			claimed_core_index,
-			assignment.validator,
-			&criteria::Config::from(session_info),
-			block_entry.relay_vrf_story(),
-			&assignment.cert,
-			approval_entry.backing_group(),
-		);
+		claimed.push((claimed_core_index, backing_group, assigned_candidate_hash, candidate_entry));
+	}
+	claimed.sort_by_key(|(core, _, _, _)| *core);
 
-		let tranche = match res {
-			Err(crate::criteria::InvalidAssignment(reason)) =>
-				return Ok((
-					AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCert(
-						assignment.validator,
-						format!("{:?}", reason),
-					)),
-					Vec::new(),
+	let claimed_cores = match CoreBitfield::new(claimed.iter().map(|(core, _, _, _)| *core)) {
+		Ok(claimed_cores) => claimed_cores,
+		Err(_) =>
+			return Ok((
+				AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCert(
+					assignment.validator,
+					"No candidates claimed".into(),
 				)),
-			Ok(tranche) => {
-				let current_tranche =
-					state.clock.tranche_now(state.slot_duration_millis, block_entry.slot());
-
-				let too_far_in_future = current_tranche + TICK_TOO_FAR_IN_FUTURE as DelayTranche;
+				Vec::new(),
+			)),
+	};
 
-				if tranche >= too_far_in_future {
-					return Ok((AssignmentCheckResult::TooFarInFuture, Vec::new()))
-				}
+	for (_, _, candidate_hash, _) in &claimed {
+		check_and_import_assignment_span
+			.add_string_tag("candidate-hash", format!("{:?}", candidate_hash));
+		check_and_import_assignment_span.add_string_tag(
+			"traceID",
+			format!("{:?}", jaeger::hash_to_trace_identifier(candidate_hash.0)),
+		);
+	}
 
-				tranche
-			},
-		};
+	let res = state.assignment_criteria.check_assignment_cert(
+		claimed_cores,
+		assignment.validator,
+		&criteria::Config::from(session_info),
+		block_entry.relay_vrf_story(),
+		&assignment.cert,
+		claimed.iter().map(|(_, backing_group, _, _)| *backing_group).collect(),
+	);
 
-		check_and_import_assignment_span.add_uint_tag("tranche", tranche as u64);
+	let tranche = match res {
+		Err(crate::criteria::InvalidAssignment(reason)) =>
+			return Ok((
+				AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCert(
+					assignment.validator,
+					format!("{:?}", reason),
+				)),
+				Vec::new(),
+			)),
+		Ok(tranche) => {
+			let current_tranche =
+				state.clock.tranche_now(state.slot_duration_millis, block_entry.slot());
 
-		let is_duplicate = approval_entry.is_assigned(assignment.validator);
-		approval_entry.import_assignment(tranche, assignment.validator, tick_now);
+			let too_far_in_future = current_tranche + TICK_TOO_FAR_IN_FUTURE as DelayTranche;
 
-		if is_duplicate {
-			AssignmentCheckResult::AcceptedDuplicate
-		} else {
-			gum::trace!(
-				target: LOG_TARGET,
-				validator = assignment.validator.0,
-				candidate_hash = ?assigned_candidate_hash,
-				para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
-				"Imported assignment.",
-			);
+			if tranche >= too_far_in_future {
+				return Ok((AssignmentCheckResult::TooFarInFuture, Vec::new()))
+			}
 
-			AssignmentCheckResult::Accepted
-		}
+			tranche
+		},
 	};
 
+	check_and_import_assignment_span.add_uint_tag("tranche", tranche as u64);
+
+	// The assignment is only a duplicate if it was imported for all claimed candidates.
+	let mut is_duplicate = true;
 	let mut actions = Vec::new();
+	for (_, _, assigned_candidate_hash, mut candidate_entry) in claimed {
+		{
+			// import the assignment.
+			let approval_entry = match candidate_entry.approval_entry_mut(&assignment.block_hash) {
+				Some(a) => a,
+				None =>
+					return Ok((
+						AssignmentCheckResult::Bad(AssignmentCheckError::Internal(
+							assignment.block_hash,
+							assigned_candidate_hash,
+						)),
+						Vec::new(),
+					)),
+			};
 
-	// We've imported a new approval, so we need to schedule a wake-up for when that might no-show.
-	if let Some((approval_entry, status)) = state
-		.approval_status(sender, session_info_provider, &block_entry, &candidate_entry)
-		.await
-	{
-		actions.extend(schedule_wakeup_action(
-			approval_entry,
-			block_entry.block_hash(),
-			block_entry.block_number(),
-			assigned_candidate_hash,
-			status.block_tick,
-			tick_now,
-			status.required_tranches,
-		));
+			if approval_entry.is_assigned(assignment.validator) {
+				gum::trace!(
+					target: LOG_TARGET,
+					validator = assignment.validator.0,
+					candidate_hash = ?assigned_candidate_hash,
+					"Duplicate assignment.",
+				);
+			} else {
+				is_duplicate = false;
+				gum::trace!(
+					target: LOG_TARGET,
+					validator = assignment.validator.0,
+					candidate_hash = ?assigned_candidate_hash,
+					para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
+					"Imported assignment.",
+				);
+			}
+			approval_entry.import_assignment(tranche, assignment.validator, tick_now);
+		}
+
+		// We've imported a new approval, so we need to schedule a wake-up for when that might
+		// no-show.
+		if let Some((approval_entry, status)) = state
+			.approval_status(sender, session_info_provider, &block_entry, &candidate_entry)
+			.await
+		{
+			actions.extend(schedule_wakeup_action(
+				approval_entry,
+				block_entry.block_hash(),
+				block_entry.block_number(),
+				assigned_candidate_hash,
+				status.block_tick,
+				tick_now,
+				status.required_tranches,
+			));
+		}
+
+		// We also write the candidate entry as it now contains the new candidate.
+		db.write_candidate_entry(candidate_entry.into());
 	}
 
-	// We also write the candidate entry as it now contains the new candidate.
-	db.write_candidate_entry(candidate_entry.into());
+	let res = if is_duplicate {
+		AssignmentCheckResult::AcceptedDuplicate
+	} else {
+		AssignmentCheckResult::Accepted
+	};
 
 	Ok((res, actions))
 }
@@ -1929,7 +2025,7 @@ async fn check_and_import_approval<T, Sender>(
 	db: &mut OverlayedBackend<'_, impl Backend>,
 	session_info_provider: &mut RuntimeInfo,
 	metrics: &Metrics,
-	approval: IndirectSignedApprovalVote,
+	approval: IndirectSignedApprovalVoteV2,
 	with_response: impl FnOnce(ApprovalCheckResult) -> T,
 ) -> SubsystemResult<(Vec<Action>, T)>
 where
@@ -1947,7 +2043,7 @@ where
 		.get(&approval.block_hash)
 		.map(|span| span.child("check-and-import-approval"))
 		.unwrap_or_else(|| jaeger::Span::new(approval.block_hash, "check-and-import-approval"))
-		.with_uint_tag("candidate-index", approval.candidate_index as u64)
+		.with_string_tag("candidate-indices", format!("{:?}", approval.candidate_indices))
 		.with_relay_parent(approval.block_hash)
 		.with_stage(jaeger::Stage::ApprovalChecking);
 
@@ -1976,89 +2072,118 @@ where
 		},
 	};
 
-	let approved_candidate_hash = match block_entry.candidate(approval.candidate_index as usize) {
-		Some((_, h)) => *h,
-		None => respond_early!(ApprovalCheckResult::Bad(
-			ApprovalCheckError::InvalidCandidateIndex(approval.candidate_index),
-		)),
-	};
+	// The approved candidates, in the order of their candidate index, as signed.
+	let mut approved_candidate_hashes = Vec::with_capacity(approval.candidate_indices.count_ones());
+	for candidate_index in approval.candidate_indices.iter_ones() {
+		match block_entry.candidate(candidate_index as usize) {
+			Some((_, h)) => approved_candidate_hashes.push((candidate_index, *h)),
+			None => respond_early!(ApprovalCheckResult::Bad(
+				ApprovalCheckError::InvalidCandidateIndex(candidate_index),
+			)),
+		}
+	}
 
-	span.add_string_tag("candidate-hash", format!("{:?}", approved_candidate_hash));
-	span.add_string_tag(
-		"traceID",
-		format!("{:?}", hash_to_trace_identifier(approved_candidate_hash.0)),
-	);
+	if approved_candidate_hashes.is_empty() {
+		respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidateIndex(
+			approval.candidate_indices.len() as CandidateIndex
+		),))
+	}
+
+	for (_, approved_candidate_hash) in &approved_candidate_hashes {
+		span.add_string_tag("candidate-hash", format!("{:?}", approved_candidate_hash));
+		span.add_string_tag(
+			"traceID",
+			format!("{:?}", hash_to_trace_identifier(approved_candidate_hash.0)),
+		);
+	}
 
+	// Cloned, as the session info can't stay borrowed while approvals are imported.
 	let pubkey = match session_info.validators.get(approval.validator) {
-		Some(k) => k,
+		Some(k) => k.clone(),
 		None => respond_early!(ApprovalCheckResult::Bad(
 			ApprovalCheckError::InvalidValidatorIndex(approval.validator),
 		)),
 	};
 
 	// Signature check:
-	match DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking).check_signature(
-		&pubkey,
-		approved_candidate_hash,
-		block_entry.session(),
-		&approval.signature,
-	) {
-		Err(_) => respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(
+	let signed_hashes = approved_candidate_hashes
+		.iter()
+		.map(|(_, h)| *h)
+		.collect::<Vec<CandidateHash>>();
+	let payload =
+		ApprovalVoteMultipleCandidates(&signed_hashes).signing_payload(block_entry.session());
+	if !approval.signature.verify(&payload[..], &pubkey) {
+		respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(
 			approval.validator
-		),)),
-		Ok(()) => {},
-	};
+		),))
+	}
 
-	let candidate_entry = match db.load_candidate_entry(&approved_candidate_hash)? {
-		Some(c) => c,
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidate(
-				approval.candidate_index,
-				approved_candidate_hash
-			),))
-		},
-	};
+	// Don't accept approvals until assignment, to any of the candidates.
+	for (candidate_index, approved_candidate_hash) in &approved_candidate_hashes {
+		let candidate_entry = match db.load_candidate_entry(approved_candidate_hash)? {
+			Some(c) => c,
+			None => {
+				respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidate(
+					*candidate_index,
+					*approved_candidate_hash
+				),))
+			},
+		};
 
-	// Don't accept approvals until assignment.
-	match candidate_entry.approval_entry(&approval.block_hash) {
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::Internal(
-				approval.block_hash,
-				approved_candidate_hash
-			),))
-		},
-		Some(e) if !e.is_assigned(approval.validator) => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::NoAssignment(
-				approval.validator
-			),))
-		},
-		_ => {},
+		match candidate_entry.approval_entry(&approval.block_hash) {
+			None => {
+				respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::Internal(
+					approval.block_hash,
+					*approved_candidate_hash
+				),))
+			},
+			Some(e) if !e.is_assigned(approval.validator) => {
+				respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::NoAssignment(
+					approval.validator
+				),))
+			},
+			_ => {},
+		}
 	}
 
 	// importing the approval can be heavy as it may trigger acceptance for a series of blocks.
 	let t = with_response(ApprovalCheckResult::Accepted);
 
-	gum::trace!(
-		target: LOG_TARGET,
-		validator_index = approval.validator.0,
-		validator = ?pubkey,
-		candidate_hash = ?approved_candidate_hash,
-		para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
-		"Importing approval vote",
-	);
+	let mut actions = Vec::new();
+	for (_, approved_candidate_hash) in approved_candidate_hashes {
+		// Entries are reloaded, as advancing the state of one candidate writes the block entry.
+		let (block_entry, candidate_entry) = match (
+			db.load_block_entry(&approval.block_hash)?,
+			db.load_candidate_entry(&approved_candidate_hash)?,
+		) {
+			(Some(b), Some(c)) => (b, c),
+			_ => continue,
+		};
 
-	let actions = advance_approval_state(
-		sender,
-		state,
-		db,
-		session_info_provider,
-		&metrics,
-		block_entry,
-		approved_candidate_hash,
-		candidate_entry,
-		ApprovalStateTransition::RemoteApproval(approval.validator),
-	)
-	.await;
+		gum::trace!(
+			target: LOG_TARGET,
+			validator_index = approval.validator.0,
+			validator = ?pubkey,
+			candidate_hash = ?approved_candidate_hash,
+			para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
+			"Importing approval vote",
+		);
+
+		actions.extend(
+			advance_approval_state(
+				sender,
+				state,
+				db,
+				session_info_provider,
+				&metrics,
+				block_entry,
+				approved_candidate_hash,
+				candidate_entry,
+				ApprovalStateTransition::RemoteApproval(approval.validator),
+			)
+			.await,
+		);
+	}
 
 	Ok((actions, t))
 }
@@ -2385,12 +2510,14 @@ async fn process_wakeup<Context>(
 			);
 
 			// sanity: should always be present.
+			let claimed_candidate_indices =
+				claimed_candidates(&block_entry, &indirect_cert.cert, i as _);
 			actions.push(Action::LaunchApproval {
 				candidate_hash,
 				indirect_cert,
 				assignment_tranche: tranche,
 				relay_block_hash: relay_block,
-				candidate_index: i as _,
+				claimed_candidate_indices,
 				session: block_entry.session(),
 				candidate: candidate_receipt,
 				backing_group,
@@ -2785,7 +2912,8 @@ async fn issue_approval<Context>(
 			candidate_index: candidate_index as _,
 			validator: validator_index,
 			signature: sig,
-		},
+		}
+		.into(),
 	));
 
 	Ok(actions)
@@ -238,18 +238,19 @@ where
 			polkadot_primitives::CoreIndex,
 			polkadot_primitives::GroupIndex,
 		)>,
+		_enable_v2_assignments: bool,
 	) -> HashMap<polkadot_primitives::CoreIndex, criteria::OurAssignment> {
 		self.0()
 	}
 
 	fn check_assignment_cert(
 		&self,
-		_claimed_core_index: polkadot_primitives::CoreIndex,
+		_claimed_cores: polkadot_node_primitives::approval::CoreBitfield,
 		validator_index: ValidatorIndex,
 		_config: &criteria::Config,
 		_relay_vrf_story: polkadot_node_primitives::approval::RelayVRFStory,
 		_assignment: &polkadot_node_primitives::approval::AssignmentCert,
-		_backing_group: polkadot_primitives::GroupIndex,
+		_backing_groups: Vec<polkadot_primitives::GroupIndex>,
 	) -> Result<polkadot_node_primitives::approval::DelayTranche, criteria::InvalidAssignment> {
 		self.1(validator_index)
 	}
@@ -489,6 +490,7 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 			Config {
 				col_approval_data: test_constants::TEST_CONFIG.col_approval_data,
 				slot_duration_millis: SLOT_DURATION_MILLIS,
+				enable_v2_assignments: false,
 			},
 			Arc::new(db),
 			Arc::new(keystore),
@@ -588,7 +590,8 @@ async fn check_and_import_approval(
 		overseer,
 		FromOrchestra::Communication {
 			msg: ApprovalVotingMessage::CheckAndImportApproval(
-				IndirectSignedApprovalVote { block_hash, candidate_index, validator, signature },
+				IndirectSignedApprovalVote { block_hash, candidate_index, validator, signature }
+					.into(),
 				tx,
 			),
 		},
@@ -621,7 +624,7 @@ async fn check_and_import_assignment(
 					validator,
 					cert: garbage_assignment_cert(AssignmentCertKind::RelayVRFModulo { sample: 0 }),
 				},
-				candidate_index,
+				candidate_index.into(),
 				tx,
 			),
 		},
@@ -1066,7 +1069,7 @@ fn blank_subsystem_act_on_bad_block() {
 							sample: 0,
 						}),
 					},
-					0u32,
+					0u32.into(),
 					tx,
 				),
 			},
@@ -1734,7 +1737,7 @@ fn linear_import_act_on_leaf() {
 							sample: 0,
 						}),
 					},
-					0u32,
+					0u32.into(),
 					tx,
 				),
 			},
@@ -1804,7 +1807,7 @@ fn forkful_import_at_same_height_act_on_leaf() {
 								sample: 0,
 							}),
 						},
-						0u32,
+						0u32.into(),
 						tx,
 					),
 				},
@@ -2097,6 +2100,240 @@ fn subsystem_import_checked_approval_sets_one_block_bit_at_a_time() {
 	});
 }
 
+#[test]
+fn subsystem_imports_assignments_and_approvals_covering_multiple_candidates() {
+	let config = HarnessConfig::default();
+	let store = config.backend();
+	test_harness(config, |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(rx)) => {
+				rx.send(Ok(0)).unwrap();
+			}
+		);
+
+		let block_hash = Hash::repeat_byte(0x01);
+
+		let candidate_receipt1 = {
+			let mut receipt = dummy_candidate_receipt(block_hash);
+			receipt.descriptor.para_id = ParaId::from(1_u32);
+			receipt
+		};
+		let candidate_receipt2 = {
+			let mut receipt = dummy_candidate_receipt(block_hash);
+			receipt.descriptor.para_id = ParaId::from(2_u32);
+			receipt
+		};
+		let candidate_hash1 = candidate_receipt1.hash();
+		let candidate_hash2 = candidate_receipt2.hash();
+		let candidate_indices: CandidateBitfield = vec![0, 1].try_into().unwrap();
+
+		let session_index = 1;
+		let validators = vec![
+			Sr25519Keyring::Alice,
+			Sr25519Keyring::Bob,
+			Sr25519Keyring::Charlie,
+			Sr25519Keyring::Dave,
+			Sr25519Keyring::Eve,
+		];
+		let session_info = SessionInfo {
+			validator_groups: IndexedVec::<GroupIndex, Vec<ValidatorIndex>>::from(vec![
+				vec![ValidatorIndex(0), ValidatorIndex(1)],
+				vec![ValidatorIndex(2)],
+				vec![ValidatorIndex(3), ValidatorIndex(4)],
+			]),
+			..session_info(&validators)
+		};
+
+		ChainBuilder::new()
+			.add_block(
+				block_hash,
+				ChainBuilder::GENESIS_HASH,
+				1,
+				BlockConfig {
+					slot: Slot::from(0),
+					candidates: Some(vec![
+						(candidate_receipt1, CoreIndex(0), GroupIndex(1)),
+						(candidate_receipt2, CoreIndex(1), GroupIndex(2)),
+					]),
+					session_info: Some(session_info),
+				},
+			)
+			.build(&mut virtual_overseer)
+			.await;
+
+		for (i, (validator, key)) in
+			[(ValidatorIndex(0), Sr25519Keyring::Alice), (ValidatorIndex(1), Sr25519Keyring::Bob)]
+				.into_iter()
+				.enumerate()
+		{
+			let (tx, rx) = oneshot::channel();
+			overseer_send(
+				&mut virtual_overseer,
+				FromOrchestra::Communication {
+					msg: ApprovalVotingMessage::CheckAndImportAssignment(
+						IndirectAssignmentCert {
+							block_hash,
+							validator,
+							cert: garbage_assignment_cert(AssignmentCertKind::RelayVRFModulo {
+								sample: 0,
+							}),
+						},
+						candidate_indices.clone(),
+						tx,
+					),
+				},
+			)
+			.await;
+			assert_eq!(rx.await, Ok(AssignmentCheckResult::Accepted));
+
+			let signature = key
+				.sign(
+					&ApprovalVoteMultipleCandidates(&[candidate_hash1, candidate_hash2])
+						.signing_payload(session_index),
+				)
+				.into();
+			let (tx, rx) = oneshot::channel();
+			overseer_send(
+				&mut virtual_overseer,
+				FromOrchestra::Communication {
+					msg: ApprovalVotingMessage::CheckAndImportApproval(
+						IndirectSignedApprovalVoteV2 {
+							block_hash,
+							candidate_indices: candidate_indices.clone(),
+							validator,
+							signature,
+						},
+						tx,
+					),
+				},
+			)
+			.await;
+
+			let expect_block_approved = i == 1;
+			if expect_block_approved {
+				assert_matches!(
+					overseer_recv(&mut virtual_overseer).await,
+					AllMessages::ChainSelection(ChainSelectionMessage::Approved(b_hash)) => {
+						assert_eq!(b_hash, block_hash);
+					}
+				);
+			}
+			assert_eq!(rx.await, Ok(ApprovalCheckResult::Accepted));
+
+			// Sleep to get a consistent read on the database.
+			futures_timer::Delay::new(Duration::from_millis(200)).await;
+
+			let block_entry = store.load_block_entry(&block_hash).unwrap().unwrap();
+			assert_eq!(block_entry.is_fully_approved(), expect_block_approved);
+			assert_eq!(block_entry.is_candidate_approved(&candidate_hash1), expect_block_approved);
+			assert_eq!(block_entry.is_candidate_approved(&candidate_hash2), expect_block_approved);
+		}
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn subsystem_rejects_multiple_candidate_approval_signed_for_one_candidate() {
+	test_harness(HarnessConfig::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(rx)) => {
+				rx.send(Ok(0)).unwrap();
+			}
+		);
+
+		let block_hash = Hash::repeat_byte(0x01);
+
+		let candidate_receipt1 = {
+			let mut receipt = dummy_candidate_receipt(block_hash);
+			receipt.descriptor.para_id = ParaId::from(1_u32);
+			receipt
+		};
+		let candidate_receipt2 = {
+			let mut receipt = dummy_candidate_receipt(block_hash);
+			receipt.descriptor.para_id = ParaId::from(2_u32);
+			receipt
+		};
+		let candidate_hash1 = candidate_receipt1.hash();
+		let candidate_indices: CandidateBitfield = vec![0, 1].try_into().unwrap();
+		let validator = ValidatorIndex(0);
+		let session_index = 1;
+
+		ChainBuilder::new()
+			.add_block(
+				block_hash,
+				ChainBuilder::GENESIS_HASH,
+				1,
+				BlockConfig {
+					slot: Slot::from(0),
+					candidates: Some(vec![
+						(candidate_receipt1, CoreIndex(0), GroupIndex(1)),
+						(candidate_receipt2, CoreIndex(1), GroupIndex(2)),
+					]),
+					session_info: None,
+				},
+			)
+			.build(&mut virtual_overseer)
+			.await;
+
+		let (tx, rx) = oneshot::channel();
+		overseer_send(
+			&mut virtual_overseer,
+			FromOrchestra::Communication {
+				msg: ApprovalVotingMessage::CheckAndImportAssignment(
+					IndirectAssignmentCert {
+						block_hash,
+						validator,
+						cert: garbage_assignment_cert(AssignmentCertKind::RelayVRFModulo {
+							sample: 0,
+						}),
+					},
+					candidate_indices.clone(),
+					tx,
+				),
+			},
+		)
+		.await;
+		assert_eq!(rx.await, Ok(AssignmentCheckResult::Accepted));
+
+		// A signature over the first candidate only does not cover both claimed candidates.
+		let signature = sign_approval(Sr25519Keyring::Alice, candidate_hash1, session_index);
+		let (tx, rx) = oneshot::channel();
+		overseer_send(
+			&mut virtual_overseer,
+			FromOrchestra::Communication {
+				msg: ApprovalVotingMessage::CheckAndImportApproval(
+					IndirectSignedApprovalVoteV2 {
+						block_hash,
+						candidate_indices,
+						validator,
+						signature,
+					},
+					tx,
+				),
+			},
+		)
+		.await;
+
+		assert_matches!(
+			rx.await,
+			Ok(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(v))) => {
+				assert_eq!(v, validator);
+			}
+		);
+
+		virtual_overseer
+	});
+}
+
 fn approved_ancestor_test(
 	skip_approval: impl Fn(BlockNumber) -> bool,
 	approved_height: BlockNumber,
@@ -2355,9 +2592,9 @@ async fn handle_double_assignment_import(
 		overseer_recv(virtual_overseer).await,
 		AllMessages::ApprovalDistribution(ApprovalDistributionMessage::DistributeAssignment(
 			_,
-			c_index,
+			c_indices,
 		)) => {
-			assert_eq!(candidate_index, c_index);
+			assert_eq!(CandidateBitfield::from(candidate_index), c_indices);
 		}
 	);
 
@@ -2368,9 +2605,9 @@ async fn handle_double_assignment_import(
 		overseer_recv(virtual_overseer).await,
 		AllMessages::ApprovalDistribution(ApprovalDistributionMessage::DistributeAssignment(
 			_,
-			c_index
+			c_indices
 		)) => {
-			assert_eq!(candidate_index, c_index);
+			assert_eq!(CandidateBitfield::from(candidate_index), c_indices);
 		}
 	);
 
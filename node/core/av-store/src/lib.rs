@@ -20,9 +20,9 @@
 #![warn(missing_docs)]
 
 use std::{
-	collections::{BTreeSet, HashMap, HashSet},
+	collections::{hash_map::Entry, BTreeSet, HashMap, HashSet},
 	io,
-	sync::Arc,
+	sync::{Arc, Mutex, MutexGuard},
 	time::{Duration, SystemTime, SystemTimeError, UNIX_EPOCH},
 };
 
@@ -48,12 +48,15 @@ use polkadot_node_subsystem::{
 };
 use polkadot_node_subsystem_util as util;
 use polkadot_primitives::{
-	BlockNumber, CandidateEvent, CandidateHash, CandidateReceipt, Hash, Header, ValidatorIndex,
+	BlockNumber, CandidateEvent, CandidateHash, CandidateReceipt, Hash, Header, Id as ParaId,
+	ValidatorIndex,
 };
 
 mod metrics;
 pub use self::metrics::*;
 
+pub mod offline;
+
 #[cfg(test)]
 mod tests;
 
@@ -66,6 +69,7 @@ const CHUNK_PREFIX: &[u8; 5] = b"chunk";
 const META_PREFIX: &[u8; 4] = b"meta";
 const UNFINALIZED_PREFIX: &[u8; 11] = b"unfinalized";
 const PRUNE_BY_TIME_PREFIX: &[u8; 13] = b"prune_by_time";
+const USAGE_PREFIX: &[u8; 5] = b"usage";
 
 // We have some keys we want to map to empty values because existence of the key is enough. We use this because
 // rocksdb doesn't support empty values.
@@ -158,6 +162,100 @@ struct CandidateMeta {
 	chunks_stored: BitVec<u8, BitOrderLsb0>,
 }
 
+// The para of a candidate and the number of bytes stored for it. Candidates stored before these
+// records were introduced don't have one and are not accounted for.
+#[derive(Debug, Default, Clone, Encode, Decode)]
+struct CandidateUsage {
+	// `None` until the candidate is seen backed or included in a block.
+	para_id: Option<ParaId>,
+	// The encoded size of the available data and the chunks.
+	stored_bytes: u64,
+}
+
+// Running totals of the bytes stored per para, as recorded by the usage records. They are built by
+// a single scan when the subsystem starts and kept up to date along with the records, so that
+// neither enforcing the size limit nor reporting the metrics requires scanning the store.
+#[derive(Debug, Default, Clone)]
+struct UsageTotals(Arc<Mutex<HashMap<Option<ParaId>, u64>>>);
+
+impl UsageTotals {
+	// Rebuild the totals from the usage records in the store.
+	fn load(&self, db: &Arc<dyn Database>, config: &Config) -> Result<(), Error> {
+		let mut totals = HashMap::new();
+		for r in db.iter_with_prefix(config.col_meta, USAGE_PREFIX) {
+			let (_k, v) = r?;
+			let usage = CandidateUsage::decode(&mut &v[..])?;
+			*totals.entry(usage.para_id).or_default() += usage.stored_bytes;
+		}
+
+		*self.lock() = totals;
+		Ok(())
+	}
+
+	// Account for the usage records changed by a transaction, once it has been written.
+	fn apply(&self, changes: UsageChanges) {
+		let mut totals = self.lock();
+		for (old, new) in changes.0.into_values() {
+			if let Some(old) = old {
+				let bytes = totals.entry(old.para_id).or_default();
+				*bytes = bytes.saturating_sub(old.stored_bytes);
+			}
+			if let Some(new) = new {
+				*totals.entry(new.para_id).or_default() += new.stored_bytes;
+			}
+		}
+	}
+
+	fn total(&self) -> u64 {
+		self.lock().values().sum()
+	}
+
+	// The total as it will be once `changes` are applied.
+	fn total_with(&self, changes: &UsageChanges) -> u64 {
+		let stored_bytes =
+			|usage: &Option<CandidateUsage>| usage.as_ref().map_or(0, |u| u.stored_bytes);
+		let (old, new) = changes
+			.0
+			.values()
+			.fold((0, 0), |(old, new), (o, n)| (old + stored_bytes(o), new + stored_bytes(n)));
+		self.total().saturating_sub(old) + new
+	}
+
+	fn per_para(&self) -> HashMap<Option<ParaId>, u64> {
+		self.lock().clone()
+	}
+
+	fn lock(&self) -> MutexGuard<'_, HashMap<Option<ParaId>, u64>> {
+		self.0.lock().expect("the lock is never held across a panic; qed")
+	}
+}
+
+// The usage records changed by a transaction which has not been written yet, by candidate: the
+// record in the store and the pending one, where `None` means that there is no record. The totals
+// only take them into account once the transaction has been written, see `UsageTotals::apply`.
+#[derive(Debug, Default)]
+struct UsageChanges(HashMap<CandidateHash, (Option<CandidateUsage>, Option<CandidateUsage>)>);
+
+impl UsageChanges {
+	// The pending usage record of the candidate, loaded from the store if the transaction doesn't
+	// change it yet.
+	fn pending(
+		&mut self,
+		db: &Arc<dyn Database>,
+		config: &Config,
+		hash: &CandidateHash,
+	) -> Result<&mut Option<CandidateUsage>, Error> {
+		let (_, pending) = match self.0.entry(*hash) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => {
+				let stored = load_usage(db, config, hash)?;
+				entry.insert((stored.clone(), stored))
+			},
+		};
+		Ok(pending)
+	}
+}
+
 fn query_inner<D: Decode>(
 	db: &Arc<dyn Database>,
 	column: u32,
@@ -258,6 +356,47 @@ fn delete_meta(tx: &mut DBTransaction, config: &Config, hash: &CandidateHash) {
 	tx.delete(config.col_meta, &key[..])
 }
 
+fn load_usage(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	hash: &CandidateHash,
+) -> Result<Option<CandidateUsage>, Error> {
+	let key = (USAGE_PREFIX, hash).encode();
+
+	query_inner(db, config.col_meta, &key)
+}
+
+fn write_usage(
+	tx: &mut DBTransaction,
+	config: &Config,
+	hash: &CandidateHash,
+	usage: &CandidateUsage,
+) {
+	let key = (USAGE_PREFIX, hash).encode();
+
+	tx.put_vec(config.col_meta, &key, usage.encode());
+}
+
+fn delete_usage(tx: &mut DBTransaction, config: &Config, hash: &CandidateHash) {
+	let key = (USAGE_PREFIX, hash).encode();
+	tx.delete(config.col_meta, &key[..])
+}
+
+// Load the pending usage record of the candidate, or a fresh one, apply `f` and write it back.
+fn update_usage(
+	db: &Arc<dyn Database>,
+	tx: &mut DBTransaction,
+	config: &Config,
+	usage_changes: &mut UsageChanges,
+	hash: &CandidateHash,
+	f: impl FnOnce(&mut CandidateUsage),
+) -> Result<(), Error> {
+	let usage = usage_changes.pending(db, config, hash)?.get_or_insert_with(Default::default);
+	f(usage);
+	write_usage(tx, config, hash, usage);
+	Ok(())
+}
+
 fn delete_unfinalized_height(tx: &mut DBTransaction, config: &Config, block_number: BlockNumber) {
 	let prefix = (UNFINALIZED_PREFIX, BEBlockNumber(block_number)).encode();
 	tx.delete_prefix(config.col_meta, &prefix);
@@ -406,8 +545,37 @@ impl Error {
 	}
 }
 
-/// Struct holding pruning timing configuration.
-/// The only purpose of this structure is to use different timing
+/// The policy by which data is pruned from the availability store.
+#[derive(Debug, Clone)]
+pub struct PruningPolicy {
+	/// How long the data of candidates that are not included in any unfinalized block is kept,
+	/// counted from when the candidate was first seen. Candidates included in unfinalized blocks
+	/// are kept until the blocks are finalized or abandoned.
+	pub keep_unfinalized_for: Duration,
+	/// How long the data of candidates included in a finalized block is kept, counted from the
+	/// finalization.
+	pub keep_finalized_for: Duration,
+	/// The maximum number of bytes of available data and chunks to keep. Once it is exceeded, the
+	/// data of candidates included in finalized blocks is evicted before its retention window
+	/// ends, least recently finalized first. Unfinalized data is never evicted.
+	pub max_total_size: Option<u64>,
+	/// Paras whose data is never pruned once the candidate is included in a finalized block.
+	pub archive_paras: HashSet<ParaId>,
+}
+
+impl Default for PruningPolicy {
+	fn default() -> Self {
+		Self {
+			keep_unfinalized_for: KEEP_UNAVAILABLE_FOR,
+			keep_finalized_for: KEEP_FINALIZED_FOR,
+			max_total_size: None,
+			archive_paras: HashSet::new(),
+		}
+	}
+}
+
+/// Struct holding the pruning configuration, i.e. the `PruningPolicy` and
+/// the pruning interval. This allows to use different timing
 /// configurations in production and in testing.
 #[derive(Clone)]
 struct PruningConfig {
@@ -419,14 +587,22 @@ struct PruningConfig {
 
 	/// How often to perform data pruning.
 	pruning_interval: Duration,
+
+	/// The size above which finalized data is evicted.
+	max_total_size: Option<u64>,
+
+	/// Paras whose finalized data is never pruned.
+	archive_paras: HashSet<ParaId>,
 }
 
-impl Default for PruningConfig {
-	fn default() -> Self {
+impl From<PruningPolicy> for PruningConfig {
+	fn from(policy: PruningPolicy) -> Self {
 		Self {
-			keep_unavailable_for: KEEP_UNAVAILABLE_FOR,
-			keep_finalized_for: KEEP_FINALIZED_FOR,
+			keep_unavailable_for: policy.keep_unfinalized_for,
+			keep_finalized_for: policy.keep_finalized_for,
 			pruning_interval: PRUNING_INTERVAL,
+			max_total_size: policy.max_total_size,
+			archive_paras: policy.archive_paras,
 		}
 	}
 }
@@ -458,6 +634,7 @@ pub struct AvailabilityStoreSubsystem {
 	pruning_config: PruningConfig,
 	config: Config,
 	db: Arc<dyn Database>,
+	usage_totals: UsageTotals,
 	known_blocks: KnownUnfinalizedBlocks,
 	finalized_number: Option<BlockNumber>,
 	metrics: Metrics,
@@ -466,17 +643,19 @@ pub struct AvailabilityStoreSubsystem {
 }
 
 impl AvailabilityStoreSubsystem {
-	/// Create a new `AvailabilityStoreSubsystem` with a given config on disk.
+	/// Create a new `AvailabilityStoreSubsystem` with a given config on disk, which prunes the
+	/// data according to the given policy.
 	pub fn new(
 		db: Arc<dyn Database>,
 		config: Config,
+		pruning_policy: PruningPolicy,
 		sync_oracle: Box<dyn SyncOracle + Send + Sync>,
 		metrics: Metrics,
 	) -> Self {
 		Self::with_pruning_config_and_clock(
 			db,
 			config,
-			PruningConfig::default(),
+			pruning_policy.into(),
 			Box::new(SystemClock),
 			sync_oracle,
 			metrics,
@@ -496,6 +675,7 @@ impl AvailabilityStoreSubsystem {
 			pruning_config,
 			config,
 			db,
+			usage_totals: UsageTotals::default(),
 			metrics,
 			clock,
 			known_blocks: KnownUnfinalizedBlocks::default(),
@@ -549,6 +729,13 @@ impl<Context> AvailabilityStoreSubsystem {
 
 #[overseer::contextbounds(AvailabilityStore, prefix = self::overseer)]
 async fn run<Context>(mut subsystem: AvailabilityStoreSubsystem, mut ctx: Context) {
+	if let Err(e) = subsystem.usage_totals.load(&subsystem.db, &subsystem.config) {
+		e.trace();
+		if e.is_fatal() {
+			return
+		}
+	}
+
 	let mut next_pruning = Delay::new(subsystem.pruning_config.pruning_interval).fuse();
 	// Pruning interval is in the order of minutes so we shouldn't have more than one task running
 	// at one moment in time, so 10 should be more than enough.
@@ -660,6 +847,8 @@ async fn start_prune_all<Context>(
 	let metrics = subsystem.metrics.clone();
 	let db = subsystem.db.clone();
 	let config = subsystem.config;
+	let usage_totals = subsystem.usage_totals.clone();
+	let max_total_size = subsystem.pruning_config.max_total_size;
 	let time_now = subsystem.clock.now()?;
 
 	ctx.spawn_blocking(
@@ -668,7 +857,12 @@ async fn start_prune_all<Context>(
 			let _timer = metrics.time_pruning();
 
 			gum::debug!(target: LOG_TARGET, "Prunning started");
-			let result = prune_all(&db, &config, time_now);
+			let result = prune_all(&db, &config, &usage_totals, time_now).and_then(|()| {
+				max_total_size.map_or(Ok(()), |max_total_size| {
+					evict_finalized(&db, &config, &usage_totals, max_total_size, &metrics)
+				})
+			});
+			metrics.on_stored_bytes(&usage_totals.per_para());
 
 			if let Err(err) = pruning_result_tx.send(result).await {
 				// This usually means that the node is closing down, log it just in case
@@ -713,12 +907,14 @@ async fn process_block_activated<Context>(
 		// it's important to commit the db transactions for a head before the next one is processed
 		// alternatively, we could utilize the OverlayBackend from approval-voting
 		let mut tx = DBTransaction::new();
+		let mut usage_changes = UsageChanges::default();
 		process_new_head(
 			ctx,
 			&subsystem.db,
 			&mut tx,
 			&subsystem.config,
 			&subsystem.pruning_config,
+			&mut usage_changes,
 			now,
 			hash,
 			header,
@@ -726,6 +922,7 @@ async fn process_block_activated<Context>(
 		.await?;
 		subsystem.known_blocks.insert(hash, block_number);
 		subsystem.db.write(tx)?;
+		subsystem.usage_totals.apply(usage_changes);
 	}
 
 	Ok(())
@@ -738,6 +935,7 @@ async fn process_new_head<Context>(
 	db_transaction: &mut DBTransaction,
 	config: &Config,
 	pruning_config: &PruningConfig,
+	usage_changes: &mut UsageChanges,
 	now: Duration,
 	hash: Hash,
 	header: Header,
@@ -757,6 +955,7 @@ async fn process_new_head<Context>(
 					db_transaction,
 					config,
 					pruning_config,
+					usage_changes,
 					now,
 					n_validators,
 					receipt,
@@ -768,6 +967,7 @@ async fn process_new_head<Context>(
 					db_transaction,
 					config,
 					pruning_config,
+					usage_changes,
 					(header.number, hash),
 					receipt,
 				)?;
@@ -784,6 +984,7 @@ fn note_block_backed(
 	db_transaction: &mut DBTransaction,
 	config: &Config,
 	pruning_config: &PruningConfig,
+	usage_changes: &mut UsageChanges,
 	now: Duration,
 	n_validators: usize,
 	candidate: CandidateReceipt,
@@ -805,7 +1006,10 @@ fn note_block_backed(
 		write_meta(db_transaction, config, &candidate_hash, &meta);
 	}
 
-	Ok(())
+	let para_id = candidate.descriptor.para_id;
+	update_usage(db, db_transaction, config, usage_changes, &candidate_hash, |usage| {
+		usage.para_id = Some(para_id)
+	})
 }
 
 fn note_block_included(
@@ -813,6 +1017,7 @@ fn note_block_included(
 	db_transaction: &mut DBTransaction,
 	config: &Config,
 	pruning_config: &PruningConfig,
+	usage_changes: &mut UsageChanges,
 	block: (BlockNumber, Hash),
 	candidate: CandidateReceipt,
 ) -> Result<(), Error> {
@@ -864,6 +1069,11 @@ fn note_block_included(
 				&candidate_hash,
 			);
 			write_meta(db_transaction, config, &candidate_hash, &meta);
+
+			let para_id = candidate.descriptor.para_id;
+			update_usage(db, db_transaction, config, usage_changes, &candidate_hash, |usage| {
+				usage.para_id = Some(para_id)
+			})?;
 		},
 	}
 
@@ -1050,14 +1260,22 @@ fn update_blocks_at_finalized_height(
 
 			meta.state = State::Finalized(now.into());
 
-			// Write the meta and a pruning record.
+			// Write the meta and a pruning record, unless the para is archived, in which case the
+			// candidate is never pruned.
 			write_meta(db_transaction, &subsystem.config, &candidate_hash, &meta);
-			write_pruning_key(
-				db_transaction,
-				&subsystem.config,
-				now + subsystem.pruning_config.keep_finalized_for,
-				&candidate_hash,
-			);
+
+			let para_id = load_usage(&subsystem.db, &subsystem.config, &candidate_hash)?
+				.and_then(|usage| usage.para_id);
+			if para_id
+				.map_or(true, |para_id| !subsystem.pruning_config.archive_paras.contains(&para_id))
+			{
+				write_pruning_key(
+					db_transaction,
+					&subsystem.config,
+					now + subsystem.pruning_config.keep_finalized_for,
+					&candidate_hash,
+				);
+			}
 		} else {
 			meta.state = match meta.state {
 				State::Finalized(_) => continue,   // sanity.
@@ -1171,7 +1389,13 @@ fn process_message(
 			subsystem.metrics.on_chunks_received(1);
 			let _timer = subsystem.metrics.time_store_chunk();
 
-			match store_chunk(&subsystem.db, &subsystem.config, candidate_hash, chunk) {
+			match store_chunk(
+				&subsystem.db,
+				&subsystem.config,
+				&subsystem.usage_totals,
+				candidate_hash,
+				chunk,
+			) {
 				Ok(true) => {
 					let _ = tx.send(Ok(()));
 				},
@@ -1196,11 +1420,15 @@ fn process_message(
 			let _timer = subsystem.metrics.time_store_available_data();
 
 			let res = store_available_data(
-				&subsystem,
+				&subsystem.db,
+				&subsystem.config,
+				&subsystem.usage_totals,
+				subsystem.pruning_config.keep_unavailable_for,
+				&*subsystem.clock,
 				candidate_hash,
 				n_validators as _,
 				available_data,
-				expected_erasure_root,
+				Some(expected_erasure_root),
 			);
 
 			match res {
@@ -1229,10 +1457,12 @@ fn process_message(
 fn store_chunk(
 	db: &Arc<dyn Database>,
 	config: &Config,
+	usage_totals: &UsageTotals,
 	candidate_hash: CandidateHash,
 	chunk: ErasureChunk,
 ) -> Result<bool, Error> {
 	let mut tx = DBTransaction::new();
+	let mut usage_changes = UsageChanges::default();
 
 	let mut meta = match load_meta(db, config, &candidate_hash)? {
 		Some(m) => m,
@@ -1246,6 +1476,11 @@ fn store_chunk(
 
 			write_chunk(&mut tx, config, &candidate_hash, chunk.index, &chunk);
 			write_meta(&mut tx, config, &candidate_hash, &meta);
+
+			let chunk_size = chunk.encoded_size() as u64;
+			update_usage(db, &mut tx, config, &mut usage_changes, &candidate_hash, |usage| {
+				usage.stored_bytes += chunk_size
+			})?;
 		},
 		None => return Ok(false), // out of bounds.
 	}
@@ -1258,20 +1493,26 @@ fn store_chunk(
 	);
 
 	db.write(tx)?;
+	usage_totals.apply(usage_changes);
 	Ok(true)
 }
 
-// Ok(true) on success, Ok(false) on failure, and Err on internal error.
+// Ok(()) on success, and Err on internal error or if the erasure root doesn't match the expected
+// one. The data is not checked against an erasure root if none is given.
 fn store_available_data(
-	subsystem: &AvailabilityStoreSubsystem,
+	db: &Arc<dyn Database>,
+	config: &Config,
+	usage_totals: &UsageTotals,
+	keep_unavailable_for: Duration,
+	clock: &dyn Clock,
 	candidate_hash: CandidateHash,
 	n_validators: usize,
 	available_data: AvailableData,
-	expected_erasure_root: Hash,
+	expected_erasure_root: Option<Hash>,
 ) -> Result<(), Error> {
 	let mut tx = DBTransaction::new();
 
-	let mut meta = match load_meta(&subsystem.db, &subsystem.config, &candidate_hash)? {
+	let mut meta = match load_meta(db, config, &candidate_hash)? {
 		Some(m) => {
 			if m.data_available {
 				return Ok(()) // already stored.
@@ -1280,11 +1521,11 @@ fn store_available_data(
 			m
 		},
 		None => {
-			let now = subsystem.clock.now()?;
+			let now = clock.now()?;
 
 			// Write a pruning record.
-			let prune_at = now + subsystem.pruning_config.keep_unavailable_for;
-			write_pruning_key(&mut tx, &subsystem.config, prune_at, &candidate_hash);
+			let prune_at = now + keep_unavailable_for;
+			write_pruning_key(&mut tx, config, prune_at, &candidate_hash);
 
 			CandidateMeta {
 				state: State::Unavailable(now.into()),
@@ -1303,7 +1544,7 @@ fn store_available_data(
 	let chunks = erasure::obtain_chunks_v1(n_validators, &available_data)?;
 	let branches = erasure::branches(chunks.as_ref());
 
-	if branches.root() != expected_erasure_root {
+	if expected_erasure_root.map_or(false, |root| root != branches.root()) {
 		return Err(Error::InvalidErasureRoot)
 	}
 
@@ -1317,27 +1558,41 @@ fn store_available_data(
 		},
 	);
 
+	let mut stored_bytes = available_data.encoded_size() as u64;
 	for chunk in erasure_chunks {
-		write_chunk(&mut tx, &subsystem.config, &candidate_hash, chunk.index, &chunk);
+		stored_bytes += chunk.encoded_size() as u64;
+		write_chunk(&mut tx, config, &candidate_hash, chunk.index, &chunk);
 	}
 
 	meta.data_available = true;
 	meta.chunks_stored = bitvec::bitvec![u8, BitOrderLsb0; 1; n_validators];
 
-	write_meta(&mut tx, &subsystem.config, &candidate_hash, &meta);
-	write_available_data(&mut tx, &subsystem.config, &candidate_hash, &available_data);
+	write_meta(&mut tx, config, &candidate_hash, &meta);
+	write_available_data(&mut tx, config, &candidate_hash, &available_data);
+	// Any previously stored chunks are overwritten by the ones derived from the data.
+	let mut usage_changes = UsageChanges::default();
+	update_usage(db, &mut tx, config, &mut usage_changes, &candidate_hash, |usage| {
+		usage.stored_bytes = stored_bytes
+	})?;
 
-	subsystem.db.write(tx)?;
+	db.write(tx)?;
+	usage_totals.apply(usage_changes);
 
 	gum::debug!(target: LOG_TARGET, ?candidate_hash, "Stored data and chunks");
 
 	Ok(())
 }
 
-fn prune_all(db: &Arc<dyn Database>, config: &Config, now: Duration) -> Result<(), Error> {
+fn prune_all(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	usage_totals: &UsageTotals,
+	now: Duration,
+) -> Result<(), Error> {
 	let (range_start, range_end) = pruning_range(now);
 
 	let mut tx = DBTransaction::new();
+	let mut usage_changes = UsageChanges::default();
 	let iter = db
 		.iter_with_prefix(config.col_meta, &range_start[..])
 		.take_while(|r| r.as_ref().map_or(true, |(k, _v)| &k[..] < &range_end[..]));
@@ -1351,38 +1606,115 @@ fn prune_all(db: &Arc<dyn Database>, config: &Config, now: Duration) -> Result<(
 			Err(_) => continue, // sanity
 		};
 
-		delete_meta(&mut tx, config, &candidate_hash);
+		// Pruning references don't need to be manually taken care of as we are deleting them as
+		// we go.
+		delete_candidate(db, &mut tx, config, &mut usage_changes, &candidate_hash)?;
+	}
 
-		// Clean up all attached data of the candidate.
-		if let Some(meta) = load_meta(db, config, &candidate_hash)? {
-			// delete available data.
-			if meta.data_available {
-				delete_available_data(&mut tx, config, &candidate_hash)
-			}
+	db.write(tx)?;
+	usage_totals.apply(usage_changes);
+	Ok(())
+}
 
-			// delete chunks.
-			for (i, b) in meta.chunks_stored.iter().enumerate() {
-				if *b {
-					delete_chunk(&mut tx, config, &candidate_hash, ValidatorIndex(i as _));
-				}
+// Delete the meta, the usage record and all attached data of the candidate, except for its
+// pruning key.
+fn delete_candidate(
+	db: &Arc<dyn Database>,
+	tx: &mut DBTransaction,
+	config: &Config,
+	usage_changes: &mut UsageChanges,
+	candidate_hash: &CandidateHash,
+) -> Result<(), Error> {
+	delete_meta(tx, config, candidate_hash);
+	*usage_changes.pending(db, config, candidate_hash)? = None;
+	delete_usage(tx, config, candidate_hash);
+
+	// Clean up all attached data of the candidate.
+	if let Some(meta) = load_meta(db, config, candidate_hash)? {
+		// delete available data.
+		if meta.data_available {
+			delete_available_data(tx, config, candidate_hash)
+		}
+
+		// delete chunks.
+		for (i, b) in meta.chunks_stored.iter().enumerate() {
+			if *b {
+				delete_chunk(tx, config, candidate_hash, ValidatorIndex(i as _));
 			}
+		}
 
-			// delete unfinalized block references. Pruning references don't need to be
-			// manually taken care of as we are deleting them as we go in the outer loop.
-			if let State::Unfinalized(_, blocks) = meta.state {
-				for (block_number, block_hash) in blocks {
-					delete_unfinalized_inclusion(
-						&mut tx,
-						config,
-						block_number.0,
-						&block_hash,
-						&candidate_hash,
-					);
-				}
+		// delete unfinalized block references.
+		if let State::Unfinalized(_, blocks) = meta.state {
+			for (block_number, block_hash) in blocks {
+				delete_unfinalized_inclusion(
+					tx,
+					config,
+					block_number.0,
+					&block_hash,
+					candidate_hash,
+				);
 			}
 		}
 	}
 
+	Ok(())
+}
+
+// If the store holds more than `max_total_size` bytes, evict the data of finalized candidates,
+// least recently finalized first, until it doesn't. Archived candidates have no pruning key and
+// are never evicted.
+fn evict_finalized(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	usage_totals: &UsageTotals,
+	max_total_size: u64,
+	metrics: &Metrics,
+) -> Result<(), Error> {
+	let mut total_size = usage_totals.total();
+	if total_size <= max_total_size {
+		return Ok(())
+	}
+
+	let mut tx = DBTransaction::new();
+	let mut usage_changes = UsageChanges::default();
+	let mut evicted = 0;
+
+	// Pruning keys are ordered by time, and those of finalized candidates are all offset by the
+	// same retention window, so this iterates in the order of finalization.
+	for r in db.iter_with_prefix(config.col_meta, PRUNE_BY_TIME_PREFIX) {
+		if total_size <= max_total_size {
+			break
+		}
+
+		let (k, _v) = r?;
+		let (_, candidate_hash) = match decode_pruning_key(&k[..]) {
+			Ok(m) => m,
+			Err(_) => continue, // sanity
+		};
+
+		match load_meta(db, config, &candidate_hash)? {
+			Some(CandidateMeta { state: State::Finalized(_), .. }) => {},
+			_ => continue,
+		}
+
+		tx.delete(config.col_meta, &k[..]);
+		delete_candidate(db, &mut tx, config, &mut usage_changes, &candidate_hash)?;
+
+		total_size = usage_totals.total_with(&usage_changes);
+		evicted += 1;
+	}
+
+	gum::debug!(
+		target: LOG_TARGET,
+		evicted,
+		total_size,
+		max_total_size,
+		"Evicted finalized candidates over the size limit",
+	);
+
 	db.write(tx)?;
+	usage_totals.apply(usage_changes);
+	metrics.on_candidates_evicted(evicted);
+
 	Ok(())
 }
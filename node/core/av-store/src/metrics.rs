@@ -15,6 +15,8 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
+use polkadot_primitives::Id as ParaId;
+use std::collections::HashMap;
 
 #[derive(Clone)]
 pub(crate) struct MetricsInner {
@@ -26,6 +28,8 @@ pub(crate) struct MetricsInner {
 	store_available_data: prometheus::Histogram,
 	store_chunk: prometheus::Histogram,
 	get_chunk: prometheus::Histogram,
+	stored_bytes: prometheus::GaugeVec<prometheus::U64>,
+	evicted_candidates_total: prometheus::Counter<prometheus::U64>,
 }
 
 /// Availability metrics.
@@ -41,6 +45,27 @@ impl Metrics {
 		}
 	}
 
+	/// Set the number of bytes stored per para. Candidates whose para is not known yet are
+	/// reported as `unknown`.
+	pub(crate) fn on_stored_bytes(&self, stored_bytes: &HashMap<Option<ParaId>, u64>) {
+		if let Some(metrics) = &self.0 {
+			metrics.stored_bytes.reset();
+			for (para_id, bytes) in stored_bytes {
+				let para_id =
+					para_id.map_or_else(|| "unknown".into(), |p| u32::from(p).to_string());
+				metrics.stored_bytes.with_label_values(&[&para_id]).set(*bytes);
+			}
+		}
+	}
+
+	pub(crate) fn on_candidates_evicted(&self, count: usize) {
+		if let Some(metrics) = &self.0 {
+			// assume usize fits into u64
+			let by = u64::try_from(count).unwrap_or_default();
+			metrics.evicted_candidates_total.inc_by(by);
+		}
+	}
+
 	/// Provide a timer for `prune_povs` which observes on drop.
 	pub(crate) fn time_pruning(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.pruning.start_timer())
@@ -152,6 +177,23 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			stored_bytes: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_av_store_stored_bytes",
+						"Bytes of available data and chunks stored per para.",
+					),
+					&["para_id"],
+				)?,
+				registry,
+			)?,
+			evicted_candidates_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_av_store_evicted_candidates_total",
+					"Number of finalized candidates evicted because the store exceeded its size limit.",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
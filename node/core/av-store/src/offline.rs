@@ -0,0 +1,326 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Access to the availability store without a running subsystem.
+//!
+//! This allows to list the stored candidates, to export their available data and chunks to files
+//! and to import such files into another store, e.g. to debug availability failures or to seed
+//! test networks.
+
+use super::*;
+use polkadot_primitives::{BlakeTwo256, HashT};
+use std::{fs, path::Path};
+
+/// The version of the export file format. Files of other versions are rejected.
+const EXPORT_VERSION: u32 = 1;
+
+/// The pruning state of a stored candidate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PruningState {
+	/// The candidate was first seen at the given time, but is not included in any block.
+	Unavailable {
+		/// The time the candidate was first seen, since the unix epoch.
+		first_seen: Duration,
+	},
+	/// The candidate is included in the given unfinalized blocks. It is not pruned as long as it
+	/// stays in this state.
+	Unfinalized {
+		/// The time the candidate was first seen, since the unix epoch.
+		first_seen: Duration,
+		/// The unfinalized blocks the candidate is included in.
+		blocks: Vec<(BlockNumber, Hash)>,
+	},
+	/// The candidate is included in a finalized block.
+	Finalized {
+		/// The time the block was finalized, since the unix epoch.
+		finalized_at: Duration,
+	},
+}
+
+/// A candidate in the availability store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredCandidate {
+	/// The hash of the candidate.
+	pub candidate_hash: CandidateHash,
+	/// The pruning state of the candidate.
+	pub state: PruningState,
+	/// When the candidate is pruned, since the unix epoch, if it is scheduled for pruning.
+	pub prune_at: Option<Duration>,
+	/// Whether the full available data is stored.
+	pub data_available: bool,
+	/// The number of validators the data is erasure coded for.
+	pub n_validators: usize,
+	/// The indices of the stored chunks.
+	pub chunks_stored: Vec<ValidatorIndex>,
+}
+
+/// The available data or a chunk of a candidate, as written to an export file.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum ExportedItem {
+	/// The full available data of a candidate.
+	#[codec(index = 0)]
+	AvailableData {
+		/// The hash of the candidate.
+		candidate_hash: CandidateHash,
+		/// The number of validators the data is erasure coded for.
+		n_validators: u32,
+		/// The available data.
+		available_data: AvailableData,
+	},
+	/// A single chunk of a candidate, with its merkle proof.
+	#[codec(index = 1)]
+	Chunk {
+		/// The hash of the candidate.
+		candidate_hash: CandidateHash,
+		/// The number of validators the data is erasure coded for.
+		n_validators: u32,
+		/// The chunk.
+		chunk: ErasureChunk,
+	},
+}
+
+impl ExportedItem {
+	/// The hash of the candidate the item belongs to.
+	pub fn candidate_hash(&self) -> CandidateHash {
+		match self {
+			Self::AvailableData { candidate_hash, .. } | Self::Chunk { candidate_hash, .. } =>
+				*candidate_hash,
+		}
+	}
+
+	/// Reads an exported item from the given file.
+	pub fn read(path: &Path) -> io::Result<Self> {
+		let bytes = fs::read(path)?;
+		let (version, item) = <(u32, Self)>::decode(&mut &bytes[..])
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+		if version != EXPORT_VERSION {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("unsupported export version {}, expected {}", version, EXPORT_VERSION),
+			))
+		}
+		Ok(item)
+	}
+
+	/// Writes the item to the given file, replacing it if it exists.
+	pub fn write(&self, path: &Path) -> io::Result<()> {
+		fs::write(path, (EXPORT_VERSION, self).encode())
+	}
+}
+
+/// List all candidates in the store, ordered by their hash.
+pub fn list_candidates(
+	db: &Arc<dyn Database>,
+	config: &Config,
+) -> Result<Vec<StoredCandidate>, Error> {
+	let prune_at = load_pruning_times(db, config)?;
+
+	let mut candidates = Vec::new();
+	for r in db.iter_with_prefix(config.col_meta, META_PREFIX) {
+		let (k, v) = r?;
+		let candidate_hash = CandidateHash::decode(&mut &k[META_PREFIX.len()..])?;
+		let meta = CandidateMeta::decode(&mut &v[..])?;
+
+		candidates.push(stored_candidate(candidate_hash, meta, &prune_at));
+	}
+
+	Ok(candidates)
+}
+
+/// Load a single candidate from the store. Returns `None` if it is not in the store.
+pub fn load_candidate(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	candidate_hash: CandidateHash,
+) -> Result<Option<StoredCandidate>, Error> {
+	let meta = match load_meta(db, config, &candidate_hash)? {
+		Some(meta) => meta,
+		None => return Ok(None),
+	};
+
+	let prune_at = load_pruning_times(db, config)?;
+	Ok(Some(stored_candidate(candidate_hash, meta, &prune_at)))
+}
+
+// the pruning time of all candidates which are scheduled for pruning.
+fn load_pruning_times(
+	db: &Arc<dyn Database>,
+	config: &Config,
+) -> Result<HashMap<CandidateHash, Duration>, Error> {
+	let mut prune_at = HashMap::new();
+	for r in db.iter_with_prefix(config.col_meta, PRUNE_BY_TIME_PREFIX) {
+		let (k, _v) = r?;
+		let (at, candidate_hash) = decode_pruning_key(&k[..])?;
+		prune_at.insert(candidate_hash, at);
+	}
+
+	Ok(prune_at)
+}
+
+fn stored_candidate(
+	candidate_hash: CandidateHash,
+	meta: CandidateMeta,
+	prune_at: &HashMap<CandidateHash, Duration>,
+) -> StoredCandidate {
+	let state = match meta.state {
+		State::Unavailable(first_seen) =>
+			PruningState::Unavailable { first_seen: first_seen.into() },
+		State::Unfinalized(first_seen, blocks) => PruningState::Unfinalized {
+			first_seen: first_seen.into(),
+			blocks: blocks.into_iter().map(|(number, hash)| (number.0, hash)).collect(),
+		},
+		State::Finalized(finalized_at) =>
+			PruningState::Finalized { finalized_at: finalized_at.into() },
+	};
+
+	StoredCandidate {
+		candidate_hash,
+		state,
+		prune_at: prune_at.get(&candidate_hash).copied(),
+		data_available: meta.data_available,
+		n_validators: meta.chunks_stored.len(),
+		chunks_stored: meta
+			.chunks_stored
+			.iter_ones()
+			.map(|index| ValidatorIndex(index as u32))
+			.collect(),
+	}
+}
+
+/// Export the available data of a candidate. Returns `None` if it is not stored.
+pub fn export_available_data(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	candidate_hash: CandidateHash,
+) -> Result<Option<ExportedItem>, Error> {
+	let meta = match load_meta(db, config, &candidate_hash)? {
+		Some(meta) if meta.data_available => meta,
+		_ => return Ok(None),
+	};
+
+	Ok(load_available_data(db, config, &candidate_hash)?.map(|available_data| {
+		ExportedItem::AvailableData {
+			candidate_hash,
+			n_validators: meta.chunks_stored.len() as u32,
+			available_data,
+		}
+	}))
+}
+
+/// Export a chunk of a candidate, along with its merkle proof. Returns `None` if it is not stored.
+pub fn export_chunk(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	candidate_hash: CandidateHash,
+	chunk_index: ValidatorIndex,
+) -> Result<Option<ExportedItem>, Error> {
+	let meta = match load_meta(db, config, &candidate_hash)? {
+		Some(meta) => meta,
+		None => return Ok(None),
+	};
+
+	if !meta.chunks_stored.get(chunk_index.0 as usize).map_or(false, |b| *b) {
+		return Ok(None)
+	}
+
+	Ok(load_chunk(db, config, &candidate_hash, chunk_index)?.map(|chunk| ExportedItem::Chunk {
+		candidate_hash,
+		n_validators: meta.chunks_stored.len() as u32,
+		chunk,
+	}))
+}
+
+/// Import an exported item of the candidate with the given erasure root into the store.
+///
+/// The item is checked against the erasure root before anything is written: available data must
+/// encode to it and a chunk's merkle proof must lead to it, otherwise `Error::InvalidErasureRoot`
+/// is returned. Candidates which are not known to the store yet are added as unavailable, i.e. they are pruned
+/// an hour after the import unless the node sees them included in a block in the meantime. The
+/// chunks of imported available data are derived from it, exactly as if it was stored by the
+/// subsystem.
+///
+/// Returns `false` if the item does not fit the candidate already in the store, i.e. if the
+/// chunk index is out of bounds of the number of validators.
+pub fn import(
+	db: &Arc<dyn Database>,
+	config: &Config,
+	item: ExportedItem,
+	erasure_root: Hash,
+) -> Result<bool, Error> {
+	// The subsystem rebuilds its running totals from the usage records when it starts.
+	let usage_totals = UsageTotals::default();
+
+	match item {
+		ExportedItem::AvailableData { candidate_hash, n_validators, available_data } => {
+			store_available_data(
+				db,
+				config,
+				&usage_totals,
+				KEEP_UNAVAILABLE_FOR,
+				&SystemClock,
+				candidate_hash,
+				n_validators as usize,
+				available_data,
+				Some(erasure_root),
+			)?;
+
+			Ok(true)
+		},
+		ExportedItem::Chunk { candidate_hash, n_validators, chunk } => {
+			let anticipated_hash =
+				erasure::branch_hash(&erasure_root, chunk.proof(), chunk.index.0 as usize)
+					.map_err(|_| Error::InvalidErasureRoot)?;
+			if anticipated_hash != BlakeTwo256::hash(&chunk.chunk) {
+				return Err(Error::InvalidErasureRoot)
+			}
+
+			let mut tx = DBTransaction::new();
+
+			let mut meta = match load_meta(db, config, &candidate_hash)? {
+				Some(meta) => meta,
+				None => {
+					let now = SystemClock.now()?;
+					write_pruning_key(&mut tx, config, now + KEEP_UNAVAILABLE_FOR, &candidate_hash);
+
+					CandidateMeta {
+						state: State::Unavailable(now.into()),
+						data_available: false,
+						chunks_stored: bitvec::bitvec![u8, BitOrderLsb0; 0; n_validators as usize],
+					}
+				},
+			};
+
+			match meta.chunks_stored.get(chunk.index.0 as usize).map(|b| *b) {
+				Some(true) => return Ok(true), // already stored.
+				Some(false) => {
+					meta.chunks_stored.set(chunk.index.0 as usize, true);
+
+					write_chunk(&mut tx, config, &candidate_hash, chunk.index, &chunk);
+					write_meta(&mut tx, config, &candidate_hash, &meta);
+
+					let chunk_size = chunk.encoded_size() as u64;
+					update_usage(db, &mut tx, config, &usage_totals, &candidate_hash, |usage| {
+						usage.stored_bytes += chunk_size
+					})?;
+				},
+				None => return Ok(false), // out of bounds.
+			}
+
+			db.write(tx)?;
+			Ok(true)
+		},
+	}
+}
@@ -95,6 +95,8 @@ impl Default for TestState {
 			keep_unavailable_for: Duration::from_secs(1),
 			keep_finalized_for: Duration::from_secs(2),
 			pruning_interval: Duration::from_millis(250),
+			max_total_size: None,
+			archive_paras: HashSet::new(),
 		};
 
 		let clock = TestClock { inner: Arc::new(Mutex::new(Duration::from_secs(0))) };
@@ -792,6 +794,199 @@ fn stored_data_kept_until_finalized() {
 	});
 }
 
+#[test]
+fn archived_para_data_is_not_pruned_after_finality() {
+	let store = test_store();
+	let mut test_state = TestState::default();
+	let para_id = ParaId::from(5);
+	test_state.pruning_config.archive_paras.insert(para_id);
+
+	test_harness(test_state.clone(), store.clone(), |mut virtual_overseer| async move {
+		let n_validators = 10;
+
+		let pov = PoV { block_data: BlockData(vec![4, 5, 6]) };
+		let pov_hash = pov.hash();
+
+		let candidate = TestCandidateBuilder { para_id, pov_hash, ..Default::default() }.build();
+		let candidate_hash = candidate.hash();
+
+		let available_data = AvailableData {
+			pov: Arc::new(pov),
+			validation_data: test_state.persisted_validation_data.clone(),
+		};
+
+		store_data(&mut virtual_overseer, candidate_hash, n_validators, &available_data).await;
+
+		let parent = Hash::repeat_byte(2);
+		let block_number = 10;
+
+		let new_leaf = import_leaf(
+			&mut virtual_overseer,
+			parent,
+			block_number,
+			vec![candidate_included(candidate)],
+			(0..n_validators).map(|_| Sr25519Keyring::Alice.public().into()).collect(),
+		)
+		.await;
+
+		overseer_signal(
+			&mut virtual_overseer,
+			OverseerSignal::BlockFinalized(new_leaf, block_number),
+		)
+		.await;
+
+		// Wait until finalized data of other paras would definitely be pruned.
+		test_state.clock.inc(test_state.pruning_config.keep_finalized_for * 10);
+		test_state.wait_for_pruning().await;
+
+		// The data of the archived para is kept.
+		assert_eq!(
+			query_available_data(&mut virtual_overseer, candidate_hash).await.unwrap(),
+			available_data,
+		);
+
+		assert!(has_all_chunks(&mut virtual_overseer, candidate_hash, n_validators, true).await);
+		virtual_overseer
+	});
+}
+
+#[test]
+fn least_recently_finalized_data_is_evicted_over_size_limit() {
+	let store = test_store();
+	let mut test_state = TestState::default();
+	let n_validators = 10;
+
+	let available_data = |byte| AvailableData {
+		pov: Arc::new(PoV { block_data: BlockData(vec![byte; 64]) }),
+		validation_data: test_state.persisted_validation_data.clone(),
+	};
+	let (available_data_a, available_data_b) = (available_data(1), available_data(2));
+
+	// Room for only one of the two candidates.
+	test_state.pruning_config.max_total_size =
+		Some(stored_bytes(n_validators as _, &available_data_a) * 3 / 2);
+	test_state.pruning_config.keep_finalized_for = Duration::from_secs(100);
+
+	test_harness(test_state.clone(), store.clone(), |mut virtual_overseer| async move {
+		let validators: Vec<ValidatorId> =
+			(0..n_validators).map(|_| Sr25519Keyring::Alice.public().into()).collect();
+
+		let candidate_a =
+			TestCandidateBuilder { pov_hash: available_data_a.pov.hash(), ..Default::default() }
+				.build();
+		let candidate_b =
+			TestCandidateBuilder { pov_hash: available_data_b.pov.hash(), ..Default::default() }
+				.build();
+
+		store_data(&mut virtual_overseer, candidate_a.hash(), n_validators, &available_data_a)
+			.await;
+		store_data(&mut virtual_overseer, candidate_b.hash(), n_validators, &available_data_b)
+			.await;
+
+		let (hash_a, hash_b) = (candidate_a.hash(), candidate_b.hash());
+
+		let leaf_a = import_leaf(
+			&mut virtual_overseer,
+			Hash::repeat_byte(2),
+			10,
+			vec![candidate_included(candidate_a)],
+			validators.clone(),
+		)
+		.await;
+		overseer_signal(&mut virtual_overseer, OverseerSignal::BlockFinalized(leaf_a, 10)).await;
+
+		test_state.clock.inc(Duration::from_secs(1));
+
+		let leaf_b = import_leaf(
+			&mut virtual_overseer,
+			leaf_a,
+			11,
+			vec![candidate_included(candidate_b)],
+			validators,
+		)
+		.await;
+		overseer_signal(&mut virtual_overseer, OverseerSignal::BlockFinalized(leaf_b, 11)).await;
+
+		test_state.wait_for_pruning().await;
+
+		// The candidate finalized first is evicted, the other one is kept.
+		assert!(query_available_data(&mut virtual_overseer, hash_a).await.is_none());
+		assert!(has_all_chunks(&mut virtual_overseer, hash_a, n_validators, false).await);
+
+		assert_eq!(
+			query_available_data(&mut virtual_overseer, hash_b).await.unwrap(),
+			available_data_b,
+		);
+		assert!(has_all_chunks(&mut virtual_overseer, hash_b, n_validators, true).await);
+		virtual_overseer
+	});
+}
+
+#[test]
+fn usage_totals_follow_the_usage_records() {
+	let store = test_store();
+	let test_state = TestState::default();
+	let usage_totals = UsageTotals::default();
+	let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+	let para_id = ParaId::from(5);
+	let n_validators = 10;
+
+	let available_data = AvailableData {
+		pov: Arc::new(PoV { block_data: BlockData(vec![1; 64]) }),
+		validation_data: test_state.persisted_validation_data.clone(),
+	};
+	let expected_bytes = stored_bytes(n_validators, &available_data);
+
+	store_available_data(
+		&store,
+		&TEST_CONFIG,
+		&usage_totals,
+		test_state.pruning_config.keep_unavailable_for,
+		&test_state.clock,
+		candidate_hash,
+		n_validators,
+		available_data,
+		None,
+	)
+	.unwrap();
+	assert_eq!(usage_totals.total(), expected_bytes);
+	assert_eq!(usage_totals.per_para().get(&None), Some(&expected_bytes));
+
+	// A transaction changing the same record several times is accounted for once, and only after
+	// it has been written.
+	let mut tx = DBTransaction::new();
+	let mut usage_changes = UsageChanges::default();
+	update_usage(&store, &mut tx, &TEST_CONFIG, &mut usage_changes, &candidate_hash, |usage| {
+		usage.para_id = Some(para_id)
+	})
+	.unwrap();
+	update_usage(&store, &mut tx, &TEST_CONFIG, &mut usage_changes, &candidate_hash, |usage| {
+		usage.stored_bytes += 1
+	})
+	.unwrap();
+	assert_eq!(usage_totals.total_with(&usage_changes), expected_bytes + 1);
+	assert_eq!(usage_totals.per_para().get(&None), Some(&expected_bytes));
+	store.write(tx).unwrap();
+	usage_totals.apply(usage_changes);
+	assert_eq!(usage_totals.total(), expected_bytes + 1);
+	assert_eq!(usage_totals.per_para().get(&None), Some(&0));
+	assert_eq!(usage_totals.per_para().get(&Some(para_id)), Some(&(expected_bytes + 1)));
+	let expected_bytes = expected_bytes + 1;
+
+	// Rebuilding the totals from the records gives the same result.
+	let loaded_totals = UsageTotals::default();
+	loaded_totals.load(&store, &TEST_CONFIG).unwrap();
+	assert_eq!(loaded_totals.total(), expected_bytes);
+	assert_eq!(loaded_totals.per_para().get(&Some(para_id)), Some(&expected_bytes));
+
+	let mut tx = DBTransaction::new();
+	let mut usage_changes = UsageChanges::default();
+	delete_candidate(&store, &mut tx, &TEST_CONFIG, &mut usage_changes, &candidate_hash).unwrap();
+	store.write(tx).unwrap();
+	usage_totals.apply(usage_changes);
+	assert_eq!(usage_totals.total(), 0);
+}
+
 #[test]
 fn we_dont_miss_anything_if_import_notifications_are_missed() {
 	let store = test_store();
@@ -1111,6 +1306,46 @@ fn forkfullness_works() {
 	});
 }
 
+async fn store_data(
+	virtual_overseer: &mut VirtualOverseer,
+	candidate_hash: CandidateHash,
+	n_validators: u32,
+	available_data: &AvailableData,
+) {
+	let chunks = erasure::obtain_chunks_v1(n_validators as _, available_data).unwrap();
+	let branches = erasure::branches(chunks.as_ref());
+
+	let (tx, rx) = oneshot::channel();
+	let block_msg = AvailabilityStoreMessage::StoreAvailableData {
+		candidate_hash,
+		n_validators,
+		available_data: available_data.clone(),
+		tx,
+		expected_erasure_root: branches.root(),
+	};
+
+	virtual_overseer.send(FromOrchestra::Communication { msg: block_msg }).await;
+	rx.await.unwrap().unwrap();
+}
+
+// The number of bytes the store accounts for the data and its chunks.
+fn stored_bytes(n_validators: usize, available_data: &AvailableData) -> u64 {
+	let chunks = erasure::obtain_chunks_v1(n_validators, available_data).unwrap();
+	let branches = erasure::branches(chunks.as_ref());
+
+	let chunks_size: usize = chunks
+		.iter()
+		.zip(branches.map(|(proof, _)| proof))
+		.enumerate()
+		.map(|(index, (chunk, proof))| {
+			ErasureChunk { chunk: chunk.clone(), proof, index: ValidatorIndex(index as u32) }
+				.encoded_size()
+		})
+		.sum();
+
+	(available_data.encoded_size() + chunks_size) as u64
+}
+
 async fn query_available_data(
 	virtual_overseer: &mut VirtualOverseer,
 	candidate_hash: CandidateHash,
@@ -1262,3 +1497,132 @@ fn query_chunk_size_works() {
 		virtual_overseer
 	});
 }
+
+#[test]
+fn offline_export_and_import_work() {
+	let store = test_store();
+	let test_state = TestState::default();
+	let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+	let n_validators = 10;
+
+	let available_data = AvailableData {
+		pov: Arc::new(PoV { block_data: BlockData(vec![4, 5, 6]) }),
+		validation_data: test_state.persisted_validation_data.clone(),
+	};
+	let chunks = erasure::obtain_chunks_v1(n_validators, &available_data).unwrap();
+	let branches = erasure::branches(chunks.as_ref());
+	let expected_erasure_root = branches.root();
+
+	test_harness(test_state.clone(), store.clone(), |mut virtual_overseer| async move {
+		let (tx, rx) = oneshot::channel();
+		let block_msg = AvailabilityStoreMessage::StoreAvailableData {
+			candidate_hash,
+			n_validators: n_validators as u32,
+			available_data: available_data.clone(),
+			tx,
+			expected_erasure_root,
+		};
+
+		virtual_overseer.send(FromOrchestra::Communication { msg: block_msg }).await;
+		assert_eq!(rx.await.unwrap(), Ok(()));
+		virtual_overseer
+	});
+
+	let stored_candidate = offline::StoredCandidate {
+		candidate_hash,
+		state: offline::PruningState::Unavailable { first_seen: Duration::ZERO },
+		prune_at: Some(test_state.pruning_config.keep_unavailable_for),
+		data_available: true,
+		n_validators,
+		chunks_stored: (0..n_validators as u32).map(ValidatorIndex).collect(),
+	};
+	assert_eq!(
+		offline::list_candidates(&store, &TEST_CONFIG).unwrap(),
+		vec![stored_candidate.clone()]
+	);
+	assert_eq!(
+		offline::load_candidate(&store, &TEST_CONFIG, candidate_hash).unwrap(),
+		Some(stored_candidate)
+	);
+
+	let exported_data = offline::export_available_data(&store, &TEST_CONFIG, candidate_hash)
+		.unwrap()
+		.unwrap();
+	let exported_chunk =
+		offline::export_chunk(&store, &TEST_CONFIG, candidate_hash, ValidatorIndex(3))
+			.unwrap()
+			.unwrap();
+	assert_matches!(
+		offline::export_chunk(&store, &TEST_CONFIG, candidate_hash, ValidatorIndex(10)),
+		Ok(None)
+	);
+	assert_matches!(
+		offline::export_available_data(&store, &TEST_CONFIG, CandidateHash(Hash::repeat_byte(2))),
+		Ok(None)
+	);
+
+	// Nothing is imported if it does not match the erasure root.
+	let chunk_store = test_store();
+	let wrong_erasure_root = Hash::repeat_byte(3);
+	assert_matches!(
+		offline::import(&chunk_store, &TEST_CONFIG, exported_chunk.clone(), wrong_erasure_root),
+		Err(Error::InvalidErasureRoot)
+	);
+	assert_matches!(
+		offline::import(&chunk_store, &TEST_CONFIG, exported_data.clone(), wrong_erasure_root),
+		Err(Error::InvalidErasureRoot)
+	);
+	let mut tampered_chunk = exported_chunk.clone();
+	if let offline::ExportedItem::Chunk { chunk, .. } = &mut tampered_chunk {
+		chunk.chunk[0] ^= 1;
+	}
+	assert_matches!(
+		offline::import(&chunk_store, &TEST_CONFIG, tampered_chunk, expected_erasure_root),
+		Err(Error::InvalidErasureRoot)
+	);
+	assert!(offline::list_candidates(&chunk_store, &TEST_CONFIG).unwrap().is_empty());
+
+	// A chunk on its own only adds that chunk.
+	assert!(offline::import(
+		&chunk_store,
+		&TEST_CONFIG,
+		exported_chunk.clone(),
+		expected_erasure_root
+	)
+	.unwrap());
+	let candidates = offline::list_candidates(&chunk_store, &TEST_CONFIG).unwrap();
+	assert_eq!(candidates.len(), 1);
+	assert!(!candidates[0].data_available);
+	assert_eq!(candidates[0].n_validators, n_validators);
+	assert_eq!(candidates[0].chunks_stored, vec![ValidatorIndex(3)]);
+	assert_eq!(
+		offline::export_chunk(&chunk_store, &TEST_CONFIG, candidate_hash, ValidatorIndex(3))
+			.unwrap(),
+		Some(exported_chunk),
+	);
+
+	// The available data restores all the chunks.
+	assert!(offline::import(
+		&chunk_store,
+		&TEST_CONFIG,
+		exported_data.clone(),
+		expected_erasure_root
+	)
+	.unwrap());
+	let candidates = offline::list_candidates(&chunk_store, &TEST_CONFIG).unwrap();
+	assert!(candidates[0].data_available);
+	assert_eq!(candidates[0].chunks_stored.len(), n_validators);
+	assert_eq!(
+		offline::export_available_data(&chunk_store, &TEST_CONFIG, candidate_hash).unwrap(),
+		Some(exported_data),
+	);
+	for (index, (proof, chunk)) in branches.enumerate() {
+		let expected =
+			ErasureChunk { chunk: chunk.to_vec(), index: ValidatorIndex(index as u32), proof };
+		assert_eq!(
+			load_chunk(&chunk_store, &TEST_CONFIG, &candidate_hash, ValidatorIndex(index as u32))
+				.unwrap(),
+			Some(expected),
+		);
+	}
+}
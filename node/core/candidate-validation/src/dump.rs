@@ -0,0 +1,324 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dumps of candidate validations and their offline replay.
+//!
+//! If [`Config::validation_dump_dir`](crate::Config::validation_dump_dir) is set, every validation
+//! that reaches the execution of the PVF and does not conclude that the candidate is valid is
+//! written to that directory as a [`ValidationDump`]. A dump can then be replayed with [`replay`],
+//! which goes through the same prepare and execute workers as the node.
+//!
+//! Dumps are written by a separate blocking task, and only the [`MAX_RETAINED_DUMPS`] most recent
+//! ones are kept.
+
+use crate::{handle_execution_result, pvf_exec_timeout, pvf_prep_timeout, LOG_TARGET};
+use futures::{
+	channel::{mpsc, oneshot},
+	Future, FutureExt as _, StreamExt as _,
+};
+use parity_scale_codec::{Decode, Encode};
+use polkadot_node_core_pvf::{
+	Metrics as PvfMetrics, PrepareJobKind, PrepareStats, Priority, PvfPrepData, ValidationError,
+};
+use polkadot_node_metrics::metrics::{prometheus, Metrics as _};
+use polkadot_node_primitives::{BlockData, PoV, POV_BOMB_LIMIT, VALIDATION_CODE_BOMB_LIMIT};
+use polkadot_parachain::primitives::ValidationParams;
+use polkadot_primitives::{
+	CandidateReceipt, ExecutorParams, PersistedValidationData, PvfExecTimeoutKind,
+	PvfPrepTimeoutKind, ValidationCode, ValidationCodeHash,
+};
+use std::{
+	fmt, fs, io,
+	path::{Path, PathBuf},
+	time::{Duration, Instant},
+};
+
+/// The version of the dump format. Dumps of other versions are rejected.
+const DUMP_VERSION: u32 = 1;
+
+/// The maximum number of dumps kept in the dump directory. Once exceeded, the oldest dumps are
+/// removed.
+pub const MAX_RETAINED_DUMPS: usize = 32;
+
+/// The maximum number of dumps waiting to be written. Further dumps are dropped until the writer
+/// has caught up.
+const MAX_PENDING_DUMPS: usize = 4;
+
+/// Everything that went into the validation of a candidate, and its result.
+#[derive(Clone, Debug, Encode, Decode)]
+pub struct ValidationDump {
+	/// The receipt of the validated candidate.
+	pub candidate_receipt: CandidateReceipt,
+	/// The persisted validation data the candidate was validated against.
+	pub persisted_validation_data: PersistedValidationData,
+	/// The proof of validity, as received.
+	pub pov: PoV,
+	/// The hash of the validation code.
+	pub validation_code_hash: ValidationCodeHash,
+	/// The validation code, as found on chain.
+	pub validation_code: ValidationCode,
+	/// The executor parameters of the session.
+	pub executor_params: ExecutorParams,
+	/// Whether the validation was done for backing or for approval.
+	pub exec_timeout_kind: PvfExecTimeoutKind,
+	/// The result of the validation, as reported by the node.
+	pub result: String,
+}
+
+impl ValidationDump {
+	/// Reads a dump from the given file.
+	pub fn read(path: &Path) -> io::Result<Self> {
+		let bytes = fs::read(path)?;
+		let (version, dump) = <(u32, Self)>::decode(&mut &bytes[..])
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+		if version != DUMP_VERSION {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("unsupported dump version {}, expected {}", version, DUMP_VERSION),
+			))
+		}
+		Ok(dump)
+	}
+
+	/// Writes the dump into the given directory and returns the path of the written file. A dump
+	/// of the same candidate and kind of validation is overwritten.
+	pub(crate) fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+		let kind = match self.exec_timeout_kind {
+			PvfExecTimeoutKind::Backing => "backing",
+			PvfExecTimeoutKind::Approval => "approval",
+		};
+		let path = dir.join(format!("{:?}-{}.dump", self.candidate_receipt.hash(), kind));
+		fs::create_dir_all(dir)?;
+		fs::write(&path, (DUMP_VERSION, self).encode())?;
+		Ok(path)
+	}
+}
+
+/// A handle for submitting dumps to the task created by [`writer`].
+#[derive(Clone)]
+pub(crate) struct DumpSender(mpsc::Sender<ValidationDump>);
+
+impl DumpSender {
+	/// Queues the dump for writing. The dump is dropped if too many dumps are pending already.
+	pub(crate) fn send(&mut self, dump: ValidationDump) {
+		let candidate_hash = dump.candidate_receipt.hash();
+		if let Err(err) = self.0.try_send(dump) {
+			gum::warn!(
+				target: LOG_TARGET,
+				?candidate_hash,
+				queue_full = err.is_full(),
+				"Dropping validation dump",
+			);
+		}
+	}
+}
+
+/// Creates the task writing the submitted dumps into `dir`, keeping at most
+/// [`MAX_RETAINED_DUMPS`] of them. The task does blocking I/O and must be spawned accordingly.
+pub(crate) fn writer(dir: PathBuf) -> (DumpSender, impl Future<Output = ()>) {
+	let (tx, mut rx) = mpsc::channel::<ValidationDump>(MAX_PENDING_DUMPS);
+	let task = async move {
+		while let Some(dump) = rx.next().await {
+			let para_id = dump.candidate_receipt.descriptor.para_id;
+			match dump.write(&dir) {
+				Ok(path) => gum::info!(target: LOG_TARGET, ?para_id, ?path, "Dumped validation"),
+				Err(err) =>
+					gum::warn!(target: LOG_TARGET, ?para_id, ?err, "Failed to dump validation"),
+			}
+			if let Err(err) = prune(&dir, MAX_RETAINED_DUMPS) {
+				gum::warn!(target: LOG_TARGET, ?err, "Failed to prune validation dumps");
+			}
+		}
+	};
+	(DumpSender(tx), task)
+}
+
+/// Removes the oldest dumps in `dir` until at most `max` of them remain.
+pub(crate) fn prune(dir: &Path, max: usize) -> io::Result<()> {
+	let mut dumps = Vec::new();
+	for entry in fs::read_dir(dir)? {
+		let entry = entry?;
+		let path = entry.path();
+		if path.extension().map_or(false, |extension| extension == "dump") {
+			dumps.push((entry.metadata()?.modified()?, path));
+		}
+	}
+	if dumps.len() <= max {
+		return Ok(())
+	}
+
+	dumps.sort();
+	let excess = dumps.len() - max;
+	for (_, path) in dumps.into_iter().take(excess) {
+		fs::remove_file(path)?;
+	}
+	Ok(())
+}
+
+/// The outcome of replaying a [`ValidationDump`].
+#[derive(Debug)]
+pub struct ReplayReport {
+	/// The result of the validation, as the node would have reported it.
+	pub result: String,
+	/// The result of the validation, as reported by the node when the dump was taken.
+	pub dumped_result: String,
+	/// The wall clock time spent preparing the artifact.
+	pub preparation_time: Duration,
+	/// The statistics reported by the prepare worker.
+	pub prepare_stats: PrepareStats,
+	/// The wall clock time spent executing the PVF.
+	pub execution_time: Duration,
+	/// The `ru_maxrss` reported by the execute worker, in kilobytes, if available.
+	pub execution_max_rss: Option<u64>,
+}
+
+impl fmt::Display for ReplayReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "result:             {}", self.result)?;
+		writeln!(f, "dumped result:      {}", self.dumped_result)?;
+		writeln!(f, "preparation time:   {:?}", self.preparation_time)?;
+		writeln!(f, "preparation cpu:    {:?}", self.prepare_stats.cpu_time_elapsed)?;
+		writeln!(f, "preparation memory: {:?}", self.prepare_stats.memory_stats)?;
+		writeln!(f, "execution time:     {:?}", self.execution_time)?;
+		match self.execution_max_rss {
+			Some(max_rss) => write!(f, "execution max rss:  {} KiB", max_rss),
+			None => write!(f, "execution max rss:  unavailable"),
+		}
+	}
+}
+
+/// Replays the given dump through a fresh validation host, which spawns the prepare and execute
+/// workers from `program_path`.
+///
+/// The artifacts are kept in `cache_path` if given. Otherwise a new temporary directory is used
+/// and removed afterwards, so that the PVF is always prepared from scratch: an artifact left from
+/// an earlier replay would be reused and the preparation stats would be meaningless.
+///
+/// The PVF is executed once, without the retries the node would do. Must be called within a tokio
+/// runtime.
+pub async fn replay(
+	dump: ValidationDump,
+	cache_path: Option<PathBuf>,
+	program_path: PathBuf,
+) -> Result<ReplayReport, String> {
+	let ValidationDump {
+		candidate_receipt,
+		persisted_validation_data,
+		pov,
+		validation_code_hash,
+		validation_code,
+		executor_params,
+		exec_timeout_kind,
+		result: dumped_result,
+	} = dump;
+
+	if validation_code.hash() != validation_code_hash {
+		return Err("the dumped validation code does not match its hash".into())
+	}
+	let raw_validation_code =
+		sp_maybe_compressed_blob::decompress(&validation_code.0, VALIDATION_CODE_BOMB_LIMIT)
+			.map_err(|err| format!("cannot decompress the validation code: {:?}", err))?;
+	let raw_block_data = sp_maybe_compressed_blob::decompress(&pov.block_data.0, POV_BOMB_LIMIT)
+		.map_err(|err| format!("cannot decompress the PoV: {:?}", err))?;
+	let params = ValidationParams {
+		parent_head: persisted_validation_data.parent_head.clone(),
+		block_data: BlockData(raw_block_data.to_vec()),
+		relay_parent_number: persisted_validation_data.relay_parent_number,
+		relay_parent_storage_root: persisted_validation_data.relay_parent_storage_root,
+	};
+
+	let exec_timeout = pvf_exec_timeout(&executor_params, exec_timeout_kind);
+	let pvf = PvfPrepData::from_code(
+		raw_validation_code.to_vec(),
+		executor_params.clone(),
+		pvf_prep_timeout(&executor_params, PvfPrepTimeoutKind::Lenient),
+		PrepareJobKind::Compilation,
+	);
+
+	let temp_cache_dir;
+	let cache_path = match cache_path {
+		Some(cache_path) => cache_path,
+		None => {
+			temp_cache_dir = tempfile::Builder::new()
+				.prefix("polkadot-replay-validation-")
+				.tempdir()
+				.map_err(|err| format!("cannot create the cache directory: {}", err))?;
+			temp_cache_dir.path().to_owned()
+		},
+	};
+
+	// The memory stats of the execution are only reported through the metrics of the host.
+	let registry = prometheus::Registry::new();
+	let metrics = PvfMetrics::try_register(&registry)
+		.map_err(|err| format!("cannot register the validation host metrics: {:?}", err))?;
+	let (mut validation_host, task) = polkadot_node_core_pvf::start(
+		polkadot_node_core_pvf::Config::new(cache_path, program_path),
+		metrics,
+	);
+
+	let run = async move {
+		let preparation_start = Instant::now();
+		let (tx, rx) = oneshot::channel();
+		validation_host.precheck_pvf(pvf.clone(), tx).await?;
+		let prepare_stats = rx
+			.await
+			.map_err(|_| "preparation was cancelled".to_string())?
+			.map_err(|err| format!("preparation failed: {:?}", err))?;
+		let preparation_time = preparation_start.elapsed();
+
+		let execution_start = Instant::now();
+		let (tx, rx) = oneshot::channel();
+		validation_host
+			.execute_pvf(pvf, exec_timeout, params.encode(), Priority::Critical, tx)
+			.await?;
+		let execution_result: Result<_, ValidationError> =
+			rx.await.map_err(|_| "execution was cancelled".to_string())?;
+		let execution_time = execution_start.elapsed();
+		let execution_max_rss = execution_max_rss(&registry);
+
+		let result = handle_execution_result(
+			execution_result,
+			&candidate_receipt,
+			persisted_validation_data,
+		);
+
+		Ok::<_, String>(ReplayReport {
+			result: format!("{:?}", result),
+			dumped_result,
+			preparation_time,
+			prepare_stats,
+			execution_time,
+			execution_max_rss,
+		})
+	};
+
+	futures::select! {
+		report = run.fuse() => report,
+		_ = task.fuse() => Err("the validation host stopped unexpectedly".into()),
+	}
+}
+
+/// The `ru_maxrss` observed by the metrics of a validation host that executed a single PVF.
+fn execution_max_rss(registry: &prometheus::Registry) -> Option<u64> {
+	registry
+		.gather()
+		.iter()
+		.find(|family| family.get_name() == "polkadot_pvf_execution_max_rss")
+		.and_then(|family| family.get_metric().first())
+		.map(|metric| metric.get_histogram())
+		.filter(|histogram| histogram.get_sample_count() == 1)
+		.map(|histogram| histogram.get_sample_sum() as u64)
+}
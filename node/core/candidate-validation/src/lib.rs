@@ -23,6 +23,7 @@
 #![deny(unused_crate_dependencies, unused_results)]
 #![warn(missing_docs)]
 
+pub use polkadot_node_core_pvf::SandboxConfig as PvfSandboxConfig;
 use polkadot_node_core_pvf::{
 	InternalValidationError, InvalidCandidate as WasmInvalidCandidate, PrepareError,
 	PrepareJobKind, PrepareStats, PvfPrepData, ValidationError, ValidationHost,
@@ -59,8 +60,12 @@ use std::{
 
 use async_trait::async_trait;
 
+pub mod dump;
 mod metrics;
-use self::metrics::Metrics;
+use self::{
+	dump::{DumpSender, ValidationDump},
+	metrics::Metrics,
+};
 
 #[cfg(test)]
 mod tests;
@@ -96,6 +101,28 @@ pub struct Config {
 	/// The path to the executable which can be used for spawning PVF compilation & validation
 	/// workers.
 	pub program_path: PathBuf,
+	/// The directory to write a [`dump::ValidationDump`] to for every candidate whose PVF was
+	/// executed without concluding that the candidate is valid. No dumps are written if `None`.
+	pub validation_dump_dir: Option<PathBuf>,
+	/// Whether the PVF workers restrict the threads running untrusted code with the seccomp
+	/// syscall allowlist.
+	pub enable_seccomp: bool,
+	/// If set, the PVF workers are spawned in their own namespaces and cgroups, with hard memory
+	/// and CPU limits.
+	pub sandbox: Option<PvfSandboxConfig>,
+}
+
+impl Config {
+	/// The configuration of the PVF validation host the subsystem starts.
+	fn pvf_host_config(&self) -> polkadot_node_core_pvf::Config {
+		let mut pvf_config = polkadot_node_core_pvf::Config::new(
+			self.artifacts_cache_path.clone(),
+			self.program_path.clone(),
+		);
+		pvf_config.enable_seccomp = self.enable_seccomp;
+		pvf_config.sandbox = self.sandbox.clone();
+		pvf_config
+	}
 }
 
 /// The candidate validation subsystem.
@@ -128,8 +155,8 @@ impl<Context> CandidateValidationSubsystem {
 			ctx,
 			self.metrics,
 			self.pvf_metrics,
-			self.config.artifacts_cache_path,
-			self.config.program_path,
+			self.config.pvf_host_config(),
+			self.config.validation_dump_dir,
 		)
 		.map_err(|e| SubsystemError::with_origin("candidate-validation", e))
 		.boxed();
@@ -142,15 +169,21 @@ async fn run<Context>(
 	mut ctx: Context,
 	metrics: Metrics,
 	pvf_metrics: polkadot_node_core_pvf::Metrics,
-	cache_path: PathBuf,
-	program_path: PathBuf,
+	pvf_config: polkadot_node_core_pvf::Config,
+	validation_dump_dir: Option<PathBuf>,
 ) -> SubsystemResult<()> {
-	let (validation_host, task) = polkadot_node_core_pvf::start(
-		polkadot_node_core_pvf::Config::new(cache_path, program_path),
-		pvf_metrics,
-	);
+	let (validation_host, task) = polkadot_node_core_pvf::start(pvf_config, pvf_metrics);
 	ctx.spawn_blocking("pvf-validation-host", task.boxed())?;
 
+	let dump_sender = match validation_dump_dir {
+		Some(dir) => {
+			let (dump_sender, writer) = dump::writer(dir);
+			ctx.spawn_blocking("validation-dump-writer", writer.boxed())?;
+			Some(dump_sender)
+		},
+		None => None,
+	};
+
 	loop {
 		match ctx.recv().await? {
 			FromOrchestra::Signal(OverseerSignal::ActiveLeaves(_)) => {},
@@ -167,6 +200,7 @@ async fn run<Context>(
 						let mut sender = ctx.sender().clone();
 						let metrics = metrics.clone();
 						let validation_host = validation_host.clone();
+						let dump_sender = dump_sender.clone();
 
 						async move {
 							let _timer = metrics.time_validate_from_chain_state();
@@ -176,6 +210,7 @@ async fn run<Context>(
 								candidate_receipt,
 								pov,
 								timeout,
+								dump_sender,
 								&metrics,
 							)
 							.await;
@@ -199,6 +234,7 @@ async fn run<Context>(
 						let mut sender = ctx.sender().clone();
 						let metrics = metrics.clone();
 						let validation_host = validation_host.clone();
+						let dump_sender = dump_sender.clone();
 
 						async move {
 							let _timer = metrics.time_validate_from_exhaustive();
@@ -210,6 +246,7 @@ async fn run<Context>(
 								candidate_receipt,
 								pov,
 								timeout,
+								dump_sender,
 								&metrics,
 							)
 							.await;
@@ -493,6 +530,7 @@ async fn validate_from_chain_state<Sender>(
 	candidate_receipt: CandidateReceipt,
 	pov: Arc<PoV>,
 	exec_timeout_kind: PvfExecTimeoutKind,
+	dump_sender: Option<DumpSender>,
 	metrics: &Metrics,
 ) -> Result<ValidationResult, ValidationFailed>
 where
@@ -513,6 +551,7 @@ where
 		candidate_receipt.clone(),
 		pov,
 		exec_timeout_kind,
+		dump_sender,
 		metrics,
 	)
 	.await;
@@ -549,6 +588,7 @@ async fn validate_candidate_exhaustive<Sender>(
 	candidate_receipt: CandidateReceipt,
 	pov: Arc<PoV>,
 	exec_timeout_kind: PvfExecTimeoutKind,
+	dump_sender: Option<DumpSender>,
 	metrics: &Metrics,
 ) -> Result<ValidationResult, ValidationFailed>
 where
@@ -630,9 +670,12 @@ where
 		return Ok(ValidationResult::Invalid(InvalidCandidate::BadParent))
 	};
 
+	let dump = dump_sender
+		.map(|sender| (sender, persisted_validation_data.clone(), executor_params.clone()));
+
 	let result = validation_backend
 		.validate_candidate_with_retry(
-			raw_validation_code.to_vec(),
+			raw_validation_code.into_owned(),
 			pvf_exec_timeout(&executor_params, exec_timeout_kind),
 			exec_timeout_kind,
 			params,
@@ -644,6 +687,36 @@ where
 		gum::info!(target: LOG_TARGET, ?para_id, ?error, "Failed to validate candidate");
 	}
 
+	let validation_result =
+		handle_execution_result(result, &candidate_receipt, persisted_validation_data);
+
+	if let Some((mut dump_sender, persisted_validation_data, executor_params)) = dump {
+		if !matches!(validation_result, Ok(ValidationResult::Valid(..))) {
+			let dump = ValidationDump {
+				candidate_receipt,
+				persisted_validation_data,
+				pov: (*pov).clone(),
+				validation_code_hash,
+				validation_code,
+				executor_params,
+				exec_timeout_kind,
+				result: format!("{:?}", validation_result),
+			};
+			dump_sender.send(dump);
+		}
+	}
+
+	validation_result
+}
+
+/// Turns the outcome of executing the PVF of a candidate into the result of its validation.
+fn handle_execution_result(
+	result: Result<WasmValidationResult, ValidationError>,
+	candidate_receipt: &CandidateReceipt,
+	persisted_validation_data: PersistedValidationData,
+) -> Result<ValidationResult, ValidationFailed> {
+	let para_id = candidate_receipt.descriptor.para_id;
+
 	match result {
 		Err(ValidationError::InternalError(e)) => {
 			gum::warn!(
@@ -662,6 +735,16 @@ where
 			Ok(ValidationResult::Invalid(InvalidCandidate::ExecutionError(
 				"ambiguous worker death".to_string(),
 			))),
+		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::ForbiddenSyscall)) => {
+			// The syscall allowlist may be incomplete for the local environment, so we can't blame
+			// the candidate for this.
+			gum::warn!(
+				target: LOG_TARGET,
+				?para_id,
+				"Execution worker was killed by seccomp, will abstain from voting",
+			);
+			Err(ValidationFailed("worker made a forbidden syscall".to_string()))
+		},
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::Panic(err))) =>
 			Ok(ValidationResult::Invalid(InvalidCandidate::ExecutionError(err))),
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::PrepareError(e))) => {
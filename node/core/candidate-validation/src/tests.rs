@@ -474,6 +474,7 @@ fn candidate_validation_ok_is_ok() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	})
@@ -533,6 +534,7 @@ fn candidate_validation_bad_return_is_invalid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	});
@@ -605,6 +607,7 @@ fn candidate_validation_one_ambiguous_error_is_valid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	})
@@ -665,6 +668,7 @@ fn candidate_validation_multiple_ambiguous_errors_is_invalid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	})
@@ -722,6 +726,7 @@ fn candidate_validation_retry_internal_errors() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	});
@@ -778,6 +783,7 @@ fn candidate_validation_retry_panic_errors() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	});
@@ -785,6 +791,65 @@ fn candidate_validation_retry_panic_errors() {
 	assert_matches!(v, Ok(ValidationResult::Invalid(InvalidCandidate::ExecutionError(s))) if s == "bar".to_string());
 }
 
+// Test that we do not retry and abstain from voting if the worker was killed for making a forbidden
+// syscall.
+#[test]
+fn candidate_validation_forbidden_syscall_is_internal_error() {
+	let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
+
+	let pov = PoV { block_data: BlockData(vec![1; 32]) };
+	let validation_code = ValidationCode(vec![2; 16]);
+
+	let descriptor = make_valid_candidate_descriptor(
+		ParaId::from(1_u32),
+		dummy_hash(),
+		validation_data.hash(),
+		pov.hash(),
+		validation_code.hash(),
+		dummy_hash(),
+		dummy_hash(),
+		Sr25519Keyring::Alice,
+	);
+
+	let check = perform_basic_checks(
+		&descriptor,
+		validation_data.max_pov_size,
+		&pov,
+		&validation_code.hash(),
+	);
+	assert!(check.is_ok());
+
+	let candidate_receipt = CandidateReceipt { descriptor, commitments_hash: Hash::zero() };
+
+	let pool = TaskExecutor::new();
+	let (mut ctx, ctx_handle) =
+		test_helpers::make_subsystem_context::<AllMessages, _>(pool.clone());
+	let metrics = Metrics::default();
+
+	let v = test_with_executor_params(ctx_handle, || {
+		validate_candidate_exhaustive(
+			ctx.sender(),
+			MockValidateCandidateBackend::with_hardcoded_result_list(vec![
+				Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::ForbiddenSyscall)),
+				// Should not be reached.
+				Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::Panic("foo".into()))),
+			]),
+			validation_data,
+			validation_code,
+			candidate_receipt,
+			Arc::new(pov),
+			PvfExecTimeoutKind::Backing,
+			None,
+			&metrics,
+		)
+	});
+
+	assert_matches!(
+		v,
+		Err(ValidationFailed(s)) if s == "worker made a forbidden syscall".to_string()
+	);
+}
+
 #[test]
 fn candidate_validation_timeout_is_internal_error() {
 	let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
@@ -829,6 +894,7 @@ fn candidate_validation_timeout_is_internal_error() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	});
@@ -836,6 +902,85 @@ fn candidate_validation_timeout_is_internal_error() {
 	assert_matches!(v, Ok(ValidationResult::Invalid(InvalidCandidate::Timeout)));
 }
 
+#[test]
+fn candidate_validation_timeout_is_dumped() {
+	let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
+
+	let pov = PoV { block_data: BlockData(vec![1; 32]) };
+	let validation_code = ValidationCode(vec![2; 16]);
+
+	let descriptor = make_valid_candidate_descriptor(
+		ParaId::from(1_u32),
+		dummy_hash(),
+		validation_data.hash(),
+		pov.hash(),
+		validation_code.hash(),
+		dummy_hash(),
+		dummy_hash(),
+		Sr25519Keyring::Alice,
+	);
+	let candidate_receipt = CandidateReceipt { descriptor, commitments_hash: Hash::zero() };
+	let candidate_hash = candidate_receipt.hash();
+
+	let pool = TaskExecutor::new();
+	let (mut ctx, ctx_handle) =
+		test_helpers::make_subsystem_context::<AllMessages, _>(pool.clone());
+	let metrics = Metrics::default();
+	let dump_dir = tempfile::tempdir().unwrap();
+	let (dump_sender, dump_writer) = dump::writer(dump_dir.path().to_owned());
+
+	let v = test_with_executor_params(ctx_handle, || {
+		validate_candidate_exhaustive(
+			ctx.sender(),
+			MockValidateCandidateBackend::with_hardcoded_result(Err(
+				ValidationError::InvalidCandidate(WasmInvalidCandidate::HardTimeout),
+			)),
+			validation_data.clone(),
+			validation_code.clone(),
+			candidate_receipt,
+			Arc::new(pov.clone()),
+			PvfExecTimeoutKind::Approval,
+			Some(dump_sender),
+			&metrics,
+		)
+	});
+	assert_matches!(v, Ok(ValidationResult::Invalid(InvalidCandidate::Timeout)));
+
+	// The sender has been dropped, so the writer stops once the dump is written.
+	executor::block_on(dump_writer);
+
+	let dump =
+		ValidationDump::read(&dump_dir.path().join(format!("{:?}-approval.dump", candidate_hash)))
+			.unwrap();
+	assert_eq!(dump.candidate_receipt.hash(), candidate_hash);
+	assert_eq!(dump.persisted_validation_data, validation_data);
+	assert_eq!(dump.pov, pov);
+	assert_eq!(dump.validation_code, validation_code);
+	assert_eq!(dump.validation_code_hash, validation_code.hash());
+	assert_eq!(dump.executor_params, ExecutorParams::default());
+	assert_eq!(dump.exec_timeout_kind, PvfExecTimeoutKind::Approval);
+	assert_eq!(dump.result, format!("{:?}", v));
+}
+
+#[test]
+fn only_the_latest_dumps_are_retained() {
+	let dump_dir = tempfile::tempdir().unwrap();
+	for i in 0u8..5 {
+		std::fs::write(dump_dir.path().join(format!("{}.dump", i)), [i]).unwrap();
+	}
+	std::fs::write(dump_dir.path().join("unrelated"), b"").unwrap();
+
+	dump::prune(dump_dir.path(), 3).unwrap();
+
+	let mut remaining = std::fs::read_dir(dump_dir.path())
+		.unwrap()
+		.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+		.collect::<Vec<_>>();
+	remaining.sort();
+	assert_eq!(remaining.len(), 4);
+	assert_eq!(remaining.last().unwrap(), "unrelated");
+}
+
 #[test]
 fn candidate_validation_commitment_hash_mismatch_is_invalid() {
 	let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
@@ -881,6 +1026,7 @@ fn candidate_validation_commitment_hash_mismatch_is_invalid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	})
@@ -932,6 +1078,7 @@ fn candidate_validation_code_mismatch_is_invalid() {
 		candidate_receipt,
 		Arc::new(pov),
 		PvfExecTimeoutKind::Backing,
+		None,
 		&Default::default(),
 	))
 	.unwrap();
@@ -995,6 +1142,7 @@ fn compressed_code_works() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			None,
 			&metrics,
 		)
 	});
@@ -1048,6 +1196,7 @@ fn code_decompression_failure_is_error() {
 		candidate_receipt,
 		Arc::new(pov),
 		PvfExecTimeoutKind::Backing,
+		None,
 		&Default::default(),
 	));
 
@@ -1101,6 +1250,7 @@ fn pov_decompression_failure_is_invalid() {
 		candidate_receipt,
 		Arc::new(pov),
 		PvfExecTimeoutKind::Backing,
+		None,
 		&Default::default(),
 	));
 
@@ -1316,4 +1466,43 @@ fn precheck_properly_classifies_outcomes() {
 
 	inner(Err(PrepareError::TimedOut), PreCheckOutcome::Failed);
 	inner(Err(PrepareError::IoErr("fizz".to_owned())), PreCheckOutcome::Failed);
+	inner(Err(PrepareError::ForbiddenSyscall), PreCheckOutcome::Failed);
+	inner(Err(PrepareError::OutOfMemory), PreCheckOutcome::Failed);
+}
+
+#[test]
+fn pvf_host_restricts_workers_if_seccomp_is_enabled() {
+	let config = |enable_seccomp| Config {
+		artifacts_cache_path: "/tmp/pvf-artifacts".into(),
+		program_path: "/usr/bin/polkadot".into(),
+		validation_dump_dir: None,
+		enable_seccomp,
+		sandbox: None,
+	};
+
+	assert!(!config(false).pvf_host_config().enable_seccomp);
+
+	let pvf_config = config(true).pvf_host_config();
+	assert!(pvf_config.enable_seccomp);
+	assert_eq!(pvf_config.cache_path, PathBuf::from("/tmp/pvf-artifacts"));
+	assert_eq!(pvf_config.prepare_worker_program_path, PathBuf::from("/usr/bin/polkadot"));
+	assert_eq!(pvf_config.execute_worker_program_path, PathBuf::from("/usr/bin/polkadot"));
+}
+
+#[test]
+fn pvf_host_sandboxes_workers_if_configured() {
+	let mut config = Config {
+		artifacts_cache_path: "/tmp/pvf-artifacts".into(),
+		program_path: "/usr/bin/polkadot".into(),
+		validation_dump_dir: None,
+		enable_seccomp: false,
+		sandbox: None,
+	};
+	assert!(config.pvf_host_config().sandbox.is_none());
+
+	config.sandbox = Some(PvfSandboxConfig::new("/sys/fs/cgroup/polkadot-pvf".into()));
+	assert_matches!(
+		config.pvf_host_config().sandbox,
+		Some(sandbox) if sandbox.cgroup_root == PathBuf::from("/sys/fs/cgroup/polkadot-pvf")
+	);
 }
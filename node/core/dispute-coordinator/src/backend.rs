@@ -23,9 +23,9 @@
 
 use polkadot_primitives::{CandidateHash, SessionIndex};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use super::db::v1::{CandidateVotes, RecentDisputes};
+use super::db::v1::{CandidateVotes, QueuedParticipation, RecentDisputes};
 use crate::error::FatalResult;
 
 #[derive(Debug)]
@@ -34,6 +34,8 @@ pub enum BackendWriteOp {
 	WriteRecentDisputes(RecentDisputes),
 	WriteCandidateVotes(SessionIndex, CandidateHash, CandidateVotes),
 	DeleteCandidateVotes(SessionIndex, CandidateHash),
+	WriteQueuedParticipation(SessionIndex, CandidateHash, QueuedParticipation),
+	DeleteQueuedParticipation(SessionIndex, CandidateHash),
 }
 
 /// An abstraction over backend storage for the logic of this subsystem.
@@ -51,6 +53,11 @@ pub trait Backend {
 		candidate_hash: &CandidateHash,
 	) -> FatalResult<Option<CandidateVotes>>;
 
+	/// Load all persisted participation requests, ordered by session and candidate hash.
+	fn load_queued_participations(
+		&self,
+	) -> FatalResult<Vec<(SessionIndex, CandidateHash, QueuedParticipation)>>;
+
 	/// Atomically writes the list of operations, with later operations taking precedence over
 	/// prior.
 	fn write<I>(&mut self, ops: I) -> FatalResult<()>
@@ -72,6 +79,8 @@ pub struct OverlayedBackend<'a, B: 'a> {
 	recent_disputes: Option<RecentDisputes>,
 	// `None` means deleted, missing means query inner.
 	candidate_votes: HashMap<(SessionIndex, CandidateHash), Option<CandidateVotes>>,
+	// `None` means deleted, missing means query inner.
+	queued_participations: HashMap<(SessionIndex, CandidateHash), Option<QueuedParticipation>>,
 }
 
 impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
@@ -81,6 +90,7 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 			earliest_session: None,
 			recent_disputes: None,
 			candidate_votes: HashMap::new(),
+			queued_participations: HashMap::new(),
 		}
 	}
 
@@ -88,7 +98,8 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 	pub fn is_empty(&self) -> bool {
 		self.earliest_session.is_none() &&
 			self.recent_disputes.is_none() &&
-			self.candidate_votes.is_empty()
+			self.candidate_votes.is_empty() &&
+			self.queued_participations.is_empty()
 	}
 
 	/// Load the earliest session, if any.
@@ -122,6 +133,30 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 		self.inner.load_candidate_votes(session, candidate_hash)
 	}
 
+	/// Load all persisted participation requests, ordered by session and candidate hash.
+	pub fn load_queued_participations(
+		&self,
+	) -> FatalResult<Vec<(SessionIndex, CandidateHash, QueuedParticipation)>> {
+		let mut queued: BTreeMap<_, _> = self
+			.inner
+			.load_queued_participations()?
+			.into_iter()
+			.map(|(session, candidate_hash, queued)| ((session, candidate_hash), queued))
+			.collect();
+
+		for (key, val) in &self.queued_participations {
+			match val {
+				Some(val) => queued.insert(*key, val.clone()),
+				None => queued.remove(key),
+			};
+		}
+
+		Ok(queued
+			.into_iter()
+			.map(|((session, candidate_hash), queued)| (session, candidate_hash, queued))
+			.collect())
+	}
+
 	/// Prepare a write to the "earliest session" field of the DB.
 	///
 	/// Later calls to this function will override earlier ones.
@@ -148,6 +183,27 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 		self.candidate_votes.insert((session, candidate_hash), Some(votes));
 	}
 
+	/// Prepare a write of a participation request for the indicated candidate.
+	///
+	/// Later calls to this function for the same candidate will override earlier ones.
+	pub fn write_queued_participation(
+		&mut self,
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		queued: QueuedParticipation,
+	) {
+		self.queued_participations.insert((session, candidate_hash), Some(queued));
+	}
+
+	/// Prepare a deletion of the participation request for the indicated candidate.
+	pub fn delete_queued_participation(
+		&mut self,
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+	) {
+		self.queued_participations.insert((session, candidate_hash), None);
+	}
+
 	/// Transform this backend into a set of write-ops to be written to the inner backend.
 	pub fn into_write_ops(self) -> impl Iterator<Item = BackendWriteOp> {
 		let earliest_session_ops = self
@@ -166,6 +222,17 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 					None => BackendWriteOp::DeleteCandidateVotes(session, candidate),
 				});
 
-		earliest_session_ops.chain(recent_dispute_ops).chain(candidate_vote_ops)
+		let queued_participation_ops = self.queued_participations.into_iter().map(
+			|((session, candidate), queued)| match queued {
+				Some(queued) =>
+					BackendWriteOp::WriteQueuedParticipation(session, candidate, queued),
+				None => BackendWriteOp::DeleteQueuedParticipation(session, candidate),
+			},
+		);
+
+		earliest_session_ops
+			.chain(recent_dispute_ops)
+			.chain(candidate_vote_ops)
+			.chain(queued_participation_ops)
 	}
 }
@@ -43,6 +43,7 @@ use crate::{
 const RECENT_DISPUTES_KEY: &[u8; 15] = b"recent-disputes";
 const EARLIEST_SESSION_KEY: &[u8; 16] = b"earliest-session";
 const CANDIDATE_VOTES_SUBKEY: &[u8; 15] = b"candidate-votes";
+const QUEUED_PARTICIPATION_SUBKEY: &[u8; 20] = b"queued-participation";
 /// Until what session have votes been cleaned up already?
 const CLEANED_VOTES_WATERMARK_KEY: &[u8; 23] = b"cleaned-votes-watermark";
 
@@ -105,6 +106,10 @@ impl DbBackend {
 			"Cleaning votes for session index"
 			);
 			tx.delete_prefix(self.config.col_dispute_data, &candidate_votes_session_prefix(index));
+			tx.delete_prefix(
+				self.config.col_dispute_data,
+				&queued_participation_session_prefix(index),
+			);
 		}
 		// New watermark:
 		tx.put_vec(self.config.col_dispute_data, CLEANED_VOTES_WATERMARK_KEY, clean_until.encode());
@@ -132,6 +137,13 @@ impl Backend for DbBackend {
 		load_candidate_votes(&*self.inner, &self.config, session, candidate_hash)
 	}
 
+	/// Load all persisted participation requests, ordered by session and candidate hash.
+	fn load_queued_participations(
+		&self,
+	) -> FatalResult<Vec<(SessionIndex, CandidateHash, QueuedParticipation)>> {
+		load_queued_participations(&*self.inner, &self.config)
+	}
+
 	/// Atomically writes the list of operations, with later operations taking precedence over
 	/// prior.
 	///
@@ -180,6 +192,19 @@ impl Backend for DbBackend {
 						&candidate_votes_key(session, &candidate_hash),
 					);
 				},
+				BackendWriteOp::WriteQueuedParticipation(session, candidate_hash, queued) => {
+					tx.put_vec(
+						self.config.col_dispute_data,
+						&queued_participation_key(session, &candidate_hash),
+						queued.encode(),
+					);
+				},
+				BackendWriteOp::DeleteQueuedParticipation(session, candidate_hash) => {
+					tx.delete(
+						self.config.col_dispute_data,
+						&queued_participation_key(session, &candidate_hash),
+					);
+				},
 			}
 		}
 
@@ -207,6 +232,29 @@ fn candidate_votes_session_prefix(session: SessionIndex) -> [u8; 15 + 4] {
 	buf
 }
 
+fn queued_participation_key(
+	session: SessionIndex,
+	candidate_hash: &CandidateHash,
+) -> [u8; 20 + 4 + 32] {
+	let mut buf = [0u8; 20 + 4 + 32];
+	buf[..20].copy_from_slice(QUEUED_PARTICIPATION_SUBKEY);
+
+	// big-endian encoding is used to ensure lexicographic ordering.
+	buf[20..][..4].copy_from_slice(&session.to_be_bytes());
+	candidate_hash.using_encoded(|s| buf[(20 + 4)..].copy_from_slice(s));
+
+	buf
+}
+
+fn queued_participation_session_prefix(session: SessionIndex) -> [u8; 20 + 4] {
+	let mut buf = [0u8; 20 + 4];
+	buf[..20].copy_from_slice(QUEUED_PARTICIPATION_SUBKEY);
+
+	// big-endian encoding is used to ensure lexicographic ordering.
+	buf[20..][..4].copy_from_slice(&session.to_be_bytes());
+	buf
+}
+
 /// Column configuration information for the DB.
 #[derive(Debug, Clone)]
 pub struct ColumnConfiguration {
@@ -249,6 +297,21 @@ impl From<polkadot_node_primitives::CandidateVotes> for CandidateVotes {
 	}
 }
 
+/// A participation request which is queued or running, persisted so it survives restarts.
+///
+/// The entry is removed once the participation concluded.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct QueuedParticipation {
+	/// The receipt of the disputed candidate.
+	pub candidate_receipt: CandidateReceipt,
+	/// Whether the request was queued with priority.
+	pub priority: bool,
+	/// Whether the candidate was seen backed on chain when the request was queued.
+	pub is_backed: bool,
+	/// The number of votes against the candidate when the request was queued.
+	pub votes_against: u32,
+}
+
 /// The mapping for recent disputes; any which have not yet been pruned for being ancient.
 pub type RecentDisputes = std::collections::BTreeMap<(SessionIndex, CandidateHash), DisputeStatus>;
 
@@ -313,6 +376,27 @@ pub(crate) fn load_recent_disputes(
 		.map_err(|e| FatalError::DbReadFailed(e))
 }
 
+/// Load all persisted participation requests, ordered by session and candidate hash.
+pub(crate) fn load_queued_participations(
+	db: &dyn Database,
+	config: &ColumnConfiguration,
+) -> FatalResult<Vec<(SessionIndex, CandidateHash, QueuedParticipation)>> {
+	let mut queued = Vec::new();
+	for r in db.iter_with_prefix(config.col_dispute_data, QUEUED_PARTICIPATION_SUBKEY) {
+		let (k, v) = r.map_err(|e| FatalError::DbReadFailed(e.into()))?;
+		let decode = || -> Result<_> {
+			let session = SessionIndex::from_be_bytes(k[20..][..4].try_into().map_err(|_| {
+				parity_scale_codec::Error::from("queued participation key too short")
+			})?);
+			let candidate_hash = CandidateHash::decode(&mut &k[(20 + 4)..])?;
+			let entry = QueuedParticipation::decode(&mut &v[..])?;
+			Ok((session, candidate_hash, entry))
+		};
+		queued.push(decode().map_err(FatalError::DbReadFailed)?);
+	}
+	Ok(queued)
+}
+
 /// Maybe prune data in the DB based on the provided session index.
 ///
 /// This is intended to be called on every block, and as such will be used to populate the DB on
@@ -601,6 +685,50 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn queued_participations_are_persisted_and_pruned_with_their_session() {
+		let mut backend = make_db();
+
+		let hash_a = CandidateHash(Hash::repeat_byte(0x0a));
+		let hash_b = CandidateHash(Hash::repeat_byte(0x0b));
+		let queued = |priority| QueuedParticipation {
+			candidate_receipt: dummy_candidate_receipt(dummy_hash()),
+			priority,
+			is_backed: true,
+			votes_against: 1,
+		};
+
+		let mut overlay_db = OverlayedBackend::new(&backend);
+		overlay_db.write_earliest_session(0);
+		overlay_db.write_queued_participation(1, hash_a, queued(false));
+		overlay_db.write_queued_participation(2, hash_b, queued(false));
+		overlay_db.write_queued_participation(2, hash_b, queued(true));
+
+		let write_ops = overlay_db.into_write_ops();
+		backend.write(write_ops).unwrap();
+
+		assert_eq!(
+			backend.load_queued_participations().unwrap(),
+			vec![(1, hash_a, queued(false)), (2, hash_b, queued(true))],
+		);
+
+		// Deletions are visible through the overlay before being written:
+		let mut overlay_db = OverlayedBackend::new(&backend);
+		overlay_db.delete_queued_participation(2, hash_b);
+		assert_eq!(
+			overlay_db.load_queued_participations().unwrap(),
+			vec![(1, hash_a, queued(false))],
+		);
+		overlay_db.write_queued_participation(2, hash_b, queued(true));
+
+		// Requests of pruned sessions are removed together with their votes.
+		note_earliest_session(&mut overlay_db, 2).unwrap();
+		let write_ops = overlay_db.into_write_ops();
+		backend.write(write_ops).unwrap();
+
+		assert_eq!(backend.load_queued_participations().unwrap(), vec![(2, hash_b, queued(true))]);
+	}
+
 	#[test]
 	fn note_earliest_session_prunes_old() {
 		let mut backend = make_db();
@@ -17,7 +17,7 @@
 //! Dispute coordinator subsystem in initialized state (after first active leaf is received).
 
 use std::{
-	collections::{BTreeMap, VecDeque},
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
 	sync::Arc,
 };
 
@@ -77,6 +77,13 @@ use super::{
 /// updates (and especially on startup) so the dispute coordinator won't be considered stalling.
 const CHAIN_IMPORT_MAX_BATCH_SIZE: usize = 8;
 
+/// After how many blocks we report a still unapplied slash again.
+///
+/// Slashing reports are unsigned transactions which are not propagated, so they only make it on
+/// chain once we author a block ourselves. Should the transaction get dropped from our pool in
+/// the meantime, we retry after this many blocks.
+const SLASHING_REPORT_RESUBMIT_INTERVAL: BlockNumber = 600;
+
 // Initial data for `dispute-coordinator`. It is provided only at first start.
 pub struct InitialData {
 	pub participations: Vec<(ParticipationPriority, ParticipationRequest)>,
@@ -112,6 +119,13 @@ pub(crate) struct Initialized {
 	/// To resolve this, we limit the amount of votes imported at once to
 	/// `CHAIN_IMPORT_MAX_BATCH_SIZE` and put the rest here for later processing.
 	chain_import_backlog: VecDeque<ScrapedOnChainVotes>,
+	/// Pending slashes we already submitted a report for, together with the number of the block
+	/// the report was submitted on.
+	///
+	/// Unapplied slashes are returned by the runtime on every new leaf until they got applied, this
+	/// keeps us from reporting them over and over again. Entries are dropped once the slash is no
+	/// longer pending.
+	reported_slashes: HashMap<(SessionIndex, CandidateHash, ValidatorIndex), BlockNumber>,
 	metrics: Metrics,
 }
 
@@ -126,10 +140,11 @@ impl Initialized {
 		highest_session_seen: SessionIndex,
 		gaps_in_cache: bool,
 	) -> Self {
-		let DisputeCoordinatorSubsystem { config: _, store: _, keystore, metrics } = subsystem;
+		let DisputeCoordinatorSubsystem { config, store: _, keystore, metrics } = subsystem;
 
 		let (participation_sender, participation_receiver) = mpsc::channel(1);
-		let participation = Participation::new(participation_sender, metrics.clone());
+		let participation =
+			Participation::new(participation_sender, config.participation, metrics.clone());
 
 		Self {
 			keystore,
@@ -141,6 +156,7 @@ impl Initialized {
 			participation,
 			participation_receiver,
 			chain_import_backlog: VecDeque::new(),
+			reported_slashes: HashMap::new(),
 			metrics,
 		}
 	}
@@ -202,6 +218,7 @@ impl Initialized {
 			)
 			.await;
 
+			self.participation.persist_changes(&mut overlay_db);
 			if !overlay_db.is_empty() {
 				let ops = overlay_db.into_write_ops();
 				backend.write(ops)?;
@@ -273,6 +290,7 @@ impl Initialized {
 					},
 				};
 
+			self.participation.persist_changes(&mut overlay_db);
 			if !overlay_db.is_empty() {
 				let ops = overlay_db.into_write_ops();
 				backend.write(ops)?;
@@ -295,8 +313,7 @@ impl Initialized {
 			self.scraper.process_active_leaves_update(ctx.sender(), &update).await?;
 		log_error(
 			self.participation
-				.bump_to_priority_for_candidates(ctx, &scraped_updates.included_receipts)
-				.await,
+				.bump_to_priority_for_candidates(&scraped_updates.included_receipts),
 		)?;
 		self.participation.process_active_leaves_update(ctx, &update).await?;
 
@@ -353,7 +370,17 @@ impl Initialized {
 
 			let ScrapedUpdates { unapplied_slashes, on_chain_votes, .. } = scraped_updates;
 
-			self.process_unapplied_slashes(ctx, new_leaf.hash, unapplied_slashes).await;
+			// Without the unapplied slashes we can't tell which reports are still pending, keep
+			// everything as is until the next leaf.
+			if let Some(unapplied_slashes) = unapplied_slashes {
+				self.process_unapplied_slashes(
+					ctx,
+					new_leaf.hash,
+					new_leaf.number,
+					unapplied_slashes,
+				)
+				.await;
+			}
 
 			gum::trace!(
 				target: LOG_TARGET,
@@ -372,13 +399,51 @@ impl Initialized {
 
 	/// For each unapplied (past-session) slash, report an unsigned extrinsic
 	/// to the runtime.
+	///
+	/// Slashes which have already been reported are skipped, unless the report is older than
+	/// `SLASHING_REPORT_RESUBMIT_INTERVAL` blocks.
 	async fn process_unapplied_slashes<Context>(
 		&mut self,
 		ctx: &mut Context,
 		relay_parent: Hash,
+		relay_parent_number: BlockNumber,
 		unapplied_slashes: Vec<(SessionIndex, CandidateHash, vstaging::slashing::PendingSlashes)>,
 	) {
-		for (session_index, candidate_hash, pending) in unapplied_slashes {
+		// Slashes which are no longer pending have either been applied or pruned, no need to keep
+		// track of them any longer.
+		let still_pending: HashSet<_> = unapplied_slashes
+			.iter()
+			.flat_map(|(session_index, candidate_hash, pending)| {
+				pending
+					.keys
+					.keys()
+					.map(move |validator_index| (*session_index, *candidate_hash, *validator_index))
+			})
+			.collect();
+		self.reported_slashes.retain(|slash, _| still_pending.contains(slash));
+		self.metrics.report_unapplied_slashes(still_pending.len() as u64);
+
+		for (session_index, candidate_hash, mut pending) in unapplied_slashes {
+			let reported_slashes = &self.reported_slashes;
+			pending.keys.retain(|validator_index, _| {
+				reported_slashes.get(&(session_index, candidate_hash, *validator_index)).map_or(
+					true,
+					|reported_at| {
+						relay_parent_number >=
+							reported_at.saturating_add(SLASHING_REPORT_RESUBMIT_INTERVAL)
+					},
+				)
+			});
+			if pending.keys.is_empty() {
+				gum::trace!(
+					target: LOG_TARGET,
+					?session_index,
+					?candidate_hash,
+					"Unapplied validator slashes have already been reported",
+				);
+				continue
+			}
+
 			gum::info!(
 				target: LOG_TARGET,
 				?session_index,
@@ -391,9 +456,11 @@ impl Initialized {
 			if inclusions.is_empty() {
 				gum::info!(
 					target: LOG_TARGET,
+					?session_index,
+					?candidate_hash,
 					"Couldn't find inclusion parent for an unapplied slash",
 				);
-				return
+				continue
 			}
 
 			// Find the first inclusion parent that we can use
@@ -466,6 +533,8 @@ impl Initialized {
 					"Could not generate key ownership proofs for {} keys",
 					expected_keys - resolved_keys,
 				);
+				self.metrics
+					.on_missing_key_ownership_proofs((expected_keys - resolved_keys) as u64);
 			}
 			debug_assert_eq!(resolved_keys, dispute_proofs.len());
 
@@ -473,6 +542,7 @@ impl Initialized {
 				key_ownership_proofs.into_iter().zip(dispute_proofs.into_iter())
 			{
 				let validator_id = dispute_proof.validator_id.clone();
+				let validator_index = dispute_proof.validator_index;
 
 				gum::info!(
 					target: LOG_TARGET,
@@ -509,6 +579,7 @@ impl Initialized {
 							?candidate_hash,
 							"Error reporting pending slash",
 						);
+						self.metrics.on_slashing_report_failed();
 					},
 					Ok(Some(())) => {
 						gum::info!(
@@ -518,6 +589,11 @@ impl Initialized {
 							?validator_id,
 							"Successfully reported pending slash",
 						);
+						self.metrics.on_slashing_report_submitted();
+						self.reported_slashes.insert(
+							(session_index, candidate_hash, validator_index),
+							relay_parent_number,
+						);
 					},
 					Ok(None) => {
 						gum::debug!(
@@ -527,6 +603,11 @@ impl Initialized {
 							?validator_id,
 							"Duplicate pending slash report",
 						);
+						self.metrics.on_slashing_report_duplicate();
+						self.reported_slashes.insert(
+							(session_index, candidate_hash, validator_index),
+							relay_parent_number,
+						);
 					},
 				}
 			}
@@ -905,6 +986,52 @@ impl Initialized {
 
 				let _ = tx.send(undisputed_chain);
 			},
+			DisputeCoordinatorMessage::InspectCandidateVotes(query, tx) => {
+				gum::trace!(target: LOG_TARGET, "DisputeCoordinatorMessage::InspectCandidateVotes");
+				let mut query_output = Vec::new();
+				for (session_index, candidate_hash) in query {
+					let votes: CandidateVotes =
+						match overlay_db.load_candidate_votes(session_index, &candidate_hash)? {
+							Some(votes) => votes.into(),
+							None => continue,
+						};
+
+					// Our own votes can only be told apart if the session is still known.
+					let voted_indices = votes.voted_indices();
+					let own_votes = match CandidateEnvironment::new(
+						&self.keystore,
+						ctx,
+						&mut self.runtime_info,
+						session_index,
+						votes.candidate_receipt.descriptor.relay_parent,
+					)
+					.await
+					{
+						Some(env) => voted_indices
+							.into_iter()
+							.filter(|index| env.controlled_indices().contains(index))
+							.filter_map(|index| {
+								env.validators().get(index).map(|id| (index, id.clone()))
+							})
+							.collect(),
+						None => Vec::new(),
+					};
+
+					query_output.push((session_index, candidate_hash, votes, own_votes));
+				}
+				let _ = tx.send(query_output);
+			},
+			DisputeCoordinatorMessage::InspectSpamSlots(tx) => {
+				gum::trace!(target: LOG_TARGET, "DisputeCoordinatorMessage::InspectSpamSlots");
+				let _ = tx.send(self.spam_slots.occupied());
+			},
+			DisputeCoordinatorMessage::InspectParticipationQueue(tx) => {
+				gum::trace!(
+					target: LOG_TARGET,
+					"DisputeCoordinatorMessage::InspectParticipationQueue"
+				);
+				let _ = tx.send(self.participation.contents());
+			},
 		}
 
 		Ok(Box::new(|| Ok(())))
@@ -1057,6 +1184,16 @@ impl Initialized {
 							count = votes.len(),
 							"Successfully received approval votes."
 						);
+						// There is no dispute statement kind for approvals covering multiple
+						// candidates on chain yet, so only approvals of this very candidate can
+						// be imported.
+						let votes = votes
+							.into_iter()
+							.filter_map(|(validator_index, (candidate_hashes, signature))| {
+								(candidate_hashes == [candidate_hash])
+									.then_some((validator_index, signature))
+							})
+							.collect();
 						intermediate_result.import_approval_votes(&env, votes, now)
 					},
 				}
@@ -1161,7 +1298,8 @@ impl Initialized {
 						new_state.candidate_receipt().clone(),
 						session,
 						request_timer,
-					),
+					)
+					.with_dispute_state(is_backed, new_state.votes().invalid.len() as u32),
 				)
 				.await;
 			log_error(r)?;
@@ -24,7 +24,7 @@
 //! validation results as well as a sink for votes received by other subsystems. When importing a dispute vote from
 //! another node, this will trigger dispute participation to recover and validate the block.
 
-use std::{num::NonZeroUsize, sync::Arc};
+use std::{collections::HashMap, num::NonZeroUsize, sync::Arc};
 
 use futures::FutureExt;
 
@@ -97,6 +97,7 @@ mod spam_slots;
 /// first and more importantly it will order requests in a way so disputes will get resolved, even
 /// if there are lots of them.
 pub(crate) mod participation;
+pub use participation::{ParticipationConfig, ParticipationOrdering};
 
 /// Pure processing of vote imports.
 pub(crate) mod import;
@@ -127,6 +128,8 @@ pub struct DisputeCoordinatorSubsystem {
 pub struct Config {
 	/// The data column in the store to use for dispute data.
 	pub col_dispute_data: u32,
+	/// Ordering and limits of the dispute participation queues.
+	pub participation: ParticipationConfig,
 }
 
 impl Config {
@@ -267,7 +270,7 @@ impl DisputeCoordinatorSubsystem {
 	// Restores the subsystem's state before proceeding with the main event loop.
 	//
 	// - Prune any old disputes.
-	// - Find disputes we need to participate in.
+	// - Find disputes we need to participate in, resuming persisted participation requests.
 	// - Initialize spam slots & OrderingProvider.
 	async fn handle_startup<Context>(
 		&self,
@@ -329,11 +332,20 @@ impl DisputeCoordinatorSubsystem {
 			highest_session.saturating_sub(DISPUTE_WINDOW.get() - 1),
 		)?;
 
+		// Participation requests persisted before the restart, by dispute. Any left over after
+		// going through the active disputes are obsolete.
+		let mut persisted_requests: HashMap<_, _> = overlay_db
+			.load_queued_participations()?
+			.into_iter()
+			.map(|(session, candidate_hash, queued)| ((session, candidate_hash), queued))
+			.collect();
+
 		let mut participation_requests = Vec::new();
 		let mut spam_disputes: UnconfirmedDisputes = UnconfirmedDisputes::new();
 		let leaf_hash = initial_head.hash;
 		let (scraper, votes) = ChainScraper::new(ctx.sender(), initial_head).await?;
 		for ((session, ref candidate_hash), _) in active_disputes {
+			let persisted_request = persisted_requests.remove(&(session, *candidate_hash));
 			let env = match CandidateEnvironment::new(
 				&self.keystore,
 				ctx,
@@ -370,9 +382,14 @@ impl DisputeCoordinatorSubsystem {
 				};
 			let vote_state = CandidateVoteState::new(votes, &env, now);
 
-			let potential_spam = is_potential_spam(&scraper, &vote_state, candidate_hash);
-			let is_included =
-				scraper.is_candidate_included(&vote_state.votes().candidate_receipt.hash());
+			// Requests which got queued before the restart have been checked already, while the
+			// scraper might not know about the inclusion of their candidates anymore.
+			let potential_spam = persisted_request.is_none() &&
+				is_potential_spam(&scraper, &vote_state, candidate_hash);
+			let is_included = scraper.is_candidate_included(candidate_hash) ||
+				persisted_request.as_ref().map_or(false, |queued| queued.priority);
+			let is_backed = scraper.is_candidate_backed(candidate_hash) ||
+				persisted_request.as_ref().map_or(false, |queued| queued.is_backed);
 
 			if potential_spam {
 				gum::trace!(
@@ -399,7 +416,8 @@ impl DisputeCoordinatorSubsystem {
 							vote_state.votes().candidate_receipt.clone(),
 							session,
 							request_timer,
-						),
+						)
+						.with_dispute_state(is_backed, vote_state.votes().invalid.len() as u32),
 					));
 				}
 				// Else make sure our own vote is distributed:
@@ -411,10 +429,23 @@ impl DisputeCoordinatorSubsystem {
 						"Found valid dispute, with vote from us on startup - send vote."
 					);
 					send_dispute_messages(ctx, &env, &vote_state).await;
+					if persisted_request.is_some() {
+						overlay_db.delete_queued_participation(session, *candidate_hash);
+					}
 				}
 			}
 		}
 
+		for (session, candidate_hash) in persisted_requests.into_keys() {
+			gum::trace!(
+				target: LOG_TARGET,
+				?session,
+				?candidate_hash,
+				"Dropping persisted participation request of inactive dispute."
+			);
+			overlay_db.delete_queued_participation(session, candidate_hash);
+		}
+
 		Ok((
 			participation_requests,
 			votes,
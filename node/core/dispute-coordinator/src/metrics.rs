@@ -42,6 +42,10 @@ struct MetricsInner {
 	participation_priority_queue_size: prometheus::Gauge<prometheus::U64>,
 	/// Size of participation best effort queue
 	participation_best_effort_queue_size: prometheus::Gauge<prometheus::U64>,
+	/// Outcome of slashing reports for pending slashes.
+	slashing_reports: prometheus::CounterVec<prometheus::U64>,
+	/// Number of validator keys pending a slash as of the latest leaf.
+	unapplied_slashes: prometheus::Gauge<prometheus::U64>,
 }
 
 /// Candidate validation metrics.
@@ -136,6 +140,40 @@ impl Metrics {
 			metrics.participation_best_effort_queue_size.set(size);
 		}
 	}
+
+	pub(crate) fn on_slashing_report_submitted(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.slashing_reports.with_label_values(&["submitted"]).inc();
+		}
+	}
+
+	pub(crate) fn on_slashing_report_duplicate(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.slashing_reports.with_label_values(&["duplicate"]).inc();
+		}
+	}
+
+	pub(crate) fn on_slashing_report_failed(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.slashing_reports.with_label_values(&["failed"]).inc();
+		}
+	}
+
+	pub(crate) fn on_missing_key_ownership_proofs(&self, count: u64) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.slashing_reports
+				.with_label_values(&["missing-key-ownership-proof"])
+				.inc_by(count);
+		}
+	}
+
+	/// Set the unapplied_slashes metric
+	pub(crate) fn report_unapplied_slashes(&self, count: u64) {
+		if let Some(metrics) = &self.0 {
+			metrics.unapplied_slashes.set(count);
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -231,6 +269,23 @@ impl metrics::Metrics for Metrics {
 				"Number of disputes waiting for local participation in the best effort queue.")?,
 				registry,
 			)?,
+			slashing_reports: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_dispute_slashing_reports",
+						"Slashing reports for lost disputes, sorted by outcome: `submitted`, `duplicate`, `failed` and `missing-key-ownership-proof`.",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+			unapplied_slashes: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_dispute_unapplied_slashes",
+					"Number of validator keys with a pending slash, as seen on the latest leaf.",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -27,7 +27,9 @@ use futures_timer::Delay;
 
 use polkadot_node_primitives::ValidationResult;
 use polkadot_node_subsystem::{
-	messages::{AvailabilityRecoveryMessage, CandidateValidationMessage},
+	messages::{
+		AvailabilityRecoveryMessage, CandidateValidationMessage, ParticipationQueueContents,
+	},
 	overseer, ActiveLeavesUpdate, RecoveryError,
 };
 use polkadot_node_subsystem_util::runtime::get_validation_code_by_hash;
@@ -35,7 +37,10 @@ use polkadot_primitives::{
 	BlockNumber, CandidateHash, CandidateReceipt, Hash, PvfExecTimeoutKind, SessionIndex,
 };
 
-use crate::LOG_TARGET;
+use crate::{
+	backend::{Backend, OverlayedBackend},
+	LOG_TARGET,
+};
 
 use crate::error::{FatalError, FatalResult, Result};
 
@@ -46,7 +51,10 @@ pub use tests::{participation_full_happy_path, participation_missing_availabilit
 
 mod queues;
 use queues::Queues;
-pub use queues::{ParticipationPriority, ParticipationRequest, QueueError};
+pub use queues::{
+	ParticipationConfig, ParticipationOrdering, ParticipationPriority, ParticipationRequest,
+	QueueError,
+};
 
 use crate::metrics::Metrics;
 use polkadot_node_subsystem_util::metrics::prometheus::prometheus;
@@ -68,7 +76,7 @@ pub(crate) const MAX_PARALLEL_PARTICIPATIONS: usize = 1;
 pub struct Participation {
 	/// Participations currently being processed.
 	running_participations: HashSet<CandidateHash>,
-	/// Priority and best effort queues.
+	/// Priority and best effort queues, also tracking the persisted requests.
 	queue: Queues,
 	/// Sender to be passed to worker tasks.
 	worker_sender: WorkerMessageSender,
@@ -140,10 +148,10 @@ impl Participation {
 	/// The passed in sender will be used by background workers to communicate back their results.
 	/// The calling context should make sure to call `Participation::on_worker_message()` for the
 	/// received messages.
-	pub fn new(sender: WorkerMessageSender, metrics: Metrics) -> Self {
+	pub fn new(sender: WorkerMessageSender, config: ParticipationConfig, metrics: Metrics) -> Self {
 		Self {
 			running_participations: HashSet::new(),
-			queue: Queues::new(metrics.clone()),
+			queue: Queues::new(config, metrics.clone()),
 			worker_sender: sender,
 			recent_block: None,
 			metrics,
@@ -170,6 +178,7 @@ impl Participation {
 		// Available capacity - participate right away (if we already have a recent block):
 		if let Some((_, h)) = self.recent_block {
 			if self.running_participations.len() < MAX_PARALLEL_PARTICIPATIONS {
+				self.queue.note_running(&priority, &req);
 				self.fork_participation(ctx, req, h)?;
 				return Ok(())
 			}
@@ -194,6 +203,7 @@ impl Participation {
 	) -> FatalResult<ParticipationStatement> {
 		let WorkerMessage(statement) = msg;
 		self.running_participations.remove(&statement.candidate_hash);
+		self.queue.note_concluded(statement.session, statement.candidate_hash);
 		let recent_block = self.recent_block.expect("We never ever reset recent_block to `None` and we already received a result, so it must have been set before. qed.");
 		self.dequeue_until_capacity(ctx, recent_block.1).await?;
 		Ok(statement)
@@ -224,19 +234,38 @@ impl Participation {
 		Ok(())
 	}
 
+	/// The running and queued participations.
+	pub fn contents(&self) -> ParticipationQueueContents {
+		let mut running: Vec<_> = self.running_participations.iter().copied().collect();
+		running.sort();
+		let (priority, best_effort) = self.queue.queued();
+		ParticipationQueueContents { running, priority, best_effort }
+	}
+
 	/// Moving any request concerning the given candidates from best-effort to
 	/// priority, ignoring any candidates that don't have any queued participation requests.
-	pub async fn bump_to_priority_for_candidates<Context>(
+	pub fn bump_to_priority_for_candidates(
 		&mut self,
-		ctx: &mut Context,
 		included_receipts: &Vec<CandidateReceipt>,
 	) -> Result<()> {
 		for receipt in included_receipts {
-			self.queue.prioritize_if_present(ctx.sender(), receipt).await?;
+			self.queue.prioritize_if_present(&receipt.hash())?;
 		}
 		Ok(())
 	}
 
+	/// Write the requests which got queued, reprioritized or concluded since the last call to the
+	/// database, so they can be resumed after a restart.
+	pub fn persist_changes(&mut self, overlay_db: &mut OverlayedBackend<'_, impl Backend>) {
+		for ((session, candidate_hash), queued) in self.queue.take_changes() {
+			match queued {
+				Some(queued) =>
+					overlay_db.write_queued_participation(session, candidate_hash, queued),
+				None => overlay_db.delete_queued_participation(session, candidate_hash),
+			}
+		}
+	}
+
 	/// Dequeue until `MAX_PARALLEL_PARTICIPATIONS` is reached.
 	async fn dequeue_until_capacity<Context>(
 		&mut self,
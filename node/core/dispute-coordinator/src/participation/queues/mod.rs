@@ -16,7 +16,7 @@
 
 use std::{
 	cmp::Ordering,
-	collections::{btree_map::Entry, BTreeMap},
+	collections::{BTreeMap, HashMap},
 };
 
 use futures::channel::oneshot;
@@ -24,6 +24,7 @@ use polkadot_node_subsystem::{messages::ChainApiMessage, overseer};
 use polkadot_primitives::{BlockNumber, CandidateHash, CandidateReceipt, Hash, SessionIndex};
 
 use crate::{
+	db::v1::QueuedParticipation,
 	error::{FatalError, FatalResult, Result},
 	LOG_TARGET,
 };
@@ -53,9 +54,64 @@ const PRIORITY_QUEUE_SIZE: usize = 20_000;
 #[cfg(test)]
 const PRIORITY_QUEUE_SIZE: usize = 2;
 
+/// How many best-effort requests of a single session can be queued by default.
+///
+/// Half of the best-effort queue, so a burst of spam disputes in one session still leaves room
+/// for the disputes of other sessions.
+#[cfg(not(test))]
+const BEST_EFFORT_SESSION_BUDGET: usize = BEST_EFFORT_QUEUE_SIZE / 2;
+#[cfg(test)]
+const BEST_EFFORT_SESSION_BUDGET: usize = BEST_EFFORT_QUEUE_SIZE;
+
+/// How participation requests are ordered within the priority and the best-effort queue.
+///
+/// Requests which rank equally are ordered by the age of their relay parent, see
+/// `CandidateComparator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ParticipationOrdering {
+	/// Oldest relay parent first.
+	RelayParentAge,
+	/// Candidates which have been seen backed on chain first.
+	BackedFirst,
+	/// Candidates with the most votes against them first.
+	MostVotesAgainstFirst,
+}
+
+impl Default for ParticipationOrdering {
+	fn default() -> Self {
+		Self::RelayParentAge
+	}
+}
+
+/// Configuration of the dispute participation queues.
+#[derive(Debug, Clone, Copy)]
+pub struct ParticipationConfig {
+	/// How queued requests are ordered.
+	///
+	/// This is a local choice which does not affect consensus. Disputes conclude sooner though if
+	/// validators work on the same ones first, so it is best changed by all of them or by none.
+	pub ordering: ParticipationOrdering,
+	/// How many best-effort requests of a single session can be queued at the same time. Further
+	/// best-effort requests of that session are dropped until participations of it are dequeued.
+	pub best_effort_session_budget: usize,
+}
+
+impl Default for ParticipationConfig {
+	fn default() -> Self {
+		Self {
+			ordering: ParticipationOrdering::default(),
+			best_effort_session_budget: BEST_EFFORT_SESSION_BUDGET,
+		}
+	}
+}
+
 /// Queues for dispute participation.
 /// In both queues we have a strict ordering of candidates and participation will
 /// happen in that order. Refer to `CandidateComparator` for details on the ordering.
+///
+/// Requests are persisted from the moment they are queued until their participation concluded,
+/// see `take_changes`.
 pub struct Queues {
 	/// Set of best effort participation requests.
 	best_effort: BTreeMap<CandidateComparator, ParticipationRequest>,
@@ -63,6 +119,21 @@ pub struct Queues {
 	/// Priority queue.
 	priority: BTreeMap<CandidateComparator, ParticipationRequest>,
 
+	/// The comparators of all queued requests, by candidate.
+	///
+	/// The rank of a request changes with the state of its dispute, so it needs to be found
+	/// without knowing the comparator it was queued with.
+	comparators: HashMap<CandidateHash, CandidateComparator>,
+
+	/// Number of best-effort requests queued per session.
+	best_effort_per_session: HashMap<SessionIndex, usize>,
+
+	/// Changes to the persisted requests since the last call to `take_changes`. `None` means
+	/// deleted.
+	changes: HashMap<(SessionIndex, CandidateHash), Option<QueuedParticipation>>,
+
+	config: ParticipationConfig,
+
 	/// Handle for recording queues data in metrics
 	metrics: Metrics,
 }
@@ -73,6 +144,10 @@ pub struct ParticipationRequest {
 	candidate_hash: CandidateHash,
 	candidate_receipt: CandidateReceipt,
 	session: SessionIndex,
+	/// Whether the candidate has been seen backed on chain.
+	is_backed: bool,
+	/// The number of votes against the candidate.
+	votes_against: u32,
 	request_timer: Option<prometheus::HistogramTimer>, // Sends metric data when request is dropped
 }
 
@@ -113,6 +188,10 @@ pub enum QueueError {
 	BestEffortFull,
 	#[error("Request could not be queued, because priority queue was already full.")]
 	PriorityFull,
+	#[error(
+		"Request could not be queued, because the best effort budget of session {0} is used up."
+	)]
+	SessionBudgetExhausted(SessionIndex),
 }
 
 impl ParticipationRequest {
@@ -122,7 +201,21 @@ impl ParticipationRequest {
 		session: SessionIndex,
 		request_timer: Option<prometheus::HistogramTimer>,
 	) -> Self {
-		Self { candidate_hash: candidate_receipt.hash(), candidate_receipt, session, request_timer }
+		Self {
+			candidate_hash: candidate_receipt.hash(),
+			candidate_receipt,
+			session,
+			is_backed: false,
+			votes_against: 0,
+			request_timer,
+		}
+	}
+
+	/// Set the state of the dispute the request is ranked by, see `ParticipationOrdering`.
+	pub fn with_dispute_state(mut self, is_backed: bool, votes_against: u32) -> Self {
+		self.is_backed = is_backed;
+		self.votes_against = votes_against;
+		self
 	}
 
 	pub fn candidate_receipt(&'_ self) -> &'_ CandidateReceipt {
@@ -143,6 +236,24 @@ impl ParticipationRequest {
 		let Self { candidate_hash, candidate_receipt, .. } = self;
 		(candidate_hash, candidate_receipt)
 	}
+
+	fn to_queued(&self, priority: bool) -> QueuedParticipation {
+		QueuedParticipation {
+			candidate_receipt: self.candidate_receipt.clone(),
+			priority,
+			is_backed: self.is_backed,
+			votes_against: self.votes_against,
+		}
+	}
+
+	/// Replace this request by an older one for the same candidate, keeping the newer dispute
+	/// state.
+	fn replace_by_older(mut self, mut older: ParticipationRequest) -> ParticipationRequest {
+		self.discard_timer();
+		older.is_backed |= self.is_backed;
+		older.votes_against = older.votes_against.max(self.votes_against);
+		older
+	}
 }
 
 // We want to compare and clone participation requests in unit tests, so we
@@ -150,8 +261,14 @@ impl ParticipationRequest {
 #[cfg(test)]
 impl PartialEq for ParticipationRequest {
 	fn eq(&self, other: &Self) -> bool {
-		let ParticipationRequest { candidate_receipt, candidate_hash, session, request_timer: _ } =
-			self;
+		let ParticipationRequest {
+			candidate_receipt,
+			candidate_hash,
+			session,
+			is_backed: _,
+			votes_against: _,
+			request_timer: _,
+		} = self;
 		candidate_receipt == other.candidate_receipt() &&
 			candidate_hash == other.candidate_hash() &&
 			*session == other.session()
@@ -162,8 +279,16 @@ impl Eq for ParticipationRequest {}
 
 impl Queues {
 	/// Create new `Queues`.
-	pub fn new(metrics: Metrics) -> Self {
-		Self { best_effort: BTreeMap::new(), priority: BTreeMap::new(), metrics }
+	pub fn new(config: ParticipationConfig, metrics: Metrics) -> Self {
+		Self {
+			best_effort: BTreeMap::new(),
+			priority: BTreeMap::new(),
+			comparators: HashMap::new(),
+			best_effort_per_session: HashMap::new(),
+			changes: HashMap::new(),
+			config,
+			metrics,
+		}
 	}
 
 	/// Will put message in queue, either priority or best effort depending on priority.
@@ -178,14 +303,27 @@ impl Queues {
 		priority: ParticipationPriority,
 		req: ParticipationRequest,
 	) -> Result<()> {
-		let comparator = CandidateComparator::new(sender, &req.candidate_receipt).await?;
+		let comparator = CandidateComparator::new(sender, &req, self.config.ordering).await?;
 
 		self.queue_with_comparator(comparator, priority, req)?;
 		Ok(())
 	}
 
+	/// The session and candidate of the queued requests, in the order of participation. Priority
+	/// requests come first, best-effort ones second.
+	pub fn queued(
+		&self,
+	) -> (Vec<(SessionIndex, CandidateHash)>, Vec<(SessionIndex, CandidateHash)>) {
+		let requests = |queue: &BTreeMap<CandidateComparator, ParticipationRequest>| {
+			queue.values().map(|req| (req.session, req.candidate_hash)).collect()
+		};
+		(requests(&self.priority), requests(&self.best_effort))
+	}
+
 	/// Get the next best request for dispute participation if any.
 	/// First the priority queue is considered and then the best effort one.
+	///
+	/// The request stays persisted until `note_concluded` is called for it.
 	pub fn dequeue(&mut self) -> Option<ParticipationRequest> {
 		if let Some(req) = self.pop_priority() {
 			self.metrics.report_priority_queue_size(self.priority.len() as u64);
@@ -199,26 +337,20 @@ impl Queues {
 	}
 
 	/// Reprioritizes any participation requests pertaining to the
-	/// passed candidates from best effort to priority.
-	pub async fn prioritize_if_present(
+	/// passed candidate from best effort to priority.
+	pub fn prioritize_if_present(
 		&mut self,
-		sender: &mut impl overseer::DisputeCoordinatorSenderTrait,
-		receipt: &CandidateReceipt,
-	) -> Result<()> {
-		let comparator = CandidateComparator::new(sender, receipt).await?;
-		self.prioritize_with_comparator(comparator)?;
-		Ok(())
-	}
-
-	fn prioritize_with_comparator(
-		&mut self,
-		comparator: CandidateComparator,
+		candidate_hash: &CandidateHash,
 	) -> std::result::Result<(), QueueError> {
+		let comparator = match self.comparators.get(candidate_hash) {
+			Some(comparator) if self.best_effort.contains_key(comparator) => *comparator,
+			_ => return Ok(()),
+		};
 		if self.priority.len() >= PRIORITY_QUEUE_SIZE {
 			return Err(QueueError::PriorityFull)
 		}
-		if let Some(request) = self.best_effort.remove(&comparator) {
-			self.priority.insert(comparator, request);
+		if let Some(request) = self.remove_best_effort(&comparator) {
+			self.insert_priority(comparator, request);
 			// Report changes to both queue sizes
 			self.metrics.report_priority_queue_size(self.priority.len() as u64);
 			self.metrics.report_best_effort_queue_size(self.best_effort.len() as u64);
@@ -226,12 +358,32 @@ impl Queues {
 		Ok(())
 	}
 
+	/// Persist a request which is participated in right away, without being queued.
+	pub fn note_running(&mut self, priority: &ParticipationPriority, req: &ParticipationRequest) {
+		self.changes
+			.insert((req.session, req.candidate_hash), Some(req.to_queued(priority.is_priority())));
+	}
+
+	/// The participation in the given candidate concluded, so its request is no longer persisted.
+	pub fn note_concluded(&mut self, session: SessionIndex, candidate_hash: CandidateHash) {
+		self.changes.insert((session, candidate_hash), None);
+	}
+
+	/// Take the changes to the persisted requests since the last call.
+	pub fn take_changes(
+		&mut self,
+	) -> impl Iterator<Item = ((SessionIndex, CandidateHash), Option<QueuedParticipation>)> {
+		std::mem::take(&mut self.changes).into_iter()
+	}
+
 	/// Will put message in queue, either priority or best effort depending on priority.
 	///
 	/// If the message was already previously present on best effort, it will be moved to priority
-	/// if it is considered priority now.
+	/// if it is considered priority now. A request which is queued already is re-ranked with the
+	/// given comparator.
 	///
-	/// Returns error in case a queue was found full already.
+	/// Returns error in case a queue was found full already, or the session's best-effort budget
+	/// is used up.
 	///
 	///  # Request timers
 	///
@@ -244,71 +396,96 @@ impl Queues {
 		priority: ParticipationPriority,
 		mut req: ParticipationRequest,
 	) -> std::result::Result<(), QueueError> {
+		let previous = self.comparators.get(&req.candidate_hash).copied();
+		let is_queued_priority = previous.map_or(false, |c| self.priority.contains_key(&c));
+
 		if priority.is_priority() {
-			if self.priority.len() >= PRIORITY_QUEUE_SIZE {
+			if !is_queued_priority && self.priority.len() >= PRIORITY_QUEUE_SIZE {
 				return Err(QueueError::PriorityFull)
 			}
-			// Remove any best effort entry, using it to replace our new
-			// request.
-			if let Some(older_request) = self.best_effort.remove(&comparator) {
-				req.discard_timer();
-				req = older_request;
+			// Remove any older entry, using it to replace our new request.
+			if let Some(older_request) = previous.and_then(|c| self.remove(&c)) {
+				req = req.replace_by_older(older_request);
 			}
-			// Keeping old request if any.
-			match self.priority.entry(comparator) {
-				Entry::Occupied(_) => req.discard_timer(),
-				Entry::Vacant(vac) => {
-					vac.insert(req);
-				},
-			}
-			self.metrics.report_priority_queue_size(self.priority.len() as u64);
-			self.metrics.report_best_effort_queue_size(self.best_effort.len() as u64);
+			// The merged dispute state may rank differently than the new one.
+			self.insert_priority(comparator.reranked(&req, self.config.ordering), req);
 		} else {
-			if self.priority.contains_key(&comparator) {
+			if is_queued_priority {
 				// The candidate is already in priority queue - don't
 				// add in in best effort too.
 				return Ok(())
 			}
-			if self.best_effort.len() >= BEST_EFFORT_QUEUE_SIZE {
-				return Err(QueueError::BestEffortFull)
-			}
-			// Keeping old request if any.
-			match self.best_effort.entry(comparator) {
-				Entry::Occupied(_) => req.discard_timer(),
-				Entry::Vacant(vac) => {
-					vac.insert(req);
+			match previous.and_then(|c| self.remove_best_effort(&c)) {
+				Some(older_request) => req = req.replace_by_older(older_request),
+				None => {
+					if self.best_effort.len() >= BEST_EFFORT_QUEUE_SIZE {
+						return Err(QueueError::BestEffortFull)
+					}
+					let queued_in_session =
+						self.best_effort_per_session.get(&req.session).copied().unwrap_or(0);
+					if queued_in_session >= self.config.best_effort_session_budget {
+						return Err(QueueError::SessionBudgetExhausted(req.session))
+					}
 				},
 			}
-			self.metrics.report_best_effort_queue_size(self.best_effort.len() as u64);
+			self.insert_best_effort(comparator.reranked(&req, self.config.ordering), req);
 		}
+		self.metrics.report_priority_queue_size(self.priority.len() as u64);
+		self.metrics.report_best_effort_queue_size(self.best_effort.len() as u64);
 		Ok(())
 	}
 
+	fn insert_priority(&mut self, comparator: CandidateComparator, req: ParticipationRequest) {
+		self.changes
+			.insert((req.session, req.candidate_hash), Some(req.to_queued(true)));
+		self.comparators.insert(req.candidate_hash, comparator);
+		self.priority.insert(comparator, req);
+	}
+
+	fn insert_best_effort(&mut self, comparator: CandidateComparator, req: ParticipationRequest) {
+		self.changes
+			.insert((req.session, req.candidate_hash), Some(req.to_queued(false)));
+		self.comparators.insert(req.candidate_hash, comparator);
+		*self.best_effort_per_session.entry(req.session).or_default() += 1;
+		self.best_effort.insert(comparator, req);
+	}
+
+	/// Remove the request with the given comparator from whatever queue it is in.
+	fn remove(&mut self, comparator: &CandidateComparator) -> Option<ParticipationRequest> {
+		match self.priority.remove(comparator) {
+			Some(req) => {
+				self.comparators.remove(&req.candidate_hash);
+				Some(req)
+			},
+			None => self.remove_best_effort(comparator),
+		}
+	}
+
+	fn remove_best_effort(
+		&mut self,
+		comparator: &CandidateComparator,
+	) -> Option<ParticipationRequest> {
+		let req = self.best_effort.remove(comparator)?;
+		self.comparators.remove(&req.candidate_hash);
+		if let Some(queued) = self.best_effort_per_session.get_mut(&req.session) {
+			*queued -= 1;
+			if *queued == 0 {
+				self.best_effort_per_session.remove(&req.session);
+			}
+		}
+		Some(req)
+	}
+
 	/// Get best from the best effort queue.
 	fn pop_best_effort(&mut self) -> Option<(CandidateComparator, ParticipationRequest)> {
-		return Self::pop_impl(&mut self.best_effort)
+		let comparator = *self.best_effort.keys().next()?;
+		self.remove_best_effort(&comparator).map(|req| (comparator, req))
 	}
 
 	/// Get best priority queue entry.
 	fn pop_priority(&mut self) -> Option<(CandidateComparator, ParticipationRequest)> {
-		return Self::pop_impl(&mut self.priority)
-	}
-
-	// `pop_best_effort` and `pop_priority` do the same but on different `BTreeMap`s. This function has
-	// the extracted implementation
-	fn pop_impl(
-		target: &mut BTreeMap<CandidateComparator, ParticipationRequest>,
-	) -> Option<(CandidateComparator, ParticipationRequest)> {
-		// Once https://github.com/rust-lang/rust/issues/62924 is there, we can use a simple:
-		// target.pop_first().
-		if let Some((comparator, _)) = target.iter().next() {
-			let comparator = *comparator;
-			target
-				.remove(&comparator)
-				.map(|participation_request| (comparator, participation_request))
-		} else {
-			None
-		}
+		let comparator = *self.priority.keys().next()?;
+		self.remove(&comparator).map(|req| (comparator, req))
 	}
 }
 
@@ -328,9 +505,15 @@ impl Queues {
 /// treated as low priority when it comes to disputes, as even in the case of a negative outcome,
 /// we are already too late. The ordering mechanism here serves to prevent this from happening in
 /// the first place.
+///
+/// Depending on the configured `ParticipationOrdering`, candidates are ranked by the state of their
+/// dispute first and only candidates of equal rank are ordered by age.
 #[derive(Copy, Clone)]
 #[cfg_attr(test, derive(Debug))]
 struct CandidateComparator {
+	/// The rank of the candidate according to the configured `ParticipationOrdering`, lower ranks
+	/// are participated in first.
+	rank: u32,
 	/// Block number of the relay parent. It's wrapped in an `Option<>` because there are cases when
 	/// it can't be obtained. For example when the node is lagging behind and new leaves are received
 	/// with a slight delay. Candidates with unknown relay parent are treated with the lowest priority.
@@ -358,23 +541,40 @@ impl CandidateComparator {
 	/// Useful for testing.
 	#[cfg(test)]
 	pub fn new_dummy(block_number: Option<BlockNumber>, candidate_hash: CandidateHash) -> Self {
-		Self { relay_parent_block_number: block_number, candidate_hash }
+		Self { rank: 0, relay_parent_block_number: block_number, candidate_hash }
 	}
 
-	/// Create a candidate comparator for a given candidate.
+	/// Create a ranked candidate comparator based on given (fake) values.
+	///
+	/// Useful for testing.
+	#[cfg(test)]
+	pub fn new_dummy_ranked(
+		req: &ParticipationRequest,
+		ordering: ParticipationOrdering,
+		block_number: Option<BlockNumber>,
+	) -> Self {
+		Self {
+			rank: Self::rank(req, ordering),
+			relay_parent_block_number: block_number,
+			candidate_hash: req.candidate_hash,
+		}
+	}
+
+	/// Create a candidate comparator for the candidate of a given request.
 	///
 	/// Returns:
-	///	- `Ok(CandidateComparator{Some(relay_parent_block_number), candidate_hash})` when the
+	///	- `Ok(CandidateComparator{rank, Some(relay_parent_block_number), candidate_hash})` when the
 	/// 	relay parent can be obtained. This is the happy case.
-	/// - `Ok(CandidateComparator{None, candidate_hash})` in case the candidate's relay parent
+	/// - `Ok(CandidateComparator{rank, None, candidate_hash})` in case the candidate's relay parent
 	/// 	can't be obtained.
 	///	- `FatalError` in case the chain API call fails with an unexpected error.
 	pub async fn new(
 		sender: &mut impl overseer::DisputeCoordinatorSenderTrait,
-		candidate: &CandidateReceipt,
+		req: &ParticipationRequest,
+		ordering: ParticipationOrdering,
 	) -> FatalResult<Self> {
-		let candidate_hash = candidate.hash();
-		let n = get_block_number(sender, candidate.descriptor().relay_parent).await?;
+		let candidate_hash = req.candidate_hash;
+		let n = get_block_number(sender, req.candidate_receipt.descriptor().relay_parent).await?;
 
 		if n.is_none() {
 			gum::warn!(
@@ -385,7 +585,24 @@ impl CandidateComparator {
 			);
 		}
 
-		Ok(CandidateComparator { relay_parent_block_number: n, candidate_hash })
+		Ok(CandidateComparator {
+			rank: Self::rank(req, ordering),
+			relay_parent_block_number: n,
+			candidate_hash,
+		})
+	}
+
+	/// The comparator with the rank of the given request.
+	fn reranked(self, req: &ParticipationRequest, ordering: ParticipationOrdering) -> Self {
+		Self { rank: Self::rank(req, ordering), ..self }
+	}
+
+	fn rank(req: &ParticipationRequest, ordering: ParticipationOrdering) -> u32 {
+		match ordering {
+			ParticipationOrdering::RelayParentAge => 0,
+			ParticipationOrdering::BackedFirst => (!req.is_backed).into(),
+			ParticipationOrdering::MostVotesAgainstFirst => u32::MAX - req.votes_against,
+		}
 	}
 }
 
@@ -405,6 +622,9 @@ impl PartialOrd for CandidateComparator {
 
 impl Ord for CandidateComparator {
 	fn cmp(&self, other: &Self) -> Ordering {
+		if self.rank != other.rank {
+			return self.rank.cmp(&other.rank)
+		}
 		return match (self.relay_parent_block_number, other.relay_parent_block_number) {
 			(None, None) => {
 				// No relay parents for both -> compare hashes
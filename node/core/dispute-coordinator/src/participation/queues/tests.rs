@@ -17,9 +17,12 @@
 use crate::{metrics::Metrics, ParticipationPriority};
 use ::test_helpers::{dummy_candidate_receipt, dummy_hash};
 use assert_matches::assert_matches;
-use polkadot_primitives::{BlockNumber, Hash};
+use polkadot_primitives::{BlockNumber, Hash, SessionIndex};
 
-use super::{CandidateComparator, ParticipationRequest, QueueError, Queues};
+use super::{
+	CandidateComparator, ParticipationConfig, ParticipationOrdering, ParticipationRequest,
+	QueueError, Queues,
+};
 
 /// Make a `ParticipationRequest` based on the given commitments hash.
 fn make_participation_request(hash: Hash) -> ParticipationRequest {
@@ -30,6 +33,13 @@ fn make_participation_request(hash: Hash) -> ParticipationRequest {
 	ParticipationRequest::new(receipt, 1, request_timer)
 }
 
+/// Make a `ParticipationRequest` in the given session.
+fn make_session_request(hash: Hash, session: SessionIndex) -> ParticipationRequest {
+	let mut req = make_participation_request(hash);
+	req.session = session;
+	req
+}
+
 /// Make dummy comparator for request, based on the given block number.
 fn make_dummy_comparator(
 	req: &ParticipationRequest,
@@ -46,6 +56,8 @@ fn clone_request(request: &ParticipationRequest) -> ParticipationRequest {
 		candidate_receipt: request.candidate_receipt.clone(),
 		candidate_hash: request.candidate_hash.clone(),
 		session: request.session,
+		is_backed: request.is_backed,
+		votes_against: request.votes_against,
 		request_timer: None,
 	}
 }
@@ -58,7 +70,7 @@ fn clone_request(request: &ParticipationRequest) -> ParticipationRequest {
 #[test]
 fn ordering_works_as_expected() {
 	let metrics = Metrics::default();
-	let mut queue = Queues::new(metrics.clone());
+	let mut queue = Queues::new(Default::default(), metrics.clone());
 	let req1 = make_participation_request(Hash::repeat_byte(0x01));
 	let req_prio = make_participation_request(Hash::repeat_byte(0x02));
 	let req3 = make_participation_request(Hash::repeat_byte(0x03));
@@ -133,7 +145,7 @@ fn ordering_works_as_expected() {
 #[test]
 fn candidate_is_only_dequeued_once() {
 	let metrics = Metrics::default();
-	let mut queue = Queues::new(metrics.clone());
+	let mut queue = Queues::new(Default::default(), metrics.clone());
 	let req1 = make_participation_request(Hash::repeat_byte(0x01));
 	let req_prio = make_participation_request(Hash::repeat_byte(0x02));
 	let req_best_effort_then_prio = make_participation_request(Hash::repeat_byte(0x03));
@@ -211,3 +223,218 @@ fn candidate_is_only_dequeued_once() {
 	assert_eq!(queue.dequeue(), Some(req1));
 	assert_matches!(queue.dequeue(), None);
 }
+
+/// The queued requests are listed in the order they are going to be dequeued.
+#[test]
+fn queued_lists_requests_in_participation_order() {
+	let mut queue = Queues::new(Default::default(), Metrics::default());
+	let req1 = make_participation_request(Hash::repeat_byte(0x01));
+	let req2 = make_participation_request(Hash::repeat_byte(0x02));
+	let req_prio = make_participation_request(Hash::repeat_byte(0x03));
+
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req1, Some(2)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req1),
+		)
+		.unwrap();
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req2, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req2),
+		)
+		.unwrap();
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req_prio, Some(3)),
+			ParticipationPriority::Priority,
+			clone_request(&req_prio),
+		)
+		.unwrap();
+
+	let (priority, best_effort) = queue.queued();
+	assert_eq!(priority, vec![(1, *req_prio.candidate_hash())]);
+	assert_eq!(best_effort, vec![(1, *req2.candidate_hash()), (1, *req1.candidate_hash())]);
+
+	assert_eq!(queue.dequeue(), Some(req_prio));
+	assert_eq!(queue.dequeue(), Some(req2));
+	assert_eq!(queue.dequeue(), Some(req1));
+}
+
+/// Best-effort requests of a session are limited by the session budget, while priority requests
+/// and requests of other sessions are not.
+#[test]
+fn session_budget_limits_best_effort_requests() {
+	let config = ParticipationConfig { best_effort_session_budget: 1, ..Default::default() };
+	let mut queue = Queues::new(config, Metrics::default());
+	let req1 = make_session_request(Hash::repeat_byte(0x01), 1);
+	let req_spam = make_session_request(Hash::repeat_byte(0x02), 1);
+	let req_prio = make_session_request(Hash::repeat_byte(0x03), 1);
+	let req_other_session = make_session_request(Hash::repeat_byte(0x04), 2);
+
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req1, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req1),
+		)
+		.unwrap();
+	assert_matches!(
+		queue.queue_with_comparator(
+			make_dummy_comparator(&req_spam, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req_spam),
+		),
+		Err(QueueError::SessionBudgetExhausted(1))
+	);
+	// Re-queuing an already queued request does not use up more of the budget:
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req1, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req1),
+		)
+		.unwrap();
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req_prio, Some(1)),
+			ParticipationPriority::Priority,
+			clone_request(&req_prio),
+		)
+		.unwrap();
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req_other_session, Some(2)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req_other_session),
+		)
+		.unwrap();
+
+	assert_eq!(queue.dequeue(), Some(req_prio));
+	assert_eq!(queue.dequeue(), Some(req1));
+
+	// The budget is freed again once requests got dequeued:
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req_spam, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req_spam),
+		)
+		.unwrap();
+}
+
+/// The configured ordering takes precedence over the relay parent age, and re-queuing a request
+/// with a changed dispute state re-ranks it.
+#[test]
+fn ordering_policy_ranks_and_reranks_requests() {
+	let ordering = ParticipationOrdering::MostVotesAgainstFirst;
+	let config = ParticipationConfig { ordering, ..Default::default() };
+	let mut queue = Queues::new(config, Metrics::default());
+	let req_old = make_participation_request(Hash::repeat_byte(0x01)).with_dispute_state(false, 1);
+	let req_new = make_participation_request(Hash::repeat_byte(0x02)).with_dispute_state(false, 2);
+
+	queue
+		.queue_with_comparator(
+			CandidateComparator::new_dummy_ranked(&req_old, ordering, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req_old),
+		)
+		.unwrap();
+	queue
+		.queue_with_comparator(
+			CandidateComparator::new_dummy_ranked(&req_new, ordering, Some(2)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req_new),
+		)
+		.unwrap();
+	assert_eq!(
+		queue.queued().1,
+		vec![(1, *req_new.candidate_hash()), (1, *req_old.candidate_hash())]
+	);
+
+	// More votes against the older candidate come in:
+	let req_old = clone_request(&req_old).with_dispute_state(false, 3);
+	queue
+		.queue_with_comparator(
+			CandidateComparator::new_dummy_ranked(&req_old, ordering, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req_old),
+		)
+		.unwrap();
+
+	assert_eq!(queue.dequeue(), Some(req_old));
+	assert_eq!(queue.dequeue(), Some(req_new));
+	assert_matches!(queue.dequeue(), None);
+
+	// Backed candidates are preferred with `BackedFirst`:
+	let ordering = ParticipationOrdering::BackedFirst;
+	let req_backed =
+		make_participation_request(Hash::repeat_byte(0x03)).with_dispute_state(true, 0);
+	let req_unbacked = make_participation_request(Hash::repeat_byte(0x04));
+	assert!(
+		CandidateComparator::new_dummy_ranked(&req_backed, ordering, Some(2)) <
+			CandidateComparator::new_dummy_ranked(&req_unbacked, ordering, Some(1))
+	);
+}
+
+/// A re-queued request is ranked by its merged dispute state, not by the one it was re-queued with.
+#[test]
+fn requeued_request_is_ranked_by_merged_dispute_state() {
+	let ordering = ParticipationOrdering::MostVotesAgainstFirst;
+	let config = ParticipationConfig { ordering, ..Default::default() };
+	let mut queue = Queues::new(config, Metrics::default());
+	let req_a = make_participation_request(Hash::repeat_byte(0x01)).with_dispute_state(false, 3);
+	let req_b = make_participation_request(Hash::repeat_byte(0x02)).with_dispute_state(false, 2);
+
+	for req in [&req_a, &req_b] {
+		queue
+			.queue_with_comparator(
+				CandidateComparator::new_dummy_ranked(req, ordering, Some(1)),
+				ParticipationPriority::BestEffort,
+				clone_request(req),
+			)
+			.unwrap();
+	}
+
+	// A stale view of the dispute of `req_a` comes in, the votes already known are kept:
+	let req_a_stale = clone_request(&req_a).with_dispute_state(false, 1);
+	queue
+		.queue_with_comparator(
+			CandidateComparator::new_dummy_ranked(&req_a_stale, ordering, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req_a_stale),
+		)
+		.unwrap();
+
+	assert_eq!(queue.queued().1, vec![(1, *req_a.candidate_hash()), (1, *req_b.candidate_hash())]);
+}
+
+/// Queued requests are persisted until their participation concluded.
+#[test]
+fn changes_track_persisted_requests() {
+	let mut queue = Queues::new(Default::default(), Metrics::default());
+	let req = make_participation_request(Hash::repeat_byte(0x01));
+	let key = (req.session(), *req.candidate_hash());
+
+	queue
+		.queue_with_comparator(
+			make_dummy_comparator(&req, Some(1)),
+			ParticipationPriority::BestEffort,
+			clone_request(&req),
+		)
+		.unwrap();
+	queue.prioritize_if_present(req.candidate_hash()).unwrap();
+
+	let changes: Vec<_> = queue.take_changes().collect();
+	assert_eq!(changes, vec![(key, Some(req.to_queued(true)))]);
+
+	// Dequeuing keeps the request persisted, as the participation is still running:
+	assert_eq!(queue.dequeue(), Some(clone_request(&req)));
+	assert_eq!(queue.take_changes().count(), 0);
+
+	queue.note_concluded(key.0, key.1);
+	let changes: Vec<_> = queue.take_changes().collect();
+	assert_eq!(changes, vec![(key, None)]);
+}
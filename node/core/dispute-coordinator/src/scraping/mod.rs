@@ -67,16 +67,13 @@ const LRU_OBSERVED_BLOCKS_CAPACITY: NonZeroUsize = match NonZeroUsize::new(20) {
 pub struct ScrapedUpdates {
 	pub on_chain_votes: Vec<ScrapedOnChainVotes>,
 	pub included_receipts: Vec<CandidateReceipt>,
-	pub unapplied_slashes: Vec<(SessionIndex, CandidateHash, PendingSlashes)>,
+	/// `None` if the unapplied slashes could not be fetched from the runtime.
+	pub unapplied_slashes: Option<Vec<(SessionIndex, CandidateHash, PendingSlashes)>>,
 }
 
 impl ScrapedUpdates {
 	pub fn new() -> Self {
-		Self {
-			on_chain_votes: Vec::new(),
-			included_receipts: Vec::new(),
-			unapplied_slashes: Vec::new(),
-		}
+		Self { on_chain_votes: Vec::new(), included_receipts: Vec::new(), unapplied_slashes: None }
 	}
 }
 
@@ -268,7 +265,7 @@ impl ChainScraper {
 		// it should accumulate them all
 		match get_unapplied_slashes(sender, activated.hash).await {
 			Ok(unapplied_slashes) => {
-				scraped_updates.unapplied_slashes = unapplied_slashes;
+				scraped_updates.unapplied_slashes = Some(unapplied_slashes);
 			},
 			Err(runtime::Error::RuntimeRequest(RuntimeApiError::NotSupported { .. })) => {
 				gum::debug!(
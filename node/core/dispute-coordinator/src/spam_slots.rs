@@ -127,6 +127,18 @@ impl SpamSlots {
 			}
 		}
 	}
+
+	/// The occupied spam slots per session and validator, ordered by session and validator.
+	pub fn occupied(&self) -> Vec<(SessionIndex, ValidatorIndex, SpamCount)> {
+		let mut occupied: Vec<_> = self
+			.slots
+			.iter()
+			.map(|((session, validator), count)| (*session, *validator, *count))
+			.collect();
+		occupied.sort();
+		occupied
+	}
+
 	/// Prune all spam slots for sessions older than the given index.
 	pub fn prune_old(&mut self, oldest_index: SessionIndex) {
 		self.unconfirmed.retain(|(session, _), _| *session >= oldest_index);
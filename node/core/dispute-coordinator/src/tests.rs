@@ -41,7 +41,7 @@ use polkadot_node_subsystem::{
 		DisputeDistributionMessage, ImportStatementsResult,
 	},
 	overseer::FromOrchestra,
-	OverseerSignal,
+	OverseerSignal, RuntimeApiError,
 };
 
 use polkadot_node_subsystem_util::TimeoutExt;
@@ -62,10 +62,10 @@ use polkadot_node_subsystem_test_helpers::{
 	make_buffered_subsystem_context, TestSubsystemContextHandle,
 };
 use polkadot_primitives::{
-	ApprovalVote, BlockNumber, CandidateCommitments, CandidateEvent, CandidateHash,
-	CandidateReceipt, CoreIndex, DisputeStatement, GroupIndex, Hash, HeadData, Header, IndexedVec,
-	MultiDisputeStatementSet, ScrapedOnChainVotes, SessionIndex, SessionInfo, SigningContext,
-	ValidDisputeStatementKind, ValidatorId, ValidatorIndex, ValidatorSignature,
+	vstaging::slashing, ApprovalVote, BlockNumber, CandidateCommitments, CandidateEvent,
+	CandidateHash, CandidateReceipt, CoreIndex, DisputeStatement, GroupIndex, Hash, HeadData,
+	Header, IndexedVec, MultiDisputeStatementSet, ScrapedOnChainVotes, SessionIndex, SessionInfo,
+	SigningContext, ValidDisputeStatementKind, ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 
 use crate::{
@@ -177,6 +177,9 @@ struct TestState {
 	last_block: Hash,
 	// last session the subsystem knows about.
 	known_session: Option<SessionIndex>,
+	// unapplied slashes returned by the runtime on leaf activation.
+	unapplied_slashes:
+		Result<Vec<(SessionIndex, CandidateHash, slashing::PendingSlashes)>, RuntimeApiError>,
 }
 
 impl Default for TestState {
@@ -217,7 +220,7 @@ impl Default for TestState {
 		let db = kvdb_memorydb::create(1);
 		let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[0]);
 		let db = Arc::new(db);
-		let config = Config { col_dispute_data: 0 };
+		let config = Config { col_dispute_data: 0, participation: Default::default() };
 
 		let genesis_header = Header {
 			parent_hash: Hash::zero(),
@@ -246,6 +249,7 @@ impl Default for TestState {
 			block_num_to_header,
 			last_block,
 			known_session: None,
+			unapplied_slashes: Ok(Vec::new()),
 		}
 	}
 }
@@ -389,7 +393,7 @@ impl TestState {
 					_new_leaf,
 					RuntimeApiRequest::UnappliedSlashes(tx),
 				)) => {
-					tx.send(Ok(Vec::new())).unwrap();
+					tx.send(Ok(self.unapplied_slashes.clone())).unwrap();
 				},
 				AllMessages::ChainApi(ChainApiMessage::Ancestors { hash, k, response_channel }) => {
 					let target_header = self
@@ -651,7 +655,7 @@ fn make_candidate_included_event(candidate_receipt: CandidateReceipt) -> Candida
 pub async fn handle_approval_vote_request(
 	ctx_handle: &mut VirtualOverseer,
 	expected_hash: &CandidateHash,
-	votes_to_send: HashMap<ValidatorIndex, ValidatorSignature>,
+	votes_to_send: HashMap<ValidatorIndex, (Vec<CandidateHash>, ValidatorSignature)>,
 ) {
 	assert_matches!(
 		ctx_handle.recv().await,
@@ -677,6 +681,68 @@ async fn handle_get_block_number(ctx_handle: &mut VirtualOverseer, test_state: &
 	)
 }
 
+fn make_pending_slashes(
+	test_state: &TestState,
+	validators: &[ValidatorIndex],
+) -> slashing::PendingSlashes {
+	slashing::PendingSlashes {
+		keys: validators
+			.iter()
+			.map(|index| (*index, test_state.validator_public.get(*index).unwrap().clone()))
+			.collect(),
+		kind: slashing::SlashingOffenceKind::ForInvalid,
+	}
+}
+
+fn make_key_ownership_proof(index: ValidatorIndex) -> slashing::OpaqueKeyOwnershipProof {
+	slashing::OpaqueKeyOwnershipProof::new(index.0.to_le_bytes().to_vec())
+}
+
+/// Handle key ownership proof requests for the given validators, in order.
+async fn handle_key_ownership_proof_requests(
+	virtual_overseer: &mut VirtualOverseer,
+	test_state: &TestState,
+	expected_relay_parent: Hash,
+	validators: &[ValidatorIndex],
+) {
+	for index in validators {
+		assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				relay_parent,
+				RuntimeApiRequest::KeyOwnershipProof(validator_id, tx),
+			)) => {
+				assert_eq!(relay_parent, expected_relay_parent);
+				assert_eq!(Some(&validator_id), test_state.validator_public.get(*index));
+				tx.send(Ok(Some(make_key_ownership_proof(*index)))).unwrap();
+			}
+		);
+	}
+}
+
+/// Handle a single slashing report submission and answer it with `response`.
+async fn handle_slashing_report(
+	virtual_overseer: &mut VirtualOverseer,
+	expected_relay_parent: Hash,
+	expected_candidate_hash: CandidateHash,
+	expected_index: ValidatorIndex,
+	response: Result<Option<()>, RuntimeApiError>,
+) {
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			relay_parent,
+			RuntimeApiRequest::SubmitReportDisputeLost(dispute_proof, key_ownership_proof, tx),
+		)) => {
+			assert_eq!(relay_parent, expected_relay_parent);
+			assert_eq!(dispute_proof.time_slot.candidate_hash, expected_candidate_hash);
+			assert_eq!(dispute_proof.validator_index, expected_index);
+			assert_eq!(key_ownership_proof, make_key_ownership_proof(expected_index));
+			tx.send(response).unwrap();
+		}
+	);
+}
+
 #[test]
 fn too_many_unconfirmed_statements_are_considered_spam() {
 	test_harness(|mut test_state, mut virtual_overseer| {
@@ -857,9 +923,12 @@ fn approval_vote_import_works() {
 				.await;
 			gum::trace!("After sending `ImportStatements`");
 
-			let approval_votes = [(ValidatorIndex(4), approval_vote.into_validator_signature())]
-				.into_iter()
-				.collect();
+			let approval_votes = [(
+				ValidatorIndex(4),
+				(vec![candidate_hash1], approval_vote.into_validator_signature()),
+			)]
+			.into_iter()
+			.collect();
 
 			handle_approval_vote_request(&mut virtual_overseer, &candidate_hash1, approval_votes)
 				.await;
@@ -3573,3 +3642,253 @@ fn session_info_small_jump_works() {
 		})
 	});
 }
+
+#[test]
+fn unapplied_slashes_are_reported_only_once() {
+	test_harness(|mut test_state, mut virtual_overseer| {
+		Box::pin(async move {
+			let session = 1;
+
+			test_state.handle_resume_sync(&mut virtual_overseer, session).await;
+
+			let candidate_receipt = make_valid_candidate_receipt();
+			let candidate_hash = candidate_receipt.hash();
+			let slashed = [ValidatorIndex(2), ValidatorIndex(3)];
+
+			test_state
+				.activate_leaf_at_session(
+					&mut virtual_overseer,
+					session,
+					3,
+					vec![make_candidate_included_event(candidate_receipt)],
+				)
+				.await;
+			let inclusion_block = test_state.last_block;
+
+			test_state.unapplied_slashes =
+				Ok(vec![(session, candidate_hash, make_pending_slashes(&test_state, &slashed))]);
+
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 4, Vec::new())
+				.await;
+			let leaf = test_state.last_block;
+
+			// Key ownership proofs are generated at the block including the candidate ...
+			handle_key_ownership_proof_requests(
+				&mut virtual_overseer,
+				&test_state,
+				inclusion_block,
+				&slashed,
+			)
+			.await;
+			// ... and the reports are submitted on the new leaf.
+			for index in slashed {
+				handle_slashing_report(
+					&mut virtual_overseer,
+					leaf,
+					candidate_hash,
+					index,
+					Ok(Some(())),
+				)
+				.await;
+			}
+
+			// The slashes are still pending on the next leaf, but must not be reported again.
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 5, Vec::new())
+				.await;
+			// Any report for the previous leaf would arrive before the queries for this one.
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 6, Vec::new())
+				.await;
+
+			virtual_overseer.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+
+			// No more messages expected:
+			assert!(virtual_overseer.try_recv().await.is_none());
+
+			test_state
+		})
+	});
+}
+
+#[test]
+fn reported_slashes_are_kept_when_fetching_unapplied_slashes_fails() {
+	test_harness(|mut test_state, mut virtual_overseer| {
+		Box::pin(async move {
+			let session = 1;
+
+			test_state.handle_resume_sync(&mut virtual_overseer, session).await;
+
+			let candidate_receipt = make_valid_candidate_receipt();
+			let candidate_hash = candidate_receipt.hash();
+			let slashed = [ValidatorIndex(2), ValidatorIndex(3)];
+
+			test_state
+				.activate_leaf_at_session(
+					&mut virtual_overseer,
+					session,
+					3,
+					vec![make_candidate_included_event(candidate_receipt)],
+				)
+				.await;
+			let inclusion_block = test_state.last_block;
+
+			let unapplied_slashes =
+				vec![(session, candidate_hash, make_pending_slashes(&test_state, &slashed))];
+			test_state.unapplied_slashes = Ok(unapplied_slashes.clone());
+
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 4, Vec::new())
+				.await;
+			let leaf = test_state.last_block;
+
+			handle_key_ownership_proof_requests(
+				&mut virtual_overseer,
+				&test_state,
+				inclusion_block,
+				&slashed,
+			)
+			.await;
+			for index in slashed {
+				handle_slashing_report(
+					&mut virtual_overseer,
+					leaf,
+					candidate_hash,
+					index,
+					Ok(Some(())),
+				)
+				.await;
+			}
+
+			// The runtime fails to provide the unapplied slashes on the next leaf ...
+			test_state.unapplied_slashes = Err(RuntimeApiError::Execution {
+				runtime_api_name: "unapplied_slashes",
+				source: Arc::new(std::io::Error::new(std::io::ErrorKind::Other, "faux")),
+			});
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 5, Vec::new())
+				.await;
+
+			// ... which must not make us forget about the reports already submitted.
+			test_state.unapplied_slashes = Ok(unapplied_slashes);
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 6, Vec::new())
+				.await;
+			// Any report for the previous leaf would arrive before the queries for this one.
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 7, Vec::new())
+				.await;
+
+			virtual_overseer.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+
+			// No more messages expected:
+			assert!(virtual_overseer.try_recv().await.is_none());
+
+			test_state
+		})
+	});
+}
+
+#[test]
+fn failed_slashing_reports_are_retried() {
+	test_harness(|mut test_state, mut virtual_overseer| {
+		Box::pin(async move {
+			let session = 1;
+
+			test_state.handle_resume_sync(&mut virtual_overseer, session).await;
+
+			let candidate_receipt = make_valid_candidate_receipt();
+			let candidate_hash = candidate_receipt.hash();
+			let slashed = [ValidatorIndex(2), ValidatorIndex(3)];
+
+			test_state
+				.activate_leaf_at_session(
+					&mut virtual_overseer,
+					session,
+					3,
+					vec![make_candidate_included_event(candidate_receipt)],
+				)
+				.await;
+			let inclusion_block = test_state.last_block;
+
+			// Slashes for a candidate we never saw included must not keep us from reporting the
+			// rest.
+			let unknown_candidate_hash = CandidateHash(Hash::repeat_byte(0xff));
+			test_state.unapplied_slashes = Ok(vec![
+				(
+					session,
+					unknown_candidate_hash,
+					make_pending_slashes(&test_state, &[ValidatorIndex(4)]),
+				),
+				(session, candidate_hash, make_pending_slashes(&test_state, &slashed)),
+			]);
+
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 4, Vec::new())
+				.await;
+			let leaf = test_state.last_block;
+
+			handle_key_ownership_proof_requests(
+				&mut virtual_overseer,
+				&test_state,
+				inclusion_block,
+				&slashed,
+			)
+			.await;
+			handle_slashing_report(
+				&mut virtual_overseer,
+				leaf,
+				candidate_hash,
+				slashed[0],
+				Err(RuntimeApiError::Execution {
+					runtime_api_name: "submit_report_dispute_lost",
+					source: Arc::new(std::io::Error::new(std::io::ErrorKind::Other, "faux")),
+				}),
+			)
+			.await;
+			// The transaction pool already knows about this one.
+			handle_slashing_report(
+				&mut virtual_overseer,
+				leaf,
+				candidate_hash,
+				slashed[1],
+				Ok(None),
+			)
+			.await;
+
+			// Only the failed report is submitted again.
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 5, Vec::new())
+				.await;
+			let leaf = test_state.last_block;
+
+			handle_key_ownership_proof_requests(
+				&mut virtual_overseer,
+				&test_state,
+				inclusion_block,
+				&slashed[..1],
+			)
+			.await;
+			handle_slashing_report(
+				&mut virtual_overseer,
+				leaf,
+				candidate_hash,
+				slashed[0],
+				Ok(Some(())),
+			)
+			.await;
+
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 6, Vec::new())
+				.await;
+
+			virtual_overseer.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+
+			// No more messages expected:
+			assert!(virtual_overseer.try_recv().await.is_none());
+
+			test_state
+		})
+	});
+}
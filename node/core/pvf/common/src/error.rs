@@ -43,6 +43,12 @@ pub enum PrepareError {
 	/// The response from the worker is received, but the file cannot be renamed (moved) to the final destination
 	/// location. This state is reported by the validation host (not by the worker).
 	RenameTmpFileErr(String),
+	/// The worker was killed by seccomp for making a syscall that is not in the allowlist. This state is reported by
+	/// the validation host (not by the worker).
+	ForbiddenSyscall,
+	/// The worker was killed for exceeding the memory limit of its sandbox. This state is reported by the validation
+	/// host (not by the worker).
+	OutOfMemory,
 }
 
 impl PrepareError {
@@ -57,6 +63,11 @@ impl PrepareError {
 		match self {
 			Prevalidation(_) | Preparation(_) | Panic(_) => true,
 			TimedOut | IoErr(_) | CreateTmpFileErr(_) | RenameTmpFileErr(_) => false,
+			// Depends on whether seccomp is enabled on this node, and the allowlist may be
+			// incomplete for the local environment.
+			ForbiddenSyscall => false,
+			// The memory limit of the sandbox is set by the node, not by the PVF.
+			OutOfMemory => false,
 			// Can occur due to issues with the PVF, but also due to local errors.
 			RuntimeConstruction(_) => false,
 		}
@@ -75,6 +86,8 @@ impl fmt::Display for PrepareError {
 			IoErr(err) => write!(f, "prepare: io error while receiving response: {}", err),
 			CreateTmpFileErr(err) => write!(f, "prepare: error creating tmp file: {}", err),
 			RenameTmpFileErr(err) => write!(f, "prepare: error renaming tmp file: {}", err),
+			ForbiddenSyscall => write!(f, "prepare: worker made a forbidden syscall"),
+			OutOfMemory => write!(f, "prepare: worker exceeded the memory limit of its sandbox"),
 		}
 	}
 }
@@ -93,6 +106,9 @@ pub enum InternalValidationError {
 	CpuTimeMonitorThread(String),
 	/// Some non-deterministic preparation error occurred.
 	NonDeterministicPrepareError(PrepareError),
+	/// The worker was killed for exceeding the memory limit of its sandbox. The limit is set by the
+	/// node, so this is not evidence against the candidate.
+	OutOfMemory,
 }
 
 impl fmt::Display for InternalValidationError {
@@ -106,6 +122,8 @@ impl fmt::Display for InternalValidationError {
 			CpuTimeMonitorThread(err) =>
 				write!(f, "validation: an error occurred in the CPU time monitor thread: {}", err),
 			NonDeterministicPrepareError(err) => write!(f, "validation: prepare: {}", err),
+			OutOfMemory =>
+				write!(f, "validation: the worker exceeded the memory limit of its sandbox"),
 		}
 	}
 }
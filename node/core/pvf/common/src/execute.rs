@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::error::InternalValidationError;
+use crate::{error::InternalValidationError, prepare::MemoryStats};
 use parity_scale_codec::{Decode, Encode};
 use polkadot_parachain::primitives::ValidationResult;
 use polkadot_primitives::ExecutorParams;
@@ -37,6 +37,8 @@ pub enum Response {
 		result_descriptor: ValidationResult,
 		/// The amount of CPU time taken by the job.
 		duration: Duration,
+		/// The observed memory statistics for the job.
+		memory_stats: MemoryStats,
 	},
 	/// The candidate is invalid.
 	InvalidCandidate(String),
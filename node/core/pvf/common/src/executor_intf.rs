@@ -120,6 +120,26 @@ pub fn params_to_wasmtime_semantics(par: &ExecutorParams) -> Result<Semantics, S
 	Ok(sem)
 }
 
+/// Returns the maximum number of bytes that the linear memory and the native stack of a PVF may
+/// take up when it is executed with the given parameters.
+pub fn params_to_max_execution_memory(par: &ExecutorParams) -> Result<u64, String> {
+	// The maximum number of pages a 32-bit linear memory can have.
+	const MAX_WASM_PAGES: u32 = 65536;
+	const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+	let sem = params_to_wasmtime_semantics(par)?;
+	let max_pages = match sem.heap_alloc_strategy {
+		HeapAllocStrategy::Dynamic { maximum_pages } => maximum_pages.unwrap_or(MAX_WASM_PAGES),
+		HeapAllocStrategy::Static { extra_pages } =>
+			DEFAULT_HEAP_PAGES_ESTIMATE.saturating_add(extra_pages),
+	};
+	let native_stack_max = sem
+		.deterministic_stack_limit
+		.map_or(NATIVE_STACK_MAX, |stack_limit| stack_limit.native_stack_max);
+
+	Ok(u64::from(max_pages) * WASM_PAGE_SIZE + u64::from(native_stack_max))
+}
+
 /// A WASM executor with a given configuration. It is instantiated once per execute worker and is
 /// specific to that worker.
 #[derive(Clone)]
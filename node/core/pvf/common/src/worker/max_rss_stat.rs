@@ -0,0 +1,60 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dealing with the `ru_maxrss` (peak resident memory) stat from `getrusage`.
+//!
+//! NOTE: `getrusage` with the `RUSAGE_THREAD` parameter is only supported on Linux. `RUSAGE_SELF`
+//! works on MacOS, but we need to get the max rss only for the thread running the job. Getting it
+//! for the current process would conflate the stats of previous jobs run by the process.
+
+use crate::LOG_TARGET;
+use core::mem::MaybeUninit;
+use libc::{getrusage, rusage, RUSAGE_THREAD};
+use std::io;
+
+/// Get the rusage stats for the current thread.
+fn getrusage_thread() -> io::Result<rusage> {
+	let mut result: MaybeUninit<rusage> = MaybeUninit::zeroed();
+
+	// SAFETY: `result` is a valid pointer, so calling this is safe.
+	if unsafe { getrusage(RUSAGE_THREAD, result.as_mut_ptr()) } == -1 {
+		return Err(io::Error::last_os_error())
+	}
+
+	// SAFETY: `result` was successfully initialized by `getrusage`.
+	unsafe { Ok(result.assume_init()) }
+}
+
+/// Gets the `ru_maxrss` for the current thread.
+pub fn get_max_rss_thread() -> io::Result<i64> {
+	// `c_long` is either `i32` or `i64` depending on architecture. `i64::from` always works.
+	getrusage_thread().map(|rusage| i64::from(rusage.ru_maxrss))
+}
+
+/// Extracts the max_rss stat and logs any error.
+pub fn extract_max_rss_stat(max_rss: io::Result<i64>, worker_pid: u32) -> Option<i64> {
+	max_rss
+		.map_err(|err| {
+			gum::warn!(
+				target: LOG_TARGET,
+				%worker_pid,
+				"error getting `ru_maxrss` in the worker thread: {}",
+				err
+			);
+			err
+		})
+		.ok()
+}
@@ -16,11 +16,14 @@
 
 //! Functionality common to both prepare and execute workers.
 
+#[cfg(target_os = "linux")]
+pub mod max_rss_stat;
 pub mod security;
 
-use crate::LOG_TARGET;
+use crate::{framed_send, LOG_TARGET};
 use cpu_time::ProcessTime;
 use futures::never::Never;
+use parity_scale_codec::Encode;
 use std::{
 	any::Any,
 	path::PathBuf,
@@ -44,11 +47,15 @@ macro_rules! decl_worker_main {
 
 			let mut version = None;
 			let mut socket_path: &str = "";
+			let mut enable_seccomp = false;
+			let mut sandbox_root = None;
 
 			for i in 2..args.len() {
 				match args[i].as_ref() {
 					"--socket-path" => socket_path = args[i + 1].as_str(),
 					"--node-version" => version = Some(args[i + 1].as_str()),
+					"--enable-seccomp" => enable_seccomp = true,
+					"--sandbox-root" => sandbox_root = Some(args[i + 1].as_str()),
 					_ => (),
 				}
 			}
@@ -60,7 +67,7 @@ macro_rules! decl_worker_main {
 					$expected_command, subcommand
 				)
 			}
-			$entrypoint(&socket_path, version);
+			$entrypoint(&socket_path, version, enable_seccomp, sandbox_root);
 		}
 	};
 }
@@ -75,10 +82,25 @@ pub fn bytes_to_path(bytes: &[u8]) -> Option<PathBuf> {
 	std::str::from_utf8(bytes).ok().map(PathBuf::from)
 }
 
+/// The empty root a sandboxed worker pivots into. See [`security::namespaces`].
+#[derive(Debug, Clone, Copy)]
+pub struct SandboxRoot<'a> {
+	/// The directory the host created for the worker in the artifacts directory. This is the only
+	/// part of the host filesystem the worker can see.
+	pub artifacts_dir: &'a str,
+	/// Whether the worker is allowed to write to the directory.
+	pub writable: bool,
+}
+
+/// Starts the worker and runs the given event loop on the connection to the host.
+///
+/// If `sandbox_root` is given, the worker enters the sandbox right after connecting to the host,
+/// and reports the outcome to the host before running the event loop.
 pub fn worker_event_loop<F, Fut>(
 	debug_id: &'static str,
 	socket_path: &str,
 	node_version: Option<&str>,
+	sandbox_root: Option<SandboxRoot>,
 	mut event_loop: F,
 ) where
 	F: FnMut(UnixStream) -> Fut,
@@ -103,12 +125,33 @@ pub fn worker_event_loop<F, Fut>(
 		}
 	}
 
+	// Connect before entering the sandbox, as the socket is not reachable from inside of it.
+	let stream = match std::os::unix::net::UnixStream::connect(socket_path) {
+		Ok(stream) => stream,
+		Err(err) => {
+			gum::debug!(target: LOG_TARGET, %worker_pid, "quitting pvf worker ({}): {:?}", debug_id, err);
+			return
+		},
+	};
+	let _ = std::fs::remove_file(socket_path);
+
+	// The sandbox must be entered while the process is still single-threaded, i.e. before the
+	// runtime is started.
+	let sandbox_status = sandbox_root.map(enter_sandbox);
+
 	// Run the main worker loop.
 	let rt = Runtime::new().expect("Creates tokio runtime. If this panics the worker will die and the host will detect that and deal with it.");
 	let err = rt
 		.block_on(async move {
-			let stream = UnixStream::connect(socket_path).await?;
-			let _ = tokio::fs::remove_file(socket_path).await;
+			stream.set_nonblocking(true)?;
+			let mut stream = UnixStream::from_std(stream)?;
+
+			if let Some(sandbox_status) = sandbox_status {
+				framed_send(&mut stream, &sandbox_status.encode()).await?;
+				if let Err(err) = sandbox_status {
+					return Err(io::Error::new(io::ErrorKind::Other, err))
+				}
+			}
 
 			let result = event_loop(stream).await;
 
@@ -125,6 +168,20 @@ pub fn worker_event_loop<F, Fut>(
 	rt.shutdown_background();
 }
 
+/// Moves the current process into the sandbox described by `root`.
+fn enter_sandbox(root: SandboxRoot) -> Result<(), String> {
+	#[cfg(target_os = "linux")]
+	{
+		security::namespaces::enter(std::path::Path::new(root.artifacts_dir), root.writable)
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	{
+		let _ = root;
+		Err("sandboxing workers is only supported on Linux".to_string())
+	}
+}
+
 /// Loop that runs in the CPU time monitor thread on prepare and execute jobs. Continuously wakes up
 /// and then either blocks for the remaining CPU time, or returns if we exceed the CPU timeout.
 ///
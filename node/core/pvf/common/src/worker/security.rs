@@ -40,6 +40,16 @@ impl LandlockStatus {
 	}
 }
 
+/// To what degree seccomp is enabled.
+pub enum SeccompStatus {
+	/// The syscall filter was installed on the thread.
+	Enforced,
+	/// Seccomp was disabled in the configuration or is not supported on this platform.
+	NotEnforced,
+	/// Thread panicked, we don't know what the status is.
+	Unavailable,
+}
+
 /// The	[landlock] docs say it best:
 ///
 /// > "Landlock is a security feature available since Linux 5.13. The goal is to enable to restrict
@@ -186,3 +196,352 @@ pub mod landlock {
 		}
 	}
 }
+
+/// [Seccomp] restricts the syscalls that a thread is allowed to make.
+///
+/// We install an allowlist: any syscall that is not explicitly allowed kills the whole worker
+/// process with `SIGSYS`. This covers networking, process spawning and everything else that the
+/// compilation and execution of a PVF does not need. Filesystem access is left to [`landlock`].
+///
+/// [Seccomp]: https://www.kernel.org/doc/html/latest/userspace-api/seccomp_filter.html
+#[cfg(target_os = "linux")]
+pub mod seccomp {
+	use seccompiler::{
+		apply_filter, BpfProgram, Error, SeccompAction, SeccompCmpArgLen, SeccompCmpOp,
+		SeccompCondition, SeccompFilter, SeccompRule, TargetArch,
+	};
+	use std::collections::BTreeMap;
+
+	/// The signal a worker process is terminated with when it makes a forbidden syscall.
+	pub const FORBIDDEN_SYSCALL_SIGNAL: i32 = libc::SIGSYS;
+
+	/// Syscalls allowed unconditionally in a restricted thread.
+	///
+	/// Filesystem syscalls are allowed here because access to the filesystem is already governed
+	/// by landlock, and the standard library and the allocator probe e.g. `/proc` on their own.
+	const ALLOWED_SYSCALLS: &[libc::c_long] = &[
+		// Memory management, needed by the allocator and by wasmtime.
+		libc::SYS_brk,
+		libc::SYS_mmap,
+		libc::SYS_munmap,
+		libc::SYS_mremap,
+		libc::SYS_mprotect,
+		libc::SYS_madvise,
+		libc::SYS_membarrier,
+		libc::SYS_memfd_create,
+		libc::SYS_ftruncate,
+		// Threads and synchronization.
+		libc::SYS_futex,
+		libc::SYS_set_robust_list,
+		libc::SYS_rseq,
+		libc::SYS_sched_yield,
+		libc::SYS_sched_getaffinity,
+		libc::SYS_prctl,
+		libc::SYS_gettid,
+		libc::SYS_getpid,
+		libc::SYS_tgkill,
+		libc::SYS_exit,
+		libc::SYS_exit_group,
+		// Signals, needed by wasmtime trap handling.
+		libc::SYS_rt_sigaction,
+		libc::SYS_rt_sigprocmask,
+		libc::SYS_rt_sigreturn,
+		libc::SYS_sigaltstack,
+		// Time and resource usage.
+		libc::SYS_clock_gettime,
+		libc::SYS_clock_nanosleep,
+		libc::SYS_nanosleep,
+		libc::SYS_getrusage,
+		libc::SYS_getrandom,
+		// Already opened file descriptors, e.g. for logging.
+		libc::SYS_read,
+		libc::SYS_write,
+		libc::SYS_close,
+		libc::SYS_lseek,
+		libc::SYS_openat,
+		libc::SYS_newfstatat,
+		libc::SYS_fstat,
+		libc::SYS_statx,
+		#[cfg(target_arch = "x86_64")]
+		libc::SYS_open,
+		#[cfg(target_arch = "x86_64")]
+		libc::SYS_arch_prctl,
+		// `clone3` arguments cannot be inspected, it is made to fail below so that the libc falls
+		// back to `clone`.
+		libc::SYS_clone3,
+	];
+
+	/// Tries to restrict the current thread, and all threads spawned by it, with the syscall
+	/// allowlist.
+	///
+	/// Creating threads is allowed but spawning new processes is not.
+	pub fn try_restrict_thread() -> Result<(), Error> {
+		let arch: TargetArch = std::env::consts::ARCH.try_into().map_err(Error::Backend)?;
+
+		// Only allow `clone` if it creates a thread, and not a new process.
+		let mut rules: BTreeMap<i64, Vec<SeccompRule>> =
+			ALLOWED_SYSCALLS.iter().map(|syscall| (*syscall as i64, vec![])).collect();
+		rules.insert(
+			libc::SYS_clone as i64,
+			vec![SeccompRule::new(vec![SeccompCondition::new(
+				0,
+				SeccompCmpArgLen::Qword,
+				SeccompCmpOp::MaskedEq(libc::CLONE_THREAD as u64),
+				libc::CLONE_THREAD as u64,
+			)?])?],
+		);
+		let allowlist: BpfProgram =
+			SeccompFilter::new(rules, SeccompAction::KillProcess, SeccompAction::Allow, arch)?
+				.try_into()?;
+
+		// Stacked filters are all evaluated and the most restrictive action wins, so `clone3` is
+		// allowed by the allowlist but fails with `ENOSYS` here.
+		let clone3: BpfProgram = SeccompFilter::new(
+			[(libc::SYS_clone3 as i64, vec![])].into_iter().collect(),
+			SeccompAction::Allow,
+			SeccompAction::Errno(libc::ENOSYS as u32),
+			arch,
+		)?
+		.try_into()?;
+
+		apply_filter(&allowlist)?;
+		apply_filter(&clone3)?;
+		Ok(())
+	}
+
+	/// Returns whether seccomp can be enabled on the current Linux environment.
+	pub fn check_is_fully_enabled() -> bool {
+		matches!(std::thread::spawn(|| try_restrict_thread()).join(), Ok(Ok(())))
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+		use std::{os::unix::process::ExitStatusExt, process::Command};
+
+		const CHILD_ENV: &str = "PVF_SECCOMP_TEST_CHILD";
+
+		// The forbidden syscall kills the process, so it is made in a child test process.
+		#[test]
+		fn restricted_thread_cannot_open_socket() {
+			if std::env::var(CHILD_ENV).is_ok() {
+				std::thread::spawn(|| {
+					try_restrict_thread().unwrap();
+					let _ = std::net::TcpListener::bind("127.0.0.1:0");
+				})
+				.join()
+				.unwrap();
+				return
+			}
+
+			if !check_is_fully_enabled() {
+				return
+			}
+
+			let status = Command::new(std::env::current_exe().unwrap())
+				.args([
+					"--exact",
+					"worker::security::seccomp::tests::restricted_thread_cannot_open_socket",
+				])
+				.env(CHILD_ENV, "1")
+				.status()
+				.unwrap();
+			assert_eq!(status.signal(), Some(FORBIDDEN_SYSCALL_SIGNAL));
+		}
+
+		#[test]
+		fn restricted_thread_can_spawn_threads() {
+			if !check_is_fully_enabled() {
+				return
+			}
+
+			let handle = std::thread::spawn(|| {
+				try_restrict_thread().unwrap();
+				std::thread::spawn(|| 1 + 1).join().unwrap()
+			});
+
+			assert_eq!(handle.join().unwrap(), 2);
+		}
+	}
+}
+
+/// Linux [namespaces] isolate the worker process from the rest of the system.
+///
+/// The worker unshares the user, mount, network, PID and IPC namespaces and pivots into an empty
+/// root that only contains the artifacts directory. This must happen while the process is still
+/// single-threaded, i.e. before the tokio runtime is started, because the kernel does not allow a
+/// multi-threaded process to enter a new user namespace.
+///
+/// A new PID namespace only applies to the children of the worker. Since the worker never spawns
+/// processes and there is no `/proc` in the new root, it can not observe other processes either.
+///
+/// [namespaces]: https://man7.org/linux/man-pages/man7/namespaces.7.html
+#[cfg(target_os = "linux")]
+pub mod namespaces {
+	use std::{
+		ffi::CString,
+		fs, io,
+		os::unix::{ffi::OsStrExt, fs::DirBuilderExt, io::AsRawFd},
+		path::{Path, PathBuf},
+	};
+
+	/// The namespaces the worker process is moved into.
+	const CLONE_FLAGS: libc::c_int = libc::CLONE_NEWUSER |
+		libc::CLONE_NEWNS |
+		libc::CLONE_NEWNET |
+		libc::CLONE_NEWPID |
+		libc::CLONE_NEWIPC;
+
+	/// Name of the directory the old root is mounted at while pivoting. It is removed right after.
+	const OLD_ROOT: &str = ".old-root";
+
+	/// Moves the current process into new namespaces and pivots into an empty root which only
+	/// contains `artifacts_dir`, bind-mounted at the same absolute path. The artifacts directory is
+	/// mounted read-only unless `writable` is set.
+	///
+	/// Already opened file descriptors, like the socket to the host, remain usable.
+	pub fn enter(artifacts_dir: &Path, writable: bool) -> Result<(), String> {
+		if !artifacts_dir.is_absolute() {
+			return Err(format!("artifacts dir {} is not absolute", artifacts_dir.display()))
+		}
+
+		let uid = unsafe { libc::getuid() };
+		let gid = unsafe { libc::getgid() };
+		check(unsafe { libc::unshare(CLONE_FLAGS) }, "unshare")?;
+
+		// Map the current user to root in the new user namespace, which gives us the capabilities
+		// needed to set up the mounts below, but nothing outside of the namespaces.
+		fs::write("/proc/self/setgroups", "deny")
+			.and_then(|()| fs::write("/proc/self/uid_map", format!("0 {} 1", uid)))
+			.and_then(|()| fs::write("/proc/self/gid_map", format!("0 {} 1", gid)))
+			.map_err(|err| format!("cannot write id maps: {}", err))?;
+
+		// Make sure none of the mounts below propagate back to the host.
+		mount(None, Path::new("/"), None, libc::MS_REC | libc::MS_PRIVATE)?;
+
+		// Keep a handle to the artifacts directory, as it may get shadowed by the new root. It has
+		// to be opened after unsharing, so that it refers to a mount in the new mount namespace.
+		let artifacts = fs::File::open(artifacts_dir)
+			.map_err(|err| format!("cannot open artifacts dir: {}", err))?;
+
+		let new_root = std::env::temp_dir();
+		mount(Some(b"tmpfs"), &new_root, Some("tmpfs"), libc::MS_NODEV | libc::MS_NOSUID)?;
+
+		let target = new_root.join(artifacts_dir.strip_prefix("/").expect("checked above; qed"));
+		fs::DirBuilder::new()
+			.recursive(true)
+			.mode(0o700)
+			.create(&target)
+			.map_err(|err| format!("cannot create {}: {}", target.display(), err))?;
+		let source = PathBuf::from(format!("/proc/self/fd/{}", artifacts.as_raw_fd()));
+		let flags = libc::MS_BIND | libc::MS_NODEV | libc::MS_NOSUID | libc::MS_NOEXEC;
+		mount(Some(source.as_os_str().as_bytes()), &target, None, flags)?;
+		if !writable {
+			mount(None, &target, None, flags | libc::MS_REMOUNT | libc::MS_RDONLY)?;
+		}
+		drop(artifacts);
+
+		let old_root = new_root.join(OLD_ROOT);
+		fs::create_dir(&old_root).map_err(|err| format!("cannot create old root: {}", err))?;
+		let new_root_c = c_path(&new_root)?;
+		let old_root_c = c_path(&old_root)?;
+		check(
+			unsafe { libc::syscall(libc::SYS_pivot_root, new_root_c.as_ptr(), old_root_c.as_ptr()) }
+				as libc::c_int,
+			"pivot_root",
+		)?;
+		std::env::set_current_dir("/").map_err(|err| format!("cannot chdir: {}", err))?;
+
+		let old_root = c_path(&Path::new("/").join(OLD_ROOT))?;
+		check(unsafe { libc::umount2(old_root.as_ptr(), libc::MNT_DETACH) }, "umount old root")?;
+		fs::remove_dir(Path::new("/").join(OLD_ROOT))
+			.map_err(|err| format!("cannot remove old root: {}", err))?;
+
+		// Nothing but the artifacts directory should be writable.
+		mount(
+			None,
+			Path::new("/"),
+			None,
+			libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY | libc::MS_NODEV | libc::MS_NOSUID,
+		)
+	}
+
+	fn mount(
+		source: Option<&[u8]>,
+		target: &Path,
+		fstype: Option<&str>,
+		flags: libc::c_ulong,
+	) -> Result<(), String> {
+		let source = source.map(|s| CString::new(s).map_err(|err| err.to_string())).transpose()?;
+		let fstype = fstype.map(|s| CString::new(s).map_err(|err| err.to_string())).transpose()?;
+		let target_c = c_path(target)?;
+		check(
+			unsafe {
+				libc::mount(
+					source.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+					target_c.as_ptr(),
+					fstype.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+					flags,
+					std::ptr::null(),
+				)
+			},
+			&format!("mount {}", target.display()),
+		)
+	}
+
+	fn c_path(path: &Path) -> Result<CString, String> {
+		CString::new(path.as_os_str().as_bytes()).map_err(|err| err.to_string())
+	}
+
+	fn check(ret: libc::c_int, what: &str) -> Result<(), String> {
+		if ret == -1 {
+			Err(format!("{}: {}", what, io::Error::last_os_error()))
+		} else {
+			Ok(())
+		}
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+
+		#[test]
+		fn sandboxed_process_only_sees_artifacts_dir() {
+			let artifacts_dir = tempfile::tempdir().unwrap();
+			let artifacts_dir = artifacts_dir.path();
+			fs::write(artifacts_dir.join("artifact"), "foo").unwrap();
+
+			// Entering the namespaces can not be undone and requires a single-threaded process, so
+			// it is done in a forked child.
+			match unsafe { libc::fork() } {
+				0 => {
+					let code = match enter(artifacts_dir, false) {
+						// User namespaces are not available in this environment.
+						Err(err) if err.starts_with("unshare") => 0,
+						Err(_) => 1,
+						Ok(()) => {
+							let sees_artifact = fs::read_to_string(artifacts_dir.join("artifact"))
+								.map_or(false, |s| s == "foo");
+							let can_write = fs::write(artifacts_dir.join("other"), "bar").is_ok();
+							let sees_host =
+								Path::new("/proc/self").exists() || Path::new("/etc").exists();
+							if sees_artifact && !can_write && !sees_host {
+								0
+							} else {
+								2
+							}
+						},
+					};
+					unsafe { libc::_exit(code) }
+				},
+				-1 => panic!("fork failed: {}", io::Error::last_os_error()),
+				pid => {
+					let mut status = 0;
+					assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+					assert!(libc::WIFEXITED(status));
+					assert_eq!(libc::WEXITSTATUS(status), 0);
+				},
+			}
+		}
+	}
+}
@@ -22,17 +22,20 @@ const LOG_TARGET: &str = "parachain::pvf-execute-worker";
 
 use cpu_time::ProcessTime;
 use parity_scale_codec::{Decode, Encode};
+#[cfg(target_os = "linux")]
+use polkadot_node_core_pvf_common::worker::max_rss_stat;
 use polkadot_node_core_pvf_common::{
 	error::InternalValidationError,
 	execute::{Handshake, Response},
 	executor_intf::NATIVE_STACK_MAX,
 	framed_recv, framed_send,
+	prepare::MemoryStats,
 	worker::{
 		bytes_to_path, cpu_time_monitor_loop,
-		security::LandlockStatus,
+		security::{LandlockStatus, SeccompStatus},
 		stringify_panic_payload,
 		thread::{self, WaitOutcome},
-		worker_event_loop,
+		worker_event_loop, SandboxRoot,
 	},
 };
 use polkadot_parachain::primitives::ValidationResult;
@@ -120,9 +123,20 @@ async fn send_response(stream: &mut UnixStream, response: Response) -> io::Resul
 /// The `socket_path` specifies the path to the socket used to communicate with the host. The
 /// `node_version`, if `Some`, is checked against the worker version. A mismatch results in
 /// immediate worker termination. `None` is used for tests and in other situations when version
-/// check is not necessary.
-pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
-	worker_event_loop("execute", socket_path, node_version, |mut stream| async move {
+/// check is not necessary. If `enable_seccomp` is set, the execution thread is restricted with
+/// the seccomp syscall allowlist.
+///
+/// If `sandbox_root` is `Some`, it is the directory of the worker and the worker is moved into its
+/// own namespaces, with an empty root that only contains that directory (read-only). The host
+/// links the artifact of every job into it.
+pub fn worker_entrypoint(
+	socket_path: &str,
+	node_version: Option<&str>,
+	enable_seccomp: bool,
+	sandbox_root: Option<&str>,
+) {
+	let sandbox = sandbox_root.map(|artifacts_dir| SandboxRoot { artifacts_dir, writable: false });
+	worker_event_loop("execute", socket_path, node_version, sandbox, |mut stream| async move {
 		let worker_pid = std::process::id();
 
 		let handshake = recv_handshake(&mut stream).await?;
@@ -180,15 +194,40 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 					#[cfg(not(target_os = "linux"))]
 					let landlock_status: Result<LandlockStatus, String> = Ok(LandlockStatus::NotEnforced);
 
-					(
-						validate_using_artifact(
-							&compiled_artifact_blob,
-							&params,
-							executor_2,
-							cpu_time_start,
-						),
-						landlock_status,
-					)
+					// Try to enable seccomp, after landlock since it needs syscalls that are not
+					// in the allowlist.
+					#[cfg(target_os = "linux")]
+					let seccomp_status = if enable_seccomp {
+						polkadot_node_core_pvf_common::worker::security::seccomp::try_restrict_thread()
+							.map(|()| SeccompStatus::Enforced)
+							.map_err(|e| e.to_string())
+					} else {
+						Ok(SeccompStatus::NotEnforced)
+					};
+					#[cfg(not(target_os = "linux"))]
+					let seccomp_status: Result<SeccompStatus, String> = {
+						let _ = enable_seccomp;
+						Ok(SeccompStatus::NotEnforced)
+					};
+
+					#[allow(unused_mut)]
+					let mut response = validate_using_artifact(
+						&compiled_artifact_blob,
+						&params,
+						executor_2,
+						cpu_time_start,
+					);
+
+					// Get the `ru_maxrss` stat. If supported, call getrusage for the thread.
+					#[cfg(target_os = "linux")]
+					if let Response::Ok { memory_stats, .. } = &mut response {
+						memory_stats.max_rss = max_rss_stat::extract_max_rss_stat(
+							max_rss_stat::get_max_rss_thread(),
+							worker_pid,
+						);
+					}
+
+					(response, landlock_status, seccomp_status)
 				},
 				Arc::clone(&condvar),
 				WaitOutcome::Finished,
@@ -200,12 +239,14 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 			let response = match outcome {
 				WaitOutcome::Finished => {
 					let _ = cpu_time_monitor_tx.send(());
-					let (result, landlock_status) = execute_thread.join().unwrap_or_else(|e| {
-						(
-							Response::Panic(stringify_panic_payload(e)),
-							Ok(LandlockStatus::Unavailable),
-						)
-					});
+					let (result, landlock_status, seccomp_status) =
+						execute_thread.join().unwrap_or_else(|e| {
+							(
+								Response::Panic(stringify_panic_payload(e)),
+								Ok(LandlockStatus::Unavailable),
+								Ok(SeccompStatus::Unavailable),
+							)
+						});
 
 					// Log if landlock threw an error.
 					if let Err(err) = landlock_status {
@@ -216,6 +257,15 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 							err
 						);
 					}
+					// Log if seccomp threw an error.
+					if let Err(err) = seccomp_status {
+						gum::warn!(
+							target: LOG_TARGET,
+							%worker_pid,
+							"error enabling seccomp: {}",
+							err
+						);
+					}
 
 					result
 				},
@@ -279,5 +329,7 @@ fn validate_using_artifact(
 	// bug in decoding.
 	let duration = cpu_time_start.elapsed();
 
-	Response::Ok { result_descriptor, duration }
+	// Execution is too short for the polling memory tracker of the prepare worker, so only
+	// `ru_maxrss` is filled in, by the caller.
+	Response::Ok { result_descriptor, duration, memory_stats: MemoryStats::default() }
 }
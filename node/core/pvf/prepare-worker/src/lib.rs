@@ -23,11 +23,13 @@ pub use executor_intf::{prepare, prevalidate};
 //       separate spawned processes. Run with e.g. `RUST_LOG=parachain::pvf-prepare-worker=trace`.
 const LOG_TARGET: &str = "parachain::pvf-prepare-worker";
 
-#[cfg(target_os = "linux")]
-use crate::memory_stats::max_rss_stat::{extract_max_rss_stat, get_max_rss_thread};
 #[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
 use crate::memory_stats::memory_tracker::{get_memory_tracker_loop_stats, memory_tracker_loop};
 use parity_scale_codec::{Decode, Encode};
+#[cfg(target_os = "linux")]
+use polkadot_node_core_pvf_common::worker::max_rss_stat::{
+	extract_max_rss_stat, get_max_rss_thread,
+};
 use polkadot_node_core_pvf_common::{
 	error::{PrepareError, PrepareResult},
 	executor_intf::Executor,
@@ -36,10 +38,10 @@ use polkadot_node_core_pvf_common::{
 	pvf::PvfPrepData,
 	worker::{
 		bytes_to_path, cpu_time_monitor_loop,
-		security::LandlockStatus,
+		security::{LandlockStatus, SeccompStatus},
 		stringify_panic_payload,
 		thread::{self, WaitOutcome},
-		worker_event_loop,
+		worker_event_loop, SandboxRoot,
 	},
 	ProcessTime,
 };
@@ -96,7 +98,12 @@ async fn send_response(stream: &mut UnixStream, result: PrepareResult) -> io::Re
 /// The `socket_path` specifies the path to the socket used to communicate with the host. The
 /// `node_version`, if `Some`, is checked against the worker version. A mismatch results in
 /// immediate worker termination. `None` is used for tests and in other situations when version
-/// check is not necessary.
+/// check is not necessary. If `enable_seccomp` is set, the preparation thread is restricted with
+/// the seccomp syscall allowlist.
+///
+/// If `sandbox_root` is `Some`, it is the directory of the worker and the worker is moved into its
+/// own namespaces, with an empty root that only contains that directory (read-write). The host
+/// asks for the artifacts to be written into it.
 ///
 /// # Flow
 ///
@@ -116,8 +123,14 @@ async fn send_response(stream: &mut UnixStream, result: PrepareResult) -> io::Re
 ///
 /// 7. Send the result of preparation back to the host. If any error occurred in the above steps, we
 ///    send that in the `PrepareResult`.
-pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
-	worker_event_loop("prepare", socket_path, node_version, |mut stream| async move {
+pub fn worker_entrypoint(
+	socket_path: &str,
+	node_version: Option<&str>,
+	enable_seccomp: bool,
+	sandbox_root: Option<&str>,
+) {
+	let sandbox = sandbox_root.map(|artifacts_dir| SandboxRoot { artifacts_dir, writable: true });
+	worker_event_loop("prepare", socket_path, node_version, sandbox, |mut stream| async move {
 		let worker_pid = std::process::id();
 
 		loop {
@@ -165,6 +178,22 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 					#[cfg(not(target_os = "linux"))]
 					let landlock_status: Result<LandlockStatus, String> = Ok(LandlockStatus::NotEnforced);
 
+					// Try to enable seccomp, after landlock since it needs syscalls that are not
+					// in the allowlist.
+					#[cfg(target_os = "linux")]
+					let seccomp_status = if enable_seccomp {
+						polkadot_node_core_pvf_common::worker::security::seccomp::try_restrict_thread()
+							.map(|()| SeccompStatus::Enforced)
+							.map_err(|e| e.to_string())
+					} else {
+						Ok(SeccompStatus::NotEnforced)
+					};
+					#[cfg(not(target_os = "linux"))]
+					let seccomp_status: Result<SeccompStatus, String> = {
+						let _ = enable_seccomp;
+						Ok(SeccompStatus::NotEnforced)
+					};
+
 					#[allow(unused_mut)]
 					let mut result = prepare_artifact(pvf, cpu_time_start);
 
@@ -183,7 +212,7 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 						});
 					}
 
-					(result, landlock_status)
+					(result, landlock_status, seccomp_status)
 				},
 				Arc::clone(&condvar),
 				WaitOutcome::Finished,
@@ -199,13 +228,14 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 						(
 							Err(PrepareError::Panic(stringify_panic_payload(err))),
 							Ok(LandlockStatus::Unavailable),
+							Ok(SeccompStatus::Unavailable),
 						)
 					}) {
-						(Err(err), _) => {
+						(Err(err), _, _) => {
 							// Serialized error will be written into the socket.
 							Err(err)
 						},
-						(Ok(ok), landlock_status) => {
+						(Ok(ok), landlock_status, seccomp_status) => {
 							#[cfg(not(target_os = "linux"))]
 							let (artifact, cpu_time_elapsed) = ok;
 							#[cfg(target_os = "linux")]
@@ -230,6 +260,15 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 									err
 								);
 							}
+							// Log if seccomp threw an error.
+							if let Err(err) = seccomp_status {
+								gum::warn!(
+									target: LOG_TARGET,
+									%worker_pid,
+									"error enabling seccomp: {}",
+									err
+								);
+							}
 
 							// Write the serialized artifact into a temp file.
 							//
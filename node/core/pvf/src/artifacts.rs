@@ -18,8 +18,10 @@
 //!
 //!	# Lifecycle of an artifact
 //!
-//! 1. During node start-up, the artifacts cache is cleaned up. This means that all local artifacts
-//!    stored on-disk are cleared, and we start with an empty [`Artifacts`] table.
+//! 1. During node start-up, the artifacts table is rebuilt from the artifacts cache. Every artifact
+//!    found on-disk is checked against its [`ArtifactMetadata`] sidecar: the artifact id, the node
+//!    version and the checksum of the artifact contents must all match. Artifacts that pass are
+//!    inserted as [`ArtifactState::Prepared`], everything else found in the cache is removed.
 //!
 //! 2. In order to be executed, a PVF should be prepared first. This means that artifacts should
 //!    have an [`ArtifactState::Prepared`] entry for that artifact in the table. If not, the
@@ -35,8 +37,9 @@
 //!
 //! 3. The pool gets an available worker and instructs it to work on the given PVF. The worker
 //!    starts compilation. When the worker finishes successfully, it writes the serialized artifact
-//!    into a temporary file and notifies the host that it's done. The host atomically moves
-//!    (renames) the temporary file to the destination filename of the artifact.
+//!    into a temporary file and notifies the host that it's done. The host writes the metadata
+//!    sidecar for the artifact and then atomically moves (renames) the temporary file to the
+//!    destination filename of the artifact.
 //!
 //! 4. If the worker concluded successfully or returned an error, then the pool notifies the queue.
 //!    In both cases, the queue reports to the host that the result is ready.
@@ -53,13 +56,15 @@
 //!
 //! 7. There is a separate process for pruning the prepared artifacts whose `last_time_needed` is
 //!    older by a predefined parameter. This process is run very rarely (say, once a day). Once the
-//!    artifact is expired it is removed from disk eagerly atomically.
+//!    artifact is expired it is removed from disk eagerly atomically, together with its metadata.
 
-use crate::host::PrepareResultSender;
+use crate::{host::PrepareResultSender, LOG_TARGET};
 use always_assert::always;
+use parity_scale_codec::{Decode, Encode};
 use polkadot_node_core_pvf_common::{error::PrepareError, prepare::PrepareStats, pvf::PvfPrepData};
 use polkadot_parachain::primitives::ValidationCodeHash;
 use polkadot_primitives::ExecutorParamsHash;
+use sp_core::blake2_256;
 use std::{
 	collections::HashMap,
 	path::{Path, PathBuf},
@@ -67,7 +72,7 @@ use std::{
 };
 
 /// Identifier of an artifact. Encodes a code hash of the PVF and a hash of executor parameter set.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode)]
 pub struct ArtifactId {
 	pub(crate) code_hash: ValidationCodeHash,
 	pub(crate) executor_params_hash: ExecutorParamsHash,
@@ -87,7 +92,6 @@ impl ArtifactId {
 	}
 
 	/// Tries to recover the artifact id from the given file name.
-	#[cfg(test)]
 	pub fn from_file_name(file_name: &str) -> Option<Self> {
 		use polkadot_core_primitives::Hash;
 		use std::str::FromStr as _;
@@ -109,6 +113,62 @@ impl ArtifactId {
 	}
 }
 
+/// Returns the path of the metadata sidecar for the artifact located at the given path.
+pub fn metadata_path(artifact_path: &Path) -> PathBuf {
+	let mut file_name = artifact_path.file_name().map(|n| n.to_owned()).unwrap_or_default();
+	file_name.push(ArtifactMetadata::SUFFIX);
+	artifact_path.with_file_name(file_name)
+}
+
+/// Metadata stored next to every prepared artifact on-disk.
+///
+/// It allows the host to tell, after a restart, whether the artifact found in the cache can be
+/// reused or should be evicted.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct ArtifactMetadata {
+	/// The id of the artifact the metadata belongs to.
+	pub(crate) artifact_id: ArtifactId,
+	/// The version of the node that prepared the artifact.
+	pub(crate) node_version: String,
+	/// The checksum of the artifact contents.
+	pub(crate) checksum: [u8; 32],
+	/// Stats produced by the preparation of the artifact.
+	pub(crate) prepare_stats: PrepareStats,
+}
+
+impl ArtifactMetadata {
+	const SUFFIX: &'static str = ".meta";
+
+	/// Creates the metadata for an artifact with the given contents, prepared by this node.
+	pub fn new(artifact_id: ArtifactId, artifact: &[u8], prepare_stats: PrepareStats) -> Self {
+		Self {
+			artifact_id,
+			node_version: env!("SUBSTRATE_CLI_IMPL_VERSION").to_owned(),
+			checksum: blake2_256(artifact),
+			prepare_stats,
+		}
+	}
+
+	/// Reads and decodes the metadata sidecar for the artifact located at the given path.
+	pub async fn read(artifact_path: &Path) -> Option<Self> {
+		let bytes = tokio::fs::read(metadata_path(artifact_path)).await.ok()?;
+		Self::decode(&mut &bytes[..]).ok()
+	}
+
+	/// Writes the metadata sidecar for the artifact located at the given path.
+	pub async fn write(&self, artifact_path: &Path) -> std::io::Result<()> {
+		tokio::fs::write(metadata_path(artifact_path), self.encode()).await
+	}
+
+	/// Checks whether the artifact with the given id and contents is described by this metadata
+	/// and was prepared by this version of the node.
+	fn is_valid_for(&self, artifact_id: &ArtifactId, artifact: &[u8]) -> bool {
+		self.artifact_id == *artifact_id &&
+			self.node_version == env!("SUBSTRATE_CLI_IMPL_VERSION") &&
+			self.checksum == blake2_256(artifact)
+	}
+}
+
 /// A bundle of the artifact ID and the path.
 ///
 /// Rationale for having this is two-fold:
@@ -167,17 +227,125 @@ pub struct Artifacts {
 }
 
 impl Artifacts {
-	/// Initialize a blank cache at the given path. This will clear everything present at the
-	/// given path, to be populated over time.
+	/// Initialize the cache at the given path, rebuilding the table from the artifacts present on
+	/// disk.
 	///
-	/// The recognized artifacts will be filled in the table and unrecognized will be removed.
+	/// The recognized artifacts will be filled in the table and unrecognized will be removed. An
+	/// artifact is recognized if its metadata matches its id, the current node version and its
+	/// contents.
 	pub async fn new(cache_path: &Path) -> Self {
 		// Make sure that the cache path directory and all its parents are created.
-		// First delete the entire cache. Nodes are long-running so this should populate shortly.
-		let _ = tokio::fs::remove_dir_all(cache_path).await;
 		let _ = tokio::fs::create_dir_all(cache_path).await;
 
-		Self { artifacts: HashMap::new() }
+		let mut artifacts = HashMap::new();
+		let mut entries = match tokio::fs::read_dir(cache_path).await {
+			Ok(entries) => entries,
+			Err(err) => {
+				gum::warn!(
+					target: LOG_TARGET,
+					"failed to read the artifacts cache at {}: {:?}",
+					cache_path.display(),
+					err,
+				);
+				return Self { artifacts }
+			},
+		};
+
+		let mut to_remove = vec![];
+		loop {
+			let entry = match entries.next_entry().await {
+				Ok(Some(entry)) => entry,
+				Ok(None) => break,
+				Err(err) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						"failed to read an entry of the artifacts cache: {:?}",
+						err,
+					);
+					break
+				},
+			};
+			let path = entry.path();
+			let file_name = entry.file_name();
+			let file_name = match file_name.to_str() {
+				Some(file_name) => file_name,
+				None => {
+					to_remove.push(path);
+					continue
+				},
+			};
+
+			// Metadata sidecars are handled together with their artifacts below. Leftover ones are
+			// removed once all the artifacts are known.
+			if file_name.ends_with(ArtifactMetadata::SUFFIX) {
+				continue
+			}
+
+			match Self::recognize(&path, file_name).await {
+				Some((artifact_id, prepare_stats)) => {
+					artifacts.insert(
+						artifact_id,
+						ArtifactState::Prepared {
+							last_time_needed: SystemTime::now(),
+							prepare_stats,
+						},
+					);
+				},
+				None => {
+					gum::debug!(
+						target: LOG_TARGET,
+						"evicting unrecognized artifact {}",
+						path.display(),
+					);
+					to_remove.push(metadata_path(&path));
+					to_remove.push(path);
+				},
+			}
+		}
+
+		// Remove the metadata sidecars that do not belong to any recognized artifact.
+		if let Ok(mut entries) = tokio::fs::read_dir(cache_path).await {
+			while let Ok(Some(entry)) = entries.next_entry().await {
+				let path = entry.path();
+				let artifact_id = entry
+					.file_name()
+					.to_str()
+					.and_then(|name| name.strip_suffix(ArtifactMetadata::SUFFIX))
+					.map(ArtifactId::from_file_name);
+				match artifact_id {
+					Some(Some(artifact_id)) if artifacts.contains_key(&artifact_id) => {},
+					Some(_) => to_remove.push(path),
+					None => {},
+				}
+			}
+		}
+
+		for path in to_remove {
+			let _ = tokio::fs::remove_file(&path).await;
+		}
+
+		gum::debug!(
+			target: LOG_TARGET,
+			"recovered {} prepared artifacts from {}",
+			artifacts.len(),
+			cache_path.display(),
+		);
+
+		Self { artifacts }
+	}
+
+	/// Checks the on-disk artifact at the given path against its metadata. Returns the id of the
+	/// artifact and its preparation stats if the artifact can be reused.
+	async fn recognize(path: &Path, file_name: &str) -> Option<(ArtifactId, PrepareStats)> {
+		let artifact_id = ArtifactId::from_file_name(file_name)?;
+		let metadata = ArtifactMetadata::read(path).await?;
+		let artifact = tokio::fs::read(path).await.ok()?;
+
+		if metadata.is_valid_for(&artifact_id, &artifact) {
+			Some((artifact_id, metadata.prepare_stats))
+		} else {
+			None
+		}
 	}
 
 	#[cfg(test)]
@@ -251,7 +419,9 @@ impl Artifacts {
 
 #[cfg(test)]
 mod tests {
-	use super::{ArtifactId, Artifacts};
+	use super::{metadata_path, ArtifactId, ArtifactMetadata, ArtifactState, Artifacts};
+	use assert_matches::assert_matches;
+	use polkadot_node_core_pvf_common::prepare::PrepareStats;
 	use polkadot_primitives::ExecutorParamsHash;
 	use sp_core::H256;
 	use std::{path::Path, str::FromStr};
@@ -292,8 +462,22 @@ mod tests {
 		);
 	}
 
+	async fn write_artifact(cache_path: &Path, artifact_id: &ArtifactId, contents: &[u8]) {
+		let artifact_path = artifact_id.path(cache_path);
+		std::fs::write(&artifact_path, contents).unwrap();
+		ArtifactMetadata::new(artifact_id.clone(), contents, PrepareStats::default())
+			.write(&artifact_path)
+			.await
+			.unwrap();
+	}
+
+	fn artifact_id(discriminator: u8) -> ArtifactId {
+		let hash = H256::repeat_byte(discriminator);
+		ArtifactId::new(hash.into(), ExecutorParamsHash::from_hash(hash))
+	}
+
 	#[tokio::test]
-	async fn artifacts_removes_cache_on_startup() {
+	async fn artifacts_removes_unrecognized_files_on_startup() {
 		let fake_cache_path = crate::worker_intf::tmpfile("test-cache").await.unwrap();
 		let fake_artifact_path = {
 			let mut p = fake_cache_path.clone();
@@ -315,4 +499,72 @@ mod tests {
 
 		std::fs::remove_dir_all(fake_cache_path).unwrap();
 	}
+
+	#[tokio::test]
+	async fn artifacts_reuses_valid_artifacts_on_startup() {
+		let fake_cache_path = crate::worker_intf::tmpfile("test-cache").await.unwrap();
+		std::fs::create_dir_all(&fake_cache_path).unwrap();
+
+		write_artifact(&fake_cache_path, &artifact_id(1), b"artifact").await;
+
+		let mut artifacts = Artifacts::new(&fake_cache_path).await;
+
+		assert_matches!(
+			artifacts.artifact_state_mut(&artifact_id(1)),
+			Some(ArtifactState::Prepared { .. })
+		);
+		assert!(artifact_id(1).path(&fake_cache_path).exists());
+		assert!(metadata_path(&artifact_id(1).path(&fake_cache_path)).exists());
+
+		std::fs::remove_dir_all(fake_cache_path).unwrap();
+	}
+
+	#[tokio::test]
+	async fn artifacts_evicts_stale_and_corrupt_artifacts_on_startup() {
+		let fake_cache_path = crate::worker_intf::tmpfile("test-cache").await.unwrap();
+		std::fs::create_dir_all(&fake_cache_path).unwrap();
+
+		// Corrupted contents.
+		write_artifact(&fake_cache_path, &artifact_id(1), b"artifact").await;
+		std::fs::write(artifact_id(1).path(&fake_cache_path), b"corrupted").unwrap();
+
+		// Prepared by another node version.
+		let artifact_path = artifact_id(2).path(&fake_cache_path);
+		std::fs::write(&artifact_path, b"artifact").unwrap();
+		let mut metadata =
+			ArtifactMetadata::new(artifact_id(2), b"artifact", PrepareStats::default());
+		metadata.node_version = "0.0.0-outdated".into();
+		metadata.write(&artifact_path).await.unwrap();
+
+		// Metadata describing another artifact.
+		let artifact_path = artifact_id(3).path(&fake_cache_path);
+		std::fs::write(&artifact_path, b"artifact").unwrap();
+		ArtifactMetadata::new(artifact_id(4), b"artifact", PrepareStats::default())
+			.write(&artifact_path)
+			.await
+			.unwrap();
+
+		// Missing metadata.
+		std::fs::write(artifact_id(5).path(&fake_cache_path), b"artifact").unwrap();
+
+		// Metadata without an artifact.
+		write_artifact(&fake_cache_path, &artifact_id(6), b"artifact").await;
+		std::fs::remove_file(artifact_id(6).path(&fake_cache_path)).unwrap();
+
+		// A valid one.
+		write_artifact(&fake_cache_path, &artifact_id(7), b"artifact").await;
+
+		let mut artifacts = Artifacts::new(&fake_cache_path).await;
+
+		for discriminator in 1..=6 {
+			assert!(artifacts.artifact_state_mut(&artifact_id(discriminator)).is_none());
+		}
+		assert_matches!(
+			artifacts.artifact_state_mut(&artifact_id(7)),
+			Some(ArtifactState::Prepared { .. })
+		);
+		assert_eq!(std::fs::read_dir(&fake_cache_path).unwrap().count(), 2);
+
+		std::fs::remove_dir_all(fake_cache_path).unwrap();
+	}
 }
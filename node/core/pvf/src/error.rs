@@ -55,6 +55,11 @@ pub enum InvalidCandidate {
 	/// validator. On the other hand, if the worker died because of (b) we would have better chances
 	/// to stop the attack.
 	AmbiguousWorkerDeath,
+	/// The worker was killed by seccomp for making a syscall that is not in the allowlist while
+	/// validating the candidate. Unlike [`InvalidCandidate::AmbiguousWorkerDeath`], we know the
+	/// cause of the death, so it is not retried. As the allowlist may be incomplete for the local
+	/// environment, this must not be treated as evidence against the candidate.
+	ForbiddenSyscall,
 	/// PVF execution (compilation is not included) took more time than was allotted.
 	HardTimeout,
 	/// A panic occurred and we can't be sure whether the candidate is really invalid or some internal glitch occurred.
@@ -21,7 +21,8 @@ use crate::{
 	artifacts::{ArtifactId, ArtifactPathId},
 	host::ResultSender,
 	metrics::Metrics,
-	worker_intf::{IdleWorker, WorkerHandle},
+	sandbox::WorkerSandbox,
+	worker_intf::{IdleWorker, SpawnErr, WorkerDeath, WorkerHandle, WORKER_EXIT_TIMEOUT},
 	InvalidCandidate, ValidationError, LOG_TARGET,
 };
 use futures::{
@@ -30,6 +31,8 @@ use futures::{
 	stream::{FuturesUnordered, StreamExt as _},
 	Future, FutureExt,
 };
+use polkadot_node_core_pvf_common::error::InternalValidationError;
+use polkadot_parachain::primitives::ValidationResult;
 use polkadot_primitives::{ExecutorParams, ExecutorParamsHash};
 use slotmap::HopSlotMap;
 use std::{
@@ -127,6 +130,8 @@ impl Workers {
 enum QueueEvent {
 	Spawn(IdleWorker, WorkerHandle, ExecuteJob),
 	StartWork(Worker, Outcome, ArtifactId, ResultSender),
+	/// The worker died while executing a job and its process has been reaped.
+	WorkerDied(Worker, WorkerDeath, ArtifactId, ResultSender),
 }
 
 type Mux = FuturesUnordered<BoxFuture<'static, QueueEvent>>;
@@ -139,6 +144,8 @@ struct Queue {
 
 	program_path: PathBuf,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<WorkerSandbox>,
 
 	/// The queue of jobs that are waiting for a worker to pick up.
 	queue: VecDeque<ExecuteJob>,
@@ -152,12 +159,16 @@ impl Queue {
 		program_path: PathBuf,
 		worker_capacity: usize,
 		spawn_timeout: Duration,
+		enable_seccomp: bool,
+		sandbox: Option<WorkerSandbox>,
 		to_queue_rx: mpsc::Receiver<ToQueue>,
 	) -> Self {
 		Self {
 			metrics,
 			program_path,
 			spawn_timeout,
+			enable_seccomp,
+			sandbox,
 			to_queue_rx,
 			queue: VecDeque::new(),
 			mux: Mux::new(),
@@ -179,7 +190,7 @@ impl Queue {
 						break;
 					}
 				}
-				ev = self.mux.select_next_some() => handle_mux(&mut self, ev).await,
+				ev = self.mux.select_next_some() => handle_mux(&mut self, ev),
 			}
 
 			purge_dead(&self.metrics, &mut self.workers).await;
@@ -250,6 +261,13 @@ impl Queue {
 async fn purge_dead(metrics: &Metrics, workers: &mut Workers) {
 	let mut to_remove = vec![];
 	for (worker, data) in workers.running.iter_mut() {
+		if data.idle.is_none() {
+			// The idle token is missing, meaning this worker is now occupied: skip it. This is
+			// because the worker process is observed by the work task and should it be terminated
+			// it will be handled by the corresponding mux event.
+			continue
+		}
+
 		if futures::poll!(&mut data.handle).is_ready() {
 			// a resolved future means that the worker has terminated. Weed it out.
 			to_remove.push(worker);
@@ -284,7 +302,7 @@ fn handle_to_queue(queue: &mut Queue, to_queue: ToQueue) {
 	queue.try_assign_next_job(None);
 }
 
-async fn handle_mux(queue: &mut Queue, event: QueueEvent) {
+fn handle_mux(queue: &mut Queue, event: QueueEvent) {
 	match event {
 		QueueEvent::Spawn(idle, handle, job) => {
 			handle_worker_spawned(queue, idle, handle, job);
@@ -292,6 +310,26 @@ async fn handle_mux(queue: &mut Queue, event: QueueEvent) {
 		QueueEvent::StartWork(worker, outcome, artifact_id, result_tx) => {
 			handle_job_finish(queue, worker, outcome, artifact_id, result_tx);
 		},
+		QueueEvent::WorkerDied(worker, death, artifact_id, result_tx) => {
+			let err = match death {
+				WorkerDeath::ForbiddenSyscall =>
+					ValidationError::InvalidCandidate(InvalidCandidate::ForbiddenSyscall),
+				// The memory limit is set by the node, so this is no evidence against the candidate.
+				WorkerDeath::OutOfMemory => {
+					gum::warn!(
+						target: LOG_TARGET,
+						?artifact_id,
+						"execute worker exceeded the memory limit of its sandbox, consider raising \
+						 --pvf-sandbox-memory-overhead",
+					);
+					ValidationError::InternalError(InternalValidationError::OutOfMemory)
+				},
+				// "Maybe invalid" error (will retry).
+				WorkerDeath::Unknown =>
+					ValidationError::InvalidCandidate(InvalidCandidate::AmbiguousWorkerDeath),
+			};
+			conclude_job(queue, worker, None, Err(err), None, artifact_id, result_tx);
+		},
 	}
 }
 
@@ -324,9 +362,11 @@ fn handle_job_finish(
 	result_tx: ResultSender,
 ) {
 	let (idle_worker, result, duration) = match outcome {
-		Outcome::Ok { result_descriptor, duration, idle_worker } => {
+		Outcome::Ok { result_descriptor, duration, memory_stats, idle_worker } => {
 			// TODO: propagate the soft timeout
 
+			queue.metrics.observe_execution_memory_metrics(memory_stats);
+
 			(Some(idle_worker), Ok(result_descriptor), Some(duration))
 		},
 		Outcome::InvalidCandidate { err, idle_worker } => (
@@ -337,16 +377,50 @@ fn handle_job_finish(
 		Outcome::InternalError { err } => (None, Err(ValidationError::InternalError(err)), None),
 		Outcome::HardTimeout =>
 			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::HardTimeout)), None),
-		// "Maybe invalid" errors (will retry).
-		Outcome::IoErr => (
-			None,
-			Err(ValidationError::InvalidCandidate(InvalidCandidate::AmbiguousWorkerDeath)),
-			None,
-		),
+		Outcome::IoErr => {
+			// The worker may have been killed for making a forbidden syscall or for exceeding its
+			// memory limit, which we want to tell apart from an ambiguous death. Reaping the
+			// process may take a moment, so we do it in the background and conclude the job once
+			// `QueueEvent::WorkerDied` comes back.
+			if let Some(data) = queue.workers.running.remove(worker) {
+				queue.metrics.execute_worker().on_retired();
+				let mut handle = data.handle;
+				queue.mux.push(
+					async move {
+						let death = handle.cause_of_death(WORKER_EXIT_TIMEOUT).await;
+						QueueEvent::WorkerDied(worker, death, artifact_id, result_tx)
+					}
+					.boxed(),
+				);
+				// The worker slot is free already.
+				queue.try_assign_next_job(None);
+				return
+			}
+
+			// "Maybe invalid" error (will retry).
+			(
+				None,
+				Err(ValidationError::InvalidCandidate(InvalidCandidate::AmbiguousWorkerDeath)),
+				None,
+			)
+		},
 		Outcome::Panic { err } =>
 			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::Panic(err))), None),
 	};
 
+	conclude_job(queue, worker, idle_worker, result, duration, artifact_id, result_tx);
+}
+
+/// Reports the result of a job and deals with the worker that executed it.
+fn conclude_job(
+	queue: &mut Queue,
+	worker: Worker,
+	idle_worker: Option<IdleWorker>,
+	result: Result<ValidationResult, ValidationError>,
+	duration: Option<Duration>,
+	artifact_id: ArtifactId,
+	result_tx: ResultSender,
+) {
 	queue.metrics.execute_finished();
 	if let Err(ref err) = result {
 		gum::warn!(
@@ -398,9 +472,17 @@ fn spawn_extra_worker(queue: &mut Queue, job: ExecuteJob) {
 	queue.metrics.execute_worker().on_begin_spawn();
 	gum::debug!(target: LOG_TARGET, "spawning an extra worker");
 
-	queue
-		.mux
-		.push(spawn_worker_task(queue.program_path.clone(), job, queue.spawn_timeout).boxed());
+	queue.mux.push(
+		spawn_worker_task(
+			queue.metrics.clone(),
+			queue.program_path.clone(),
+			job,
+			queue.spawn_timeout,
+			queue.enable_seccomp,
+			queue.sandbox.clone(),
+		)
+		.boxed(),
+	);
 	queue.workers.spawn_inflight += 1;
 }
 
@@ -411,19 +493,31 @@ fn spawn_extra_worker(queue: &mut Queue, job: ExecuteJob) {
 /// the queue would have to kill a newly started worker and spawn another one.
 /// Nevertheless, if the worker finishes executing the job, it becomes idle and may be used to execute other jobs with a compatible execution environment.
 async fn spawn_worker_task(
+	metrics: Metrics,
 	program_path: PathBuf,
 	job: ExecuteJob,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<WorkerSandbox>,
 ) -> QueueEvent {
 	use futures_timer::Delay;
 
 	loop {
-		match super::worker_intf::spawn(&program_path, job.executor_params.clone(), spawn_timeout)
-			.await
+		match super::worker_intf::spawn(
+			&program_path,
+			job.executor_params.clone(),
+			spawn_timeout,
+			enable_seccomp,
+			sandbox.as_ref(),
+		)
+		.await
 		{
 			Ok((idle, handle)) => break QueueEvent::Spawn(idle, handle, job),
 			Err(err) => {
 				gum::warn!(target: LOG_TARGET, "failed to spawn an execute worker: {:?}", err);
+				if let SpawnErr::Sandbox(stage) = err {
+					metrics.execute_worker().on_sandbox_setup_failed(stage);
+				}
 
 				// Assume that the failure is intermittent and retry after a delay.
 				Delay::new(Duration::from_secs(3)).await;
@@ -459,17 +553,38 @@ fn assign(queue: &mut Queue, worker: Worker, job: ExecuteJob) {
 			thus claim_idle cannot return None;
 			qed.",
 	);
+	// A sandboxed worker can only see its own directory, so the artifact is staged there.
+	let stage_artifact = queue
+		.workers
+		.running
+		.get(worker)
+		.and_then(|data| data.handle.dir())
+		.map(|dir| dir.stage_artifact(&job.artifact.path));
 	let execution_timer = queue.metrics.time_execution();
 	queue.mux.push(
 		async move {
 			let _timer = execution_timer;
-			let outcome = super::worker_intf::start_work(
-				idle,
-				job.artifact.clone(),
-				job.exec_timeout,
-				job.params,
-			)
-			.await;
+			let artifact = match stage_artifact {
+				Some(stage_artifact) => match stage_artifact.await {
+					Ok(path) => ArtifactPathId { id: job.artifact.id.clone(), path },
+					Err(err) => {
+						let err = InternalValidationError::CouldNotOpenFile(format!(
+							"cannot stage the artifact for the sandboxed worker: {}",
+							err
+						));
+						let outcome = Outcome::InternalError { err };
+						return QueueEvent::StartWork(
+							worker,
+							outcome,
+							job.artifact.id,
+							job.result_tx,
+						)
+					},
+				},
+				None => job.artifact.clone(),
+			};
+			let outcome =
+				super::worker_intf::start_work(idle, artifact, job.exec_timeout, job.params).await;
 			QueueEvent::StartWork(worker, outcome, job.artifact.id, job.result_tx)
 		}
 		.boxed(),
@@ -481,8 +596,19 @@ pub fn start(
 	program_path: PathBuf,
 	worker_capacity: usize,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<WorkerSandbox>,
 ) -> (mpsc::Sender<ToQueue>, impl Future<Output = ()>) {
 	let (to_queue_tx, to_queue_rx) = mpsc::channel(20);
-	let run = Queue::new(metrics, program_path, worker_capacity, spawn_timeout, to_queue_rx).run();
+	let run = Queue::new(
+		metrics,
+		program_path,
+		worker_capacity,
+		spawn_timeout,
+		enable_seccomp,
+		sandbox,
+		to_queue_rx,
+	)
+	.run();
 	(to_queue_tx, run)
 }
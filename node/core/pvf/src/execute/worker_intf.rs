@@ -18,6 +18,7 @@
 
 use crate::{
 	artifacts::ArtifactPathId,
+	sandbox::{SandboxSetupStage, WorkerSandbox},
 	worker_intf::{
 		path_to_bytes, spawn_with_program_path, IdleWorker, SpawnErr, WorkerHandle,
 		JOB_TIMEOUT_WALL_CLOCK_FACTOR,
@@ -31,6 +32,7 @@ use polkadot_node_core_pvf_common::{
 	error::InternalValidationError,
 	execute::{Handshake, Response},
 	framed_recv, framed_send,
+	prepare::MemoryStats,
 };
 use polkadot_parachain::primitives::ValidationResult;
 use polkadot_primitives::ExecutorParams;
@@ -40,19 +42,45 @@ use tokio::{io, net::UnixStream};
 /// Spawns a new worker with the given program path that acts as the worker and the spawn timeout.
 /// Sends a handshake message to the worker as soon as it is spawned.
 ///
+/// If a sandbox is given, the worker is set up in it with the limits for the given executor
+/// parameters, see [`WorkerSandbox::execution_limits`].
+///
 /// The program should be able to handle `<program-path> execute-worker <socket-path>` invocation.
 pub async fn spawn(
 	program_path: &Path,
 	executor_params: ExecutorParams,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<&WorkerSandbox>,
 ) -> Result<(IdleWorker, WorkerHandle), SpawnErr> {
-	let (mut idle_worker, worker_handle) = spawn_with_program_path(
-		"execute",
-		program_path,
-		&["execute-worker", "--node-impl-version", env!("SUBSTRATE_CLI_IMPL_VERSION")],
-		spawn_timeout,
-	)
-	.await?;
+	let mut extra_args =
+		vec!["execute-worker", "--node-impl-version", env!("SUBSTRATE_CLI_IMPL_VERSION")];
+	if enable_seccomp {
+		extra_args.push("--enable-seccomp");
+	}
+	let worker_dir = sandbox.map(WorkerSandbox::create_worker_dir).transpose()?;
+	if let Some(worker_dir) = &worker_dir {
+		extra_args.extend(worker_dir.worker_args());
+	}
+	let (mut idle_worker, mut worker_handle) =
+		spawn_with_program_path("execute", program_path, &extra_args, spawn_timeout).await?;
+	if let Some(worker_dir) = worker_dir {
+		worker_handle.set_dir(worker_dir);
+	}
+	if let Some(sandbox) = sandbox {
+		let limits = sandbox.execution_limits(&executor_params).map_err(|err| {
+			gum::warn!(
+				target: LOG_TARGET,
+				worker_pid = %idle_worker.pid,
+				"cannot derive the worker limits from the executor params: {}",
+				err,
+			);
+			SpawnErr::Sandbox(SandboxSetupStage::Cgroup)
+		})?;
+		sandbox
+			.setup_worker(&mut idle_worker, &mut worker_handle, limits, spawn_timeout)
+			.await?;
+	}
 	send_handshake(&mut idle_worker.stream, Handshake { executor_params })
 		.await
 		.map_err(|error| {
@@ -73,7 +101,12 @@ pub async fn spawn(
 pub enum Outcome {
 	/// PVF execution completed successfully and the result is returned. The worker is ready for
 	/// another job.
-	Ok { result_descriptor: ValidationResult, duration: Duration, idle_worker: IdleWorker },
+	Ok {
+		result_descriptor: ValidationResult,
+		duration: Duration,
+		memory_stats: MemoryStats,
+		idle_worker: IdleWorker,
+	},
 	/// The candidate validation failed. It may be for example because the wasm execution triggered a trap.
 	/// Errors related to the preparation process are not expected to be encountered by the execution workers.
 	InvalidCandidate { err: String, idle_worker: IdleWorker },
@@ -177,8 +210,12 @@ pub async fn start_work(
 	};
 
 	match response {
-		Response::Ok { result_descriptor, duration } =>
-			Outcome::Ok { result_descriptor, duration, idle_worker: IdleWorker { stream, pid } },
+		Response::Ok { result_descriptor, duration, memory_stats } => Outcome::Ok {
+			result_descriptor,
+			duration,
+			memory_stats,
+			idle_worker: IdleWorker { stream, pid },
+		},
 		Response::InvalidCandidate(err) =>
 			Outcome::InvalidCandidate { err, idle_worker: IdleWorker { stream, pid } },
 		Response::TimedOut => Outcome::HardTimeout,
@@ -21,10 +21,12 @@
 //! [`ValidationHost`], that allows communication with that event-loop.
 
 use crate::{
-	artifacts::{ArtifactId, ArtifactPathId, ArtifactState, Artifacts},
+	artifacts::{self, ArtifactId, ArtifactPathId, ArtifactState, Artifacts},
 	execute::{self, PendingExecutionRequest},
 	metrics::Metrics,
-	prepare, Priority, ValidationError, LOG_TARGET,
+	prepare,
+	sandbox::{SandboxConfig, WorkerSandbox},
+	Priority, ValidationError, LOG_TARGET,
 };
 use always_assert::never;
 use futures::{
@@ -159,6 +161,12 @@ pub struct Config {
 	pub execute_worker_spawn_timeout: Duration,
 	/// The maximum number of execute workers that can run at the same time.
 	pub execute_workers_max_num: usize,
+	/// Whether the workers should restrict the threads running untrusted code with the seccomp
+	/// syscall allowlist. A worker making a forbidden syscall is killed.
+	pub enable_seccomp: bool,
+	/// If set, the workers are spawned in their own namespaces and cgroups, with hard memory
+	/// limits derived from the executor parameters. Only supported on Linux.
+	pub sandbox: Option<SandboxConfig>,
 }
 
 impl Config {
@@ -177,6 +185,8 @@ impl Config {
 			execute_worker_program_path: program_path,
 			execute_worker_spawn_timeout: Duration::from_secs(3),
 			execute_workers_max_num: 2,
+			enable_seccomp: false,
+			sandbox: None,
 		}
 	}
 }
@@ -189,11 +199,35 @@ impl Config {
 /// The future should not return normally but if it does then that indicates an unrecoverable error.
 /// In that case all pending requests will be canceled, dropping the result senders and new ones
 /// will be rejected.
-pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<Output = ()>) {
+pub fn start(mut config: Config, metrics: Metrics) -> (ValidationHost, impl Future<Output = ()>) {
 	gum::debug!(target: LOG_TARGET, ?config, "starting PVF validation host");
 
+	// Sandboxed workers see the artifacts at the same paths as the host, which requires them to be
+	// absolute.
+	if config.sandbox.is_some() && config.cache_path.is_relative() {
+		if let Ok(current_dir) = std::env::current_dir() {
+			config.cache_path = current_dir.join(&config.cache_path);
+		}
+	}
+	let sandbox = config
+		.sandbox
+		.clone()
+		.map(|sandbox_config| WorkerSandbox::new(sandbox_config, config.cache_path.clone()));
+	if let Some(sandbox) = &sandbox {
+		if let Err(err) = sandbox.init() {
+			gum::error!(
+				target: LOG_TARGET,
+				?err,
+				"Cannot initialize the cgroup for the PVF worker sandbox. Workers will fail to spawn until it is set up."
+			);
+		}
+	}
+
 	// Run checks for supported security features once per host startup.
 	warn_if_no_landlock();
+	if config.enable_seccomp {
+		warn_if_no_seccomp();
+	}
 
 	let (to_host_tx, to_host_rx) = mpsc::channel(10);
 
@@ -204,6 +238,8 @@ pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<O
 		config.prepare_worker_program_path.clone(),
 		config.cache_path.clone(),
 		config.prepare_worker_spawn_timeout,
+		config.enable_seccomp,
+		sandbox.clone(),
 	);
 
 	let (to_prepare_queue_tx, from_prepare_queue_rx, run_prepare_queue) = prepare::start_queue(
@@ -220,6 +256,8 @@ pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<O
 		config.execute_worker_program_path.to_owned(),
 		config.execute_workers_max_num,
 		config.execute_worker_spawn_timeout,
+		config.enable_seccomp,
+		sandbox,
 	);
 
 	let (to_sweeper_tx, to_sweeper_rx) = mpsc::channel(100);
@@ -814,12 +852,14 @@ async fn handle_cleanup_pulse(
 	Ok(())
 }
 
-/// A simple task which sole purpose is to delete files thrown at it.
+/// A simple task which sole purpose is to delete artifact files thrown at it, together with their
+/// metadata.
 async fn sweeper_task(mut sweeper_rx: mpsc::Receiver<PathBuf>) {
 	loop {
 		match sweeper_rx.next().await {
 			None => break,
 			Some(condemned) => {
+				let _ = tokio::fs::remove_file(artifacts::metadata_path(&condemned)).await;
 				let result = tokio::fs::remove_file(&condemned).await;
 				gum::trace!(
 					target: LOG_TARGET,
@@ -884,6 +924,27 @@ fn warn_if_no_landlock() {
 	);
 }
 
+/// Check if seccomp is supported and emit a warning if not. Only called if seccomp is enabled in
+/// the configuration.
+fn warn_if_no_seccomp() {
+	#[cfg(target_os = "linux")]
+	{
+		use polkadot_node_core_pvf_common::worker::security::seccomp;
+		if !seccomp::check_is_fully_enabled() {
+			gum::warn!(
+				target: LOG_TARGET,
+				"Cannot enable seccomp, a Linux kernel security feature. PVF workers will run without a syscall filter. Consider running on a kernel with seccomp support for maximum security."
+			);
+		}
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	gum::warn!(
+		target: LOG_TARGET,
+		"Cannot enable seccomp, a Linux kernel security feature. PVF workers will run without a syscall filter. Consider running on Linux with seccomp support for maximum security."
+	);
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
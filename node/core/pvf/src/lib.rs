@@ -95,6 +95,7 @@ mod host;
 mod metrics;
 mod prepare;
 mod priority;
+mod sandbox;
 mod worker_intf;
 
 #[doc(hidden)]
@@ -108,6 +109,7 @@ pub use error::{InvalidCandidate, ValidationError};
 pub use host::{start, Config, ValidationHost};
 pub use metrics::Metrics;
 pub use priority::Priority;
+pub use sandbox::{SandboxConfig, SandboxSetupStage};
 pub use worker_intf::{framed_recv, framed_send, JOB_TIMEOUT_WALL_CLOCK_FACTOR};
 
 // Re-export some common types.
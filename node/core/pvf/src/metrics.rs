@@ -16,6 +16,7 @@
 
 //! Prometheus metrics related to the validation host.
 
+use crate::sandbox::SandboxSetupStage;
 use polkadot_node_core_pvf_common::prepare::MemoryStats;
 use polkadot_node_metrics::metrics::{self, prometheus};
 
@@ -94,6 +95,17 @@ impl Metrics {
 			}
 		}
 	}
+
+	/// Observe memory stats for execution.
+	#[allow(unused_variables)]
+	pub(crate) fn observe_execution_memory_metrics(&self, memory_stats: MemoryStats) {
+		#[cfg(target_os = "linux")]
+		if let Some(metrics) = &self.0 {
+			if let Some(max_rss) = memory_stats.max_rss {
+				metrics.execution_max_rss.observe(max_rss as f64);
+			}
+		}
+	}
 }
 
 #[derive(Clone)]
@@ -101,6 +113,7 @@ struct MetricsInner {
 	worker_spawning: prometheus::CounterVec<prometheus::U64>,
 	worker_spawned: prometheus::CounterVec<prometheus::U64>,
 	worker_retired: prometheus::CounterVec<prometheus::U64>,
+	worker_sandbox_setup_failed: prometheus::CounterVec<prometheus::U64>,
 	prepare_enqueued: prometheus::Counter<prometheus::U64>,
 	prepare_concluded: prometheus::Counter<prometheus::U64>,
 	execute_enqueued: prometheus::Counter<prometheus::U64>,
@@ -113,6 +126,8 @@ struct MetricsInner {
 	preparation_max_allocated: prometheus::Histogram,
 	#[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
 	preparation_max_resident: prometheus::Histogram,
+	#[cfg(target_os = "linux")]
+	execution_max_rss: prometheus::Histogram,
 }
 
 impl metrics::Metrics for Metrics {
@@ -148,6 +163,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			worker_sandbox_setup_failed: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_pvf_worker_sandbox_setup_failed",
+						"The total number of workers that could not be set up in the sandbox",
+					),
+					&["flavor", "stage"],
+				)?,
+				registry,
+			)?,
 			prepare_enqueued: prometheus::register(
 				prometheus::Counter::new(
 					"polkadot_pvf_prepare_enqueued",
@@ -270,6 +295,19 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			#[cfg(target_os = "linux")]
+			execution_max_rss: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_pvf_execution_max_rss",
+						"ru_maxrss (maximum resident set size) observed for execution (in kilobytes)",
+					).buckets(
+						prometheus::exponential_buckets(8192.0, 2.0, 10)
+							.expect("arguments are always valid; qed"),
+					),
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(inner)))
 	}
@@ -315,4 +353,14 @@ impl<'a> WorkerRelatedMetrics<'a> {
 			metrics.worker_retired.with_label_values(&[self.flavor.as_label()]).inc();
 		}
 	}
+
+	/// When the worker could not be set up in the sandbox.
+	pub(crate) fn on_sandbox_setup_failed(&self, stage: SandboxSetupStage) {
+		if let Some(metrics) = &self.metrics.0 {
+			metrics
+				.worker_sandbox_setup_failed
+				.with_label_values(&[self.flavor.as_label(), stage.as_label()])
+				.inc();
+		}
+	}
 }
@@ -17,7 +17,8 @@
 use super::worker_intf::{self, Outcome};
 use crate::{
 	metrics::Metrics,
-	worker_intf::{IdleWorker, WorkerHandle},
+	sandbox::{SandboxSetupStage, WorkerSandbox},
+	worker_intf::{IdleWorker, SpawnErr, WorkerDeath, WorkerHandle, WORKER_EXIT_TIMEOUT},
 	LOG_TARGET,
 };
 use always_assert::never;
@@ -30,7 +31,7 @@ use polkadot_node_core_pvf_common::{
 };
 use slotmap::HopSlotMap;
 use std::{
-	fmt,
+	fmt, io,
 	path::{Path, PathBuf},
 	task::Poll,
 	time::Duration,
@@ -105,6 +106,8 @@ impl fmt::Debug for WorkerData {
 enum PoolEvent {
 	Spawn(IdleWorker, WorkerHandle),
 	StartWork(Worker, Outcome),
+	/// The worker lost its connection while preparing and its process has been reaped.
+	WorkerDied(Worker, String, WorkerDeath),
 }
 
 type Mux = FuturesUnordered<BoxFuture<'static, PoolEvent>>;
@@ -113,6 +116,8 @@ struct Pool {
 	program_path: PathBuf,
 	cache_path: PathBuf,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<WorkerSandbox>,
 	to_pool: mpsc::Receiver<ToPool>,
 	from_pool: mpsc::UnboundedSender<FromPool>,
 	spawned: HopSlotMap<Worker, WorkerData>,
@@ -128,6 +133,8 @@ async fn run(
 		program_path,
 		cache_path,
 		spawn_timeout,
+		enable_seccomp,
+		sandbox,
 		to_pool,
 		mut from_pool,
 		mut spawned,
@@ -155,13 +162,15 @@ async fn run(
 					&program_path,
 					&cache_path,
 					spawn_timeout,
+					enable_seccomp,
+					sandbox.as_ref(),
 					&mut spawned,
 					&mut mux,
 					to_pool,
 				)
 			}
 			ev = mux.select_next_some() => {
-				break_if_fatal!(handle_mux(&metrics, &mut from_pool, &mut spawned, ev))
+				break_if_fatal!(handle_mux(&metrics, &mut from_pool, &mut spawned, &mut mux, ev))
 			}
 		}
 
@@ -201,6 +210,8 @@ fn handle_to_pool(
 	program_path: &Path,
 	cache_path: &Path,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<&WorkerSandbox>,
 	spawned: &mut HopSlotMap<Worker, WorkerData>,
 	mux: &mut Mux,
 	to_pool: ToPool,
@@ -209,11 +220,30 @@ fn handle_to_pool(
 		ToPool::Spawn => {
 			gum::debug!(target: LOG_TARGET, "spawning a new prepare worker");
 			metrics.prepare_worker().on_begin_spawn();
-			mux.push(spawn_worker_task(program_path.to_owned(), spawn_timeout).boxed());
+			mux.push(
+				spawn_worker_task(
+					metrics.clone(),
+					program_path.to_owned(),
+					spawn_timeout,
+					enable_seccomp,
+					sandbox.cloned(),
+				)
+				.boxed(),
+			);
 		},
 		ToPool::StartWork { worker, pvf, artifact_path } => {
 			if let Some(data) = spawned.get_mut(worker) {
 				if let Some(idle) = data.idle.take() {
+					// The limits of a sandboxed worker are adjusted to this job.
+					let set_limits = match (sandbox, data.handle.cgroup()) {
+						(Some(sandbox), Some(cgroup)) => Some(
+							cgroup.set_limits(sandbox.preparation_limits(&pvf.executor_params())),
+						),
+						_ => None,
+					};
+					// A sandboxed worker can only write to its own directory.
+					let tmp_dir = data.handle.dir().map_or(cache_path, |dir| dir.path()).to_owned();
+
 					let preparation_timer = metrics.time_preparation();
 					mux.push(
 						start_work_task(
@@ -221,7 +251,8 @@ fn handle_to_pool(
 							worker,
 							idle,
 							pvf,
-							cache_path.to_owned(),
+							set_limits,
+							tmp_dir,
 							artifact_path,
 							preparation_timer,
 						)
@@ -248,14 +279,25 @@ fn handle_to_pool(
 	}
 }
 
-async fn spawn_worker_task(program_path: PathBuf, spawn_timeout: Duration) -> PoolEvent {
+async fn spawn_worker_task(
+	metrics: Metrics,
+	program_path: PathBuf,
+	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<WorkerSandbox>,
+) -> PoolEvent {
 	use futures_timer::Delay;
 
 	loop {
-		match worker_intf::spawn(&program_path, spawn_timeout).await {
+		match worker_intf::spawn(&program_path, spawn_timeout, enable_seccomp, sandbox.as_ref())
+			.await
+		{
 			Ok((idle, handle)) => break PoolEvent::Spawn(idle, handle),
 			Err(err) => {
 				gum::warn!(target: LOG_TARGET, "failed to spawn a prepare worker: {:?}", err);
+				if let SpawnErr::Sandbox(stage) = err {
+					metrics.prepare_worker().on_sandbox_setup_failed(stage);
+				}
 
 				// Assume that the failure intermittent and retry after a delay.
 				Delay::new(Duration::from_secs(3)).await;
@@ -269,11 +311,27 @@ async fn start_work_task<Timer>(
 	worker: Worker,
 	idle: IdleWorker,
 	pvf: PvfPrepData,
-	cache_path: PathBuf,
+	set_limits: Option<impl Future<Output = io::Result<()>>>,
+	tmp_dir: PathBuf,
 	artifact_path: PathBuf,
 	_preparation_timer: Option<Timer>,
 ) -> PoolEvent {
-	let outcome = worker_intf::start_work(&metrics, idle, pvf, &cache_path, artifact_path).await;
+	// If the limits can't be set, the worker must not run the job with the limits of the previous
+	// one, so it is killed.
+	if let Some(set_limits) = set_limits {
+		if let Err(err) = set_limits.await {
+			gum::warn!(
+				target: LOG_TARGET,
+				worker_pid = %idle.pid,
+				"failed to set the limits of the prepare worker: {:?}",
+				err,
+			);
+			metrics.prepare_worker().on_sandbox_setup_failed(SandboxSetupStage::Cgroup);
+			return PoolEvent::StartWork(worker, Outcome::Unreachable)
+		}
+	}
+
+	let outcome = worker_intf::start_work(&metrics, idle, pvf, &tmp_dir, artifact_path).await;
 	PoolEvent::StartWork(worker, outcome)
 }
 
@@ -281,6 +339,7 @@ fn handle_mux(
 	metrics: &Metrics,
 	from_pool: &mut mpsc::UnboundedSender<FromPool>,
 	spawned: &mut HopSlotMap<Worker, WorkerData>,
+	mux: &mut Mux,
 	event: PoolEvent,
 ) -> Result<(), Fatal> {
 	match event {
@@ -324,15 +383,20 @@ fn handle_mux(
 					Ok(())
 				},
 				Outcome::IoErr(err) => {
-					if attempt_retire(metrics, spawned, worker) {
-						reply(
-							from_pool,
-							FromPool::Concluded {
-								worker,
-								rip: true,
-								result: Err(PrepareError::IoErr(err)),
-							},
-						)?;
+					// The worker may have been killed for making a forbidden syscall or for exceeding
+					// its memory limit, which we want to tell apart from other I/O errors. Reaping
+					// the process may take a moment, so it's done in the background and concluded
+					// on `PoolEvent::WorkerDied`.
+					if let Some(data) = spawned.remove(worker) {
+						metrics.prepare_worker().on_retired();
+						let mut handle = data.handle;
+						mux.push(
+							async move {
+								let death = handle.cause_of_death(WORKER_EXIT_TIMEOUT).await;
+								PoolEvent::WorkerDied(worker, err, death)
+							}
+							.boxed(),
+						);
 					}
 
 					Ok(())
@@ -353,6 +417,21 @@ fn handle_mux(
 				},
 			}
 		},
+		PoolEvent::WorkerDied(worker, err, death) => {
+			let result = match death {
+				WorkerDeath::ForbiddenSyscall => Err(PrepareError::ForbiddenSyscall),
+				WorkerDeath::OutOfMemory => {
+					gum::warn!(
+						target: LOG_TARGET,
+						"prepare worker exceeded the memory limit of its sandbox, consider raising \
+						 --pvf-sandbox-memory-overhead",
+					);
+					Err(PrepareError::OutOfMemory)
+				},
+				WorkerDeath::Unknown => Err(PrepareError::IoErr(err)),
+			};
+			reply(from_pool, FromPool::Concluded { worker, rip: true, result })
+		},
 	}
 }
 
@@ -419,6 +498,8 @@ pub fn start(
 	program_path: PathBuf,
 	cache_path: PathBuf,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<WorkerSandbox>,
 ) -> (mpsc::Sender<ToPool>, mpsc::UnboundedReceiver<FromPool>, impl Future<Output = ()>) {
 	let (to_pool_tx, to_pool_rx) = mpsc::channel(10);
 	let (from_pool_tx, from_pool_rx) = mpsc::unbounded();
@@ -428,6 +509,8 @@ pub fn start(
 		program_path,
 		cache_path,
 		spawn_timeout,
+		enable_seccomp,
+		sandbox,
 		to_pool: to_pool_rx,
 		from_pool: from_pool_tx,
 		spawned: HopSlotMap::with_capacity_and_key(20),
@@ -17,7 +17,9 @@
 //! Host interface to the prepare worker.
 
 use crate::{
+	artifacts::{ArtifactId, ArtifactMetadata},
 	metrics::Metrics,
+	sandbox::WorkerSandbox,
 	worker_intf::{
 		path_to_bytes, spawn_with_program_path, tmpfile_in, IdleWorker, SpawnErr, WorkerHandle,
 		JOB_TIMEOUT_WALL_CLOCK_FACTOR,
@@ -31,6 +33,7 @@ use polkadot_node_core_pvf_common::{
 	prepare::PrepareStats,
 	pvf::PvfPrepData,
 };
+use polkadot_primitives::ExecutorParams;
 
 use sp_core::hexdisplay::HexDisplay;
 use std::{
@@ -41,18 +44,37 @@ use tokio::{io, net::UnixStream};
 
 /// Spawns a new worker with the given program path that acts as the worker and the spawn timeout.
 ///
+/// If a sandbox is given, the worker is set up in it with the limits for the default executor
+/// parameters. The limits are adjusted for every job, see [`WorkerSandbox::preparation_limits`].
+///
 /// The program should be able to handle `<program-path> prepare-worker <socket-path>` invocation.
 pub async fn spawn(
 	program_path: &Path,
 	spawn_timeout: Duration,
+	enable_seccomp: bool,
+	sandbox: Option<&WorkerSandbox>,
 ) -> Result<(IdleWorker, WorkerHandle), SpawnErr> {
-	spawn_with_program_path(
-		"prepare",
-		program_path,
-		&["prepare-worker", "--node-impl-version", env!("SUBSTRATE_CLI_IMPL_VERSION")],
-		spawn_timeout,
-	)
-	.await
+	let mut extra_args =
+		vec!["prepare-worker", "--node-impl-version", env!("SUBSTRATE_CLI_IMPL_VERSION")];
+	if enable_seccomp {
+		extra_args.push("--enable-seccomp");
+	}
+	let worker_dir = sandbox.map(WorkerSandbox::create_worker_dir).transpose()?;
+	if let Some(worker_dir) = &worker_dir {
+		extra_args.extend(worker_dir.worker_args());
+	}
+	let (mut idle_worker, mut worker_handle) =
+		spawn_with_program_path("prepare", program_path, &extra_args, spawn_timeout).await?;
+	if let Some(worker_dir) = worker_dir {
+		worker_handle.set_dir(worker_dir);
+	}
+	if let Some(sandbox) = sandbox {
+		let limits = sandbox.preparation_limits(&ExecutorParams::default());
+		sandbox
+			.setup_worker(&mut idle_worker, &mut worker_handle, limits, spawn_timeout)
+			.await?;
+	}
+	Ok((idle_worker, worker_handle))
 }
 
 pub enum Outcome {
@@ -79,13 +101,16 @@ pub enum Outcome {
 /// Given the idle token of a worker and parameters of work, communicates with the worker and
 /// returns the outcome.
 ///
+/// The worker writes the artifact to a temporary file in `tmp_dir`, which must be visible to the
+/// worker and on the same filesystem as `artifact_path`.
+///
 /// NOTE: Returning the `TimedOut`, `IoErr` or `Unreachable` outcomes will trigger the child process
 /// being killed.
 pub async fn start_work(
 	metrics: &Metrics,
 	worker: IdleWorker,
 	pvf: PvfPrepData,
-	cache_path: &Path,
+	tmp_dir: &Path,
 	artifact_path: PathBuf,
 ) -> Outcome {
 	let IdleWorker { stream, pid } = worker;
@@ -97,7 +122,8 @@ pub async fn start_work(
 		artifact_path.display(),
 	);
 
-	with_tmp_file(stream, pid, cache_path, |tmp_file, mut stream| async move {
+	with_tmp_file(stream, pid, tmp_dir, |tmp_file, mut stream| async move {
+		let artifact_id = ArtifactId::from_pvf_prep_data(&pvf);
 		let preparation_timeout = pvf.prep_timeout();
 		if let Err(err) = send_request(&mut stream, pvf, &tmp_file).await {
 			gum::warn!(
@@ -130,6 +156,7 @@ pub async fn start_work(
 					prepare_result,
 					pid,
 					tmp_file,
+					artifact_id,
 					artifact_path,
 					preparation_timeout,
 				)
@@ -168,15 +195,17 @@ async fn handle_response(
 	result: PrepareResult,
 	worker_pid: u32,
 	tmp_file: PathBuf,
+	artifact_id: ArtifactId,
 	artifact_path: PathBuf,
 	preparation_timeout: Duration,
 ) -> Outcome {
-	let PrepareStats { cpu_time_elapsed, memory_stats } = match result.clone() {
+	let prepare_stats = match result.clone() {
 		Ok(result) => result,
 		// Timed out on the child. This should already be logged by the child.
 		Err(PrepareError::TimedOut) => return Outcome::TimedOut,
 		Err(_) => return Outcome::Concluded { worker, result },
 	};
+	let PrepareStats { cpu_time_elapsed, memory_stats } = prepare_stats.clone();
 
 	if cpu_time_elapsed > preparation_timeout {
 		// The job didn't complete within the timeout.
@@ -191,6 +220,21 @@ async fn handle_response(
 		return Outcome::TimedOut
 	}
 
+	// Write the metadata before promoting the artifact, so that an artifact is never visible
+	// without its metadata. If this fails the artifact is still usable, but it will be evicted on
+	// the next start-up.
+	if let Err(err) =
+		write_metadata(&tmp_file, artifact_id, &artifact_path, prepare_stats.clone()).await
+	{
+		gum::warn!(
+			target: LOG_TARGET,
+			%worker_pid,
+			"failed to write the metadata for the artifact {}: {:?}",
+			artifact_path.display(),
+			err,
+		);
+	}
+
 	gum::debug!(
 		target: LOG_TARGET,
 		%worker_pid,
@@ -221,16 +265,29 @@ async fn handle_response(
 	outcome
 }
 
-/// Create a temporary file for an artifact at the given cache path and execute the given
+/// Computes the metadata of the WIP artifact and writes it next to the final artifact location.
+async fn write_metadata(
+	tmp_file: &Path,
+	artifact_id: ArtifactId,
+	artifact_path: &Path,
+	prepare_stats: PrepareStats,
+) -> io::Result<()> {
+	let artifact = tokio::fs::read(tmp_file).await?;
+	ArtifactMetadata::new(artifact_id, &artifact, prepare_stats)
+		.write(artifact_path)
+		.await
+}
+
+/// Create a temporary file for an artifact in the given directory and execute the given
 /// future/closure passing the file path in.
 ///
 /// The function will try best effort to not leave behind the temporary file.
-async fn with_tmp_file<F, Fut>(stream: UnixStream, pid: u32, cache_path: &Path, f: F) -> Outcome
+async fn with_tmp_file<F, Fut>(stream: UnixStream, pid: u32, tmp_dir: &Path, f: F) -> Outcome
 where
 	Fut: futures::Future<Output = Outcome>,
 	F: FnOnce(PathBuf, UnixStream) -> Fut,
 {
-	let tmp_file = match tmpfile_in("prepare-artifact-", cache_path).await {
+	let tmp_file = match tmpfile_in("prepare-artifact-", tmp_dir).await {
 		Ok(f) => f,
 		Err(err) => {
 			gum::warn!(
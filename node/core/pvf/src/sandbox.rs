@@ -0,0 +1,498 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optional sandbox for the worker processes.
+//!
+//! When the sandbox is enabled, every worker:
+//!
+//! 1. Moves itself into new user, mount, network, PID and IPC namespaces and pivots into an empty
+//!    root that only contains its own directory, right after connecting to the host. It then
+//!    reports whether that succeeded. The directory is created by the host in the artifacts
+//!    directory, so that artifacts can be moved in and out of it without copying: the host links
+//!    the artifact of every execute job into it, and prepare workers write their artifacts there.
+//!
+//! 2. Is moved by the host into its own cgroup-v2 group, with a hard memory limit derived from the
+//!    [`ExecutorParams`] of the job and a CPU limit of a single core.
+//!
+//! A worker for which any of this fails is killed and the failure is reported through the metrics.
+//! Workers are never run outside of the sandbox once it is enabled.
+//!
+//! A worker that is killed for exceeding its memory limit is reported as an internal error rather
+//! than as an invalid candidate, as the limit is set by the node and not by the PVF.
+
+use crate::{
+	worker_intf::{framed_recv, IdleWorker, SpawnErr, WorkerHandle},
+	LOG_TARGET,
+};
+use futures::FutureExt as _;
+use futures_timer::Delay;
+use parity_scale_codec::Decode;
+use polkadot_node_core_pvf_common::executor_intf::params_to_max_execution_memory;
+use polkadot_primitives::{ExecutorParam, ExecutorParams};
+use std::{
+	fs,
+	future::Future,
+	io,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+/// The prefix of the names of the cgroups created for the workers.
+const WORKER_CGROUP_PREFIX: &str = "pvf-worker-";
+
+/// The prefix of the names of the directories created for the workers in the artifacts directory.
+const WORKER_DIR_PREFIX: &str = "worker-";
+
+/// The name under which the artifact of an execute job is linked into the directory of the worker.
+const STAGED_ARTIFACT_NAME: &str = "artifact";
+
+/// The memory a prepare worker may use if the executor parameters do not specify
+/// `PrecheckingMaxMemory`.
+const DEFAULT_PREPARATION_MEMORY_MAX: u64 = 2 * 1024 * 1024 * 1024;
+
+/// The default of [`SandboxConfig::worker_memory_overhead`].
+pub const DEFAULT_WORKER_MEMORY_OVERHEAD: u64 = 512 * 1024 * 1024;
+
+/// The value written to `cpu.max`, i.e. the quota and the period in microseconds. Compilation and
+/// execution are both single-threaded (parallel compilation is disabled in the executor config),
+/// so a worker never needs more than one core.
+const CPU_MAX: &str = "100000 100000";
+
+/// Configuration of the worker sandbox.
+#[derive(Clone, Debug)]
+pub struct SandboxConfig {
+	/// A cgroup-v2 directory delegated to the node, e.g. `/sys/fs/cgroup/polkadot-pvf`. The
+	/// `memory` and `cpu` controllers must be available in it, and the node must be allowed to
+	/// create child groups and move its workers into them.
+	pub cgroup_root: PathBuf,
+	/// The memory a worker may use, in bytes, on top of what the executor parameters allow the
+	/// PVF to use. This is not bounded by the executor parameters: it covers the resident pages of
+	/// the worker binary and of its runtime, and the compiled artifact, whose size depends on the
+	/// PVF and on the version of the compiler. It should be raised if workers are reported to
+	/// exceed the memory limit of their sandbox.
+	pub worker_memory_overhead: u64,
+}
+
+impl SandboxConfig {
+	/// Creates the configuration for the given cgroup root, with the default memory overhead.
+	pub fn new(cgroup_root: PathBuf) -> Self {
+		Self { cgroup_root, worker_memory_overhead: DEFAULT_WORKER_MEMORY_OVERHEAD }
+	}
+}
+
+/// The part of the sandbox setup that failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SandboxSetupStage {
+	/// The worker could not enter its namespaces.
+	Namespaces,
+	/// The worker could not be moved into its cgroup, or its limits could not be set.
+	Cgroup,
+}
+
+impl SandboxSetupStage {
+	pub(crate) fn as_label(&self) -> &'static str {
+		match *self {
+			SandboxSetupStage::Namespaces => "namespaces",
+			SandboxSetupStage::Cgroup => "cgroup",
+		}
+	}
+}
+
+/// The hard resource limits of a worker's cgroup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ResourceLimits {
+	/// The value written to `memory.max`, in bytes.
+	pub memory_max: u64,
+}
+
+/// The sandbox the workers are spawned in.
+#[derive(Clone, Debug)]
+pub(crate) struct WorkerSandbox {
+	cgroup_root: PathBuf,
+	worker_memory_overhead: u64,
+	artifacts_dir: PathBuf,
+}
+
+impl WorkerSandbox {
+	/// Creates the sandbox for workers that use the given artifacts directory.
+	pub fn new(config: SandboxConfig, artifacts_dir: PathBuf) -> Self {
+		Self {
+			cgroup_root: config.cgroup_root,
+			worker_memory_overhead: config.worker_memory_overhead,
+			artifacts_dir,
+		}
+	}
+
+	/// Removes the groups and directories left over by previous runs and enables the controllers
+	/// needed for the limits in the child groups.
+	pub fn init(&self) -> io::Result<()> {
+		remove_stale_cgroups(&self.cgroup_root);
+		remove_stale_worker_dirs(&self.artifacts_dir);
+		fs::write(self.cgroup_root.join("cgroup.subtree_control"), "+memory +cpu")
+	}
+
+	/// Limits for a prepare worker preparing a PVF with the given executor parameters.
+	pub fn preparation_limits(&self, executor_params: &ExecutorParams) -> ResourceLimits {
+		let prechecking_max_memory = executor_params.iter().find_map(|param| match param {
+			ExecutorParam::PrecheckingMaxMemory(max_memory) => Some(*max_memory),
+			_ => None,
+		});
+		let memory_max = prechecking_max_memory.unwrap_or(DEFAULT_PREPARATION_MEMORY_MAX);
+		ResourceLimits { memory_max: memory_max.saturating_add(self.worker_memory_overhead) }
+	}
+
+	/// Limits for an execute worker executing PVFs with the given executor parameters.
+	pub fn execution_limits(
+		&self,
+		executor_params: &ExecutorParams,
+	) -> Result<ResourceLimits, String> {
+		let memory_max = params_to_max_execution_memory(executor_params)?;
+		Ok(ResourceLimits { memory_max: memory_max.saturating_add(self.worker_memory_overhead) })
+	}
+
+	/// Creates the directory of a new worker. The worker sees it at the same path as the host, so
+	/// the artifacts directory must be absolute.
+	pub fn create_worker_dir(&self) -> Result<WorkerDir, SpawnErr> {
+		if !self.artifacts_dir.is_absolute() || self.artifacts_dir.to_str().is_none() {
+			gum::warn!(
+				target: LOG_TARGET,
+				artifacts_dir = ?self.artifacts_dir,
+				"the artifacts dir of sandboxed workers must be an absolute utf-8 path",
+			);
+			return Err(SpawnErr::Sandbox(SandboxSetupStage::Namespaces))
+		}
+
+		tempfile::Builder::new()
+			.prefix(WORKER_DIR_PREFIX)
+			.tempdir_in(&self.artifacts_dir)
+			.map(|dir| WorkerDir { dir })
+			.map_err(|err| {
+				gum::warn!(
+					target: LOG_TARGET,
+					artifacts_dir = ?self.artifacts_dir,
+					"failed to create the directory of a sandboxed worker: {:?}",
+					err,
+				);
+				SpawnErr::Sandbox(SandboxSetupStage::Namespaces)
+			})
+	}
+
+	/// Waits for a freshly spawned worker to report that it entered its namespaces, then moves it
+	/// into a new cgroup with the given limits.
+	pub async fn setup_worker(
+		&self,
+		idle_worker: &mut IdleWorker,
+		worker_handle: &mut WorkerHandle,
+		limits: ResourceLimits,
+		timeout: Duration,
+	) -> Result<(), SpawnErr> {
+		let pid = idle_worker.pid;
+		let status = futures::select! {
+			status = recv_sandbox_status(&mut idle_worker.stream).fuse() => status,
+			_ = Delay::new(timeout).fuse() =>
+				Err(io::Error::new(io::ErrorKind::TimedOut, "no sandbox status from the worker")),
+		};
+		match status {
+			Ok(Ok(())) => (),
+			Ok(Err(err)) => {
+				gum::warn!(
+					target: LOG_TARGET,
+					worker_pid = %pid,
+					"worker failed to enter its namespaces: {}",
+					err,
+				);
+				return Err(SpawnErr::Sandbox(SandboxSetupStage::Namespaces))
+			},
+			Err(err) => {
+				gum::warn!(
+					target: LOG_TARGET,
+					worker_pid = %pid,
+					"failed to receive the sandbox status from the worker: {:?}",
+					err,
+				);
+				return Err(SpawnErr::Sandbox(SandboxSetupStage::Namespaces))
+			},
+		}
+
+		let cgroup_root = self.cgroup_root.clone();
+		let cgroup = run_blocking(move || {
+			let cgroup = WorkerCgroup::create(&cgroup_root, pid)?;
+			write_limits(&cgroup.path, limits)?;
+			fs::write(cgroup.path.join("cgroup.procs"), pid.to_string())?;
+			Ok(cgroup)
+		})
+		.await
+		.map_err(|err| {
+			gum::warn!(
+				target: LOG_TARGET,
+				worker_pid = %pid,
+				cgroup_root = ?self.cgroup_root,
+				"failed to move the worker into its cgroup: {:?}",
+				err,
+			);
+			SpawnErr::Sandbox(SandboxSetupStage::Cgroup)
+		})?;
+		worker_handle.set_cgroup(cgroup);
+
+		Ok(())
+	}
+}
+
+/// The directory of a single worker, which is the only part of the host filesystem the worker can
+/// see. It is removed along with its contents on drop.
+#[derive(Debug)]
+pub(crate) struct WorkerDir {
+	dir: tempfile::TempDir,
+}
+
+impl WorkerDir {
+	/// The path of the directory, which is the same for the host and the worker.
+	pub fn path(&self) -> &Path {
+		self.dir.path()
+	}
+
+	/// The extra arguments to pass to the worker so that it enters the sandbox with this directory
+	/// as the only one visible.
+	pub fn worker_args(&self) -> [&str; 2] {
+		let path = self.path().to_str().expect("checked in `create_worker_dir`; qed");
+		["--sandbox-root", path]
+	}
+
+	/// Makes the artifact at the given path available to the worker, in place of the one of the
+	/// previous job, and returns the path the worker can read it from.
+	///
+	/// The artifact is hard-linked, so that neither a copy is needed nor the worker sees any other
+	/// artifact. It is only copied if linking is not supported.
+	pub fn stage_artifact(
+		&self,
+		artifact_path: &Path,
+	) -> impl Future<Output = io::Result<PathBuf>> + 'static {
+		let artifact_path = artifact_path.to_owned();
+		let staged_path = self.path().join(STAGED_ARTIFACT_NAME);
+		run_blocking(move || {
+			match fs::remove_file(&staged_path) {
+				Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+				_ => (),
+			}
+			fs::hard_link(&artifact_path, &staged_path)
+				.or_else(|_| fs::copy(&artifact_path, &staged_path).map(|_| ()))?;
+			Ok(staged_path)
+		})
+	}
+}
+
+/// A cgroup created for a single worker. It is removed on drop, on a best-effort basis: it can
+/// only be removed once the worker has exited, so leftovers are cleaned up when creating the next
+/// ones.
+#[derive(Debug)]
+pub(crate) struct WorkerCgroup {
+	path: PathBuf,
+}
+
+impl WorkerCgroup {
+	fn create(cgroup_root: &Path, pid: u32) -> io::Result<Self> {
+		remove_stale_cgroups(cgroup_root);
+		let path = cgroup_root.join(format!("{}{}", WORKER_CGROUP_PREFIX, pid));
+		fs::create_dir(&path)?;
+		Ok(Self { path })
+	}
+
+	/// Sets the hard limits of the group. The worker is killed by the kernel if it exceeds the
+	/// memory limit.
+	pub fn set_limits(
+		&self,
+		limits: ResourceLimits,
+	) -> impl Future<Output = io::Result<()>> + 'static {
+		let path = self.path.clone();
+		run_blocking(move || write_limits(&path, limits))
+	}
+
+	/// Returns whether a process of the group was killed for exceeding the memory limit.
+	pub async fn was_out_of_memory(&self) -> bool {
+		let events_path = self.path.join("memory.events");
+		let events = run_blocking(move || fs::read_to_string(events_path)).await;
+		events.map_or(false, |events| {
+			events.lines().any(|line| match line.split_once(' ') {
+				Some(("oom_kill", count)) => count.trim().parse::<u64>().map_or(false, |n| n > 0),
+				_ => false,
+			})
+		})
+	}
+}
+
+fn write_limits(cgroup_path: &Path, limits: ResourceLimits) -> io::Result<()> {
+	fs::write(cgroup_path.join("memory.max"), limits.memory_max.to_string())?;
+	fs::write(cgroup_path.join("memory.swap.max"), "0")?;
+	fs::write(cgroup_path.join("cpu.max"), CPU_MAX)
+}
+
+impl Drop for WorkerCgroup {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir(&self.path);
+	}
+}
+
+/// Removes the worker groups under `cgroup_root` that do not contain any processes anymore.
+/// Groups that are still in use can not be removed, so this can be called at any time.
+fn remove_stale_cgroups(cgroup_root: &Path) {
+	let entries = match fs::read_dir(cgroup_root) {
+		Ok(entries) => entries,
+		Err(_) => return,
+	};
+	for entry in entries.flatten() {
+		if entry.file_name().to_string_lossy().starts_with(WORKER_CGROUP_PREFIX) {
+			let _ = fs::remove_dir(entry.path());
+		}
+	}
+}
+
+/// Removes the worker directories under `artifacts_dir`. Must only be called before any workers
+/// are spawned.
+fn remove_stale_worker_dirs(artifacts_dir: &Path) {
+	let entries = match fs::read_dir(artifacts_dir) {
+		Ok(entries) => entries,
+		Err(_) => return,
+	};
+	for entry in entries.flatten() {
+		if entry.file_name().to_string_lossy().starts_with(WORKER_DIR_PREFIX) {
+			let _ = fs::remove_dir_all(entry.path());
+		}
+	}
+}
+
+/// Runs the given filesystem operation on the blocking thread pool, as the cgroup filesystem may
+/// block for a while, e.g. when processes are moved between groups.
+fn run_blocking<T: Send + 'static>(
+	f: impl FnOnce() -> io::Result<T> + Send + 'static,
+) -> impl Future<Output = io::Result<T>> + 'static {
+	tokio::task::spawn_blocking(f)
+		.map(|result| result.unwrap_or_else(|err| Err(io::Error::new(io::ErrorKind::Other, err))))
+}
+
+async fn recv_sandbox_status(
+	stream: &mut tokio::net::UnixStream,
+) -> io::Result<Result<(), String>> {
+	let status_bytes = framed_recv(stream).await?;
+	Result::<(), String>::decode(&mut &status_bytes[..]).map_err(|e| {
+		io::Error::new(
+			io::ErrorKind::Other,
+			format!("sandbox status decode error: {:?}", e.to_string()),
+		)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_matches::assert_matches;
+
+	fn sandbox(artifacts_dir: &Path) -> WorkerSandbox {
+		let config = SandboxConfig::new(PathBuf::from("/sys/fs/cgroup/pvf"));
+		WorkerSandbox::new(config, artifacts_dir.to_owned())
+	}
+
+	#[test]
+	fn preparation_limits_follow_prechecking_max_memory() {
+		let sandbox = sandbox(Path::new("/var/lib/artifacts"));
+		assert_eq!(
+			sandbox.preparation_limits(&ExecutorParams::default()).memory_max,
+			DEFAULT_PREPARATION_MEMORY_MAX + DEFAULT_WORKER_MEMORY_OVERHEAD,
+		);
+
+		let executor_params =
+			ExecutorParams::from(&[ExecutorParam::PrecheckingMaxMemory(1024 * 1024 * 1024)][..]);
+		assert_eq!(
+			sandbox.preparation_limits(&executor_params).memory_max,
+			1024 * 1024 * 1024 + DEFAULT_WORKER_MEMORY_OVERHEAD,
+		);
+	}
+
+	#[test]
+	fn execution_limits_follow_max_memory_pages() {
+		let sandbox = sandbox(Path::new("/var/lib/artifacts"));
+		let small = ExecutorParams::from(&[ExecutorParam::MaxMemoryPages(1024)][..]);
+		let large = ExecutorParams::from(&[ExecutorParam::MaxMemoryPages(4096)][..]);
+
+		let small = sandbox.execution_limits(&small).unwrap().memory_max;
+		let large = sandbox.execution_limits(&large).unwrap().memory_max;
+		assert_eq!(large - small, 3072 * 64 * 1024);
+	}
+
+	#[test]
+	fn limits_include_the_configured_overhead() {
+		let mut config = SandboxConfig::new(PathBuf::from("/sys/fs/cgroup/pvf"));
+		config.worker_memory_overhead = 1024;
+		let sandbox = WorkerSandbox::new(config, PathBuf::from("/var/lib/artifacts"));
+
+		assert_eq!(
+			sandbox.preparation_limits(&ExecutorParams::default()).memory_max,
+			DEFAULT_PREPARATION_MEMORY_MAX + 1024,
+		);
+	}
+
+	#[test]
+	fn sandbox_requires_absolute_artifacts_dir() {
+		assert_matches!(
+			sandbox(Path::new("artifacts")).create_worker_dir(),
+			Err(SpawnErr::Sandbox(SandboxSetupStage::Namespaces))
+		);
+
+		let artifacts_dir = tempfile::tempdir().unwrap();
+		let worker_dir = sandbox(artifacts_dir.path()).create_worker_dir().unwrap();
+		assert_eq!(worker_dir.path().parent(), Some(artifacts_dir.path()));
+		assert_eq!(
+			worker_dir.worker_args(),
+			["--sandbox-root", worker_dir.path().to_str().unwrap()]
+		);
+	}
+
+	#[tokio::test]
+	async fn worker_dir_only_holds_the_staged_artifact() {
+		let artifacts_dir = tempfile::tempdir().unwrap();
+		let first = artifacts_dir.path().join("first");
+		let second = artifacts_dir.path().join("second");
+		fs::write(&first, b"first").unwrap();
+		fs::write(&second, b"second").unwrap();
+
+		let worker_dir = sandbox(artifacts_dir.path()).create_worker_dir().unwrap();
+		let staged = worker_dir.stage_artifact(&first).await.unwrap();
+		assert_eq!(staged.parent(), Some(worker_dir.path()));
+		assert_eq!(fs::read(&staged).unwrap(), b"first");
+
+		let staged = worker_dir.stage_artifact(&second).await.unwrap();
+		assert_eq!(fs::read(&staged).unwrap(), b"second");
+		assert_eq!(fs::read_dir(worker_dir.path()).unwrap().count(), 1);
+
+		let path = worker_dir.path().to_owned();
+		drop(worker_dir);
+		assert!(!path.exists());
+		assert_eq!(fs::read(&first).unwrap(), b"first");
+	}
+
+	#[test]
+	fn init_removes_stale_worker_dirs() {
+		let artifacts_dir = tempfile::tempdir().unwrap();
+		let sandbox = sandbox(artifacts_dir.path());
+		let stale = sandbox.create_worker_dir().unwrap().dir.into_path();
+		let artifact = artifacts_dir.path().join("artifact");
+		fs::write(&artifact, b"artifact").unwrap();
+
+		// Enabling the controllers fails without a cgroup root, but the directories are removed.
+		let _ = sandbox.init();
+		assert!(!stale.exists());
+		assert!(artifact.exists());
+	}
+}
@@ -49,6 +49,22 @@ pub fn validate_candidate(
 	Ok(result)
 }
 
+/// Connects to the host like a worker would, and then makes a syscall that is forbidden by the
+/// seccomp allowlist. The process is expected to be killed.
+pub fn connect_and_make_forbidden_syscall(socket_path: &str) {
+	let _stream = std::os::unix::net::UnixStream::connect(socket_path)
+		.expect("the host should be listening on the socket");
+
+	#[cfg(target_os = "linux")]
+	std::thread::spawn(|| {
+		polkadot_node_core_pvf_common::worker::security::seccomp::try_restrict_thread()
+			.expect("seccomp should be supported");
+		let _ = std::net::TcpListener::bind("127.0.0.1:0");
+	})
+	.join()
+	.expect("the thread should not panic");
+}
+
 /// Use this macro to declare a `fn main() {}` that will check the arguments and dispatch them to
 /// the appropriate worker, making the executable that can be used for spawning workers.
 #[macro_export]
@@ -64,11 +80,15 @@ macro_rules! decl_puppet_worker_main {
 
 			let mut version = None;
 			let mut socket_path: &str = "";
+			let mut enable_seccomp = false;
+			let mut sandbox_root = None;
 
 			for i in 2..args.len() {
 				match args[i].as_ref() {
 					"--socket-path" => socket_path = args[i + 1].as_str(),
 					"--node-version" => version = Some(args[i + 1].as_str()),
+					"--enable-seccomp" => enable_seccomp = true,
+					"--sandbox-root" => sandbox_root = Some(args[i + 1].as_str()),
 					_ => (),
 				}
 			}
@@ -81,11 +101,24 @@ macro_rules! decl_puppet_worker_main {
 				"sleep" => {
 					std::thread::sleep(std::time::Duration::from_secs(5));
 				},
+				"forbidden-syscall" => {
+					$crate::testing::connect_and_make_forbidden_syscall(&socket_path);
+				},
 				"prepare-worker" => {
-					$crate::prepare_worker_entrypoint(&socket_path, version);
+					$crate::prepare_worker_entrypoint(
+						&socket_path,
+						version,
+						enable_seccomp,
+						sandbox_root,
+					);
 				},
 				"execute-worker" => {
-					$crate::execute_worker_entrypoint(&socket_path, version);
+					$crate::execute_worker_entrypoint(
+						&socket_path,
+						version,
+						enable_seccomp,
+						sandbox_root,
+					);
 				},
 				other => panic!("unknown subcommand: {}", other),
 			}
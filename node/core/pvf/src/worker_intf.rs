@@ -16,7 +16,10 @@
 
 //! Common logic for implementation of worker processes.
 
-use crate::LOG_TARGET;
+use crate::{
+	sandbox::{SandboxSetupStage, WorkerCgroup, WorkerDir},
+	LOG_TARGET,
+};
 use futures::FutureExt as _;
 use futures_timer::Delay;
 use pin_project::pin_project;
@@ -38,15 +41,20 @@ use tokio::{
 /// wall clock time). This is lenient because CPU time may go slower than wall clock time.
 pub const JOB_TIMEOUT_WALL_CLOCK_FACTOR: u32 = 4;
 
+/// How long to wait for a worker process that we lost the connection to to terminate, in order to
+/// find out why it died.
+pub const WORKER_EXIT_TIMEOUT: Duration = Duration::from_millis(500);
+
 /// This is publicly exposed only for integration tests.
 #[doc(hidden)]
 pub async fn spawn_with_program_path(
 	debug_id: &'static str,
 	program_path: impl Into<PathBuf>,
-	extra_args: &'static [&'static str],
+	extra_args: &[&str],
 	spawn_timeout: Duration,
 ) -> Result<(IdleWorker, WorkerHandle), SpawnErr> {
 	let program_path = program_path.into();
+	let extra_args: Vec<String> = extra_args.iter().map(|arg| arg.to_string()).collect();
 	with_transient_socket_path(debug_id, |socket_path| {
 		let socket_path = socket_path.to_owned();
 		async move {
@@ -63,7 +71,7 @@ pub async fn spawn_with_program_path(
 			})?;
 
 			let handle =
-				WorkerHandle::spawn(&program_path, extra_args, socket_path).map_err(|err| {
+				WorkerHandle::spawn(&program_path, &extra_args, socket_path).map_err(|err| {
 					gum::warn!(
 						target: LOG_TARGET,
 						%debug_id,
@@ -192,6 +200,8 @@ pub enum SpawnErr {
 	AcceptTimeout,
 	/// Failed to send handshake after successful spawning was signaled
 	Handshake,
+	/// The sandbox was enabled but could not be set up for the worker.
+	Sandbox(SandboxSetupStage),
 }
 
 /// This is a representation of a potentially running worker. Drop it and the process will be killed.
@@ -209,12 +219,27 @@ pub struct WorkerHandle {
 	stdout: process::ChildStdout,
 	program: PathBuf,
 	drop_box: Box<[u8]>,
+	// Declared after `child` so that the process is killed before the group and the directory are
+	// removed.
+	cgroup: Option<WorkerCgroup>,
+	dir: Option<WorkerDir>,
+}
+
+/// The cause of the death of a worker, as far as the host can tell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkerDeath {
+	/// The worker was killed by seccomp for making a forbidden syscall.
+	ForbiddenSyscall,
+	/// The worker was killed for exceeding the memory limit of its sandbox.
+	OutOfMemory,
+	/// The cause is not known.
+	Unknown,
 }
 
 impl WorkerHandle {
 	fn spawn(
 		program: impl AsRef<Path>,
-		extra_args: &[&str],
+		extra_args: &[String],
 		socket_path: impl AsRef<Path>,
 	) -> io::Result<Self> {
 		let mut child = process::Command::new(program.as_ref())
@@ -248,6 +273,8 @@ impl WorkerHandle {
 			// OTOH, we also don't want to be super smart here and we could just afford to allocate a buffer
 			// for that here.
 			drop_box: vec![0; 8192].into_boxed_slice(),
+			cgroup: None,
+			dir: None,
 		})
 	}
 
@@ -255,6 +282,65 @@ impl WorkerHandle {
 	pub fn id(&self) -> u32 {
 		self.child_id
 	}
+
+	/// Returns the cgroup of this worker, if it runs in the sandbox.
+	pub(crate) fn cgroup(&self) -> Option<&WorkerCgroup> {
+		self.cgroup.as_ref()
+	}
+
+	/// Records the cgroup this worker was moved into. The group is removed along with the handle.
+	pub(crate) fn set_cgroup(&mut self, cgroup: WorkerCgroup) {
+		self.cgroup = Some(cgroup);
+	}
+
+	/// Returns the directory of this worker, if it runs in the sandbox.
+	pub(crate) fn dir(&self) -> Option<&WorkerDir> {
+		self.dir.as_ref()
+	}
+
+	/// Records the directory this worker sees as its root. The directory is removed along with the
+	/// handle.
+	pub(crate) fn set_dir(&mut self, dir: WorkerDir) {
+		self.dir = Some(dir);
+	}
+
+	/// Waits for the worker process to terminate, for at most the given duration, and returns
+	/// what it was killed for.
+	pub async fn cause_of_death(&mut self, timeout: Duration) -> WorkerDeath {
+		if self.was_killed_by_seccomp(timeout).await {
+			return WorkerDeath::ForbiddenSyscall
+		}
+		let out_of_memory = match &self.cgroup {
+			Some(cgroup) => cgroup.was_out_of_memory().await,
+			None => false,
+		};
+		if out_of_memory {
+			WorkerDeath::OutOfMemory
+		} else {
+			WorkerDeath::Unknown
+		}
+	}
+
+	/// Waits for the worker process to terminate, for at most the given duration, and returns
+	/// whether it was killed by seccomp for making a forbidden syscall.
+	pub async fn was_killed_by_seccomp(&mut self, timeout: Duration) -> bool {
+		#[cfg(target_os = "linux")]
+		{
+			use polkadot_node_core_pvf_common::worker::security::seccomp::FORBIDDEN_SYSCALL_SIGNAL;
+			use std::os::unix::process::ExitStatusExt;
+
+			match tokio::time::timeout(timeout, self.child.wait()).await {
+				Ok(Ok(status)) => status.signal() == Some(FORBIDDEN_SYSCALL_SIGNAL),
+				_ => false,
+			}
+		}
+
+		#[cfg(not(target_os = "linux"))]
+		{
+			let _ = timeout;
+			false
+		}
+	}
 }
 
 impl futures::Future for WorkerHandle {
@@ -51,6 +51,42 @@ async fn execute_good_block_on_parent() {
 	assert_eq!(new_head.post_state, hash_state(512));
 }
 
+// The workers of a host with seccomp enabled run restricted, which must not affect valid
+// candidates.
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn execute_good_block_with_seccomp() {
+	use polkadot_node_core_pvf_common::worker::security::seccomp;
+
+	if !seccomp::check_is_fully_enabled() {
+		return
+	}
+
+	let parent_head = HeadData { number: 0, parent_hash: [0; 32], post_state: hash_state(0) };
+
+	let block_data = BlockData { state: 0, add: 512 };
+
+	let host = TestHost::new_with_config(|config| config.enable_seccomp = true);
+
+	let ret = host
+		.validate_candidate(
+			adder::wasm_binary_unwrap(),
+			ValidationParams {
+				parent_head: GenericHeadData(parent_head.encode()),
+				block_data: GenericBlockData(block_data.encode()),
+				relay_parent_number: 1,
+				relay_parent_storage_root: Default::default(),
+			},
+			Default::default(),
+		)
+		.await
+		.unwrap();
+
+	let new_head = HeadData::decode(&mut &ret.head_data.0[..]).unwrap();
+
+	assert_eq!(new_head.post_state, hash_state(512));
+}
+
 #[tokio::test]
 async fn execute_good_chain_on_parent() {
 	let mut number = 0;
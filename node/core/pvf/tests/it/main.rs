@@ -279,13 +279,17 @@ async fn deleting_prepared_artifact_does_not_dispute() {
 
 	// Delete the prepared artifact.
 	{
-		// Get the artifact path (asserting it exists).
-		let mut cache_dir: Vec<_> = std::fs::read_dir(cache_dir).unwrap().collect();
+		// Get the artifact path (asserting it exists). The artifact metadata is left in place.
+		let mut cache_dir: Vec<_> = std::fs::read_dir(cache_dir)
+			.unwrap()
+			.map(|entry| entry.unwrap().path())
+			.filter(|path| path.extension().map_or(true, |ext| ext != "meta"))
+			.collect();
 		assert_eq!(cache_dir.len(), 1);
-		let artifact_path = cache_dir.pop().unwrap().unwrap();
+		let artifact_path = cache_dir.pop().unwrap();
 
 		// Delete the artifact.
-		std::fs::remove_file(artifact_path.path()).unwrap();
+		std::fs::remove_file(artifact_path).unwrap();
 	}
 
 	// Try to validate again, artifact should get recreated.
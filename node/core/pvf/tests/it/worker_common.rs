@@ -46,3 +46,25 @@ async fn should_connect() {
 	.await
 	.unwrap();
 }
+
+// Test that a worker making a forbidden syscall is killed, and that the host can tell.
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn forbidden_syscall_kills_worker() {
+	use polkadot_node_core_pvf_common::worker::security::seccomp;
+
+	if !seccomp::check_is_fully_enabled() {
+		return
+	}
+
+	let (_idle, mut handle) = spawn_with_program_path(
+		"integration-test",
+		PUPPET_EXE,
+		&["forbidden-syscall"],
+		Duration::from_secs(2),
+	)
+	.await
+	.unwrap();
+
+	assert!(handle.was_killed_by_seccomp(Duration::from_secs(2)).await);
+}
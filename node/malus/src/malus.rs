@@ -100,6 +100,8 @@ impl MalusCli {
 					polkadot_node_core_pvf_prepare_worker::worker_entrypoint(
 						&cmd.socket_path,
 						None,
+						cmd.enable_seccomp,
+						cmd.sandbox_root.as_deref(),
 					);
 				}
 			},
@@ -114,6 +116,8 @@ impl MalusCli {
 					polkadot_node_core_pvf_execute_worker::worker_entrypoint(
 						&cmd.socket_path,
 						None,
+						cmd.enable_seccomp,
+						cmd.sandbox_root.as_deref(),
 					);
 				}
 			},
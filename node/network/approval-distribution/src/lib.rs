@@ -26,10 +26,11 @@ use polkadot_node_network_protocol::{
 	self as net_protocol,
 	grid_topology::{RandomRouting, RequiredRouting, SessionGridTopologies, SessionGridTopology},
 	peer_set::MAX_NOTIFICATION_SIZE,
-	v1 as protocol_v1, PeerId, UnifiedReputationChange as Rep, Versioned, View,
+	v1 as protocol_v1, v2 as protocol_v2, PeerId, UnifiedReputationChange as Rep, Versioned, View,
 };
 use polkadot_node_primitives::approval::{
-	AssignmentCert, BlockApprovalMeta, IndirectAssignmentCert, IndirectSignedApprovalVote,
+	AssignmentCert, AssignmentCertKind, BlockApprovalMeta, CandidateBitfield,
+	IndirectAssignmentCert, IndirectSignedApprovalVote, IndirectSignedApprovalVoteV2,
 };
 use polkadot_node_subsystem::{
 	messages::{
@@ -205,6 +206,18 @@ enum MessageKind {
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct MessageSubject(Hash, CandidateIndex, ValidatorIndex);
 
+// The subjects of a message by `validator` covering all of the `candidate_indices` of a block.
+fn message_subjects(
+	block_hash: Hash,
+	candidate_indices: &CandidateBitfield,
+	validator: ValidatorIndex,
+) -> Vec<MessageSubject> {
+	candidate_indices
+		.iter_ones()
+		.map(|candidate_index| MessageSubject(block_hash, candidate_index, validator))
+		.collect()
+}
+
 #[derive(Debug, Clone, Default)]
 struct Knowledge {
 	// When there is no entry, this means the message is unknown
@@ -214,6 +227,17 @@ struct Knowledge {
 }
 
 impl Knowledge {
+	fn contains_all(&self, messages: &[MessageSubject], kind: MessageKind) -> bool {
+		messages.iter().all(|message| self.contains(message, kind))
+	}
+
+	// Returns `true` if any of the messages wasn't known before.
+	fn insert_all(&mut self, messages: &[MessageSubject], kind: MessageKind) -> bool {
+		messages
+			.iter()
+			.fold(false, |inserted, message| self.insert(message.clone(), kind) || inserted)
+	}
+
 	fn contains(&self, message: &MessageSubject, kind: MessageKind) -> bool {
 		match (kind, self.known_messages.get(message)) {
 			(_, None) => false,
@@ -255,6 +279,10 @@ impl PeerKnowledge {
 	fn contains(&self, message: &MessageSubject, kind: MessageKind) -> bool {
 		self.sent.contains(message, kind) || self.received.contains(message, kind)
 	}
+
+	fn contains_all(&self, messages: &[MessageSubject], kind: MessageKind) -> bool {
+		messages.iter().all(|message| self.contains(message, kind))
+	}
 }
 
 /// Information about blocks in our current view as well as whether peers know of them.
@@ -274,24 +302,34 @@ struct BlockEntry {
 	session: SessionIndex,
 }
 
+// Assignments and approvals may each cover several candidates of the block, which are
+// stored alongside them. The same state is then kept in the entry of every such candidate.
 #[derive(Debug)]
 enum ApprovalState {
-	Assigned(AssignmentCert),
-	Approved(AssignmentCert, ValidatorSignature),
+	Assigned(AssignmentCert, CandidateBitfield),
+	Approved(AssignmentCert, CandidateBitfield, CandidateBitfield, ValidatorSignature),
 }
 
 impl ApprovalState {
 	fn assignment_cert(&self) -> &AssignmentCert {
 		match *self {
-			ApprovalState::Assigned(ref cert) => cert,
-			ApprovalState::Approved(ref cert, _) => cert,
+			ApprovalState::Assigned(ref cert, _) => cert,
+			ApprovalState::Approved(ref cert, _, _, _) => cert,
+		}
+	}
+
+	fn claimed_candidates(&self) -> &CandidateBitfield {
+		match *self {
+			ApprovalState::Assigned(_, ref claimed) => claimed,
+			ApprovalState::Approved(_, ref claimed, _, _) => claimed,
 		}
 	}
 
-	fn approval_signature(&self) -> Option<ValidatorSignature> {
+	fn approval_signature(&self) -> Option<(CandidateBitfield, ValidatorSignature)> {
 		match *self {
-			ApprovalState::Assigned(_) => None,
-			ApprovalState::Approved(_, ref sig) => Some(sig.clone()),
+			ApprovalState::Assigned(_, _) => None,
+			ApprovalState::Approved(_, _, ref approved, ref sig) =>
+				Some((approved.clone(), sig.clone())),
 		}
 	}
 }
@@ -331,8 +369,8 @@ impl MessageSource {
 }
 
 enum PendingMessage {
-	Assignment(IndirectAssignmentCert, CandidateIndex),
-	Approval(IndirectSignedApprovalVote),
+	Assignment(IndirectAssignmentCert, CandidateBitfield),
+	Approval(IndirectSignedApprovalVoteV2),
 }
 
 #[overseer::contextbounds(ApprovalDistribution, prefix = self::overseer)]
@@ -390,6 +428,9 @@ impl State {
 				});
 			},
 			NetworkBridgeEvent::PeerMessage(peer_id, Versioned::V1(msg)) => {
+				self.process_incoming_peer_message(ctx, metrics, peer_id, msg.into(), rng).await;
+			},
+			NetworkBridgeEvent::PeerMessage(peer_id, Versioned::V2(msg)) => {
 				self.process_incoming_peer_message(ctx, metrics, peer_id, msg, rng).await;
 			},
 		}
@@ -495,13 +536,13 @@ impl State {
 
 				for (peer_id, message) in to_import {
 					match message {
-						PendingMessage::Assignment(assignment, claimed_index) => {
+						PendingMessage::Assignment(assignment, claimed_indices) => {
 							self.import_and_circulate_assignment(
 								ctx,
 								metrics,
 								MessageSource::Peer(peer_id),
 								assignment,
-								claimed_index,
+								claimed_indices,
 								rng,
 							)
 							.await;
@@ -559,36 +600,34 @@ impl State {
 		ctx: &mut Context,
 		metrics: &Metrics,
 		peer_id: PeerId,
-		msg: protocol_v1::ApprovalDistributionMessage,
+		msg: protocol_v2::ApprovalDistributionMessage,
 		rng: &mut R,
 	) where
 		R: CryptoRng + Rng,
 	{
 		match msg {
-			protocol_v1::ApprovalDistributionMessage::Assignments(assignments) => {
+			protocol_v2::ApprovalDistributionMessage::Assignments(assignments) => {
 				gum::trace!(
 					target: LOG_TARGET,
 					peer_id = %peer_id,
 					num = assignments.len(),
 					"Processing assignments from a peer",
 				);
-				for (assignment, claimed_index) in assignments.into_iter() {
+				for (assignment, claimed_indices) in assignments.into_iter() {
 					if let Some(pending) = self.pending_known.get_mut(&assignment.block_hash) {
-						let message_subject = MessageSubject(
-							assignment.block_hash,
-							claimed_index,
-							assignment.validator,
-						);
-
 						gum::trace!(
 							target: LOG_TARGET,
 							%peer_id,
-							?message_subject,
+							block_hash = ?assignment.block_hash,
+							?claimed_indices,
+							validator_index = ?assignment.validator,
 							"Pending assignment",
 						);
 
-						pending
-							.push((peer_id, PendingMessage::Assignment(assignment, claimed_index)));
+						pending.push((
+							peer_id,
+							PendingMessage::Assignment(assignment, claimed_indices),
+						));
 
 						continue
 					}
@@ -598,13 +637,13 @@ impl State {
 						metrics,
 						MessageSource::Peer(peer_id),
 						assignment,
-						claimed_index,
+						claimed_indices,
 						rng,
 					)
 					.await;
 				}
 			},
-			protocol_v1::ApprovalDistributionMessage::Approvals(approvals) => {
+			protocol_v2::ApprovalDistributionMessage::Approvals(approvals) => {
 				gum::trace!(
 					target: LOG_TARGET,
 					peer_id = %peer_id,
@@ -613,16 +652,12 @@ impl State {
 				);
 				for approval_vote in approvals.into_iter() {
 					if let Some(pending) = self.pending_known.get_mut(&approval_vote.block_hash) {
-						let message_subject = MessageSubject(
-							approval_vote.block_hash,
-							approval_vote.candidate_index,
-							approval_vote.validator,
-						);
-
 						gum::trace!(
 							target: LOG_TARGET,
 							%peer_id,
-							?message_subject,
+							block_hash = ?approval_vote.block_hash,
+							candidate_indices = ?approval_vote.candidate_indices,
+							validator_index = ?approval_vote.validator,
 							"Pending approval",
 						);
 
@@ -727,7 +762,7 @@ impl State {
 		metrics: &Metrics,
 		source: MessageSource,
 		assignment: IndirectAssignmentCert,
-		claimed_candidate_index: CandidateIndex,
+		claimed_candidate_indices: CandidateBitfield,
 		rng: &mut R,
 	) where
 		R: CryptoRng + Rng,
@@ -776,21 +811,42 @@ impl State {
 		};
 
 		// compute metadata on the assignment.
-		let message_subject = MessageSubject(block_hash, claimed_candidate_index, validator_index);
+		let message_subjects =
+			message_subjects(block_hash, &claimed_candidate_indices, validator_index);
 		let message_kind = MessageKind::Assignment;
 
+		if message_subjects.is_empty() {
+			if let Some(peer_id) = source.peer_id() {
+				gum::debug!(
+					target: LOG_TARGET,
+					?peer_id,
+					hash = ?block_hash,
+					?validator_index,
+					"Assignment claims no candidates",
+				);
+				modify_reputation(
+					&mut self.reputation,
+					ctx.sender(),
+					peer_id,
+					COST_INVALID_MESSAGE,
+				)
+				.await;
+			}
+			return
+		}
+
 		if let Some(peer_id) = source.peer_id() {
 			// check if our knowledge of the peer already contains this assignment
 			match entry.known_by.entry(peer_id) {
 				hash_map::Entry::Occupied(mut peer_knowledge) => {
 					let peer_knowledge = peer_knowledge.get_mut();
-					if peer_knowledge.contains(&message_subject, message_kind) {
+					if peer_knowledge.contains_all(&message_subjects, message_kind) {
 						// wasn't included before
-						if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
+						if !peer_knowledge.received.insert_all(&message_subjects, message_kind) {
 							gum::debug!(
 								target: LOG_TARGET,
 								?peer_id,
-								?message_subject,
+								?message_subjects,
 								"Duplicate assignment",
 							);
 							modify_reputation(
@@ -808,7 +864,7 @@ impl State {
 					gum::debug!(
 						target: LOG_TARGET,
 						?peer_id,
-						?message_subject,
+						?message_subjects,
 						"Assignment from a peer is out of view",
 					);
 					modify_reputation(
@@ -822,7 +878,7 @@ impl State {
 			}
 
 			// if the assignment is known to be valid, reward the peer
-			if entry.knowledge.contains(&message_subject, message_kind) {
+			if entry.knowledge.contains_all(&message_subjects, message_kind) {
 				modify_reputation(
 					&mut self.reputation,
 					ctx.sender(),
@@ -831,8 +887,13 @@ impl State {
 				)
 				.await;
 				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-					gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known assignment");
-					peer_knowledge.received.insert(message_subject, message_kind);
+					gum::trace!(
+						target: LOG_TARGET,
+						?peer_id,
+						?message_subjects,
+						"Known assignment",
+					);
+					peer_knowledge.received.insert_all(&message_subjects, message_kind);
 				}
 				return
 			}
@@ -841,7 +902,7 @@ impl State {
 
 			ctx.send_message(ApprovalVotingMessage::CheckAndImportAssignment(
 				assignment.clone(),
-				claimed_candidate_index,
+				claimed_candidate_indices.clone(),
 				tx,
 			))
 			.await;
@@ -859,7 +920,7 @@ impl State {
 			gum::trace!(
 				target: LOG_TARGET,
 				?source,
-				?message_subject,
+				?message_subjects,
 				?result,
 				"Checked assignment",
 			);
@@ -872,9 +933,14 @@ impl State {
 						BENEFIT_VALID_MESSAGE_FIRST,
 					)
 					.await;
-					entry.knowledge.known_messages.insert(message_subject.clone(), message_kind);
+					for message_subject in &message_subjects {
+						entry
+							.knowledge
+							.known_messages
+							.insert(message_subject.clone(), message_kind);
+					}
 					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
+						peer_knowledge.received.insert_all(&message_subjects, message_kind);
 					}
 				},
 				AssignmentCheckResult::AcceptedDuplicate => {
@@ -882,7 +948,7 @@ impl State {
 					// There is more than one way each validator can be assigned to each core.
 					// cf. https://github.com/paritytech/polkadot/pull/2160#discussion_r557628699
 					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
+						peer_knowledge.received.insert_all(&message_subjects, message_kind);
 					}
 					gum::debug!(
 						target: LOG_TARGET,
@@ -927,18 +993,28 @@ impl State {
 				},
 			}
 		} else {
-			if !entry.knowledge.insert(message_subject.clone(), message_kind) {
-				// if we already imported an assignment, there is no need to distribute it again
-				gum::warn!(
-					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally an already known assignment",
-				);
+			if !entry.knowledge.insert_all(&message_subjects, message_kind) {
+				// if we already imported an assignment, there is no need to distribute it again.
+				// Assignments covering multiple candidates are expected to be distributed once
+				// for each of them.
+				if message_subjects.len() > 1 {
+					gum::debug!(
+						target: LOG_TARGET,
+						?message_subjects,
+						"Importing locally an already known assignment",
+					);
+				} else {
+					gum::warn!(
+						target: LOG_TARGET,
+						?message_subjects,
+						"Importing locally an already known assignment",
+					);
+				}
 				return
 			} else {
 				gum::debug!(
 					target: LOG_TARGET,
-					?message_subject,
+					?message_subjects,
 					"Importing locally a new assignment",
 				);
 			}
@@ -954,27 +1030,44 @@ impl State {
 			t.local_grid_neighbors().required_routing_by_index(validator_index, local)
 		});
 
-		let message_state = match entry.candidates.get_mut(claimed_candidate_index as usize) {
-			Some(candidate_entry) => {
-				// set the approval state for validator_index to Assigned
-				// unless the approval state is set already
-				candidate_entry.messages.entry(validator_index).or_insert_with(|| MessageState {
+		if let Some(claimed_candidate_index) = claimed_candidate_indices
+			.iter_ones()
+			.find(|index| entry.candidates.get(*index as usize).is_none())
+		{
+			gum::warn!(
+				target: LOG_TARGET,
+				hash = ?block_hash,
+				?claimed_candidate_index,
+				"Expected a candidate entry on import_and_circulate_assignment",
+			);
+
+			return
+		}
+
+		for claimed_candidate_index in claimed_candidate_indices.iter_ones() {
+			// set the approval state for validator_index to Assigned
+			// unless the approval state is set already
+			entry.candidates[claimed_candidate_index as usize]
+				.messages
+				.entry(validator_index)
+				.or_insert_with(|| MessageState {
 					required_routing,
 					local,
 					random_routing: Default::default(),
-					approval_state: ApprovalState::Assigned(assignment.cert.clone()),
-				})
-			},
-			None => {
-				gum::warn!(
-					target: LOG_TARGET,
-					hash = ?block_hash,
-					?claimed_candidate_index,
-					"Expected a candidate entry on import_and_circulate_assignment",
-				);
+					approval_state: ApprovalState::Assigned(
+						assignment.cert.clone(),
+						claimed_candidate_indices.clone(),
+					),
+				});
+		}
 
-				return
-			},
+		// The random routing of an assignment is tracked with its first claimed candidate.
+		let message_state = match claimed_candidate_indices
+			.first_one()
+			.and_then(|index| entry.candidates[index as usize].messages.get_mut(&validator_index))
+		{
+			Some(message_state) => message_state,
+			None => return,
 		};
 
 		// Dispatch the message to all peers in the routing set which
@@ -983,7 +1076,7 @@ impl State {
 		// If the topology isn't known yet (race with networking subsystems)
 		// then messages will be sent when we get it.
 
-		let assignments = vec![(assignment, claimed_candidate_index)];
+		let assignments = vec![(assignment, claimed_candidate_indices.clone())];
 		let n_peers_total = self.peer_views.len();
 		let source_peer = source.peer_id();
 
@@ -1017,7 +1110,7 @@ impl State {
 		for peer in peers.iter() {
 			// we already filtered peers above, so this should always be Some
 			if let Some(peer_knowledge) = entry.known_by.get_mut(peer) {
-				peer_knowledge.sent.insert(message_subject.clone(), message_kind);
+				peer_knowledge.sent.insert_all(&message_subjects, message_kind);
 			}
 		}
 
@@ -1025,7 +1118,7 @@ impl State {
 			gum::trace!(
 				target: LOG_TARGET,
 				?block_hash,
-				?claimed_candidate_index,
+				?claimed_candidate_indices,
 				local = source.peer_id().is_none(),
 				num_peers = peers.len(),
 				"Sending an assignment to peers",
@@ -1033,9 +1126,7 @@ impl State {
 
 			ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(
 				peers,
-				Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
-					protocol_v1::ApprovalDistributionMessage::Assignments(assignments),
-				)),
+				assignments_message(assignments),
 			))
 			.await;
 		}
@@ -1046,7 +1137,7 @@ impl State {
 		ctx: &mut Context,
 		metrics: &Metrics,
 		source: MessageSource,
-		vote: IndirectSignedApprovalVote,
+		vote: IndirectSignedApprovalVoteV2,
 	) {
 		let _span = self
 			.spans
@@ -1065,10 +1156,15 @@ impl State {
 
 		let block_hash = vote.block_hash;
 		let validator_index = vote.validator;
-		let candidate_index = vote.candidate_index;
+		let candidate_indices = vote.candidate_indices.clone();
 
 		let entry = match self.blocks.get_mut(&block_hash) {
-			Some(entry) if entry.candidates.get(candidate_index as usize).is_some() => entry,
+			Some(entry)
+				if candidate_indices.count_ones() > 0 &&
+					candidate_indices
+						.iter_ones()
+						.all(|index| entry.candidates.get(index as usize).is_some()) =>
+				entry,
 			_ => {
 				if let Some(peer_id) = source.peer_id() {
 					if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
@@ -1086,15 +1182,15 @@ impl State {
 		};
 
 		// compute metadata on the assignment.
-		let message_subject = MessageSubject(block_hash, candidate_index, validator_index);
+		let message_subjects = message_subjects(block_hash, &candidate_indices, validator_index);
 		let message_kind = MessageKind::Approval;
 
 		if let Some(peer_id) = source.peer_id() {
-			if !entry.knowledge.contains(&message_subject, MessageKind::Assignment) {
+			if !entry.knowledge.contains_all(&message_subjects, MessageKind::Assignment) {
 				gum::debug!(
 					target: LOG_TARGET,
 					?peer_id,
-					?message_subject,
+					?message_subjects,
 					"Unknown approval assignment",
 				);
 				modify_reputation(
@@ -1111,12 +1207,12 @@ impl State {
 			match entry.known_by.entry(peer_id) {
 				hash_map::Entry::Occupied(mut knowledge) => {
 					let peer_knowledge = knowledge.get_mut();
-					if peer_knowledge.contains(&message_subject, message_kind) {
-						if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
+					if peer_knowledge.contains_all(&message_subjects, message_kind) {
+						if !peer_knowledge.received.insert_all(&message_subjects, message_kind) {
 							gum::debug!(
 								target: LOG_TARGET,
 								?peer_id,
-								?message_subject,
+								?message_subjects,
 								"Duplicate approval",
 							);
 
@@ -1135,7 +1231,7 @@ impl State {
 					gum::debug!(
 						target: LOG_TARGET,
 						?peer_id,
-						?message_subject,
+						?message_subjects,
 						"Approval from a peer is out of view",
 					);
 					modify_reputation(
@@ -1149,8 +1245,8 @@ impl State {
 			}
 
 			// if the approval is known to be valid, reward the peer
-			if entry.knowledge.contains(&message_subject, message_kind) {
-				gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known approval");
+			if entry.knowledge.contains_all(&message_subjects, message_kind) {
+				gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subjects, "Known approval");
 				modify_reputation(
 					&mut self.reputation,
 					ctx.sender(),
@@ -1159,7 +1255,7 @@ impl State {
 				)
 				.await;
 				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-					peer_knowledge.received.insert(message_subject.clone(), message_kind);
+					peer_knowledge.received.insert_all(&message_subjects, message_kind);
 				}
 				return
 			}
@@ -1182,7 +1278,7 @@ impl State {
 			gum::trace!(
 				target: LOG_TARGET,
 				?peer_id,
-				?message_subject,
+				?message_subjects,
 				?result,
 				"Checked approval",
 			);
@@ -1196,9 +1292,9 @@ impl State {
 					)
 					.await;
 
-					entry.knowledge.insert(message_subject.clone(), message_kind);
+					entry.knowledge.insert_all(&message_subjects, message_kind);
 					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
+						peer_knowledge.received.insert_all(&message_subjects, message_kind);
 					}
 				},
 				ApprovalCheckResult::Bad(error) => {
@@ -1219,18 +1315,18 @@ impl State {
 				},
 			}
 		} else {
-			if !entry.knowledge.insert(message_subject.clone(), message_kind) {
+			if !entry.knowledge.insert_all(&message_subjects, message_kind) {
 				// if we already imported an approval, there is no need to distribute it again
 				gum::warn!(
 					target: LOG_TARGET,
-					?message_subject,
+					?message_subjects,
 					"Importing locally an already known approval",
 				);
 				return
 			} else {
 				gum::debug!(
 					target: LOG_TARGET,
-					?message_subject,
+					?message_subjects,
 					"Importing locally a new approval",
 				);
 			}
@@ -1239,62 +1335,46 @@ impl State {
 		// Invariant: to our knowledge, none of the peers except for the `source` know about the approval.
 		metrics.on_approval_imported();
 
-		let required_routing = match entry.candidates.get_mut(candidate_index as usize) {
-			Some(candidate_entry) => {
-				// set the approval state for validator_index to Approved
-				// it should be in assigned state already
-				match candidate_entry.messages.remove(&validator_index) {
-					Some(MessageState {
-						approval_state: ApprovalState::Assigned(cert),
-						required_routing,
-						local,
-						random_routing,
-					}) => {
-						candidate_entry.messages.insert(
-							validator_index,
-							MessageState {
-								approval_state: ApprovalState::Approved(
-									cert,
-									vote.signature.clone(),
-								),
-								required_routing,
-								local,
-								random_routing,
-							},
-						);
-
-						required_routing
-					},
-					Some(_) => {
-						unreachable!(
-							"we only insert it after the metadata, checked the metadata above; qed"
-						);
-					},
-					None => {
-						// this would indicate a bug in approval-voting
-						gum::warn!(
-							target: LOG_TARGET,
-							hash = ?block_hash,
-							?candidate_index,
-							?validator_index,
-							"Importing an approval we don't have an assignment for",
+		let mut required_routing = None;
+		for candidate_index in candidate_indices.iter_ones() {
+			// set the approval state for validator_index to Approved
+			// it should be in assigned state already
+			match entry
+				.candidates
+				.get_mut(candidate_index as usize)
+				.and_then(|candidate_entry| candidate_entry.messages.get_mut(&validator_index))
+			{
+				Some(message_state) => {
+					// An approval covering several candidates may overlap with one which was
+					// imported before, which is kept.
+					if let ApprovalState::Assigned(cert, claimed) = &message_state.approval_state {
+						message_state.approval_state = ApprovalState::Approved(
+							cert.clone(),
+							claimed.clone(),
+							candidate_indices.clone(),
+							vote.signature.clone(),
 						);
+					}
 
-						return
-					},
-				}
-			},
-			None => {
-				gum::warn!(
-					target: LOG_TARGET,
-					hash = ?block_hash,
-					?candidate_index,
-					?validator_index,
-					"Expected a candidate entry on import_and_circulate_approval",
-				);
+					required_routing.get_or_insert(message_state.required_routing);
+				},
+				None => {
+					// this would indicate a bug in approval-voting
+					gum::warn!(
+						target: LOG_TARGET,
+						hash = ?block_hash,
+						?candidate_index,
+						?validator_index,
+						"Importing an approval we don't have an assignment for",
+					);
 
-				return
-			},
+					return
+				},
+			}
+		}
+		let required_routing = match required_routing {
+			Some(required_routing) => required_routing,
+			None => return,
 		};
 
 		// Dispatch a ApprovalDistributionV1Message::Approval(vote)
@@ -1303,7 +1383,7 @@ impl State {
 		let topology = self.topologies.get_topology(entry.session);
 		let source_peer = source.peer_id();
 
-		let message_subject = &message_subject;
+		let message_subjects = &message_subjects;
 		let peer_filter = move |peer, knowledge: &PeerKnowledge| {
 			if Some(peer) == source_peer.as_ref() {
 				return false
@@ -1320,7 +1400,7 @@ impl State {
 			//   3. Any randomly selected peers have been sent the assignment already.
 			let in_topology = topology
 				.map_or(false, |t| t.local_grid_neighbors().route_to_peer(required_routing, peer));
-			in_topology || knowledge.sent.contains(message_subject, MessageKind::Assignment)
+			in_topology || knowledge.sent.contains_all(message_subjects, MessageKind::Assignment)
 		};
 
 		let peers = entry
@@ -1335,7 +1415,7 @@ impl State {
 		for peer in peers.iter() {
 			// we already filtered peers above, so this should always be Some
 			if let Some(entry) = entry.known_by.get_mut(peer) {
-				entry.sent.insert(message_subject.clone(), message_kind);
+				entry.sent.insert_all(message_subjects, message_kind);
 			}
 		}
 
@@ -1344,7 +1424,7 @@ impl State {
 			gum::trace!(
 				target: LOG_TARGET,
 				?block_hash,
-				?candidate_index,
+				?candidate_indices,
 				local = source.peer_id().is_none(),
 				num_peers = peers.len(),
 				"Sending an approval to peers",
@@ -1352,19 +1432,18 @@ impl State {
 
 			ctx.send_message(NetworkBridgeTxMessage::SendValidationMessage(
 				peers,
-				Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
-					protocol_v1::ApprovalDistributionMessage::Approvals(approvals),
-				)),
+				approvals_message(approvals),
 			))
 			.await;
 		}
 	}
 
-	/// Retrieve approval signatures from state for the given relay block/indices:
+	/// Retrieve approval signatures from state for the given relay block/indices, together with
+	/// all candidates of the relay block covered by each signature:
 	fn get_approval_signatures(
 		&mut self,
 		indices: HashSet<(Hash, CandidateIndex)>,
-	) -> HashMap<ValidatorIndex, ValidatorSignature> {
+	) -> HashMap<ValidatorIndex, (Hash, Vec<CandidateIndex>, ValidatorSignature)> {
 		let mut all_sigs = HashMap::new();
 		for (hash, index) in indices {
 			let _span = self
@@ -1402,8 +1481,11 @@ impl State {
 			let sigs =
 				candidate_entry.messages.iter().filter_map(|(validator_index, message_state)| {
 					match &message_state.approval_state {
-						ApprovalState::Approved(_, sig) => Some((*validator_index, sig.clone())),
-						ApprovalState::Assigned(_) => None,
+						ApprovalState::Approved(_, _, approved, sig) => Some((
+							*validator_index,
+							(hash, approved.iter_ones().collect(), sig.clone()),
+						)),
+						ApprovalState::Assigned(_, _) => None,
 					}
 				});
 			all_sigs.extend(sigs);
@@ -1479,38 +1561,43 @@ impl State {
 
 					let message_subject = MessageSubject(block, candidate_index, *validator);
 
-					let assignment_message = (
-						IndirectAssignmentCert {
-							block_hash: block,
-							validator: *validator,
-							cert: message_state.approval_state.assignment_cert().clone(),
-						},
-						candidate_index,
-					);
-
-					let approval_message =
-						message_state.approval_state.approval_signature().map(|signature| {
-							IndirectSignedApprovalVote {
+					if !peer_knowledge.contains(&message_subject, MessageKind::Assignment) {
+						let claimed_candidates =
+							message_state.approval_state.claimed_candidates().clone();
+						peer_knowledge.sent.insert_all(
+							&message_subjects(block, &claimed_candidates, *validator),
+							MessageKind::Assignment,
+						);
+						assignments_to_send.push((
+							IndirectAssignmentCert {
 								block_hash: block,
 								validator: *validator,
-								candidate_index,
-								signature,
-							}
-						});
-
-					if !peer_knowledge.contains(&message_subject, MessageKind::Assignment) {
-						peer_knowledge
-							.sent
-							.insert(message_subject.clone(), MessageKind::Assignment);
-						assignments_to_send.push(assignment_message);
+								cert: message_state.approval_state.assignment_cert().clone(),
+							},
+							claimed_candidates,
+						));
 					}
 
-					if let Some(approval_message) = approval_message {
-						if !peer_knowledge.contains(&message_subject, MessageKind::Approval) {
+					if let Some((candidate_indices, signature)) =
+						message_state.approval_state.approval_signature()
+					{
+						// Approvals are only sent once the assignments for all of their candidates
+						// are known by the peer.
+						let approval_subjects =
+							message_subjects(block, &candidate_indices, *validator);
+						if !peer_knowledge.contains(&message_subject, MessageKind::Approval) &&
+							peer_knowledge
+								.contains_all(&approval_subjects, MessageKind::Assignment)
+						{
 							peer_knowledge
 								.sent
-								.insert(message_subject.clone(), MessageKind::Approval);
-							approvals_to_send.push(approval_message);
+								.insert_all(&approval_subjects, MessageKind::Approval);
+							approvals_to_send.push(IndirectSignedApprovalVoteV2 {
+								block_hash: block,
+								validator: *validator,
+								candidate_indices,
+								signature,
+							});
 						}
 					}
 				}
@@ -1699,23 +1786,29 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 			// Propagate the message to all peers in the required routing set.
 			let message_subject = MessageSubject(*block_hash, candidate_index, *validator);
 
+			let claimed_candidates = message_state.approval_state.claimed_candidates();
+			let assignment_subjects = message_subjects(*block_hash, claimed_candidates, *validator);
 			let assignment_message = (
 				IndirectAssignmentCert {
 					block_hash: *block_hash,
 					validator: *validator,
 					cert: message_state.approval_state.assignment_cert().clone(),
 				},
-				candidate_index,
+				claimed_candidates.clone(),
 			);
-			let approval_message =
-				message_state.approval_state.approval_signature().map(|signature| {
-					IndirectSignedApprovalVote {
+			let approval_message = message_state.approval_state.approval_signature().map(
+				|(candidate_indices, signature)| {
+					let approval_subjects =
+						message_subjects(*block_hash, &candidate_indices, *validator);
+					let approval = IndirectSignedApprovalVoteV2 {
 						block_hash: *block_hash,
 						validator: *validator,
-						candidate_index,
+						candidate_indices,
 						signature,
-					}
-				});
+					};
+					(approval_subjects, approval)
+				},
+			);
 
 			for (peer, peer_knowledge) in &mut block_entry.known_by {
 				if !topology
@@ -1726,16 +1819,18 @@ async fn adjust_required_routing_and_propagate<Context, BlockFilter, RoutingModi
 				}
 
 				if !peer_knowledge.contains(&message_subject, MessageKind::Assignment) {
-					peer_knowledge.sent.insert(message_subject.clone(), MessageKind::Assignment);
+					peer_knowledge.sent.insert_all(&assignment_subjects, MessageKind::Assignment);
 					peer_assignments
 						.entry(*peer)
 						.or_insert_with(Vec::new)
 						.push(assignment_message.clone());
 				}
 
-				if let Some(approval_message) = approval_message.as_ref() {
-					if !peer_knowledge.contains(&message_subject, MessageKind::Approval) {
-						peer_knowledge.sent.insert(message_subject.clone(), MessageKind::Approval);
+				if let Some((approval_subjects, approval_message)) = approval_message.as_ref() {
+					if !peer_knowledge.contains(&message_subject, MessageKind::Approval) &&
+						peer_knowledge.contains_all(approval_subjects, MessageKind::Assignment)
+					{
+						peer_knowledge.sent.insert_all(approval_subjects, MessageKind::Approval);
 						peer_approvals
 							.entry(*peer)
 							.or_insert_with(Vec::new)
@@ -1855,12 +1950,12 @@ impl ApprovalDistribution {
 			ApprovalDistributionMessage::NewBlocks(metas) => {
 				state.handle_new_blocks(ctx, metrics, metas, rng).await;
 			},
-			ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index) => {
+			ApprovalDistributionMessage::DistributeAssignment(cert, candidate_indices) => {
 				gum::debug!(
 					target: LOG_TARGET,
-					"Distributing our assignment on candidate (block={}, index={})",
+					"Distributing our assignment on candidates (block={}, indices={:?})",
 					cert.block_hash,
-					candidate_index,
+					candidate_indices,
 				);
 
 				state
@@ -1869,7 +1964,7 @@ impl ApprovalDistribution {
 						&metrics,
 						MessageSource::Local,
 						cert,
-						candidate_index,
+						candidate_indices,
 						rng,
 					)
 					.await;
@@ -1877,9 +1972,9 @@ impl ApprovalDistribution {
 			ApprovalDistributionMessage::DistributeApproval(vote) => {
 				gum::debug!(
 					target: LOG_TARGET,
-					"Distributing our approval vote on candidate (block={}, index={})",
+					"Distributing our approval vote on candidates (block={}, indices={:?})",
 					vote.block_hash,
-					vote.candidate_index,
+					vote.candidate_indices,
 				);
 
 				state
@@ -1927,15 +2022,61 @@ const fn ensure_size_not_zero(size: usize) -> usize {
 /// configuration.
 pub const MAX_ASSIGNMENT_BATCH_SIZE: usize = ensure_size_not_zero(
 	MAX_NOTIFICATION_SIZE as usize /
-		std::mem::size_of::<(IndirectAssignmentCert, CandidateIndex)>() /
+		std::mem::size_of::<(IndirectAssignmentCert, CandidateBitfield)>() /
 		3,
 );
 
 /// The maximum amount of approvals per batch is 33% of maximum allowed by protocol.
 pub const MAX_APPROVAL_BATCH_SIZE: usize = ensure_size_not_zero(
-	MAX_NOTIFICATION_SIZE as usize / std::mem::size_of::<IndirectSignedApprovalVote>() / 3,
+	MAX_NOTIFICATION_SIZE as usize / std::mem::size_of::<IndirectSignedApprovalVoteV2>() / 3,
 );
 
+// Assignments and approvals are sent as `v1` messages unless some of them cover multiple
+// candidates, which only peers on `v2` can receive. The network bridge drops those when
+// sending to older peers.
+fn assignments_message(
+	assignments: Vec<(IndirectAssignmentCert, CandidateBitfield)>,
+) -> net_protocol::VersionedValidationProtocol {
+	let is_v1 = assignments.iter().all(|(assignment, candidates)| {
+		!matches!(assignment.cert.kind, AssignmentCertKind::RelayVRFModuloCompact { .. }) &&
+			candidates.count_ones() == 1
+	});
+
+	if is_v1 {
+		let assignments = assignments
+			.into_iter()
+			.filter_map(|(assignment, candidates)| {
+				candidates.first_one().map(|candidate_index| (assignment, candidate_index))
+			})
+			.collect();
+		Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
+			protocol_v1::ApprovalDistributionMessage::Assignments(assignments),
+		))
+	} else {
+		Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+			protocol_v2::ApprovalDistributionMessage::Assignments(assignments),
+		))
+	}
+}
+
+fn approvals_message(
+	approvals: Vec<IndirectSignedApprovalVoteV2>,
+) -> net_protocol::VersionedValidationProtocol {
+	if approvals.iter().all(|approval| approval.candidate_indices.count_ones() == 1) {
+		let approvals = approvals
+			.into_iter()
+			.filter_map(|approval| IndirectSignedApprovalVote::try_from(approval).ok())
+			.collect();
+		Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
+			protocol_v1::ApprovalDistributionMessage::Approvals(approvals),
+		))
+	} else {
+		Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+			protocol_v2::ApprovalDistributionMessage::Approvals(approvals),
+		))
+	}
+}
+
 /// Send assignments while honoring the `max_notification_size` of the protocol.
 ///
 /// Splitting the messages into multiple notifications allows more granular processing at the
@@ -1943,7 +2084,7 @@ pub const MAX_APPROVAL_BATCH_SIZE: usize = ensure_size_not_zero(
 /// of assignments and can `select!` other tasks.
 pub(crate) async fn send_assignments_batched(
 	sender: &mut impl overseer::ApprovalDistributionSenderTrait,
-	assignments: Vec<(IndirectAssignmentCert, CandidateIndex)>,
+	assignments: Vec<(IndirectAssignmentCert, CandidateBitfield)>,
 	peer: PeerId,
 ) {
 	let mut batches = assignments.into_iter().peekable();
@@ -1954,9 +2095,7 @@ pub(crate) async fn send_assignments_batched(
 		sender
 			.send_message(NetworkBridgeTxMessage::SendValidationMessage(
 				vec![peer],
-				Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
-					protocol_v1::ApprovalDistributionMessage::Assignments(batch),
-				)),
+				assignments_message(batch),
 			))
 			.await;
 	}
@@ -1965,7 +2104,7 @@ pub(crate) async fn send_assignments_batched(
 /// Send approvals while honoring the `max_notification_size` of the protocol.
 pub(crate) async fn send_approvals_batched(
 	sender: &mut impl overseer::ApprovalDistributionSenderTrait,
-	approvals: Vec<IndirectSignedApprovalVote>,
+	approvals: Vec<IndirectSignedApprovalVoteV2>,
 	peer: PeerId,
 ) {
 	let mut batches = approvals.into_iter().peekable();
@@ -1976,9 +2115,7 @@ pub(crate) async fn send_approvals_batched(
 		sender
 			.send_message(NetworkBridgeTxMessage::SendValidationMessage(
 				vec![peer],
-				Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
-					protocol_v1::ApprovalDistributionMessage::Approvals(batch),
-				)),
+				approvals_message(batch),
 			))
 			.await;
 	}
@@ -256,6 +256,21 @@ async fn send_message_from_peer(
 	.await;
 }
 
+async fn send_message_from_peer_v2(
+	virtual_overseer: &mut VirtualOverseer,
+	peer_id: &PeerId,
+	msg: protocol_v2::ApprovalDistributionMessage,
+) {
+	overseer_send(
+		virtual_overseer,
+		ApprovalDistributionMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerMessage(
+			peer_id.clone(),
+			Versioned::V2(msg),
+		)),
+	)
+	.await;
+}
+
 fn fake_assignment_cert(block_hash: Hash, validator: ValidatorIndex) -> IndirectAssignmentCert {
 	let ctx = schnorrkel::signing_context(RELAY_VRF_MODULO_CONTEXT);
 	let msg = b"WhenParachains?";
@@ -363,10 +378,11 @@ fn try_import_the_same_assignment() {
 			overseer_recv(overseer).await,
 			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
 				assignment,
-				0u32,
+				claimed_indices,
 				tx,
 			)) => {
 				assert_eq!(assignment, cert);
+				assert_eq!(claimed_indices, 0u32.into());
 				tx.send(AssignmentCheckResult::Accepted).unwrap();
 			}
 		);
@@ -441,10 +457,11 @@ fn delay_reputation_change() {
 			overseer_recv(overseer).await,
 			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
 				assignment,
-				0u32,
+				claimed_indices,
 				tx,
 			)) => {
 				assert_eq!(assignment, cert);
+				assert_eq!(claimed_indices, 0u32.into());
 				tx.send(AssignmentCheckResult::Accepted).unwrap();
 			}
 		);
@@ -515,7 +532,7 @@ fn spam_attack_results_in_negative_reputation_change() {
 					tx,
 				)) => {
 					assert_eq!(assignment, assignments[i].0);
-					assert_eq!(claimed_candidate_index, assignments[i].1);
+					assert_eq!(claimed_candidate_index, assignments[i].1.into());
 					tx.send(AssignmentCheckResult::Accepted).unwrap();
 				}
 			);
@@ -579,7 +596,7 @@ fn peer_sending_us_the_same_we_just_sent_them_is_ok() {
 		let cert = fake_assignment_cert(hash, validator_index);
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index.into()),
 		)
 		.await;
 
@@ -657,7 +674,7 @@ fn import_approval_happy_path() {
 		let cert = fake_assignment_cert(hash, validator_index);
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index.into()),
 		)
 		.await;
 
@@ -690,7 +707,7 @@ fn import_approval_happy_path() {
 				vote,
 				tx,
 			)) => {
-				assert_eq!(vote, approval);
+				assert_eq!(vote, approval.clone().into());
 				tx.send(ApprovalCheckResult::Accepted).unwrap();
 			}
 		);
@@ -767,7 +784,7 @@ fn import_approval_bad() {
 				tx,
 			)) => {
 				assert_eq!(assignment, cert);
-				assert_eq!(i, candidate_index);
+				assert_eq!(i, candidate_index.into());
 				tx.send(AssignmentCheckResult::Accepted).unwrap();
 			}
 		);
@@ -784,7 +801,7 @@ fn import_approval_bad() {
 				vote,
 				tx,
 			)) => {
-				assert_eq!(vote, approval);
+				assert_eq!(vote, approval.clone().into());
 				tx.send(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownBlock(hash))).unwrap();
 			}
 		);
@@ -794,6 +811,190 @@ fn import_approval_bad() {
 	});
 }
 
+#[test]
+fn import_multiple_candidate_assignment_and_approval() {
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+	let parent_hash = Hash::repeat_byte(0xFF);
+	let hash = Hash::repeat_byte(0xAA);
+
+	let _ = test_harness(state_without_reputation_delay(), |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		// setup peers
+		setup_peer_with_view(overseer, &peer_a, view![]).await;
+		setup_peer_with_view(overseer, &peer_b, view![hash]).await;
+		setup_peer_with_view(overseer, &peer_c, view![hash]).await;
+
+		// new block `hash` with 2 candidates
+		let meta = BlockApprovalMeta {
+			hash,
+			parent_hash,
+			number: 1,
+			candidates: vec![Default::default(); 2],
+			slot: 1.into(),
+			session: 1,
+		};
+		let msg = ApprovalDistributionMessage::NewBlocks(vec![meta]);
+		overseer_send(overseer, msg).await;
+
+		// import an assignment claiming both candidates locally
+		let validator_index = ValidatorIndex(0);
+		let candidate_indices: CandidateBitfield = vec![0, 1].try_into().unwrap();
+		let cert = fake_assignment_cert(hash, validator_index);
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeAssignment(
+				cert.clone(),
+				candidate_indices.clone(),
+			),
+		)
+		.await;
+
+		// it is sent once, as a `v2` message
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+					protocol_v2::ApprovalDistributionMessage::Assignments(assignments)
+				))
+			)) => {
+				assert_eq!(peers.len(), 2);
+				assert_eq!(assignments, vec![(cert.clone(), candidate_indices.clone())]);
+			}
+		);
+
+		// send an approval covering both candidates from peer_b
+		let approval = IndirectSignedApprovalVoteV2 {
+			block_hash: hash,
+			candidate_indices: candidate_indices.clone(),
+			validator: validator_index,
+			signature: dummy_signature(),
+		};
+		let msg = protocol_v2::ApprovalDistributionMessage::Approvals(vec![approval.clone()]);
+		send_message_from_peer_v2(overseer, &peer_b, msg).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApproval(
+				vote,
+				tx,
+			)) => {
+				assert_eq!(vote, approval);
+				tx.send(ApprovalCheckResult::Accepted).unwrap();
+			}
+		);
+
+		expect_reputation_change(overseer, &peer_b, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+					protocol_v2::ApprovalDistributionMessage::Approvals(approvals)
+				))
+			)) => {
+				assert_eq!(peers, vec![peer_c]);
+				assert_eq!(approvals, vec![approval.clone()]);
+			}
+		);
+
+		// a peer learning about the block gets both messages only once
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerViewChange(
+				peer_a.clone(),
+				view![hash],
+			)),
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+					protocol_v2::ApprovalDistributionMessage::Assignments(assignments)
+				))
+			)) => {
+				assert_eq!(peers, vec![peer_a]);
+				assert_eq!(assignments, vec![(cert, candidate_indices)]);
+			}
+		);
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V2(protocol_v2::ValidationProtocol::ApprovalDistribution(
+					protocol_v2::ApprovalDistributionMessage::Approvals(approvals)
+				))
+			)) => {
+				assert_eq!(peers, vec![peer_a]);
+				assert_eq!(approvals, vec![approval]);
+			}
+		);
+
+		assert!(overseer.recv().timeout(TIMEOUT).await.is_none(), "no message should be sent");
+		virtual_overseer
+	});
+}
+
+#[test]
+fn multiple_candidate_assignment_from_peer_is_checked_once() {
+	let peer_a = PeerId::random();
+	let parent_hash = Hash::repeat_byte(0xFF);
+	let hash = Hash::repeat_byte(0xAA);
+
+	let _ = test_harness(state_without_reputation_delay(), |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		setup_peer_with_view(overseer, &peer_a, view![hash]).await;
+
+		// new block `hash` with 2 candidates
+		let meta = BlockApprovalMeta {
+			hash,
+			parent_hash,
+			number: 1,
+			candidates: vec![Default::default(); 2],
+			slot: 1.into(),
+			session: 1,
+		};
+		let msg = ApprovalDistributionMessage::NewBlocks(vec![meta]);
+		overseer_send(overseer, msg).await;
+
+		let validator_index = ValidatorIndex(0);
+		let candidate_indices: CandidateBitfield = vec![0, 1].try_into().unwrap();
+		let cert = fake_assignment_cert(hash, validator_index);
+		let msg = protocol_v2::ApprovalDistributionMessage::Assignments(vec![(
+			cert.clone(),
+			candidate_indices.clone(),
+		)]);
+		send_message_from_peer_v2(overseer, &peer_a, msg.clone()).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
+				assignment,
+				claimed_indices,
+				tx,
+			)) => {
+				assert_eq!(assignment, cert);
+				assert_eq!(claimed_indices, candidate_indices);
+				tx.send(AssignmentCheckResult::Accepted).unwrap();
+			}
+		);
+		expect_reputation_change(overseer, &peer_a, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+		// the same assignment again is a duplicate for both candidates
+		send_message_from_peer_v2(overseer, &peer_a, msg).await;
+		expect_reputation_change(overseer, &peer_a, COST_DUPLICATE_MESSAGE).await;
+
+		assert!(overseer.recv().timeout(TIMEOUT).await.is_none(), "no message should be sent");
+		virtual_overseer
+	});
+}
+
 /// make sure we clean up the state on block finalized
 #[test]
 fn update_our_view() {
@@ -912,9 +1113,17 @@ fn update_peer_view() {
 		let cert_a = fake_assignment_cert(hash_a, ValidatorIndex(0));
 		let cert_b = fake_assignment_cert(hash_b, ValidatorIndex(0));
 
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeAssignment(cert_a, 0)).await;
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeAssignment(cert_a, 0u32.into()),
+		)
+		.await;
 
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeAssignment(cert_b, 0)).await;
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeAssignment(cert_b, 0u32.into()),
+		)
+		.await;
 
 		// connect a peer
 		setup_peer_with_view(overseer, peer, view![hash_a]).await;
@@ -966,7 +1175,7 @@ fn update_peer_view() {
 
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert_c.clone(), 0),
+			ApprovalDistributionMessage::DistributeAssignment(cert_c.clone(), 0u32.into()),
 		)
 		.await;
 
@@ -1063,7 +1272,7 @@ fn import_remotely_then_locally() {
 				tx,
 			)) => {
 				assert_eq!(assignment, cert);
-				assert_eq!(i, candidate_index);
+				assert_eq!(i, candidate_index.into());
 				tx.send(AssignmentCheckResult::Accepted).unwrap();
 			}
 		);
@@ -1073,7 +1282,7 @@ fn import_remotely_then_locally() {
 		// import the same assignment locally
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index.into()),
 		)
 		.await;
 
@@ -1095,14 +1304,15 @@ fn import_remotely_then_locally() {
 				vote,
 				tx,
 			)) => {
-				assert_eq!(vote, approval);
+				assert_eq!(vote, approval.clone().into());
 				tx.send(ApprovalCheckResult::Accepted).unwrap();
 			}
 		);
 		expect_reputation_change(overseer, peer, BENEFIT_VALID_MESSAGE_FIRST).await;
 
 		// import the same approval locally
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeApproval(approval)).await;
+		overseer_send(overseer, ApprovalDistributionMessage::DistributeApproval(approval.into()))
+			.await;
 
 		assert!(overseer.recv().timeout(TIMEOUT).await.is_none(), "no message should be sent");
 		virtual_overseer
@@ -1145,12 +1355,15 @@ fn sends_assignments_even_when_state_is_approved() {
 
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index.into()),
 		)
 		.await;
 
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeApproval(approval.clone()))
-			.await;
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeApproval(approval.clone().into()),
+		)
+		.await;
 
 		// connect the peer.
 		setup_peer_with_view(overseer, peer, view![hash]).await;
@@ -1256,7 +1469,7 @@ fn race_condition_in_local_vs_remote_view_update() {
 					tx,
 				)) => {
 					assert_eq!(assignment, assignments[i].0);
-					assert_eq!(claimed_candidate_index, assignments[i].1);
+					assert_eq!(claimed_candidate_index, assignments[i].1.into());
 					tx.send(AssignmentCheckResult::Accepted).unwrap();
 				}
 			);
@@ -1323,12 +1536,15 @@ fn propagates_locally_generated_assignment_to_both_dimensions() {
 
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index.into()),
 		)
 		.await;
 
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeApproval(approval.clone()))
-			.await;
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeApproval(approval.clone().into()),
+		)
+		.await;
 
 		let assignments = vec![(cert.clone(), candidate_index)];
 		let approvals = vec![approval.clone()];
@@ -1571,12 +1787,15 @@ fn propagates_to_required_after_connect() {
 
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index.into()),
 		)
 		.await;
 
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeApproval(approval.clone()))
-			.await;
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeApproval(approval.clone().into()),
+		)
+		.await;
 
 		let assignments = vec![(cert.clone(), candidate_index)];
 		let approvals = vec![approval.clone()];
@@ -1696,12 +1915,15 @@ fn sends_to_more_peers_after_getting_topology() {
 
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index.into()),
 		)
 		.await;
 
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeApproval(approval.clone()))
-			.await;
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeApproval(approval.clone().into()),
+		)
+		.await;
 
 		let assignments = vec![(cert.clone(), candidate_index)];
 		let approvals = vec![approval.clone()];
@@ -1855,12 +2077,15 @@ fn originator_aggression_l1() {
 
 		overseer_send(
 			overseer,
-			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index),
+			ApprovalDistributionMessage::DistributeAssignment(cert.clone(), candidate_index.into()),
 		)
 		.await;
 
-		overseer_send(overseer, ApprovalDistributionMessage::DistributeApproval(approval.clone()))
-			.await;
+		overseer_send(
+			overseer,
+			ApprovalDistributionMessage::DistributeApproval(approval.clone().into()),
+		)
+		.await;
 
 		let assignments = vec![(cert.clone(), candidate_index)];
 		let approvals = vec![approval.clone()];
@@ -2393,17 +2618,22 @@ fn batch_test_round(message_count: usize) {
 		let validators = 0..message_count;
 		let assignments: Vec<_> = validators
 			.clone()
-			.map(|index| (fake_assignment_cert(Hash::zero(), ValidatorIndex(index as u32)), 0))
+			.map(|index| {
+				(fake_assignment_cert(Hash::zero(), ValidatorIndex(index as u32)), 0u32.into())
+			})
 			.collect();
 
 		let approvals: Vec<_> = validators
-			.map(|index| IndirectSignedApprovalVote {
-				block_hash: Hash::zero(),
-				candidate_index: 0,
-				validator: ValidatorIndex(index as u32),
-				signature: dummy_signature(),
+			.map(|index| {
+				IndirectSignedApprovalVote {
+					block_hash: Hash::zero(),
+					candidate_index: 0,
+					validator: ValidatorIndex(index as u32),
+					signature: dummy_signature(),
+				}
+				.into()
 			})
-			.collect();
+			.collect::<Vec<IndirectSignedApprovalVoteV2>>();
 
 		let peer = PeerId::random();
 		send_assignments_batched(&mut sender, assignments.clone(), peer).await;
@@ -2456,7 +2686,10 @@ fn batch_test_round(message_count: usize) {
 					assert_eq!(peers.len(), 1);
 
 					for (message_index,  approval) in sent_approvals.iter().enumerate() {
-						assert_eq!(approval, &approvals[approval_index + message_index]);
+						assert_eq!(
+							&IndirectSignedApprovalVoteV2::from(approval.clone()),
+							&approvals[approval_index + message_index],
+						);
 					}
 				}
 			);
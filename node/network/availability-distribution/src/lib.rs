@@ -18,7 +18,7 @@ use futures::{future::Either, FutureExt, StreamExt, TryFutureExt};
 
 use sp_keystore::KeystorePtr;
 
-use polkadot_node_network_protocol::request_response::{v1, IncomingRequestReceiver};
+use polkadot_node_network_protocol::request_response::{v1, QuotaReceiver};
 use polkadot_node_subsystem::{
 	jaeger, messages::AvailabilityDistributionMessage, overseer, FromOrchestra, OverseerSignal,
 	SpawnedSubsystem, SubsystemError,
@@ -34,6 +34,7 @@ use polkadot_node_subsystem_util::runtime::RuntimeInfo;
 
 /// `Requester` taking care of requesting chunks for candidates pending availability.
 mod requester;
+pub use requester::ChunkFetchingBudget;
 use requester::Requester;
 
 /// Handing requests for PoVs during backing.
@@ -58,16 +59,20 @@ pub struct AvailabilityDistributionSubsystem {
 	runtime: RuntimeInfo,
 	/// Receivers to receive messages from.
 	recvs: IncomingRequestReceivers,
+	/// Bandwidth budget for fetching our chunks.
+	budget: ChunkFetchingBudget,
 	/// Prometheus metrics.
 	metrics: Metrics,
 }
 
 /// Receivers to be passed into availability distribution.
+///
+/// Requests of peers exceeding their quota are answered with an error by the receivers.
 pub struct IncomingRequestReceivers {
 	/// Receiver for incoming PoV requests.
-	pub pov_req_receiver: IncomingRequestReceiver<v1::PoVFetchingRequest>,
+	pub pov_req_receiver: QuotaReceiver<v1::PoVFetchingRequest>,
 	/// Receiver for incoming availability chunk requests.
-	pub chunk_req_receiver: IncomingRequestReceiver<v1::ChunkFetchingRequest>,
+	pub chunk_req_receiver: QuotaReceiver<v1::ChunkFetchingRequest>,
 }
 
 #[overseer::subsystem(AvailabilityDistribution, error=SubsystemError, prefix=self::overseer)]
@@ -85,18 +90,23 @@ impl<Context> AvailabilityDistributionSubsystem {
 #[overseer::contextbounds(AvailabilityDistribution, prefix = self::overseer)]
 impl AvailabilityDistributionSubsystem {
 	/// Create a new instance of the availability distribution.
-	pub fn new(keystore: KeystorePtr, recvs: IncomingRequestReceivers, metrics: Metrics) -> Self {
+	pub fn new(
+		keystore: KeystorePtr,
+		recvs: IncomingRequestReceivers,
+		budget: ChunkFetchingBudget,
+		metrics: Metrics,
+	) -> Self {
 		let runtime = RuntimeInfo::new(Some(keystore));
-		Self { runtime, recvs, metrics }
+		Self { runtime, recvs, budget, metrics }
 	}
 
 	/// Start processing work as passed on from the Overseer.
 	async fn run<Context>(self, mut ctx: Context) -> std::result::Result<(), FatalError> {
-		let Self { mut runtime, recvs, metrics } = self;
+		let Self { mut runtime, recvs, budget, metrics } = self;
 		let mut spans: HashMap<Hash, jaeger::PerLeafSpan> = HashMap::new();
 
 		let IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver } = recvs;
-		let mut requester = Requester::new(metrics.clone()).fuse();
+		let mut requester = Requester::new(budget, metrics.clone()).fuse();
 
 		{
 			let sender = ctx.sender().clone();
@@ -18,9 +18,13 @@ use polkadot_node_subsystem_util::{
 	metrics,
 	metrics::{
 		prometheus,
-		prometheus::{Counter, CounterVec, Opts, PrometheusError, Registry, U64},
+		prometheus::{
+			Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramOpts, Opts, PrometheusError,
+			Registry, U64,
+		},
 	},
 };
+use std::time::Duration;
 
 /// Label for success counters.
 pub const SUCCEEDED: &'static str = "succeeded";
@@ -56,6 +60,18 @@ struct MetricsInner {
 	/// Number of times our first set of validators did not provide the needed chunk and we had to
 	/// query further validators.
 	retries: Counter<U64>,
+
+	/// Number of chunk requests granted by the scheduler, by whether they had to wait.
+	scheduled_chunk_requests: CounterVec<U64>,
+
+	/// Time chunk requests waited for the scheduler.
+	chunk_request_queue_time: Histogram,
+
+	/// Number of chunk requests waiting for the scheduler.
+	queued_chunk_requests: Gauge<U64>,
+
+	/// Bytes of chunk requests and their expected responses in flight.
+	in_flight_chunk_bytes: GaugeVec<U64>,
 }
 
 impl Metrics {
@@ -98,6 +114,27 @@ impl Metrics {
 			metrics.retries.inc()
 		}
 	}
+
+	/// A chunk request was granted by the scheduler after waiting for `queued`.
+	pub fn on_chunk_request_granted(&self, queued: Duration) {
+		if let Some(metrics) = &self.0 {
+			let label = if queued.is_zero() { "immediate" } else { "deferred" };
+			metrics.scheduled_chunk_requests.with_label_values(&[label]).inc();
+			metrics.chunk_request_queue_time.observe(queued.as_secs_f64());
+		}
+	}
+
+	/// Update the scheduler's queue length and bytes in flight.
+	pub fn on_scheduler_state(&self, queued: usize, downlink_bytes: u64, uplink_bytes: u64) {
+		if let Some(metrics) = &self.0 {
+			metrics.queued_chunk_requests.set(queued as u64);
+			metrics
+				.in_flight_chunk_bytes
+				.with_label_values(&["downlink"])
+				.set(downlink_bytes);
+			metrics.in_flight_chunk_bytes.with_label_values(&["uplink"]).set(uplink_bytes);
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -150,6 +187,43 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			scheduled_chunk_requests: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"polkadot_parachain_scheduled_chunk_requests_total",
+						"Number of chunk requests granted by the bandwidth scheduler.",
+					),
+					&["decision"],
+				)?,
+				registry,
+			)?,
+			chunk_request_queue_time: prometheus::register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"polkadot_parachain_chunk_request_queue_time",
+						"Time chunk requests waited for the bandwidth scheduler.",
+					)
+					.buckets(vec![0.0, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]),
+				)?,
+				registry,
+			)?,
+			queued_chunk_requests: prometheus::register(
+				Gauge::new(
+					"polkadot_parachain_queued_chunk_requests",
+					"Number of chunk requests waiting for the bandwidth scheduler.",
+				)?,
+				registry,
+			)?,
+			in_flight_chunk_bytes: prometheus::register(
+				GaugeVec::new(
+					Opts::new(
+						"polkadot_parachain_in_flight_chunk_bytes",
+						"Bytes of chunk requests and expected responses in flight.",
+					),
+					&["direction"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -21,6 +21,7 @@ use futures::{
 	future::select,
 	FutureExt, SinkExt,
 };
+use parity_scale_codec::Encode;
 
 use polkadot_erasure_coding::branch_hash;
 use polkadot_node_network_protocol::request_response::{
@@ -34,14 +35,17 @@ use polkadot_node_subsystem::{
 	overseer,
 };
 use polkadot_primitives::{
-	AuthorityDiscoveryId, BlakeTwo256, CandidateHash, GroupIndex, Hash, HashT, OccupiedCore,
-	SessionIndex,
+	AuthorityDiscoveryId, BlakeTwo256, BlockNumber, CandidateHash, GroupIndex, Hash, HashT,
+	OccupiedCore, SessionIndex,
 };
 
 use crate::{
 	error::{FatalError, Result},
 	metrics::{Metrics, FAILED, SUCCEEDED},
-	requester::session_cache::{BadValidators, SessionInfo},
+	requester::{
+		scheduler::{RequestOutcome, RequestPermit, ScheduleRequest},
+		session_cache::{BadValidators, SessionInfo},
+	},
 	LOG_TARGET,
 };
 
@@ -94,6 +98,9 @@ pub enum FromFetchTask {
 
 	/// We were not able to fetch the desired chunk for the given `CandidateHash`.
 	Failed(CandidateHash),
+
+	/// Ask for a permit to send a chunk request.
+	Schedule(ScheduleRequest),
 }
 
 /// Information a running task needs.
@@ -120,6 +127,11 @@ struct RunningTask {
 	/// Relay parent of the candidate to fetch.
 	relay_parent: Hash,
 
+	/// Block number at which the candidate times out, if it does not become available.
+	///
+	/// Used for prioritising our requests.
+	time_out_at: BlockNumber,
+
 	/// Sender for communicating with other subsystems and reporting results.
 	sender: mpsc::Sender<FromFetchTask>,
 
@@ -171,6 +183,7 @@ impl FetchTaskConfig {
 			},
 			erasure_root: core.candidate_descriptor.erasure_root,
 			relay_parent: core.candidate_descriptor.relay_parent,
+			time_out_at: core.time_out_at,
 			metrics,
 			sender,
 			span,
@@ -354,6 +367,8 @@ impl RunningTask {
 			"Starting chunk request",
 		);
 
+		let permit = self.acquire_permit(validator).await?;
+
 		let (full_request, response_recv) =
 			OutgoingRequest::new(Recipient::Authority(validator.clone()), self.request);
 		let requests = Requests::ChunkFetchingV1(full_request);
@@ -369,7 +384,13 @@ impl RunningTask {
 			.await
 			.map_err(|_| TaskError::ShuttingDown)?;
 
-		match response_recv.await {
+		let response = response_recv.await;
+		permit.conclude(match &response {
+			Ok(resp) => RequestOutcome::Received(resp.encoded_size() as u64),
+			Err(_) => RequestOutcome::Failed,
+		});
+
+		match response {
 			Ok(resp) => Ok(resp),
 			Err(RequestError::InvalidResponse(err)) => {
 				gum::warn!(
@@ -415,6 +436,24 @@ impl RunningTask {
 		}
 	}
 
+	/// Wait until the scheduler allows us to send a request to `validator`.
+	async fn acquire_permit(
+		&mut self,
+		validator: &AuthorityDiscoveryId,
+	) -> std::result::Result<RequestPermit, TaskError> {
+		let (tx, rx) = oneshot::channel();
+		self.sender
+			.send(FromFetchTask::Schedule(ScheduleRequest {
+				peer: validator.clone(),
+				time_out_at: self.time_out_at,
+				request_size: self.request.encoded_size() as u64,
+				permit: tx,
+			}))
+			.await
+			.map_err(|_| TaskError::ShuttingDown)?;
+		rx.await.map_err(|_| TaskError::ShuttingDown)
+	}
+
 	fn validate_chunk(&self, validator: &AuthorityDiscoveryId, chunk: &ErasureChunk) -> bool {
 		let anticipated_hash =
 			match branch_hash(&self.erasure_root, chunk.proof(), chunk.index.0 as usize) {
@@ -217,6 +217,10 @@ impl TestRun {
 					FromFetchTask::Concluded(_) => break,
 					FromFetchTask::Failed(_) => break,
 					FromFetchTask::Message(msg) => end_ok = self.handle_message(msg).await,
+					FromFetchTask::Schedule(request) => {
+						let (permit, _) = RequestPermit::new();
+						let _ = request.permit.send(permit);
+					},
 				}
 			}
 			if !end_ok {
@@ -289,6 +293,7 @@ fn get_test_running_task() -> (RunningTask, mpsc::Receiver<FromFetchTask>) {
 			},
 			erasure_root: Hash::repeat_byte(99),
 			relay_parent: Hash::repeat_byte(71),
+			time_out_at: 0,
 			sender: tx,
 			metrics: Metrics::new_dummy(),
 			span: jaeger::Span::Disabled,
@@ -24,12 +24,15 @@ use std::{
 	},
 	iter::IntoIterator,
 	pin::Pin,
+	time::Instant,
 };
 
 use futures::{
 	channel::{mpsc, oneshot},
+	future::BoxFuture,
+	stream::FuturesUnordered,
 	task::{Context, Poll},
-	Stream,
+	FutureExt, Stream,
 };
 
 use polkadot_node_subsystem::{
@@ -53,6 +56,11 @@ use session_cache::SessionCache;
 mod fetch_task;
 use fetch_task::{FetchTask, FetchTaskConfig, FromFetchTask};
 
+/// Bandwidth-aware scheduling of chunk requests.
+mod scheduler;
+pub use scheduler::ChunkFetchingBudget;
+use scheduler::{RequestId, RequestOutcome, RequestPermit, RequestScheduler};
+
 /// Requester takes care of requesting erasure chunks from backing groups and stores them in the
 /// av store.
 ///
@@ -75,6 +83,14 @@ pub struct Requester {
 	/// Receive messages from `FetchTask`.
 	rx: mpsc::Receiver<FromFetchTask>,
 
+	/// Decides when `FetchTask`s may send their requests.
+	scheduler: RequestScheduler<oneshot::Sender<RequestPermit>>,
+
+	/// Outcomes of requests the scheduler granted.
+	///
+	/// Resolves to `None` if a task dropped its permit.
+	in_flight: FuturesUnordered<BoxFuture<'static, (RequestId, Option<RequestOutcome>)>>,
+
 	/// Prometheus Metrics
 	metrics: Metrics,
 }
@@ -88,9 +104,17 @@ impl Requester {
 	///
 	/// You must feed it with `ActiveLeavesUpdate` via `update_fetching_heads` and make it progress
 	/// by advancing the stream.
-	pub fn new(metrics: Metrics) -> Self {
+	pub fn new(budget: ChunkFetchingBudget, metrics: Metrics) -> Self {
 		let (tx, rx) = mpsc::channel(1);
-		Requester { fetches: HashMap::new(), session_cache: SessionCache::new(), tx, rx, metrics }
+		Requester {
+			fetches: HashMap::new(),
+			session_cache: SessionCache::new(),
+			tx,
+			rx,
+			scheduler: RequestScheduler::new(budget, metrics.clone()),
+			in_flight: FuturesUnordered::new(),
+			metrics,
+		}
 	}
 
 	/// Update heads that need availability distribution.
@@ -255,11 +279,39 @@ impl Requester {
 	}
 }
 
+impl Requester {
+	/// Hand out permits for as many queued requests as the scheduler allows.
+	fn grant_requests(&mut self) {
+		loop {
+			let now = Instant::now();
+			let granted = self.scheduler.grant(now);
+			if granted.is_empty() {
+				return
+			}
+
+			for (id, permit_tx) in granted {
+				let (permit, outcome) = RequestPermit::new();
+				if permit_tx.send(permit).is_err() {
+					// The task is gone already, free up its budget right away.
+					self.scheduler.complete(id, None, now);
+					continue
+				}
+				self.in_flight.push(outcome.map(move |outcome| (id, outcome.ok())).boxed());
+			}
+		}
+	}
+}
+
 impl Stream for Requester {
 	type Item = overseer::AvailabilityDistributionOutgoingMessages;
 
 	fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
 		loop {
+			if let Poll::Ready(Some((id, outcome))) = Pin::new(&mut self.in_flight).poll_next(ctx) {
+				self.scheduler.complete(id, outcome, Instant::now());
+				self.grant_requests();
+				continue
+			}
 			match Pin::new(&mut self.rx).poll_next(ctx) {
 				Poll::Ready(Some(FromFetchTask::Message(m))) => return Poll::Ready(Some(m)),
 				Poll::Ready(Some(FromFetchTask::Concluded(Some(bad_boys)))) => {
@@ -271,6 +323,16 @@ impl Stream for Requester {
 					// Make sure we retry on next block still pending availability.
 					self.fetches.remove(&candidate_hash);
 				},
+				Poll::Ready(Some(FromFetchTask::Schedule(request))) => {
+					self.scheduler.enqueue(
+						request.peer,
+						request.time_out_at,
+						request.request_size,
+						request.permit,
+						Instant::now(),
+					);
+					self.grant_requests();
+				},
 				Poll::Ready(None) => return Poll::Ready(None),
 				Poll::Pending => return Poll::Pending,
 			}
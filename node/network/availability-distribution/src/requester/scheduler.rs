@@ -0,0 +1,360 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bandwidth-aware scheduling of chunk requests.
+//!
+//! Fetch tasks ask for a permit before sending each chunk request. Permits are handed out to
+//! candidates closest to their availability timeout first, as long as the bytes in flight fit
+//! into the configured bandwidth budget and into what the requested peer is estimated to deliver
+//! before the request times out.
+
+use std::{
+	cmp::Ordering,
+	collections::{BinaryHeap, HashMap},
+	time::{Duration, Instant},
+};
+
+use futures::channel::oneshot;
+
+use polkadot_node_network_protocol::request_response::CHUNK_REQUEST_TIMEOUT;
+use polkadot_primitives::{AuthorityDiscoveryId, BlockNumber};
+
+use crate::metrics::Metrics;
+
+/// Chunk size we assume until we received the first chunk.
+const DEFAULT_EXPECTED_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// New samples are weighted with `1 / EMA_DIVISOR` in all moving averages.
+const EMA_DIVISOR: u64 = 8;
+
+/// Bandwidth budget for fetching availability chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkFetchingBudget {
+	/// Downlink bandwidth responses may use, in bytes per second.
+	pub downlink_bytes_per_sec: u64,
+	/// Uplink bandwidth requests may use, in bytes per second.
+	pub uplink_bytes_per_sec: u64,
+}
+
+impl Default for ChunkFetchingBudget {
+	fn default() -> Self {
+		// 500 Mbit/s, as recommended for validators.
+		Self { downlink_bytes_per_sec: 62_500_000, uplink_bytes_per_sec: 62_500_000 }
+	}
+}
+
+/// Bytes which may be in flight at once for all of them to arrive within
+/// [`CHUNK_REQUEST_TIMEOUT`].
+fn max_in_flight(bytes_per_sec: u64) -> u64 {
+	bytes_per_sec.saturating_mul(CHUNK_REQUEST_TIMEOUT.as_millis() as u64) / 1000
+}
+
+fn ema(current: u64, sample: u64) -> u64 {
+	current.saturating_mul(EMA_DIVISOR - 1).saturating_add(sample) / EMA_DIVISOR
+}
+
+/// Identifies a granted request within the scheduler.
+pub type RequestId = u64;
+
+/// A fetch task asking for permission to send a chunk request.
+pub struct ScheduleRequest {
+	/// The validator the request is going to be sent to.
+	pub peer: AuthorityDiscoveryId,
+	/// Block number at which the candidate times out if it does not become available.
+	pub time_out_at: BlockNumber,
+	/// Encoded size of the request.
+	pub request_size: u64,
+	/// Where to send the permit once granted.
+	pub permit: oneshot::Sender<RequestPermit>,
+}
+
+/// Permission to send a single chunk request.
+///
+/// Dropping the permit without concluding it frees the reserved budget without updating any
+/// estimates.
+pub struct RequestPermit {
+	outcome: oneshot::Sender<RequestOutcome>,
+}
+
+impl RequestPermit {
+	/// Create a new permit, together with the receiver the outcome will be reported to.
+	pub fn new() -> (Self, oneshot::Receiver<RequestOutcome>) {
+		let (outcome, rx) = oneshot::channel();
+		(Self { outcome }, rx)
+	}
+
+	/// Report the outcome of the request.
+	pub fn conclude(self, outcome: RequestOutcome) {
+		let _ = self.outcome.send(outcome);
+	}
+}
+
+/// How a request sent with a permit ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+	/// We received a response of the given encoded size.
+	Received(u64),
+	/// The request failed on the network level or the response could not be decoded.
+	Failed,
+}
+
+/// Throughput and round trip time estimates of a peer, from completed requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerEstimate {
+	/// Bytes per second.
+	pub throughput: u64,
+	/// Time from sending a request until its response arrived.
+	pub rtt: Duration,
+}
+
+impl PeerEstimate {
+	fn new(received: u64, elapsed: Duration) -> Self {
+		let elapsed_ms = elapsed.as_millis().max(1) as u64;
+		Self {
+			throughput: received.saturating_mul(1000) / elapsed_ms,
+			rtt: Duration::from_millis(elapsed_ms),
+		}
+	}
+
+	fn note(&mut self, received: u64, elapsed: Duration) {
+		let sample = Self::new(received, elapsed);
+		self.throughput = ema(self.throughput, sample.throughput);
+		self.rtt =
+			Duration::from_millis(ema(self.rtt.as_millis() as u64, sample.rtt.as_millis() as u64));
+	}
+
+	/// How many bytes the peer is expected to deliver before a request sent now times out.
+	fn capacity(&self) -> u64 {
+		let window = CHUNK_REQUEST_TIMEOUT.saturating_sub(self.rtt);
+		self.throughput.saturating_mul(window.as_millis() as u64) / 1000
+	}
+}
+
+/// A request waiting for a permit.
+struct Queued<T> {
+	time_out_at: BlockNumber,
+	/// Insertion order, requests with the same timeout are served first come first served.
+	seq: u64,
+	peer: AuthorityDiscoveryId,
+	request_size: u64,
+	queued_at: Instant,
+	payload: T,
+}
+
+impl<T> PartialEq for Queued<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<T> Eq for Queued<T> {}
+
+impl<T> PartialOrd for Queued<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T> Ord for Queued<T> {
+	// `BinaryHeap` is a max-heap, so the earliest timeout has to compare greatest.
+	fn cmp(&self, other: &Self) -> Ordering {
+		(other.time_out_at, other.seq).cmp(&(self.time_out_at, self.seq))
+	}
+}
+
+/// A request we handed out a permit for.
+struct InFlight {
+	peer: AuthorityDiscoveryId,
+	expected_size: u64,
+	request_size: u64,
+	started: Instant,
+}
+
+/// Decides which chunk requests may be sent and when.
+///
+/// `T` is whatever needs to be handed back to the requester once a request is granted.
+pub struct RequestScheduler<T> {
+	max_in_flight_down: u64,
+	max_in_flight_up: u64,
+	in_flight_down: u64,
+	in_flight_up: u64,
+	in_flight: HashMap<RequestId, InFlight>,
+	/// Expected response bytes in flight per peer.
+	peer_in_flight: HashMap<AuthorityDiscoveryId, u64>,
+	peers: HashMap<AuthorityDiscoveryId, PeerEstimate>,
+	/// Moving average of the size of received chunks.
+	expected_chunk_size: u64,
+	queue: BinaryHeap<Queued<T>>,
+	next_seq: u64,
+	next_id: RequestId,
+	metrics: Metrics,
+}
+
+impl<T> RequestScheduler<T> {
+	/// Create a new scheduler honouring the given budget.
+	pub fn new(budget: ChunkFetchingBudget, metrics: Metrics) -> Self {
+		Self {
+			max_in_flight_down: max_in_flight(budget.downlink_bytes_per_sec),
+			max_in_flight_up: max_in_flight(budget.uplink_bytes_per_sec),
+			in_flight_down: 0,
+			in_flight_up: 0,
+			in_flight: HashMap::new(),
+			peer_in_flight: HashMap::new(),
+			peers: HashMap::new(),
+			expected_chunk_size: DEFAULT_EXPECTED_CHUNK_SIZE,
+			queue: BinaryHeap::new(),
+			next_seq: 0,
+			next_id: 0,
+			metrics,
+		}
+	}
+
+	/// Queue a request to `peer` for a candidate timing out at `time_out_at`.
+	pub fn enqueue(
+		&mut self,
+		peer: AuthorityDiscoveryId,
+		time_out_at: BlockNumber,
+		request_size: u64,
+		payload: T,
+		now: Instant,
+	) {
+		let seq = self.next_seq;
+		self.next_seq += 1;
+		self.queue
+			.push(Queued { time_out_at, seq, peer, request_size, queued_at: now, payload });
+		self.update_metrics();
+	}
+
+	/// Grant as many queued requests as the budget allows.
+	///
+	/// Requests to peers which are already busy are skipped in favour of later ones, while an
+	/// exhausted overall budget stops granting altogether. A single request is always granted
+	/// if nothing is in flight, so that chunks larger than the budget still get fetched.
+	pub fn grant(&mut self, now: Instant) -> Vec<(RequestId, T)> {
+		let mut granted = Vec::new();
+		let mut peer_busy = Vec::new();
+		let expected_size = self.expected_chunk_size;
+
+		while let Some(next) = self.queue.peek() {
+			let within_budget = self.in_flight_down.saturating_add(expected_size) <=
+				self.max_in_flight_down &&
+				self.in_flight_up.saturating_add(next.request_size) <= self.max_in_flight_up;
+			if !within_budget && !self.in_flight.is_empty() {
+				break
+			}
+
+			let next = self.queue.pop().expect("We just peeked an element; qed");
+			if !self.peer_has_capacity(&next.peer, expected_size) {
+				peer_busy.push(next);
+				continue
+			}
+
+			let id = self.next_id;
+			self.next_id += 1;
+			self.in_flight_down += expected_size;
+			self.in_flight_up += next.request_size;
+			*self.peer_in_flight.entry(next.peer.clone()).or_default() += expected_size;
+			self.in_flight.insert(
+				id,
+				InFlight {
+					peer: next.peer,
+					expected_size,
+					request_size: next.request_size,
+					started: now,
+				},
+			);
+			self.metrics
+				.on_chunk_request_granted(now.saturating_duration_since(next.queued_at));
+			granted.push((id, next.payload));
+		}
+
+		self.queue.extend(peer_busy);
+		self.update_metrics();
+		granted
+	}
+
+	/// A granted request finished, `outcome` is `None` if the request was abandoned.
+	pub fn complete(&mut self, id: RequestId, outcome: Option<RequestOutcome>, now: Instant) {
+		let request = match self.in_flight.remove(&id) {
+			Some(request) => request,
+			None => return,
+		};
+
+		self.in_flight_down -= request.expected_size;
+		self.in_flight_up -= request.request_size;
+		if let Some(bytes) = self.peer_in_flight.get_mut(&request.peer) {
+			*bytes = bytes.saturating_sub(request.expected_size);
+			if *bytes == 0 {
+				self.peer_in_flight.remove(&request.peer);
+			}
+		}
+
+		let elapsed = now.saturating_duration_since(request.started);
+		let received = match outcome {
+			Some(RequestOutcome::Received(received)) => {
+				self.expected_chunk_size = ema(self.expected_chunk_size, received).max(1);
+				received
+			},
+			// A failed request delivered nothing in the time it took.
+			Some(RequestOutcome::Failed) => 0,
+			None => {
+				self.update_metrics();
+				return
+			},
+		};
+		self.peers
+			.entry(request.peer)
+			.and_modify(|estimate| estimate.note(received, elapsed))
+			.or_insert_with(|| PeerEstimate::new(received, elapsed));
+		self.update_metrics();
+	}
+
+	/// Current estimates for the given peer, if we completed any request to it.
+	#[cfg(test)]
+	pub fn peer_estimate(&self, peer: &AuthorityDiscoveryId) -> Option<PeerEstimate> {
+		self.peers.get(peer).copied()
+	}
+
+	/// Number of requests waiting for a permit.
+	#[cfg(test)]
+	pub fn queued(&self) -> usize {
+		self.queue.len()
+	}
+
+	/// Number of granted requests which did not complete yet.
+	#[cfg(test)]
+	pub fn in_flight(&self) -> usize {
+		self.in_flight.len()
+	}
+
+	/// Idle peers are always asked, busy ones only if they are estimated to deliver another
+	/// chunk before the request times out.
+	fn peer_has_capacity(&self, peer: &AuthorityDiscoveryId, expected_size: u64) -> bool {
+		let in_flight = match self.peer_in_flight.get(peer) {
+			Some(in_flight) => *in_flight,
+			None => return true,
+		};
+		match self.peers.get(peer) {
+			Some(estimate) => in_flight.saturating_add(expected_size) <= estimate.capacity(),
+			None => true,
+		}
+	}
+
+	fn update_metrics(&self) {
+		self.metrics
+			.on_scheduler_state(self.queue.len(), self.in_flight_down, self.in_flight_up);
+	}
+}
@@ -16,18 +16,23 @@
 
 use std::collections::HashMap;
 
-use std::{future::Future, sync::Arc};
+use std::{
+	future::Future,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 use futures::FutureExt;
 
-use polkadot_node_network_protocol::jaeger;
+use polkadot_node_network_protocol::{jaeger, request_response::CHUNK_REQUEST_TIMEOUT};
 use polkadot_node_primitives::{BlockData, ErasureChunk, PoV};
 use polkadot_node_subsystem_util::runtime::RuntimeInfo;
 use polkadot_primitives::{
-	BlockNumber, CoreState, GroupIndex, Hash, Id as ParaId, ScheduledCore, SessionIndex,
-	SessionInfo,
+	AuthorityDiscoveryId, BlockNumber, CoreState, GroupIndex, Hash, Id as ParaId, ScheduledCore,
+	SessionIndex, SessionInfo,
 };
 use sp_core::traits::SpawnNamed;
+use sp_keyring::Sr25519Keyring;
 
 use polkadot_node_subsystem::{
 	messages::{
@@ -45,7 +50,10 @@ use sp_core::testing::TaskExecutor;
 
 use crate::tests::mock::{get_valid_chunk_data, make_session_info, OccupiedCoreBuilder};
 
-use super::Requester;
+use super::{
+	scheduler::{ChunkFetchingBudget, PeerEstimate, RequestOutcome, RequestScheduler},
+	Requester,
+};
 
 fn get_erasure_chunk() -> ErasureChunk {
 	let pov = PoV { block_data: BlockData(vec![45, 46, 47]) };
@@ -192,7 +200,7 @@ fn test_harness<T: Future<Output = ()>>(
 #[test]
 fn check_ancestry_lookup_in_same_session() {
 	let test_state = TestState::new();
-	let mut requester = Requester::new(Default::default());
+	let mut requester = Requester::new(Default::default(), Default::default());
 	let keystore = make_ferdie_keystore();
 	let mut runtime = RuntimeInfo::new(Some(keystore));
 
@@ -274,7 +282,7 @@ fn check_ancestry_lookup_in_same_session() {
 #[test]
 fn check_ancestry_lookup_in_different_sessions() {
 	let mut test_state = TestState::new();
-	let mut requester = Requester::new(Default::default());
+	let mut requester = Requester::new(Default::default(), Default::default());
 	let keystore = make_ferdie_keystore();
 	let mut runtime = RuntimeInfo::new(Some(keystore));
 
@@ -341,3 +349,112 @@ fn check_ancestry_lookup_in_different_sessions() {
 		assert_eq!(fetch_tasks.len(), 2.min(Requester::LEAF_ANCESTRY_LEN_WITHIN_SESSION + 1));
 	});
 }
+
+fn peer(keyring: Sr25519Keyring) -> AuthorityDiscoveryId {
+	keyring.public().into()
+}
+
+#[test]
+fn scheduler_grants_closest_timeout_first() {
+	let mut scheduler = RequestScheduler::new(Default::default(), Default::default());
+	let now = Instant::now();
+	scheduler.enqueue(peer(Sr25519Keyring::Alice), 30, 100, 30, now);
+	scheduler.enqueue(peer(Sr25519Keyring::Bob), 10, 100, 10, now);
+	scheduler.enqueue(peer(Sr25519Keyring::Charlie), 20, 100, 20, now);
+
+	let granted: Vec<_> = scheduler.grant(now).into_iter().map(|(_, t)| t).collect();
+	assert_eq!(granted, vec![10, 20, 30]);
+	assert_eq!(scheduler.queued(), 0);
+	assert_eq!(scheduler.in_flight(), 3);
+}
+
+#[test]
+fn scheduler_bounds_bytes_in_flight() {
+	// Room for two chunks of the default expected size:
+	let budget = ChunkFetchingBudget {
+		downlink_bytes_per_sec: 2 * 64 * 1024 * 1000 / CHUNK_REQUEST_TIMEOUT.as_millis() as u64,
+		..Default::default()
+	};
+	let mut scheduler = RequestScheduler::new(budget, Default::default());
+	let now = Instant::now();
+	let peers =
+		[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie, Sr25519Keyring::Dave];
+	for (i, keyring) in peers.into_iter().enumerate() {
+		scheduler.enqueue(peer(keyring), i as BlockNumber, 100, i, now);
+	}
+
+	let granted = scheduler.grant(now);
+	assert_eq!(granted.iter().map(|(_, t)| *t).collect::<Vec<_>>(), vec![0, 1]);
+	assert!(scheduler.grant(now).is_empty());
+	assert_eq!(scheduler.queued(), 2);
+
+	scheduler.complete(granted[0].0, Some(RequestOutcome::Received(64 * 1024)), now);
+	let granted: Vec<_> = scheduler.grant(now).into_iter().map(|(_, t)| t).collect();
+	assert_eq!(granted, vec![2]);
+	assert_eq!(scheduler.queued(), 1);
+}
+
+#[test]
+fn scheduler_always_grants_one_request() {
+	let budget = ChunkFetchingBudget { downlink_bytes_per_sec: 1, uplink_bytes_per_sec: 1 };
+	let mut scheduler = RequestScheduler::new(budget, Default::default());
+	let now = Instant::now();
+	scheduler.enqueue(peer(Sr25519Keyring::Alice), 1, 100, 1, now);
+	scheduler.enqueue(peer(Sr25519Keyring::Bob), 2, 100, 2, now);
+
+	let granted = scheduler.grant(now);
+	assert_eq!(granted.len(), 1);
+	assert!(scheduler.grant(now).is_empty());
+
+	// An abandoned request frees its budget, but yields no estimate:
+	scheduler.complete(granted[0].0, None, now);
+	let granted: Vec<_> = scheduler.grant(now).into_iter().map(|(_, t)| t).collect();
+	assert_eq!(granted, vec![2]);
+	assert_eq!(scheduler.peer_estimate(&peer(Sr25519Keyring::Alice)), None);
+}
+
+#[test]
+fn scheduler_estimates_peer_throughput() {
+	let mut scheduler = RequestScheduler::new(Default::default(), Default::default());
+	let alice = peer(Sr25519Keyring::Alice);
+	let start = Instant::now();
+	scheduler.enqueue(alice.clone(), 1, 100, (), start);
+	let (id, ()) = scheduler.grant(start).pop().unwrap();
+
+	scheduler.complete(
+		id,
+		Some(RequestOutcome::Received(100_000)),
+		start + Duration::from_millis(100),
+	);
+	assert_eq!(
+		scheduler.peer_estimate(&alice),
+		Some(PeerEstimate { throughput: 1_000_000, rtt: Duration::from_millis(100) })
+	);
+	assert_eq!(scheduler.in_flight(), 0);
+}
+
+#[test]
+fn scheduler_skips_slow_busy_peers() {
+	let mut scheduler = RequestScheduler::new(Default::default(), Default::default());
+	let alice = peer(Sr25519Keyring::Alice);
+	let start = Instant::now();
+
+	// Alice turns out to be slow:
+	scheduler.enqueue(alice.clone(), 1, 100, 0, start);
+	let (id, _) = scheduler.grant(start).pop().unwrap();
+	scheduler.complete(
+		id,
+		Some(RequestOutcome::Received(1000)),
+		start + Duration::from_millis(900),
+	);
+
+	let now = start + Duration::from_secs(1);
+	scheduler.enqueue(alice.clone(), 1, 100, 1, now);
+	scheduler.enqueue(alice, 2, 100, 2, now);
+	scheduler.enqueue(peer(Sr25519Keyring::Bob), 3, 100, 3, now);
+
+	// The second request to Alice waits, even though it is more urgent than the one to Bob:
+	let granted: Vec<_> = scheduler.grant(now).into_iter().map(|(_, t)| t).collect();
+	assert_eq!(granted, vec![1, 3]);
+	assert_eq!(scheduler.queued(), 1);
+}
@@ -22,7 +22,7 @@ use futures::channel::oneshot;
 
 use fatality::Nested;
 use polkadot_node_network_protocol::{
-	request_response::{v1, IncomingRequest, IncomingRequestReceiver},
+	request_response::{v1, IncomingRequest, QuotaReceiver},
 	UnifiedReputationChange as Rep,
 };
 use polkadot_node_primitives::{AvailableData, ErasureChunk};
@@ -40,7 +40,7 @@ const COST_INVALID_REQUEST: Rep = Rep::CostMajor("Received message could not be
 /// Receiver task to be forked as a separate task to handle PoV requests.
 pub async fn run_pov_receiver<Sender>(
 	mut sender: Sender,
-	mut receiver: IncomingRequestReceiver<v1::PoVFetchingRequest>,
+	mut receiver: QuotaReceiver<v1::PoVFetchingRequest>,
 	metrics: Metrics,
 ) where
 	Sender: SubsystemSender<AvailabilityStoreMessage>,
@@ -59,7 +59,7 @@ pub async fn run_pov_receiver<Sender>(
 				return
 			},
 			Ok(Err(jfyi)) => {
-				gum::debug!(target: LOG_TARGET, error = ?jfyi, "Error decoding incoming PoV request.");
+				gum::debug!(target: LOG_TARGET, error = ?jfyi, "Error receiving incoming PoV request.");
 			},
 		}
 	}
@@ -68,7 +68,7 @@ pub async fn run_pov_receiver<Sender>(
 /// Receiver task to be forked as a separate task to handle chunk requests.
 pub async fn run_chunk_receiver<Sender>(
 	mut sender: Sender,
-	mut receiver: IncomingRequestReceiver<v1::ChunkFetchingRequest>,
+	mut receiver: QuotaReceiver<v1::ChunkFetchingRequest>,
 	metrics: Metrics,
 ) where
 	Sender: SubsystemSender<AvailabilityStoreMessage>,
@@ -90,7 +90,7 @@ pub async fn run_chunk_receiver<Sender>(
 				gum::debug!(
 					target: LOG_TARGET,
 					error = ?jfyi,
-					"Error decoding incoming chunk request."
+					"Error receiving incoming chunk request."
 				);
 			},
 		}
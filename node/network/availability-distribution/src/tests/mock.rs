@@ -16,16 +16,19 @@
 
 //! Helper functions and tools to generate mock data useful for testing this subsystem.
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
+use async_trait::async_trait;
 use sp_keyring::Sr25519Keyring;
 
+use polkadot_node_network_protocol::{authority_discovery::AuthorityDiscovery, PeerId};
+
 use polkadot_erasure_coding::{branches, obtain_chunks_v1 as obtain_chunks};
 use polkadot_node_primitives::{AvailableData, BlockData, ErasureChunk, PoV, Proof};
 use polkadot_primitives::{
-	CandidateCommitments, CandidateDescriptor, CandidateHash, CommittedCandidateReceipt,
-	GroupIndex, Hash, HeadData, Id as ParaId, IndexedVec, OccupiedCore, PersistedValidationData,
-	SessionInfo, ValidatorIndex,
+	AuthorityDiscoveryId, CandidateCommitments, CandidateDescriptor, CandidateHash,
+	CommittedCandidateReceipt, GroupIndex, Hash, HeadData, Id as ParaId, IndexedVec, OccupiedCore,
+	PersistedValidationData, SessionInfo, ValidatorIndex,
 };
 use polkadot_primitives_test_helpers::{
 	dummy_collator, dummy_collator_signature, dummy_hash, dummy_validation_code,
@@ -156,3 +159,24 @@ pub fn get_valid_chunk_data(pov: PoV) -> (Hash, ErasureChunk) {
 		.expect("There really should be 10 chunks.");
 	(root, chunk)
 }
+
+/// Authority discovery considering every peer an authority.
+#[derive(Debug)]
+pub struct AllAuthorities;
+
+#[async_trait]
+impl AuthorityDiscovery for AllAuthorities {
+	async fn get_addresses_by_authority_id(
+		&mut self,
+		_: AuthorityDiscoveryId,
+	) -> Option<HashSet<sc_network::Multiaddr>> {
+		None
+	}
+
+	async fn get_authority_ids_by_peer_id(
+		&mut self,
+		_: PeerId,
+	) -> Option<HashSet<AuthorityDiscoveryId>> {
+		Some(HashSet::from([Sr25519Keyring::Alice.public().into()]))
+	}
+}
@@ -18,7 +18,9 @@ use std::collections::HashSet;
 
 use futures::{executor, future, Future};
 
-use polkadot_node_network_protocol::request_response::{IncomingRequest, ReqProtocolNames};
+use polkadot_node_network_protocol::request_response::{
+	IncomingRequest, QuotaReceiver, ReqProtocolNames,
+};
 use polkadot_primitives::{CoreState, Hash};
 use sp_keystore::KeystorePtr;
 
@@ -36,6 +38,14 @@ pub(crate) mod mock;
 fn test_harness<T: Future<Output = ()>>(
 	keystore: KeystorePtr,
 	test_fx: impl FnOnce(TestHarness) -> T,
+) {
+	test_harness_with_budget(keystore, Default::default(), test_fx)
+}
+
+fn test_harness_with_budget<T: Future<Output = ()>>(
+	keystore: KeystorePtr,
+	budget: ChunkFetchingBudget,
+	test_fx: impl FnOnce(TestHarness) -> T,
 ) {
 	sp_tracing::try_init_simple();
 
@@ -45,11 +55,16 @@ fn test_harness<T: Future<Output = ()>>(
 	let req_protocol_names = ReqProtocolNames::new(&genesis_hash, None);
 
 	let (pov_req_receiver, pov_req_cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
+	let pov_req_receiver =
+		QuotaReceiver::with_default_quota(pov_req_receiver, Box::new(mock::AllAuthorities));
 	let (chunk_req_receiver, chunk_req_cfg) =
 		IncomingRequest::get_config_receiver(&req_protocol_names);
+	let chunk_req_receiver =
+		QuotaReceiver::with_default_quota(chunk_req_receiver, Box::new(mock::AllAuthorities));
 	let subsystem = AvailabilityDistributionSubsystem::new(
 		keystore,
 		IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver },
+		budget,
 		Default::default(),
 	);
 	let subsystem = subsystem.run(context);
@@ -123,3 +138,17 @@ fn check_fetch_retry() {
 	}
 	test_harness(state.keystore.clone(), move |harness| state.run(harness));
 }
+
+/// Check that a budget for only a single chunk at a time still gets all chunks fetched, one
+/// request after the other.
+#[test]
+fn check_fetch_with_tight_budget() {
+	let mut state = TestState::default();
+	state.max_in_flight_requests = Some(1);
+	for (_, v) in state.chunks.iter_mut() {
+		// Make sure retries are scheduled as well:
+		v.push(None);
+	}
+	let budget = ChunkFetchingBudget { downlink_bytes_per_sec: 1, uplink_bytes_per_sec: 1 };
+	test_harness_with_budget(state.keystore.clone(), budget, move |harness| state.run(harness));
+}
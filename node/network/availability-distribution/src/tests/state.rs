@@ -16,7 +16,10 @@
 
 use std::{
 	collections::{HashMap, HashSet},
-	sync::Arc,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
 	time::Duration,
 };
 
@@ -84,6 +87,8 @@ pub struct TestState {
 	/// Cores per relay chain block.
 	pub cores: HashMap<Hash, Vec<CoreState>>,
 	pub keystore: KeystorePtr,
+	/// If set, the test fails if more chunk requests than this are awaiting a response at once.
+	pub max_in_flight_requests: Option<usize>,
 }
 
 impl Default for TestState {
@@ -146,6 +151,7 @@ impl Default for TestState {
 			session_info,
 			cores,
 			keystore,
+			max_in_flight_requests: None,
 		}
 	}
 }
@@ -191,6 +197,9 @@ impl TestState {
 		// Test will fail if this does not happen until timeout.
 		let mut remaining_stores = self.valid_chunks.len();
 
+		// Chunk requests we have not answered yet:
+		let in_flight_requests = Arc::new(AtomicUsize::new(0));
+
 		let TestSubsystemContextHandle { tx, mut rx } = harness.virtual_overseer;
 
 		// Spawning necessary as incoming queue can only hold a single item, we don't want to dead
@@ -219,8 +228,13 @@ impl TestState {
 					IfDisconnected::ImmediateError,
 				)) => {
 					for req in reqs {
+						let in_flight = in_flight_requests.fetch_add(1, Ordering::SeqCst) + 1;
+						if let Some(max) = self.max_in_flight_requests {
+							assert!(in_flight <= max, "More chunk requests in flight than allowed");
+						}
 						// Forward requests:
-						let in_req = to_incoming_req(&harness.pool, req);
+						let in_req =
+							to_incoming_req(&harness.pool, req, in_flight_requests.clone());
 						harness
 							.chunk_req_cfg
 							.inbound_queue
@@ -313,6 +327,7 @@ async fn overseer_recv(rx: &mut mpsc::UnboundedReceiver<AllMessages>) -> AllMess
 fn to_incoming_req(
 	executor: &TaskExecutor,
 	outgoing: Requests,
+	in_flight_requests: Arc<AtomicUsize>,
 ) -> IncomingRequest<v1::ChunkFetchingRequest> {
 	match outgoing {
 		Requests::ChunkFetchingV1(OutgoingRequest { payload, pending_response, .. }) => {
@@ -324,6 +339,7 @@ fn to_incoming_req(
 				async {
 					let response = rx.await;
 					let payload = response.expect("Unexpected canceled request").result;
+					in_flight_requests.fetch_sub(1, Ordering::SeqCst);
 					pending_response
 						.send(payload.map_err(|_| network::RequestFailure::Refused))
 						.expect("Sending response is expected to work");
@@ -40,14 +40,15 @@ use rand::seq::SliceRandom;
 
 use fatality::Nested;
 use polkadot_erasure_coding::{
-	branch_hash, branches, obtain_chunks_v1, recovery_threshold, Error as ErasureEncodingError,
+	branch_hash, branches, obtain_chunks_v1, recovery_threshold, systematic_recovery_threshold,
+	Error as ErasureEncodingError,
 };
 #[cfg(not(test))]
 use polkadot_node_network_protocol::request_response::CHUNK_REQUEST_TIMEOUT;
 use polkadot_node_network_protocol::{
 	request_response::{
-		self as req_res, outgoing::RequestError, v1 as request_v1, IncomingRequestReceiver,
-		OutgoingRequest, Recipient, Requests,
+		self as req_res, outgoing::RequestError, v1 as request_v1, OutgoingRequest, QuotaReceiver,
+		Recipient, Requests,
 	},
 	IfDisconnected, UnifiedReputationChange as Rep,
 };
@@ -59,7 +60,9 @@ use polkadot_node_subsystem::{
 	overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError,
 	SubsystemResult,
 };
-use polkadot_node_subsystem_util::request_session_info;
+use polkadot_node_subsystem_util::{
+	metrics::prometheus::prometheus::HistogramTimer, request_session_info,
+};
 use polkadot_primitives::{
 	AuthorityDiscoveryId, BlakeTwo256, BlockNumber, CandidateHash, CandidateReceipt, GroupIndex,
 	Hash, HashT, IndexedVec, SessionIndex, SessionInfo, ValidatorId, ValidatorIndex,
@@ -115,6 +118,12 @@ pub enum RecoveryStrategy {
 	BackersFirstIfSizeLower(usize),
 	/// We always recover using validator chunks.
 	ChunksAlways,
+	/// We first try to recover from the systematic chunks, which does not require any decoding,
+	/// then fallback to any validator chunks.
+	SystematicChunksFirst,
+	/// We try the backing group first if PoV size is lower than specified, then fallback to the
+	/// systematic chunks and finally to any validator chunks.
+	BackersFirstIfSizeLowerThenSystematicChunks(usize),
 	/// Do not request data from the availability store.
 	/// This is the useful for nodes where the
 	/// availability-store subsystem is not expected to run,
@@ -126,8 +135,18 @@ impl RecoveryStrategy {
 	/// Returns true if the strategy needs backing group index.
 	pub fn needs_backing_group(&self) -> bool {
 		match self {
-			RecoveryStrategy::BackersFirstAlways | RecoveryStrategy::BackersFirstIfSizeLower(_) =>
-				true,
+			RecoveryStrategy::BackersFirstAlways |
+			RecoveryStrategy::BackersFirstIfSizeLower(_) |
+			RecoveryStrategy::BackersFirstIfSizeLowerThenSystematicChunks(_) => true,
+			_ => false,
+		}
+	}
+
+	/// Returns true if the strategy recovers from the systematic chunks before trying any chunks.
+	pub fn prefers_systematic_chunks(&self) -> bool {
+		match self {
+			RecoveryStrategy::SystematicChunksFirst |
+			RecoveryStrategy::BackersFirstIfSizeLowerThenSystematicChunks(_) => true,
 			_ => false,
 		}
 	}
@@ -135,7 +154,8 @@ impl RecoveryStrategy {
 	/// Returns the PoV size limit in bytes for `BackersFirstIfSizeLower` strategy, otherwise `None`.
 	pub fn pov_size_limit(&self) -> Option<usize> {
 		match *self {
-			RecoveryStrategy::BackersFirstIfSizeLower(limit) => Some(limit),
+			RecoveryStrategy::BackersFirstIfSizeLower(limit) |
+			RecoveryStrategy::BackersFirstIfSizeLowerThenSystematicChunks(limit) => Some(limit),
 			_ => None,
 		}
 	}
@@ -145,7 +165,7 @@ pub struct AvailabilityRecoverySubsystem {
 	/// PoV recovery strategy to use.
 	recovery_strategy: RecoveryStrategy,
 	/// Receiver for available data requests.
-	req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+	req_receiver: QuotaReceiver<request_v1::AvailableDataFetchingRequest>,
 	/// Metrics for this subsystem.
 	metrics: Metrics,
 }
@@ -158,6 +178,22 @@ struct RequestFromBackers {
 	erasure_task_tx: futures::channel::mpsc::Sender<ErasureTask>,
 }
 
+struct FetchSystematicChunks {
+	/// The number of systematic chunks, which are held by the validators `0..threshold`.
+	threshold: usize,
+	/// The validators holding a systematic chunk which have not been requested yet, in reverse
+	/// order.
+	unrequested: Vec<ValidatorIndex>,
+	/// Systematic chunks received so far.
+	received_chunks: HashMap<ValidatorIndex, ErasureChunk>,
+	/// Whether any systematic chunk could not be fetched.
+	failed: bool,
+	/// Pending chunk requests with soft timeout.
+	requesting_chunks: FuturesUndead<Result<Option<ErasureChunk>, (ValidatorIndex, RequestError)>>,
+	// channel to the erasure task handler.
+	erasure_task_tx: futures::channel::mpsc::Sender<ErasureTask>,
+}
+
 struct RequestChunksFromValidators {
 	/// How many request have been unsuccessful so far.
 	error_count: usize,
@@ -186,6 +222,9 @@ struct RecoveryParams {
 	/// The number of pieces needed.
 	threshold: usize,
 
+	/// The number of systematic chunks needed, if they are tried before any chunks.
+	systematic_threshold: Option<usize>,
+
 	/// A hash of the relevant candidate.
 	candidate_hash: CandidateHash,
 
@@ -204,6 +243,7 @@ struct RecoveryParams {
 /// backers (a.k.a. fast-path), or recover from chunks.
 enum Source {
 	RequestFromBackers(RequestFromBackers),
+	FetchSystematicChunks(FetchSystematicChunks),
 	RequestChunks(RequestChunksFromValidators),
 }
 
@@ -215,6 +255,12 @@ enum ErasureTask {
 		HashMap<ValidatorIndex, ErasureChunk>,
 		oneshot::Sender<Result<AvailableData, ErasureEncodingError>>,
 	),
+	/// Reconstructs `AvailableData` from the systematic chunks, in order, given `n_validators`.
+	ReconstructFromSystematic(
+		usize,
+		Vec<ErasureChunk>,
+		oneshot::Sender<Result<AvailableData, ErasureEncodingError>>,
+	),
 	/// Re-encode `AvailableData` into erasure chunks in order to verify the provided root hash of the Merkle tree.
 	Reencode(usize, Hash, AvailableData, oneshot::Sender<Option<AvailableData>>),
 }
@@ -324,6 +370,215 @@ impl RequestFromBackers {
 	}
 }
 
+impl FetchSystematicChunks {
+	fn new(threshold: usize, erasure_task_tx: futures::channel::mpsc::Sender<ErasureTask>) -> Self {
+		FetchSystematicChunks {
+			threshold,
+			unrequested: (0..threshold as u32).rev().map(ValidatorIndex).collect(),
+			received_chunks: HashMap::new(),
+			failed: false,
+			requesting_chunks: FuturesUndead::new(),
+			erasure_task_tx,
+		}
+	}
+
+	/// Continue with requesting any chunks, starting from the systematic chunks received so far.
+	fn fallback(&mut self, n_validators: u32) -> RequestChunksFromValidators {
+		let mut from_all =
+			RequestChunksFromValidators::new(n_validators, self.erasure_task_tx.clone());
+		from_all.received_chunks = std::mem::take(&mut self.received_chunks);
+		let received_chunks = &from_all.received_chunks;
+		from_all.shuffling.retain(|i| !received_chunks.contains_key(i));
+		from_all
+	}
+
+	/// Request the systematic chunks which are not requested yet, while not exceeding
+	/// `N_PARALLEL` requests in flight.
+	async fn launch_parallel_requests<Sender>(
+		&mut self,
+		params: &RecoveryParams,
+		sender: &mut Sender,
+	) where
+		Sender: overseer::AvailabilityRecoverySenderTrait,
+	{
+		let mut requests = Vec::new();
+		while self.requesting_chunks.len() < N_PARALLEL {
+			match self.unrequested.pop() {
+				Some(validator_index) => requests.push(request_chunk(
+					params,
+					validator_index,
+					&mut self.requesting_chunks,
+				)),
+				None => break,
+			}
+		}
+
+		if requests.is_empty() {
+			return
+		}
+
+		gum::debug!(
+			target: LOG_TARGET,
+			candidate_hash = ?params.candidate_hash,
+			num_requests = requests.len(),
+			threshold = self.threshold,
+			"Requesting systematic chunks for a candidate",
+		);
+
+		sender
+			.send_message(NetworkBridgeTxMessage::SendRequests(
+				requests,
+				IfDisconnected::TryConnect,
+			))
+			.await;
+	}
+
+	/// Wait until either all systematic chunks are received, one of them could not be fetched,
+	/// or some requests time out.
+	async fn wait_for_chunks(&mut self, params: &RecoveryParams) {
+		let metrics = &params.metrics;
+
+		while let Some(request_result) =
+			self.requesting_chunks.next_with_timeout(TIMEOUT_START_NEW_REQUESTS).await
+		{
+			match request_result {
+				Ok(Some(chunk)) =>
+					if is_chunk_valid(params, &chunk) {
+						metrics.on_chunk_request_succeeded();
+						gum::trace!(
+							target: LOG_TARGET,
+							candidate_hash = ?params.candidate_hash,
+							validator_index = ?chunk.index,
+							"Received valid systematic chunk",
+						);
+						self.received_chunks.insert(chunk.index, chunk);
+					} else {
+						metrics.on_chunk_request_invalid();
+						self.failed = true;
+					},
+				Ok(None) => {
+					metrics.on_chunk_request_no_such_chunk();
+					self.failed = true;
+				},
+				Err((validator_index, e)) => {
+					gum::trace!(
+						target: LOG_TARGET,
+						candidate_hash= ?params.candidate_hash,
+						err = ?e,
+						?validator_index,
+						"Failure requesting systematic chunk",
+					);
+
+					match e {
+						RequestError::InvalidResponse(_) => metrics.on_chunk_request_invalid(),
+						RequestError::NetworkError(RequestFailure::Network(
+							OutboundFailure::Timeout,
+						)) => metrics.on_chunk_request_timeout(),
+						RequestError::NetworkError(_) | RequestError::Canceled(_) =>
+							metrics.on_chunk_request_error(),
+					}
+					self.failed = true;
+				},
+			}
+
+			if self.failed || self.received_chunks.len() >= self.threshold {
+				break
+			}
+		}
+	}
+
+	async fn run<Sender>(
+		&mut self,
+		params: &RecoveryParams,
+		sender: &mut Sender,
+	) -> Result<AvailableData, RecoveryError>
+	where
+		Sender: overseer::AvailabilityRecoverySenderTrait,
+	{
+		let metrics = &params.metrics;
+
+		// First query the store for any chunks we've got.
+		if !params.bypass_availability_store {
+			let (tx, rx) = oneshot::channel();
+			sender
+				.send_message(AvailabilityStoreMessage::QueryAllChunks(params.candidate_hash, tx))
+				.await;
+
+			match rx.await {
+				Ok(chunks) =>
+					for chunk in chunks {
+						if (chunk.index.0 as usize) < self.threshold &&
+							is_chunk_valid(params, &chunk)
+						{
+							self.unrequested.retain(|i| *i != chunk.index);
+							self.received_chunks.insert(chunk.index, chunk);
+						}
+					},
+				Err(oneshot::Canceled) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						candidate_hash = ?params.candidate_hash,
+						"Failed to reach the availability store"
+					);
+				},
+			}
+		}
+
+		let recovery_timer = metrics.time_full_recovery();
+
+		loop {
+			if self.received_chunks.len() >= self.threshold {
+				let recovery_duration = metrics.time_erasure_recovery();
+
+				let mut chunks: Vec<_> =
+					std::mem::take(&mut self.received_chunks).into_values().collect();
+				chunks.sort_by_key(|chunk| chunk.index);
+
+				// Send request to reconstruct available data from the systematic chunks.
+				let (available_data_tx, available_data_rx) = channel();
+				self.erasure_task_tx
+					.send(ErasureTask::ReconstructFromSystematic(
+						params.validators.len(),
+						chunks,
+						available_data_tx,
+					))
+					.await
+					.map_err(|_| RecoveryError::ChannelClosed)?;
+
+				let available_data_response =
+					available_data_rx.await.map_err(|_| RecoveryError::ChannelClosed)?;
+
+				return conclude_reconstruction(
+					params,
+					&mut self.erasure_task_tx,
+					recovery_duration,
+					available_data_response,
+				)
+				.await
+			}
+
+			if self.failed ||
+				(self.unrequested.is_empty() && self.requesting_chunks.total_len() == 0)
+			{
+				gum::debug!(
+					target: LOG_TARGET,
+					candidate_hash = ?params.candidate_hash,
+					received = self.received_chunks.len(),
+					threshold = self.threshold,
+					"Recovery from systematic chunks is not possible, falling back to any chunks",
+				);
+				// The full recovery is timed again by the next phase.
+				recovery_timer.map(|timer| timer.stop_and_discard());
+
+				return Err(RecoveryError::Unavailable)
+			}
+
+			self.launch_parallel_requests(params, sender).await;
+			self.wait_for_chunks(params).await;
+		}
+	}
+}
+
 impl RequestChunksFromValidators {
 	fn new(
 		n_validators: u32,
@@ -413,36 +668,7 @@ impl RequestChunksFromValidators {
 
 		while self.requesting_chunks.len() < num_requests {
 			if let Some(validator_index) = self.shuffling.pop_back() {
-				let validator = params.validator_authority_keys[validator_index.0 as usize].clone();
-				gum::trace!(
-					target: LOG_TARGET,
-					?validator,
-					?validator_index,
-					?candidate_hash,
-					"Requesting chunk",
-				);
-
-				// Request data.
-				let raw_request = req_res::v1::ChunkFetchingRequest {
-					candidate_hash: params.candidate_hash,
-					index: validator_index,
-				};
-
-				let (req, res) = OutgoingRequest::new(Recipient::Authority(validator), raw_request);
-				requests.push(Requests::ChunkFetchingV1(req));
-
-				params.metrics.on_chunk_request_issued();
-				let timer = params.metrics.time_chunk_request();
-
-				self.requesting_chunks.push(Box::pin(async move {
-					let _timer = timer;
-					match res.await {
-						Ok(req_res::v1::ChunkFetchingResponse::Chunk(chunk)) =>
-							Ok(Some(chunk.recombine_into_chunk(&raw_request))),
-						Ok(req_res::v1::ChunkFetchingResponse::NoSuchChunk) => Ok(None),
-						Err(e) => Err((validator_index, e)),
-					}
-				}));
+				requests.push(request_chunk(params, validator_index, &mut self.requesting_chunks));
 			} else {
 				break
 			}
@@ -640,62 +866,126 @@ impl RequestChunksFromValidators {
 				let available_data_response =
 					available_data_rx.await.map_err(|_| RecoveryError::ChannelClosed)?;
 
-				return match available_data_response {
-					Ok(data) => {
-						// Send request to re-encode the chunks and check merkle root.
-						let (reencode_tx, reencode_rx) = channel();
-						self.erasure_task_tx
-							.send(ErasureTask::Reencode(
-								params.validators.len(),
-								params.erasure_root,
-								data,
-								reencode_tx,
-							))
-							.await
-							.map_err(|_| RecoveryError::ChannelClosed)?;
-
-						let reencode_response =
-							reencode_rx.await.map_err(|_| RecoveryError::ChannelClosed)?;
-
-						if let Some(data) = reencode_response {
-							gum::trace!(
-								target: LOG_TARGET,
-								candidate_hash = ?params.candidate_hash,
-								erasure_root = ?params.erasure_root,
-								"Data recovery complete",
-							);
-							metrics.on_recovery_succeeded();
+				return conclude_reconstruction(
+					params,
+					&mut self.erasure_task_tx,
+					recovery_duration,
+					available_data_response,
+				)
+				.await
+			}
+		}
+	}
+}
 
-							Ok(data)
-						} else {
-							recovery_duration.map(|rd| rd.stop_and_discard());
-							gum::trace!(
-								target: LOG_TARGET,
-								candidate_hash = ?params.candidate_hash,
-								erasure_root = ?params.erasure_root,
-								"Data recovery - root mismatch",
-							);
-							metrics.on_recovery_invalid();
+/// Issues a request for the chunk of the given validator.
+///
+/// The pending response is pushed to `requesting_chunks`, while the returned request still needs
+/// to be sent to the network bridge.
+fn request_chunk(
+	params: &RecoveryParams,
+	validator_index: ValidatorIndex,
+	requesting_chunks: &mut FuturesUndead<
+		Result<Option<ErasureChunk>, (ValidatorIndex, RequestError)>,
+	>,
+) -> Requests {
+	let validator = params.validator_authority_keys[validator_index.0 as usize].clone();
+	gum::trace!(
+		target: LOG_TARGET,
+		?validator,
+		?validator_index,
+		candidate_hash = ?params.candidate_hash,
+		"Requesting chunk",
+	);
+
+	// Request data.
+	let raw_request = req_res::v1::ChunkFetchingRequest {
+		candidate_hash: params.candidate_hash,
+		index: validator_index,
+	};
 
-							Err(RecoveryError::Invalid)
-						}
-					},
-					Err(err) => {
-						recovery_duration.map(|rd| rd.stop_and_discard());
-						gum::trace!(
-							target: LOG_TARGET,
-							candidate_hash = ?params.candidate_hash,
-							erasure_root = ?params.erasure_root,
-							?err,
-							"Data recovery error ",
-						);
-						metrics.on_recovery_invalid();
+	let (req, res) = OutgoingRequest::new(Recipient::Authority(validator), raw_request);
 
-						Err(RecoveryError::Invalid)
-					},
-				}
-			}
+	params.metrics.on_chunk_request_issued();
+	let timer = params.metrics.time_chunk_request();
+
+	requesting_chunks.push(Box::pin(async move {
+		let _timer = timer;
+		match res.await {
+			Ok(req_res::v1::ChunkFetchingResponse::Chunk(chunk)) =>
+				Ok(Some(chunk.recombine_into_chunk(&raw_request))),
+			Ok(req_res::v1::ChunkFetchingResponse::NoSuchChunk) => Ok(None),
+			Err(e) => Err((validator_index, e)),
 		}
+	}));
+
+	Requests::ChunkFetchingV1(req)
+}
+
+/// Conclude the recovery from the data reconstructed from chunks.
+///
+/// The data is re-encoded in order to check it against the erasure root, see
+/// [`reconstructed_data_matches_root`].
+async fn conclude_reconstruction(
+	params: &RecoveryParams,
+	erasure_task_tx: &mut futures::channel::mpsc::Sender<ErasureTask>,
+	recovery_duration: Option<HistogramTimer>,
+	available_data_response: Result<AvailableData, ErasureEncodingError>,
+) -> Result<AvailableData, RecoveryError> {
+	let metrics = &params.metrics;
+
+	match available_data_response {
+		Ok(data) => {
+			// Send request to re-encode the chunks and check merkle root.
+			let (reencode_tx, reencode_rx) = channel();
+			erasure_task_tx
+				.send(ErasureTask::Reencode(
+					params.validators.len(),
+					params.erasure_root,
+					data,
+					reencode_tx,
+				))
+				.await
+				.map_err(|_| RecoveryError::ChannelClosed)?;
+
+			let reencode_response = reencode_rx.await.map_err(|_| RecoveryError::ChannelClosed)?;
+
+			if let Some(data) = reencode_response {
+				gum::trace!(
+					target: LOG_TARGET,
+					candidate_hash = ?params.candidate_hash,
+					erasure_root = ?params.erasure_root,
+					"Data recovery complete",
+				);
+				metrics.on_recovery_succeeded();
+
+				Ok(data)
+			} else {
+				recovery_duration.map(|rd| rd.stop_and_discard());
+				gum::trace!(
+					target: LOG_TARGET,
+					candidate_hash = ?params.candidate_hash,
+					erasure_root = ?params.erasure_root,
+					"Data recovery - root mismatch",
+				);
+				metrics.on_recovery_invalid();
+
+				Err(RecoveryError::Invalid)
+			}
+		},
+		Err(err) => {
+			recovery_duration.map(|rd| rd.stop_and_discard());
+			gum::trace!(
+				target: LOG_TARGET,
+				candidate_hash = ?params.candidate_hash,
+				erasure_root = ?params.erasure_root,
+				?err,
+				"Data recovery error ",
+			);
+			metrics.on_recovery_invalid();
+
+			Err(RecoveryError::Invalid)
+		},
 	}
 }
 
@@ -818,10 +1108,29 @@ where
 						Err(RecoveryError::ChannelClosed) =>
 							break Err(RecoveryError::ChannelClosed),
 						Err(RecoveryError::Unavailable) =>
-							self.source = Source::RequestChunks(RequestChunksFromValidators::new(
-								self.params.validators.len() as _,
-								self.erasure_task_tx.clone(),
-							)),
+							self.source = match self.params.systematic_threshold {
+								Some(systematic_threshold) =>
+									Source::FetchSystematicChunks(FetchSystematicChunks::new(
+										systematic_threshold,
+										self.erasure_task_tx.clone(),
+									)),
+								None => Source::RequestChunks(RequestChunksFromValidators::new(
+									self.params.validators.len() as _,
+									self.erasure_task_tx.clone(),
+								)),
+							},
+					}
+				},
+				Source::FetchSystematicChunks(ref mut systematic) => {
+					match systematic.run(&self.params, &mut self.sender).await {
+						Ok(data) => break Ok(data),
+						Err(RecoveryError::Invalid) => break Err(RecoveryError::Invalid),
+						Err(RecoveryError::ChannelClosed) =>
+							break Err(RecoveryError::ChannelClosed),
+						Err(RecoveryError::Unavailable) =>
+							self.source = Source::RequestChunks(
+								systematic.fallback(self.params.validators.len() as _),
+							),
 					}
 				},
 				Source::RequestChunks(ref mut from_all) =>
@@ -980,10 +1289,16 @@ async fn launch_recovery_task<Context>(
 	erasure_task_tx: futures::channel::mpsc::Sender<ErasureTask>,
 ) -> error::Result<()> {
 	let candidate_hash = receipt.hash();
+	let systematic_threshold = if recovery_strategy.prefers_systematic_chunks() {
+		Some(systematic_recovery_threshold(session_info.validators.len())?)
+	} else {
+		None
+	};
 	let params = RecoveryParams {
 		validator_authority_keys: session_info.discovery_keys.clone(),
 		validators: session_info.validators.clone(),
 		threshold: recovery_threshold(session_info.validators.len())?,
+		systematic_threshold,
 		candidate_hash,
 		erasure_root: receipt.descriptor.erasure_root,
 		metrics: metrics.clone(),
@@ -1022,11 +1337,14 @@ async fn launch_recovery_task<Context>(
 				erasure_task_tx.clone(),
 			))
 		})
-		.unwrap_or_else(|| {
-			Source::RequestChunks(RequestChunksFromValidators::new(
+		.unwrap_or_else(|| match params.systematic_threshold {
+			Some(systematic_threshold) => Source::FetchSystematicChunks(
+				FetchSystematicChunks::new(systematic_threshold, erasure_task_tx.clone()),
+			),
+			None => Source::RequestChunks(RequestChunksFromValidators::new(
 				params.validators.len() as _,
 				erasure_task_tx.clone(),
-			))
+			)),
 		});
 
 	let recovery_task =
@@ -1151,7 +1469,7 @@ impl AvailabilityRecoverySubsystem {
 	/// Create a new instance of `AvailabilityRecoverySubsystem` which never requests the  
 	/// `AvailabilityStoreSubsystem` subsystem.
 	pub fn with_availability_store_skip(
-		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+		req_receiver: QuotaReceiver<request_v1::AvailableDataFetchingRequest>,
 		metrics: Metrics,
 	) -> Self {
 		Self { recovery_strategy: RecoveryStrategy::BypassAvailabilityStore, req_receiver, metrics }
@@ -1160,7 +1478,7 @@ impl AvailabilityRecoverySubsystem {
 	/// Create a new instance of `AvailabilityRecoverySubsystem` which starts with a fast path to
 	/// request data from backers.
 	pub fn with_fast_path(
-		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+		req_receiver: QuotaReceiver<request_v1::AvailableDataFetchingRequest>,
 		metrics: Metrics,
 	) -> Self {
 		Self { recovery_strategy: RecoveryStrategy::BackersFirstAlways, req_receiver, metrics }
@@ -1168,16 +1486,25 @@ impl AvailabilityRecoverySubsystem {
 
 	/// Create a new instance of `AvailabilityRecoverySubsystem` which requests only chunks
 	pub fn with_chunks_only(
-		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+		req_receiver: QuotaReceiver<request_v1::AvailableDataFetchingRequest>,
 		metrics: Metrics,
 	) -> Self {
 		Self { recovery_strategy: RecoveryStrategy::ChunksAlways, req_receiver, metrics }
 	}
 
+	/// Create a new instance of `AvailabilityRecoverySubsystem` which requests the systematic chunks
+	/// first, and any chunks if that fails.
+	pub fn with_systematic_chunks(
+		req_receiver: QuotaReceiver<request_v1::AvailableDataFetchingRequest>,
+		metrics: Metrics,
+	) -> Self {
+		Self { recovery_strategy: RecoveryStrategy::SystematicChunksFirst, req_receiver, metrics }
+	}
+
 	/// Create a new instance of `AvailabilityRecoverySubsystem` which requests chunks if PoV is
 	/// above a threshold.
 	pub fn with_chunks_if_pov_large(
-		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+		req_receiver: QuotaReceiver<request_v1::AvailableDataFetchingRequest>,
 		metrics: Metrics,
 	) -> Self {
 		Self {
@@ -1187,6 +1514,21 @@ impl AvailabilityRecoverySubsystem {
 		}
 	}
 
+	/// Create a new instance of `AvailabilityRecoverySubsystem` which requests data from backers if
+	/// PoV is below a threshold, and the systematic chunks otherwise.
+	pub fn with_systematic_chunks_if_pov_large(
+		req_receiver: QuotaReceiver<request_v1::AvailableDataFetchingRequest>,
+		metrics: Metrics,
+	) -> Self {
+		Self {
+			recovery_strategy: RecoveryStrategy::BackersFirstIfSizeLowerThenSystematicChunks(
+				SMALL_POV_LIMIT,
+			),
+			req_receiver,
+			metrics,
+		}
+	}
+
 	async fn run<Context>(self, mut ctx: Context) -> SubsystemResult<()> {
 		let mut state = State::default();
 		let Self { recovery_strategy, mut req_receiver, metrics } = self;
@@ -1399,6 +1741,12 @@ async fn erasure_task_thread(
 					chunks.values().map(|c| (&c.chunk[..], c.index.0 as usize)),
 				));
 			},
+			Some(ErasureTask::ReconstructFromSystematic(n_validators, chunks, sender)) => {
+				let _ = sender.send(polkadot_erasure_coding::reconstruct_from_systematic_v1(
+					n_validators,
+					chunks.iter().map(|c| &c.chunk[..]),
+				));
+			},
 			Some(ErasureTask::Reencode(n_validators, root, available_data, sender)) => {
 				let metrics = metrics.clone();
 
@@ -0,0 +1,207 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compression of notifications on the wire.
+//!
+//! Peers connected on [`ValidationVersion::V2`] exchange the same messages as on
+//! [`ValidationVersion::V1`], but each notification may be zstd compressed.
+//! [`ValidationVersion::V3`] keeps the compression and carries `v2` messages. Compressed
+//! notifications carry the magic prefix of `sp_maybe_compressed_blob`, which no SCALE encoded
+//! [`WireMessage`](crate::WireMessage) starts with, so the sender can decide per notification
+//! whether compressing it pays off and the receiver accepts both forms.
+
+use std::borrow::Cow;
+
+use polkadot_node_network_protocol::peer_set::{
+	PeerSet, ProtocolVersion, ValidationVersion, MAX_NOTIFICATION_SIZE,
+};
+
+use crate::Metrics;
+
+/// Notifications smaller than this are never compressed, as zstd framing would eat up any gain.
+const COMPRESSION_THRESHOLD: usize = 256;
+
+/// Upper bound for the size of a decompressed notification.
+///
+/// Senders keep the uncompressed encoding within the notification size limit, so anything
+/// expanding beyond it is bogus.
+const DECOMPRESSION_BOMB_LIMIT: usize = MAX_NOTIFICATION_SIZE as usize;
+
+/// Whether notifications on `peer_set` may be compressed for peers connected with `version`.
+pub(crate) fn is_compressed(peer_set: PeerSet, version: ProtocolVersion) -> bool {
+	match peer_set {
+		PeerSet::Validation =>
+			version == ValidationVersion::V2.into() || version == ValidationVersion::V3.into(),
+		PeerSet::Collation => false,
+	}
+}
+
+/// The version of the messages carried by `version` of the `peer_set` protocol.
+///
+/// This is what subsystems get to see, compression is an implementation detail of the bridge.
+/// Peers on [`ValidationVersion::V3`] speak `v2` messages, which is reported as version 2.
+pub(crate) fn message_version(peer_set: PeerSet, version: ProtocolVersion) -> ProtocolVersion {
+	match peer_set {
+		PeerSet::Validation if version == ValidationVersion::V3.into() =>
+			ValidationVersion::V2.into(),
+		PeerSet::Validation if version == ValidationVersion::V2.into() =>
+			ValidationVersion::V1.into(),
+		_ => version,
+	}
+}
+
+/// Prepare an encoded notification for sending to peers connected with `version`.
+pub(crate) fn compress(
+	peer_set: PeerSet,
+	version: ProtocolVersion,
+	encoded: Vec<u8>,
+	metrics: &Metrics,
+) -> Vec<u8> {
+	if !is_compressed(peer_set, version) || encoded.len() < COMPRESSION_THRESHOLD {
+		return encoded
+	}
+
+	let raw_len = encoded.len();
+	let wire = match sp_maybe_compressed_blob::compress(&encoded, DECOMPRESSION_BOMB_LIMIT) {
+		Some(compressed) if compressed.len() < raw_len => compressed,
+		_ => encoded,
+	};
+	metrics.on_notification_compressed(peer_set, raw_len, wire.len());
+	wire
+}
+
+/// Recover the encoded notification from what a peer connected with `version` sent us.
+pub(crate) fn decompress<'a>(
+	peer_set: PeerSet,
+	version: ProtocolVersion,
+	wire: &'a [u8],
+	metrics: &Metrics,
+) -> Result<Cow<'a, [u8]>, sp_maybe_compressed_blob::Error> {
+	if !is_compressed(peer_set, version) {
+		return Ok(Cow::Borrowed(wire))
+	}
+
+	let encoded = sp_maybe_compressed_blob::decompress(wire, DECOMPRESSION_BOMB_LIMIT)?;
+	if let Cow::Owned(ref raw) = encoded {
+		metrics.on_notification_decompressed(peer_set, raw.len(), wire.len());
+	}
+	Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use parity_scale_codec::{Decode, Encode};
+	use polkadot_node_network_protocol::{peer_set::CollationVersion, View};
+	use polkadot_primitives::Hash;
+
+	use crate::WireMessage;
+
+	fn large_message() -> Vec<u8> {
+		// Repetitive, as most gossip is.
+		let view = View::new((0..100).map(|_| Hash::repeat_byte(7)), 1);
+		WireMessage::<()>::ViewUpdate(view).encode()
+	}
+
+	#[test]
+	fn compresses_large_validation_notifications() {
+		let encoded = large_message();
+		let wire = compress(
+			PeerSet::Validation,
+			ValidationVersion::V2.into(),
+			encoded.clone(),
+			&Metrics::default(),
+		);
+		assert!(wire.len() < encoded.len());
+
+		let decompressed = decompress(
+			PeerSet::Validation,
+			ValidationVersion::V2.into(),
+			&wire,
+			&Metrics::default(),
+		)
+		.unwrap();
+		assert_eq!(decompressed, encoded);
+		assert!(WireMessage::<()>::decode(&mut &decompressed[..]).is_ok());
+	}
+
+	#[test]
+	fn small_notifications_are_sent_raw() {
+		let encoded = WireMessage::<()>::ViewUpdate(View::default()).encode();
+		let wire = compress(
+			PeerSet::Validation,
+			ValidationVersion::V2.into(),
+			encoded.clone(),
+			&Metrics::default(),
+		);
+		assert_eq!(wire, encoded);
+		assert_eq!(
+			decompress(
+				PeerSet::Validation,
+				ValidationVersion::V2.into(),
+				&wire,
+				&Metrics::default()
+			)
+			.unwrap(),
+			encoded,
+		);
+	}
+
+	#[test]
+	fn old_versions_are_never_compressed() {
+		let encoded = large_message();
+		for (peer_set, version) in [
+			(PeerSet::Validation, ValidationVersion::V1.into()),
+			(PeerSet::Collation, CollationVersion::V1.into()),
+		] {
+			assert_eq!(compress(peer_set, version, encoded.clone(), &Metrics::default()), encoded);
+			assert_eq!(message_version(peer_set, version), version);
+		}
+		assert_eq!(
+			message_version(PeerSet::Validation, ValidationVersion::V2.into()),
+			ValidationVersion::V1.into(),
+		);
+	}
+
+	#[test]
+	fn v3_is_compressed_and_carries_v2_messages() {
+		let version = ValidationVersion::V3.into();
+		assert!(is_compressed(PeerSet::Validation, version));
+		assert_eq!(message_version(PeerSet::Validation, version), ValidationVersion::V2.into());
+
+		let encoded = large_message();
+		let wire = compress(PeerSet::Validation, version, encoded.clone(), &Metrics::default());
+		assert!(wire.len() < encoded.len());
+		assert_eq!(
+			decompress(PeerSet::Validation, version, &wire, &Metrics::default()).unwrap(),
+			encoded,
+		);
+	}
+
+	#[test]
+	fn decompression_bombs_are_rejected() {
+		let huge = vec![0u8; DECOMPRESSION_BOMB_LIMIT * 2];
+		let bomb = sp_maybe_compressed_blob::compress(&huge, huge.len()).unwrap();
+		assert!(decompress(
+			PeerSet::Validation,
+			ValidationVersion::V2.into(),
+			&bomb,
+			&Metrics::default()
+		)
+		.is_err());
+	}
+}
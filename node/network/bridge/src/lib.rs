@@ -28,7 +28,8 @@ use parking_lot::Mutex;
 use sp_consensus::SyncOracle;
 
 use polkadot_node_network_protocol::{
-	peer_set::{PeerSet, ProtocolVersion},
+	peer_set::{CollationVersion, PeerSet, ProtocolVersion, ValidationVersion},
+	request_response::RequestTrafficObserver,
 	PeerId, UnifiedReputationChange as Rep, View,
 };
 
@@ -50,6 +51,11 @@ use self::network::Network;
 mod metrics;
 pub use self::metrics::Metrics;
 
+mod compression;
+
+mod traffic;
+use self::traffic::TrafficTable;
+
 mod errors;
 pub(crate) use self::errors::Error;
 
@@ -90,9 +96,50 @@ pub(crate) struct PeerData {
 }
 
 /// Shared state between incoming and outgoing.
-
+///
+/// The same instance has to be passed to [`NetworkBridgeRx`] and [`NetworkBridgeTx`], so that
+/// outgoing messages can be encoded according to the protocol version negotiated with each peer.
+/// It also holds the traffic exchanged with each peer, which is accounted for by both.
 #[derive(Default, Clone)]
-pub(crate) struct Shared(Arc<Mutex<SharedInner>>);
+pub struct Shared(Arc<Mutex<SharedInner>>, TrafficTable);
+
+impl Shared {
+	/// The traffic exchanged with each peer.
+	pub(crate) fn traffic(&self) -> &TrafficTable {
+		&self.1
+	}
+
+	/// An observer accounting the traffic of incoming requests and of our responses to them.
+	///
+	/// To be passed to `IncomingRequestReceiver::with_traffic_observer` for every
+	/// request-response protocol, as incoming requests don't pass through the bridge.
+	pub fn request_traffic_observer(&self) -> Arc<dyn RequestTrafficObserver> {
+		Arc::new(self.1.clone())
+	}
+
+	/// Group `peers` by the protocol version they are connected with on `peer_set`.
+	///
+	/// Peers we are not connected to are put with the oldest version, as whatever we send them
+	/// will be dropped anyway.
+	pub(crate) fn peers_by_version(
+		&self,
+		peer_set: PeerSet,
+		peers: Vec<PeerId>,
+	) -> HashMap<ProtocolVersion, Vec<PeerId>> {
+		let shared = self.0.lock();
+		let (peer_map, oldest_version) = match peer_set {
+			PeerSet::Validation => (&shared.validation_peers, ValidationVersion::V1.into()),
+			PeerSet::Collation => (&shared.collation_peers, CollationVersion::V1.into()),
+		};
+
+		let mut by_version: HashMap<_, Vec<_>> = HashMap::new();
+		for peer in peers {
+			let version = peer_map.get(&peer).map_or(oldest_version, |data| data.version);
+			by_version.entry(version).or_default().push(peer);
+		}
+		by_version
+	}
+}
 
 #[derive(Default)]
 struct SharedInner {
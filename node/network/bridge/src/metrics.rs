@@ -103,6 +103,31 @@ impl Metrics {
 		});
 	}
 
+	/// Note a notification of `raw` bytes we sent as `wire` bytes.
+	pub fn on_notification_compressed(&self, peer_set: PeerSet, raw: usize, wire: usize) {
+		self.observe_compression_ratio(peer_set, "sent", raw, wire)
+	}
+
+	/// Note a notification we received as `wire` bytes, which decompressed to `raw` bytes.
+	pub fn on_notification_decompressed(&self, peer_set: PeerSet, raw: usize, wire: usize) {
+		self.observe_compression_ratio(peer_set, "received", raw, wire)
+	}
+
+	fn observe_compression_ratio(
+		&self,
+		peer_set: PeerSet,
+		direction: &'static str,
+		raw: usize,
+		wire: usize,
+	) {
+		if let Some(metrics) = self.0.as_ref() {
+			metrics
+				.compression_ratio
+				.with_label_values(&[peer_set.get_label(), direction])
+				.observe(wire as f64 / raw.max(1) as f64);
+		}
+	}
+
 	pub fn on_report_event(&self) {
 		if let Some(metrics) = self.0.as_ref() {
 			metrics.report_events.inc()
@@ -123,6 +148,8 @@ pub(crate) struct MetricsInner {
 
 	bytes_received: prometheus::CounterVec<prometheus::U64>,
 	bytes_sent: prometheus::CounterVec<prometheus::U64>,
+
+	compression_ratio: prometheus::HistogramVec,
 }
 
 impl metrics::Metrics for Metrics {
@@ -217,6 +244,17 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			compression_ratio: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_notification_compression_ratio",
+						"Size on the wire relative to the encoded size of notifications on compressing protocol versions",
+					)
+					.buckets(vec![0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0]),
+					&["protocol", "direction"]
+				)?,
+				registry,
+			)?,
 		};
 
 		Ok(Metrics(Some(metrics)))
@@ -34,7 +34,10 @@ use polkadot_node_network_protocol::{
 };
 use polkadot_primitives::{AuthorityDiscoveryId, Block, Hash};
 
-use crate::validator_discovery::AuthorityDiscovery;
+use crate::{
+	traffic::{TrafficKind, TrafficTable},
+	validator_discovery::AuthorityDiscovery,
+};
 
 // network bridge network abstraction log target
 const LOG_TARGET: &'static str = "parachain::network-bridge-net";
@@ -44,6 +47,9 @@ const LOG_TARGET: &'static str = "parachain::network-bridge-net";
 /// This function is only used internally by the network-bridge, which is responsible to only send
 /// messages that are compatible with the passed peer set, as that is currently not enforced by
 /// this function. These are messages of type `WireMessage` parameterized on the matching type.
+///
+/// All `peers` must be connected with `version` of the peer set's protocol, which determines how
+/// the message is put on the wire.
 pub(crate) fn send_message<M>(
 	net: &mut impl Network,
 	mut peers: Vec<PeerId>,
@@ -52,12 +58,15 @@ pub(crate) fn send_message<M>(
 	protocol_names: &PeerSetProtocolNames,
 	message: M,
 	metrics: &super::Metrics,
+	traffic: &TrafficTable,
 ) where
-	M: Encode + Clone,
+	M: Encode + Clone + TrafficKind,
 {
 	let message = {
-		let encoded = message.encode();
+		let kind = message.traffic_kind();
+		let encoded = crate::compression::compress(peer_set, version, message.encode(), metrics);
 		metrics.on_notification_sent(peer_set, version, encoded.len(), peers.len());
+		traffic.note_sent(&peers, kind, encoded.len());
 		encoded
 	};
 
@@ -66,7 +75,9 @@ pub(crate) fn send_message<M>(
 	// network used `Bytes` this would not be necessary.
 	let last_peer = peers.pop();
 	// optimization: generate the protocol name once.
-	let protocol_name = protocol_names.get_name(peer_set, version);
+	// The network keeps track of substreams by the main protocol name, whichever version got
+	// negotiated.
+	let protocol_name = protocol_names.get_main_name(peer_set);
 	peers.into_iter().for_each(|peer| {
 		net.write_notification(peer, protocol_name.clone(), message.clone());
 	});
@@ -97,13 +108,16 @@ pub trait Network: Clone + Send + 'static {
 	async fn remove_from_peers_set(&mut self, protocol: ProtocolName, peers: Vec<PeerId>);
 
 	/// Send a request to a remote peer.
+	///
+	/// Returns the peer the request was sent to and the size of its payload, or `None` if no peer
+	/// could be found for the recipient.
 	async fn start_request<AD: AuthorityDiscovery>(
 		&self,
 		authority_discovery: &mut AD,
 		req: Requests,
 		req_protocol_names: &ReqProtocolNames,
 		if_disconnected: IfDisconnected,
-	);
+	) -> Option<(PeerId, usize)>;
 
 	/// Report a given peer as either beneficial (+) or costly (-) according to the given scalar.
 	fn report_peer(&self, who: PeerId, rep: ReputationChange);
@@ -151,7 +165,7 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 		req: Requests,
 		req_protocol_names: &ReqProtocolNames,
 		if_disconnected: IfDisconnected,
-	) {
+	) -> Option<(PeerId, usize)> {
 		let (protocol, OutgoingRequest { peer, payload, pending_response }) = req.encode_request();
 
 		let peer_id = match peer {
@@ -194,7 +208,7 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 					},
 					Ok(_) => {},
 				}
-				return
+				return None
 			},
 			Some(peer_id) => peer_id,
 		};
@@ -207,6 +221,7 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 			"Starting request",
 		);
 
+		let payload_size = payload.len();
 		NetworkService::start_request(
 			self,
 			peer_id,
@@ -215,6 +230,8 @@ impl Network for Arc<NetworkService<Block, Hash>> {
 			pending_response,
 			if_disconnected,
 		);
+
+		Some((peer_id, payload_size))
 	}
 }
 
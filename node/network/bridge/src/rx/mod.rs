@@ -32,7 +32,8 @@ use polkadot_node_network_protocol::{
 		CollationVersion, PeerSet, PeerSetProtocolNames, PerPeerSet, ProtocolVersion,
 		ValidationVersion,
 	},
-	v1 as protocol_v1, ObservedRole, OurView, PeerId, UnifiedReputationChange as Rep, View,
+	v1 as protocol_v1, v2 as protocol_v2, ObservedRole, OurView, PeerId,
+	UnifiedReputationChange as Rep, View,
 };
 
 use polkadot_node_subsystem::{
@@ -64,7 +65,11 @@ use super::validator_discovery;
 /// Defines the `Network` trait with an implementation for an `Arc<NetworkService>`.
 use crate::network::{send_message, Network};
 
-use crate::network::get_peer_id_by_authority_id;
+use crate::{
+	compression,
+	network::get_peer_id_by_authority_id,
+	traffic::{self, TrafficKind},
+};
 
 use super::metrics::Metrics;
 
@@ -90,14 +95,16 @@ impl<N, AD> NetworkBridgeRx<N, AD> {
 	///
 	/// This assumes that the network service has had the notifications protocol for the network
 	/// bridge already registered. See [`peers_sets_info`](peers_sets_info).
+	///
+	/// `shared` must be the state passed to the [`NetworkBridgeTx`] of the same network.
 	pub fn new(
 		network_service: N,
 		authority_discovery_service: AD,
 		sync_oracle: Box<dyn SyncOracle + Send>,
 		metrics: Metrics,
 		peerset_protocol_names: PeerSetProtocolNames,
+		shared: Shared,
 	) -> Self {
-		let shared = Shared::default();
 		Self {
 			network_service,
 			authority_discovery_service,
@@ -228,6 +235,9 @@ where
 				let maybe_authority =
 					authority_discovery_service.get_authority_ids_by_peer_id(peer).await;
 
+				// Subsystems don't care how messages are put on the wire.
+				let message_version = compression::message_version(peer_set, version);
+
 				match peer_set {
 					PeerSet::Validation => {
 						dispatch_validation_events_to_all(
@@ -235,7 +245,7 @@ where
 								NetworkBridgeEvent::PeerConnected(
 									peer,
 									role,
-									version,
+									message_version,
 									maybe_authority,
 								),
 								NetworkBridgeEvent::PeerViewChange(peer, View::default()),
@@ -252,6 +262,7 @@ where
 							&peerset_protocol_names,
 							WireMessage::<protocol_v1::ValidationProtocol>::ViewUpdate(local_view),
 							&metrics,
+							shared.traffic(),
 						);
 					},
 					PeerSet::Collation => {
@@ -260,7 +271,7 @@ where
 								NetworkBridgeEvent::PeerConnected(
 									peer,
 									role,
-									version,
+									message_version,
 									maybe_authority,
 								),
 								NetworkBridgeEvent::PeerViewChange(peer, View::default()),
@@ -277,6 +288,7 @@ where
 							&peerset_protocol_names,
 							WireMessage::<protocol_v1::CollationProtocol>::ViewUpdate(local_view),
 							&metrics,
+							shared.traffic(),
 						);
 					},
 				}
@@ -415,30 +427,41 @@ where
 				);
 
 				if !v_messages.is_empty() {
-					let (events, reports) =
-						if expected_versions[PeerSet::Validation] ==
-							Some(ValidationVersion::V1.into())
-						{
-							handle_v1_peer_messages::<protocol_v1::ValidationProtocol, _>(
-								remote,
-								PeerSet::Validation,
-								&mut shared.0.lock().validation_peers,
-								v_messages,
-								&metrics,
-							)
-						} else {
-							gum::warn!(
-								target: LOG_TARGET,
-								version = ?expected_versions[PeerSet::Validation],
-								"Major logic bug. Peer somehow has unsupported validation protocol version."
-							);
+					// Compression is undone before decoding, only the message format matters.
+					let message_version = expected_versions[PeerSet::Validation]
+						.map(|v| compression::message_version(PeerSet::Validation, v));
+					let (events, reports) = if message_version == Some(ValidationVersion::V1.into())
+					{
+						handle_peer_messages::<protocol_v1::ValidationProtocol, _>(
+							remote,
+							PeerSet::Validation,
+							&mut shared.0.lock().validation_peers,
+							v_messages,
+							&metrics,
+							shared.traffic(),
+						)
+					} else if message_version == Some(ValidationVersion::V2.into()) {
+						handle_peer_messages::<protocol_v2::ValidationProtocol, _>(
+							remote,
+							PeerSet::Validation,
+							&mut shared.0.lock().validation_peers,
+							v_messages,
+							&metrics,
+							shared.traffic(),
+						)
+					} else {
+						gum::warn!(
+							target: LOG_TARGET,
+							version = ?expected_versions[PeerSet::Validation],
+							"Major logic bug. Peer somehow has unsupported validation protocol version."
+						);
 
-							never!("Only version 1 is supported; peer set connection checked above; qed");
+						never!("Only versions 1, 2 and 3 are supported; peer set connection checked above; qed");
 
-							// If a peer somehow triggers this, we'll disconnect them
-							// eventually.
-							(Vec::new(), vec![UNCONNECTED_PEERSET_COST])
-						};
+						// If a peer somehow triggers this, we'll disconnect them
+						// eventually.
+						(Vec::new(), vec![UNCONNECTED_PEERSET_COST])
+					};
 
 					for report in reports {
 						network_service.report_peer(remote, report.into());
@@ -452,12 +475,13 @@ where
 						if expected_versions[PeerSet::Collation] ==
 							Some(CollationVersion::V1.into())
 						{
-							handle_v1_peer_messages::<protocol_v1::CollationProtocol, _>(
+							handle_peer_messages::<protocol_v1::CollationProtocol, _>(
 								remote,
 								PeerSet::Collation,
 								&mut shared.0.lock().collation_peers,
 								c_messages,
 								&metrics,
+								shared.traffic(),
 							)
 						} else {
 							gum::warn!(
@@ -720,10 +744,12 @@ fn update_our_view<Net, Context>(
 		)
 	};
 
+	// View updates are encoded the same way by all protocol versions.
 	send_validation_message_v1(
 		net,
 		validation_peers,
 		peerset_protocol_names,
+		shared,
 		WireMessage::ViewUpdate(new_view.clone()),
 		metrics,
 	);
@@ -732,6 +758,7 @@ fn update_our_view<Net, Context>(
 		net,
 		collation_peers,
 		peerset_protocol_names,
+		shared,
 		WireMessage::ViewUpdate(new_view),
 		metrics,
 	);
@@ -752,14 +779,15 @@ fn update_our_view<Net, Context>(
 	);
 }
 
-// Handle messages on a specific v1 peer-set. The peer is expected to be connected on that
-// peer-set.
-fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
+// Handle messages on a specific peer-set. The peer is expected to be connected on that
+// peer-set, with a protocol version carrying `RawMessage`s.
+fn handle_peer_messages<RawMessage: Decode + TrafficKind, OutMessage: From<RawMessage>>(
 	peer: PeerId,
 	peer_set: PeerSet,
 	peers: &mut HashMap<PeerId, PeerData>,
 	messages: Vec<Bytes>,
 	metrics: &Metrics,
+	traffic: &TrafficTable,
 ) -> (Vec<NetworkBridgeEvent<OutMessage>>, Vec<Rep>) {
 	let peer_data = match peers.get_mut(&peer) {
 		None => return (Vec::new(), vec![UNCONNECTED_PEERSET_COST]),
@@ -770,14 +798,26 @@ fn handle_v1_peer_messages<RawMessage: Decode, OutMessage: From<RawMessage>>(
 	let mut reports = Vec::new();
 
 	for message in messages {
-		metrics.on_notification_received(peer_set, peer_data.version, message.len());
+		let size = message.len();
+		metrics.on_notification_received(peer_set, peer_data.version, size);
+		let message = match compression::decompress(peer_set, peer_data.version, &message, metrics)
+		{
+			Ok(m) => m,
+			Err(_) => {
+				traffic.note_received(peer, traffic::MALFORMED, size);
+				reports.push(MALFORMED_MESSAGE_COST);
+				continue
+			},
+		};
 		let message = match WireMessage::<RawMessage>::decode_all(&mut message.as_ref()) {
 			Err(_) => {
+				traffic.note_received(peer, traffic::MALFORMED, size);
 				reports.push(MALFORMED_MESSAGE_COST);
 				continue
 			},
 			Ok(m) => m,
 		};
+		traffic.note_received(peer, message.traffic_kind(), size);
 
 		outgoing_events.push(match message {
 			WireMessage::ViewUpdate(new_view) => {
@@ -809,24 +849,29 @@ fn send_validation_message_v1(
 	net: &mut impl Network,
 	peers: Vec<PeerId>,
 	peerset_protocol_names: &PeerSetProtocolNames,
+	shared: &Shared,
 	message: WireMessage<protocol_v1::ValidationProtocol>,
 	metrics: &Metrics,
 ) {
-	send_message(
-		net,
-		peers,
-		PeerSet::Validation,
-		ValidationVersion::V1.into(),
-		peerset_protocol_names,
-		message,
-		metrics,
-	);
+	for (version, peers) in shared.peers_by_version(PeerSet::Validation, peers) {
+		send_message(
+			net,
+			peers,
+			PeerSet::Validation,
+			version,
+			peerset_protocol_names,
+			message.clone(),
+			metrics,
+			shared.traffic(),
+		);
+	}
 }
 
 fn send_collation_message_v1(
 	net: &mut impl Network,
 	peers: Vec<PeerId>,
 	peerset_protocol_names: &PeerSetProtocolNames,
+	shared: &Shared,
 	message: WireMessage<protocol_v1::CollationProtocol>,
 	metrics: &Metrics,
 ) {
@@ -838,6 +883,7 @@ fn send_collation_message_v1(
 		peerset_protocol_names,
 		message,
 		metrics,
+		shared.traffic(),
 	);
 }
 
@@ -888,6 +934,21 @@ fn dispatch_collation_event_to_all_unbounded(
 	if let Ok(msg) = event.focus() {
 		sender.send_unbounded_message(CollatorProtocolMessage::NetworkBridgeUpdate(msg))
 	}
+	if let Some(msg) = collation_connectivity_update(&event) {
+		sender.send_unbounded_message(msg)
+	}
+}
+
+/// Gossip support is only interested in peers connecting and disconnecting on the collation peer
+/// set, so that it can report on connectivity.
+fn collation_connectivity_update(
+	event: &NetworkBridgeEvent<net_protocol::VersionedCollationProtocol>,
+) -> Option<GossipSupportMessage> {
+	match event {
+		NetworkBridgeEvent::PeerConnected(..) | NetworkBridgeEvent::PeerDisconnected(_) =>
+			event.focus().ok().map(GossipSupportMessage::CollationNetworkBridgeUpdate),
+		_ => None,
+	}
 }
 
 async fn dispatch_validation_events_to_all<I>(
@@ -914,9 +975,9 @@ async fn dispatch_collation_events_to_all<I>(
 	I: IntoIterator<Item = NetworkBridgeEvent<net_protocol::VersionedCollationProtocol>>,
 	I::IntoIter: Send,
 {
-	let messages_for = |event: NetworkBridgeEvent<net_protocol::VersionedCollationProtocol>| {
-		event.focus().ok().map(|m| CollatorProtocolMessage::NetworkBridgeUpdate(m))
-	};
-
-	ctx.send_messages(events.into_iter().flat_map(messages_for)).await
+	for event in events {
+		ctx.send_messages(event.focus().ok().map(CollatorProtocolMessage::NetworkBridgeUpdate))
+			.await;
+		ctx.send_messages(collation_connectivity_update(&event)).await;
+	}
 }
@@ -125,7 +125,8 @@ impl Network for TestNetwork {
 		_: Requests,
 		_: &ReqProtocolNames,
 		_: IfDisconnected,
-	) {
+	) -> Option<(PeerId, usize)> {
+		None
 	}
 
 	fn report_peer(&self, who: PeerId, rep: ReputationChange) {
@@ -401,7 +402,19 @@ async fn assert_sends_collation_event_to_all(
 		AllMessages::CollatorProtocol(
 			CollatorProtocolMessage::NetworkBridgeUpdate(e)
 		) if e == event.focus().expect("could not focus message")
-	)
+	);
+
+	if matches!(
+		event,
+		NetworkBridgeEvent::PeerConnected(..) | NetworkBridgeEvent::PeerDisconnected(_)
+	) {
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::GossipSupport(
+				GossipSupportMessage::CollationNetworkBridgeUpdate(e)
+			) if e == event.focus().expect("could not focus message")
+		);
+	}
 }
 
 #[test]
@@ -708,7 +721,7 @@ fn peer_view_updates_sent_via_overseer() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -758,7 +771,7 @@ fn peer_messages_sent_via_overseer() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -773,9 +786,9 @@ fn peer_messages_sent_via_overseer() {
 		}
 
 		let approval_distribution_message =
-			protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new());
+			protocol_v2::ApprovalDistributionMessage::Approvals(Vec::new());
 
-		let message_v1 = protocol_v1::ValidationProtocol::ApprovalDistribution(
+		let message_v2 = protocol_v2::ValidationProtocol::ApprovalDistribution(
 			approval_distribution_message.clone(),
 		);
 
@@ -783,7 +796,7 @@ fn peer_messages_sent_via_overseer() {
 			.peer_message(
 				peer.clone(),
 				PeerSet::Validation,
-				WireMessage::ProtocolMessage(message_v1.clone()).encode(),
+				WireMessage::ProtocolMessage(message_v2.clone()).encode(),
 			)
 			.await;
 
@@ -796,7 +809,7 @@ fn peer_messages_sent_via_overseer() {
 			virtual_overseer.recv().await,
 			AllMessages::ApprovalDistribution(
 				ApprovalDistributionMessage::NetworkBridgeUpdate(
-					NetworkBridgeEvent::PeerMessage(p, Versioned::V1(m))
+					NetworkBridgeEvent::PeerMessage(p, Versioned::V2(m))
 				)
 			) => {
 				assert_eq!(p, peer);
@@ -813,6 +826,191 @@ fn peer_messages_sent_via_overseer() {
 	});
 }
 
+#[test]
+fn compressed_peer_messages_are_decompressed() {
+	test_harness(done_syncing_oracle(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer, shared } = test_harness;
+
+		let peer = PeerId::random();
+
+		// Connects on the main protocol, which compresses.
+		network_handle
+			.connect_peer(peer.clone(), PeerSet::Validation, ObservedRole::Full)
+			.await;
+
+		await_peer_connections(&shared, 1, 0).await;
+
+		// Subsystems are told about the messages, not the wire format.
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerConnected(
+				peer.clone(),
+				ObservedRole::Full,
+				ValidationVersion::V2.into(),
+				None,
+			),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		let approval_distribution_message =
+			protocol_v2::ApprovalDistributionMessage::Approvals(Vec::new());
+		let message_v2 = protocol_v2::ValidationProtocol::ApprovalDistribution(
+			approval_distribution_message.clone(),
+		);
+		let encoded = WireMessage::ProtocolMessage(message_v2).encode();
+		let compressed = sp_maybe_compressed_blob::compress(&encoded, encoded.len()).unwrap();
+
+		network_handle.peer_message(peer.clone(), PeerSet::Validation, compressed).await;
+		// Uncompressed notifications are fine as well.
+		network_handle.peer_message(peer.clone(), PeerSet::Validation, encoded).await;
+
+		for _ in 0..2 {
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::ApprovalDistribution(
+					ApprovalDistributionMessage::NetworkBridgeUpdate(
+						NetworkBridgeEvent::PeerMessage(p, Versioned::V2(m))
+					)
+				) => {
+					assert_eq!(p, peer);
+					assert_eq!(m, approval_distribution_message);
+				}
+			);
+		}
+		virtual_overseer
+	});
+}
+
+#[test]
+fn peers_on_compressing_fallback_version_send_v1_messages() {
+	test_harness(done_syncing_oracle(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer, shared } = test_harness;
+
+		let peer = PeerId::random();
+		let fallback = network_handle
+			.protocol_names
+			.get_name(PeerSet::Validation, ValidationVersion::V2.into());
+
+		network_handle
+			.send_network_event(NetworkEvent::NotificationStreamOpened {
+				remote: peer,
+				protocol: network_handle.protocol_names.get_main_name(PeerSet::Validation),
+				negotiated_fallback: Some(fallback),
+				role: ObservedRole::Full.into(),
+				received_handshake: vec![],
+			})
+			.await;
+
+		await_peer_connections(&shared, 1, 0).await;
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerConnected(
+				peer.clone(),
+				ObservedRole::Full,
+				ValidationVersion::V1.into(),
+				None,
+			),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		let approval_distribution_message =
+			protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new());
+		let message_v1 = protocol_v1::ValidationProtocol::ApprovalDistribution(
+			approval_distribution_message.clone(),
+		);
+		let encoded = WireMessage::ProtocolMessage(message_v1).encode();
+		let compressed = sp_maybe_compressed_blob::compress(&encoded, encoded.len()).unwrap();
+
+		network_handle.peer_message(peer.clone(), PeerSet::Validation, compressed).await;
+
+		assert_matches!(
+			virtual_overseer.recv().await,
+			AllMessages::ApprovalDistribution(
+				ApprovalDistributionMessage::NetworkBridgeUpdate(
+					NetworkBridgeEvent::PeerMessage(p, Versioned::V1(m))
+				)
+			) => {
+				assert_eq!(p, peer);
+				assert_eq!(m, approval_distribution_message);
+			}
+		);
+		virtual_overseer
+	});
+}
+
+#[test]
+fn peers_on_fallback_version_do_not_compress() {
+	test_harness(done_syncing_oracle(), |test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer, shared } = test_harness;
+
+		let peer = PeerId::random();
+		let fallback = network_handle
+			.protocol_names
+			.get_name(PeerSet::Validation, ValidationVersion::V1.into());
+
+		network_handle
+			.send_network_event(NetworkEvent::NotificationStreamOpened {
+				remote: peer,
+				protocol: network_handle.protocol_names.get_main_name(PeerSet::Validation),
+				negotiated_fallback: Some(fallback),
+				role: ObservedRole::Full.into(),
+				received_handshake: vec![],
+			})
+			.await;
+
+		await_peer_connections(&shared, 1, 0).await;
+		assert_eq!(
+			shared.0.lock().validation_peers.get(&peer).map(|data| data.version),
+			Some(ValidationVersion::V1.into()),
+		);
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerConnected(
+				peer.clone(),
+				ObservedRole::Full,
+				ValidationVersion::V1.into(),
+				None,
+			),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		assert_sends_validation_event_to_all(
+			NetworkBridgeEvent::PeerViewChange(peer.clone(), View::default()),
+			&mut virtual_overseer,
+		)
+		.await;
+
+		let message_v1 = protocol_v1::ValidationProtocol::ApprovalDistribution(
+			protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new()),
+		);
+		let encoded = WireMessage::ProtocolMessage(message_v1).encode();
+		let compressed = sp_maybe_compressed_blob::compress(&encoded, encoded.len()).unwrap();
+
+		network_handle.peer_message(peer.clone(), PeerSet::Validation, compressed).await;
+
+		let actions = network_handle.next_network_actions(2).await;
+		assert_network_actions_contains(
+			&actions,
+			&NetworkAction::ReputationChange(peer.clone(), MALFORMED_MESSAGE_COST.into()),
+		);
+		virtual_overseer
+	});
+}
+
 #[test]
 fn peer_disconnect_from_just_one_peerset() {
 	test_harness(done_syncing_oracle(), |test_harness| async move {
@@ -835,7 +1033,7 @@ fn peer_disconnect_from_just_one_peerset() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -930,7 +1128,7 @@ fn relays_collation_protocol_messages() {
 				NetworkBridgeEvent::PeerConnected(
 					peer_a.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
@@ -1035,7 +1233,7 @@ fn different_views_on_different_peer_sets() {
 				NetworkBridgeEvent::PeerConnected(
 					peer.clone(),
 					ObservedRole::Full,
-					ValidationVersion::V1.into(),
+					ValidationVersion::V2.into(),
 					None,
 				),
 				&mut virtual_overseer,
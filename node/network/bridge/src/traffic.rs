@@ -0,0 +1,347 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Accounting of the traffic exchanged with individual peers.
+//!
+//! Unlike the Prometheus metrics, which are aggregated per peer set, the traffic is tracked per
+//! peer and per kind of message, so that peers flooding us can be told apart.
+
+use std::{cmp::Reverse, collections::HashMap, num::NonZeroUsize, sync::Arc};
+
+use futures::channel::oneshot;
+use lru::LruCache;
+use parking_lot::Mutex;
+use sc_network::RequestFailure;
+
+use polkadot_node_network_protocol::{
+	request_response::{outgoing::ResponseSender, Protocol, RequestTrafficObserver},
+	v1 as protocol_v1, v2 as protocol_v2, PeerId,
+};
+use polkadot_node_subsystem::messages::{MessageTraffic, PeerTraffic, Traffic};
+
+use crate::WireMessage;
+
+/// The maximum number of peers whose traffic is tracked.
+///
+/// Once reached, the peer we exchanged a message with the longest time ago is forgotten to make
+/// room for a new one. This keeps the peers currently flooding us around, even after they
+/// disconnected.
+const MAX_TRACKED_PEERS: NonZeroUsize = match NonZeroUsize::new(1000) {
+	Some(max) => max,
+	None => panic!("Maximum number of tracked peers must be non-zero"),
+};
+
+/// The kind of messages which could not be decompressed or decoded.
+pub(crate) const MALFORMED: &str = "malformed";
+
+/// Messages which can be told apart in the traffic accounting.
+pub(crate) trait TrafficKind {
+	/// The kind of view updates on the peer set of the message.
+	const VIEW_UPDATE: &'static str;
+
+	/// The kind of the message, by subsystem and message variant.
+	fn traffic_kind(&self) -> &'static str;
+}
+
+/// Implement [`TrafficKind`] for the validation protocol of the given protocol version.
+///
+/// The versions only differ in the content of their messages, which does not matter here.
+macro_rules! impl_validation_traffic_kind {
+	($version:ident) => {
+		impl TrafficKind for $version::ValidationProtocol {
+			const VIEW_UPDATE: &'static str = "validation/view-update";
+
+			fn traffic_kind(&self) -> &'static str {
+				use $version::{
+					ApprovalDistributionMessage as Approval,
+					BitfieldDistributionMessage as Bitfield,
+					StatementDistributionMessage as Statement,
+				};
+
+				match self {
+					Self::BitfieldDistribution(Bitfield::Bitfield(..)) =>
+						"bitfield-distribution/bitfield",
+					Self::StatementDistribution(Statement::Statement(..)) =>
+						"statement-distribution/statement",
+					Self::StatementDistribution(Statement::LargeStatement(..)) =>
+						"statement-distribution/large-statement",
+					Self::ApprovalDistribution(Approval::Assignments(..)) =>
+						"approval-distribution/assignments",
+					Self::ApprovalDistribution(Approval::Approvals(..)) =>
+						"approval-distribution/approvals",
+				}
+			}
+		}
+	};
+}
+
+impl_validation_traffic_kind!(protocol_v1);
+impl_validation_traffic_kind!(protocol_v2);
+
+impl TrafficKind for protocol_v1::CollationProtocol {
+	const VIEW_UPDATE: &'static str = "collation/view-update";
+
+	fn traffic_kind(&self) -> &'static str {
+		use protocol_v1::CollatorProtocolMessage as Collator;
+
+		match self {
+			Self::CollatorProtocol(Collator::Declare(..)) => "collator-protocol/declare",
+			Self::CollatorProtocol(Collator::AdvertiseCollation(..)) =>
+				"collator-protocol/advertise-collation",
+			Self::CollatorProtocol(Collator::CollationSeconded(..)) =>
+				"collator-protocol/collation-seconded",
+		}
+	}
+}
+
+impl<M: TrafficKind> TrafficKind for WireMessage<M> {
+	const VIEW_UPDATE: &'static str = M::VIEW_UPDATE;
+
+	fn traffic_kind(&self) -> &'static str {
+		match self {
+			WireMessage::ProtocolMessage(message) => message.traffic_kind(),
+			WireMessage::ViewUpdate(_) => Self::VIEW_UPDATE,
+		}
+	}
+}
+
+/// The kind of requests sent on `protocol`.
+pub(crate) fn request_kind(protocol: Protocol) -> &'static str {
+	match protocol {
+		Protocol::ChunkFetchingV1 => "request/chunk-fetching-v1",
+		Protocol::CollationFetchingV1 => "request/collation-fetching-v1",
+		Protocol::PoVFetchingV1 => "request/pov-fetching-v1",
+		Protocol::AvailableDataFetchingV1 => "request/available-data-fetching-v1",
+		Protocol::StatementFetchingV1 => "request/statement-fetching-v1",
+		Protocol::StatementFetchingV2 => "request/statement-fetching-v2",
+		Protocol::DisputeSendingV1 => "request/dispute-sending-v1",
+	}
+}
+
+/// The kind of responses to requests on `protocol`.
+pub(crate) fn response_kind(protocol: Protocol) -> &'static str {
+	match protocol {
+		Protocol::ChunkFetchingV1 => "response/chunk-fetching-v1",
+		Protocol::CollationFetchingV1 => "response/collation-fetching-v1",
+		Protocol::PoVFetchingV1 => "response/pov-fetching-v1",
+		Protocol::AvailableDataFetchingV1 => "response/available-data-fetching-v1",
+		Protocol::StatementFetchingV1 => "response/statement-fetching-v1",
+		Protocol::StatementFetchingV2 => "response/statement-fetching-v2",
+		Protocol::DisputeSendingV1 => "response/dispute-sending-v1",
+	}
+}
+
+/// Traffic received and sent, per kind of message.
+type PeerEntry = HashMap<&'static str, (Traffic, Traffic)>;
+
+/// The traffic exchanged with each peer, shared by [`NetworkBridgeRx`](crate::NetworkBridgeRx)
+/// and [`NetworkBridgeTx`](crate::NetworkBridgeTx).
+#[derive(Clone)]
+pub(crate) struct TrafficTable(Arc<Mutex<LruCache<PeerId, PeerEntry>>>);
+
+impl Default for TrafficTable {
+	fn default() -> Self {
+		Self(Arc::new(Mutex::new(LruCache::new(MAX_TRACKED_PEERS))))
+	}
+}
+
+impl TrafficTable {
+	/// Account for a message of `bytes` bytes received from `peer`.
+	pub(crate) fn note_received(&self, peer: PeerId, kind: &'static str, bytes: usize) {
+		let mut peers = self.0.lock();
+		entry(&mut peers, peer).entry(kind).or_default().0.note(bytes);
+	}
+
+	/// Account for a message of `bytes` bytes sent to each of `peers`.
+	pub(crate) fn note_sent(&self, peers: &[PeerId], kind: &'static str, bytes: usize) {
+		let mut table = self.0.lock();
+		for peer in peers {
+			entry(&mut table, *peer).entry(kind).or_default().1.note(bytes);
+		}
+	}
+
+	/// The traffic of the `limit` peers we received the most bytes from, in descending order.
+	///
+	/// Ties are broken by the number of bytes sent to the peers.
+	pub(crate) fn top(&self, limit: usize) -> Vec<PeerTraffic> {
+		let mut peers: Vec<_> = self
+			.0
+			.lock()
+			.iter()
+			.map(|(peer, entry)| {
+				let mut messages: Vec<_> = entry
+					.iter()
+					.map(|(kind, (received, sent))| MessageTraffic {
+						kind: *kind,
+						received: *received,
+						sent: *sent,
+					})
+					.collect();
+				messages
+					.sort_by_key(|m| (Reverse(m.received.bytes), Reverse(m.sent.bytes), m.kind));
+
+				let (received, sent) = messages.iter().fold(
+					(Traffic::default(), Traffic::default()),
+					|(received, sent), m| {
+						(received.saturating_add(m.received), sent.saturating_add(m.sent))
+					},
+				);
+				PeerTraffic { peer: *peer, received, sent, messages }
+			})
+			.collect();
+
+		peers.sort_by_key(|p| (Reverse(p.received.bytes), Reverse(p.sent.bytes)));
+		peers.truncate(limit);
+		peers
+	}
+}
+
+impl RequestTrafficObserver for TrafficTable {
+	fn on_request_received(&self, peer: PeerId, protocol: Protocol, bytes: usize) {
+		self.note_received(peer, request_kind(protocol), bytes);
+	}
+
+	fn on_response_sent(&self, peer: PeerId, protocol: Protocol, bytes: usize) {
+		self.note_sent(&[peer], response_kind(protocol), bytes);
+	}
+}
+
+/// Pass the response to a request we sent to `peer` on to the requester, accounting for it.
+///
+/// `peer` is `None` if the request could not be sent, in which case only the failure is passed
+/// on.
+pub(crate) async fn forward_response(
+	traffic: TrafficTable,
+	peer: Option<PeerId>,
+	protocol: Protocol,
+	response: oneshot::Receiver<Result<Vec<u8>, RequestFailure>>,
+	pending_response: ResponseSender,
+) {
+	// If the network drops the request, dropping `pending_response` cancels it for the
+	// requester as well.
+	if let Ok(response) = response.await {
+		if let (Some(peer), Ok(bytes)) = (peer, &response) {
+			traffic.note_received(peer, response_kind(protocol), bytes.len());
+		}
+		let _ = pending_response.send(response);
+	}
+}
+
+/// Get the entry of `peer`, marking it as the most recently active one.
+///
+/// If the table is full, the least recently active peer is forgotten to make room for a new one.
+fn entry(peers: &mut LruCache<PeerId, PeerEntry>, peer: PeerId) -> &mut PeerEntry {
+	if !peers.contains(&peer) {
+		peers.put(peer, PeerEntry::default());
+	}
+	peers.get_mut(&peer).expect("the entry was inserted above; qed")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn top_is_ordered_by_received_bytes() {
+		let table = TrafficTable::default();
+		let (a, b, c) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+		table.note_received(a, "approval-distribution/assignments", 100);
+		table.note_received(a, "approval-distribution/approvals", 300);
+		table.note_received(b, "approval-distribution/assignments", 1000);
+		table.note_sent(&[a, b, c], "validation/view-update", 10);
+
+		let top = table.top(2);
+		assert_eq!(top.len(), 2);
+		assert_eq!(top[0].peer, b);
+		assert_eq!(top[0].received, Traffic { messages: 1, bytes: 1000 });
+		assert_eq!(top[1].peer, a);
+		assert_eq!(top[1].received, Traffic { messages: 2, bytes: 400 });
+		assert_eq!(top[1].sent, Traffic { messages: 1, bytes: 10 });
+		assert_eq!(
+			top[1].messages.iter().map(|m| m.kind).collect::<Vec<_>>(),
+			vec![
+				"approval-distribution/approvals",
+				"approval-distribution/assignments",
+				"validation/view-update",
+			],
+		);
+	}
+
+	#[test]
+	fn requests_and_responses_are_accounted_in_both_directions() {
+		let table = TrafficTable::default();
+		let peer = PeerId::random();
+
+		// A request we served.
+		table.on_request_received(peer, Protocol::ChunkFetchingV1, 10);
+		table.on_response_sent(peer, Protocol::ChunkFetchingV1, 100);
+
+		// A request we sent, whose response is passed on to the requester.
+		table.note_sent(&[peer], request_kind(Protocol::PoVFetchingV1), 20);
+		let (response_tx, response_rx) = oneshot::channel();
+		let (pending_response, requester) = oneshot::channel();
+		response_tx.send(Ok(vec![0; 50])).unwrap();
+		futures::executor::block_on(forward_response(
+			table.clone(),
+			Some(peer),
+			Protocol::PoVFetchingV1,
+			response_rx,
+			pending_response,
+		));
+		assert_eq!(futures::executor::block_on(requester).unwrap().unwrap(), vec![0; 50]);
+
+		let top = table.top(1);
+		assert_eq!(top[0].received, Traffic { messages: 2, bytes: 60 });
+		assert_eq!(top[0].sent, Traffic { messages: 2, bytes: 120 });
+		let kinds = |f: fn(&MessageTraffic) -> Traffic| {
+			let mut kinds: Vec<_> =
+				top[0].messages.iter().filter(|m| f(m).messages > 0).map(|m| m.kind).collect();
+			kinds.sort();
+			kinds
+		};
+		assert_eq!(
+			kinds(|m| m.received),
+			vec!["request/chunk-fetching-v1", "response/pov-fetching-v1"],
+		);
+		assert_eq!(
+			kinds(|m| m.sent),
+			vec!["request/pov-fetching-v1", "response/chunk-fetching-v1"]
+		);
+	}
+
+	#[test]
+	fn least_recently_active_peer_is_forgotten() {
+		let table = TrafficTable::default();
+		let (quiet, loud) = (PeerId::random(), PeerId::random());
+
+		table.note_received(quiet, MALFORMED, 1);
+		table.note_received(loud, MALFORMED, 1);
+		for _ in 0..MAX_TRACKED_PEERS.get() - 2 {
+			table.note_received(PeerId::random(), MALFORMED, 2);
+		}
+		table.note_sent(&[loud], MALFORMED, 1);
+
+		let newcomer = PeerId::random();
+		table.note_received(newcomer, MALFORMED, 3);
+
+		let top = table.top(usize::MAX);
+		assert_eq!(top.len(), MAX_TRACKED_PEERS.get());
+		assert_eq!(top[0].peer, newcomer);
+		assert!(top.iter().any(|p| p.peer == loud));
+		assert!(top.iter().all(|p| p.peer != quiet));
+	}
+}
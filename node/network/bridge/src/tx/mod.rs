@@ -20,9 +20,11 @@ use super::*;
 use polkadot_node_network_protocol::{
 	peer_set::{CollationVersion, PeerSet, PeerSetProtocolNames, ValidationVersion},
 	request_response::ReqProtocolNames,
-	v1 as protocol_v1, PeerId, Versioned,
+	v1 as protocol_v1, v2 as protocol_v2, PeerId, Versioned, VersionedValidationProtocol,
 };
 
+use futures::{channel::oneshot, future::BoxFuture, stream::FuturesUnordered};
+
 use polkadot_node_subsystem::{
 	errors::SubsystemError,
 	messages::{NetworkBridgeTxMessage, ReportPeerMessage},
@@ -35,7 +37,7 @@ use polkadot_node_subsystem::{
 pub use polkadot_node_network_protocol::peer_set::{peer_sets_info, IsAuthority};
 use sc_network::ReputationChange;
 
-use crate::validator_discovery;
+use crate::{compression, traffic, validator_discovery};
 
 /// Actual interfacing to the network based on the `Network` trait.
 ///
@@ -58,6 +60,7 @@ pub struct NetworkBridgeTx<N, AD> {
 	metrics: Metrics,
 	req_protocol_names: ReqProtocolNames,
 	peerset_protocol_names: PeerSetProtocolNames,
+	shared: Shared,
 }
 
 impl<N, AD> NetworkBridgeTx<N, AD> {
@@ -65,12 +68,15 @@ impl<N, AD> NetworkBridgeTx<N, AD> {
 	///
 	/// This assumes that the network service has had the notifications protocol for the network
 	/// bridge already registered. See [`peers_sets_info`](peers_sets_info).
+	///
+	/// `shared` must be the state passed to the [`NetworkBridgeRx`] of the same network.
 	pub fn new(
 		network_service: N,
 		authority_discovery_service: AD,
 		metrics: Metrics,
 		req_protocol_names: ReqProtocolNames,
 		peerset_protocol_names: PeerSetProtocolNames,
+		shared: Shared,
 	) -> Self {
 		Self {
 			network_service,
@@ -78,6 +84,7 @@ impl<N, AD> NetworkBridgeTx<N, AD> {
 			metrics,
 			req_protocol_names,
 			peerset_protocol_names,
+			shared,
 		}
 	}
 }
@@ -104,6 +111,7 @@ async fn handle_subsystem_messages<Context, N, AD>(
 	metrics: Metrics,
 	req_protocol_names: ReqProtocolNames,
 	peerset_protocol_names: PeerSetProtocolNames,
+	shared: Shared,
 ) -> Result<(), Error>
 where
 	N: Network,
@@ -111,25 +119,31 @@ where
 {
 	let mut validator_discovery =
 		validator_discovery::Service::<N, AD>::new(peerset_protocol_names.clone());
+	let mut pending_responses = FuturesUnordered::new();
 
 	loop {
-		match ctx.recv().fuse().await? {
-			FromOrchestra::Signal(OverseerSignal::Conclude) => return Ok(()),
-			FromOrchestra::Signal(_) => { /* handled by incoming */ },
-			FromOrchestra::Communication { msg } => {
-				(network_service, authority_discovery_service) =
-					handle_incoming_subsystem_communication(
-						&mut ctx,
-						network_service,
-						&mut validator_discovery,
-						authority_discovery_service.clone(),
-						msg,
-						&metrics,
-						&req_protocol_names,
-						&peerset_protocol_names,
-					)
-					.await;
+		futures::select! {
+			msg = ctx.recv().fuse() => match msg? {
+				FromOrchestra::Signal(OverseerSignal::Conclude) => return Ok(()),
+				FromOrchestra::Signal(_) => { /* handled by incoming */ },
+				FromOrchestra::Communication { msg } => {
+					(network_service, authority_discovery_service) =
+						handle_incoming_subsystem_communication(
+							&mut ctx,
+							network_service,
+							&mut validator_discovery,
+							authority_discovery_service.clone(),
+							msg,
+							&metrics,
+							&req_protocol_names,
+							&peerset_protocol_names,
+							&shared,
+							&mut pending_responses,
+						)
+						.await;
+				},
 			},
+			() = pending_responses.select_next_some() => {},
 		}
 	}
 }
@@ -144,6 +158,8 @@ async fn handle_incoming_subsystem_communication<Context, N, AD>(
 	metrics: &Metrics,
 	req_protocol_names: &ReqProtocolNames,
 	peerset_protocol_names: &PeerSetProtocolNames,
+	shared: &Shared,
+	pending_responses: &mut FuturesUnordered<BoxFuture<'static, ()>>,
 ) -> (N, AD)
 where
 	N: Network,
@@ -188,15 +204,14 @@ where
 				num_messages = 1usize,
 			);
 
-			match msg {
-				Versioned::V1(msg) => send_validation_message_v1(
-					&mut network_service,
-					peers,
-					peerset_protocol_names,
-					WireMessage::ProtocolMessage(msg),
-					&metrics,
-				),
-			}
+			send_validation_message(
+				&mut network_service,
+				peers,
+				peerset_protocol_names,
+				shared,
+				msg,
+				&metrics,
+			);
 		},
 		NetworkBridgeTxMessage::SendValidationMessages(msgs) => {
 			gum::trace!(
@@ -206,15 +221,14 @@ where
 			);
 
 			for (peers, msg) in msgs {
-				match msg {
-					Versioned::V1(msg) => send_validation_message_v1(
-						&mut network_service,
-						peers,
-						peerset_protocol_names,
-						WireMessage::ProtocolMessage(msg),
-						&metrics,
-					),
-				}
+				send_validation_message(
+					&mut network_service,
+					peers,
+					peerset_protocol_names,
+					shared,
+					msg,
+					&metrics,
+				);
 			}
 		},
 		NetworkBridgeTxMessage::SendCollationMessage(peers, msg) => {
@@ -225,10 +239,11 @@ where
 			);
 
 			match msg {
-				Versioned::V1(msg) => send_collation_message_v1(
+				Versioned::V1(msg) | Versioned::V2(msg) => send_collation_message_v1(
 					&mut network_service,
 					peers,
 					peerset_protocol_names,
+					shared,
 					WireMessage::ProtocolMessage(msg),
 					&metrics,
 				),
@@ -243,10 +258,11 @@ where
 
 			for (peers, msg) in msgs {
 				match msg {
-					Versioned::V1(msg) => send_collation_message_v1(
+					Versioned::V1(msg) | Versioned::V2(msg) => send_collation_message_v1(
 						&mut network_service,
 						peers,
 						peerset_protocol_names,
+						shared,
 						WireMessage::ProtocolMessage(msg),
 						&metrics,
 					),
@@ -260,8 +276,13 @@ where
 				num_requests = %reqs.len(),
 			);
 
-			for req in reqs {
-				network_service
+			for mut req in reqs {
+				let protocol = req.get_protocol();
+				// Responses pass through us, so that they can be accounted for.
+				let (response_tx, response_rx) = oneshot::channel();
+				let pending_response = req.replace_response_sender(response_tx);
+
+				let sent = network_service
 					.start_request(
 						&mut authority_discovery_service,
 						req,
@@ -269,6 +290,21 @@ where
 						if_disconnected,
 					)
 					.await;
+
+				if let Some((peer, size)) = sent {
+					shared.traffic().note_sent(&[peer], traffic::request_kind(protocol), size);
+				}
+
+				pending_responses.push(
+					traffic::forward_response(
+						shared.traffic().clone(),
+						sent.map(|(peer, _)| peer),
+						protocol,
+						response_rx,
+						pending_response,
+					)
+					.boxed(),
+				);
 			}
 		},
 		NetworkBridgeTxMessage::ConnectToValidators { validator_ids, peer_set, failed } => {
@@ -311,6 +347,9 @@ where
 				.await;
 			return (network_service, authority_discovery_service)
 		},
+		NetworkBridgeTxMessage::InspectPeerTraffic(limit, tx) => {
+			let _ = tx.send(shared.traffic().top(limit));
+		},
 	}
 	(network_service, authority_discovery_service)
 }
@@ -330,6 +369,7 @@ where
 		metrics,
 		req_protocol_names,
 		peerset_protocol_names,
+		shared,
 	} = bridge;
 
 	handle_subsystem_messages(
@@ -339,34 +379,73 @@ where
 		metrics,
 		req_protocol_names,
 		peerset_protocol_names,
+		shared,
 	)
 	.await?;
 
 	Ok(())
 }
 
-fn send_validation_message_v1(
+fn send_validation_message(
 	net: &mut impl Network,
 	peers: Vec<PeerId>,
 	protocol_names: &PeerSetProtocolNames,
-	message: WireMessage<protocol_v1::ValidationProtocol>,
+	shared: &Shared,
+	message: VersionedValidationProtocol,
 	metrics: &Metrics,
 ) {
-	send_message(
-		net,
-		peers,
-		PeerSet::Validation,
-		ValidationVersion::V1.into(),
-		protocol_names,
-		message,
-		metrics,
-	);
+	for (version, peers) in shared.peers_by_version(PeerSet::Validation, peers) {
+		let message_version = compression::message_version(PeerSet::Validation, version);
+		if message_version == ValidationVersion::V2.into() {
+			let message = match message.clone() {
+				Versioned::V1(message) => protocol_v2::ValidationProtocol::from(message),
+				Versioned::V2(message) => message,
+			};
+			send_message(
+				net,
+				peers,
+				PeerSet::Validation,
+				version,
+				protocol_names,
+				WireMessage::ProtocolMessage(message),
+				metrics,
+				shared.traffic(),
+			);
+		} else {
+			let message = match message.clone() {
+				Versioned::V1(message) => message,
+				Versioned::V2(message) => match message.into_v1() {
+					Some(message) => message,
+					None => {
+						gum::trace!(
+							target: LOG_TARGET,
+							?version,
+							num_peers = peers.len(),
+							"Message can't be represented for peers on an older protocol version",
+						);
+						continue
+					},
+				},
+			};
+			send_message(
+				net,
+				peers,
+				PeerSet::Validation,
+				version,
+				protocol_names,
+				WireMessage::ProtocolMessage(message),
+				metrics,
+				shared.traffic(),
+			);
+		}
+	}
 }
 
 fn send_collation_message_v1(
 	net: &mut impl Network,
 	peers: Vec<PeerId>,
 	protocol_names: &PeerSetProtocolNames,
+	shared: &Shared,
 	message: WireMessage<protocol_v1::CollationProtocol>,
 	metrics: &Metrics,
 ) {
@@ -378,5 +457,6 @@ fn send_collation_message_v1(
 		protocol_names,
 		message,
 		metrics,
+		shared.traffic(),
 	);
 }
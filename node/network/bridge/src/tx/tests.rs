@@ -29,11 +29,14 @@ use polkadot_node_network_protocol::{
 	request_response::{outgoing::Requests, ReqProtocolNames},
 	ObservedRole, Versioned,
 };
+use polkadot_node_primitives::approval::{
+	IndirectSignedApprovalVote, IndirectSignedApprovalVoteV2,
+};
 use polkadot_node_subsystem::{FromOrchestra, OverseerSignal};
 use polkadot_node_subsystem_test_helpers::TestSubsystemContextHandle;
 use polkadot_node_subsystem_util::metered;
-use polkadot_primitives::{AuthorityDiscoveryId, Hash};
-use polkadot_primitives_test_helpers::dummy_collator_signature;
+use polkadot_primitives::{AuthorityDiscoveryId, Hash, ValidatorIndex};
+use polkadot_primitives_test_helpers::{dummy_collator_signature, dummy_signature};
 use sc_network::Multiaddr;
 use sp_keyring::Sr25519Keyring;
 
@@ -113,7 +116,8 @@ impl Network for TestNetwork {
 		_: Requests,
 		_: &ReqProtocolNames,
 		_: IfDisconnected,
-	) {
+	) -> Option<(PeerId, usize)> {
+		None
 	}
 
 	fn report_peer(&self, who: PeerId, rep: ReputationChange) {
@@ -188,6 +192,7 @@ type VirtualOverseer = TestSubsystemContextHandle<NetworkBridgeTxMessage>;
 struct TestHarness {
 	network_handle: TestNetworkHandle,
 	virtual_overseer: VirtualOverseer,
+	shared: Shared,
 }
 
 fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarness) -> T) {
@@ -202,19 +207,21 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarne
 	let (context, virtual_overseer) =
 		polkadot_node_subsystem_test_helpers::make_subsystem_context(pool);
 
+	let shared = Shared::default();
 	let bridge_out = NetworkBridgeTx::new(
 		network,
 		discovery,
 		Metrics(None),
 		req_protocol_names,
 		peerset_protocol_names,
+		shared.clone(),
 	);
 
 	let network_bridge_out_fut = run_network_out(bridge_out, context)
 		.map_err(|e| panic!("bridge-out subsystem execution failed {:?}", e))
 		.map(|_| ());
 
-	let test_fut = test(TestHarness { network_handle, virtual_overseer });
+	let test_fut = test(TestHarness { network_handle, virtual_overseer, shared });
 
 	futures::pin_mut!(test_fut);
 	futures::pin_mut!(network_bridge_out_fut);
@@ -231,7 +238,7 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarne
 #[test]
 fn send_messages_to_peers() {
 	test_harness(|test_harness| async move {
-		let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+		let TestHarness { mut network_handle, mut virtual_overseer, .. } = test_harness;
 
 		let peer = PeerId::random();
 
@@ -322,3 +329,204 @@ fn send_messages_to_peers() {
 		virtual_overseer
 	});
 }
+
+#[test]
+fn compresses_messages_for_peers_on_compressing_version() {
+	test_harness(|test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer, shared } = test_harness;
+
+		let peer_v1 = PeerId::random();
+		let peer_v2 = PeerId::random();
+		{
+			let mut shared = shared.0.lock();
+			shared.validation_peers.insert(
+				peer_v1,
+				PeerData { view: View::default(), version: ValidationVersion::V1.into() },
+			);
+			shared.validation_peers.insert(
+				peer_v2,
+				PeerData { view: View::default(), version: ValidationVersion::V2.into() },
+			);
+		}
+
+		let votes = (0..20)
+			.map(|i| IndirectSignedApprovalVote {
+				block_hash: Hash::repeat_byte(1),
+				candidate_index: i,
+				validator: ValidatorIndex(i),
+				signature: dummy_signature(),
+			})
+			.collect();
+		let message_v1 = protocol_v1::ValidationProtocol::ApprovalDistribution(
+			protocol_v1::ApprovalDistributionMessage::Approvals(votes),
+		);
+		let encoded = WireMessage::ProtocolMessage(message_v1.clone()).encode();
+
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeTxMessage::SendValidationMessage(
+					vec![peer_v1, peer_v2],
+					Versioned::V1(message_v1),
+				),
+			})
+			.timeout(TIMEOUT)
+			.await
+			.expect("Timeout does not occur");
+
+		for _ in 0..2 {
+			let action = network_handle
+				.next_network_action()
+				.timeout(TIMEOUT)
+				.await
+				.expect("Timeout does not occur");
+			match action {
+				NetworkAction::WriteNotification(peer, PeerSet::Validation, wire)
+					if peer == peer_v1 =>
+					assert_eq!(wire, encoded),
+				NetworkAction::WriteNotification(peer, PeerSet::Validation, wire)
+					if peer == peer_v2 =>
+				{
+					assert!(wire.len() < encoded.len());
+					assert_eq!(
+						sp_maybe_compressed_blob::decompress(&wire, encoded.len()).unwrap(),
+						encoded,
+					);
+				},
+				action => panic!("Unexpected network action: {:?}", action),
+			}
+		}
+		virtual_overseer
+	});
+}
+
+#[test]
+fn v2_messages_are_converted_for_older_peers() {
+	test_harness(|test_harness| async move {
+		let TestHarness { mut network_handle, mut virtual_overseer, shared } = test_harness;
+
+		let peer_v1 = PeerId::random();
+		let peer_v3 = PeerId::random();
+		{
+			let mut shared = shared.0.lock();
+			shared.validation_peers.insert(
+				peer_v1,
+				PeerData { view: View::default(), version: ValidationVersion::V1.into() },
+			);
+			shared.validation_peers.insert(
+				peer_v3,
+				PeerData { view: View::default(), version: ValidationVersion::V3.into() },
+			);
+		}
+
+		let single = IndirectSignedApprovalVote {
+			block_hash: Hash::repeat_byte(1),
+			candidate_index: 0,
+			validator: ValidatorIndex(0),
+			signature: dummy_signature(),
+		};
+		let multiple = IndirectSignedApprovalVoteV2 {
+			block_hash: Hash::repeat_byte(1),
+			candidate_indices: vec![1, 2].try_into().unwrap(),
+			validator: ValidatorIndex(1),
+			signature: dummy_signature(),
+		};
+		let message_v2 = protocol_v2::ValidationProtocol::ApprovalDistribution(
+			protocol_v2::ApprovalDistributionMessage::Approvals(vec![
+				single.clone().into(),
+				multiple,
+			]),
+		);
+		let encoded_v2 = WireMessage::ProtocolMessage(message_v2.clone()).encode();
+		let encoded_v1 =
+			WireMessage::ProtocolMessage(protocol_v1::ValidationProtocol::ApprovalDistribution(
+				protocol_v1::ApprovalDistributionMessage::Approvals(vec![single]),
+			))
+			.encode();
+
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeTxMessage::SendValidationMessage(
+					vec![peer_v1, peer_v3],
+					Versioned::V2(message_v2),
+				),
+			})
+			.timeout(TIMEOUT)
+			.await
+			.expect("Timeout does not occur");
+
+		for _ in 0..2 {
+			let action = network_handle
+				.next_network_action()
+				.timeout(TIMEOUT)
+				.await
+				.expect("Timeout does not occur");
+			match action {
+				NetworkAction::WriteNotification(peer, PeerSet::Validation, wire)
+					if peer == peer_v1 =>
+					assert_eq!(wire, encoded_v1),
+				NetworkAction::WriteNotification(peer, PeerSet::Validation, wire)
+					if peer == peer_v3 =>
+					assert_eq!(
+						sp_maybe_compressed_blob::decompress(&wire, encoded_v2.len() * 2).unwrap(),
+						encoded_v2,
+					),
+				action => panic!("Unexpected network action: {:?}", action),
+			}
+		}
+
+		// Nothing of this can be understood by `v1` peers.
+		let only_multiple = protocol_v2::ValidationProtocol::ApprovalDistribution(
+			protocol_v2::ApprovalDistributionMessage::Approvals(vec![
+				IndirectSignedApprovalVoteV2 {
+					block_hash: Hash::repeat_byte(2),
+					candidate_indices: vec![0, 1].try_into().unwrap(),
+					validator: ValidatorIndex(2),
+					signature: dummy_signature(),
+				},
+			]),
+		);
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeTxMessage::SendValidationMessage(
+					vec![peer_v1, peer_v3],
+					Versioned::V2(only_multiple),
+				),
+			})
+			.timeout(TIMEOUT)
+			.await
+			.expect("Timeout does not occur");
+
+		// Followed by something `v1` peers understand, which must be the only thing they get.
+		let follow_up = protocol_v1::ValidationProtocol::ApprovalDistribution(
+			protocol_v1::ApprovalDistributionMessage::Approvals(Vec::new()),
+		);
+		let encoded_follow_up = WireMessage::ProtocolMessage(follow_up.clone()).encode();
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: NetworkBridgeTxMessage::SendValidationMessage(
+					vec![peer_v1],
+					Versioned::V1(follow_up),
+				),
+			})
+			.timeout(TIMEOUT)
+			.await
+			.expect("Timeout does not occur");
+
+		for _ in 0..2 {
+			let action = network_handle
+				.next_network_action()
+				.timeout(TIMEOUT)
+				.await
+				.expect("Timeout does not occur");
+			match action {
+				NetworkAction::WriteNotification(peer, PeerSet::Validation, wire)
+					if peer == peer_v1 =>
+					assert_eq!(wire, encoded_follow_up),
+				NetworkAction::WriteNotification(peer, PeerSet::Validation, _)
+					if peer == peer_v3 => {},
+				action => panic!("Unexpected network action: {:?}", action),
+			}
+		}
+		virtual_overseer
+	});
+}
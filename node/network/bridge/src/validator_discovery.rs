@@ -246,7 +246,8 @@ mod tests {
 			_: Requests,
 			_: &ReqProtocolNames,
 			_: IfDisconnected,
-		) {
+		) -> Option<(PeerId, usize)> {
+			None
 		}
 
 		fn report_peer(&self, _: PeerId, _: ReputationChange) {
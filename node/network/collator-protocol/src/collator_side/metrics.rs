@@ -15,26 +15,30 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
+use polkadot_primitives::Id as ParaId;
 
 #[derive(Clone, Default)]
 pub struct Metrics(Option<MetricsInner>);
 
 impl Metrics {
-	pub fn on_advertisment_made(&self) {
+	pub fn on_advertisment_made(&self, para_id: ParaId) {
 		if let Some(metrics) = &self.0 {
-			metrics.advertisements_made.inc();
+			metrics.advertisements_made.with_label_values(&[&para_id.to_string()]).inc();
 		}
 	}
 
-	pub fn on_collation_sent_requested(&self) {
+	pub fn on_collation_sent_requested(&self, para_id: ParaId) {
 		if let Some(metrics) = &self.0 {
-			metrics.collations_send_requested.inc();
+			metrics
+				.collations_send_requested
+				.with_label_values(&[&para_id.to_string()])
+				.inc();
 		}
 	}
 
-	pub fn on_collation_sent(&self) {
+	pub fn on_collation_sent(&self, para_id: ParaId) {
 		if let Some(metrics) = &self.0 {
-			metrics.collations_sent.inc();
+			metrics.collations_sent.with_label_values(&[&para_id.to_string()]).inc();
 		}
 	}
 
@@ -56,9 +60,9 @@ impl Metrics {
 
 #[derive(Clone)]
 struct MetricsInner {
-	advertisements_made: prometheus::Counter<prometheus::U64>,
-	collations_sent: prometheus::Counter<prometheus::U64>,
-	collations_send_requested: prometheus::Counter<prometheus::U64>,
+	advertisements_made: prometheus::CounterVec<prometheus::U64>,
+	collations_sent: prometheus::CounterVec<prometheus::U64>,
+	collations_send_requested: prometheus::CounterVec<prometheus::U64>,
 	process_msg: prometheus::Histogram,
 	collation_distribution_time: prometheus::HistogramVec,
 }
@@ -69,23 +73,32 @@ impl metrics::Metrics for Metrics {
 	) -> std::result::Result<Self, prometheus::PrometheusError> {
 		let metrics = MetricsInner {
 			advertisements_made: prometheus::register(
-				prometheus::Counter::new(
-					"polkadot_parachain_collation_advertisements_made_total",
-					"A number of collation advertisements sent to validators.",
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collation_advertisements_made_total",
+						"A number of collation advertisements sent to validators.",
+					),
+					&["para_id"],
 				)?,
 				registry,
 			)?,
 			collations_send_requested: prometheus::register(
-				prometheus::Counter::new(
-					"polkadot_parachain_collations_sent_requested_total",
-					"A number of collations requested to be sent to validators.",
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collations_sent_requested_total",
+						"A number of collations requested to be sent to validators.",
+					),
+					&["para_id"],
 				)?,
 				registry,
 			)?,
 			collations_sent: prometheus::register(
-				prometheus::Counter::new(
-					"polkadot_parachain_collations_sent_total",
-					"A number of collations sent to validators.",
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collations_sent_total",
+						"A number of collations sent to validators.",
+					),
+					&["para_id"],
 				)?,
 				registry,
 			)?,
@@ -31,7 +31,7 @@ use polkadot_node_network_protocol::{
 	request_response::{
 		incoming::{self, OutgoingResponse},
 		v1::{self as request_v1, CollationFetchingRequest, CollationFetchingResponse},
-		IncomingRequest, IncomingRequestReceiver,
+		IncomingRequest, QuotaReceiver,
 	},
 	v1 as protocol_v1, OurView, PeerId, UnifiedReputationChange as Rep, Versioned, View,
 };
@@ -56,7 +56,7 @@ use polkadot_primitives::{
 use super::LOG_TARGET;
 use crate::{
 	error::{log_error, Error, FatalError, Result},
-	modify_reputation,
+	modify_reputation, CollatorKeys,
 };
 use fatality::Split;
 
@@ -99,6 +99,8 @@ const RECONNECT_POLL: Duration = Duration::from_secs(1);
 /// It keeps track to which validators we advertised our collation.
 #[derive(Debug)]
 struct ValidatorGroup {
+	/// All [`ValidatorId`]'s of the group.
+	validators: Vec<AuthorityDiscoveryId>,
 	/// All [`ValidatorId`]'s of the current group to that we advertised our collation.
 	advertised_to: HashSet<AuthorityDiscoveryId>,
 }
@@ -107,8 +109,13 @@ impl ValidatorGroup {
 	/// Create a new `ValidatorGroup`
 	///
 	/// without any advertisements.
-	fn new() -> Self {
-		Self { advertised_to: HashSet::new() }
+	fn new(validators: Vec<AuthorityDiscoveryId>) -> Self {
+		Self { validators, advertised_to: HashSet::new() }
+	}
+
+	/// Returns `true` if any of the given discovery ids belongs to this group.
+	fn contains_any(&self, authority_ids: &HashSet<AuthorityDiscoveryId>) -> bool {
+		self.validators.iter().any(|v| authority_ids.contains(v))
 	}
 
 	/// Returns `true` if we should advertise our collation to the given peer.
@@ -185,6 +192,7 @@ struct WaitingCollationFetches {
 }
 
 struct CollationSendResult {
+	para_id: ParaId,
 	relay_parent: Hash,
 	peer_id: PeerId,
 	timed_out: bool,
@@ -193,16 +201,65 @@ struct CollationSendResult {
 type ActiveCollationFetches =
 	FuturesUnordered<Pin<Box<dyn Future<Output = CollationSendResult> + Send + 'static>>>;
 
+/// The state kept for every para we are collating on.
+struct PerParaState {
+	/// The collator pair we declare ourselves with for this para.
+	collator_pair: CollatorPair,
+
+	/// Possessed collations.
+	///
+	/// We will keep up to one local collation per relay-parent.
+	collations: HashMap<Hash, Collation>,
+
+	/// Our validator groups per active leaf.
+	our_validators_groups: HashMap<Hash, ValidatorGroup>,
+
+	/// All collation fetching requests that are still waiting to be answered.
+	///
+	/// They are stored per relay parent, when our view changes and the relay parent moves out, we will cancel the fetch
+	/// request.
+	waiting_collation_fetches: HashMap<Hash, WaitingCollationFetches>,
+}
+
+impl PerParaState {
+	fn new(collator_pair: CollatorPair) -> Self {
+		Self {
+			collator_pair,
+			collations: Default::default(),
+			our_validators_groups: Default::default(),
+			waiting_collation_fetches: Default::default(),
+		}
+	}
+
+	/// Returns `true` if the validator with the given discovery ids is in one of our
+	/// validator groups.
+	fn is_assigned_validator(&self, authority_ids: &HashSet<AuthorityDiscoveryId>) -> bool {
+		self.our_validators_groups.values().any(|g| g.contains_any(authority_ids))
+	}
+}
+
 struct State {
 	/// Our network peer id.
 	local_peer_id: PeerId,
 
-	/// Our collator pair.
-	collator_pair: CollatorPair,
+	/// The keys we declare ourselves with, per para.
+	collator_keys: CollatorKeys,
+
+	/// The paras this collator is collating on.
+	/// Starts empty and a para is added with every `CollateOn` message.
+	collating_on: HashMap<ParaId, PerParaState>,
 
-	/// The para this collator is collating on.
-	/// Starts as `None` and is updated with every `CollateOn` message.
-	collating_on: Option<ParaId>,
+	/// The para we declared ourselves as a collator of to each peer.
+	///
+	/// Advertisements carry no para id, so a peer only ever learns about collations of
+	/// the para we declared to it.
+	declared_to: HashMap<PeerId, ParaId>,
+
+	/// Peers that got assigned to another para after we declared ourselves to them.
+	///
+	/// Validators accept a single `Declare` per connection, so these peers are disconnected
+	/// and declared to with the key of the para they are assigned to now once they reconnect.
+	reassigned: HashMap<PeerId, ParaId>,
 
 	/// Track all active peers and their views
 	/// to determine what is relevant to them.
@@ -214,17 +271,9 @@ struct State {
 	/// Span per relay parent.
 	span_per_relay_parent: HashMap<Hash, PerLeafSpan>,
 
-	/// Possessed collations.
-	///
-	/// We will keep up to one local collation per relay-parent.
-	collations: HashMap<Hash, Collation>,
-
 	/// The result senders per collation.
 	collation_result_senders: HashMap<CandidateHash, oneshot::Sender<CollationSecondedSignal>>,
 
-	/// Our validator groups per active leaf.
-	our_validators_groups: HashMap<Hash, ValidatorGroup>,
-
 	/// The mapping from [`PeerId`] to [`HashSet<AuthorityDiscoveryId>`]. This is filled over time as we learn the [`PeerId`]'s
 	/// by `PeerConnected` events.
 	peer_ids: HashMap<PeerId, HashSet<AuthorityDiscoveryId>>,
@@ -239,12 +288,6 @@ struct State {
 	/// Metrics.
 	metrics: Metrics,
 
-	/// All collation fetching requests that are still waiting to be answered.
-	///
-	/// They are stored per relay parent, when our view changes and the relay parent moves out, we will cancel the fetch
-	/// request.
-	waiting_collation_fetches: HashMap<Hash, WaitingCollationFetches>,
-
 	/// Active collation fetches.
 	///
 	/// Each future returns the relay parent of the finished collation fetch.
@@ -259,25 +302,24 @@ impl State {
 	/// state fields to their default values (i.e. empty).
 	fn new(
 		local_peer_id: PeerId,
-		collator_pair: CollatorPair,
+		collator_keys: CollatorKeys,
 		metrics: Metrics,
 		reputation: ReputationAggregator,
 	) -> State {
 		State {
 			local_peer_id,
-			collator_pair,
+			collator_keys,
 			metrics,
 			collating_on: Default::default(),
+			declared_to: Default::default(),
+			reassigned: Default::default(),
 			peer_views: Default::default(),
 			view: Default::default(),
 			span_per_relay_parent: Default::default(),
-			collations: Default::default(),
 			collation_result_senders: Default::default(),
-			our_validators_groups: Default::default(),
 			peer_ids: Default::default(),
 			validator_groups_buf: ValidatorGroupsBuffer::with_capacity(VALIDATORS_BUFFER_CAPACITY),
 			last_connected_at: None,
-			waiting_collation_fetches: Default::default(),
 			active_collation_fetches: Default::default(),
 			reputation,
 		}
@@ -291,6 +333,27 @@ impl State {
 			.map(|(peer, _)| *peer)
 			.collect()
 	}
+
+	/// The para to declare ourselves as a collator of to the given peer.
+	///
+	/// When collating on a single para this is always that para. Otherwise it is the
+	/// para the peer was reassigned to or the para whose validator groups the peer belongs
+	/// to, if any.
+	fn para_to_declare(&self, peer: &PeerId) -> Option<ParaId> {
+		if let Some(para_id) = self.reassigned.get(peer) {
+			return Some(*para_id)
+		}
+
+		if self.collating_on.len() == 1 {
+			return self.collating_on.keys().next().copied()
+		}
+
+		let authority_ids = self.peer_ids.get(peer)?;
+		self.collating_on
+			.iter()
+			.find(|(_, per_para)| per_para.is_assigned_validator(authority_ids))
+			.map(|(para_id, _)| *para_id)
+	}
 }
 
 /// Distribute a collation.
@@ -326,15 +389,37 @@ async fn distribute_collation<Context>(
 	}
 
 	// We have already seen collation for this relay parent.
-	if state.collations.contains_key(&relay_parent) {
+	if state
+		.collating_on
+		.get(&id)
+		.map_or(false, |per_para| per_para.collations.contains_key(&relay_parent))
+	{
 		gum::debug!(
 			target: LOG_TARGET,
+			para_id = %id,
 			?relay_parent,
 			"Already seen collation for this relay parent",
 		);
 		return Ok(())
 	}
 
+	// Validators drop collations signed with another key than the one we declared with, so
+	// there is no point in advertising them.
+	if let Some(per_para) = state.collating_on.get(&id) {
+		let declared = per_para.collator_pair.public();
+		if receipt.descriptor.collator != declared {
+			gum::error!(
+				target: LOG_TARGET,
+				para_id = %id,
+				collator = ?receipt.descriptor.collator,
+				?declared,
+				"Collation is signed with another key than the one declared for the para, \
+				check the key of the collation generation config",
+			);
+			return Ok(())
+		}
+	}
+
 	// Determine which core the para collated-on is assigned to.
 	// If it is not scheduled then ignore the message.
 	let (our_core, num_cores) = match determine_core(ctx.sender(), id, relay_parent).await? {
@@ -391,20 +476,23 @@ async fn distribute_collation<Context>(
 	// Update a set of connected validators if necessary.
 	state.last_connected_at = connect_to_validators(ctx, &state.validator_groups_buf).await;
 
-	state.our_validators_groups.insert(relay_parent, ValidatorGroup::new());
-
 	if let Some(result_sender) = result_sender {
 		state.collation_result_senders.insert(candidate_hash, result_sender);
 	}
 
-	state
-		.collations
-		.insert(relay_parent, Collation { receipt, pov, status: CollationStatus::Created });
+	if let Some(per_para) = state.collating_on.get_mut(&id) {
+		per_para
+			.our_validators_groups
+			.insert(relay_parent, ValidatorGroup::new(validators));
+		per_para
+			.collations
+			.insert(relay_parent, Collation { receipt, pov, status: CollationStatus::Created });
+	}
 
 	let interested = state.peers_interested_in_leaf(&relay_parent);
 	// Make sure already connected peers get collations:
 	for peer_id in interested {
-		advertise_collation(ctx, state, relay_parent, peer_id).await;
+		advertise_collation(ctx, state, id, relay_parent, peer_id).await;
 	}
 
 	Ok(())
@@ -479,23 +567,71 @@ async fn determine_our_validators<Context>(
 }
 
 /// Issue a `Declare` collation message to the given `peer`.
+///
+/// Does nothing if we already declared ourselves to the peer or if there is no para
+/// to declare for it.
 #[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
 async fn declare<Context>(ctx: &mut Context, state: &mut State, peer: PeerId) {
-	let declare_signature_payload = protocol_v1::declare_signature_payload(&state.local_peer_id);
+	if state.declared_to.contains_key(&peer) {
+		return
+	}
 
-	if let Some(para_id) = state.collating_on {
-		let wire_message = protocol_v1::CollatorProtocolMessage::Declare(
-			state.collator_pair.public(),
-			para_id,
-			state.collator_pair.sign(&declare_signature_payload),
-		);
+	match state.para_to_declare(&peer) {
+		Some(para_id) => declare_for(ctx, state, peer, para_id).await,
+		None => gum::trace!(
+			target: LOG_TARGET,
+			peer_id = %peer,
+			"Not declaring to peer as it is not assigned to any of our paras",
+		),
+	}
+}
 
-		ctx.send_message(NetworkBridgeTxMessage::SendCollationMessage(
-			vec![peer],
-			Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(wire_message)),
-		))
-		.await;
+/// Issue a `Declare` collation message for `para_id` to the given `peer`.
+///
+/// Does nothing if we already declared ourselves to the peer for this para. If we declared
+/// ourselves for another para, the peer got rotated to a group of `para_id`: it is
+/// disconnected, so that we declare ourselves anew once it reconnects.
+#[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
+async fn declare_for<Context>(ctx: &mut Context, state: &mut State, peer: PeerId, para_id: ParaId) {
+	match state.declared_to.get(&peer) {
+		Some(declared) if *declared == para_id => return,
+		Some(declared) => {
+			if state.reassigned.insert(peer, para_id) != Some(para_id) {
+				gum::debug!(
+					target: LOG_TARGET,
+					peer_id = %peer,
+					declared_para_id = %declared,
+					para_id = %para_id,
+					"Validator was assigned to another para, reconnecting to declare again",
+				);
+				ctx.send_message(NetworkBridgeTxMessage::DisconnectPeer(peer, PeerSet::Collation))
+					.await;
+			}
+			return
+		},
+		None => {},
 	}
+
+	let collator_pair = match state.collating_on.get(&para_id) {
+		Some(per_para) => &per_para.collator_pair,
+		None => return,
+	};
+
+	let declare_signature_payload = protocol_v1::declare_signature_payload(&state.local_peer_id);
+	let wire_message = protocol_v1::CollatorProtocolMessage::Declare(
+		collator_pair.public(),
+		para_id,
+		collator_pair.sign(&declare_signature_payload),
+	);
+
+	ctx.send_message(NetworkBridgeTxMessage::SendCollationMessage(
+		vec![peer],
+		Versioned::V1(protocol_v1::CollationProtocol::CollatorProtocol(wire_message)),
+	))
+	.await;
+
+	state.reassigned.remove(&peer);
+	state.declared_to.insert(peer, para_id);
 }
 
 /// Updates a set of connected validators based on their advertisement-bits
@@ -524,27 +660,74 @@ async fn connect_to_validators<Context>(
 	(!is_disconnect).then_some(Instant::now())
 }
 
-/// Advertise collation to the given `peer`.
+/// Advertise collations of all our paras at `relay_parent` to the given `peer`.
+#[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
+async fn advertise_collations<Context>(
+	ctx: &mut Context,
+	state: &mut State,
+	relay_parent: Hash,
+	peer: PeerId,
+) {
+	let paras: Vec<ParaId> = state.collating_on.keys().copied().collect();
+	for para_id in paras {
+		advertise_collation(ctx, state, para_id, relay_parent, peer).await;
+	}
+}
+
+/// Advertise collation of `para_id` to the given `peer`.
 ///
 /// This will only advertise a collation if there exists one for the given `relay_parent` and the given `peer` is
-/// set as validator for our para at the given `relay_parent`.
+/// set as validator for the para at the given `relay_parent`.
+///
+/// Peers we have not declared ourselves to yet are declared to first. Peers we declared
+/// ourselves to as a collator of another para are skipped.
 #[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
 async fn advertise_collation<Context>(
 	ctx: &mut Context,
 	state: &mut State,
+	para_id: ParaId,
 	relay_parent: Hash,
 	peer: PeerId,
 ) {
-	let should_advertise = state
+	let is_assigned = match (state.collating_on.get(&para_id), state.peer_ids.get(&peer)) {
+		(Some(per_para), Some(authority_ids)) => per_para
+			.our_validators_groups
+			.get(&relay_parent)
+			.map_or(false, |g| g.contains_any(authority_ids)),
+		_ => false,
+	};
+
+	if is_assigned {
+		declare_for(ctx, state, peer, para_id).await;
+	}
+
+	if state.declared_to.get(&peer).map_or(true, |declared| *declared != para_id) {
+		gum::trace!(
+			target: LOG_TARGET,
+			para_id = %para_id,
+			?relay_parent,
+			peer_id = %peer,
+			"Not advertising collation as we are not declared to the peer for this para.",
+		);
+		return
+	}
+
+	let per_para = match state.collating_on.get_mut(&para_id) {
+		Some(per_para) => per_para,
+		None => return,
+	};
+
+	let should_advertise = per_para
 		.our_validators_groups
 		.get(&relay_parent)
 		.map(|g| g.should_advertise_to(&state.peer_ids, &peer))
 		.unwrap_or(false);
 
-	match (state.collations.get_mut(&relay_parent), should_advertise) {
+	match (per_para.collations.get_mut(&relay_parent), should_advertise) {
 		(None, _) => {
 			gum::trace!(
 				target: LOG_TARGET,
+				para_id = %para_id,
 				?relay_parent,
 				peer_id = %peer,
 				"No collation to advertise.",
@@ -554,6 +737,7 @@ async fn advertise_collation<Context>(
 		(_, false) => {
 			gum::debug!(
 				target: LOG_TARGET,
+				para_id = %para_id,
 				?relay_parent,
 				peer_id = %peer,
 				"Not advertising collation as we already advertised it to this validator.",
@@ -563,6 +747,7 @@ async fn advertise_collation<Context>(
 		(Some(collation), true) => {
 			gum::debug!(
 				target: LOG_TARGET,
+				para_id = %para_id,
 				?relay_parent,
 				peer_id = %peer,
 				"Advertising collation.",
@@ -579,11 +764,15 @@ async fn advertise_collation<Context>(
 	))
 	.await;
 
-	if let Some(validators) = state.our_validators_groups.get_mut(&relay_parent) {
+	if let Some(validators) = state
+		.collating_on
+		.get_mut(&para_id)
+		.and_then(|per_para| per_para.our_validators_groups.get_mut(&relay_parent))
+	{
 		validators.advertised_to_peer(&state.peer_ids, &peer);
 	}
 
-	state.metrics.on_advertisment_made();
+	state.metrics.on_advertisment_made(para_id);
 }
 
 /// The main incoming message dispatching switch.
@@ -597,38 +786,36 @@ async fn process_msg<Context>(
 	use CollatorProtocolMessage::*;
 
 	match msg {
-		CollateOn(id) => {
-			state.collating_on = Some(id);
-		},
+		CollateOn(id) =>
+			if !state.collating_on.contains_key(&id) {
+				let collator_pair = state.collator_keys.key_for(id).clone();
+				state.collating_on.insert(id, PerParaState::new(collator_pair));
+			},
 		DistributeCollation(receipt, pov, result_sender) => {
 			let _span1 = state
 				.span_per_relay_parent
 				.get(&receipt.descriptor.relay_parent)
 				.map(|s| s.child("distributing-collation"));
 			let _span2 = jaeger::Span::new(&pov, "distributing-collation");
-			match state.collating_on {
-				Some(id) if receipt.descriptor.para_id != id => {
-					// If the ParaId of a collation requested to be distributed does not match
-					// the one we expect, we ignore the message.
-					gum::warn!(
-						target: LOG_TARGET,
-						para_id = %receipt.descriptor.para_id,
-						collating_on = %id,
-						"DistributeCollation for unexpected para_id",
-					);
-				},
-				Some(id) => {
-					let _ = state.metrics.time_collation_distribution("distribute");
-					distribute_collation(ctx, runtime, state, id, receipt, pov, result_sender)
-						.await?;
-				},
-				None => {
-					gum::warn!(
-						target: LOG_TARGET,
-						para_id = %receipt.descriptor.para_id,
-						"DistributeCollation message while not collating on any",
-					);
-				},
+			let id = receipt.descriptor.para_id;
+			if state.collating_on.is_empty() {
+				gum::warn!(
+					target: LOG_TARGET,
+					para_id = %id,
+					"DistributeCollation message while not collating on any",
+				);
+			} else if !state.collating_on.contains_key(&id) {
+				// If the ParaId of a collation requested to be distributed does not match
+				// the ones we expect, we ignore the message.
+				gum::warn!(
+					target: LOG_TARGET,
+					para_id = %id,
+					collating_on = ?state.collating_on.keys().collect::<Vec<_>>(),
+					"DistributeCollation for unexpected para_id",
+				);
+			} else {
+				let _ = state.metrics.time_collation_distribution("distribute");
+				distribute_collation(ctx, runtime, state, id, receipt, pov, result_sender).await?;
 			}
 		},
 		ReportCollator(_) => {
@@ -664,6 +851,7 @@ async fn send_collation(
 ) {
 	let (tx, rx) = oneshot::channel();
 
+	let para_id = request.payload.para_id;
 	let relay_parent = request.payload.relay_parent;
 	let peer_id = request.peer;
 
@@ -682,12 +870,12 @@ async fn send_collation(
 			let r = rx.timeout(MAX_UNSHARED_UPLOAD_TIME).await;
 			let timed_out = r.is_none();
 
-			CollationSendResult { relay_parent, peer_id, timed_out }
+			CollationSendResult { para_id, relay_parent, peer_id, timed_out }
 		}
 		.boxed(),
 	);
 
-	state.metrics.on_collation_sent();
+	state.metrics.on_collation_sent(para_id);
 }
 
 /// A networking messages switch.
@@ -779,69 +967,72 @@ async fn handle_incoming_request<Context>(
 		.get(&req.payload.relay_parent)
 		.map(|s| s.child("request-collation"));
 
-	match state.collating_on {
-		Some(our_para_id) if our_para_id == req.payload.para_id => {
-			let (receipt, pov) =
-				if let Some(collation) = state.collations.get_mut(&req.payload.relay_parent) {
-					collation.status.advance_to_requested();
-					(collation.receipt.clone(), collation.pov.clone())
-				} else {
-					gum::warn!(
-						target: LOG_TARGET,
-						relay_parent = %req.payload.relay_parent,
-						"received a `RequestCollation` for a relay parent we don't have collation stored.",
-					);
-
-					return Ok(())
-				};
-
-			state.metrics.on_collation_sent_requested();
-
-			let _span = _span.as_ref().map(|s| s.child("sending"));
-
-			let waiting =
-				state.waiting_collation_fetches.entry(req.payload.relay_parent).or_default();
-
-			if !waiting.waiting_peers.insert(req.peer) {
-				gum::debug!(
-					target: LOG_TARGET,
-					"Dropping incoming request as peer has a request in flight already."
-				);
-				modify_reputation(
-					&mut state.reputation,
-					ctx.sender(),
-					req.peer,
-					COST_APPARENT_FLOOD.into(),
-				)
-				.await;
-				return Ok(())
-			}
-
-			if waiting.collation_fetch_active {
-				waiting.waiting.push_back(req);
-			} else {
-				waiting.collation_fetch_active = true;
-				// Obtain a timer for sending collation
-				let _ = state.metrics.time_collation_distribution("send");
-				send_collation(state, req, receipt, pov).await;
-			}
-		},
-		Some(our_para_id) => {
+	let para_id = req.payload.para_id;
+	let per_para = match state.collating_on.get_mut(&para_id) {
+		Some(per_para) => per_para,
+		None if state.collating_on.is_empty() => {
 			gum::warn!(
 				target: LOG_TARGET,
-				for_para_id = %req.payload.para_id,
-				our_para_id = %our_para_id,
-				"received a `CollationFetchingRequest` for unexpected para_id",
+				for_para_id = %para_id,
+				"received a `RequestCollation` while not collating on any para",
 			);
+			return Ok(())
 		},
 		None => {
 			gum::warn!(
 				target: LOG_TARGET,
-				for_para_id = %req.payload.para_id,
-				"received a `RequestCollation` while not collating on any para",
+				for_para_id = %para_id,
+				"received a `CollationFetchingRequest` for unexpected para_id",
 			);
+			return Ok(())
 		},
+	};
+
+	let (receipt, pov) =
+		if let Some(collation) = per_para.collations.get_mut(&req.payload.relay_parent) {
+			collation.status.advance_to_requested();
+			(collation.receipt.clone(), collation.pov.clone())
+		} else {
+			gum::warn!(
+				target: LOG_TARGET,
+				para_id = %para_id,
+				relay_parent = %req.payload.relay_parent,
+				"received a `RequestCollation` for a relay parent we don't have collation stored.",
+			);
+
+			return Ok(())
+		};
+
+	state.metrics.on_collation_sent_requested(para_id);
+
+	let _span = _span.as_ref().map(|s| s.child("sending"));
+
+	let waiting = per_para.waiting_collation_fetches.entry(req.payload.relay_parent).or_default();
+
+	if !waiting.waiting_peers.insert(req.peer) {
+		gum::debug!(
+			target: LOG_TARGET,
+			"Dropping incoming request as peer has a request in flight already."
+		);
+		modify_reputation(
+			&mut state.reputation,
+			ctx.sender(),
+			req.peer,
+			COST_APPARENT_FLOOD.into(),
+		)
+		.await;
+		return Ok(())
 	}
+
+	if waiting.collation_fetch_active {
+		waiting.waiting.push_back(req);
+	} else {
+		waiting.collation_fetch_active = true;
+		// Obtain a timer for sending collation
+		let _ = state.metrics.time_collation_distribution("send");
+		send_collation(state, req, receipt, pov).await;
+	}
+
 	Ok(())
 }
 
@@ -860,7 +1051,7 @@ async fn handle_peer_view_change<Context>(
 	*current = view;
 
 	for added in added.into_iter() {
-		advertise_collation(ctx, state, added, peer_id).await;
+		advertise_collations(ctx, state, added, peer_id).await;
 	}
 }
 
@@ -899,12 +1090,13 @@ async fn handle_network_msg<Context>(
 			gum::trace!(target: LOG_TARGET, ?peer_id, "Peer disconnected");
 			state.peer_views.remove(&peer_id);
 			state.peer_ids.remove(&peer_id);
+			state.declared_to.remove(&peer_id);
 		},
 		OurViewChange(view) => {
 			gum::trace!(target: LOG_TARGET, ?view, "Own view change");
 			handle_our_view_change(state, view).await?;
 		},
-		PeerMessage(remote, Versioned::V1(msg)) => {
+		PeerMessage(remote, Versioned::V1(msg) | Versioned::V2(msg)) => {
 			handle_incoming_peer_message(ctx, runtime, state, remote, msg).await?;
 		},
 		NewGossipTopology { .. } => {
@@ -920,36 +1112,45 @@ async fn handle_our_view_change(state: &mut State, view: OurView) -> Result<()>
 	for removed in state.view.difference(&view) {
 		gum::debug!(target: LOG_TARGET, relay_parent = ?removed, "Removing relay parent because our view changed.");
 
-		if let Some(collation) = state.collations.remove(removed) {
-			state.collation_result_senders.remove(&collation.receipt.hash());
+		for per_para in state.collating_on.values_mut() {
+			if let Some(collation) = per_para.collations.remove(removed) {
+				state.collation_result_senders.remove(&collation.receipt.hash());
 
-			match collation.status {
-				CollationStatus::Created => gum::warn!(
-					target: LOG_TARGET,
-					candidate_hash = ?collation.receipt.hash(),
-					pov_hash = ?collation.pov.hash(),
-					"Collation wasn't advertised to any validator.",
-				),
-				CollationStatus::Advertised => gum::debug!(
-					target: LOG_TARGET,
-					candidate_hash = ?collation.receipt.hash(),
-					pov_hash = ?collation.pov.hash(),
-					"Collation was advertised but not requested by any validator.",
-				),
-				CollationStatus::Requested => gum::debug!(
-					target: LOG_TARGET,
-					candidate_hash = ?collation.receipt.hash(),
-					pov_hash = ?collation.pov.hash(),
-					"Collation was requested.",
-				),
+				match collation.status {
+					CollationStatus::Created => gum::warn!(
+						target: LOG_TARGET,
+						candidate_hash = ?collation.receipt.hash(),
+						pov_hash = ?collation.pov.hash(),
+						"Collation wasn't advertised to any validator.",
+					),
+					CollationStatus::Advertised => gum::debug!(
+						target: LOG_TARGET,
+						candidate_hash = ?collation.receipt.hash(),
+						pov_hash = ?collation.pov.hash(),
+						"Collation was advertised but not requested by any validator.",
+					),
+					CollationStatus::Requested => gum::debug!(
+						target: LOG_TARGET,
+						candidate_hash = ?collation.receipt.hash(),
+						pov_hash = ?collation.pov.hash(),
+						"Collation was requested.",
+					),
+				}
 			}
+			per_para.our_validators_groups.remove(removed);
+			per_para.waiting_collation_fetches.remove(removed);
 		}
-		state.our_validators_groups.remove(removed);
 		state.span_per_relay_parent.remove(removed);
-		state.waiting_collation_fetches.remove(removed);
 		state.validator_groups_buf.remove_relay_parent(removed);
 	}
 
+	let collating_on = &state.collating_on;
+	state.reassigned.retain(|_, para_id| {
+		collating_on
+			.get(para_id)
+			.map_or(false, |per_para| !per_para.our_validators_groups.is_empty())
+	});
+
 	state.view = view;
 
 	Ok(())
@@ -960,14 +1161,14 @@ async fn handle_our_view_change(state: &mut State, view: OurView) -> Result<()>
 pub(crate) async fn run<Context>(
 	ctx: Context,
 	local_peer_id: PeerId,
-	collator_pair: CollatorPair,
-	req_receiver: IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
+	collator_keys: CollatorKeys,
+	req_receiver: QuotaReceiver<request_v1::CollationFetchingRequest>,
 	metrics: Metrics,
 ) -> std::result::Result<(), FatalError> {
 	run_inner(
 		ctx,
 		local_peer_id,
-		collator_pair,
+		collator_keys,
 		req_receiver,
 		metrics,
 		ReputationAggregator::default(),
@@ -980,8 +1181,8 @@ pub(crate) async fn run<Context>(
 async fn run_inner<Context>(
 	mut ctx: Context,
 	local_peer_id: PeerId,
-	collator_pair: CollatorPair,
-	mut req_receiver: IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
+	collator_keys: CollatorKeys,
+	mut req_receiver: QuotaReceiver<request_v1::CollationFetchingRequest>,
 	metrics: Metrics,
 	reputation: ReputationAggregator,
 	reputation_interval: Duration,
@@ -991,7 +1192,7 @@ async fn run_inner<Context>(
 	let new_reputation_delay = || futures_timer::Delay::new(reputation_interval).fuse();
 	let mut reputation_delay = new_reputation_delay();
 
-	let mut state = State::new(local_peer_id, collator_pair, metrics, reputation);
+	let mut state = State::new(local_peer_id, collator_keys, metrics, reputation);
 	let mut runtime = RuntimeInfo::new(None);
 
 	let reconnect_stream = super::tick_stream(RECONNECT_POLL);
@@ -1017,6 +1218,7 @@ async fn run_inner<Context>(
 				FromOrchestra::Signal(Conclude) => return Ok(()),
 			},
 			CollationSendResult {
+				para_id,
 				relay_parent,
 				peer_id,
 				timed_out,
@@ -1035,7 +1237,12 @@ async fn run_inner<Context>(
 					}
 				}
 
-				let next = if let Some(waiting) = state.waiting_collation_fetches.get_mut(&relay_parent) {
+				let per_para = match state.collating_on.get_mut(&para_id) {
+					Some(per_para) => per_para,
+					None => continue,
+				};
+
+				let next = if let Some(waiting) = per_para.waiting_collation_fetches.get_mut(&relay_parent) {
 					waiting.waiting_peers.remove(&peer_id);
 					if let Some(next) = waiting.waiting.pop_front() {
 						next
@@ -1048,7 +1255,7 @@ async fn run_inner<Context>(
 					continue
 				};
 
-				if let Some(collation) = state.collations.get(&relay_parent) {
+				if let Some(collation) = per_para.collations.get(&relay_parent) {
 					let receipt = collation.receipt.clone();
 					let pov = collation.pov.clone();
 
@@ -1090,7 +1297,7 @@ async fn run_inner<Context>(
 						gum::debug!(
 							target: LOG_TARGET,
 							error = ?jfyi,
-							"Decoding incoming request failed"
+							"Receiving incoming request failed"
 						);
 						continue
 					}
@@ -19,6 +19,7 @@ use super::*;
 use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use assert_matches::assert_matches;
+use async_trait::async_trait;
 use futures::{executor, future, Future};
 use futures_timer::Delay;
 
@@ -30,9 +31,10 @@ use sp_keyring::Sr25519Keyring;
 use sp_runtime::traits::AppVerify;
 
 use polkadot_node_network_protocol::{
+	authority_discovery::AuthorityDiscovery,
 	our_view,
-	peer_set::CollationVersion,
-	request_response::{IncomingRequest, ReqProtocolNames},
+	peer_set::{CollationVersion, PeerSet},
+	request_response::{IncomingRequest, QuotaReceiver, ReqProtocolNames},
 	view,
 };
 use polkadot_node_primitives::BlockData;
@@ -51,6 +53,27 @@ use polkadot_primitives_test_helpers::TestCandidateBuilder;
 
 const REPUTATION_CHANGE_TEST_INTERVAL: Duration = Duration::from_millis(10);
 
+/// Authority discovery considering every peer an authority.
+#[derive(Debug)]
+struct AllAuthorities;
+
+#[async_trait]
+impl AuthorityDiscovery for AllAuthorities {
+	async fn get_addresses_by_authority_id(
+		&mut self,
+		_: AuthorityDiscoveryId,
+	) -> Option<HashSet<sc_network::Multiaddr>> {
+		None
+	}
+
+	async fn get_authority_ids_by_peer_id(
+		&mut self,
+		_: PeerId,
+	) -> Option<HashSet<AuthorityDiscoveryId>> {
+		Some(HashSet::from([Sr25519Keyring::Alice.public().into()]))
+	}
+}
+
 #[derive(Clone)]
 struct TestState {
 	para_id: ParaId,
@@ -198,7 +221,7 @@ struct TestHarness {
 
 fn test_harness<T: Future<Output = TestHarness>>(
 	local_peer_id: PeerId,
-	collator_pair: CollatorPair,
+	collator_keys: impl Into<CollatorKeys>,
 	reputation: ReputationAggregator,
 	test: impl FnOnce(TestHarness) -> T,
 ) {
@@ -217,11 +240,13 @@ fn test_harness<T: Future<Output = TestHarness>>(
 
 	let (collation_req_receiver, req_cfg) =
 		IncomingRequest::get_config_receiver(&req_protocol_names);
+	let collation_req_receiver =
+		QuotaReceiver::with_default_quota(collation_req_receiver, Box::new(AllAuthorities));
 	let subsystem = async {
 		run_inner(
 			context,
 			local_peer_id,
-			collator_pair,
+			collator_keys.into(),
 			collation_req_receiver,
 			Default::default(),
 			reputation,
@@ -325,13 +350,14 @@ async fn distribute_collation(
 
 	let pov_hash = pov_block.hash();
 
-	let candidate = TestCandidateBuilder {
+	let mut candidate = TestCandidateBuilder {
 		para_id: test_state.para_id,
 		relay_parent: test_state.relay_parent,
 		pov_hash,
 		..Default::default()
 	}
 	.build();
+	candidate.descriptor.collator = test_state.collator_pair.public();
 
 	overseer_send(
 		virtual_overseer,
@@ -1249,3 +1275,213 @@ fn connect_to_buffered_groups() {
 		},
 	);
 }
+
+#[test]
+fn collate_on_multiple_paras_with_own_keys() {
+	let test_state = TestState::default();
+	let local_peer_id = test_state.local_peer_id.clone();
+
+	// The second para is scheduled on the second core and uses its own key.
+	let mut test_state_b = test_state.clone();
+	test_state_b.para_id = ParaId::from(2);
+	test_state_b.collator_pair = CollatorPair::generate().0;
+	test_state_b.availability_cores = vec![
+		CoreState::Scheduled(ScheduledCore { para_id: test_state.para_id, collator: None }),
+		CoreState::Scheduled(ScheduledCore { para_id: test_state_b.para_id, collator: None }),
+	];
+
+	let collator_keys = CollatorKeys::new(test_state.collator_pair.clone())
+		.with_para_key(test_state_b.para_id, test_state_b.collator_pair.clone());
+
+	test_harness(
+		local_peer_id,
+		collator_keys,
+		ReputationAggregator::new(|_| true),
+		|test_harness| async move {
+			let mut virtual_overseer = test_harness.virtual_overseer;
+			let mut req_cfg = test_harness.req_cfg;
+
+			overseer_send(
+				&mut virtual_overseer,
+				CollatorProtocolMessage::CollateOn(test_state_b.para_id),
+			)
+			.await;
+			setup_system(&mut virtual_overseer, &test_state).await;
+
+			distribute_collation(&mut virtual_overseer, &test_state, true).await;
+			let DistributeCollation { candidate: candidate_b, pov_block: pov_block_b } =
+				distribute_collation(&mut virtual_overseer, &test_state_b, true).await;
+
+			let group_b = test_state_b.group_rotation_info.group_for_core(CoreIndex(1), 2);
+			let group_b_validators =
+				test_state_b.session_info.validator_groups.get(group_b).unwrap().clone();
+			let peer_a = test_state.current_group_validator_peer_ids()[0];
+			let validator_a = test_state.current_group_validator_authority_ids()[0].clone();
+			let peer_b = test_state.validator_peer_id[group_b_validators[0].0 as usize];
+			let validator_b =
+				test_state.session_info.discovery_keys[group_b_validators[0].0 as usize].clone();
+
+			// Every validator is declared to with the key of the para it is assigned to.
+			connect_peer(&mut virtual_overseer, peer_a, Some(validator_a)).await;
+			expect_declare_msg(&mut virtual_overseer, &test_state, &peer_a).await;
+			connect_peer(&mut virtual_overseer, peer_b, Some(validator_b)).await;
+			expect_declare_msg(&mut virtual_overseer, &test_state_b, &peer_b).await;
+
+			// Each validator only learns about the collation of its own para.
+			send_peer_view_change(&mut virtual_overseer, &peer_a, vec![test_state.relay_parent])
+				.await;
+			expect_advertise_collation_msg(&mut virtual_overseer, &peer_a, test_state.relay_parent)
+				.await;
+			send_peer_view_change(&mut virtual_overseer, &peer_b, vec![test_state.relay_parent])
+				.await;
+			expect_advertise_collation_msg(&mut virtual_overseer, &peer_b, test_state.relay_parent)
+				.await;
+			assert!(overseer_recv_with_timeout(&mut virtual_overseer, TIMEOUT).await.is_none());
+
+			// Fetch requests are served from the collations of the requested para.
+			let (pending_response, rx) = oneshot::channel();
+			req_cfg
+				.inbound_queue
+				.as_mut()
+				.unwrap()
+				.send(RawIncomingRequest {
+					peer: peer_b,
+					payload: CollationFetchingRequest {
+						relay_parent: test_state.relay_parent,
+						para_id: test_state_b.para_id,
+					}
+					.encode(),
+					pending_response,
+				})
+				.await
+				.unwrap();
+
+			assert_matches!(
+				rx.await,
+				Ok(full_response) => {
+					let CollationFetchingResponse::Collation(receipt, pov): CollationFetchingResponse
+						= CollationFetchingResponse::decode(
+							&mut full_response.result
+							.expect("We should have a proper answer").as_ref()
+					)
+					.expect("Decoding should work");
+					assert_eq!(receipt, candidate_b);
+					assert_eq!(pov, pov_block_b);
+				}
+			);
+
+			TestHarness { virtual_overseer, req_cfg }
+		},
+	);
+}
+
+#[test]
+fn redeclare_to_validators_rotated_to_another_para() {
+	let mut test_state = TestState::default();
+	let local_peer_id = test_state.local_peer_id.clone();
+
+	let mut test_state_b = test_state.clone();
+	test_state_b.para_id = ParaId::from(2);
+	test_state_b.collator_pair = CollatorPair::generate().0;
+	test_state_b.availability_cores = vec![
+		CoreState::Scheduled(ScheduledCore { para_id: test_state.para_id, collator: None }),
+		CoreState::Scheduled(ScheduledCore { para_id: test_state_b.para_id, collator: None }),
+	];
+
+	let collator_keys = CollatorKeys::new(test_state.collator_pair.clone())
+		.with_para_key(test_state_b.para_id, test_state_b.collator_pair.clone());
+
+	test_harness(
+		local_peer_id,
+		collator_keys,
+		ReputationAggregator::new(|_| true),
+		|mut test_harness| async move {
+			let virtual_overseer = &mut test_harness.virtual_overseer;
+
+			overseer_send(
+				virtual_overseer,
+				CollatorProtocolMessage::CollateOn(test_state_b.para_id),
+			)
+			.await;
+			setup_system(virtual_overseer, &test_state).await;
+
+			distribute_collation(virtual_overseer, &test_state, true).await;
+
+			let peer = test_state.current_group_validator_peer_ids()[0];
+			let validator_id = test_state.current_group_validator_authority_ids()[0].clone();
+
+			connect_peer(virtual_overseer, peer, Some(validator_id.clone())).await;
+			expect_declare_msg(virtual_overseer, &test_state, &peer).await;
+
+			// The groups rotate and the validator now backs the second para.
+			test_state.advance_to_new_round(virtual_overseer, true).await;
+			test_state.group_rotation_info = test_state.group_rotation_info.bump_rotation();
+			test_state_b.relay_parent = test_state.relay_parent;
+			test_state_b.group_rotation_info = test_state.group_rotation_info.clone();
+
+			distribute_collation(virtual_overseer, &test_state_b, true).await;
+
+			// We declared ourselves for the first para on this connection, so the validator is
+			// disconnected instead of getting an advertisement it can't attribute.
+			send_peer_view_change(virtual_overseer, &peer, vec![test_state.relay_parent]).await;
+			assert_matches!(
+				overseer_recv(virtual_overseer).await,
+				AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::DisconnectPeer(
+					disconnected,
+					PeerSet::Collation,
+				)) => {
+					assert_eq!(disconnected, peer);
+				}
+			);
+			assert!(overseer_recv_with_timeout(virtual_overseer, TIMEOUT).await.is_none());
+
+			// Once it reconnects we declare ourselves for the second para and advertise.
+			disconnect_peer(virtual_overseer, peer).await;
+			connect_peer(virtual_overseer, peer, Some(validator_id)).await;
+			expect_declare_msg(virtual_overseer, &test_state_b, &peer).await;
+
+			send_peer_view_change(virtual_overseer, &peer, vec![test_state.relay_parent]).await;
+			expect_advertise_collation_msg(virtual_overseer, &peer, test_state.relay_parent).await;
+
+			test_harness
+		},
+	)
+}
+
+#[test]
+fn collations_signed_with_another_key_are_not_advertised() {
+	let test_state = TestState::default();
+	let local_peer_id = test_state.local_peer_id.clone();
+	let collator_pair = test_state.collator_pair.clone();
+
+	test_harness(
+		local_peer_id,
+		collator_pair,
+		ReputationAggregator::new(|_| true),
+		|mut test_harness| async move {
+			let virtual_overseer = &mut test_harness.virtual_overseer;
+
+			setup_system(virtual_overseer, &test_state).await;
+
+			let pov_block = PoV { block_data: BlockData(vec![42, 43, 44]) };
+			let mut candidate = TestCandidateBuilder {
+				para_id: test_state.para_id,
+				relay_parent: test_state.relay_parent,
+				pov_hash: pov_block.hash(),
+				..Default::default()
+			}
+			.build();
+			candidate.descriptor.collator = CollatorPair::generate().0.public();
+
+			overseer_send(
+				virtual_overseer,
+				CollatorProtocolMessage::DistributeCollation(candidate, pov_block, None),
+			)
+			.await;
+
+			// The collation is dropped before the cores are even looked up.
+			assert!(overseer_recv_with_timeout(virtual_overseer, TIMEOUT).await.is_none());
+			test_harness
+		},
+	)
+}
@@ -21,21 +21,26 @@
 #![deny(unused_crate_dependencies)]
 #![recursion_limit = "256"]
 
-use std::time::{Duration, Instant};
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 use futures::{
 	stream::{FusedStream, StreamExt},
 	FutureExt, TryFutureExt,
 };
 
-use polkadot_node_subsystem_util::reputation::ReputationAggregator;
+use polkadot_node_subsystem_util::{database::Database, reputation::ReputationAggregator};
+use sp_core::Pair;
 use sp_keystore::KeystorePtr;
 
 use polkadot_node_network_protocol::{
-	request_response::{v1 as request_v1, IncomingRequestReceiver},
+	request_response::{v1 as request_v1, QuotaReceiver},
 	PeerId, UnifiedReputationChange as Rep,
 };
-use polkadot_primitives::CollatorPair;
+use polkadot_primitives::{CollatorPair, Id as ParaId};
 
 use polkadot_node_subsystem::{errors::SubsystemError, overseer, SpawnedSubsystem};
 
@@ -64,6 +69,63 @@ impl Default for CollatorEvictionPolicy {
 	}
 }
 
+/// Configuration of the collator scoring on the validator side.
+#[derive(Debug, Clone, Copy)]
+pub struct CollatorScoringConfig {
+	/// The column of the database the collator scores are stored in.
+	pub col_data: u32,
+}
+
+/// The keys a collator node declares itself with.
+///
+/// A single node may collate on several paras, each with its own collator key. Paras
+/// without a dedicated key fall back to the default one.
+#[derive(Clone)]
+pub struct CollatorKeys {
+	default: CollatorPair,
+	per_para: HashMap<ParaId, CollatorPair>,
+}
+
+impl CollatorKeys {
+	/// Create a new set of keys using `default` for every para.
+	pub fn new(default: CollatorPair) -> Self {
+		Self { default, per_para: HashMap::new() }
+	}
+
+	/// Use a dedicated `key` when collating on `para_id`.
+	pub fn with_para_key(mut self, para_id: ParaId, key: CollatorPair) -> Self {
+		self.per_para.insert(para_id, key);
+		self
+	}
+
+	/// The key to declare ourselves with when collating on `para_id`.
+	pub fn key_for(&self, para_id: ParaId) -> &CollatorPair {
+		self.per_para.get(&para_id).unwrap_or(&self.default)
+	}
+}
+
+impl std::fmt::Debug for CollatorKeys {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		fmt.debug_struct("CollatorKeys")
+			.field("default", &self.default.public())
+			.field(
+				"per_para",
+				&self
+					.per_para
+					.iter()
+					.map(|(para_id, key)| (para_id, key.public()))
+					.collect::<Vec<_>>(),
+			)
+			.finish()
+	}
+}
+
+impl From<CollatorPair> for CollatorKeys {
+	fn from(default: CollatorPair) -> Self {
+		Self::new(default)
+	}
+}
+
 /// What side of the collator protocol is being engaged
 pub enum ProtocolSide {
 	/// Validators operate on the relay chain.
@@ -74,12 +136,16 @@ pub enum ProtocolSide {
 		eviction_policy: CollatorEvictionPolicy,
 		/// Prometheus metrics for validators.
 		metrics: validator_side::Metrics,
+		/// The database collator scores are persisted in.
+		db: Arc<dyn Database>,
+		/// Configuration of the collator scoring.
+		scoring_config: CollatorScoringConfig,
 	},
-	/// Collators operate on a parachain.
+	/// Collators operate on one or more parachains.
 	Collator(
 		PeerId,
-		CollatorPair,
-		IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
+		CollatorKeys,
+		QuotaReceiver<request_v1::CollationFetchingRequest>,
 		collator_side::Metrics,
 	),
 }
@@ -101,10 +167,11 @@ impl CollatorProtocolSubsystem {
 
 	async fn run<Context>(self, ctx: Context) -> std::result::Result<(), error::FatalError> {
 		match self.protocol_side {
-			ProtocolSide::Validator { keystore, eviction_policy, metrics } =>
-				validator_side::run(ctx, keystore, eviction_policy, metrics).await,
-			ProtocolSide::Collator(local_peer_id, collator_pair, req_receiver, metrics) =>
-				collator_side::run(ctx, local_peer_id, collator_pair, req_receiver, metrics).await,
+			ProtocolSide::Validator { keystore, eviction_policy, metrics, db, scoring_config } =>
+				validator_side::run(ctx, keystore, eviction_policy, metrics, db, scoring_config)
+					.await,
+			ProtocolSide::Collator(local_peer_id, collator_keys, req_receiver, metrics) =>
+				collator_side::run(ctx, local_peer_id, collator_keys, req_receiver, metrics).await,
 		}
 	}
 }
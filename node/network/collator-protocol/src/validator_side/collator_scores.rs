@@ -0,0 +1,312 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scoring of collators on the validator side.
+//!
+//! Each collator we deal with accrues a [`Score`] for the `CollatorId` it declared and another one
+//! for the `PeerId` it is connected with. Scores are persisted in the parachains DB, so they
+//! outlive both restarts and sessions, and they decide which advertisement is fetched next when
+//! several collators compete for the same relay parent.
+
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use parity_scale_codec::{Decode, Encode};
+use sp_core::hexdisplay::HexDisplay;
+
+use polkadot_node_network_protocol::PeerId;
+use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_primitives::CollatorId;
+
+use super::Metrics;
+use crate::{CollatorScoringConfig, LOG_TARGET};
+
+const COLLATOR_SCORE_PREFIX: &[u8] = b"CollatorScore";
+const PEER_SCORE_PREFIX: &[u8] = b"PeerScore";
+
+/// Once any counter of a score reaches this value, all counters are halved. This keeps recent
+/// behaviour more relevant than what happened a long time ago.
+const MAX_COUNTER: u32 = 64;
+
+/// Scores which were not updated for this long are removed from the database on startup.
+const SCORE_RETENTION: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+const FETCH_SUCCESS_WEIGHT: i64 = 10;
+const FETCH_FAILURE_WEIGHT: i64 = 20;
+const INVALID_CANDIDATE_WEIGHT: i64 = 200;
+const DUPLICATE_ADVERTISEMENT_WEIGHT: i64 = 5;
+/// One point is deducted for every this many milliseconds of average fetch latency.
+const LATENCY_PENALTY_MS: u32 = 50;
+
+/// Something a collator did which affects its score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreEvent {
+	/// A collation was fetched, the given time after it was advertised.
+	Fetched(Duration),
+	/// Fetching an advertised collation failed or timed out.
+	FetchFailed,
+	/// A fetched candidate was found to be invalid when seconding.
+	InvalidCandidate,
+	/// The same collation was advertised more than once.
+	DuplicateAdvertisement,
+}
+
+impl ScoreEvent {
+	/// The label used for this event in metrics.
+	pub fn label(&self) -> &'static str {
+		match self {
+			ScoreEvent::Fetched(_) => "fetched",
+			ScoreEvent::FetchFailed => "fetch_failed",
+			ScoreEvent::InvalidCandidate => "invalid_candidate",
+			ScoreEvent::DuplicateAdvertisement => "duplicate_advertisement",
+		}
+	}
+}
+
+/// The track record of a single collator or peer.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Score {
+	/// Moving average of the advertisement-to-fetch latency, in milliseconds.
+	pub fetch_latency_ms: Option<u32>,
+	/// Number of collations fetched successfully.
+	pub fetched: u32,
+	/// Number of failed collation fetches.
+	pub fetch_failures: u32,
+	/// Number of fetched candidates which turned out to be invalid.
+	pub invalid_candidates: u32,
+	/// Number of duplicate advertisements.
+	pub duplicate_advertisements: u32,
+	/// When the score was last changed, in seconds since the UNIX epoch.
+	pub last_updated: u64,
+}
+
+impl Score {
+	/// The value of the score. Higher is better, a collator without history scores `0`.
+	pub fn value(&self) -> i64 {
+		self.fetched as i64 * FETCH_SUCCESS_WEIGHT -
+			self.fetch_failures as i64 * FETCH_FAILURE_WEIGHT -
+			self.invalid_candidates as i64 * INVALID_CANDIDATE_WEIGHT -
+			self.duplicate_advertisements as i64 * DUPLICATE_ADVERTISEMENT_WEIGHT -
+			self.fetch_latency_ms.map_or(0, |ms| (ms / LATENCY_PENALTY_MS) as i64)
+	}
+
+	fn apply(&mut self, event: ScoreEvent, now: u64) {
+		match event {
+			ScoreEvent::Fetched(latency) => {
+				let sample = u32::try_from(latency.as_millis()).unwrap_or(u32::MAX);
+				self.fetch_latency_ms = Some(match self.fetch_latency_ms {
+					None => sample,
+					// Each new sample accounts for an eighth of the average.
+					Some(average) => average - average / 8 + sample / 8,
+				});
+				self.fetched += 1;
+			},
+			ScoreEvent::FetchFailed => self.fetch_failures += 1,
+			ScoreEvent::InvalidCandidate => self.invalid_candidates += 1,
+			ScoreEvent::DuplicateAdvertisement => self.duplicate_advertisements += 1,
+		}
+		self.last_updated = now;
+
+		let counters = [
+			&mut self.fetched,
+			&mut self.fetch_failures,
+			&mut self.invalid_candidates,
+			&mut self.duplicate_advertisements,
+		];
+		if counters.iter().any(|c| **c >= MAX_COUNTER) {
+			for counter in counters {
+				*counter /= 2;
+			}
+		}
+	}
+}
+
+/// Scores of the collators we are connected to, backed by the database.
+pub struct CollatorScores {
+	db: Arc<dyn Database>,
+	config: CollatorScoringConfig,
+	metrics: Metrics,
+	collators: HashMap<CollatorId, Score>,
+	peers: HashMap<PeerId, Score>,
+	dirty_collators: HashSet<CollatorId>,
+	dirty_peers: HashSet<PeerId>,
+}
+
+impl CollatorScores {
+	/// Create a new instance, dropping scores from the database which have not been updated for
+	/// longer than [`SCORE_RETENTION`].
+	pub fn new(db: Arc<dyn Database>, config: CollatorScoringConfig, metrics: Metrics) -> Self {
+		let scores = CollatorScores {
+			db,
+			config,
+			metrics,
+			collators: HashMap::new(),
+			peers: HashMap::new(),
+			dirty_collators: HashSet::new(),
+			dirty_peers: HashSet::new(),
+		};
+		scores.prune_stale(unix_time().saturating_sub(SCORE_RETENTION.as_secs()));
+		scores
+	}
+
+	/// Load the scores of a collator which just declared itself, unless already loaded.
+	pub fn load(&mut self, peer_id: &PeerId, collator_id: &CollatorId) {
+		if !self.peers.contains_key(peer_id) {
+			let score = self.read(&peer_key(peer_id));
+			self.peers.insert(*peer_id, score);
+		}
+
+		if !self.collators.contains_key(collator_id) {
+			let score = self.read(&collator_key(collator_id));
+			self.metrics.note_collator_score(&collator_label(collator_id), score.value());
+			self.collators.insert(collator_id.clone(), score);
+		}
+	}
+
+	/// Record an event for a peer and, if it is known, the collator it declared as.
+	///
+	/// Events for scores which are not loaded, e.g. because the peer has disconnected
+	/// meanwhile, are ignored.
+	pub fn note(&mut self, peer_id: &PeerId, collator_id: Option<&CollatorId>, event: ScoreEvent) {
+		gum::trace!(target: LOG_TARGET, ?peer_id, ?collator_id, ?event, "Updating collator score");
+
+		self.metrics.on_collator_score_event(event);
+
+		let now = unix_time();
+		if let Some(score) = self.peers.get_mut(peer_id) {
+			score.apply(event, now);
+			self.dirty_peers.insert(*peer_id);
+		}
+
+		if let Some((collator_id, score)) =
+			collator_id.and_then(|id| self.collators.get_mut(id).map(|score| (id, score)))
+		{
+			score.apply(event, now);
+			self.metrics.note_collator_score(&collator_label(collator_id), score.value());
+			self.dirty_collators.insert(collator_id.clone());
+		}
+	}
+
+	/// The fetch priority of an advertisement made by the given peer and collator. Advertisements
+	/// with a higher priority are fetched first.
+	pub fn priority(&self, peer_id: &PeerId, collator_id: &CollatorId) -> i64 {
+		self.peers.get(peer_id).map_or(0, Score::value) +
+			self.collators.get(collator_id).map_or(0, Score::value)
+	}
+
+	/// Write all scores changed since the last flush to the database.
+	pub fn flush(&mut self) {
+		if self.dirty_peers.is_empty() && self.dirty_collators.is_empty() {
+			return
+		}
+
+		let mut tx = DBTransaction::new();
+		for peer_id in self.dirty_peers.drain() {
+			if let Some(score) = self.peers.get(&peer_id) {
+				tx.put_vec(self.config.col_data, &peer_key(&peer_id), score.encode());
+			}
+		}
+		for collator_id in self.dirty_collators.drain() {
+			if let Some(score) = self.collators.get(&collator_id) {
+				tx.put_vec(self.config.col_data, &collator_key(&collator_id), score.encode());
+			}
+		}
+
+		if let Err(error) = self.db.write(tx) {
+			gum::warn!(target: LOG_TARGET, ?error, "Failed to persist collator scores");
+		}
+	}
+
+	/// Persist and drop the in-memory scores of a disconnected peer.
+	pub fn unload(&mut self, peer_id: &PeerId, collator_id: Option<&CollatorId>) {
+		self.flush();
+
+		self.peers.remove(peer_id);
+		if let Some(collator_id) = collator_id {
+			self.collators.remove(collator_id);
+			self.metrics.remove_collator_score(&collator_label(collator_id));
+		}
+	}
+
+	/// The loaded score of a collator.
+	#[cfg(test)]
+	pub fn collator_score(&self, collator_id: &CollatorId) -> Option<&Score> {
+		self.collators.get(collator_id)
+	}
+
+	fn read(&self, key: &[u8]) -> Score {
+		match self.db.get(self.config.col_data, key) {
+			Ok(Some(raw)) => Score::decode(&mut &raw[..]).unwrap_or_else(|error| {
+				gum::debug!(target: LOG_TARGET, ?error, "Discarding corrupted collator score");
+				Score::default()
+			}),
+			Ok(None) => Score::default(),
+			Err(error) => {
+				gum::warn!(target: LOG_TARGET, ?error, "Failed to read collator score");
+				Score::default()
+			},
+		}
+	}
+
+	fn prune_stale(&self, cutoff: u64) {
+		let mut tx = DBTransaction::new();
+		for prefix in [COLLATOR_SCORE_PREFIX, PEER_SCORE_PREFIX] {
+			for entry in self.db.iter_with_prefix(self.config.col_data, prefix) {
+				let (key, raw) = match entry {
+					Ok(entry) => entry,
+					Err(error) => {
+						gum::warn!(target: LOG_TARGET, ?error, "Failed to read collator scores");
+						return
+					},
+				};
+				let stale = Score::decode(&mut &raw[..]).map_or(true, |s| s.last_updated < cutoff);
+				if stale {
+					tx.delete(self.config.col_data, &key);
+				}
+			}
+		}
+
+		if !tx.ops.is_empty() {
+			gum::debug!(target: LOG_TARGET, pruned = tx.ops.len(), "Pruning stale collator scores");
+			if let Err(error) = self.db.write(tx) {
+				gum::warn!(target: LOG_TARGET, ?error, "Failed to prune collator scores");
+			}
+		}
+	}
+}
+
+fn collator_key(collator_id: &CollatorId) -> Vec<u8> {
+	let mut key = COLLATOR_SCORE_PREFIX.to_vec();
+	collator_id.encode_to(&mut key);
+	key
+}
+
+fn peer_key(peer_id: &PeerId) -> Vec<u8> {
+	let mut key = PEER_SCORE_PREFIX.to_vec();
+	key.extend(peer_id.to_bytes());
+	key
+}
+
+fn collator_label(collator_id: &CollatorId) -> String {
+	format!("0x{}", HexDisplay::from(&collator_id.encode()))
+}
+
+fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
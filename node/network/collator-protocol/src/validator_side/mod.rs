@@ -53,6 +53,7 @@ use polkadot_node_subsystem::{
 	overseer, FromOrchestra, OverseerSignal, PerLeafSpan, SubsystemSender,
 };
 use polkadot_node_subsystem_util::{
+	database::Database,
 	metrics::{self, prometheus},
 	reputation::{ReputationAggregator, REPUTATION_CHANGE_INTERVAL},
 };
@@ -60,7 +61,10 @@ use polkadot_primitives::{CandidateReceipt, CollatorId, Hash, Id as ParaId};
 
 use crate::error::Result;
 
-use super::{modify_reputation, tick_stream, LOG_TARGET};
+use super::{modify_reputation, tick_stream, CollatorScoringConfig, LOG_TARGET};
+
+mod collator_scores;
+use collator_scores::{CollatorScores, ScoreEvent};
 
 #[cfg(test)]
 mod tests;
@@ -146,6 +150,30 @@ impl Metrics {
 	) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.collation_request_duration.start_timer())
 	}
+
+	/// Note an event affecting the score of a collator.
+	fn on_collator_score_event(&self, event: ScoreEvent) {
+		if let Some(metrics) = &self.0 {
+			metrics.collator_score_events.with_label_values(&[event.label()]).inc();
+			if let ScoreEvent::Fetched(latency) = event {
+				metrics.advertisement_to_fetch_latency.observe(latency.as_secs_f64());
+			}
+		}
+	}
+
+	/// Note the current score of a collator.
+	fn note_collator_score(&self, collator: &str, score: i64) {
+		if let Some(metrics) = &self.0 {
+			metrics.collator_scores.with_label_values(&[collator]).set(score as f64);
+		}
+	}
+
+	/// Stop reporting the score of a collator we are no longer connected to.
+	fn remove_collator_score(&self, collator: &str) {
+		if let Some(metrics) = &self.0 {
+			let _ = metrics.collator_scores.remove_label_values(&[collator]);
+		}
+	}
 }
 
 #[derive(Clone)]
@@ -155,6 +183,9 @@ struct MetricsInner {
 	handle_collation_request_result: prometheus::Histogram,
 	collator_peer_count: prometheus::Gauge<prometheus::U64>,
 	collation_request_duration: prometheus::Histogram,
+	collator_score_events: prometheus::CounterVec<prometheus::U64>,
+	advertisement_to_fetch_latency: prometheus::Histogram,
+	collator_scores: prometheus::GaugeVec<prometheus::F64>,
 }
 
 impl metrics::Metrics for Metrics {
@@ -206,6 +237,35 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			collator_score_events: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collator_score_events_total",
+						"Number of events affecting collator scores, by kind.",
+					),
+					&["event"],
+				)?,
+				registry,
+			)?,
+			advertisement_to_fetch_latency: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_collator_protocol_validator_advertisement_to_fetch_latency",
+						"Time between a collation advertisement and the completed fetch",
+					).buckets(vec![0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 5.0]),
+				)?,
+				registry,
+			)?,
+			collator_scores: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collator_score",
+						"Scores of the connected collators, higher is better.",
+					),
+					&["collator"],
+				)?,
+				registry,
+			)?,
 		};
 
 		Ok(Metrics(Some(metrics)))
@@ -227,8 +287,8 @@ struct PerRequest {
 struct CollatingPeerState {
 	collator_id: CollatorId,
 	para_id: ParaId,
-	// Advertised relay parents.
-	advertisements: HashSet<Hash>,
+	// Advertised relay parents and when they were advertised.
+	advertisements: HashMap<Hash, Instant>,
 	last_active: Instant,
 }
 
@@ -272,7 +332,7 @@ impl PeerData {
 	/// Prune old advertisements relative to our view.
 	fn prune_old_advertisements(&mut self, our_view: &View) {
 		if let PeerState::Collating(ref mut peer_state) = self.state {
-			peer_state.advertisements.retain(|a| our_view.contains(a));
+			peer_state.advertisements.retain(|a, _| our_view.contains(a));
 		}
 	}
 
@@ -288,11 +348,14 @@ impl PeerData {
 			PeerState::Connected(_) => Err(AdvertisementError::UndeclaredCollator),
 			_ if !our_view.contains(&on_relay_parent) => Err(AdvertisementError::OutOfOurView),
 			PeerState::Collating(ref mut state) =>
-				if state.advertisements.insert(on_relay_parent) {
-					state.last_active = Instant::now();
-					Ok((state.collator_id.clone(), state.para_id))
-				} else {
-					Err(AdvertisementError::Duplicate)
+				match state.advertisements.entry(on_relay_parent) {
+					Entry::Vacant(entry) => {
+						let now = Instant::now();
+						entry.insert(now);
+						state.last_active = now;
+						Ok((state.collator_id.clone(), state.para_id))
+					},
+					Entry::Occupied(_) => Err(AdvertisementError::Duplicate),
 				},
 		}
 	}
@@ -313,7 +376,7 @@ impl PeerData {
 		self.state = PeerState::Collating(CollatingPeerState {
 			collator_id,
 			para_id,
-			advertisements: HashSet::new(),
+			advertisements: HashMap::new(),
 			last_active: Instant::now(),
 		});
 	}
@@ -334,9 +397,14 @@ impl PeerData {
 
 	/// Whether the peer has advertised the given collation.
 	fn has_advertised(&self, relay_parent: &Hash) -> bool {
+		self.advertised_at(relay_parent).is_some()
+	}
+
+	/// When the peer advertised the given collation, if it did.
+	fn advertised_at(&self, relay_parent: &Hash) -> Option<Instant> {
 		match self.state {
-			PeerState::Connected(_) => false,
-			PeerState::Collating(ref state) => state.advertisements.contains(relay_parent),
+			PeerState::Connected(_) => None,
+			PeerState::Collating(ref state) => state.advertisements.get(relay_parent).copied(),
 		}
 	}
 
@@ -543,10 +611,12 @@ impl CollationsPerRelayParent {
 	/// This will reset the status back to `Waiting` using [`CollationStatus::back_to_waiting`].
 	///
 	/// Returns `Some(_)` if there is any collation to fetch, the `status` is not `Seconded` and
-	/// the passed in `finished_one` is the currently `waiting_collation`.
+	/// the passed in `finished_one` is the currently `waiting_collation`. The advertisement of the
+	/// collator with the best score is picked, ties go to the most recent advertisement.
 	pub fn get_next_collation_to_fetch(
 		&mut self,
 		finished_one: Option<&CollatorId>,
+		scores: &CollatorScores,
 	) -> Option<(PendingCollation, CollatorId)> {
 		// If finished one does not match waiting_collation, then we already dequeued another fetch
 		// to replace it.
@@ -565,7 +635,13 @@ impl CollationsPerRelayParent {
 			// We don't need to fetch any other collation when we already have seconded one.
 			CollationStatus::Seconded => None,
 			CollationStatus::Waiting => {
-				let next = self.unfetched_collations.pop();
+				let next = self
+					.unfetched_collations
+					.iter()
+					.enumerate()
+					.max_by_key(|(_, (pc, id))| scores.priority(&pc.peer_id, id))
+					.map(|(index, _)| index)
+					.map(|index| self.unfetched_collations.remove(index));
 				self.waiting_collation = next.as_ref().map(|(_, collator_id)| collator_id.clone());
 				next
 			},
@@ -576,7 +652,6 @@ impl CollationsPerRelayParent {
 }
 
 /// All state relevant for the validator side of the protocol lives here.
-#[derive(Default)]
 struct State {
 	/// Our own view.
 	view: OurView,
@@ -618,6 +693,9 @@ struct State {
 
 	/// Aggregated reputation change
 	reputation: ReputationAggregator,
+
+	/// Scores of the connected collators.
+	scores: CollatorScores,
 }
 
 // O(n) search for collator ID by iterating through the peers map. This should be fast enough
@@ -895,6 +973,7 @@ async fn process_incoming_peer_message<Context>(
 					"Declared as collator for current para",
 				);
 
+				state.scores.load(&origin, &collator_id);
 				peer_data.set_collating(collator_id, para_id);
 			} else {
 				gum::debug!(
@@ -1012,6 +1091,14 @@ async fn process_incoming_peer_message<Context>(
 						"Invalid advertisement",
 					);
 
+					if let AdvertisementError::Duplicate = error {
+						state.scores.note(
+							&origin,
+							peer_data.collator_id(),
+							ScoreEvent::DuplicateAdvertisement,
+						);
+					}
+
 					modify_reputation(
 						&mut state.reputation,
 						ctx.sender(),
@@ -1116,7 +1203,9 @@ async fn handle_network_msg<Context>(
 			state.metrics.note_collator_peer_count(state.peer_data.len());
 		},
 		PeerDisconnected(peer_id) => {
-			state.peer_data.remove(&peer_id);
+			if let Some(peer_data) = state.peer_data.remove(&peer_id) {
+				state.scores.unload(&peer_id, peer_data.collator_id());
+			}
 			state.metrics.note_collator_peer_count(state.peer_data.len());
 		},
 		NewGossipTopology { .. } => {
@@ -1128,7 +1217,7 @@ async fn handle_network_msg<Context>(
 		OurViewChange(view) => {
 			handle_our_view_change(ctx, state, keystore, view).await?;
 		},
-		PeerMessage(remote, Versioned::V1(msg)) => {
+		PeerMessage(remote, Versioned::V1(msg) | Versioned::V2(msg)) => {
 			process_incoming_peer_message(ctx, state, remote, msg).await;
 		},
 	}
@@ -1206,11 +1295,11 @@ async fn process_msg<Context>(
 			}
 		},
 		Invalid(parent, candidate_receipt) => {
-			let id = match state.pending_candidates.entry(parent) {
+			let (id, pending_collation) = match state.pending_candidates.entry(parent) {
 				Entry::Occupied(entry)
 					if entry.get().1.commitments_hash ==
 						Some(candidate_receipt.commitments_hash) =>
-					entry.remove().0,
+					entry.remove(),
 				Entry::Occupied(_) => {
 					gum::error!(
 						target: LOG_TARGET,
@@ -1223,6 +1312,9 @@ async fn process_msg<Context>(
 				Entry::Vacant(_) => return,
 			};
 
+			state
+				.scores
+				.note(&pending_collation.peer_id, Some(&id), ScoreEvent::InvalidCandidate);
 			report_collator(&mut state.reputation, ctx.sender(), &state.peer_data, id.clone())
 				.await;
 
@@ -1238,12 +1330,16 @@ pub(crate) async fn run<Context>(
 	keystore: KeystorePtr,
 	eviction_policy: crate::CollatorEvictionPolicy,
 	metrics: Metrics,
+	db: Arc<dyn Database>,
+	scoring_config: CollatorScoringConfig,
 ) -> std::result::Result<(), crate::error::FatalError> {
+	let scores = CollatorScores::new(db, scoring_config, metrics.clone());
 	run_inner(
 		ctx,
 		keystore,
 		eviction_policy,
 		metrics,
+		scores,
 		ReputationAggregator::default(),
 		REPUTATION_CHANGE_INTERVAL,
 	)
@@ -1256,13 +1352,27 @@ async fn run_inner<Context>(
 	keystore: KeystorePtr,
 	eviction_policy: crate::CollatorEvictionPolicy,
 	metrics: Metrics,
+	scores: CollatorScores,
 	reputation: ReputationAggregator,
 	reputation_interval: Duration,
 ) -> std::result::Result<(), crate::error::FatalError> {
 	let new_reputation_delay = || futures_timer::Delay::new(reputation_interval).fuse();
 	let mut reputation_delay = new_reputation_delay();
 
-	let mut state = State { metrics, reputation, ..Default::default() };
+	let mut state = State {
+		view: Default::default(),
+		active_paras: Default::default(),
+		peer_data: Default::default(),
+		requested_collations: Default::default(),
+		metrics,
+		span_per_relay_parent: Default::default(),
+		collation_fetches: Default::default(),
+		collation_fetch_timeouts: Default::default(),
+		collations_per_relay_parent: Default::default(),
+		pending_candidates: Default::default(),
+		reputation,
+		scores,
+	};
 
 	let next_inactivity_stream = tick_stream(ACTIVITY_POLL);
 	futures::pin_mut!(next_inactivity_stream);
@@ -1293,6 +1403,7 @@ async fn run_inner<Context>(
 			}
 			_ = next_inactivity_stream.next() => {
 				disconnect_inactive_peers(ctx.sender(), &eviction_policy, &state.peer_data).await;
+				state.scores.flush();
 			}
 			res = state.collation_fetches.select_next_some() => {
 				handle_collation_fetched_result(&mut ctx, &mut state, res).await;
@@ -1308,29 +1419,37 @@ async fn run_inner<Context>(
 				dequeue_next_collation_and_fetch(&mut ctx, &mut state, relay_parent, collator_id).await;
 			}
 			_ = check_collations_stream.next() => {
-				let reputation_changes = poll_requests(
+				let failed_requests = poll_requests(
 					&mut state.requested_collations,
 					&state.metrics,
 					&state.span_per_relay_parent,
 				).await;
 
-				for (peer_id, rep) in reputation_changes {
-					modify_reputation(&mut state.reputation,ctx.sender(), peer_id, rep).await;
+				for (peer_id, rep) in failed_requests {
+					let collator_id = state.peer_data.get(&peer_id).and_then(PeerData::collator_id);
+					state.scores.note(&peer_id, collator_id, ScoreEvent::FetchFailed);
+					if let Some(rep) = rep {
+						modify_reputation(&mut state.reputation,ctx.sender(), peer_id, rep).await;
+					}
 				}
 			},
 		}
 	}
 
+	state.scores.flush();
+
 	Ok(())
 }
 
+/// Poll all pending collation requests, returning the peers of the failed ones along with the
+/// reputation change to apply, if any.
 async fn poll_requests(
 	requested_collations: &mut HashMap<PendingCollation, PerRequest>,
 	metrics: &Metrics,
 	span_per_relay_parent: &HashMap<Hash, PerLeafSpan>,
-) -> Vec<(PeerId, Rep)> {
+) -> Vec<(PeerId, Option<Rep>)> {
 	let mut retained_requested = HashSet::new();
-	let mut reputation_changes = Vec::new();
+	let mut failed_requests = Vec::new();
 	for (pending_collation, per_req) in requested_collations.iter_mut() {
 		// Despite the await, this won't block on the response itself.
 		let result =
@@ -1340,12 +1459,12 @@ async fn poll_requests(
 		if !result.is_ready() {
 			retained_requested.insert(pending_collation.clone());
 		}
-		if let CollationFetchResult::Error(Some(rep)) = result {
-			reputation_changes.push((pending_collation.peer_id, rep));
+		if let CollationFetchResult::Error(rep) = result {
+			failed_requests.push((pending_collation.peer_id, rep));
 		}
 	}
 	requested_collations.retain(|k, _| retained_requested.contains(k));
-	reputation_changes
+	failed_requests
 }
 
 /// Dequeue another collation and fetch.
@@ -1360,7 +1479,7 @@ async fn dequeue_next_collation_and_fetch<Context>(
 	if let Some((next, id)) = state
 		.collations_per_relay_parent
 		.get_mut(&relay_parent)
-		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch)))
+		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch), &state.scores))
 	{
 		gum::debug!(
 			target: LOG_TARGET,
@@ -1409,6 +1528,17 @@ async fn handle_collation_fetched_result<Context>(
 		},
 	};
 
+	let peer_id = collation_event.1.peer_id;
+	if let Some(advertised_at) =
+		state.peer_data.get(&peer_id).and_then(|p| p.advertised_at(&relay_parent))
+	{
+		state.scores.note(
+			&peer_id,
+			Some(&collation_event.0),
+			ScoreEvent::Fetched(advertised_at.elapsed()),
+		);
+	}
+
 	if let Some(collations) = state.collations_per_relay_parent.get_mut(&relay_parent) {
 		if let CollationStatus::Seconded = collations.status {
 			gum::debug!(
@@ -122,9 +122,25 @@ struct TestHarness {
 	virtual_overseer: VirtualOverseer,
 }
 
+const SCORING_CONFIG: CollatorScoringConfig = CollatorScoringConfig { col_data: 0 };
+
+fn test_db() -> Arc<dyn Database> {
+	let db = kvdb_memorydb::create(1);
+	let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[0]);
+	Arc::new(db)
+}
+
 fn test_harness<T: Future<Output = VirtualOverseer>>(
 	reputation: ReputationAggregator,
 	test: impl FnOnce(TestHarness) -> T,
+) {
+	test_harness_with_db(reputation, test_db(), test)
+}
+
+fn test_harness_with_db<T: Future<Output = VirtualOverseer>>(
+	reputation: ReputationAggregator,
+	db: Arc<dyn Database>,
+	test: impl FnOnce(TestHarness) -> T,
 ) {
 	let _ = env_logger::builder()
 		.is_test(true)
@@ -152,6 +168,7 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 			undeclared: DECLARE_TIMEOUT,
 		},
 		Metrics::default(),
+		CollatorScores::new(db, SCORING_CONFIG, Metrics::default()),
 		reputation,
 		REPUTATION_CHANGE_TEST_INTERVAL,
 	);
@@ -1168,3 +1185,110 @@ fn view_change_clears_old_collators() {
 		virtual_overseer
 	})
 }
+
+#[test]
+fn collator_scores_persist_across_restarts() {
+	let db = test_db();
+	let peer = PeerId::random();
+	let collator = CollatorPair::generate().0.public();
+
+	let mut scores = CollatorScores::new(db.clone(), SCORING_CONFIG, Metrics::default());
+	scores.load(&peer, &collator);
+	assert_eq!(scores.priority(&peer, &collator), 0);
+
+	scores.note(&peer, Some(&collator), ScoreEvent::Fetched(Duration::from_millis(100)));
+	scores.note(&peer, Some(&collator), ScoreEvent::Fetched(Duration::from_millis(300)));
+	scores.note(&peer, Some(&collator), ScoreEvent::DuplicateAdvertisement);
+	scores.note(&peer, Some(&collator), ScoreEvent::InvalidCandidate);
+
+	let score = scores.collator_score(&collator).cloned().unwrap();
+	assert_eq!(score.fetched, 2);
+	assert_eq!(score.duplicate_advertisements, 1);
+	assert_eq!(score.invalid_candidates, 1);
+	assert_eq!(score.fetch_latency_ms, Some(100 - 100 / 8 + 300 / 8));
+	assert!(score.value() < 0);
+	let priority = scores.priority(&peer, &collator);
+	assert_eq!(priority, 2 * score.value());
+
+	// Unloading persists the scores.
+	scores.unload(&peer, Some(&collator));
+	assert_eq!(scores.priority(&peer, &collator), 0);
+
+	let mut scores = CollatorScores::new(db, SCORING_CONFIG, Metrics::default());
+	scores.load(&peer, &collator);
+	assert_eq!(scores.collator_score(&collator), Some(&score));
+	assert_eq!(scores.priority(&peer, &collator), priority);
+}
+
+#[test]
+fn best_scored_collator_is_fetched_first() {
+	let test_state = TestState::default();
+
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+	let peer_d = PeerId::random();
+
+	// The collator behind `peer_c` provided an invalid candidate before.
+	let db = test_db();
+	{
+		let mut scores = CollatorScores::new(db.clone(), SCORING_CONFIG, Metrics::default());
+		let collator_c = test_state.collators[1].public();
+		scores.load(&peer_c, &collator_c);
+		scores.note(&peer_c, Some(&collator_c), ScoreEvent::InvalidCandidate);
+		scores.flush();
+	}
+
+	test_harness_with_db(ReputationAggregator::new(|_| true), db, |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		for (peer, collator) in
+			[peer_b, peer_c, peer_d].into_iter().zip(test_state.collators.iter().cloned())
+		{
+			connect_and_declare_collator(
+				&mut virtual_overseer,
+				peer,
+				collator,
+				test_state.chain_ids[0],
+			)
+			.await;
+		}
+
+		advertise_collation(&mut virtual_overseer, peer_b, test_state.relay_parent).await;
+		advertise_collation(&mut virtual_overseer, peer_d, test_state.relay_parent).await;
+		advertise_collation(&mut virtual_overseer, peer_c, test_state.relay_parent).await;
+
+		// The first advertisement is fetched right away. Dropping the response channel lets the
+		// fetch fail.
+		assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// `peer_c` advertised last, but the better scored `peer_d` is fetched first.
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendRequests(
+				reqs,
+				IfDisconnected::ImmediateError,
+			)) => {
+				assert_matches!(&reqs[..], [Requests::CollationFetchingV1(req)] => {
+					assert_eq!(req.peer, Recipient::Peer(peer_d));
+				});
+			}
+		);
+
+		virtual_overseer
+	});
+}
@@ -32,7 +32,7 @@ use polkadot_node_network_protocol::authority_discovery::AuthorityDiscovery;
 use polkadot_node_subsystem_util::nesting_sender::NestingSender;
 use sp_keystore::KeystorePtr;
 
-use polkadot_node_network_protocol::request_response::{incoming::IncomingRequestReceiver, v1};
+use polkadot_node_network_protocol::request_response::{v1, QuotaReceiver};
 use polkadot_node_primitives::DISPUTE_WINDOW;
 use polkadot_node_subsystem::{
 	messages::DisputeDistributionMessage, overseer, FromOrchestra, OverseerSignal,
@@ -124,8 +124,8 @@ pub struct DisputeDistributionSubsystem<AD> {
 	/// Receive messages from `DisputeSender` background tasks.
 	sender_rx: mpsc::Receiver<DisputeSenderMessage>,
 
-	/// Receiver for incoming requests.
-	req_receiver: Option<IncomingRequestReceiver<v1::DisputeRequest>>,
+	/// Receiver for incoming requests, enforcing a quota on each peer.
+	req_receiver: Option<QuotaReceiver<v1::DisputeRequest>>,
 
 	/// Authority discovery service.
 	authority_discovery: AD,
@@ -159,7 +159,7 @@ where
 	/// Create a new instance of the dispute distribution.
 	pub fn new(
 		keystore: KeystorePtr,
-		req_receiver: IncomingRequestReceiver<v1::DisputeRequest>,
+		req_receiver: QuotaReceiver<v1::DisputeRequest>,
 		authority_discovery: AD,
 		metrics: Metrics,
 	) -> Self {
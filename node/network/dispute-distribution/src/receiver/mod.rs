@@ -25,7 +25,7 @@ use futures::{
 	channel::oneshot,
 	future::poll_fn,
 	pin_mut,
-	stream::{FuturesUnordered, StreamExt},
+	stream::{self, BoxStream, FuturesUnordered, StreamExt},
 	Future,
 };
 
@@ -35,7 +35,7 @@ use polkadot_node_network_protocol::{
 	request_response::{
 		incoming::{self, OutgoingResponse, OutgoingResponseSender},
 		v1::{DisputeRequest, DisputeResponse},
-		IncomingRequest, IncomingRequestReceiver,
+		IncomingRequest, QuotaReceiver,
 	},
 	PeerId, UnifiedReputationChange as Rep,
 };
@@ -100,8 +100,10 @@ pub struct DisputesReceiver<Sender, AD> {
 	/// Subsystem sender for communication with other subsystems.
 	sender: Sender,
 
-	/// Channel to retrieve incoming requests from.
-	receiver: IncomingRequestReceiver<DisputeRequest>,
+	/// Incoming requests, requests of peers exceeding their quota are answered right away.
+	///
+	/// A stream, so a request being received is not dropped in between polls.
+	receiver: BoxStream<'static, incoming::Result<IncomingRequest<DisputeRequest>>>,
 
 	/// Rate limiting queue for each peer (only authorities).
 	peer_queues: PeerQueues,
@@ -155,7 +157,7 @@ where
 	/// Create a new receiver which can be `run`.
 	pub fn new(
 		sender: Sender,
-		receiver: IncomingRequestReceiver<DisputeRequest>,
+		receiver: QuotaReceiver<DisputeRequest>,
 		authority_discovery: AD,
 		metrics: Metrics,
 	) -> Self {
@@ -164,6 +166,11 @@ where
 			session_cache_lru_size: NonZeroUsize::new(DISPUTE_WINDOW.get() as usize)
 				.expect("Dispute window can not be 0; qed"),
 		});
+		let receiver = stream::unfold(receiver, |mut receiver| async move {
+			let next = receiver.recv(|| vec![COST_INVALID_REQUEST]).await;
+			Some((next, receiver))
+		})
+		.boxed();
 		Self {
 			runtime,
 			sender,
@@ -259,9 +266,8 @@ where
 				return Poll::Ready(Ok(MuxedMessage::WakeCheckBatches(ready_batches)))
 			}
 
-			let next_req = self.receiver.recv(|| vec![COST_INVALID_REQUEST]);
-			pin_mut!(next_req);
-			if let Poll::Ready(r) = next_req.poll(ctx) {
+			// The stream never ends, errors are returned as items:
+			if let Poll::Ready(Some(r)) = self.receiver.poll_next_unpin(ctx) {
 				return match r {
 					Err(e) => Poll::Ready(Err(incoming::Error::from(e).into())),
 					Ok(v) => Poll::Ready(Ok(MuxedMessage::NewRequest(v))),
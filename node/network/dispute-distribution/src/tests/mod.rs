@@ -36,7 +36,7 @@ use parity_scale_codec::{Decode, Encode};
 use sc_network::config::RequestResponseConfig;
 
 use polkadot_node_network_protocol::{
-	request_response::{v1::DisputeRequest, IncomingRequest, ReqProtocolNames},
+	request_response::{v1::DisputeRequest, IncomingRequest, QuotaReceiver, ReqProtocolNames},
 	PeerId,
 };
 use sp_keyring::Sr25519Keyring;
@@ -844,6 +844,8 @@ where
 	let genesis_hash = Hash::repeat_byte(0xff);
 	let req_protocol_names = ReqProtocolNames::new(&genesis_hash, None);
 	let (req_receiver, req_cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
+	let req_receiver =
+		QuotaReceiver::with_default_quota(req_receiver, Box::new(MOCK_AUTHORITY_DISCOVERY.clone()));
 	let subsystem = DisputeDistributionSubsystem::new(
 		keystore,
 		req_receiver,
@@ -40,13 +40,15 @@ use sp_application_crypto::{AppCrypto, ByteArray};
 use sp_keystore::{Keystore, KeystorePtr};
 
 use polkadot_node_network_protocol::{
-	authority_discovery::AuthorityDiscovery, peer_set::PeerSet, GossipSupportNetworkMessage,
-	PeerId, Versioned,
+	authority_discovery::AuthorityDiscovery,
+	grid_topology::{SessionGridTopology, TopologyPeerInfo},
+	peer_set::{PeerSet, PerPeerSet},
+	GossipSupportNetworkMessage, PeerId, Versioned,
 };
 use polkadot_node_subsystem::{
 	messages::{
-		GossipSupportMessage, NetworkBridgeEvent, NetworkBridgeRxMessage, NetworkBridgeTxMessage,
-		RuntimeApiMessage, RuntimeApiRequest,
+		AuthorityConnectivity, ConnectivityReport, GossipSupportMessage, NetworkBridgeEvent,
+		NetworkBridgeRxMessage, NetworkBridgeTxMessage, RuntimeApiMessage, RuntimeApiRequest,
 	},
 	overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError,
 };
@@ -65,6 +67,9 @@ const LOG_TARGET: &str = "parachain::gossip-support";
 // since the last authority discovery resolution failure.
 const BACKOFF_DURATION: Duration = Duration::from_secs(5);
 
+/// The backoff doubles with each consecutive failure, but never exceeds this.
+const MAX_BACKOFF_DURATION: Duration = Duration::from_secs(300);
+
 /// Duration after which we consider low connectivity a problem.
 ///
 /// Especially at startup low connectivity is expected (authority discovery cache needs to be
@@ -87,6 +92,11 @@ pub struct GossipSupport<AD> {
 	// `None` otherwise.
 	last_failure: Option<Instant>,
 
+	/// How long to wait after a failed connection request before reissuing it.
+	///
+	/// Starts at `BACKOFF_DURATION` and doubles with each consecutive failure.
+	reconnect_backoff: Duration,
+
 	/// First time we did not reach our connectivity threshold.
 	///
 	/// This is the time of the first failed attempt to connect to >2/3 of all validators in a
@@ -99,12 +109,19 @@ pub struct GossipSupport<AD> {
 	/// waiting for actual connection.
 	resolved_authorities: HashMap<AuthorityDiscoveryId, HashSet<Multiaddr>>,
 
-	/// Actually connected authorities.
-	connected_authorities: HashMap<AuthorityDiscoveryId, PeerId>,
+	/// Authorities we could not resolve any addresses of on the last connection request.
+	unresolved_authorities: HashSet<AuthorityDiscoveryId>,
+
+	/// Actually connected authorities, per peer set.
+	connected_authorities: PerPeerSet<HashMap<AuthorityDiscoveryId, PeerId>>,
 	/// By `PeerId`.
 	///
 	/// Needed for efficient handling of disconnect events.
-	connected_authorities_by_peer_id: HashMap<PeerId, HashSet<AuthorityDiscoveryId>>,
+	connected_authorities_by_peer_id: PerPeerSet<HashMap<PeerId, HashSet<AuthorityDiscoveryId>>>,
+
+	/// Our neighbors in the gossip grid of the current session.
+	grid_neighbors: HashSet<AuthorityDiscoveryId>,
+
 	/// Authority discovery service.
 	authority_discovery: AD,
 
@@ -127,10 +144,13 @@ where
 			keystore,
 			last_session_index: None,
 			last_failure: None,
+			reconnect_backoff: BACKOFF_DURATION,
 			failure_start: None,
 			resolved_authorities: HashMap::new(),
-			connected_authorities: HashMap::new(),
-			connected_authorities_by_peer_id: HashMap::new(),
+			unresolved_authorities: HashSet::new(),
+			connected_authorities: PerPeerSet::default(),
+			connected_authorities_by_peer_id: PerPeerSet::default(),
+			grid_neighbors: HashSet::new(),
 			authority_discovery,
 			metrics,
 		}
@@ -164,7 +184,15 @@ where
 			match message {
 				FromOrchestra::Communication {
 					msg: GossipSupportMessage::NetworkBridgeUpdate(ev),
-				} => self.handle_connect_disconnect(ev),
+				} => self.handle_connect_disconnect(PeerSet::Validation, ev),
+				FromOrchestra::Communication {
+					msg: GossipSupportMessage::CollationNetworkBridgeUpdate(ev),
+				} => self.handle_connect_disconnect(PeerSet::Collation, ev),
+				FromOrchestra::Communication {
+					msg: GossipSupportMessage::InspectConnectivity(tx),
+				} => {
+					let _ = tx.send(self.connectivity_report());
+				},
 				FromOrchestra::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
 					activated,
 					..
@@ -193,7 +221,7 @@ where
 		for leaf in leaves {
 			let current_index = util::request_session_index_for_child(leaf, sender).await.await??;
 			let since_failure = self.last_failure.map(|i| i.elapsed()).unwrap_or_default();
-			let force_request = since_failure >= BACKOFF_DURATION;
+			let force_request = since_failure >= self.reconnect_backoff;
 			let leaf_session = Some((current_index, leaf));
 			let maybe_new_session = match self.last_session_index {
 				Some(i) if current_index <= i => None,
@@ -262,7 +290,7 @@ where
 					// Gossip topology is only relevant for authorities in the current session.
 					let our_index = self.get_key_index_and_update_metrics(&session_info)?;
 
-					update_gossip_topology(
+					self.grid_neighbors = update_gossip_topology(
 						sender,
 						our_index,
 						session_info.discovery_keys,
@@ -270,6 +298,7 @@ where
 						session_index,
 					)
 					.await?;
+					self.update_connectivity_metrics();
 				}
 			}
 		}
@@ -328,6 +357,7 @@ where
 		let mut validator_addrs = Vec::with_capacity(authorities.len());
 		let mut failures = 0;
 		let mut resolved = HashMap::with_capacity(authorities.len());
+		let mut unresolved = HashSet::new();
 		for authority in authorities {
 			if let Some(addrs) =
 				self.authority_discovery.get_addresses_by_authority_id(authority.clone()).await
@@ -341,9 +371,12 @@ where
 					"Couldn't resolve addresses of authority: {:?}",
 					authority
 				);
+				unresolved.insert(authority);
 			}
 		}
 		self.resolved_authorities = resolved;
+		self.unresolved_authorities = unresolved;
+		self.update_connectivity_metrics();
 		gum::debug!(target: LOG_TARGET, %num, "Issuing a connection request");
 
 		sender
@@ -376,35 +409,51 @@ where
 					);
 				},
 			}
+			if self.last_failure.is_some() {
+				self.reconnect_backoff = (self.reconnect_backoff * 2).min(MAX_BACKOFF_DURATION);
+			}
+			gum::debug!(
+				target: LOG_TARGET,
+				backoff = ?self.reconnect_backoff,
+				"Reissuing the connection request after backoff",
+			);
 			self.last_failure = Some(timestamp);
 		} else {
 			self.last_failure = None;
 			self.failure_start = None;
+			self.reconnect_backoff = BACKOFF_DURATION;
 		};
 	}
 
-	fn handle_connect_disconnect(&mut self, ev: NetworkBridgeEvent<GossipSupportNetworkMessage>) {
+	fn handle_connect_disconnect(
+		&mut self,
+		peer_set: PeerSet,
+		ev: NetworkBridgeEvent<GossipSupportNetworkMessage>,
+	) {
 		match ev {
 			NetworkBridgeEvent::PeerConnected(peer_id, _, _, o_authority) => {
 				if let Some(authority_ids) = o_authority {
 					authority_ids.iter().for_each(|a| {
-						self.connected_authorities.insert(a.clone(), peer_id);
+						self.connected_authorities[peer_set].insert(a.clone(), peer_id);
 					});
-					self.connected_authorities_by_peer_id.insert(peer_id, authority_ids);
+					self.connected_authorities_by_peer_id[peer_set].insert(peer_id, authority_ids);
+					self.update_connectivity_metrics();
 				}
 			},
 			NetworkBridgeEvent::PeerDisconnected(peer_id) => {
-				if let Some(authority_ids) = self.connected_authorities_by_peer_id.remove(&peer_id)
+				if let Some(authority_ids) =
+					self.connected_authorities_by_peer_id[peer_set].remove(&peer_id)
 				{
 					authority_ids.into_iter().for_each(|a| {
-						self.connected_authorities.remove(&a);
+						self.connected_authorities[peer_set].remove(&a);
 					});
+					self.update_connectivity_metrics();
 				}
 			},
 			NetworkBridgeEvent::OurViewChange(_) => {},
 			NetworkBridgeEvent::PeerViewChange(_, _) => {},
 			NetworkBridgeEvent::NewGossipTopology { .. } => {},
-			NetworkBridgeEvent::PeerMessage(_, Versioned::V1(v)) => {
+			NetworkBridgeEvent::PeerMessage(_, Versioned::V1(v) | Versioned::V2(v)) => {
 				match v {};
 			},
 		}
@@ -412,14 +461,20 @@ where
 
 	/// Check connectivity and report on it in logs.
 	fn check_connectivity(&mut self) {
-		let absolute_connected = self.connected_authorities.len();
+		let connected_authorities = &self.connected_authorities[PeerSet::Validation];
+		let absolute_connected = connected_authorities.len();
 		let absolute_resolved = self.resolved_authorities.len();
 		let connected_ratio =
 			(100 * absolute_connected).checked_div(absolute_resolved).unwrap_or(100);
 		let unconnected_authorities = self
 			.resolved_authorities
 			.iter()
-			.filter(|(a, _)| !self.connected_authorities.contains_key(a));
+			.filter(|(a, _)| !connected_authorities.contains_key(a));
+		let unreachable_grid_neighbors = self
+			.grid_neighbors
+			.iter()
+			.filter(|a| !connected_authorities.contains_key(a))
+			.count();
 		// TODO: Make that warning once connectivity issues are fixed (no point in warning, if
 		// we already know it is broken.
 		// https://github.com/paritytech/polkadot/issues/3921
@@ -435,10 +490,62 @@ where
 			?connected_ratio,
 			?absolute_connected,
 			?absolute_resolved,
+			unresolved = ?self.unresolved_authorities.len(),
+			?unreachable_grid_neighbors,
 			unconnected_authorities = %pretty,
 			"Connectivity Report"
 		);
 	}
+
+	/// Authorities are unreachable if we are not connected to them on the validation peer set,
+	/// regardless of whether their addresses could be resolved.
+	fn update_connectivity_metrics(&self) {
+		let connected = &self.connected_authorities[PeerSet::Validation];
+		let unreachable =
+			self.resolved_authorities.keys().filter(|a| !connected.contains_key(a)).count();
+		let unreachable_grid_neighbors =
+			self.grid_neighbors.iter().filter(|a| !connected.contains_key(a)).count();
+
+		self.metrics.on_connectivity(
+			self.unresolved_authorities.len(),
+			unreachable + self.unresolved_authorities.len(),
+			unreachable_grid_neighbors,
+		);
+	}
+
+	/// The connectivity to all authorities we want to be connected to.
+	fn connectivity_report(&self) -> ConnectivityReport {
+		let authorities: HashSet<_> = self
+			.resolved_authorities
+			.keys()
+			.chain(self.unresolved_authorities.iter())
+			.chain(self.grid_neighbors.iter())
+			.collect();
+
+		let mut authorities: Vec<_> = authorities
+			.into_iter()
+			.map(|authority| AuthorityConnectivity {
+				authority: authority.clone(),
+				resolved: self.resolved_authorities.contains_key(authority),
+				connected: [PeerSet::Validation, PeerSet::Collation]
+					.into_iter()
+					.filter(|peer_set| {
+						self.connected_authorities[*peer_set].contains_key(authority)
+					})
+					.collect(),
+				grid_neighbor: self.grid_neighbors.contains(authority),
+			})
+			.collect();
+		authorities.sort_by_key(|a| (!a.grid_neighbor, a.is_reachable()));
+
+		ConnectivityReport {
+			session: self.last_session_index,
+			reconnect_in: self
+				.last_failure
+				.map(|failure| self.reconnect_backoff.saturating_sub(failure.elapsed())),
+			authorities,
+		}
+	}
 }
 
 // Get the authorities of the past, present, and future.
@@ -496,13 +603,15 @@ fn remove_all_controlled(
 /// This limits the amount of gossip peers to 2 * `sqrt(len)` and ensures the diameter of 2.
 ///
 /// [web3]: https://research.web3.foundation/en/latest/polkadot/networking/3-avail-valid.html#topology
+///
+/// Returns the authorities which are our neighbors in the grid.
 async fn update_gossip_topology(
 	sender: &mut impl overseer::GossipSupportSenderTrait,
 	our_index: usize,
 	authorities: Vec<AuthorityDiscoveryId>,
 	relay_parent: Hash,
 	session_index: SessionIndex,
-) -> Result<(), util::Error> {
+) -> Result<HashSet<AuthorityDiscoveryId>, util::Error> {
 	// retrieve BABE randomness
 	let random_seed = {
 		let (tx, rx) = oneshot::channel();
@@ -542,6 +651,8 @@ async fn update_gossip_topology(
 		(shuffled_indices, canonical_shuffling)
 	};
 
+	let grid_neighbors = grid_neighbors(our_index, &shuffled_indices, &canonical_shuffling);
+
 	sender
 		.send_message(NetworkBridgeRxMessage::NewGossipTopology {
 			session: session_index,
@@ -551,7 +662,40 @@ async fn update_gossip_topology(
 		})
 		.await;
 
-	Ok(())
+	Ok(grid_neighbors)
+}
+
+/// The authorities in the same row or column of the grid as the one at `our_index`.
+fn grid_neighbors(
+	our_index: usize,
+	shuffled_indices: &[usize],
+	canonical_shuffling: &[(AuthorityDiscoveryId, ValidatorIndex)],
+) -> HashSet<AuthorityDiscoveryId> {
+	let topology = SessionGridTopology::new(
+		shuffled_indices.to_vec(),
+		canonical_shuffling
+			.iter()
+			.map(|(discovery_id, validator_index)| TopologyPeerInfo {
+				peer_ids: Vec::new(),
+				validator_index: *validator_index,
+				discovery_id: discovery_id.clone(),
+			})
+			.collect(),
+	);
+
+	let neighbors = match topology.compute_grid_neighbors_for(ValidatorIndex(our_index as _)) {
+		Some(neighbors) => neighbors,
+		None => return HashSet::new(),
+	};
+
+	neighbors
+		.validator_indices_x
+		.iter()
+		.chain(neighbors.validator_indices_y.iter())
+		.filter_map(|v| shuffled_indices.get(v.0 as usize))
+		.filter_map(|i| canonical_shuffling.get(*i))
+		.map(|(authority, _)| authority.clone())
+		.collect()
 }
 
 #[overseer::subsystem(GossipSupport, error = SubsystemError, prefix = self::overseer)]
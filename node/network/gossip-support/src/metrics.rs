@@ -32,6 +32,12 @@ struct MetricsInner {
 	is_authority: Gauge<U64>,
 	/// Tracks authority status for parachain approval checking.
 	is_parachain_validator: Gauge<U64>,
+	/// Number of authorities authority discovery knows no addresses of.
+	unresolved_authorities: Gauge<U64>,
+	/// Number of authorities we are not connected to on the validation peer set.
+	unreachable_authorities: Gauge<U64>,
+	/// Number of our grid neighbors we are not connected to on the validation peer set.
+	unreachable_grid_neighbors: Gauge<U64>,
 }
 
 impl Metrics {
@@ -68,6 +74,20 @@ impl Metrics {
 			metrics.is_parachain_validator.set(0);
 		}
 	}
+
+	/// Set the connectivity metrics.
+	pub fn on_connectivity(
+		&self,
+		unresolved: usize,
+		unreachable: usize,
+		unreachable_grid_neighbors: usize,
+	) {
+		if let Some(metrics) = &self.0 {
+			metrics.unresolved_authorities.set(unresolved as u64);
+			metrics.unreachable_authorities.set(unreachable as u64);
+			metrics.unreachable_grid_neighbors.set(unreachable_grid_neighbors as u64);
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -85,6 +105,27 @@ impl metrics::Metrics for Metrics {
 				Updates at session boundary.")?,
 				registry,
 			)?,
+			unresolved_authorities: prometheus::register(
+				Gauge::new(
+					"polkadot_parachain_gossip_support_unresolved_authorities",
+					"Number of authorities we want to connect to but could not resolve any addresses of.",
+				)?,
+				registry,
+			)?,
+			unreachable_authorities: prometheus::register(
+				Gauge::new(
+					"polkadot_parachain_gossip_support_unreachable_authorities",
+					"Number of authorities we want to connect to but are not connected to on the validation peer set.",
+				)?,
+				registry,
+			)?,
+			unreachable_grid_neighbors: prometheus::register(
+				Gauge::new(
+					"polkadot_parachain_gossip_support_unreachable_grid_neighbors",
+					"Number of our neighbors in the gossip grid we are not connected to on the validation peer set.",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -29,7 +29,11 @@ use sp_consensus_babe::{AllowedSlots, BabeEpochConfiguration, Epoch as BabeEpoch
 use sp_core::crypto::Pair as PairT;
 use sp_keyring::Sr25519Keyring;
 
-use polkadot_node_network_protocol::grid_topology::{SessionGridTopology, TopologyPeerInfo};
+use polkadot_node_network_protocol::{
+	grid_topology::{SessionGridTopology, TopologyPeerInfo},
+	peer_set::ValidationVersion,
+	ObservedRole,
+};
 use polkadot_node_subsystem::{
 	jaeger,
 	messages::{AllMessages, RuntimeApiMessage, RuntimeApiRequest},
@@ -719,3 +723,128 @@ fn issues_a_connection_request_when_last_request_was_mostly_unresolved() {
 	assert_eq!(state.last_session_index, Some(1));
 	assert!(state.last_failure.is_none());
 }
+
+#[test]
+fn reconnect_backoff_doubles_on_consecutive_failures() {
+	let mut state = make_subsystem();
+	// Nobody can be resolved.
+	state.authority_discovery.addrs.clear();
+	let (mut sender, _receiver) = test_helpers::sender_receiver();
+
+	executor::block_on(async {
+		state
+			.issue_connection_request(&mut sender, AUTHORITIES_WITHOUT_US.clone())
+			.await;
+		assert_eq!(state.reconnect_backoff, BACKOFF_DURATION);
+
+		state
+			.issue_connection_request(&mut sender, AUTHORITIES_WITHOUT_US.clone())
+			.await;
+		assert_eq!(state.reconnect_backoff, 2 * BACKOFF_DURATION);
+
+		for _ in 0..10 {
+			state
+				.issue_connection_request(&mut sender, AUTHORITIES_WITHOUT_US.clone())
+				.await;
+		}
+		assert_eq!(state.reconnect_backoff, MAX_BACKOFF_DURATION);
+
+		state.authority_discovery = MOCK_AUTHORITY_DISCOVERY.clone();
+		state
+			.issue_connection_request(&mut sender, AUTHORITIES_WITHOUT_US.clone())
+			.await;
+		assert_eq!(state.reconnect_backoff, BACKOFF_DURATION);
+		assert!(state.last_failure.is_none());
+	});
+}
+
+#[test]
+fn reports_unreachable_grid_neighbors() {
+	let hash = Hash::repeat_byte(0xAA);
+	let mut state = make_subsystem();
+	let charlie: AuthorityDiscoveryId = Sr25519Keyring::Charlie.public().into();
+	let eve: AuthorityDiscoveryId = Sr25519Keyring::Eve.public().into();
+	let two: AuthorityDiscoveryId = Sr25519Keyring::Two.public().into();
+	state.authority_discovery.addrs.remove(&charlie);
+
+	let mut state = test_harness(state, |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		overseer_signal_active_leaves(overseer, hash).await;
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::SessionIndexForChild(tx),
+			)) => {
+				tx.send(Ok(1)).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::SessionInfo(_, tx),
+			)) => {
+				tx.send(Ok(Some(make_session_info()))).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::Authorities(tx),
+			)) => {
+				tx.send(Ok(AUTHORITIES.clone())).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ConnectToResolvedValidators { .. }
+			)
+		);
+
+		test_neighbors(overseer, 1).await;
+
+		virtual_overseer
+	});
+
+	// Charlie, Eve and Two are our neighbors, see `ROW_NEIGHBORS` and `COLUMN_NEIGHBORS`.
+	assert_eq!(state.grid_neighbors, HashSet::from([charlie.clone(), eve.clone(), two.clone()]));
+
+	let connect = |authority: &AuthorityDiscoveryId| {
+		NetworkBridgeEvent::PeerConnected(
+			PeerId::random(),
+			ObservedRole::Authority,
+			ValidationVersion::V1.into(),
+			Some(HashSet::from([authority.clone()])),
+		)
+	};
+	state.handle_connect_disconnect(PeerSet::Validation, connect(&eve));
+	state.handle_connect_disconnect(PeerSet::Collation, connect(&two));
+
+	let report = state.connectivity_report();
+	assert_eq!(report.session, Some(1));
+	assert_eq!(report.reconnect_in, None);
+	// All authorities but us.
+	assert_eq!(report.authorities.len(), AUTHORITIES_WITHOUT_US.len());
+
+	let unreachable_neighbors: HashMap<_, _> = report
+		.authorities
+		.iter()
+		.take_while(|a| a.grid_neighbor && !a.is_reachable())
+		.map(|a| (a.authority.clone(), (a.resolved, a.connected.clone())))
+		.collect();
+	assert_eq!(
+		unreachable_neighbors,
+		HashMap::from([(charlie, (false, vec![])), (two, (true, vec![PeerSet::Collation]))]),
+	);
+	assert_matches!(
+		&report.authorities[2],
+		AuthorityConnectivity { authority, grid_neighbor: true, .. } if authority == &eve
+	);
+	assert!(report.authorities[2].is_reachable());
+}
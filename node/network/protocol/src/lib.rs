@@ -251,22 +251,25 @@ impl View {
 
 /// A protocol-versioned type.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Versioned<V1> {
+pub enum Versioned<V1, V2> {
 	/// V1 type.
 	V1(V1),
+	/// V2 type.
+	V2(V2),
 }
 
-impl<V1: Clone> Versioned<&'_ V1> {
+impl<V1: Clone, V2: Clone> Versioned<&'_ V1, &'_ V2> {
 	/// Convert to a fully-owned version of the message.
-	pub fn clone_inner(&self) -> Versioned<V1> {
+	pub fn clone_inner(&self) -> Versioned<V1, V2> {
 		match *self {
 			Versioned::V1(inner) => Versioned::V1(inner.clone()),
+			Versioned::V2(inner) => Versioned::V2(inner.clone()),
 		}
 	}
 }
 
 /// All supported versions of the validation protocol message.
-pub type VersionedValidationProtocol = Versioned<v1::ValidationProtocol>;
+pub type VersionedValidationProtocol = Versioned<v1::ValidationProtocol, v2::ValidationProtocol>;
 
 impl From<v1::ValidationProtocol> for VersionedValidationProtocol {
 	fn from(v1: v1::ValidationProtocol) -> Self {
@@ -274,8 +277,14 @@ impl From<v1::ValidationProtocol> for VersionedValidationProtocol {
 	}
 }
 
+impl From<v2::ValidationProtocol> for VersionedValidationProtocol {
+	fn from(v2: v2::ValidationProtocol) -> Self {
+		VersionedValidationProtocol::V2(v2)
+	}
+}
+
 /// All supported versions of the collation protocol message.
-pub type VersionedCollationProtocol = Versioned<v1::CollationProtocol>;
+pub type VersionedCollationProtocol = Versioned<v1::CollationProtocol, v2::CollationProtocol>;
 
 impl From<v1::CollationProtocol> for VersionedCollationProtocol {
 	fn from(v1: v1::CollationProtocol) -> Self {
@@ -289,6 +298,7 @@ macro_rules! impl_versioned_full_protocol_from {
 			fn from(versioned_from: $from) -> $out {
 				match versioned_from {
 					Versioned::V1(x) => Versioned::V1(x.into()),
+					Versioned::V2(x) => Versioned::V2(x.into()),
 				}
 			}
 		}
@@ -298,7 +308,12 @@ macro_rules! impl_versioned_full_protocol_from {
 /// Implement `TryFrom` for one versioned enum variant into the inner type.
 /// `$m_ty::$variant(inner) -> Ok(inner)`
 macro_rules! impl_versioned_try_from {
-	($from:ty, $out:ty, $v1_pat:pat => $v1_out:expr) => {
+	(
+		$from:ty,
+		$out:ty,
+		$v1_pat:pat => $v1_out:expr,
+		$v2_pat:pat => $v2_out:expr
+	) => {
 		impl TryFrom<$from> for $out {
 			type Error = crate::WrongVariant;
 
@@ -306,6 +321,7 @@ macro_rules! impl_versioned_try_from {
 				#[allow(unreachable_patterns)] // when there is only one variant
 				match x {
 					Versioned::V1($v1_pat) => Ok(Versioned::V1($v1_out)),
+					Versioned::V2($v2_pat) => Ok(Versioned::V2($v2_out)),
 					_ => Err(crate::WrongVariant),
 				}
 			}
@@ -318,6 +334,7 @@ macro_rules! impl_versioned_try_from {
 				#[allow(unreachable_patterns)] // when there is only one variant
 				match x {
 					Versioned::V1($v1_pat) => Ok(Versioned::V1($v1_out.clone())),
+					Versioned::V2($v2_pat) => Ok(Versioned::V2($v2_out.clone())),
 					_ => Err(crate::WrongVariant),
 				}
 			}
@@ -326,7 +343,8 @@ macro_rules! impl_versioned_try_from {
 }
 
 /// Version-annotated messages used by the bitfield distribution subsystem.
-pub type BitfieldDistributionMessage = Versioned<v1::BitfieldDistributionMessage>;
+pub type BitfieldDistributionMessage =
+	Versioned<v1::BitfieldDistributionMessage, v2::BitfieldDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	BitfieldDistributionMessage,
 	VersionedValidationProtocol,
@@ -335,11 +353,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	BitfieldDistributionMessage,
-	v1::ValidationProtocol::BitfieldDistribution(x) => x
+	v1::ValidationProtocol::BitfieldDistribution(x) => x,
+	v2::ValidationProtocol::BitfieldDistribution(x) => x
 );
 
 /// Version-annotated messages used by the statement distribution subsystem.
-pub type StatementDistributionMessage = Versioned<v1::StatementDistributionMessage>;
+pub type StatementDistributionMessage =
+	Versioned<v1::StatementDistributionMessage, v2::StatementDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	StatementDistributionMessage,
 	VersionedValidationProtocol,
@@ -348,11 +368,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	StatementDistributionMessage,
-	v1::ValidationProtocol::StatementDistribution(x) => x
+	v1::ValidationProtocol::StatementDistribution(x) => x,
+	v2::ValidationProtocol::StatementDistribution(x) => x
 );
 
 /// Version-annotated messages used by the approval distribution subsystem.
-pub type ApprovalDistributionMessage = Versioned<v1::ApprovalDistributionMessage>;
+pub type ApprovalDistributionMessage =
+	Versioned<v1::ApprovalDistributionMessage, v2::ApprovalDistributionMessage>;
 impl_versioned_full_protocol_from!(
 	ApprovalDistributionMessage,
 	VersionedValidationProtocol,
@@ -361,11 +383,13 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedValidationProtocol,
 	ApprovalDistributionMessage,
-	v1::ValidationProtocol::ApprovalDistribution(x) => x
+	v1::ValidationProtocol::ApprovalDistribution(x) => x,
+	v2::ValidationProtocol::ApprovalDistribution(x) => x
 );
 
 /// Version-annotated messages used by the gossip-support subsystem (this is void).
-pub type GossipSupportNetworkMessage = Versioned<v1::GossipSupportNetworkMessage>;
+pub type GossipSupportNetworkMessage =
+	Versioned<v1::GossipSupportNetworkMessage, v2::GossipSupportNetworkMessage>;
 // This is a void enum placeholder, so never gets sent over the wire.
 impl TryFrom<VersionedValidationProtocol> for GossipSupportNetworkMessage {
 	type Error = WrongVariant;
@@ -381,8 +405,17 @@ impl<'a> TryFrom<&'a VersionedValidationProtocol> for GossipSupportNetworkMessag
 	}
 }
 
+// Gossip support also keeps track of the peers connected on the collation peer set.
+impl<'a> TryFrom<&'a VersionedCollationProtocol> for GossipSupportNetworkMessage {
+	type Error = WrongVariant;
+	fn try_from(_: &'a VersionedCollationProtocol) -> Result<Self, Self::Error> {
+		Err(WrongVariant)
+	}
+}
+
 /// Version-annotated messages used by the bitfield distribution subsystem.
-pub type CollatorProtocolMessage = Versioned<v1::CollatorProtocolMessage>;
+pub type CollatorProtocolMessage =
+	Versioned<v1::CollatorProtocolMessage, v2::CollatorProtocolMessage>;
 impl_versioned_full_protocol_from!(
 	CollatorProtocolMessage,
 	VersionedCollationProtocol,
@@ -391,7 +424,8 @@ impl_versioned_full_protocol_from!(
 impl_versioned_try_from!(
 	VersionedCollationProtocol,
 	CollatorProtocolMessage,
-	v1::CollationProtocol::CollatorProtocol(x) => x
+	v1::CollationProtocol::CollatorProtocol(x) => x,
+	v2::CollationProtocol::CollatorProtocol(x) => x
 );
 
 /// v1 notification protocol types.
@@ -551,3 +585,128 @@ pub mod v1 {
 		payload
 	}
 }
+
+/// v2 notification protocol types.
+///
+/// Only approval distribution changed compared to `v1`: assignments and approvals may
+/// cover multiple candidates of the same relay chain block.
+pub mod v2 {
+	use parity_scale_codec::{Decode, Encode};
+
+	use polkadot_node_primitives::approval::{
+		AssignmentCertKind, CandidateBitfield, IndirectAssignmentCert, IndirectSignedApprovalVote,
+		IndirectSignedApprovalVoteV2,
+	};
+
+	use crate::v1;
+
+	pub use v1::{
+		BitfieldDistributionMessage, CollationProtocol, CollatorProtocolMessage,
+		GossipSupportNetworkMessage, StatementDistributionMessage,
+	};
+
+	/// Network messages used by the approval distribution subsystem.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+	pub enum ApprovalDistributionMessage {
+		/// Assignments for candidates in recent, unfinalized blocks, together with the
+		/// candidates of the block claimed by each assignment.
+		///
+		/// Actually checking the assignment may yield a different result.
+		#[codec(index = 0)]
+		Assignments(Vec<(IndirectAssignmentCert, CandidateBitfield)>),
+		/// Approvals for candidates in some recent, unfinalized block.
+		#[codec(index = 1)]
+		Approvals(Vec<IndirectSignedApprovalVoteV2>),
+	}
+
+	impl From<v1::ApprovalDistributionMessage> for ApprovalDistributionMessage {
+		fn from(message: v1::ApprovalDistributionMessage) -> Self {
+			match message {
+				v1::ApprovalDistributionMessage::Assignments(assignments) => Self::Assignments(
+					assignments
+						.into_iter()
+						.map(|(cert, candidate_index)| (cert, candidate_index.into()))
+						.collect(),
+				),
+				v1::ApprovalDistributionMessage::Approvals(approvals) =>
+					Self::Approvals(approvals.into_iter().map(Into::into).collect()),
+			}
+		}
+	}
+
+	impl ApprovalDistributionMessage {
+		/// Convert into a `v1` message, keeping only the assignments and approvals which
+		/// cover a single candidate and can therefore be understood by `v1` peers.
+		///
+		/// Returns `None` if nothing representable is left.
+		pub fn into_v1(self) -> Option<v1::ApprovalDistributionMessage> {
+			match self {
+				Self::Assignments(assignments) => {
+					let assignments: Vec<_> = assignments
+						.into_iter()
+						.filter_map(|(cert, candidates)| {
+							if matches!(
+								cert.cert.kind,
+								AssignmentCertKind::RelayVRFModuloCompact { .. }
+							) || candidates.count_ones() != 1
+							{
+								return None
+							}
+							candidates.first_one().map(|candidate_index| (cert, candidate_index))
+						})
+						.collect();
+					(!assignments.is_empty())
+						.then(|| v1::ApprovalDistributionMessage::Assignments(assignments))
+				},
+				Self::Approvals(approvals) => {
+					let approvals: Vec<IndirectSignedApprovalVote> =
+						approvals.into_iter().filter_map(|vote| vote.try_into().ok()).collect();
+					(!approvals.is_empty())
+						.then(|| v1::ApprovalDistributionMessage::Approvals(approvals))
+				},
+			}
+		}
+	}
+
+	/// All network messages on the validation peer-set.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq, derive_more::From)]
+	pub enum ValidationProtocol {
+		/// Bitfield distribution messages
+		#[codec(index = 1)]
+		#[from]
+		BitfieldDistribution(BitfieldDistributionMessage),
+		/// Statement distribution messages
+		#[codec(index = 3)]
+		#[from]
+		StatementDistribution(StatementDistributionMessage),
+		/// Approval distribution messages
+		#[codec(index = 4)]
+		#[from]
+		ApprovalDistribution(ApprovalDistributionMessage),
+	}
+
+	impl From<v1::ValidationProtocol> for ValidationProtocol {
+		fn from(message: v1::ValidationProtocol) -> Self {
+			match message {
+				v1::ValidationProtocol::BitfieldDistribution(m) => Self::BitfieldDistribution(m),
+				v1::ValidationProtocol::StatementDistribution(m) => Self::StatementDistribution(m),
+				v1::ValidationProtocol::ApprovalDistribution(m) =>
+					Self::ApprovalDistribution(m.into()),
+			}
+		}
+	}
+
+	impl ValidationProtocol {
+		/// Convert into a `v1` message. See [`ApprovalDistributionMessage::into_v1`].
+		pub fn into_v1(self) -> Option<v1::ValidationProtocol> {
+			match self {
+				Self::BitfieldDistribution(m) =>
+					Some(v1::ValidationProtocol::BitfieldDistribution(m)),
+				Self::StatementDistribution(m) =>
+					Some(v1::ValidationProtocol::StatementDistribution(m)),
+				Self::ApprovalDistribution(m) =>
+					m.into_v1().map(v1::ValidationProtocol::ApprovalDistribution),
+			}
+		}
+	}
+}
@@ -72,7 +72,7 @@ impl PeerSet {
 		// Networking layer relies on `get_main_name()` being the main name of the protocol
 		// for peersets and connection management.
 		let protocol = peerset_protocol_names.get_main_name(self);
-		let fallback_names = PeerSetProtocolNames::get_fallback_names(self);
+		let fallback_names = peerset_protocol_names.get_fallback_names(self);
 		let max_notification_size = self.get_max_notification_size(is_authority);
 
 		match self {
@@ -118,7 +118,7 @@ impl PeerSet {
 	/// of the main protocol name reported by [`PeerSetProtocolNames::get_main_name()`].
 	pub fn get_main_version(self) -> ProtocolVersion {
 		match self {
-			PeerSet::Validation => ValidationVersion::V1.into(),
+			PeerSet::Validation => ValidationVersion::V3.into(),
 			PeerSet::Collation => CollationVersion::V1.into(),
 		}
 	}
@@ -144,6 +144,10 @@ impl PeerSet {
 			PeerSet::Validation =>
 				if version == ValidationVersion::V1.into() {
 					Some("validation/1")
+				} else if version == ValidationVersion::V2.into() {
+					Some("validation/2")
+				} else if version == ValidationVersion::V3.into() {
+					Some("validation/3")
 				} else {
 					None
 				},
@@ -211,6 +215,11 @@ impl From<ProtocolVersion> for u32 {
 pub enum ValidationVersion {
 	/// The first version.
 	V1 = 1,
+	/// Carries the same messages as `V1`, but notifications may be zstd compressed on the wire.
+	V2 = 2,
+	/// Carries `v2` messages, which can cover several candidates of a block with a single
+	/// approval assignment or vote. Notifications may be compressed, as on `V2`.
+	V3 = 3,
 }
 
 /// Supported collation protocol versions. Only versions defined here must be used in the codebase.
@@ -374,17 +383,31 @@ impl PeerSetProtocolNames {
 		.into()
 	}
 
-	/// Get the protocol fallback names. Currently only holds the legacy name
-	/// for `LEGACY_PROTOCOL_VERSION` = 1.
-	fn get_fallback_names(protocol: PeerSet) -> Vec<ProtocolName> {
-		std::iter::once(Self::get_legacy_name(protocol)).collect()
+	/// Get the protocol fallback names: the names of all older versions, newest first, followed
+	/// by the legacy name for `LEGACY_PROTOCOL_VERSION` = 1.
+	fn get_fallback_names(&self, protocol: PeerSet) -> Vec<ProtocolName> {
+		let main_version = protocol.get_main_version();
+		let mut older_versions: Vec<_> = self
+			.names
+			.iter()
+			.filter(|((p, version), _)| *p == protocol && *version != main_version)
+			.map(|((_, version), name)| (version.0, name.clone()))
+			.collect();
+		older_versions.sort_by(|a, b| b.0.cmp(&a.0));
+
+		older_versions
+			.into_iter()
+			.map(|(_, name)| name)
+			.chain(std::iter::once(Self::get_legacy_name(protocol)))
+			.collect()
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::{
-		CollationVersion, Hash, PeerSet, PeerSetProtocolNames, ProtocolVersion, ValidationVersion,
+		CollationVersion, Hash, PeerSet, PeerSetProtocolNames, ProtocolName, ProtocolVersion,
+		ValidationVersion,
 	};
 	use strum::IntoEnumIterator;
 
@@ -517,6 +540,29 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn older_validation_versions_are_fallbacks() {
+		let genesis_hash = Hash::repeat_byte(0xff);
+		let protocol_names = PeerSetProtocolNames::new(genesis_hash, None);
+
+		assert_eq!(
+			protocol_names.get_main_name(PeerSet::Validation),
+			protocol_names.get_name(PeerSet::Validation, ValidationVersion::V3.into()),
+		);
+		assert_eq!(
+			protocol_names.get_fallback_names(PeerSet::Validation),
+			vec![
+				protocol_names.get_name(PeerSet::Validation, ValidationVersion::V2.into()),
+				protocol_names.get_name(PeerSet::Validation, ValidationVersion::V1.into()),
+				"/polkadot/validation/1".into(),
+			],
+		);
+		assert_eq!(
+			protocol_names.get_fallback_names(PeerSet::Collation),
+			vec![ProtocolName::from("/polkadot/collation/1")],
+		);
+	}
+
 	#[test]
 	fn all_protocol_versions_have_labels() {
 		for protocol in PeerSet::iter() {
@@ -35,6 +35,10 @@ pub enum Error {
 	/// Decoding failed, but sending reputation change failed.
 	#[error("Decoding request failed for peer {0}, and changing reputation failed.")]
 	DecodingErrorNoReputationChange(PeerId, #[source] DecodingError),
+
+	/// The peer exceeded its request quota, the request got answered with an error.
+	#[error("Peer {0} exceeded its request quota.")]
+	QuotaExceeded(PeerId),
 }
 
 /// General result based on above `Error`.
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 use futures::{channel::oneshot, StreamExt};
 
@@ -22,12 +22,15 @@ use parity_scale_codec::{Decode, Encode};
 
 use sc_network::{config as netconfig, config::RequestResponseConfig, PeerId};
 
-use super::{IsRequest, ReqProtocolNames};
+use super::{IsRequest, Protocol, ReqProtocolNames};
 use crate::UnifiedReputationChange;
 
 mod error;
 pub use error::{Error, FatalError, JfyiError, Result};
 
+mod quota;
+pub use quota::{ProtocolQuota, Quota, QuotaReceiver};
+
 /// A request coming in, including a sender for sending responses.
 ///
 /// Typed `IncomingRequest`s, see `IncomingRequest::get_config_receiver` and substrate
@@ -56,7 +59,7 @@ where
 		req_protocol_names: &ReqProtocolNames,
 	) -> (IncomingRequestReceiver<Req>, RequestResponseConfig) {
 		let (raw, cfg) = Req::PROTOCOL.get_config(req_protocol_names);
-		(IncomingRequestReceiver { raw, phantom: PhantomData {} }, cfg)
+		(IncomingRequestReceiver { raw, observer: None, phantom: PhantomData {} }, cfg)
 	}
 
 	/// Create new `IncomingRequest`.
@@ -68,7 +71,11 @@ where
 		Self {
 			peer,
 			payload,
-			pending_response: OutgoingResponseSender { pending_response, phantom: PhantomData {} },
+			pending_response: OutgoingResponseSender {
+				pending_response,
+				observer: None,
+				phantom: PhantomData {},
+			},
 		}
 	}
 
@@ -133,18 +140,42 @@ where
 	}
 }
 
+/// Accounts for the traffic of incoming requests and of the responses sent to them.
+pub trait RequestTrafficObserver: Send + Sync {
+	/// A request of `bytes` bytes on `protocol` was received from `peer`.
+	fn on_request_received(&self, peer: PeerId, protocol: Protocol, bytes: usize);
+
+	/// A response of `bytes` bytes to a request on `protocol` is sent to `peer`.
+	fn on_response_sent(&self, peer: PeerId, protocol: Protocol, bytes: usize);
+}
+
 /// Sender for sending back responses on an `IncomingRequest`.
-#[derive(Debug)]
 pub struct OutgoingResponseSender<Req> {
 	pending_response: oneshot::Sender<netconfig::OutgoingResponse>,
+	/// The peer the response goes to and the observer to tell about it, if any.
+	observer: Option<(PeerId, Arc<dyn RequestTrafficObserver>)>,
 	phantom: PhantomData<Req>,
 }
 
+impl<Req> std::fmt::Debug for OutgoingResponseSender<Req> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("OutgoingResponseSender")
+			.field("pending_response", &self.pending_response)
+			.finish_non_exhaustive()
+	}
+}
+
 impl<Req> OutgoingResponseSender<Req>
 where
 	Req: IsRequest + Decode,
 	Req::Response: Encode,
 {
+	fn note_response(&self, bytes: usize) {
+		if let Some((peer, observer)) = &self.observer {
+			observer.on_response_sent(*peer, Req::PROTOCOL, bytes);
+		}
+	}
+
 	/// Send the response back.
 	///
 	/// On success we return `Ok(())`, on error we return the not sent `Response`.
@@ -152,9 +183,11 @@ where
 	/// `netconfig::OutgoingResponse` exposes a way of modifying the peer's reputation. If needed we
 	/// can change this function to expose this feature as well.
 	pub fn send_response(self, resp: Req::Response) -> std::result::Result<(), Req::Response> {
+		let encoded = resp.encode();
+		self.note_response(encoded.len());
 		self.pending_response
 			.send(netconfig::OutgoingResponse {
-				result: Ok(resp.encode()),
+				result: Ok(encoded),
 				reputation_changes: Vec::new(),
 				sent_feedback: None,
 			})
@@ -172,8 +205,13 @@ where
 	) -> std::result::Result<(), ()> {
 		let OutgoingResponse { result, reputation_changes, sent_feedback } = resp;
 
+		let result = result.map(|v| v.encode());
+		if let Ok(encoded) = &result {
+			self.note_response(encoded.len());
+		}
+
 		let response = netconfig::OutgoingResponse {
-			result: result.map(|v| v.encode()),
+			result,
 			reputation_changes: reputation_changes.into_iter().map(|c| c.into()).collect(),
 			sent_feedback,
 		};
@@ -205,6 +243,7 @@ pub struct OutgoingResponse<Response> {
 /// Takes care of decoding and handling of invalid encoded requests.
 pub struct IncomingRequestReceiver<Req> {
 	raw: async_channel::Receiver<netconfig::IncomingRequest>,
+	observer: Option<Arc<dyn RequestTrafficObserver>>,
 	phantom: PhantomData<Req>,
 }
 
@@ -213,6 +252,12 @@ where
 	Req: IsRequest + Decode + Encode,
 	Req::Response: Encode,
 {
+	/// Tell `observer` about every received request and every response sent to one.
+	pub fn with_traffic_observer(mut self, observer: Arc<dyn RequestTrafficObserver>) -> Self {
+		self.observer = Some(observer);
+		self
+	}
+
 	/// Try to receive the next incoming request.
 	///
 	/// Any received request will be decoded, on decoding errors the provided reputation changes
@@ -221,10 +266,33 @@ where
 	where
 		F: FnOnce() -> Vec<UnifiedReputationChange>,
 	{
-		let req = match self.raw.next().await {
+		let raw = self.recv_raw().await?;
+		self.decode(raw, reputation_changes())
+	}
+
+	/// Receive the next raw incoming request, accounting for it.
+	async fn recv_raw(&mut self) -> Result<netconfig::IncomingRequest> {
+		let raw = match self.raw.next().await {
 			None => return Err(FatalError::RequestChannelExhausted.into()),
-			Some(raw) => IncomingRequest::<Req>::try_from_raw(raw, reputation_changes())?,
+			Some(raw) => raw,
 		};
+
+		if let Some(observer) = &self.observer {
+			observer.on_request_received(raw.peer, Req::PROTOCOL, raw.payload.len());
+		}
+
+		Ok(raw)
+	}
+
+	/// Decode a raw request received by [`Self::recv_raw`].
+	fn decode(
+		&self,
+		raw: netconfig::IncomingRequest,
+		reputation_changes: Vec<UnifiedReputationChange>,
+	) -> Result<IncomingRequest<Req>> {
+		let mut req = IncomingRequest::<Req>::try_from_raw(raw, reputation_changes)?;
+		req.pending_response.observer =
+			self.observer.as_ref().map(|observer| (req.peer, observer.clone()));
 		Ok(req)
 	}
 }
@@ -0,0 +1,358 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-peer quotas on incoming requests.
+//!
+//! A [`QuotaReceiver`] wraps an [`IncomingRequestReceiver`] and keeps a token bucket per
+//! requesting peer. Requests of peers which ran out of tokens are answered with an error and a
+//! reputation change right away, without being decoded or handed to the subsystem.
+//!
+//! Whether a peer is an authority is looked up for every request. Authority discovery might not
+//! have resolved a validator yet when it starts sending requests, so such a peer starts out with
+//! the non-authority quota and its bucket is raised to the authority quota once it is resolved.
+
+use std::{num::NonZeroUsize, time::Instant};
+
+use lru::LruCache;
+use parity_scale_codec::{Decode, Encode};
+use sc_network::{config as netconfig, PeerId};
+
+use super::{IncomingRequest, IncomingRequestReceiver, JfyiError, Result};
+use crate::{
+	authority_discovery::AuthorityDiscovery,
+	peer_set::IsAuthority,
+	request_response::{IsRequest, Protocol},
+	UnifiedReputationChange as Rep,
+};
+
+const LOG_TARGET: &str = "parachain::request-quota";
+
+/// Cost of sending a request while being out of quota.
+const COST_QUOTA_EXCEEDED: Rep = Rep::CostMinor("Exceeded request quota");
+
+/// The maximum number of buckets kept.
+///
+/// Once reached, the bucket of the peer which sent a request the longest time ago is dropped.
+/// Unless that peer sent requests to us just as recently, its bucket would be full by now anyway.
+const MAX_BUCKETS: NonZeroUsize = match NonZeroUsize::new(1000) {
+	Some(max) => max,
+	None => panic!("Maximum number of buckets must be non-zero"),
+};
+
+/// A token bucket quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+	/// The number of requests which can be sent at once after being idle for long enough.
+	pub burst: u32,
+	/// The number of requests per second the bucket is refilled with.
+	pub per_second: u32,
+}
+
+/// The quotas of a request protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolQuota {
+	/// The quota of each peer known to be an authority.
+	pub authorities: Quota,
+	/// The quota of each peer not known to be an authority.
+	pub non_authorities: Quota,
+}
+
+impl ProtocolQuota {
+	/// The default quotas of `protocol`.
+	///
+	/// Chunks are requested by all validators for every candidate, other requests are either much
+	/// rarer or only sent by few peers, e.g. the backing group of a candidate.
+	pub fn default_for(protocol: Protocol) -> Self {
+		match protocol {
+			Protocol::ChunkFetchingV1 => Self {
+				authorities: Quota { burst: 400, per_second: 100 },
+				non_authorities: Quota { burst: 20, per_second: 5 },
+			},
+			Protocol::CollationFetchingV1 |
+			Protocol::PoVFetchingV1 |
+			Protocol::AvailableDataFetchingV1 |
+			Protocol::StatementFetchingV1 |
+			Protocol::StatementFetchingV2 |
+			Protocol::DisputeSendingV1 => Self {
+				authorities: Quota { burst: 50, per_second: 10 },
+				non_authorities: Quota { burst: 5, per_second: 1 },
+			},
+		}
+	}
+
+	fn get(&self, is_authority: IsAuthority) -> Quota {
+		match is_authority {
+			IsAuthority::Yes => self.authorities,
+			IsAuthority::No => self.non_authorities,
+		}
+	}
+}
+
+struct TokenBucket {
+	tokens: f64,
+	updated: Instant,
+	/// The quota the bucket was last used with, the peer might not be an authority anymore.
+	quota: Quota,
+}
+
+impl TokenBucket {
+	fn new(quota: Quota, now: Instant) -> Self {
+		Self { tokens: quota.burst as f64, updated: now, quota }
+	}
+
+	fn refill(&mut self, now: Instant) {
+		let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+		self.tokens =
+			(self.tokens + elapsed * self.quota.per_second as f64).min(self.quota.burst as f64);
+		self.updated = now;
+	}
+
+	fn try_take(&mut self, quota: Quota, now: Instant) -> bool {
+		self.refill(now);
+		if quota != self.quota {
+			// A peer which got resolved as an authority gets the tokens the larger burst adds, a
+			// peer which is no authority anymore keeps at most the smaller burst.
+			let raised_by = quota.burst.saturating_sub(self.quota.burst) as f64;
+			self.tokens = (self.tokens + raised_by).min(quota.burst as f64);
+			self.quota = quota;
+		}
+
+		if self.tokens >= 1.0 {
+			self.tokens -= 1.0;
+			true
+		} else {
+			false
+		}
+	}
+}
+
+/// The token buckets of all peers.
+struct Buckets {
+	quota: ProtocolQuota,
+	buckets: LruCache<PeerId, TokenBucket>,
+}
+
+impl Buckets {
+	fn new(quota: ProtocolQuota) -> Self {
+		Self { quota, buckets: LruCache::new(MAX_BUCKETS) }
+	}
+
+	/// Take a token from the bucket of `peer`, returns whether there was one.
+	fn try_acquire(&mut self, peer: PeerId, is_authority: IsAuthority, now: Instant) -> bool {
+		let quota = self.quota.get(is_authority);
+		if let Some(bucket) = self.buckets.get_mut(&peer) {
+			return bucket.try_take(quota, now)
+		}
+
+		let mut bucket = TokenBucket::new(quota, now);
+		let acquired = bucket.try_take(quota, now);
+		self.buckets.put(peer, bucket);
+		acquired
+	}
+}
+
+/// An [`IncomingRequestReceiver`] which enforces a [`ProtocolQuota`] on each peer.
+pub struct QuotaReceiver<Req> {
+	receiver: IncomingRequestReceiver<Req>,
+	authority_discovery: Box<dyn AuthorityDiscovery>,
+	buckets: Buckets,
+	/// A request received while looking up whether its peer is an authority.
+	///
+	/// Kept here, so the request is not lost if `recv` gets cancelled during the lookup.
+	pending: Option<netconfig::IncomingRequest>,
+}
+
+impl<Req> QuotaReceiver<Req>
+where
+	Req: IsRequest + Decode + Encode,
+	Req::Response: Encode,
+{
+	/// Wrap `receiver`, enforcing `quota`.
+	///
+	/// `authority_discovery` is used for telling which peers are authorities.
+	pub fn new(
+		receiver: IncomingRequestReceiver<Req>,
+		quota: ProtocolQuota,
+		authority_discovery: Box<dyn AuthorityDiscovery>,
+	) -> Self {
+		Self { receiver, authority_discovery, buckets: Buckets::new(quota), pending: None }
+	}
+
+	/// Wrap `receiver`, enforcing the default quota of its protocol.
+	pub fn with_default_quota(
+		receiver: IncomingRequestReceiver<Req>,
+		authority_discovery: Box<dyn AuthorityDiscovery>,
+	) -> Self {
+		Self::new(receiver, ProtocolQuota::default_for(Req::PROTOCOL), authority_discovery)
+	}
+
+	/// Try to receive the next incoming request.
+	///
+	/// Like [`IncomingRequestReceiver::recv`], but requests of peers which exceeded their quota
+	/// get answered with an error and `JfyiError::QuotaExceeded` is returned.
+	///
+	/// This is cancellation safe, a request being looked up is kept for the next call.
+	pub async fn recv<F>(&mut self, reputation_changes: F) -> Result<IncomingRequest<Req>>
+	where
+		F: FnOnce() -> Vec<Rep>,
+	{
+		let peer = match &self.pending {
+			Some(raw) => raw.peer,
+			None => {
+				let raw = self.receiver.recv_raw().await?;
+				let peer = raw.peer;
+				self.pending = Some(raw);
+				peer
+			},
+		};
+
+		let is_authority = match self.authority_discovery.get_authority_ids_by_peer_id(peer).await {
+			Some(_) => IsAuthority::Yes,
+			None => IsAuthority::No,
+		};
+		let raw = self.pending.take().expect("Set above and only taken here; qed");
+
+		if !self.buckets.try_acquire(raw.peer, is_authority, Instant::now()) {
+			gum::debug!(
+				target: LOG_TARGET,
+				peer = ?raw.peer,
+				protocol = ?Req::PROTOCOL,
+				?is_authority,
+				"Peer exceeded its request quota",
+			);
+
+			let response = netconfig::OutgoingResponse {
+				result: Err(()),
+				reputation_changes: vec![COST_QUOTA_EXCEEDED.into()],
+				sent_feedback: None,
+			};
+			let _ = raw.pending_response.send(response);
+			return Err(JfyiError::QuotaExceeded(raw.peer).into())
+		}
+
+		self.receiver.decode(raw, reputation_changes())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	const QUOTA: ProtocolQuota = ProtocolQuota {
+		authorities: Quota { burst: 4, per_second: 2 },
+		non_authorities: Quota { burst: 1, per_second: 1 },
+	};
+
+	#[test]
+	fn bucket_is_refilled_over_time() {
+		let mut buckets = Buckets::new(QUOTA);
+		let peer = PeerId::random();
+		let now = Instant::now();
+
+		for _ in 0..4 {
+			assert!(buckets.try_acquire(peer, IsAuthority::Yes, now));
+		}
+		assert!(!buckets.try_acquire(peer, IsAuthority::Yes, now));
+
+		let later = now + Duration::from_millis(500);
+		assert!(buckets.try_acquire(peer, IsAuthority::Yes, later));
+		assert!(!buckets.try_acquire(peer, IsAuthority::Yes, later));
+
+		// Never refilled beyond the burst.
+		let much_later = later + Duration::from_secs(60);
+		for _ in 0..4 {
+			assert!(buckets.try_acquire(peer, IsAuthority::Yes, much_later));
+		}
+		assert!(!buckets.try_acquire(peer, IsAuthority::Yes, much_later));
+	}
+
+	#[test]
+	fn non_authorities_get_their_own_quota() {
+		let mut buckets = Buckets::new(QUOTA);
+		let (authority, other) = (PeerId::random(), PeerId::random());
+		let now = Instant::now();
+
+		assert!(buckets.try_acquire(other, IsAuthority::No, now));
+		assert!(!buckets.try_acquire(other, IsAuthority::No, now));
+		for _ in 0..4 {
+			assert!(buckets.try_acquire(authority, IsAuthority::Yes, now));
+		}
+		assert!(!buckets.try_acquire(authority, IsAuthority::Yes, now));
+	}
+
+	#[test]
+	fn resolved_peers_are_raised_to_the_authority_quota() {
+		let mut buckets = Buckets::new(QUOTA);
+		let peer = PeerId::random();
+		let now = Instant::now();
+
+		// Not resolved yet.
+		assert!(buckets.try_acquire(peer, IsAuthority::No, now));
+		assert!(!buckets.try_acquire(peer, IsAuthority::No, now));
+
+		// The larger burst is added once resolved.
+		for _ in 0..3 {
+			assert!(buckets.try_acquire(peer, IsAuthority::Yes, now));
+		}
+		assert!(!buckets.try_acquire(peer, IsAuthority::Yes, now));
+	}
+
+	#[test]
+	fn new_peers_cannot_exceed_the_non_authority_quota() {
+		let mut buckets = Buckets::new(QUOTA);
+		let now = Instant::now();
+
+		for _ in 0..10 {
+			let peer = PeerId::random();
+			assert!(buckets.try_acquire(peer, IsAuthority::No, now));
+			assert!(!buckets.try_acquire(peer, IsAuthority::No, now));
+		}
+	}
+
+	#[test]
+	fn losing_authority_status_caps_the_bucket() {
+		let mut buckets = Buckets::new(QUOTA);
+		let peer = PeerId::random();
+		let now = Instant::now();
+		assert!(buckets.try_acquire(peer, IsAuthority::Yes, now));
+
+		assert!(buckets.try_acquire(peer, IsAuthority::No, now));
+		assert!(!buckets.try_acquire(peer, IsAuthority::No, now));
+	}
+
+	#[test]
+	fn least_recently_used_bucket_is_dropped() {
+		let mut buckets = Buckets::new(QUOTA);
+		let now = Instant::now();
+		let (idle, busy) = (PeerId::random(), PeerId::random());
+
+		assert!(buckets.try_acquire(idle, IsAuthority::No, now));
+		assert!(buckets.try_acquire(busy, IsAuthority::No, now));
+		for _ in 0..MAX_BUCKETS.get() - 2 {
+			assert!(buckets.try_acquire(PeerId::random(), IsAuthority::No, now));
+		}
+		assert!(!buckets.try_acquire(busy, IsAuthority::No, now));
+
+		// None of the buckets got refilled, but the map does not grow beyond its limit.
+		assert!(buckets.try_acquire(PeerId::random(), IsAuthority::No, now));
+		assert_eq!(buckets.buckets.len(), MAX_BUCKETS.get());
+		assert!(!buckets.buckets.contains(&idle));
+
+		// The bucket of the busy peer is kept.
+		assert!(!buckets.try_acquire(busy, IsAuthority::No, now));
+	}
+}
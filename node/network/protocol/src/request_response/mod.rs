@@ -30,7 +30,7 @@
 //! `trait IsRequest` .... A trait describing a particular request. It is used for gathering meta
 //! data, like what is the corresponding response type.
 //!
-//!  Versioned (v1 module): The actual requests and responses as sent over the network.
+//!  Versioned (v1, v2 modules): The actual requests and responses as sent over the network.
 
 use std::{collections::HashMap, time::Duration, u64};
 
@@ -44,7 +44,9 @@ pub mod incoming;
 /// Everything related to handling of outgoing requests.
 pub mod outgoing;
 
-pub use incoming::{IncomingRequest, IncomingRequestReceiver};
+pub use incoming::{
+	IncomingRequest, IncomingRequestReceiver, QuotaReceiver, RequestTrafficObserver,
+};
 
 pub use outgoing::{OutgoingRequest, OutgoingResult, Recipient, Requests, ResponseSender};
 
@@ -54,6 +56,9 @@ pub use outgoing::{OutgoingRequest, OutgoingResult, Recipient, Requests, Respons
 /// Actual versioned requests and responses, that are sent over the wire.
 pub mod v1;
 
+/// Requests and responses of protocols which got a second version.
+pub mod v2;
+
 /// A protocol per subsystem seems to make the most sense, this way we don't need any dispatching
 /// within protocols.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, EnumIter)]
@@ -68,6 +73,8 @@ pub enum Protocol {
 	AvailableDataFetchingV1,
 	/// Fetching of statements that are too large for gossip.
 	StatementFetchingV1,
+	/// Fetching of statements that are too large for gossip, in chunks.
+	StatementFetchingV2,
 	/// Sending of dispute statements with application level confirmations.
 	DisputeSendingV1,
 }
@@ -107,6 +114,19 @@ const STATEMENTS_TIMEOUT: Duration = Duration::from_secs(1);
 /// to have 3 slow nodes connected, to delay transfer for others by `STATEMENTS_TIMEOUT`.
 pub const MAX_PARALLEL_STATEMENT_REQUESTS: u32 = 3;
 
+/// Chunks are a fraction of the size of a full statement, so serving one to a slow requester
+/// holds up much less of our bandwidth.
+pub const MAX_PARALLEL_STATEMENT_CHUNK_REQUESTS: u32 = 10;
+
+/// The size of the chunks large statements are split into by `StatementFetchingV2`.
+///
+/// All chunks but the last one have exactly this size.
+pub const STATEMENT_CHUNK_SIZE: u32 = 256 * 1024;
+
+/// The maximum number of chunks a statement can be split into.
+pub const MAX_STATEMENT_CHUNKS: u32 =
+	(STATEMENT_RESPONSE_SIZE / STATEMENT_CHUNK_SIZE as u64) as u32 + 1;
+
 /// Response size limit for responses of POV like data.
 ///
 /// This is larger than `MAX_POV_SIZE` to account for protocol overhead and for additional data in
@@ -120,6 +140,9 @@ const POV_RESPONSE_SIZE: u64 = MAX_POV_SIZE as u64 + 10_000;
 /// This is `MAX_CODE_SIZE` plus some additional space for protocol overhead.
 const STATEMENT_RESPONSE_SIZE: u64 = MAX_CODE_SIZE as u64 + 10_000;
 
+/// Maximum response sizes for `StatementFetchingV2`.
+const STATEMENT_CHUNK_RESPONSE_SIZE: u64 = STATEMENT_CHUNK_SIZE as u64 + 1_000;
+
 /// We can have relative large timeouts here, there is no value of hitting a
 /// timeout as we want to get statements through to each node in any case.
 pub const DISPUTE_REQUEST_TIMEOUT: Duration = Duration::from_secs(12);
@@ -210,6 +233,16 @@ impl Protocol {
 				request_timeout: Duration::from_secs(1),
 				inbound_queue: tx,
 			},
+			Protocol::StatementFetchingV2 => RequestResponseConfig {
+				name,
+				fallback_names,
+				max_request_size: 1_000,
+				max_response_size: STATEMENT_CHUNK_RESPONSE_SIZE,
+				// Chunks are small, a peer not answering within that time is overloaded and the
+				// chunk better be requested from another one.
+				request_timeout: STATEMENTS_TIMEOUT,
+				inbound_queue: tx,
+			},
 			Protocol::DisputeSendingV1 => RequestResponseConfig {
 				name,
 				fallback_names,
@@ -260,6 +293,21 @@ impl Protocol {
 				);
 				size as usize
 			},
+			// Same approximation as for `StatementFetchingV1`, but with chunks instead of whole
+			// runtimes.
+			Protocol::StatementFetchingV2 => {
+				let available_bandwidth = 7 * MIN_BANDWIDTH_BYTES / 10;
+				let size = u64::saturating_sub(
+					STATEMENTS_TIMEOUT.as_millis() as u64 * available_bandwidth /
+						(1000 * STATEMENT_CHUNK_SIZE as u64),
+					MAX_PARALLEL_STATEMENT_CHUNK_REQUESTS as u64,
+				);
+				debug_assert!(
+					size > 0,
+					"We should have a channel size greater zero, otherwise we won't accept any requests."
+				);
+				size as usize
+			},
 			// Incoming requests can get bursty, we should also be able to handle them fast on
 			// average, so something in the ballpark of 100 should be fine. Nodes will retry on
 			// failure, so having a good value here is mostly about performance tuning.
@@ -269,18 +317,21 @@ impl Protocol {
 
 	/// Fallback protocol names of this protocol, as understood by substrate networking.
 	fn get_fallback_names(self) -> Vec<ProtocolName> {
-		std::iter::once(self.get_legacy_name().into()).collect()
+		self.get_legacy_name().into_iter().map(Into::into).collect()
 	}
 
 	/// Legacy protocol name associated with each peer set.
-	const fn get_legacy_name(self) -> &'static str {
+	///
+	/// Protocols introduced after the switch to genesis hash based names have none.
+	const fn get_legacy_name(self) -> Option<&'static str> {
 		match self {
-			Protocol::ChunkFetchingV1 => "/polkadot/req_chunk/1",
-			Protocol::CollationFetchingV1 => "/polkadot/req_collation/1",
-			Protocol::PoVFetchingV1 => "/polkadot/req_pov/1",
-			Protocol::AvailableDataFetchingV1 => "/polkadot/req_available_data/1",
-			Protocol::StatementFetchingV1 => "/polkadot/req_statement/1",
-			Protocol::DisputeSendingV1 => "/polkadot/send_dispute/1",
+			Protocol::ChunkFetchingV1 => Some("/polkadot/req_chunk/1"),
+			Protocol::CollationFetchingV1 => Some("/polkadot/req_collation/1"),
+			Protocol::PoVFetchingV1 => Some("/polkadot/req_pov/1"),
+			Protocol::AvailableDataFetchingV1 => Some("/polkadot/req_available_data/1"),
+			Protocol::StatementFetchingV1 => Some("/polkadot/req_statement/1"),
+			Protocol::StatementFetchingV2 => None,
+			Protocol::DisputeSendingV1 => Some("/polkadot/send_dispute/1"),
 		}
 	}
 }
@@ -335,6 +386,7 @@ impl ReqProtocolNames {
 			Protocol::PoVFetchingV1 => "/req_pov/1",
 			Protocol::AvailableDataFetchingV1 => "/req_available_data/1",
 			Protocol::StatementFetchingV1 => "/req_statement/1",
+			Protocol::StatementFetchingV2 => "/req_statement/2",
 			Protocol::DisputeSendingV1 => "/send_dispute/1",
 		};
 
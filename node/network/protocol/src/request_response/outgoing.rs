@@ -23,7 +23,7 @@ use sc_network::PeerId;
 
 use polkadot_primitives::AuthorityDiscoveryId;
 
-use super::{v1, IsRequest, Protocol};
+use super::{v1, v2, IsRequest, Protocol};
 
 /// All requests that can be sent to the network bridge via `NetworkBridgeTxMessage::SendRequest`.
 #[derive(Debug)]
@@ -38,6 +38,8 @@ pub enum Requests {
 	AvailableDataFetchingV1(OutgoingRequest<v1::AvailableDataFetchingRequest>),
 	/// Requests for fetching large statements as part of statement distribution.
 	StatementFetchingV1(OutgoingRequest<v1::StatementFetchingRequest>),
+	/// Requests for fetching chunks of large statements as part of statement distribution.
+	StatementFetchingV2(OutgoingRequest<v2::StatementFetchingRequest>),
 	/// Requests for notifying about an ongoing dispute.
 	DisputeSendingV1(OutgoingRequest<v1::DisputeRequest>),
 }
@@ -51,10 +53,26 @@ impl Requests {
 			Self::PoVFetchingV1(_) => Protocol::PoVFetchingV1,
 			Self::AvailableDataFetchingV1(_) => Protocol::AvailableDataFetchingV1,
 			Self::StatementFetchingV1(_) => Protocol::StatementFetchingV1,
+			Self::StatementFetchingV2(_) => Protocol::StatementFetchingV2,
 			Self::DisputeSendingV1(_) => Protocol::DisputeSendingV1,
 		}
 	}
 
+	/// Replace the sender the network sends the response with, returning the previous one.
+	///
+	/// This allows for intercepting the raw response before it reaches the requester.
+	pub fn replace_response_sender(&mut self, sender: ResponseSender) -> ResponseSender {
+		match self {
+			Self::ChunkFetchingV1(r) => std::mem::replace(&mut r.pending_response, sender),
+			Self::CollationFetchingV1(r) => std::mem::replace(&mut r.pending_response, sender),
+			Self::PoVFetchingV1(r) => std::mem::replace(&mut r.pending_response, sender),
+			Self::AvailableDataFetchingV1(r) => std::mem::replace(&mut r.pending_response, sender),
+			Self::StatementFetchingV1(r) => std::mem::replace(&mut r.pending_response, sender),
+			Self::StatementFetchingV2(r) => std::mem::replace(&mut r.pending_response, sender),
+			Self::DisputeSendingV1(r) => std::mem::replace(&mut r.pending_response, sender),
+		}
+	}
+
 	/// Encode the request.
 	///
 	/// The corresponding protocol is returned as well, as we are now leaving typed territory.
@@ -69,6 +87,7 @@ impl Requests {
 			Self::PoVFetchingV1(r) => r.encode_request(),
 			Self::AvailableDataFetchingV1(r) => r.encode_request(),
 			Self::StatementFetchingV1(r) => r.encode_request(),
+			Self::StatementFetchingV2(r) => r.encode_request(),
 			Self::DisputeSendingV1(r) => r.encode_request(),
 		}
 	}
@@ -105,6 +124,16 @@ impl RequestError {
 			_ => false,
 		}
 	}
+
+	/// Whether the request failed because the peer does not support its protocol.
+	pub fn is_unsupported_protocol(&self) -> bool {
+		matches!(
+			self,
+			Self::NetworkError(network::RequestFailure::Network(
+				network::OutboundFailure::UnsupportedProtocols,
+			))
+		)
+	}
 }
 
 /// A request to be sent to the network bridge, including a sender for sending responses/failures.
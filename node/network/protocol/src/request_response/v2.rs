@@ -0,0 +1,59 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Requests and responses as sent over the wire for the second version of individual protocols.
+
+use parity_scale_codec::{Decode, Encode};
+
+use polkadot_primitives::{CandidateHash, Hash};
+
+use super::{IsRequest, Protocol};
+
+/// Request for a chunk of a large statement via request/response.
+///
+/// The SCALE encoded `CommittedCandidateReceipt` of the statement is split into chunks of
+/// [`STATEMENT_CHUNK_SIZE`](super::STATEMENT_CHUNK_SIZE) bytes, so it can be fetched from several
+/// peers at once.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+pub struct StatementFetchingRequest {
+	/// Data needed to locate and identify the needed statement.
+	pub relay_parent: Hash,
+	/// Hash of candidate that was used create the `CommitedCandidateRecept`.
+	pub candidate_hash: CandidateHash,
+	/// The index of the chunk to fetch.
+	pub chunk_index: u32,
+}
+
+/// Respond with a chunk of the full statement.
+///
+/// Like for `v1::StatementFetchingRequest`, not having the data is not an option and results in
+/// a `RequestFailure`.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+pub enum StatementFetchingResponse {
+	/// A chunk of the data missing to reconstruct the full signed statement.
+	#[codec(index = 0)]
+	Chunk {
+		/// The number of chunks the data is split into.
+		total_chunks: u32,
+		/// The requested chunk.
+		data: Vec<u8>,
+	},
+}
+
+impl IsRequest for StatementFetchingRequest {
+	type Response = StatementFetchingResponse;
+	const PROTOCOL: Protocol = Protocol::StatementFetchingV2;
+}
@@ -0,0 +1,709 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An in-process simulator of the gossip of the network subsystems.
+//!
+//! The simulator runs approval-distribution, bitfield-distribution and statement-distribution for
+//! each of a set of virtual validators and connects them through a virtual network in place of
+//! the network bridge. Every validator gets the grid topology gossip-support would compute, the
+//! subsystems' requests to the runtime API are answered from a made up session and approval-voting
+//! accepts all assignments. The messages the subsystems send to their peers are delivered through
+//! the virtual network, so the results reflect the routing the subsystems actually do and changes
+//! to it can be evaluated without a real network.
+//!
+//! Every validator originates one message per protocol and relay chain block at the start of the
+//! simulation.
+//!
+//! The shuffling of the topology, the virtual network and the choice of the dishonest validators
+//! are derived from a seed. The subsystems choose the peers they route to randomly from their own
+//! randomness and iterate hash maps though, so the random routing and the results depending on it
+//! differ between runs with the same seed.
+
+use std::{
+	cmp::{Ordering, Reverse},
+	collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
+	sync::Arc,
+	time::Duration,
+};
+
+use bitvec::{bitvec, order::Lsb0};
+use polkadot_node_network_protocol::{
+	grid_topology::{SessionGridTopology, TopologyPeerInfo},
+	peer_set::ValidationVersion,
+	ObservedRole, OurView, PeerId, VersionedValidationProtocol, View,
+};
+use polkadot_node_primitives::{
+	approval::{
+		AssignmentCert, AssignmentCertKind, BlockApprovalMeta, IndirectAssignmentCert, VrfOutput,
+		VrfProof, VrfSignature, RELAY_VRF_MODULO_CONTEXT,
+	},
+	SignedFullStatement, Statement,
+};
+use polkadot_node_subsystem::{
+	jaeger,
+	messages::{
+		network_bridge_event::NewGossipTopology, AllMessages, ApprovalCheckResult,
+		ApprovalDistributionMessage, ApprovalVotingMessage, AssignmentCheckResult,
+		BitfieldDistributionMessage, NetworkBridgeEvent, NetworkBridgeTxMessage, RuntimeApiMessage,
+		RuntimeApiRequest, StatementDistributionMessage,
+	},
+	ActivatedLeaf, ActiveLeavesUpdate, FromOrchestra, LeafStatus, OverseerSignal,
+};
+use polkadot_node_subsystem_test_helpers::TestSubsystemContextHandle;
+use polkadot_primitives::{
+	AuthorityDiscoveryId, AvailabilityBitfield, CandidateHash, Hash, SessionIndex, SessionInfo,
+	SignedAvailabilityBitfield, SigningContext, ValidatorId, ValidatorIndex,
+};
+use polkadot_primitives_test_helpers::dummy_committed_candidate_receipt;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sp_application_crypto::AppCrypto;
+use sp_keystore::{testing::MemoryKeystore, Keystore, KeystorePtr};
+
+mod network;
+mod protocol;
+mod report;
+mod subsystems;
+
+#[cfg(test)]
+mod tests;
+
+pub use network::{LatencyModel, NetworkConfig};
+pub use protocol::Protocol;
+pub use report::{Percentiles, ProtocolReport, Report};
+
+use network::Network;
+use report::ProtocolStats;
+use subsystems::{Executor, Subsystems};
+
+/// The session of all relay chain blocks of a simulation.
+const SESSION: SessionIndex = 1;
+
+/// The number of validators in a backing group.
+const BACKING_GROUP_SIZE: usize = 5;
+
+/// How dishonest validators behave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DishonestBehaviour {
+	/// Neither originate nor forward any messages.
+	Silent,
+	/// Originate messages, but never forward the messages of others.
+	NoForward,
+	/// Forward messages only after the given delay.
+	Delay(Duration),
+}
+
+/// Configuration of a simulation.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+	/// The number of validators.
+	pub n_validators: usize,
+	/// The protocols to gossip messages of. They share the bandwidth of the validators.
+	pub protocols: Vec<Protocol>,
+	/// How many messages every validator originates per protocol. They are originated for as
+	/// many relay chain blocks.
+	pub messages_per_validator: usize,
+	/// The virtual network connecting the validators.
+	pub network: NetworkConfig,
+	/// The number of dishonest validators. They are chosen randomly.
+	pub n_dishonest: usize,
+	/// How the dishonest validators behave.
+	pub dishonest_behaviour: DishonestBehaviour,
+	/// The seed of all randomness of the simulation.
+	pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+	fn default() -> Self {
+		Self {
+			n_validators: 300,
+			protocols: vec![
+				Protocol::ApprovalDistribution,
+				Protocol::BitfieldDistribution,
+				Protocol::StatementDistribution,
+			],
+			messages_per_validator: 1,
+			network: NetworkConfig::default(),
+			n_dishonest: 0,
+			dishonest_behaviour: DishonestBehaviour::Silent,
+			seed: 0,
+		}
+	}
+}
+
+/// A gossiped item, identified by its protocol, originator and the relay chain block it is
+/// originated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct MessageId {
+	protocol: Protocol,
+	originator: usize,
+	block: Hash,
+}
+
+/// A virtual validator.
+struct Validator {
+	dishonest: Option<DishonestBehaviour>,
+	keystore: KeystorePtr,
+	/// The items the validator originated or received.
+	known: HashSet<MessageId>,
+	subsystems: Subsystems,
+}
+
+/// A message arriving at a validator.
+struct Delivery {
+	at: Duration,
+	/// Breaks ties between deliveries at the same time in the order they were sent.
+	seq: u64,
+	from: usize,
+	to: usize,
+	message: VersionedValidationProtocol,
+}
+
+impl PartialEq for Delivery {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Delivery {}
+
+impl PartialOrd for Delivery {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Delivery {
+	fn cmp(&self, other: &Self) -> Ordering {
+		(self.at, self.seq).cmp(&(other.at, other.seq))
+	}
+}
+
+/// Run a simulation and report how the messages spread.
+pub fn simulate(config: &SimulationConfig) -> Report {
+	Simulation::new(config).run()
+}
+
+struct Simulation<'a> {
+	config: &'a SimulationConfig,
+	rng: ChaCha20Rng,
+	executor: Executor,
+	peer_ids: Vec<PeerId>,
+	peer_indices: HashMap<PeerId, usize>,
+	topology: SessionGridTopology,
+	session_info: SessionInfo,
+	/// The relay chain blocks the messages are originated for, in order.
+	blocks: Vec<Hash>,
+	/// The VRF of all assignments. Approval-voting is not run, so it is never checked.
+	vrf: VrfSignature,
+	validators: Vec<Validator>,
+	network: Network,
+	deliveries: BinaryHeap<Reverse<Delivery>>,
+	next_seq: u64,
+	/// The time the subsystems are processing messages at.
+	now: Duration,
+	stats: BTreeMap<Protocol, ProtocolStats>,
+	last_delivery: Duration,
+}
+
+impl<'a> Simulation<'a> {
+	fn new(config: &'a SimulationConfig) -> Self {
+		let mut rng = ChaCha20Rng::seed_from_u64(config.seed);
+		let n = config.n_validators;
+
+		// Peer IDs are only compared, never ordered, so random ones don't affect the run.
+		let peer_ids: Vec<PeerId> = (0..n).map(|_| PeerId::random()).collect();
+		let peer_indices = peer_ids.iter().enumerate().map(|(v, peer)| (*peer, v)).collect();
+
+		// Raw seeds keep creating the keys cheap.
+		let keystores: Vec<KeystorePtr> =
+			(0..n).map(|_| Arc::new(MemoryKeystore::new()) as KeystorePtr).collect();
+		let public_keys: Vec<_> = keystores
+			.iter()
+			.enumerate()
+			.map(|(v, keystore)| {
+				Keystore::sr25519_generate_new(
+					&**keystore,
+					ValidatorId::ID,
+					Some(&format!("0x{:064x}", v + 1)),
+				)
+				.expect("the in-memory keystore can generate keys; qed")
+			})
+			.collect();
+
+		let validator_indices: Vec<ValidatorIndex> =
+			(0..n).map(|v| ValidatorIndex(v as u32)).collect();
+		let validator_groups: Vec<Vec<ValidatorIndex>> = validator_indices
+			.chunks(BACKING_GROUP_SIZE)
+			.map(|group| group.to_vec())
+			.collect();
+		let session_info = SessionInfo {
+			validators: public_keys.iter().map(|public| ValidatorId::from(*public)).collect(),
+			discovery_keys: public_keys
+				.iter()
+				.map(|public| AuthorityDiscoveryId::from(*public))
+				.collect(),
+			n_cores: validator_groups.len() as u32,
+			validator_groups: validator_groups.into_iter().collect(),
+			// Not used by the gossip subsystems:
+			assignment_keys: Vec::new(),
+			zeroth_delay_tranche_width: 0,
+			relay_vrf_modulo_samples: 0,
+			n_delay_tranches: 0,
+			no_show_slots: 0,
+			needed_approvals: 0,
+			active_validator_indices: Vec::new(),
+			dispute_period: 6,
+			random_seed: [0u8; 32],
+		};
+
+		// Shuffle the validators like gossip-support does with the session randomness.
+		let mut canonical_shuffling: Vec<TopologyPeerInfo> = (0..n)
+			.map(|v| TopologyPeerInfo {
+				peer_ids: vec![peer_ids[v]],
+				validator_index: validator_indices[v],
+				discovery_id: session_info.discovery_keys[v].clone(),
+			})
+			.collect();
+		canonical_shuffling.shuffle(&mut rng);
+		let mut shuffled_indices = vec![0; n];
+		for (i, info) in canonical_shuffling.iter().enumerate() {
+			shuffled_indices[info.validator_index.0 as usize] = i;
+		}
+		let topology = SessionGridTopology::new(shuffled_indices, canonical_shuffling);
+
+		let mut dishonest: Vec<usize> = (0..n).collect();
+		dishonest.shuffle(&mut rng);
+		dishonest.truncate(config.n_dishonest);
+		let dishonest: HashSet<usize> = dishonest.into_iter().collect();
+
+		let vrf = {
+			let keypair = schnorrkel::MiniSecretKey::from_bytes(&[1; 32])
+				.expect("the key has the right length; qed")
+				.expand_to_keypair(schnorrkel::ExpansionMode::Ed25519);
+			let context = schnorrkel::signing_context(RELAY_VRF_MODULO_CONTEXT);
+			let (inout, proof, _) = keypair.vrf_sign(context.bytes(b"simulation"));
+			VrfSignature { output: VrfOutput(inout.to_output()), proof: VrfProof(proof) }
+		};
+
+		let executor = Executor::default();
+		let validators = keystores
+			.into_iter()
+			.enumerate()
+			.map(|(v, keystore)| Validator {
+				dishonest: dishonest.contains(&v).then_some(config.dishonest_behaviour),
+				subsystems: Subsystems::start(
+					&executor,
+					&config.protocols,
+					keystore.clone(),
+					rng.gen(),
+				),
+				keystore,
+				known: HashSet::new(),
+			})
+			.collect();
+
+		Simulation {
+			config,
+			rng,
+			executor,
+			peer_ids,
+			peer_indices,
+			topology,
+			session_info,
+			blocks: (1..=config.messages_per_validator as u64).map(Hash::from_low_u64_be).collect(),
+			vrf,
+			validators,
+			network: Network::new(config.network.clone(), n),
+			deliveries: BinaryHeap::new(),
+			next_seq: 0,
+			now: Duration::ZERO,
+			stats: config.protocols.iter().map(|p| (*p, ProtocolStats::default())).collect(),
+			last_delivery: Duration::ZERO,
+		}
+	}
+
+	fn run(mut self) -> Report {
+		for v in 0..self.validators.len() {
+			self.connect(v);
+		}
+
+		// All messages are originated at the start of the simulation.
+		let n_honest = self.validators.iter().filter(|v| v.dishonest.is_none()).count();
+		for originator in 0..self.validators.len() {
+			let dishonest = self.validators[originator].dishonest;
+			if dishonest == Some(DishonestBehaviour::Silent) {
+				continue
+			}
+			// The honest validators the messages should reach.
+			let expected_receivers = match dishonest {
+				None => n_honest - 1,
+				Some(_) => n_honest,
+			};
+			for protocol in self.config.protocols.clone() {
+				for block in self.blocks.clone() {
+					let message = MessageId { protocol, originator, block };
+					self.stats.entry(protocol).or_default().originated(message, expected_receivers);
+					self.validators[originator].known.insert(message);
+					self.originate(message);
+				}
+			}
+			self.settle(originator);
+		}
+
+		while let Some(Reverse(delivery)) = self.deliveries.pop() {
+			self.deliver(delivery);
+		}
+
+		Report {
+			protocols: self
+				.stats
+				.into_iter()
+				.map(|(protocol, stats)| (protocol, stats.into_report()))
+				.collect(),
+			duration: self.last_delivery,
+		}
+	}
+
+	/// Tell the subsystems of a validator about the relay chain blocks, the topology and all
+	/// other validators as peers, like the overseer and the network bridge would.
+	fn connect(&mut self, v: usize) {
+		let mut parent_hash = Hash::zero();
+		let mut block_metas = Vec::with_capacity(self.blocks.len());
+		for (i, hash) in self.blocks.clone().into_iter().enumerate() {
+			let number = i as u32 + 1;
+			self.signal(
+				v,
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(ActivatedLeaf {
+					hash,
+					number,
+					status: LeafStatus::Fresh,
+					span: Arc::new(jaeger::Span::Disabled),
+				})),
+			);
+			block_metas.push(BlockApprovalMeta {
+				hash,
+				number,
+				parent_hash,
+				candidates: vec![CandidateHash(hash)],
+				slot: (number as u64).into(),
+				session: SESSION,
+			});
+			parent_hash = hash;
+		}
+		self.send(
+			v,
+			|s| &mut s.approval,
+			FromOrchestra::Communication {
+				msg: ApprovalDistributionMessage::NewBlocks(block_metas),
+			},
+		);
+
+		let our_view = OurView::new(
+			self.blocks.iter().map(|hash| (*hash, Arc::new(jaeger::Span::Disabled))),
+			0,
+		);
+		self.network_event(v, NetworkBridgeEvent::OurViewChange(our_view));
+		self.network_event(
+			v,
+			NetworkBridgeEvent::NewGossipTopology(NewGossipTopology {
+				session: SESSION,
+				topology: self.topology.clone(),
+				local_index: Some(ValidatorIndex(v as u32)),
+			}),
+		);
+
+		for peer in (0..self.validators.len()).filter(|peer| *peer != v) {
+			let peer_id = self.peer_ids[peer];
+			let authority_ids = HashSet::from([self.session_info.discovery_keys[peer].clone()]);
+			self.network_event(
+				v,
+				NetworkBridgeEvent::PeerConnected(
+					peer_id,
+					ObservedRole::Authority,
+					ValidationVersion::V1.into(),
+					Some(authority_ids),
+				),
+			);
+			self.network_event(
+				v,
+				NetworkBridgeEvent::PeerViewChange(peer_id, View::new(self.blocks.clone(), 0)),
+			);
+		}
+
+		self.settle(v);
+	}
+
+	/// Hand the item to the subsystem of its protocol to distribute, like the subsystems producing
+	/// them would.
+	fn originate(&mut self, message: MessageId) {
+		let MessageId { protocol, originator, block } = message;
+		let validator_index = ValidatorIndex(originator as u32);
+		let keystore = self.validators[originator].keystore.clone();
+		let public = self
+			.session_info
+			.validators
+			.get(validator_index)
+			.expect("every validator is in the session; qed")
+			.clone();
+		let signing_context = SigningContext { session_index: SESSION, parent_hash: block };
+
+		match protocol {
+			Protocol::ApprovalDistribution => {
+				let cert = IndirectAssignmentCert {
+					block_hash: block,
+					validator: validator_index,
+					cert: AssignmentCert {
+						kind: AssignmentCertKind::RelayVRFModulo { sample: 0 },
+						vrf: self.vrf.clone(),
+					},
+				};
+				self.send(
+					originator,
+					|s| &mut s.approval,
+					FromOrchestra::Communication {
+						msg: ApprovalDistributionMessage::DistributeAssignment(cert, 0u32.into()),
+					},
+				);
+			},
+			Protocol::BitfieldDistribution => {
+				let bitfield = SignedAvailabilityBitfield::sign(
+					&keystore,
+					AvailabilityBitfield(bitvec![u8, Lsb0; 1; self.session_info.n_cores as usize]),
+					&signing_context,
+					validator_index,
+					&public,
+				)
+				.ok()
+				.flatten()
+				.expect("the key of the validator is in its keystore; qed");
+				self.send(
+					originator,
+					|s| &mut s.bitfield,
+					FromOrchestra::Communication {
+						msg: BitfieldDistributionMessage::DistributeBitfield(block, bitfield),
+					},
+				);
+			},
+			Protocol::StatementDistribution => {
+				let mut candidate = dummy_committed_candidate_receipt(block);
+				candidate.descriptor.para_id = (originator as u32).into();
+				let statement = SignedFullStatement::sign(
+					&keystore,
+					Statement::Seconded(candidate),
+					&signing_context,
+					validator_index,
+					&public,
+				)
+				.ok()
+				.flatten()
+				.expect("the key of the validator is in its keystore; qed");
+				self.send(
+					originator,
+					|s| &mut s.statement,
+					FromOrchestra::Communication {
+						msg: StatementDistributionMessage::Share(block, statement),
+					},
+				);
+			},
+		}
+	}
+
+	fn deliver(&mut self, delivery: Delivery) {
+		let Delivery { at, from, to, message, .. } = delivery;
+		self.now = at;
+		self.last_delivery = self.last_delivery.max(at);
+
+		let honest = self.validators[to].dishonest.is_none();
+		for item in protocol::items(&message) {
+			let stats = self.stats.entry(item.protocol).or_default();
+			if !self.validators[to].known.insert(item) {
+				stats.duplicates += 1;
+			} else if honest {
+				stats.received(item, at);
+			}
+		}
+
+		let peer_id = self.peer_ids[from];
+		self.network_event(to, NetworkBridgeEvent::PeerMessage(peer_id, message));
+		self.settle(to);
+	}
+
+	/// Send a signal to all subsystems of a validator.
+	fn signal(&mut self, v: usize, signal: OverseerSignal) {
+		self.send(v, |s| &mut s.approval, FromOrchestra::Signal(signal.clone()));
+		self.send(v, |s| &mut s.bitfield, FromOrchestra::Signal(signal.clone()));
+		self.send(v, |s| &mut s.statement, FromOrchestra::Signal(signal));
+	}
+
+	/// Dispatch an event of the validation peer set to the subsystems of a validator, like the
+	/// network bridge does.
+	fn network_event(&mut self, v: usize, event: NetworkBridgeEvent<VersionedValidationProtocol>) {
+		if let Ok(event) = event.focus() {
+			let msg = ApprovalDistributionMessage::NetworkBridgeUpdate(event);
+			self.send(v, |s| &mut s.approval, FromOrchestra::Communication { msg });
+		}
+		if let Ok(event) = event.focus() {
+			let msg = BitfieldDistributionMessage::NetworkBridgeUpdate(event);
+			self.send(v, |s| &mut s.bitfield, FromOrchestra::Communication { msg });
+		}
+		if let Ok(event) = event.focus() {
+			let msg = StatementDistributionMessage::NetworkBridgeUpdate(event);
+			self.send(v, |s| &mut s.statement, FromOrchestra::Communication { msg });
+		}
+	}
+
+	/// Send a message to a subsystem of a validator, if it is run.
+	fn send<M>(
+		&mut self,
+		v: usize,
+		subsystem: fn(&mut Subsystems) -> &mut Option<TestSubsystemContextHandle<M>>,
+		message: FromOrchestra<M>,
+	) {
+		let handle = match subsystem(&mut self.validators[v].subsystems) {
+			Some(handle) => handle,
+			None => return,
+		};
+		if let Err(err) = handle.tx.try_send(message) {
+			assert!(err.is_full(), "the subsystems only exit at the end of the simulation");
+			// Let the subsystem catch up.
+			self.settle(v);
+			subsystem(&mut self.validators[v].subsystems)
+				.as_mut()
+				.expect("the subsystem is run; qed")
+				.tx
+				.try_send(err.into_inner())
+				.expect("the subsystem processed all messages sent to it; qed");
+		}
+	}
+
+	/// Let the subsystems of a validator process the messages sent to them and handle the
+	/// messages they send in turn, until they are idle.
+	fn settle(&mut self, v: usize) {
+		loop {
+			self.executor.run_until_stalled();
+			let messages = self.validators[v].subsystems.drain();
+			if messages.is_empty() {
+				break
+			}
+			for message in messages {
+				self.handle_outgoing(v, message);
+			}
+		}
+	}
+
+	/// Handle a message a subsystem of a validator sent, in place of the overseer.
+	fn handle_outgoing(&mut self, v: usize, message: AllMessages) {
+		match message {
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				message,
+			)) => self.gossip(v, &peers, message),
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessages(
+				messages,
+			)) =>
+				for (peers, message) in messages {
+					self.gossip(v, &peers, message);
+				},
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(_, request)) => match request {
+				RuntimeApiRequest::Validators(tx) => {
+					let _ = tx.send(Ok(self.session_info.validators.to_vec()));
+				},
+				RuntimeApiRequest::SessionIndexForChild(tx) => {
+					let _ = tx.send(Ok(SESSION));
+				},
+				RuntimeApiRequest::SessionInfo(_, tx) => {
+					let _ = tx.send(Ok(Some(self.session_info.clone())));
+				},
+				// Dropping the sender fails the request.
+				_ => {},
+			},
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
+				_,
+				_,
+				tx,
+			)) => {
+				let _ = tx.send(AssignmentCheckResult::Accepted);
+			},
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApproval(_, tx)) => {
+				let _ = tx.send(ApprovalCheckResult::Accepted);
+			},
+			// Reputation changes and the messages to the subsystems consuming what was gossiped.
+			_ => {},
+		}
+	}
+
+	/// Send a message of a validator to the given peers, as far as the validator is honest.
+	fn gossip(&mut self, from: usize, peers: &[PeerId], message: VersionedValidationProtocol) {
+		let own = |item: &MessageId| item.originator == from;
+		let (now, delayed) = match self.validators[from].dishonest {
+			None => (Some(message), None),
+			Some(DishonestBehaviour::Silent) => return,
+			Some(DishonestBehaviour::NoForward) => (protocol::retain_items(&message, own), None),
+			Some(DishonestBehaviour::Delay(delay)) => {
+				let others = protocol::retain_items(&message, |item| !own(item))
+					.filter(|others| !protocol::items(others).is_empty());
+				(protocol::retain_items(&message, own), others.map(|others| (others, delay)))
+			},
+		};
+
+		for peer in peers {
+			let to = self.peer_indices[peer];
+			if let Some(message) = &now {
+				self.transmit(from, to, message.clone(), self.now);
+			}
+			if let Some((message, delay)) = &delayed {
+				self.transmit(from, to, message.clone(), self.now + *delay);
+			}
+		}
+	}
+
+	fn transmit(
+		&mut self,
+		from: usize,
+		to: usize,
+		message: VersionedValidationProtocol,
+		at: Duration,
+	) {
+		let size = protocol::encoded_size(&message);
+		let stats = self.stats.entry(Protocol::of(&message)).or_default();
+		stats.sent += 1;
+		stats.bytes += size as u64;
+
+		match self.network.transmit(from, size, at, &mut self.rng) {
+			Some(arrival) => {
+				self.deliveries.push(Reverse(Delivery {
+					at: arrival,
+					seq: self.next_seq,
+					from,
+					to,
+					message,
+				}));
+				self.next_seq += 1;
+			},
+			None => stats.lost += 1,
+		}
+	}
+}
+
+/// Generate a random number in the given range, for the virtual network.
+fn gen_range_micros(rng: &mut impl Rng, min: Duration, max: Duration) -> Duration {
+	let (min, max) = (min.as_micros() as u64, max.as_micros() as u64);
+	if max <= min {
+		return Duration::from_micros(min)
+	}
+	Duration::from_micros(rng.gen_range(min..=max))
+}
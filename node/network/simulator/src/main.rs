@@ -0,0 +1,120 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Simulate the gossip of the network subsystems and print how the messages spread.
+
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use polkadot_network_simulator::{
+	simulate, DishonestBehaviour, LatencyModel, NetworkConfig, Protocol, SimulationConfig,
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ProtocolArg {
+	Approval,
+	Bitfield,
+	Statement,
+}
+
+impl From<ProtocolArg> for Protocol {
+	fn from(arg: ProtocolArg) -> Self {
+		match arg {
+			ProtocolArg::Approval => Protocol::ApprovalDistribution,
+			ProtocolArg::Bitfield => Protocol::BitfieldDistribution,
+			ProtocolArg::Statement => Protocol::StatementDistribution,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DishonestArg {
+	/// Neither originate nor forward messages.
+	Silent,
+	/// Do not forward the messages of others.
+	NoForward,
+	/// Forward messages late, see `--dishonest-delay`.
+	Delay,
+}
+
+#[derive(Debug, Parser)]
+struct Cli {
+	/// The number of validators.
+	#[arg(long, default_value_t = 300)]
+	validators: usize,
+	/// The protocols to simulate. Defaults to all of them.
+	#[arg(long = "protocol", value_enum)]
+	protocols: Vec<ProtocolArg>,
+	/// How many messages every validator originates per protocol, for as many relay chain blocks.
+	#[arg(long, default_value_t = 1)]
+	messages: usize,
+	/// The lowest latency between two validators.
+	#[arg(long, value_name = "MILLISECONDS", default_value_t = 20)]
+	latency_min: u64,
+	/// The highest latency between two validators.
+	#[arg(long, value_name = "MILLISECONDS", default_value_t = 200)]
+	latency_max: u64,
+	/// The probability of a message to get lost, between 0 and 1.
+	#[arg(long, default_value_t = 0.0)]
+	loss: f64,
+	/// The upload bandwidth of every validator. Unlimited if not given.
+	#[arg(long, value_name = "KiB/s")]
+	bandwidth: Option<u64>,
+	/// The number of dishonest validators.
+	#[arg(long, default_value_t = 0)]
+	dishonest: usize,
+	/// How dishonest validators behave.
+	#[arg(long, value_enum, default_value_t = DishonestArg::Silent)]
+	dishonest_behaviour: DishonestArg,
+	/// How long dishonest validators delay messages with `--dishonest-behaviour delay`.
+	#[arg(long, value_name = "MILLISECONDS", default_value_t = 1000)]
+	dishonest_delay: u64,
+	/// The seed of the topology, the network and the choice of dishonest validators. The random
+	/// routing of the subsystems is not seeded, so runs with the same seed still differ.
+	#[arg(long, default_value_t = 0)]
+	seed: u64,
+}
+
+fn main() {
+	let cli = Cli::parse();
+
+	let mut config = SimulationConfig {
+		n_validators: cli.validators,
+		messages_per_validator: cli.messages,
+		network: NetworkConfig {
+			latency: LatencyModel {
+				min: Duration::from_millis(cli.latency_min),
+				max: Duration::from_millis(cli.latency_max),
+			},
+			loss: cli.loss,
+			upload_bandwidth: cli.bandwidth.map(|kib| kib * 1024),
+		},
+		n_dishonest: cli.dishonest,
+		dishonest_behaviour: match cli.dishonest_behaviour {
+			DishonestArg::Silent => DishonestBehaviour::Silent,
+			DishonestArg::NoForward => DishonestBehaviour::NoForward,
+			DishonestArg::Delay =>
+				DishonestBehaviour::Delay(Duration::from_millis(cli.dishonest_delay)),
+		},
+		seed: cli.seed,
+		..Default::default()
+	};
+	if !cli.protocols.is_empty() {
+		config.protocols = cli.protocols.into_iter().map(Into::into).collect();
+	}
+
+	println!("{}", simulate(&config));
+}
@@ -0,0 +1,94 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The virtual network connecting the validators.
+
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::gen_range_micros;
+
+/// The latency of the links between validators, drawn uniformly per message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyModel {
+	/// The lowest latency.
+	pub min: Duration,
+	/// The highest latency.
+	pub max: Duration,
+}
+
+impl Default for LatencyModel {
+	fn default() -> Self {
+		Self { min: Duration::from_millis(20), max: Duration::from_millis(200) }
+	}
+}
+
+/// Configuration of the virtual network.
+///
+/// Every validator is connected to every other validator, as on the validation peer set.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConfig {
+	/// The latency of the links.
+	pub latency: LatencyModel,
+	/// The probability of a message to get lost, between 0 and 1.
+	pub loss: f64,
+	/// The upload bandwidth of every validator in bytes per second, if limited. Messages are sent
+	/// one after another, so a validator sending a lot of messages delays its later ones.
+	pub upload_bandwidth: Option<u64>,
+}
+
+/// The state of the virtual network.
+pub(crate) struct Network {
+	config: NetworkConfig,
+	/// Until when the upload link of each validator is busy.
+	upload_busy_until: Vec<Duration>,
+}
+
+impl Network {
+	pub(crate) fn new(config: NetworkConfig, n_validators: usize) -> Self {
+		Self { config, upload_busy_until: vec![Duration::ZERO; n_validators] }
+	}
+
+	/// Send a message of the given size at the given time.
+	///
+	/// Returns when the message arrives at the receiver, or `None` if it got lost. Lost messages
+	/// still use up the bandwidth of the sender.
+	pub(crate) fn transmit(
+		&mut self,
+		from: usize,
+		size: usize,
+		at: Duration,
+		rng: &mut impl Rng,
+	) -> Option<Duration> {
+		let sent_at = match self.config.upload_bandwidth {
+			Some(bandwidth) => {
+				let start = at.max(self.upload_busy_until[from]);
+				let transmission =
+					Duration::from_micros(size as u64 * 1_000_000 / bandwidth.max(1));
+				self.upload_busy_until[from] = start + transmission;
+				start + transmission
+			},
+			None => at,
+		};
+
+		if self.config.loss > 0.0 && rng.gen_bool(self.config.loss.min(1.0)) {
+			return None
+		}
+
+		Some(sent_at + gen_range_micros(rng, self.config.latency.min, self.config.latency.max))
+	}
+}
@@ -0,0 +1,146 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The gossip protocols and the items their messages carry.
+
+use parity_scale_codec::Encode;
+use polkadot_node_network_protocol::{v1, v2, Versioned, VersionedValidationProtocol};
+use polkadot_node_primitives::approval::IndirectAssignmentCert;
+use polkadot_primitives::{Hash, ValidatorIndex};
+
+use crate::MessageId;
+
+/// A gossip protocol of the network subsystems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Protocol {
+	/// Assignments of approval-distribution.
+	ApprovalDistribution,
+	/// Availability bitfields of bitfield-distribution.
+	BitfieldDistribution,
+	/// Backing statements of statement-distribution.
+	StatementDistribution,
+}
+
+impl Protocol {
+	/// The protocol of a message sent on the validation peer set.
+	pub(crate) fn of(message: &VersionedValidationProtocol) -> Self {
+		match message {
+			Versioned::V1(v1::ValidationProtocol::ApprovalDistribution(_)) |
+			Versioned::V2(v2::ValidationProtocol::ApprovalDistribution(_)) => Self::ApprovalDistribution,
+			Versioned::V1(v1::ValidationProtocol::BitfieldDistribution(_)) |
+			Versioned::V2(v2::ValidationProtocol::BitfieldDistribution(_)) => Self::BitfieldDistribution,
+			Versioned::V1(v1::ValidationProtocol::StatementDistribution(_)) |
+			Versioned::V2(v2::ValidationProtocol::StatementDistribution(_)) => Self::StatementDistribution,
+		}
+	}
+}
+
+/// The encoded size of a message, as sent on the wire.
+pub(crate) fn encoded_size(message: &VersionedValidationProtocol) -> usize {
+	match message {
+		Versioned::V1(message) => message.encoded_size(),
+		Versioned::V2(message) => message.encoded_size(),
+	}
+}
+
+fn id(protocol: Protocol, originator: ValidatorIndex, block: Hash) -> MessageId {
+	MessageId { protocol, originator: originator.0 as usize, block }
+}
+
+fn assignment_id(cert: &IndirectAssignmentCert) -> MessageId {
+	id(Protocol::ApprovalDistribution, cert.validator, cert.block_hash)
+}
+
+/// The gossiped items a message carries.
+///
+/// Approvals are never originated in the simulation, so they carry no items.
+pub(crate) fn items(message: &VersionedValidationProtocol) -> Vec<MessageId> {
+	match message {
+		Versioned::V1(v1::ValidationProtocol::BitfieldDistribution(
+			v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield),
+		)) |
+		Versioned::V2(v2::ValidationProtocol::BitfieldDistribution(
+			v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield),
+		)) => vec![id(
+			Protocol::BitfieldDistribution,
+			bitfield.unchecked_validator_index(),
+			*relay_parent,
+		)],
+		Versioned::V1(v1::ValidationProtocol::StatementDistribution(statement)) |
+		Versioned::V2(v2::ValidationProtocol::StatementDistribution(statement)) => {
+			let (originator, relay_parent) = match statement {
+				v1::StatementDistributionMessage::Statement(relay_parent, statement) =>
+					(statement.unchecked_validator_index(), *relay_parent),
+				v1::StatementDistributionMessage::LargeStatement(metadata) =>
+					(metadata.signed_by, metadata.relay_parent),
+			};
+			vec![id(Protocol::StatementDistribution, originator, relay_parent)]
+		},
+		Versioned::V1(v1::ValidationProtocol::ApprovalDistribution(
+			v1::ApprovalDistributionMessage::Assignments(assignments),
+		)) => assignments.iter().map(|(cert, _)| assignment_id(cert)).collect(),
+		Versioned::V2(v2::ValidationProtocol::ApprovalDistribution(
+			v2::ApprovalDistributionMessage::Assignments(assignments),
+		)) => assignments.iter().map(|(cert, _)| assignment_id(cert)).collect(),
+		Versioned::V1(v1::ValidationProtocol::ApprovalDistribution(
+			v1::ApprovalDistributionMessage::Approvals(_),
+		)) |
+		Versioned::V2(v2::ValidationProtocol::ApprovalDistribution(
+			v2::ApprovalDistributionMessage::Approvals(_),
+		)) => Vec::new(),
+	}
+}
+
+/// The part of a message with the items `keep` returns `true` for, if any.
+///
+/// Messages without items are kept as they are.
+pub(crate) fn retain_items(
+	message: &VersionedValidationProtocol,
+	keep: impl Fn(&MessageId) -> bool,
+) -> Option<VersionedValidationProtocol> {
+	match message {
+		Versioned::V1(v1::ValidationProtocol::ApprovalDistribution(
+			v1::ApprovalDistributionMessage::Assignments(assignments),
+		)) => {
+			let assignments: Vec<_> = assignments
+				.iter()
+				.filter(|(cert, _)| keep(&assignment_id(cert)))
+				.cloned()
+				.collect();
+			(!assignments.is_empty()).then(|| {
+				Versioned::V1(v1::ValidationProtocol::ApprovalDistribution(
+					v1::ApprovalDistributionMessage::Assignments(assignments),
+				))
+			})
+		},
+		Versioned::V2(v2::ValidationProtocol::ApprovalDistribution(
+			v2::ApprovalDistributionMessage::Assignments(assignments),
+		)) => {
+			let assignments: Vec<_> = assignments
+				.iter()
+				.filter(|(cert, _)| keep(&assignment_id(cert)))
+				.cloned()
+				.collect();
+			(!assignments.is_empty()).then(|| {
+				Versioned::V2(v2::ValidationProtocol::ApprovalDistribution(
+					v2::ApprovalDistributionMessage::Assignments(assignments),
+				))
+			})
+		},
+		// All other messages carry at most one item.
+		message => items(message).iter().all(keep).then(|| message.clone()),
+	}
+}
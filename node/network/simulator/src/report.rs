@@ -0,0 +1,164 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The results of a simulation.
+
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt,
+	time::Duration,
+};
+
+use crate::{MessageId, Protocol};
+
+/// Percentiles of the time it took messages to reach the honest validators, counted from the
+/// time they were originated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Percentiles {
+	/// The median.
+	pub p50: Duration,
+	/// The 90th percentile.
+	pub p90: Duration,
+	/// The 99th percentile.
+	pub p99: Duration,
+	/// The maximum.
+	pub max: Duration,
+}
+
+impl Percentiles {
+	pub(crate) fn from_sorted(sorted: &[Duration]) -> Self {
+		let percentile = |p: usize| {
+			if sorted.is_empty() {
+				return Duration::ZERO
+			}
+			// nearest-rank method.
+			let rank = (p * sorted.len() + 99) / 100;
+			sorted[rank.max(1) - 1]
+		};
+		Self { p50: percentile(50), p90: percentile(90), p99: percentile(99), max: percentile(100) }
+	}
+}
+
+/// The results of a simulation for one protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolReport {
+	/// The number of originated messages.
+	pub messages: usize,
+	/// The number of messages sent between validators, including lost ones.
+	pub sent: u64,
+	/// The number of messages lost by the network.
+	pub lost: u64,
+	/// The number of messages which arrived at a validator which knew them already.
+	pub duplicates: u64,
+	/// The number of bytes sent between validators.
+	pub bytes: u64,
+	/// The share of honest validators the messages reached, between 0 and 1.
+	pub coverage: f64,
+	/// The share of messages which reached every honest validator, between 0 and 1.
+	pub full_coverage: f64,
+	/// How long it took messages to reach the honest validators.
+	pub propagation: Percentiles,
+}
+
+/// The results of a simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+	/// The results per protocol.
+	pub protocols: BTreeMap<Protocol, ProtocolReport>,
+	/// The time of the last delivery of any message.
+	pub duration: Duration,
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (protocol, report) in &self.protocols {
+			writeln!(f, "{:?}:", protocol)?;
+			writeln!(f, "  messages:      {}", report.messages)?;
+			writeln!(
+				f,
+				"  sent:          {} ({} bytes, {} lost, {} duplicates)",
+				report.sent, report.bytes, report.lost, report.duplicates
+			)?;
+			writeln!(f, "  coverage:      {:.2}%", report.coverage * 100.0)?;
+			writeln!(f, "  full coverage: {:.2}%", report.full_coverage * 100.0)?;
+			writeln!(
+				f,
+				"  propagation:   p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+				report.propagation.p50,
+				report.propagation.p90,
+				report.propagation.p99,
+				report.propagation.max
+			)?;
+		}
+		write!(f, "last delivery after {:?}", self.duration)
+	}
+}
+
+/// Statistics of a protocol collected during a simulation.
+#[derive(Default)]
+pub(crate) struct ProtocolStats {
+	pub(crate) sent: u64,
+	pub(crate) lost: u64,
+	pub(crate) duplicates: u64,
+	pub(crate) bytes: u64,
+	/// The number of honest validators each message should reach and did reach.
+	receivers: HashMap<MessageId, (usize, usize)>,
+	/// The times it took messages to reach honest validators.
+	propagation_times: Vec<Duration>,
+}
+
+impl ProtocolStats {
+	/// A message got originated at the start of the simulation.
+	pub(crate) fn originated(&mut self, message: MessageId, expected_receivers: usize) {
+		self.receivers.insert(message, (expected_receivers, 0));
+	}
+
+	/// An honest validator received a message for the first time.
+	pub(crate) fn received(&mut self, message: MessageId, at: Duration) {
+		if let Some((_, received)) = self.receivers.get_mut(&message) {
+			*received += 1;
+		}
+		self.propagation_times.push(at);
+	}
+
+	pub(crate) fn into_report(mut self) -> ProtocolReport {
+		self.propagation_times.sort();
+
+		let (expected, received) = self
+			.receivers
+			.values()
+			.fold((0, 0), |(expected, received), (e, r)| (expected + e, received + r));
+		let fully_covered = self.receivers.values().filter(|(e, r)| r >= e).count();
+		let share = |part: usize, total: usize| {
+			if total == 0 {
+				1.0
+			} else {
+				part as f64 / total as f64
+			}
+		};
+
+		ProtocolReport {
+			messages: self.receivers.len(),
+			sent: self.sent,
+			lost: self.lost,
+			duplicates: self.duplicates,
+			bytes: self.bytes,
+			coverage: share(received, expected),
+			full_coverage: share(fully_covered, self.receivers.len()),
+			propagation: Percentiles::from_sorted(&self.propagation_times),
+		}
+	}
+}
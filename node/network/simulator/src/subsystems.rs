@@ -0,0 +1,237 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The gossip subsystems of a virtual validator and the executor running them.
+
+use std::{
+	collections::HashSet,
+	sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use futures::{executor::LocalPool, future::BoxFuture, task::SpawnExt, FutureExt};
+use polkadot_approval_distribution::ApprovalDistribution;
+use polkadot_availability_bitfield_distribution::BitfieldDistribution;
+use polkadot_node_network_protocol::{
+	authority_discovery::AuthorityDiscovery,
+	request_response::{
+		v1, v2, IncomingRequest, QuotaReceiver, ReqProtocolNames, RequestResponseConfig,
+	},
+	PeerId,
+};
+use polkadot_node_subsystem::{
+	messages::{
+		AllMessages, ApprovalDistributionMessage, BitfieldDistributionMessage,
+		StatementDistributionMessage,
+	},
+	overseer::Subsystem,
+	SubsystemError,
+};
+use polkadot_node_subsystem_test_helpers::{
+	make_buffered_subsystem_context, TestSubsystemContextHandle,
+};
+use polkadot_primitives::{AuthorityDiscoveryId, Hash};
+use polkadot_statement_distribution::StatementDistributionSubsystem;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sc_network::Multiaddr;
+use sp_core::traits::SpawnNamed;
+use sp_keystore::KeystorePtr;
+
+use crate::Protocol;
+
+/// How many messages can be sent to a subsystem before the simulation lets it process them.
+const CHANNEL_SIZE: usize = 64;
+
+/// Queues the tasks spawned by the subsystems until the [`Executor`] picks them up.
+#[derive(Clone, Default)]
+pub(crate) struct Spawner(Arc<Mutex<Vec<BoxFuture<'static, ()>>>>);
+
+impl SpawnNamed for Spawner {
+	fn spawn_blocking(
+		&self,
+		_name: &'static str,
+		_group: Option<&'static str>,
+		future: BoxFuture<'static, ()>,
+	) {
+		self.0.lock().expect("the lock is never poisoned; qed").push(future);
+	}
+
+	fn spawn(
+		&self,
+		_name: &'static str,
+		_group: Option<&'static str>,
+		future: BoxFuture<'static, ()>,
+	) {
+		self.0.lock().expect("the lock is never poisoned; qed").push(future);
+	}
+}
+
+/// Runs the subsystems of all validators on the current thread.
+///
+/// The subsystems only make progress when the simulation runs the executor, so the simulated
+/// time does not advance while they process a message.
+#[derive(Default)]
+pub(crate) struct Executor {
+	pool: LocalPool,
+	spawner: Spawner,
+}
+
+impl Executor {
+	pub(crate) fn spawner(&self) -> Spawner {
+		self.spawner.clone()
+	}
+
+	/// Run all tasks until none of them can make progress.
+	pub(crate) fn run_until_stalled(&mut self) {
+		loop {
+			self.pool.run_until_stalled();
+
+			let spawned = std::mem::take(
+				&mut *self.spawner.0.lock().expect("the lock is never poisoned; qed"),
+			);
+			if spawned.is_empty() {
+				break
+			}
+			let spawner = self.pool.spawner();
+			for task in spawned {
+				spawner.spawn(task).expect("the pool is alive; qed");
+			}
+		}
+	}
+
+	fn spawn_subsystem(&self, future: BoxFuture<'static, Result<(), SubsystemError>>) {
+		self.pool.spawner().spawn(future.map(|_| ())).expect("the pool is alive; qed");
+	}
+}
+
+/// Authority discovery for the requests to statement-distribution.
+///
+/// Statements are small enough to be gossiped in the simulation, so no requests are made.
+#[derive(Debug)]
+struct NoAuthorityDiscovery;
+
+#[async_trait]
+impl AuthorityDiscovery for NoAuthorityDiscovery {
+	async fn get_addresses_by_authority_id(
+		&mut self,
+		_: AuthorityDiscoveryId,
+	) -> Option<HashSet<Multiaddr>> {
+		None
+	}
+
+	async fn get_authority_ids_by_peer_id(
+		&mut self,
+		_: PeerId,
+	) -> Option<HashSet<AuthorityDiscoveryId>> {
+		None
+	}
+}
+
+/// The gossip subsystems of a virtual validator, in place of the overseer.
+pub(crate) struct Subsystems {
+	pub(crate) approval: Option<TestSubsystemContextHandle<ApprovalDistributionMessage>>,
+	pub(crate) bitfield: Option<TestSubsystemContextHandle<BitfieldDistributionMessage>>,
+	pub(crate) statement: Option<TestSubsystemContextHandle<StatementDistributionMessage>>,
+	/// Keeps the request channels of statement-distribution open.
+	_request_configs: Vec<RequestResponseConfig>,
+}
+
+impl Subsystems {
+	/// Start the subsystems of the given protocols on the executor.
+	pub(crate) fn start(
+		executor: &Executor,
+		protocols: &[Protocol],
+		keystore: KeystorePtr,
+		seed: u64,
+	) -> Self {
+		let mut subsystems = Subsystems {
+			approval: None,
+			bitfield: None,
+			statement: None,
+			_request_configs: Vec::new(),
+		};
+
+		for protocol in protocols {
+			match protocol {
+				Protocol::ApprovalDistribution => {
+					let (ctx, handle) =
+						make_buffered_subsystem_context(executor.spawner(), CHANNEL_SIZE);
+					executor.spawn_subsystem(
+						ApprovalDistribution::new(Default::default()).start(ctx).future,
+					);
+					subsystems.approval = Some(handle);
+				},
+				Protocol::BitfieldDistribution => {
+					let (ctx, handle) =
+						make_buffered_subsystem_context(executor.spawner(), CHANNEL_SIZE);
+					executor.spawn_subsystem(
+						BitfieldDistribution::new(Default::default()).start(ctx).future,
+					);
+					subsystems.bitfield = Some(handle);
+				},
+				Protocol::StatementDistribution => {
+					let req_protocol_names = ReqProtocolNames::new(Hash::zero(), None);
+					let (v1_receiver, v1_config) =
+						IncomingRequest::<v1::StatementFetchingRequest>::get_config_receiver(
+							&req_protocol_names,
+						);
+					let (v2_receiver, v2_config) =
+						IncomingRequest::<v2::StatementFetchingRequest>::get_config_receiver(
+							&req_protocol_names,
+						);
+					let subsystem = StatementDistributionSubsystem::new(
+						keystore.clone(),
+						QuotaReceiver::with_default_quota(
+							v1_receiver,
+							Box::new(NoAuthorityDiscovery),
+						),
+						QuotaReceiver::with_default_quota(
+							v2_receiver,
+							Box::new(NoAuthorityDiscovery),
+						),
+						Default::default(),
+						ChaCha20Rng::seed_from_u64(seed),
+					);
+
+					let (ctx, handle) =
+						make_buffered_subsystem_context(executor.spawner(), CHANNEL_SIZE);
+					executor.spawn_subsystem(subsystem.start(ctx).future);
+					subsystems.statement = Some(handle);
+					subsystems._request_configs.extend([v1_config, v2_config]);
+				},
+			}
+		}
+
+		subsystems
+	}
+
+	/// The messages the subsystems sent since the last call.
+	pub(crate) fn drain(&mut self) -> Vec<AllMessages> {
+		let mut messages = Vec::new();
+		let receivers = [
+			self.approval.as_mut().map(|handle| &mut handle.rx),
+			self.bitfield.as_mut().map(|handle| &mut handle.rx),
+			self.statement.as_mut().map(|handle| &mut handle.rx),
+		];
+		for rx in receivers.into_iter().flatten() {
+			while let Ok(Some(message)) = rx.try_next() {
+				messages.push(message);
+			}
+		}
+		messages
+	}
+}
@@ -0,0 +1,107 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+fn config(n_validators: usize) -> SimulationConfig {
+	SimulationConfig { n_validators, seed: 42, ..Default::default() }
+}
+
+#[test]
+fn runs_with_the_same_seed_agree_on_reliable_network() {
+	let config = config(100);
+
+	// The random routing of the subsystems differs between runs, only what is independent of it
+	// matches.
+	let (a, b) = (simulate(&config), simulate(&config));
+	for protocol in &config.protocols {
+		let (a, b) = (&a.protocols[protocol], &b.protocols[protocol]);
+		assert_eq!(a.messages, b.messages, "{:?}", protocol);
+		assert_eq!(a.coverage, b.coverage, "{:?}", protocol);
+		assert_eq!(a.full_coverage, b.full_coverage, "{:?}", protocol);
+	}
+}
+
+#[test]
+fn messages_reach_all_validators_on_reliable_network() {
+	let report = simulate(&config(100));
+
+	assert_eq!(report.protocols.len(), 3);
+	for (protocol, report) in report.protocols {
+		assert_eq!(report.messages, 100, "{:?}", protocol);
+		assert_eq!(report.lost, 0, "{:?}", protocol);
+		assert_eq!(report.coverage, 1.0, "{:?}", protocol);
+		assert_eq!(report.full_coverage, 1.0, "{:?}", protocol);
+		// The grid reaches everyone in two hops.
+		assert!(report.propagation.max <= 2 * LatencyModel::default().max, "{:?}", protocol);
+	}
+}
+
+#[test]
+fn lost_messages_are_counted() {
+	let config = SimulationConfig {
+		protocols: vec![Protocol::BitfieldDistribution],
+		network: NetworkConfig { loss: 0.5, ..Default::default() },
+		..config(100)
+	};
+	let report = &simulate(&config).protocols[&Protocol::BitfieldDistribution];
+
+	assert!(report.lost > 0);
+	assert!(report.lost < report.sent);
+	assert!(report.coverage < 1.0);
+}
+
+#[test]
+fn silent_validators_neither_originate_nor_count_towards_coverage() {
+	let config = SimulationConfig {
+		protocols: vec![Protocol::ApprovalDistribution],
+		n_dishonest: 10,
+		dishonest_behaviour: DishonestBehaviour::Silent,
+		..config(100)
+	};
+	let report = &simulate(&config).protocols[&Protocol::ApprovalDistribution];
+
+	assert_eq!(report.messages, 90);
+	// Random routing makes up for the silent validators on the grid.
+	assert!(report.coverage > 0.95);
+}
+
+#[test]
+fn limited_bandwidth_delays_propagation() {
+	let unlimited =
+		SimulationConfig { protocols: vec![Protocol::StatementDistribution], ..config(100) };
+	let limited = SimulationConfig {
+		network: NetworkConfig { upload_bandwidth: Some(10_000), ..Default::default() },
+		..unlimited.clone()
+	};
+
+	let unlimited = &simulate(&unlimited).protocols[&Protocol::StatementDistribution];
+	let limited = &simulate(&limited).protocols[&Protocol::StatementDistribution];
+
+	assert_eq!(limited.coverage, 1.0);
+	assert!(limited.propagation.p50 > unlimited.propagation.p50);
+}
+
+#[test]
+fn percentiles_use_nearest_rank() {
+	let times: Vec<_> = (1..=10).map(Duration::from_millis).collect();
+	let percentiles = Percentiles::from_sorted(&times);
+
+	assert_eq!(percentiles.p50, Duration::from_millis(5));
+	assert_eq!(percentiles.p90, Duration::from_millis(9));
+	assert_eq!(percentiles.p99, Duration::from_millis(10));
+	assert_eq!(percentiles.max, Duration::from_millis(10));
+}
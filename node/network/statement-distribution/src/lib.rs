@@ -29,7 +29,7 @@ use polkadot_node_network_protocol::{
 	self as net_protocol,
 	grid_topology::{GridNeighbors, RequiredRouting, SessionBoundGridTopologyStorage},
 	peer_set::{IsAuthority, PeerSet},
-	request_response::{v1 as request_v1, IncomingRequestReceiver},
+	request_response::{v1 as request_v1, v2 as request_v2, QuotaReceiver},
 	v1::{self as protocol_v1, StatementMetadata},
 	IfDisconnected, PeerId, UnifiedReputationChange as Rep, Versioned, View,
 };
@@ -76,11 +76,11 @@ pub use error::{Error, FatalError, JfyiError, Result};
 
 /// Background task logic for requesting of large statements.
 mod requester;
-use requester::{fetch, RequesterMessage};
+use requester::{fetch, fetch_chunked, FetchMode, RequesterMessage};
 
 /// Background task logic for responding for large statements.
 mod responder;
-use responder::{respond, ResponderMessage};
+use responder::{respond, respond_chunks, ResponderMessage};
 
 /// Metrics for the statement distribution
 pub(crate) mod metrics;
@@ -101,6 +101,7 @@ const COST_FETCH_FAIL: Rep =
 	Rep::CostMinor("Requesting `CommittedCandidateReceipt` from peer failed");
 const COST_INVALID_SIGNATURE: Rep = Rep::CostMajor("Invalid Statement Signature");
 const COST_WRONG_HASH: Rep = Rep::CostMajor("Received candidate had wrong hash");
+const COST_INVALID_CHUNK: Rep = Rep::CostMajor("Received invalid large statement chunk");
 const COST_DUPLICATE_STATEMENT: Rep =
 	Rep::CostMajorRepeated("Statement sent more than once by peer");
 const COST_APPARENT_FLOOD: Rep = Rep::Malicious("Peer appears to be flooding us with statements");
@@ -128,7 +129,11 @@ pub struct StatementDistributionSubsystem<R> {
 	/// Pointer to a keystore, which is required for determining this node's validator index.
 	keystore: KeystorePtr,
 	/// Receiver for incoming large statement requests.
-	req_receiver: Option<IncomingRequestReceiver<request_v1::StatementFetchingRequest>>,
+	req_receiver: Option<QuotaReceiver<request_v1::StatementFetchingRequest>>,
+	/// Receiver for incoming requests of large statement chunks.
+	///
+	/// Large statements are only fetched in chunks if this is set.
+	chunk_req_receiver: Option<QuotaReceiver<request_v2::StatementFetchingRequest>>,
 	/// Prometheus metrics
 	metrics: Metrics,
 	/// Pseudo-random generator for peers selection logic
@@ -1200,6 +1205,7 @@ async fn retrieve_statement_from_message<'a, Context>(
 	active_head: &'a mut ActiveHeadData,
 	ctx: &mut Context,
 	req_sender: &mpsc::Sender<RequesterMessage>,
+	fetch_mode: FetchMode,
 	metrics: &Metrics,
 ) -> Option<UncheckedSignedFullStatement> {
 	let fingerprint = message.get_fingerprint();
@@ -1266,7 +1272,8 @@ async fn retrieve_statement_from_message<'a, Context>(
 			match message {
 				protocol_v1::StatementDistributionMessage::LargeStatement(metadata) => {
 					if let Some(new_status) =
-						launch_request(metadata, peer, req_sender.clone(), ctx, metrics).await
+						launch_request(metadata, peer, req_sender.clone(), fetch_mode, ctx, metrics)
+							.await
 					{
 						vacant.insert(new_status);
 					}
@@ -1291,12 +1298,19 @@ async fn launch_request<Context>(
 	meta: StatementMetadata,
 	peer: PeerId,
 	req_sender: mpsc::Sender<RequesterMessage>,
+	fetch_mode: FetchMode,
 	ctx: &mut Context,
 	metrics: &Metrics,
 ) -> Option<LargeStatementStatus> {
-	let (task, handle) =
-		fetch(meta.relay_parent, meta.candidate_hash, vec![peer], req_sender, metrics.clone())
-			.remote_handle();
+	let (relay_parent, candidate_hash) = (meta.relay_parent, meta.candidate_hash);
+	let (task, handle) = match fetch_mode {
+		FetchMode::Whole =>
+			fetch(relay_parent, candidate_hash, vec![peer], req_sender, metrics.clone()).boxed(),
+		FetchMode::Chunked =>
+			fetch_chunked(relay_parent, candidate_hash, vec![peer], req_sender, metrics.clone())
+				.boxed(),
+	}
+	.remote_handle();
 
 	let result = ctx.spawn("large-statement-fetcher", task.boxed());
 	if let Err(err) = result {
@@ -1327,6 +1341,7 @@ async fn handle_incoming_message_and_circulate<'a, Context, R>(
 	ctx: &mut Context,
 	message: protocol_v1::StatementDistributionMessage,
 	req_sender: &mpsc::Sender<RequesterMessage>,
+	fetch_mode: FetchMode,
 	metrics: &Metrics,
 	runtime: &mut RuntimeInfo,
 	rng: &mut R,
@@ -1344,6 +1359,7 @@ async fn handle_incoming_message_and_circulate<'a, Context, R>(
 				ctx,
 				message,
 				req_sender,
+				fetch_mode,
 				metrics,
 				reputation,
 			)
@@ -1409,6 +1425,7 @@ async fn handle_incoming_message<'a, Context>(
 	ctx: &mut Context,
 	message: protocol_v1::StatementDistributionMessage,
 	req_sender: &mpsc::Sender<RequesterMessage>,
+	fetch_mode: FetchMode,
 	metrics: &Metrics,
 	reputation: &mut ReputationAggregator,
 ) -> Option<(Hash, StoredStatement<'a>)> {
@@ -1525,9 +1542,16 @@ async fn handle_incoming_message<'a, Context>(
 
 	// Fetch from the network only after signature and usefulness checks are completed.
 	let is_large_statement = message.is_large_statement();
-	let statement =
-		retrieve_statement_from_message(peer, message, active_head, ctx, req_sender, metrics)
-			.await?;
+	let statement = retrieve_statement_from_message(
+		peer,
+		message,
+		active_head,
+		ctx,
+		req_sender,
+		fetch_mode,
+		metrics,
+	)
+	.await?;
 
 	let payload = statement.unchecked_into_payload();
 
@@ -1651,6 +1675,7 @@ async fn handle_network_update<Context, R>(
 	recent_outdated_heads: &RecentOutdatedHeads,
 	ctx: &mut Context,
 	req_sender: &mpsc::Sender<RequesterMessage>,
+	fetch_mode: FetchMode,
 	update: NetworkBridgeEvent<net_protocol::StatementDistributionMessage>,
 	metrics: &Metrics,
 	runtime: &mut RuntimeInfo,
@@ -1715,7 +1740,7 @@ async fn handle_network_update<Context, R>(
 				}
 			}
 		},
-		NetworkBridgeEvent::PeerMessage(peer, Versioned::V1(message)) => {
+		NetworkBridgeEvent::PeerMessage(peer, Versioned::V1(message) | Versioned::V2(message)) => {
 			handle_incoming_message_and_circulate(
 				peer,
 				topology_storage,
@@ -1725,6 +1750,7 @@ async fn handle_network_update<Context, R>(
 				ctx,
 				message,
 				req_sender,
+				fetch_mode,
 				metrics,
 				runtime,
 				rng,
@@ -1762,13 +1788,15 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 	/// Create a new Statement Distribution Subsystem
 	pub fn new(
 		keystore: KeystorePtr,
-		req_receiver: IncomingRequestReceiver<request_v1::StatementFetchingRequest>,
+		req_receiver: QuotaReceiver<request_v1::StatementFetchingRequest>,
+		chunk_req_receiver: QuotaReceiver<request_v2::StatementFetchingRequest>,
 		metrics: Metrics,
 		rng: R,
 	) -> Self {
 		Self {
 			keystore,
 			req_receiver: Some(req_receiver),
+			chunk_req_receiver: Some(chunk_req_receiver),
 			metrics,
 			rng,
 			reputation: Default::default(),
@@ -1810,6 +1838,18 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 		)
 		.map_err(FatalError::SpawnTask)?;
 
+		let fetch_mode = match self.chunk_req_receiver.take() {
+			Some(chunk_req_receiver) => {
+				ctx.spawn(
+					"large-statement-chunk-responder",
+					respond_chunks(chunk_req_receiver, res_sender.clone()).boxed(),
+				)
+				.map_err(FatalError::SpawnTask)?;
+				FetchMode::Chunked
+			},
+			None => FetchMode::Whole,
+		};
+
 		loop {
 			select! {
 				_ = reputation_delay => {
@@ -1829,6 +1869,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 									&mut active_heads,
 									&mut recent_outdated_heads,
 									&req_sender,
+									fetch_mode,
 									result?,
 								)
 								.await;
@@ -1847,6 +1888,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 									&mut active_heads,
 									&recent_outdated_heads,
 									&req_sender,
+									fetch_mode,
 									&mut runtime,
 									result.ok_or(FatalError::RequesterReceiverFinished)?,
 								)
@@ -1917,6 +1959,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 		active_heads: &mut HashMap<Hash, ActiveHeadData>,
 		recent_outdated_heads: &RecentOutdatedHeads,
 		req_sender: &mpsc::Sender<RequesterMessage>,
+		fetch_mode: FetchMode,
 		runtime: &mut RuntimeInfo,
 		message: RequesterMessage,
 	) -> JfyiErrorResult<()> {
@@ -1924,7 +1967,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 			RequesterMessage::Finished {
 				relay_parent,
 				candidate_hash,
-				from_peer,
+				from_peers,
 				response,
 				bad_peers,
 			} => {
@@ -1932,13 +1975,15 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 					modify_reputation(&mut self.reputation, ctx.sender(), bad, COST_FETCH_FAIL)
 						.await;
 				}
-				modify_reputation(
-					&mut self.reputation,
-					ctx.sender(),
-					from_peer,
-					BENEFIT_VALID_RESPONSE,
-				)
-				.await;
+				for peer in from_peers {
+					modify_reputation(
+						&mut self.reputation,
+						ctx.sender(),
+						peer,
+						BENEFIT_VALID_RESPONSE,
+					)
+					.await;
+				}
 
 				let active_head = active_heads
 					.get_mut(&relay_parent)
@@ -1975,6 +2020,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 							ctx,
 							message,
 							req_sender,
+							fetch_mode,
 							&self.metrics,
 							runtime,
 							&mut self.rng,
@@ -2038,6 +2084,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 		active_heads: &mut HashMap<Hash, ActiveHeadData>,
 		recent_outdated_heads: &mut RecentOutdatedHeads,
 		req_sender: &mpsc::Sender<RequesterMessage>,
+		fetch_mode: FetchMode,
 		message: FromOrchestra<StatementDistributionMessage>,
 	) -> Result<bool> {
 		let metrics = &self.metrics;
@@ -2157,6 +2204,7 @@ impl<R: rand::Rng> StatementDistributionSubsystem<R> {
 						&*recent_outdated_heads,
 						ctx,
 						req_sender,
+						fetch_mode,
 						event,
 						metrics,
 						runtime,
@@ -14,17 +14,23 @@
 
 //! Large statement requesting background task logic.
 
-use std::time::Duration;
+use std::{
+	collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+	time::Duration,
+};
 
 use futures::{
 	channel::{mpsc, oneshot},
-	SinkExt,
+	future::{self, BoxFuture, Either},
+	stream::FuturesUnordered,
+	FutureExt, SinkExt, StreamExt,
 };
+use parity_scale_codec::DecodeAll;
 
 use polkadot_node_network_protocol::{
 	request_response::{
-		v1::{StatementFetchingRequest, StatementFetchingResponse},
-		OutgoingRequest, Recipient, Requests,
+		v1, v2, OutgoingRequest, OutgoingResult, Recipient, Requests, MAX_STATEMENT_CHUNKS,
+		STATEMENT_CHUNK_SIZE,
 	},
 	PeerId, UnifiedReputationChange,
 };
@@ -32,13 +38,28 @@ use polkadot_node_subsystem::{Span, Stage};
 use polkadot_node_subsystem_util::TimeoutExt;
 use polkadot_primitives::{CandidateHash, CommittedCandidateReceipt, Hash};
 
-use crate::{metrics::Metrics, COST_WRONG_HASH, LOG_TARGET};
+use crate::{metrics::Metrics, COST_INVALID_CHUNK, COST_WRONG_HASH, LOG_TARGET};
 
 // In case we failed fetching from our known peers, how long we should wait before attempting a
 // retry, even though we have not yet discovered any new peers. Or in other words how long to
 // wait before retrying peers that already failed.
 const RETRY_TIMEOUT: Duration = Duration::from_millis(500);
 
+/// How many requests for the same chunk may be in flight at once.
+///
+/// Once every missing chunk has been requested, idle peers are asked for chunks which are already
+/// being fetched, so a single slow peer can't hold up the whole statement.
+const MAX_REQUESTS_PER_CHUNK: usize = 2;
+
+/// How large statements are fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchMode {
+	/// Fetch the whole statement from one peer at a time, see [`fetch`].
+	Whole,
+	/// Fetch chunks of the statement from all peers which announced it, see [`fetch_chunked`].
+	Chunked,
+}
+
 /// Messages coming from a background task.
 pub enum RequesterMessage {
 	/// Get an update of available peers to try for fetching a given statement.
@@ -54,9 +75,9 @@ pub enum RequesterMessage {
 		relay_parent: Hash,
 		/// The candidate we fetched data for.
 		candidate_hash: CandidateHash,
-		/// Data was fetched from this peer.
-		from_peer: PeerId,
-		/// Response we received from above peer.
+		/// Data was fetched from these peers.
+		from_peers: Vec<PeerId>,
+		/// Response we received from above peers.
 		response: CommittedCandidateReceipt,
 		/// Peers which failed providing the data.
 		bad_peers: Vec<PeerId>,
@@ -95,7 +116,7 @@ pub async fn fetch(
 	// Peers left for trying out.
 	let mut new_peers = peers;
 
-	let req = StatementFetchingRequest { relay_parent, candidate_hash };
+	let req = v1::StatementFetchingRequest { relay_parent, candidate_hash };
 
 	// We retry endlessly (with sleep periods), and rely on the subsystem to kill us eventually.
 	loop {
@@ -121,7 +142,7 @@ pub async fn fetch(
 			metrics.on_sent_request();
 
 			match pending_response.await {
-				Ok(StatementFetchingResponse::Statement(statement)) => {
+				Ok(v1::StatementFetchingResponse::Statement(statement)) => {
 					if statement.hash() != candidate_hash {
 						metrics.on_received_response(false);
 						metrics.on_unexpected_statement_large();
@@ -143,7 +164,7 @@ pub async fn fetch(
 						.feed(RequesterMessage::Finished {
 							relay_parent,
 							candidate_hash,
-							from_peer: peer,
+							from_peers: vec![peer],
 							response: statement,
 							bad_peers: tried_peers,
 						})
@@ -195,6 +216,488 @@ pub async fn fetch(
 	}
 }
 
+/// A fetching task, fetching large statements in chunks from all peers which announced them.
+///
+/// Every peer gets asked for one chunk at a time, peers which don't support
+/// `StatementFetchingV2` are asked for the whole statement instead. Chunks can't be checked on
+/// their own, only the reassembled `CommittedCandidateReceipt` can be checked against the
+/// candidate hash. If that check fails and the chunks came from several peers, all chunks are
+/// fetched from a single peer from then on, so the peer sending garbage can be punished.
+pub async fn fetch_chunked(
+	relay_parent: Hash,
+	candidate_hash: CandidateHash,
+	peers: Vec<PeerId>,
+	mut sender: mpsc::Sender<RequesterMessage>,
+	metrics: Metrics,
+) {
+	let span = Span::new(candidate_hash, "fetch-large-statement-chunks")
+		.with_relay_parent(relay_parent)
+		.with_stage(Stage::StatementDistribution);
+
+	gum::debug!(
+		target: LOG_TARGET,
+		?candidate_hash,
+		?relay_parent,
+		"Chunked fetch for large statement started",
+	);
+
+	// Peers without a request in flight, new arrivals are tried first.
+	let mut idle_peers = peers;
+	// Peers we already tried (and failed).
+	let mut tried_peers = Vec::new();
+	// Peers which don't support fetching chunks.
+	let mut legacy_peers = HashSet::new();
+	let mut sources = Sources::Any;
+	let mut chunks = Chunks::default();
+	let mut pending: FuturesUnordered<BoxFuture<'static, (PeerId, Response)>> =
+		FuturesUnordered::new();
+
+	// Peers announcing the statement while we are fetching it are put to work right away.
+	let mut more_peers = match request_more_peers(relay_parent, candidate_hash, &mut sender).await {
+		Ok(rx) => rx,
+		Err(()) => return,
+	};
+
+	// We retry endlessly (with sleep periods), and rely on the subsystem to kill us eventually.
+	loop {
+		// Hand out requests to all peers we have something to ask for.
+		let mut waiting_peers = Vec::new();
+		while let Some(peer) = idle_peers.pop() {
+			let (request, response) = if legacy_peers.contains(&peer) {
+				let (outgoing, response) = OutgoingRequest::new(
+					Recipient::Peer(peer),
+					v1::StatementFetchingRequest { relay_parent, candidate_hash },
+				);
+				let response = response.map(move |r| (peer, Response::Whole(r))).boxed();
+				(Requests::StatementFetchingV1(outgoing), response)
+			} else {
+				let chunk_index = match sources {
+					Sources::Single(Some(source)) if source != peer => None,
+					_ => chunks.next(),
+				};
+				let chunk_index = match chunk_index {
+					Some(chunk_index) => chunk_index,
+					None => {
+						waiting_peers.push(peer);
+						continue
+					},
+				};
+				if let Sources::Single(source) = &mut sources {
+					*source = Some(peer);
+				}
+				chunks.on_request_sent(chunk_index);
+
+				let (outgoing, response) = OutgoingRequest::new(
+					Recipient::Peer(peer),
+					v2::StatementFetchingRequest { relay_parent, candidate_hash, chunk_index },
+				);
+				let response =
+					response.map(move |r| (peer, Response::Chunk(chunk_index, r))).boxed();
+				(Requests::StatementFetchingV2(outgoing), response)
+			};
+
+			let _span = span.child("try-peer").with_peer_id(&peer);
+			if let Err(err) = sender.feed(RequesterMessage::SendRequest(request)).await {
+				gum::info!(
+					target: LOG_TARGET,
+					?err,
+					"Sending request failed, node might be shutting down - exiting."
+				);
+				return
+			}
+			metrics.on_sent_request();
+			pending.push(response);
+		}
+		idle_peers = waiting_peers;
+
+		if pending.is_empty() {
+			// All our peers failed us - wait for new ones before trying again:
+			idle_peers.append(&mut tried_peers);
+
+			let _span = span.child("wait-for-peers");
+			match (&mut more_peers).timeout(RETRY_TIMEOUT).await {
+				// No new peers, try the old ones again (if we have any):
+				None => continue,
+				Some(Ok(mut peers)) => {
+					gum::trace!(target: LOG_TARGET, ?peers, "Received new peers.");
+					idle_peers.append(&mut peers);
+				},
+				Some(Err(_)) => {
+					gum::debug!(target: LOG_TARGET, "Failed fetching more peers.");
+					return
+				},
+			}
+		} else {
+			let next = match future::select(pending.next(), &mut more_peers).await {
+				Either::Left((response, _)) => Either::Left(response),
+				Either::Right((peers, _)) => Either::Right(peers),
+			};
+
+			match next {
+				Either::Left(Some((peer, response))) => {
+					let done = handle_response(
+						peer,
+						response,
+						candidate_hash,
+						&mut sources,
+						&mut chunks,
+						&mut idle_peers,
+						&mut tried_peers,
+						&mut legacy_peers,
+						&mut sender,
+						&metrics,
+					)
+					.await;
+					if let Some((from_peers, response)) = done {
+						if let Err(err) = sender
+							.feed(RequesterMessage::Finished {
+								relay_parent,
+								candidate_hash,
+								from_peers,
+								response,
+								bad_peers: tried_peers,
+							})
+							.await
+						{
+							gum::warn!(
+								target: LOG_TARGET,
+								?err,
+								"Sending task response failed: This should not happen."
+							);
+						}
+
+						// We are done now.
+						return
+					}
+					continue
+				},
+				Either::Left(None) => continue,
+				Either::Right(Ok(mut peers)) => {
+					gum::trace!(target: LOG_TARGET, ?peers, "Received new peers.");
+					idle_peers.append(&mut peers);
+				},
+				Either::Right(Err(_)) => {
+					gum::debug!(target: LOG_TARGET, "Failed fetching more peers.");
+					return
+				},
+			}
+		}
+
+		// The last request for peers got answered, ask for the next ones:
+		more_peers = match request_more_peers(relay_parent, candidate_hash, &mut sender).await {
+			Ok(rx) => rx,
+			Err(()) => return,
+		};
+	}
+}
+
+/// The response to a request sent by [`fetch_chunked`].
+enum Response {
+	/// Response to a request for the chunk with the given index.
+	Chunk(u32, OutgoingResult<v2::StatementFetchingResponse>),
+	/// Response to a request for the whole statement.
+	Whole(OutgoingResult<v1::StatementFetchingResponse>),
+}
+
+/// The peers chunks are accepted from.
+enum Sources {
+	/// Chunks are fetched from all peers.
+	Any,
+	/// All chunks are fetched from a single peer, picked once one is idle.
+	Single(Option<PeerId>),
+}
+
+impl Sources {
+	/// Stop fetching chunks from `peer`, dropping all chunks it sent if it was the only source.
+	fn forget(&mut self, peer: PeerId, chunks: &mut Chunks) {
+		if let Sources::Single(source) = self {
+			if *source == Some(peer) {
+				*source = None;
+				chunks.clear();
+			}
+		}
+	}
+}
+
+/// Handle a response received by [`fetch_chunked`].
+///
+/// Returns the peers the statement was fetched from, along with the statement, once it is
+/// complete.
+async fn handle_response(
+	peer: PeerId,
+	response: Response,
+	candidate_hash: CandidateHash,
+	sources: &mut Sources,
+	chunks: &mut Chunks,
+	idle_peers: &mut Vec<PeerId>,
+	tried_peers: &mut Vec<PeerId>,
+	legacy_peers: &mut HashSet<PeerId>,
+	sender: &mut mpsc::Sender<RequesterMessage>,
+	metrics: &Metrics,
+) -> Option<(Vec<PeerId>, CommittedCandidateReceipt)> {
+	let (chunk_index, result) = match response {
+		Response::Whole(Ok(v1::StatementFetchingResponse::Statement(statement))) => {
+			if statement.hash() == candidate_hash {
+				metrics.on_received_response(true);
+				return Some((vec![peer], statement))
+			}
+
+			metrics.on_received_response(false);
+			metrics.on_unexpected_statement_large();
+			// We want to get rid of this peer:
+			report_peer(sender, peer, COST_WRONG_HASH).await;
+			return None
+		},
+		Response::Whole(Err(err)) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				?err,
+				"Receiving response failed with error - trying next peer."
+			);
+
+			metrics.on_received_response(false);
+			metrics.on_unexpected_statement_large();
+			tried_peers.push(peer);
+			return None
+		},
+		Response::Chunk(chunk_index, result) => (chunk_index, result),
+	};
+
+	chunks.on_response(chunk_index);
+
+	let (total_chunks, data) = match result {
+		Ok(v2::StatementFetchingResponse::Chunk { total_chunks, data }) => (total_chunks, data),
+		Err(err) if err.is_unsupported_protocol() => {
+			gum::trace!(
+				target: LOG_TARGET,
+				?peer,
+				"Peer does not support fetching chunks - requesting the whole statement."
+			);
+			legacy_peers.insert(peer);
+			sources.forget(peer, chunks);
+			idle_peers.push(peer);
+			return None
+		},
+		Err(err) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				?err,
+				?chunk_index,
+				"Receiving chunk failed with error - trying next peer."
+			);
+
+			metrics.on_received_response(false);
+			sources.forget(peer, chunks);
+			tried_peers.push(peer);
+			return None
+		},
+	};
+
+	match sources {
+		// Requested before we stopped accepting chunks from everyone.
+		Sources::Single(Some(source)) if *source != peer => {
+			idle_peers.push(peer);
+			return None
+		},
+		Sources::Single(source) => *source = Some(peer),
+		Sources::Any => {},
+	}
+
+	match chunks.insert(peer, chunk_index, total_chunks, data) {
+		Ok(()) => {
+			metrics.on_received_response(true);
+			idle_peers.push(peer);
+		},
+		Err(ChunkError::Inconsistent) if matches!(sources, Sources::Any) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				?candidate_hash,
+				"Received chunks don't fit together - fetching all chunks from a single peer."
+			);
+
+			metrics.on_received_response(false);
+			*sources = Sources::Single(None);
+			chunks.clear();
+			idle_peers.push(peer);
+		},
+		Err(_) => {
+			metrics.on_received_response(false);
+			sources.forget(peer, chunks);
+			report_peer(sender, peer, COST_INVALID_CHUNK).await;
+		},
+	}
+
+	if !chunks.is_complete() {
+		return None
+	}
+
+	let (data, from_peers) = chunks.take();
+	let statement = CommittedCandidateReceipt::decode_all(&mut data.as_slice()).ok();
+	if let Some(statement) = statement.filter(|s| s.hash() == candidate_hash) {
+		return Some((from_peers, statement))
+	}
+
+	metrics.on_unexpected_statement_large();
+	match from_peers.as_slice() {
+		[peer] => {
+			// We want to get rid of this peer:
+			idle_peers.retain(|p| p != peer);
+			sources.forget(*peer, chunks);
+			report_peer(sender, *peer, COST_WRONG_HASH).await;
+		},
+		_ => {
+			gum::debug!(
+				target: LOG_TARGET,
+				?candidate_hash,
+				?from_peers,
+				"Reassembled statement has wrong hash - fetching all chunks from a single peer."
+			);
+			*sources = Sources::Single(None);
+		},
+	}
+	None
+}
+
+/// Why a chunk was rejected.
+enum ChunkError {
+	/// The chunk is malformed, no matter what other peers sent.
+	Invalid,
+	/// The chunk does not fit to the chunks received before.
+	Inconsistent,
+}
+
+/// The chunks of a large statement received so far.
+#[derive(Default)]
+struct Chunks {
+	/// The number of chunks, as claimed by the first accepted chunk.
+	total: Option<u32>,
+	/// The chunks received so far, along with the peer which sent them.
+	received: BTreeMap<u32, (PeerId, Vec<u8>)>,
+	/// The number of requests in flight, per chunk.
+	in_flight: HashMap<u32, usize>,
+}
+
+impl Chunks {
+	/// The chunk to request next, if any.
+	///
+	/// Chunks with fewer requests in flight are preferred. As long as the number of chunks is
+	/// unknown, only the first chunk gets requested.
+	fn next(&self) -> Option<u32> {
+		let in_flight = |index| self.in_flight.get(&index).copied().unwrap_or(0);
+		let missing =
+			(0..self.total.unwrap_or(1)).filter(|index| !self.received.contains_key(index));
+		missing
+			.map(|index| (in_flight(index), index))
+			.filter(|(requests, _)| *requests < MAX_REQUESTS_PER_CHUNK)
+			.min()
+			.map(|(_, index)| index)
+	}
+
+	fn on_request_sent(&mut self, index: u32) {
+		*self.in_flight.entry(index).or_default() += 1;
+	}
+
+	fn on_response(&mut self, index: u32) {
+		if let Entry::Occupied(mut entry) = self.in_flight.entry(index) {
+			*entry.get_mut() -= 1;
+			if *entry.get() == 0 {
+				entry.remove();
+			}
+		}
+	}
+
+	/// Add the chunk with the given index, received from `peer`.
+	fn insert(
+		&mut self,
+		peer: PeerId,
+		index: u32,
+		total_chunks: u32,
+		data: Vec<u8>,
+	) -> Result<(), ChunkError> {
+		if total_chunks == 0 || total_chunks > MAX_STATEMENT_CHUNKS {
+			return Err(ChunkError::Invalid)
+		}
+		// We only ask for chunks beyond the first one once we know how many there are.
+		if self.total.map_or(false, |total| total != total_chunks) || index >= total_chunks {
+			return Err(ChunkError::Inconsistent)
+		}
+
+		let chunk_size = STATEMENT_CHUNK_SIZE as usize;
+		let size_ok = if index + 1 == total_chunks {
+			(1..=chunk_size).contains(&data.len())
+		} else {
+			data.len() == chunk_size
+		};
+		if !size_ok {
+			return Err(ChunkError::Invalid)
+		}
+
+		self.total = Some(total_chunks);
+		self.received.entry(index).or_insert((peer, data));
+		Ok(())
+	}
+
+	fn is_complete(&self) -> bool {
+		self.total.map_or(false, |total| self.received.len() == total as usize)
+	}
+
+	/// Take all received chunks, returning the reassembled data and the peers which sent them.
+	fn take(&mut self) -> (Vec<u8>, Vec<PeerId>) {
+		self.total = None;
+		let mut data = Vec::new();
+		let mut peers = Vec::new();
+		for (peer, chunk) in std::mem::take(&mut self.received).into_values() {
+			data.extend(chunk);
+			if !peers.contains(&peer) {
+				peers.push(peer);
+			}
+		}
+		(data, peers)
+	}
+
+	/// Forget about all received chunks.
+	fn clear(&mut self) {
+		self.total = None;
+		self.received.clear();
+	}
+}
+
+/// Ask the subsystem for more peers, which it sends once there are any.
+async fn request_more_peers(
+	relay_parent: Hash,
+	candidate_hash: CandidateHash,
+	sender: &mut mpsc::Sender<RequesterMessage>,
+) -> Result<oneshot::Receiver<Vec<PeerId>>, ()> {
+	let (tx, rx) = oneshot::channel();
+
+	if let Err(err) = sender
+		.send(RequesterMessage::GetMorePeers { relay_parent, candidate_hash, tx })
+		.await
+	{
+		gum::debug!(
+			target: LOG_TARGET,
+			?err,
+			"Failed sending background task message, subsystem probably moved on."
+		);
+		return Err(())
+	}
+	Ok(rx)
+}
+
+async fn report_peer(
+	sender: &mut mpsc::Sender<RequesterMessage>,
+	peer: PeerId,
+	rep: UnifiedReputationChange,
+) {
+	if let Err(err) = sender.feed(RequesterMessage::ReportPeer(peer, rep)).await {
+		gum::warn!(
+			target: LOG_TARGET,
+			?err,
+			"Sending reputation change failed: This should not happen."
+		);
+	}
+}
+
 /// Try getting new peers from subsystem.
 ///
 /// If there are non, we will return after a timeout with `None`.
@@ -14,6 +14,8 @@
 
 //! Large statement responding background task logic.
 
+use std::num::NonZeroUsize;
+
 use futures::{
 	channel::{mpsc, oneshot},
 	stream::FuturesUnordered,
@@ -21,11 +23,14 @@ use futures::{
 };
 
 use fatality::Nested;
+use lru::LruCache;
+use parity_scale_codec::Encode;
 use polkadot_node_network_protocol::{
 	request_response::{
 		incoming::OutgoingResponse,
 		v1::{StatementFetchingRequest, StatementFetchingResponse},
-		IncomingRequestReceiver, MAX_PARALLEL_STATEMENT_REQUESTS,
+		v2, QuotaReceiver, MAX_PARALLEL_STATEMENT_CHUNK_REQUESTS, MAX_PARALLEL_STATEMENT_REQUESTS,
+		STATEMENT_CHUNK_SIZE,
 	},
 	PeerId, UnifiedReputationChange as Rep,
 };
@@ -35,6 +40,15 @@ use crate::LOG_TARGET;
 
 const COST_INVALID_REQUEST: Rep = Rep::CostMajor("Peer sent unparsable request");
 
+/// The number of candidates whose encoded receipt is kept for answering chunk requests.
+///
+/// Each chunk of a large statement is requested separately, usually by several peers at once,
+/// so this saves encoding the receipt for every single chunk.
+const ENCODED_RECEIPTS_CACHE_SIZE: NonZeroUsize = match NonZeroUsize::new(8) {
+	Some(size) => size,
+	None => panic!("Encoded receipts cache size must be non-zero"),
+};
+
 /// Messages coming from a background task.
 pub enum ResponderMessage {
 	/// Get an update of available peers to try for fetching a given statement.
@@ -52,7 +66,7 @@ pub enum ResponderMessage {
 /// `CommittedCandidateReceipt` from peers, whether this can be used to re-assemble one ore
 /// many `SignedFullStatement`s needs to be verified by the caller.
 pub async fn respond(
-	mut receiver: IncomingRequestReceiver<StatementFetchingRequest>,
+	mut receiver: QuotaReceiver<StatementFetchingRequest>,
 	mut sender: mpsc::Sender<ResponderMessage>,
 ) {
 	let mut pending_out = FuturesUnordered::new();
@@ -82,7 +96,7 @@ pub async fn respond(
 				return
 			},
 			Ok(Err(jfyi)) => {
-				gum::debug!(target: LOG_TARGET, error = ?jfyi, "Decoding request failed");
+				gum::debug!(target: LOG_TARGET, error = ?jfyi, "Receiving request failed");
 				continue
 			},
 		};
@@ -119,3 +133,99 @@ pub async fn respond(
 		}
 	}
 }
+
+/// A task answering requests for chunks of large statements.
+///
+/// Like [`respond`], but the `CommittedCandidateReceipt` is split into chunks of
+/// `STATEMENT_CHUNK_SIZE` bytes and only the requested one is sent. Requests for chunks out of
+/// range are answered with an error and cost the requester reputation.
+pub async fn respond_chunks(
+	mut receiver: QuotaReceiver<v2::StatementFetchingRequest>,
+	mut sender: mpsc::Sender<ResponderMessage>,
+) {
+	let mut pending_out = FuturesUnordered::new();
+	let mut encoded_receipts = LruCache::new(ENCODED_RECEIPTS_CACHE_SIZE);
+	loop {
+		// Chunks are small, so we can afford serving more of them in parallel, see `respond` for
+		// why we limit parallelism at all.
+		if pending_out.len() >= MAX_PARALLEL_STATEMENT_CHUNK_REQUESTS as usize {
+			// Wait for one to finish:
+			pending_out.next().await;
+		}
+
+		let req = match receiver.recv(|| vec![COST_INVALID_REQUEST]).await.into_nested() {
+			Ok(Ok(v)) => v,
+			Err(fatal) => {
+				gum::debug!(target: LOG_TARGET, error = ?fatal, "Shutting down chunk responder");
+				return
+			},
+			Ok(Err(jfyi)) => {
+				gum::debug!(target: LOG_TARGET, error = ?jfyi, "Receiving request failed");
+				continue
+			},
+		};
+
+		let (tx, rx) = oneshot::channel();
+		if let Err(err) = sender
+			.feed(ResponderMessage::GetData {
+				requesting_peer: req.peer,
+				relay_parent: req.payload.relay_parent,
+				candidate_hash: req.payload.candidate_hash,
+				tx,
+			})
+			.await
+		{
+			gum::debug!(target: LOG_TARGET, ?err, "Shutting down chunk responder");
+			return
+		}
+		let chunk_index = req.payload.chunk_index;
+		let mut reputation_changes = Vec::new();
+		let response = match rx.await {
+			Err(err) => {
+				gum::debug!(target: LOG_TARGET, ?err, "Requested data not found.");
+				Err(())
+			},
+			Ok(v) => {
+				// The subsystem checked the peer is allowed to request the candidate, we only
+				// reuse the encoding.
+				let encoded =
+					encoded_receipts.get_or_insert(req.payload.candidate_hash, || v.encode());
+				statement_chunk(encoded, chunk_index).ok_or_else(|| {
+					gum::debug!(
+						target: LOG_TARGET,
+						peer = ?req.peer,
+						?chunk_index,
+						"Requested chunk out of range."
+					);
+					reputation_changes.push(COST_INVALID_REQUEST);
+				})
+			},
+		};
+		let (pending_sent_tx, pending_sent_rx) = oneshot::channel();
+		let response = OutgoingResponse {
+			result: response,
+			reputation_changes,
+			sent_feedback: Some(pending_sent_tx),
+		};
+		pending_out.push(pending_sent_rx);
+		if let Err(_) = req.send_outgoing_response(response) {
+			gum::debug!(target: LOG_TARGET, "Sending response failed");
+		}
+	}
+}
+
+/// Cut the chunk with index `chunk_index` out of an encoded `CommittedCandidateReceipt`.
+pub fn statement_chunk(encoded: &[u8], chunk_index: u32) -> Option<v2::StatementFetchingResponse> {
+	let chunk_size = STATEMENT_CHUNK_SIZE as usize;
+	let total_chunks = (encoded.len() + chunk_size - 1) / chunk_size;
+	if chunk_index as usize >= total_chunks {
+		return None
+	}
+	let start = chunk_index as usize * chunk_size;
+	let end = encoded.len().min(start + chunk_size);
+
+	Some(v2::StatementFetchingResponse::Chunk {
+		total_chunks: total_chunks as u32,
+		data: encoded[start..end].to_vec(),
+	})
+}
@@ -16,15 +16,18 @@
 
 use super::{metrics::Metrics, *};
 use assert_matches::assert_matches;
+use async_trait::async_trait;
 use futures::executor;
 use futures_timer::Delay;
 use parity_scale_codec::{Decode, Encode};
 use polkadot_node_network_protocol::{
+	authority_discovery::AuthorityDiscovery,
 	grid_topology::{SessionGridTopology, TopologyPeerInfo},
 	peer_set::ValidationVersion,
 	request_response::{
 		v1::{StatementFetchingRequest, StatementFetchingResponse},
-		IncomingRequest, Recipient, ReqProtocolNames, Requests,
+		v2, IncomingRequest, IncomingRequestReceiver, QuotaReceiver, Recipient, ReqProtocolNames,
+		Requests, ResponseSender, STATEMENT_CHUNK_SIZE,
 	},
 	view, ObservedRole,
 };
@@ -48,12 +51,39 @@ use sp_application_crypto::{sr25519::Pair, AppCrypto, Pair as TraitPair};
 use sp_authority_discovery::AuthorityPair;
 use sp_keyring::Sr25519Keyring;
 use sp_keystore::{Keystore, KeystorePtr};
-use std::{iter::FromIterator as _, sync::Arc, time::Duration};
+use std::{collections::HashSet, iter::FromIterator as _, sync::Arc, time::Duration};
 use util::reputation::add_reputation;
 
 // Some deterministic genesis hash for protocol names
 const GENESIS_HASH: Hash = Hash::repeat_byte(0xff);
 
+/// Authority discovery considering every peer an authority.
+#[derive(Debug)]
+struct AllAuthorities;
+
+#[async_trait]
+impl AuthorityDiscovery for AllAuthorities {
+	async fn get_addresses_by_authority_id(
+		&mut self,
+		_: AuthorityDiscoveryId,
+	) -> Option<HashSet<sc_network::Multiaddr>> {
+		None
+	}
+
+	async fn get_authority_ids_by_peer_id(
+		&mut self,
+		_: PeerId,
+	) -> Option<HashSet<AuthorityDiscoveryId>> {
+		Some(HashSet::from([Sr25519Keyring::Alice.public().into()]))
+	}
+}
+
+fn quota_receiver(
+	receiver: IncomingRequestReceiver<StatementFetchingRequest>,
+) -> QuotaReceiver<StatementFetchingRequest> {
+	QuotaReceiver::with_default_quota(receiver, Box::new(AllAuthorities))
+}
+
 #[test]
 fn active_head_accepts_only_2_seconded_per_validator() {
 	let validators = vec![
@@ -738,7 +768,8 @@ fn receiving_from_one_sends_to_another_and_to_candidate_backing() {
 	let bg = async move {
 		let s = StatementDistributionSubsystem {
 			keystore: Arc::new(LocalKeystore::in_memory()),
-			req_receiver: Some(statement_req_receiver),
+			req_receiver: Some(quota_receiver(statement_req_receiver)),
+			chunk_req_receiver: None,
 			metrics: Default::default(),
 			rng: AlwaysZeroRng,
 			reputation: ReputationAggregator::new(|_| true),
@@ -942,7 +973,8 @@ fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing(
 	let bg = async move {
 		let s = StatementDistributionSubsystem {
 			keystore: make_ferdie_keystore(),
-			req_receiver: Some(statement_req_receiver),
+			req_receiver: Some(quota_receiver(statement_req_receiver)),
+			chunk_req_receiver: None,
 			metrics: Default::default(),
 			rng: AlwaysZeroRng,
 			reputation: ReputationAggregator::new(|_| true),
@@ -1438,7 +1470,8 @@ fn delay_reputation_changes() {
 	let bg = async move {
 		let s = StatementDistributionSubsystem {
 			keystore: make_ferdie_keystore(),
-			req_receiver: Some(statement_req_receiver),
+			req_receiver: Some(quota_receiver(statement_req_receiver)),
+			chunk_req_receiver: None,
 			metrics: Default::default(),
 			rng: AlwaysZeroRng,
 			reputation: ReputationAggregator::new(|_| false),
@@ -1890,7 +1923,8 @@ fn share_prioritizes_backing_group() {
 	let bg = async move {
 		let s = StatementDistributionSubsystem {
 			keystore: make_ferdie_keystore(),
-			req_receiver: Some(statement_req_receiver),
+			req_receiver: Some(quota_receiver(statement_req_receiver)),
+			chunk_req_receiver: None,
 			metrics: Default::default(),
 			rng: AlwaysZeroRng,
 			reputation: ReputationAggregator::new(|_| true),
@@ -2184,7 +2218,8 @@ fn peer_cant_flood_with_large_statements() {
 	let bg = async move {
 		let s = StatementDistributionSubsystem {
 			keystore: make_ferdie_keystore(),
-			req_receiver: Some(statement_req_receiver),
+			req_receiver: Some(quota_receiver(statement_req_receiver)),
+			chunk_req_receiver: None,
 			metrics: Default::default(),
 			rng: AlwaysZeroRng,
 			reputation: ReputationAggregator::new(|_| true),
@@ -2389,7 +2424,8 @@ fn handle_multiple_seconded_statements() {
 	let virtual_overseer_fut = async move {
 		let s = StatementDistributionSubsystem {
 			keystore: Arc::new(LocalKeystore::in_memory()),
-			req_receiver: Some(statement_req_receiver),
+			req_receiver: Some(quota_receiver(statement_req_receiver)),
+			chunk_req_receiver: None,
 			metrics: Default::default(),
 			rng: AlwaysZeroRng,
 			reputation: ReputationAggregator::new(|_| true),
@@ -2749,6 +2785,304 @@ fn handle_multiple_seconded_statements() {
 	executor::block_on(future::join(test_fut, virtual_overseer_fut));
 }
 
+#[test]
+fn large_statement_chunks_reassemble() {
+	let candidate = large_candidate(Hash::repeat_byte(1), 3 * STATEMENT_CHUNK_SIZE as usize);
+	let encoded = candidate.encode();
+
+	let mut reassembled = Vec::new();
+	for chunk_index in 0..4 {
+		let v2::StatementFetchingResponse::Chunk { total_chunks, data } =
+			responder::statement_chunk(&encoded, chunk_index).unwrap();
+		assert_eq!(total_chunks, 4);
+		reassembled.extend(data);
+	}
+	assert_eq!(reassembled, encoded);
+	assert!(responder::statement_chunk(&encoded, 4).is_none());
+}
+
+#[test]
+fn chunk_requests_out_of_range_are_punished() {
+	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
+	let (receiver, mut req_cfg) =
+		IncomingRequest::<v2::StatementFetchingRequest>::get_config_receiver(&req_protocol_names);
+	let receiver = QuotaReceiver::with_default_quota(receiver, Box::new(AllAuthorities));
+	let (res_sender, mut res_receiver) = mpsc::channel(1);
+
+	let candidate = large_candidate(Hash::repeat_byte(1), 3 * STATEMENT_CHUNK_SIZE as usize);
+	let peer = PeerId::random();
+
+	let test_fut = async move {
+		for (chunk_index, in_range) in [(3, true), (4, false)] {
+			let (pending_response, response_rx) = oneshot::channel();
+			let req = sc_network::config::IncomingRequest {
+				peer,
+				payload: v2::StatementFetchingRequest {
+					relay_parent: candidate.descriptor.relay_parent,
+					candidate_hash: candidate.hash(),
+					chunk_index,
+				}
+				.encode(),
+				pending_response,
+			};
+			req_cfg.inbound_queue.as_mut().unwrap().send(req).await.unwrap();
+
+			assert_matches!(
+				res_receiver.next().await,
+				Some(ResponderMessage::GetData { requesting_peer, tx, .. }) => {
+					assert_eq!(requesting_peer, peer);
+					tx.send(candidate.clone()).unwrap();
+				}
+			);
+
+			let response = response_rx.await.unwrap();
+			if in_range {
+				let chunk = responder::statement_chunk(&candidate.encode(), chunk_index).unwrap();
+				assert_eq!(response.result, Ok(chunk.encode()));
+				assert!(response.reputation_changes.is_empty());
+			} else {
+				assert_eq!(response.result, Err(()));
+				assert_matches!(&response.reputation_changes[..], [change] if change.value < 0);
+			}
+		}
+	};
+
+	executor::block_on(future::join(responder::respond_chunks(receiver, res_sender), test_fut));
+}
+
+#[test]
+fn large_statement_is_fetched_in_chunks_from_multiple_peers() {
+	let hash_a = Hash::repeat_byte(1);
+	let candidate = large_candidate(hash_a, 3 * STATEMENT_CHUNK_SIZE as usize);
+	let candidate_hash = candidate.hash();
+	let encoded = candidate.encode();
+	let (peer_a, peer_b, peer_c) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+	let (sender, mut rx) = mpsc::channel(1);
+	let fetch_fut = fetch_chunked(hash_a, candidate_hash, vec![peer_a], sender, Metrics::default());
+
+	let test_fut = async move {
+		let more_peers = recv_get_more_peers(&mut rx).await;
+		let (peer, chunk_index, a_0) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 0));
+
+		// New peers are put to work right away. As long as the number of chunks is unknown, they
+		// can only help with the first one.
+		more_peers.send(vec![peer_b, peer_c]).unwrap();
+		let _more_peers = recv_get_more_peers(&mut rx).await;
+		let (peer, chunk_index, c_0) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_c, 0));
+
+		// Once it is known, the remaining chunks are spread over all peers:
+		a_0.send(chunk_response(&encoded, 0)).unwrap();
+		let (peer, chunk_index, a_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 1));
+		let (peer, chunk_index, b_2) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_b, 2));
+		c_0.send(chunk_response(&encoded, 0)).unwrap();
+		let (peer, chunk_index, c_3) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_c, 3));
+
+		a_1.send(chunk_response(&encoded, 1)).unwrap();
+		b_2.send(chunk_response(&encoded, 2)).unwrap();
+		c_3.send(chunk_response(&encoded, 3)).unwrap();
+
+		let (_requests, finished) = recv_past_requests(&mut rx).await;
+		assert_matches!(
+			finished,
+			RequesterMessage::Finished { relay_parent, candidate_hash: h, from_peers, response, bad_peers } => {
+				assert_eq!(relay_parent, hash_a);
+				assert_eq!(h, candidate_hash);
+				assert_eq!(from_peers, vec![peer_a, peer_b, peer_c]);
+				assert_eq!(response, candidate);
+				assert!(bad_peers.is_empty());
+			}
+		);
+	};
+
+	futures::pin_mut!(test_fut);
+	futures::pin_mut!(fetch_fut);
+
+	executor::block_on(future::join(test_fut, fetch_fut));
+}
+
+#[test]
+fn failed_chunks_are_fetched_from_other_peers() {
+	let hash_a = Hash::repeat_byte(1);
+	let candidate = large_candidate(hash_a, STATEMENT_CHUNK_SIZE as usize);
+	let candidate_hash = candidate.hash();
+	let encoded = candidate.encode();
+	let (peer_a, peer_b, peer_legacy) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+	let (sender, mut rx) = mpsc::channel(1);
+	let fetch_fut = fetch_chunked(hash_a, candidate_hash, vec![peer_a], sender, Metrics::default());
+
+	let test_fut = async move {
+		let more_peers = recv_get_more_peers(&mut rx).await;
+		let (peer, chunk_index, a_0) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 0));
+		a_0.send(chunk_response(&encoded, 0)).unwrap();
+
+		let (peer, chunk_index, a_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 1));
+		a_1.send(Err(sc_network::RequestFailure::Network(
+			sc_network::OutboundFailure::ConnectionClosed,
+		)))
+		.unwrap();
+
+		// All peers failed us, the chunk is requested from new peers and the failed ones again.
+		// Peers not supporting chunks are asked for the whole statement.
+		more_peers.send(vec![peer_legacy, peer_b]).unwrap();
+		let _more_peers = recv_get_more_peers(&mut rx).await;
+		let (peer, chunk_index, b_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_b, 1));
+		let (peer, chunk_index, legacy_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_legacy, 1));
+
+		legacy_1
+			.send(Err(sc_network::RequestFailure::Network(
+				sc_network::OutboundFailure::UnsupportedProtocols,
+			)))
+			.unwrap();
+		let _legacy_whole = assert_matches!(
+			rx.next().await,
+			Some(RequesterMessage::SendRequest(Requests::StatementFetchingV1(outgoing))) => {
+				assert_eq!(outgoing.peer, Recipient::Peer(peer_legacy));
+				assert_eq!(outgoing.payload.candidate_hash, candidate_hash);
+				outgoing
+			}
+		);
+		// Peer A gets a second chance:
+		let (peer, chunk_index, _a_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 1));
+
+		b_1.send(chunk_response(&encoded, 1)).unwrap();
+		assert_matches!(
+			rx.next().await,
+			Some(RequesterMessage::Finished { from_peers, response, bad_peers, .. }) => {
+				assert_eq!(from_peers, vec![peer_a, peer_b]);
+				assert_eq!(response, candidate);
+				assert!(bad_peers.is_empty());
+			}
+		);
+	};
+
+	futures::pin_mut!(test_fut);
+	futures::pin_mut!(fetch_fut);
+
+	executor::block_on(future::join(test_fut, fetch_fut));
+}
+
+#[test]
+fn chunks_with_wrong_hash_are_refetched_from_single_peer() {
+	let hash_a = Hash::repeat_byte(1);
+	let candidate = large_candidate(hash_a, STATEMENT_CHUNK_SIZE as usize);
+	let candidate_hash = candidate.hash();
+	let encoded = candidate.encode();
+	let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+
+	let (sender, mut rx) = mpsc::channel(1);
+	let fetch_fut = fetch_chunked(hash_a, candidate_hash, vec![peer_a], sender, Metrics::default());
+
+	let test_fut = async move {
+		let more_peers = recv_get_more_peers(&mut rx).await;
+		let (peer, chunk_index, a_0) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 0));
+		more_peers.send(vec![peer_b]).unwrap();
+		let _more_peers = recv_get_more_peers(&mut rx).await;
+		let (peer, chunk_index, b_0) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_b, 0));
+
+		// Peer B sends garbage, which can only be noticed once the statement is reassembled:
+		let garbage = v2::StatementFetchingResponse::Chunk {
+			total_chunks: 2,
+			data: vec![0xff; STATEMENT_CHUNK_SIZE as usize],
+		};
+		b_0.send(Ok(garbage.encode())).unwrap();
+		let (peer, chunk_index, b_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_b, 1));
+		a_0.send(chunk_response(&encoded, 0)).unwrap();
+		let (peer, chunk_index, a_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 1));
+		a_1.send(chunk_response(&encoded, 1)).unwrap();
+
+		// We can't tell which peer sent garbage, so all chunks are fetched from a single peer:
+		let (peer, chunk_index, a_0) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 0));
+		a_0.send(chunk_response(&encoded, 0)).unwrap();
+		let (peer, chunk_index, a_1) = recv_chunk_request(&mut rx).await;
+		assert_eq!((peer, chunk_index), (peer_a, 1));
+
+		// Chunks of other peers are ignored from now on:
+		b_1.send(chunk_response(&encoded, 1)).unwrap();
+		a_1.send(chunk_response(&encoded, 1)).unwrap();
+
+		assert_matches!(
+			rx.next().await,
+			Some(RequesterMessage::Finished { from_peers, response, bad_peers, .. }) => {
+				assert_eq!(from_peers, vec![peer_a]);
+				assert_eq!(response, candidate);
+				assert!(bad_peers.is_empty());
+			}
+		);
+		assert!(rx.next().await.is_none());
+	};
+
+	futures::pin_mut!(test_fut);
+	futures::pin_mut!(fetch_fut);
+
+	executor::block_on(future::join(test_fut, fetch_fut));
+}
+
+/// A candidate which is large enough to be fetched in chunks.
+fn large_candidate(relay_parent: Hash, code_size: usize) -> CommittedCandidateReceipt {
+	let mut c = dummy_committed_candidate_receipt(dummy_hash());
+	c.descriptor.relay_parent = relay_parent;
+	c.descriptor.para_id = 1.into();
+	c.commitments.new_validation_code = Some(ValidationCode(vec![7; code_size]));
+	c
+}
+
+fn chunk_response(encoded: &[u8], chunk_index: u32) -> Result<Vec<u8>, sc_network::RequestFailure> {
+	Ok(responder::statement_chunk(encoded, chunk_index).unwrap().encode())
+}
+
+async fn recv_get_more_peers(
+	rx: &mut mpsc::Receiver<RequesterMessage>,
+) -> oneshot::Sender<Vec<PeerId>> {
+	assert_matches!(
+		rx.next().await,
+		Some(RequesterMessage::GetMorePeers { tx, .. }) => tx
+	)
+}
+
+async fn recv_chunk_request(
+	rx: &mut mpsc::Receiver<RequesterMessage>,
+) -> (PeerId, u32, ResponseSender) {
+	assert_matches!(
+		rx.next().await,
+		Some(RequesterMessage::SendRequest(Requests::StatementFetchingV2(outgoing))) => {
+			let peer = assert_matches!(outgoing.peer, Recipient::Peer(peer) => peer);
+			(peer, outgoing.payload.chunk_index, outgoing.pending_response)
+		}
+	)
+}
+
+/// Receive requests up to the next other message.
+///
+/// The requests are returned as well, so they don't fail by being dropped.
+async fn recv_past_requests(
+	rx: &mut mpsc::Receiver<RequesterMessage>,
+) -> (Vec<Requests>, RequesterMessage) {
+	let mut requests = Vec::new();
+	loop {
+		match rx.next().await.unwrap() {
+			RequesterMessage::SendRequest(request) => requests.push(request),
+			message => return (requests, message),
+		}
+	}
+}
+
 fn make_session_info(validators: Vec<Pair>, groups: Vec<Vec<u32>>) -> SessionInfo {
 	let validator_groups: IndexedVec<GroupIndex, Vec<ValidatorIndex>> = groups
 		.iter()
@@ -795,11 +795,11 @@ fn test_chain_api_msg() -> ChainApiMessage {
 }
 
 fn test_collator_generation_msg() -> CollationGenerationMessage {
-	CollationGenerationMessage::Initialize(CollationGenerationConfig {
+	CollationGenerationMessage::Initialize(vec![CollationGenerationConfig {
 		key: CollatorPair::generate().0,
 		collator: Box::new(|_, _| TestCollator.boxed()),
 		para_id: Default::default(),
-	})
+	}])
 }
 struct TestCollator;
 
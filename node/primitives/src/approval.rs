@@ -18,6 +18,7 @@
 
 pub use sp_consensus_babe::{Randomness, Slot, VrfOutput, VrfProof, VrfSignature, VrfTranscript};
 
+use bitvec::{order::Lsb0, vec::BitVec};
 use parity_scale_codec::{Decode, Encode};
 use polkadot_primitives::{
 	BlockNumber, CandidateHash, CandidateIndex, CoreIndex, Hash, Header, SessionIndex,
@@ -37,12 +38,18 @@ pub const RELAY_VRF_STORY_CONTEXT: &[u8] = b"A&V RC-VRF";
 /// A static context used for all relay-vrf-modulo VRFs.
 pub const RELAY_VRF_MODULO_CONTEXT: &[u8] = b"A&V MOD";
 
+/// A static context used for all compact relay-vrf-modulo VRFs.
+pub const RELAY_VRF_MODULO_COMPACT_CONTEXT: &[u8] = b"A&V MOD v2";
+
 /// A static context used for all relay-vrf-modulo VRFs.
 pub const RELAY_VRF_DELAY_CONTEXT: &[u8] = b"A&V DELAY";
 
 /// A static context used for transcripts indicating assigned availability core.
 pub const ASSIGNED_CORE_CONTEXT: &[u8] = b"A&V ASSIGNED";
 
+/// A static context used for transcripts indicating multiple assigned availability cores.
+pub const ASSIGNED_CORES_CONTEXT: &[u8] = b"A&V ASSIGNED v2";
+
 /// A static context associated with producing randomness for a core.
 pub const CORE_RANDOMNESS_CONTEXT: &[u8] = b"A&V CORE";
 
@@ -54,6 +61,122 @@ pub const TRANCHE_RANDOMNESS_CONTEXT: &[u8] = b"A&V TRANCHE";
 #[derive(Debug, Clone, Encode, Decode, PartialEq)]
 pub struct RelayVRFStory(pub [u8; 32]);
 
+/// Conversion between the items covered by a [`Bitfield`] and bit positions.
+pub trait BitIndex: Sized {
+	/// The bit position of this item.
+	fn bit_index(&self) -> usize;
+	/// The item at the given bit position.
+	fn from_bit_index(index: usize) -> Self;
+}
+
+impl BitIndex for CandidateIndex {
+	fn bit_index(&self) -> usize {
+		*self as usize
+	}
+
+	fn from_bit_index(index: usize) -> Self {
+		index as CandidateIndex
+	}
+}
+
+impl BitIndex for CoreIndex {
+	fn bit_index(&self) -> usize {
+		self.0 as usize
+	}
+
+	fn from_bit_index(index: usize) -> Self {
+		CoreIndex(index as u32)
+	}
+}
+
+/// Errors constructing a [`Bitfield`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum BitfieldError {
+	/// A bitfield has to cover at least one item.
+	#[error("Bitfield covers no items")]
+	NoBitsSet,
+}
+
+/// A set of candidates or cores a single message refers to, one bit per index.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq, Hash)]
+pub struct Bitfield<T>(BitVec<u8, Lsb0>, #[codec(skip)] std::marker::PhantomData<T>);
+
+/// The candidates of a relay chain block a message refers to, by [`CandidateIndex`].
+pub type CandidateBitfield = Bitfield<CandidateIndex>;
+
+/// The availability cores an assignment refers to, by [`CoreIndex`].
+pub type CoreBitfield = Bitfield<CoreIndex>;
+
+impl<T: BitIndex> Bitfield<T> {
+	/// Create a bitfield with the bits of all given items set.
+	pub fn new(items: impl IntoIterator<Item = T>) -> Result<Self, BitfieldError> {
+		let mut bits = BitVec::new();
+		for index in items.into_iter().map(|item| item.bit_index()) {
+			if bits.len() <= index {
+				bits.resize(index + 1, false);
+			}
+			bits.set(index, true);
+		}
+
+		if bits.not_any() {
+			return Err(BitfieldError::NoBitsSet)
+		}
+
+		Ok(Bitfield(bits, Default::default()))
+	}
+
+	/// Whether the bit of `item` is set.
+	pub fn bit_at(&self, item: &T) -> bool {
+		self.0.get(item.bit_index()).map_or(false, |bit| *bit)
+	}
+
+	/// The number of set bits.
+	pub fn count_ones(&self) -> usize {
+		self.0.count_ones()
+	}
+
+	/// The item with the lowest set bit, if any.
+	pub fn first_one(&self) -> Option<T> {
+		self.0.first_one().map(T::from_bit_index)
+	}
+
+	/// All items with their bit set, in ascending order.
+	pub fn iter_ones(&self) -> impl Iterator<Item = T> + '_ {
+		self.0.iter_ones().map(T::from_bit_index)
+	}
+
+	/// The number of bits, set or not.
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	/// Whether the bitfield has no bits at all.
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Whether both bitfields have the same bits set, regardless of trailing unset bits.
+	pub fn same_ones(&self, other: &Self) -> bool {
+		self.0.iter_ones().eq(other.0.iter_ones())
+	}
+}
+
+impl<T: BitIndex> From<T> for Bitfield<T> {
+	fn from(item: T) -> Self {
+		let mut bits = BitVec::repeat(false, item.bit_index() + 1);
+		bits.set(item.bit_index(), true);
+		Bitfield(bits, Default::default())
+	}
+}
+
+impl<T: BitIndex> TryFrom<Vec<T>> for Bitfield<T> {
+	type Error = BitfieldError;
+
+	fn try_from(items: Vec<T>) -> Result<Self, Self::Error> {
+		Self::new(items)
+	}
+}
+
 /// Different kinds of input data or criteria that can prove a validator's assignment
 /// to check a particular parachain.
 #[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
@@ -62,6 +185,7 @@ pub enum AssignmentCertKind {
 	/// candidate was included combined with a sample number.
 	///
 	/// The context used to produce bytes is [`RELAY_VRF_MODULO_CONTEXT`]
+	#[codec(index = 0)]
 	RelayVRFModulo {
 		/// The sample number used in this cert.
 		sample: u32,
@@ -70,10 +194,21 @@ pub enum AssignmentCertKind {
 	/// candidate was included combined with the index of a particular core.
 	///
 	/// The context is [`RELAY_VRF_DELAY_CONTEXT`]
+	#[codec(index = 1)]
 	RelayVRFDelay {
 		/// The core index chosen in this cert.
 		core_index: CoreIndex,
 	},
+	/// An assignment story based on the VRF that authorized the relay-chain block where the
+	/// candidates were included. All samples are derived from a single VRF output, so one cert
+	/// claims every core the samples hit.
+	///
+	/// The context is [`RELAY_VRF_MODULO_COMPACT_CONTEXT`]
+	#[codec(index = 2)]
+	RelayVRFModuloCompact {
+		/// The cores claimed by this cert.
+		core_bitfield: CoreBitfield,
+	},
 }
 
 /// A certification of assignment.
@@ -113,6 +248,57 @@ pub struct IndirectSignedApprovalVote {
 	pub signature: ValidatorSignature,
 }
 
+/// A signed approval vote on one or more candidates included by the same block.
+///
+/// A vote on a single candidate signs [`ApprovalVote`](polkadot_primitives::ApprovalVote) and is
+/// interchangeable with an [`IndirectSignedApprovalVote`]. Votes on several candidates sign
+/// [`ApprovalVoteMultipleCandidates`](polkadot_primitives::ApprovalVoteMultipleCandidates) over
+/// the candidate hashes in candidate index order.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+pub struct IndirectSignedApprovalVoteV2 {
+	/// A block hash where the candidates appear.
+	pub block_hash: Hash,
+	/// The indices of the candidates in the list of candidates fully included as-of the block.
+	pub candidate_indices: CandidateBitfield,
+	/// The validator index.
+	pub validator: ValidatorIndex,
+	/// The signature by the validator.
+	pub signature: ValidatorSignature,
+}
+
+impl From<IndirectSignedApprovalVote> for IndirectSignedApprovalVoteV2 {
+	fn from(vote: IndirectSignedApprovalVote) -> Self {
+		IndirectSignedApprovalVoteV2 {
+			block_hash: vote.block_hash,
+			candidate_indices: vote.candidate_index.into(),
+			validator: vote.validator,
+			signature: vote.signature,
+		}
+	}
+}
+
+/// A vote on more than one candidate can't be expressed as [`IndirectSignedApprovalVote`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("The approval vote covers more than one candidate")]
+pub struct MultipleCandidates;
+
+impl TryFrom<IndirectSignedApprovalVoteV2> for IndirectSignedApprovalVote {
+	type Error = MultipleCandidates;
+
+	fn try_from(vote: IndirectSignedApprovalVoteV2) -> Result<Self, Self::Error> {
+		if vote.candidate_indices.count_ones() != 1 {
+			return Err(MultipleCandidates)
+		}
+
+		Ok(IndirectSignedApprovalVote {
+			block_hash: vote.block_hash,
+			candidate_index: vote.candidate_indices.first_one().ok_or(MultipleCandidates)?,
+			validator: vote.validator,
+			signature: vote.signature,
+		})
+	}
+}
+
 /// Metadata about a block which is now live in the approval protocol.
 #[derive(Debug)]
 pub struct BlockApprovalMeta {
@@ -38,16 +38,23 @@ mod tests;
 use {
 	grandpa::{self, FinalityProofProvider as GrandpaFinalityProofProvider},
 	gum::info,
+	polkadot_availability_distribution::ChunkFetchingBudget,
+	polkadot_collator_protocol::CollatorScoringConfig,
 	polkadot_node_core_approval_voting::{
 		self as approval_voting_subsystem, Config as ApprovalVotingConfig,
 	},
 	polkadot_node_core_av_store::Config as AvailabilityConfig,
 	polkadot_node_core_av_store::Error as AvailabilityError,
-	polkadot_node_core_candidate_validation::Config as CandidateValidationConfig,
+	polkadot_node_core_av_store::PruningPolicy as AvailabilityPruningPolicy,
+	polkadot_node_core_candidate_validation::{
+		Config as CandidateValidationConfig, PvfSandboxConfig,
+	},
 	polkadot_node_core_chain_selection::{
 		self as chain_selection_subsystem, Config as ChainSelectionConfig,
 	},
-	polkadot_node_core_dispute_coordinator::Config as DisputeCoordinatorConfig,
+	polkadot_node_core_dispute_coordinator::{
+		Config as DisputeCoordinatorConfig, ParticipationConfig as DisputeParticipationConfig,
+	},
 	polkadot_node_network_protocol::{
 		peer_set::PeerSetProtocolNames, request_response::ReqProtocolNames,
 	},
@@ -60,6 +67,7 @@ use polkadot_node_subsystem_util::database::Database;
 
 #[cfg(feature = "full-node")]
 pub use {
+	polkadot_collator_protocol::CollatorKeys,
 	polkadot_overseer::{Handle, Overseer, OverseerConnector, OverseerHandle},
 	polkadot_primitives::runtime_api::ParachainHost,
 	relay_chain_selection::SelectRelayChain,
@@ -229,6 +237,10 @@ pub enum Error {
 	#[error("Creating a custom database is required for validators")]
 	DatabasePathRequired,
 
+	#[cfg(feature = "full-node")]
+	#[error("Custom databases cannot be opened read-only")]
+	CustomDatabaseReadOnly,
+
 	#[cfg(feature = "full-node")]
 	#[error("Expected at least one of polkadot, kusama, westend or rococo runtime feature")]
 	NoRuntime,
@@ -344,6 +356,30 @@ pub fn open_database(db_source: &DatabaseSource) -> Result<Arc<dyn Database>, Er
 	Ok(parachains_db)
 }
 
+/// Open the existing parachains database of the given source in read-only mode, e.g. to inspect it
+/// while a node is running.
+#[cfg(feature = "full-node")]
+pub fn open_database_read_only(db_source: &DatabaseSource) -> Result<Arc<dyn Database>, Error> {
+	let parachains_db = match db_source {
+		DatabaseSource::RocksDb { path, .. } =>
+			parachains_db::open_rocksdb_read_only(path.clone())?,
+		DatabaseSource::ParityDb { path, .. } => parachains_db::open_paritydb_read_only(
+			path.parent().ok_or(Error::DatabasePathRequired)?.into(),
+		)?,
+		DatabaseSource::Auto { paritydb_path, rocksdb_path, .. } => {
+			if paritydb_path.is_dir() && paritydb_path.exists() {
+				parachains_db::open_paritydb_read_only(
+					paritydb_path.parent().ok_or(Error::DatabasePathRequired)?.into(),
+				)?
+			} else {
+				parachains_db::open_rocksdb_read_only(rocksdb_path.clone())?
+			}
+		},
+		DatabaseSource::Custom { .. } => return Err(Error::CustomDatabaseReadOnly),
+	};
+	Ok(parachains_db)
+}
+
 /// Initialize the `Jeager` collector. The destination must listen
 /// on the given address and port for `UDP` packets.
 #[cfg(any(test, feature = "full-node"))]
@@ -445,6 +481,7 @@ fn new_partial<ChainSelection>(
 	config: &mut Configuration,
 	Basics { task_manager, backend, client, keystore_container, telemetry }: Basics,
 	select_chain: ChainSelection,
+	overseer_handle: Option<Handle>,
 ) -> Result<
 	service::PartialComponents<
 		FullClient,
@@ -579,6 +616,9 @@ where
 					beefy_best_block_stream: beefy_rpc_links.from_voter_best_beefy_stream.clone(),
 					subscription_executor,
 				},
+				subsystems: polkadot_rpc::SubsystemsDeps {
+					overseer_handle: overseer_handle.clone(),
+				},
 			};
 
 			polkadot_rpc::create_full(deps, backend.clone()).map_err(Into::into)
@@ -612,8 +652,8 @@ pub struct NewFull {
 #[cfg(feature = "full-node")]
 #[derive(Clone)]
 pub enum IsCollator {
-	/// This node is a collator.
-	Yes(CollatorPair),
+	/// This node is a collator, declaring itself with the given keys.
+	Yes(CollatorKeys),
 	/// This node is not a collator.
 	No,
 }
@@ -621,9 +661,8 @@ pub enum IsCollator {
 #[cfg(feature = "full-node")]
 impl std::fmt::Debug for IsCollator {
 	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-		use sp_core::Pair;
 		match self {
-			IsCollator::Yes(pair) => write!(fmt, "Yes({})", pair.public()),
+			IsCollator::Yes(keys) => write!(fmt, "Yes({:?})", keys),
 			IsCollator::No => write!(fmt, "No"),
 		}
 	}
@@ -637,6 +676,41 @@ impl IsCollator {
 	}
 }
 
+/// Parameters of a full node, passed to [`new_full`] and [`build_full`].
+#[cfg(feature = "full-node")]
+pub struct NewFullParams<OverseerGenerator: OverseerGen> {
+	pub is_collator: IsCollator,
+	pub grandpa_pause: Option<(u32, u32)>,
+	pub enable_beefy: bool,
+	pub jaeger_agent: Option<std::net::SocketAddr>,
+	pub telemetry_worker_handle: Option<TelemetryWorkerHandle>,
+	/// The binary spawned for PVF workers, defaults to the current executable.
+	pub program_path: Option<std::path::PathBuf>,
+	pub subsystems: SubsystemsParams,
+	pub overseer_enable_anyways: bool,
+	pub overseer_gen: OverseerGenerator,
+	pub overseer_message_channel_capacity_override: Option<usize>,
+	pub malus_finality_delay: Option<u32>,
+	pub hwbench: Option<sc_sysinfo::HwBench>,
+}
+
+/// Node-side options of the parachain subsystems.
+#[cfg(feature = "full-node")]
+#[derive(Debug, Clone, Default)]
+pub struct SubsystemsParams {
+	/// Directory to write dumps of failed candidate validations to, if any.
+	pub validation_dump_dir: Option<std::path::PathBuf>,
+	pub availability_pruning_policy: AvailabilityPruningPolicy,
+	pub enable_v2_assignments: bool,
+	pub chunk_fetching_budget: ChunkFetchingBudget,
+	pub dispute_participation: DisputeParticipationConfig,
+	pub systematic_chunks_recovery: bool,
+	/// Restrict the PVF workers with the seccomp syscall allowlist.
+	pub pvf_enable_seccomp: bool,
+	/// Spawn the PVF workers in a sandbox with limits enforced by cgroups under the given root.
+	pub pvf_sandbox: Option<PvfSandboxConfig>,
+}
+
 pub const AVAILABILITY_CONFIG: AvailabilityConfig = AvailabilityConfig {
 	col_data: parachains_db::REAL_COLUMNS.col_availability_data,
 	col_meta: parachains_db::REAL_COLUMNS.col_availability_meta,
@@ -651,23 +725,33 @@ pub const AVAILABILITY_CONFIG: AvailabilityConfig = AvailabilityConfig {
 /// regardless of the role the node has. The relay chain selection (longest or disputes-aware) is
 /// still determined based on the role of the node. Likewise for authority discovery.
 #[cfg(feature = "full-node")]
-pub fn new_full<OverseerGenerator>(
+pub fn new_full<OverseerGenerator: OverseerGen>(
 	mut config: Configuration,
-	is_collator: IsCollator,
-	grandpa_pause: Option<(u32, u32)>,
-	enable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
-	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
-	program_path: Option<std::path::PathBuf>,
-	overseer_enable_anyways: bool,
-	overseer_gen: OverseerGenerator,
-	overseer_message_channel_capacity_override: Option<usize>,
-	_malus_finality_delay: Option<u32>,
-	hwbench: Option<sc_sysinfo::HwBench>,
-) -> Result<NewFull, Error>
-where
-	OverseerGenerator: OverseerGen,
-{
+	NewFullParams {
+		is_collator,
+		grandpa_pause,
+		enable_beefy,
+		jaeger_agent,
+		telemetry_worker_handle,
+		program_path,
+		subsystems:
+			SubsystemsParams {
+				validation_dump_dir,
+				availability_pruning_policy,
+				enable_v2_assignments,
+				chunk_fetching_budget,
+				dispute_participation,
+				systematic_chunks_recovery,
+				pvf_enable_seccomp,
+				pvf_sandbox,
+			},
+		overseer_enable_anyways,
+		overseer_gen,
+		overseer_message_channel_capacity_override,
+		malus_finality_delay: _malus_finality_delay,
+		hwbench,
+	}: NewFullParams<OverseerGenerator>,
+) -> Result<NewFull, Error> {
 	use polkadot_node_network_protocol::request_response::IncomingRequest;
 	use sc_network_common::sync::warp::WarpSyncParams;
 
@@ -738,7 +822,12 @@ where
 		import_queue,
 		transaction_pool,
 		other: (rpc_extensions_builder, import_setup, rpc_setup, slot_duration, mut telemetry),
-	} = new_partial::<SelectRelayChain<_>>(&mut config, basics, select_chain)?;
+	} = new_partial::<SelectRelayChain<_>>(
+		&mut config,
+		basics,
+		select_chain,
+		Some(overseer_handle.clone()),
+	)?;
 
 	let shared_voter_state = rpc_setup;
 	let auth_disc_publish_non_global_ips = config.network.allow_non_globals_in_dht;
@@ -796,6 +885,9 @@ where
 	net_config.add_request_response_protocol(cfg);
 	let (statement_req_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	net_config.add_request_response_protocol(cfg);
+	let (statement_chunk_req_receiver, cfg) =
+		IncomingRequest::get_config_receiver(&req_protocol_names);
+	net_config.add_request_response_protocol(cfg);
 	let (dispute_req_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	net_config.add_request_response_protocol(cfg);
 
@@ -848,6 +940,7 @@ where
 	let approval_voting_config = ApprovalVotingConfig {
 		col_approval_data: parachains_db::REAL_COLUMNS.col_approval_data,
 		slot_duration_millis: slot_duration.as_millis() as u64,
+		enable_v2_assignments,
 	};
 
 	let candidate_validation_config = CandidateValidationConfig {
@@ -860,6 +953,9 @@ where
 			None => std::env::current_exe()?,
 			Some(p) => p,
 		},
+		validation_dump_dir,
+		enable_seccomp: pvf_enable_seccomp,
+		sandbox: pvf_sandbox,
 	};
 
 	let chain_selection_config = ChainSelectionConfig {
@@ -870,8 +966,12 @@ where
 
 	let dispute_coordinator_config = DisputeCoordinatorConfig {
 		col_dispute_data: parachains_db::REAL_COLUMNS.col_dispute_coordinator_data,
+		participation: dispute_participation,
 	};
 
+	let collator_scoring_config =
+		CollatorScoringConfig { col_data: parachains_db::REAL_COLUMNS.col_collator_scores_data };
+
 	let rpc_handlers = service::spawn_tasks(service::SpawnTasksParams {
 		config,
 		backend: backend.clone(),
@@ -968,15 +1068,20 @@ where
 					collation_req_receiver,
 					available_data_req_receiver,
 					statement_req_receiver,
+					statement_chunk_req_receiver,
 					dispute_req_receiver,
 					registry: prometheus_registry.as_ref(),
 					spawner,
 					is_collator,
 					approval_voting_config,
 					availability_config: AVAILABILITY_CONFIG,
+					availability_pruning_policy,
+					chunk_fetching_budget,
+					systematic_chunks_recovery,
 					candidate_validation_config,
 					chain_selection_config,
 					dispute_coordinator_config,
+					collator_scoring_config,
 					pvf_checker_enabled,
 					overseer_message_channel_capacity_override,
 					req_protocol_names,
@@ -1221,7 +1326,7 @@ macro_rules! chain_ops {
 		let chain_selection = LongestChain::new(basics.backend.clone());
 
 		let service::PartialComponents { client, backend, import_queue, task_manager, .. } =
-			new_partial::<LongestChain<_, Block>>(&mut config, basics, chain_selection)?;
+			new_partial::<LongestChain<_, Block>>(&mut config, basics, chain_selection, None)?;
 		Ok((client, backend, import_queue, task_manager))
 	}};
 }
@@ -1265,40 +1370,21 @@ pub fn new_chain_ops(
 /// regardless of the role the node has. The relay chain selection (longest or disputes-aware) is
 /// still determined based on the role of the node. Likewise for authority discovery.
 #[cfg(feature = "full-node")]
-pub fn build_full(
+pub fn build_full<OverseerGenerator: OverseerGen>(
 	config: Configuration,
-	is_collator: IsCollator,
-	grandpa_pause: Option<(u32, u32)>,
-	enable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
-	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
-	overseer_enable_anyways: bool,
-	overseer_gen: impl OverseerGen,
-	overseer_message_channel_override: Option<usize>,
-	malus_finality_delay: Option<u32>,
-	hwbench: Option<sc_sysinfo::HwBench>,
+	mut params: NewFullParams<OverseerGenerator>,
 ) -> Result<NewFull, Error> {
 	let is_polkadot = config.chain_spec.is_polkadot();
 
-	new_full(
-		config,
-		is_collator,
-		grandpa_pause,
-		enable_beefy,
-		jaeger_agent,
-		telemetry_worker_handle,
-		None,
-		overseer_enable_anyways,
-		overseer_gen,
-		overseer_message_channel_override.map(move |capacity| {
+	params.overseer_message_channel_capacity_override =
+		params.overseer_message_channel_capacity_override.map(move |capacity| {
 			if is_polkadot {
 				gum::warn!("Channel capacity should _never_ be tampered with on polkadot!");
 			}
 			capacity
-		}),
-		malus_finality_delay,
-		hwbench,
-	)
+		});
+
+	new_full(config, params)
 }
 
 /// Reverts the node state down to at most the last finalized block.
@@ -1360,6 +1446,8 @@ fn revert_approval_voting(db: Arc<dyn Database>, hash: Hash) -> sp_blockchain::R
 	let config = approval_voting_subsystem::Config {
 		col_approval_data: parachains_db::REAL_COLUMNS.col_approval_data,
 		slot_duration_millis: Default::default(),
+		// No assignments are issued while reverting.
+		enable_v2_assignments: false,
 	};
 
 	let approval_voting = approval_voting_subsystem::ApprovalVotingSubsystem::with_config(
@@ -18,15 +18,20 @@ use super::{AuthorityDiscoveryApi, Block, Error, Hash, IsCollator, Registry};
 use sp_core::traits::SpawnNamed;
 
 use lru::LruCache;
-use polkadot_availability_distribution::IncomingRequestReceivers;
+use polkadot_availability_distribution::{ChunkFetchingBudget, IncomingRequestReceivers};
 use polkadot_node_core_approval_voting::Config as ApprovalVotingConfig;
-use polkadot_node_core_av_store::Config as AvailabilityConfig;
+use polkadot_node_core_av_store::{
+	Config as AvailabilityConfig, PruningPolicy as AvailabilityPruningPolicy,
+};
 use polkadot_node_core_candidate_validation::Config as CandidateValidationConfig;
 use polkadot_node_core_chain_selection::Config as ChainSelectionConfig;
 use polkadot_node_core_dispute_coordinator::Config as DisputeCoordinatorConfig;
 use polkadot_node_network_protocol::{
 	peer_set::PeerSetProtocolNames,
-	request_response::{v1 as request_v1, IncomingRequestReceiver, ReqProtocolNames},
+	request_response::{
+		v1 as request_v1, v2 as request_v2, IncomingRequestReceiver, QuotaReceiver,
+		ReqProtocolNames,
+	},
 };
 #[cfg(any(feature = "malus", test))]
 pub use polkadot_overseer::{
@@ -52,12 +57,14 @@ pub use polkadot_approval_distribution::ApprovalDistribution as ApprovalDistribu
 pub use polkadot_availability_bitfield_distribution::BitfieldDistribution as BitfieldDistributionSubsystem;
 pub use polkadot_availability_distribution::AvailabilityDistributionSubsystem;
 pub use polkadot_availability_recovery::AvailabilityRecoverySubsystem;
-pub use polkadot_collator_protocol::{CollatorProtocolSubsystem, ProtocolSide};
+pub use polkadot_collator_protocol::{
+	CollatorKeys, CollatorProtocolSubsystem, CollatorScoringConfig, ProtocolSide,
+};
 pub use polkadot_dispute_distribution::DisputeDistributionSubsystem;
 pub use polkadot_gossip_support::GossipSupport as GossipSupportSubsystem;
 pub use polkadot_network_bridge::{
 	Metrics as NetworkBridgeMetrics, NetworkBridgeRx as NetworkBridgeRxSubsystem,
-	NetworkBridgeTx as NetworkBridgeTxSubsystem,
+	NetworkBridgeTx as NetworkBridgeTxSubsystem, Shared as NetworkBridgeShared,
 };
 pub use polkadot_node_collation_generation::CollationGenerationSubsystem;
 pub use polkadot_node_core_approval_voting::ApprovalVotingSubsystem;
@@ -100,6 +107,7 @@ where
 	pub available_data_req_receiver:
 		IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
 	pub statement_req_receiver: IncomingRequestReceiver<request_v1::StatementFetchingRequest>,
+	pub statement_chunk_req_receiver: IncomingRequestReceiver<request_v2::StatementFetchingRequest>,
 	pub dispute_req_receiver: IncomingRequestReceiver<request_v1::DisputeRequest>,
 	/// Prometheus registry, commonly used for production systems, less so for test.
 	pub registry: Option<&'a Registry>,
@@ -111,12 +119,20 @@ where
 	pub approval_voting_config: ApprovalVotingConfig,
 	/// Configuration for the availability store subsystem.
 	pub availability_config: AvailabilityConfig,
+	/// The pruning policy of the availability store subsystem.
+	pub availability_pruning_policy: AvailabilityPruningPolicy,
+	/// Bandwidth budget for fetching chunks in the availability distribution subsystem.
+	pub chunk_fetching_budget: ChunkFetchingBudget,
+	/// Recover large PoVs from the systematic chunks first, instead of from any chunks.
+	pub systematic_chunks_recovery: bool,
 	/// Configuration for the candidate validation subsystem.
 	pub candidate_validation_config: CandidateValidationConfig,
 	/// Configuration for the chain selection subsystem.
 	pub chain_selection_config: ChainSelectionConfig,
 	/// Configuration for the dispute coordinator subsystem.
 	pub dispute_coordinator_config: DisputeCoordinatorConfig,
+	/// Configuration of the collator scoring on the validator side of the collator protocol.
+	pub collator_scoring_config: CollatorScoringConfig,
 	/// Enable PVF pre-checking
 	pub pvf_checker_enabled: bool,
 	/// Overseer channel capacity override.
@@ -142,15 +158,20 @@ pub fn prepared_overseer_builder<Spawner, RuntimeClient>(
 		collation_req_receiver,
 		available_data_req_receiver,
 		statement_req_receiver,
+		statement_chunk_req_receiver,
 		dispute_req_receiver,
 		registry,
 		spawner,
 		is_collator,
 		approval_voting_config,
 		availability_config,
+		availability_pruning_policy,
+		chunk_fetching_budget,
+		systematic_chunks_recovery,
 		candidate_validation_config,
 		chain_selection_config,
 		dispute_coordinator_config,
+		collator_scoring_config,
 		pvf_checker_enabled,
 		overseer_message_channel_capacity_override,
 		req_protocol_names,
@@ -203,6 +224,52 @@ where
 	let spawner = SpawnGlue(spawner);
 
 	let network_bridge_metrics: NetworkBridgeMetrics = Metrics::register(registry)?;
+	let network_bridge_shared = NetworkBridgeShared::default();
+
+	// Incoming requests don't pass through the network bridge, so the receivers account for
+	// their traffic.
+	let request_traffic = network_bridge_shared.request_traffic_observer();
+	let pov_req_receiver = pov_req_receiver.with_traffic_observer(request_traffic.clone());
+	let chunk_req_receiver = chunk_req_receiver.with_traffic_observer(request_traffic.clone());
+	let collation_req_receiver =
+		collation_req_receiver.with_traffic_observer(request_traffic.clone());
+	let available_data_req_receiver =
+		available_data_req_receiver.with_traffic_observer(request_traffic.clone());
+	let statement_req_receiver =
+		statement_req_receiver.with_traffic_observer(request_traffic.clone());
+	let statement_chunk_req_receiver =
+		statement_chunk_req_receiver.with_traffic_observer(request_traffic.clone());
+	let dispute_req_receiver = dispute_req_receiver.with_traffic_observer(request_traffic);
+
+	// Requests of peers exceeding their quota are answered with an error by the receivers.
+	let pov_req_receiver = QuotaReceiver::with_default_quota(
+		pov_req_receiver,
+		Box::new(authority_discovery_service.clone()),
+	);
+	let chunk_req_receiver = QuotaReceiver::with_default_quota(
+		chunk_req_receiver,
+		Box::new(authority_discovery_service.clone()),
+	);
+	let collation_req_receiver = QuotaReceiver::with_default_quota(
+		collation_req_receiver,
+		Box::new(authority_discovery_service.clone()),
+	);
+	let available_data_req_receiver = QuotaReceiver::with_default_quota(
+		available_data_req_receiver,
+		Box::new(authority_discovery_service.clone()),
+	);
+	let statement_req_receiver = QuotaReceiver::with_default_quota(
+		statement_req_receiver,
+		Box::new(authority_discovery_service.clone()),
+	);
+	let statement_chunk_req_receiver = QuotaReceiver::with_default_quota(
+		statement_chunk_req_receiver,
+		Box::new(authority_discovery_service.clone()),
+	);
+	let dispute_req_receiver = QuotaReceiver::with_default_quota(
+		dispute_req_receiver,
+		Box::new(authority_discovery_service.clone()),
+	);
 
 	let builder = Overseer::builder()
 		.network_bridge_tx(NetworkBridgeTxSubsystem::new(
@@ -211,6 +278,7 @@ where
 			network_bridge_metrics.clone(),
 			req_protocol_names,
 			peerset_protocol_names.clone(),
+			network_bridge_shared.clone(),
 		))
 		.network_bridge_rx(NetworkBridgeRxSubsystem::new(
 			network_service.clone(),
@@ -218,19 +286,29 @@ where
 			Box::new(sync_service.clone()),
 			network_bridge_metrics,
 			peerset_protocol_names,
+			network_bridge_shared,
 		))
 		.availability_distribution(AvailabilityDistributionSubsystem::new(
 			keystore.clone(),
 			IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver },
+			chunk_fetching_budget,
 			Metrics::register(registry)?,
 		))
-		.availability_recovery(AvailabilityRecoverySubsystem::with_chunks_if_pov_large(
-			available_data_req_receiver,
-			Metrics::register(registry)?,
-		))
+		.availability_recovery(if systematic_chunks_recovery {
+			AvailabilityRecoverySubsystem::with_systematic_chunks_if_pov_large(
+				available_data_req_receiver,
+				Metrics::register(registry)?,
+			)
+		} else {
+			AvailabilityRecoverySubsystem::with_chunks_if_pov_large(
+				available_data_req_receiver,
+				Metrics::register(registry)?,
+			)
+		})
 		.availability_store(AvailabilityStoreSubsystem::new(
 			parachains_db.clone(),
 			availability_config,
+			availability_pruning_policy,
 			Box::new(sync_service.clone()),
 			Metrics::register(registry)?,
 		))
@@ -257,9 +335,9 @@ where
 		.collation_generation(CollationGenerationSubsystem::new(Metrics::register(registry)?))
 		.collator_protocol({
 			let side = match is_collator {
-				IsCollator::Yes(collator_pair) => ProtocolSide::Collator(
+				IsCollator::Yes(collator_keys) => ProtocolSide::Collator(
 					network_service.local_peer_id(),
-					collator_pair,
+					collator_keys,
 					collation_req_receiver,
 					Metrics::register(registry)?,
 				),
@@ -267,6 +345,8 @@ where
 					keystore: keystore.clone(),
 					eviction_policy: Default::default(),
 					metrics: Metrics::register(registry)?,
+					db: parachains_db.clone(),
+					scoring_config: collator_scoring_config,
 				},
 			};
 			CollatorProtocolSubsystem::new(side)
@@ -280,6 +360,7 @@ where
 		.statement_distribution(StatementDistributionSubsystem::new(
 			keystore.clone(),
 			statement_req_receiver,
+			statement_chunk_req_receiver,
 			Metrics::register(registry)?,
 			rand::rngs::StdRng::from_entropy(),
 		))
@@ -43,14 +43,29 @@ pub(crate) mod columns {
 
 	pub mod v3 {
 		pub const NUM_COLUMNS: u32 = 5;
+
+		pub const ORDERED_COL: &[u32] = &[
+			super::v4::COL_AVAILABILITY_META,
+			super::v4::COL_CHAIN_SELECTION_DATA,
+			super::v4::COL_DISPUTE_COORDINATOR_DATA,
+		];
+	}
+
+	pub mod v4 {
+		pub const NUM_COLUMNS: u32 = 6;
 		pub const COL_AVAILABILITY_DATA: u32 = 0;
 		pub const COL_AVAILABILITY_META: u32 = 1;
 		pub const COL_APPROVAL_DATA: u32 = 2;
 		pub const COL_CHAIN_SELECTION_DATA: u32 = 3;
 		pub const COL_DISPUTE_COORDINATOR_DATA: u32 = 4;
-
-		pub const ORDERED_COL: &[u32] =
-			&[COL_AVAILABILITY_META, COL_CHAIN_SELECTION_DATA, COL_DISPUTE_COORDINATOR_DATA];
+		pub const COL_COLLATOR_SCORES_DATA: u32 = 5;
+
+		pub const ORDERED_COL: &[u32] = &[
+			COL_AVAILABILITY_META,
+			COL_CHAIN_SELECTION_DATA,
+			COL_DISPUTE_COORDINATOR_DATA,
+			COL_COLLATOR_SCORES_DATA,
+		];
 	}
 }
 
@@ -68,19 +83,22 @@ pub struct ColumnsConfig {
 	pub col_chain_selection_data: u32,
 	/// The column used by dispute coordinator for data.
 	pub col_dispute_coordinator_data: u32,
+	/// The column used by the collator protocol for collator scores.
+	pub col_collator_scores_data: u32,
 }
 
 /// The real columns used by the parachains DB.
 #[cfg(any(test, feature = "full-node"))]
 pub const REAL_COLUMNS: ColumnsConfig = ColumnsConfig {
-	col_availability_data: columns::v3::COL_AVAILABILITY_DATA,
-	col_availability_meta: columns::v3::COL_AVAILABILITY_META,
-	col_approval_data: columns::v3::COL_APPROVAL_DATA,
-	col_chain_selection_data: columns::v3::COL_CHAIN_SELECTION_DATA,
-	col_dispute_coordinator_data: columns::v3::COL_DISPUTE_COORDINATOR_DATA,
+	col_availability_data: columns::v4::COL_AVAILABILITY_DATA,
+	col_availability_meta: columns::v4::COL_AVAILABILITY_META,
+	col_approval_data: columns::v4::COL_APPROVAL_DATA,
+	col_chain_selection_data: columns::v4::COL_CHAIN_SELECTION_DATA,
+	col_dispute_coordinator_data: columns::v4::COL_DISPUTE_COORDINATOR_DATA,
+	col_collator_scores_data: columns::v4::COL_COLLATOR_SCORES_DATA,
 };
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 pub(crate) enum DatabaseKind {
 	ParityDB,
 	RocksDB,
@@ -125,28 +143,28 @@ pub fn open_creating_rocksdb(
 
 	let path = root.join("parachains").join("db");
 
-	let mut db_config = DatabaseConfig::with_columns(columns::v3::NUM_COLUMNS);
+	let mut db_config = DatabaseConfig::with_columns(columns::v4::NUM_COLUMNS);
 
 	let _ = db_config
 		.memory_budget
-		.insert(columns::v3::COL_AVAILABILITY_DATA, cache_sizes.availability_data);
+		.insert(columns::v4::COL_AVAILABILITY_DATA, cache_sizes.availability_data);
 	let _ = db_config
 		.memory_budget
-		.insert(columns::v3::COL_AVAILABILITY_META, cache_sizes.availability_meta);
+		.insert(columns::v4::COL_AVAILABILITY_META, cache_sizes.availability_meta);
 	let _ = db_config
 		.memory_budget
-		.insert(columns::v3::COL_APPROVAL_DATA, cache_sizes.approval_data);
+		.insert(columns::v4::COL_APPROVAL_DATA, cache_sizes.approval_data);
 
 	let path_str = path
 		.to_str()
 		.ok_or_else(|| other_io_error(format!("Bad database path: {:?}", path)))?;
 
 	std::fs::create_dir_all(&path_str)?;
-	upgrade::try_upgrade_db(&path, DatabaseKind::RocksDB)?;
+	upgrade::try_upgrade_db(&path, DatabaseKind::RocksDB, upgrade::CURRENT_VERSION)?;
 	let db = Database::open(&db_config, &path_str)?;
 	let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(
 		db,
-		columns::v3::ORDERED_COL,
+		columns::v4::ORDERED_COL,
 	);
 
 	Ok(Arc::new(db))
@@ -164,14 +182,70 @@ pub fn open_creating_paritydb(
 		.ok_or_else(|| other_io_error(format!("Bad database path: {:?}", path)))?;
 
 	std::fs::create_dir_all(&path_str)?;
-	upgrade::try_upgrade_db(&path, DatabaseKind::ParityDB)?;
+	upgrade::try_upgrade_db(&path, DatabaseKind::ParityDB, upgrade::CURRENT_VERSION)?;
+
+	let db = parity_db::Db::open_or_create(&upgrade::paritydb_version_4_config(&path))
+		.map_err(|err| io::Error::new(io::ErrorKind::Other, format!("{:?}", err)))?;
+
+	let db = polkadot_node_subsystem_util::database::paritydb_impl::DbAdapter::new(
+		db,
+		columns::v4::ORDERED_COL,
+	);
+	Ok(Arc::new(db))
+}
+
+/// Open the existing database on disk in read-only mode.
+///
+/// The database is opened as a secondary instance, so this works while a node is using it. Changes
+/// made by the node after opening are not visible.
+#[cfg(feature = "full-node")]
+pub fn open_rocksdb_read_only(root: PathBuf) -> io::Result<Arc<dyn Database>> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let path = root.join("parachains").join("db");
+
+	let mut db_config = DatabaseConfig::with_columns(columns::v4::NUM_COLUMNS);
+	// the secondary instance keeps its own info logs in this directory.
+	db_config.secondary =
+		Some(std::env::temp_dir().join(format!("polkadot-parachains-db-{}", std::process::id())));
 
-	let db = parity_db::Db::open_or_create(&upgrade::paritydb_version_3_config(&path))
+	let path_str = path
+		.to_str()
+		.ok_or_else(|| other_io_error(format!("Bad database path: {:?}", path)))?;
+
+	check_exists(&path)?;
+	upgrade::check_current_version(&path)?;
+	let db = Database::open(&db_config, &path_str)?;
+	let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(
+		db,
+		columns::v4::ORDERED_COL,
+	);
+
+	Ok(Arc::new(db))
+}
+
+/// Open the existing parity db database in read-only mode.
+#[cfg(feature = "full-node")]
+pub fn open_paritydb_read_only(root: PathBuf) -> io::Result<Arc<dyn Database>> {
+	let path = root.join("parachains");
+
+	check_exists(&path)?;
+	upgrade::check_current_version(&path)?;
+	let db = parity_db::Db::open_read_only(&upgrade::paritydb_version_4_config(&path))
 		.map_err(|err| io::Error::new(io::ErrorKind::Other, format!("{:?}", err)))?;
 
 	let db = polkadot_node_subsystem_util::database::paritydb_impl::DbAdapter::new(
 		db,
-		columns::v3::ORDERED_COL,
+		columns::v4::ORDERED_COL,
 	);
 	Ok(Arc::new(db))
 }
+
+#[cfg(feature = "full-node")]
+fn check_exists(path: &std::path::Path) -> io::Result<()> {
+	if path.is_dir() {
+		Ok(())
+	} else {
+		Err(io::Error::new(io::ErrorKind::NotFound, format!("No database at {:?}", path)))
+	}
+}
@@ -28,7 +28,7 @@ type Version = u32;
 const VERSION_FILE_NAME: &'static str = "parachain_db_version";
 
 /// Current db version.
-const CURRENT_VERSION: Version = 3;
+pub(crate) const CURRENT_VERSION: Version = 4;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -38,6 +38,8 @@ pub enum Error {
 	CorruptedVersionFile,
 	#[error("Parachains DB has a future version (expected {current:?}, found {got:?})")]
 	FutureVersion { current: Version, got: Version },
+	#[error("Parachains DB has an outdated version (expected {current:?}, found {got:?})")]
+	OutdatedVersion { current: Version, got: Option<Version> },
 }
 
 impl From<Error> for io::Error {
@@ -49,32 +51,59 @@ impl From<Error> for io::Error {
 	}
 }
 
-/// Try upgrading parachain's database to the current version.
-pub(crate) fn try_upgrade_db(db_path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+/// Try upgrading parachain's database to the target version.
+///
+/// Migrations are applied one after another, so a database several versions behind is brought
+/// up to date in a single call.
+pub(crate) fn try_upgrade_db(
+	db_path: &Path,
+	db_kind: DatabaseKind,
+	target_version: Version,
+) -> Result<(), Error> {
 	let is_empty = db_path.read_dir().map_or(true, |mut d| d.next().is_none());
 	if !is_empty {
-		match get_db_version(db_path)? {
-			// 0 -> 1 migration
-			Some(0) => migrate_from_version_0_to_1(db_path, db_kind)?,
-			// 1 -> 2 migration
-			Some(1) => migrate_from_version_1_to_2(db_path, db_kind)?,
-			// 2 -> 3 migration
-			Some(2) => migrate_from_version_2_to_3(db_path, db_kind)?,
-			// Already at current version, do nothing.
-			Some(CURRENT_VERSION) => (),
-			// This is an arbitrary future version, we don't handle it.
-			Some(v) => return Err(Error::FutureVersion { current: CURRENT_VERSION, got: v }),
-			// No version file. For `RocksDB` we dont need to do anything.
-			None if db_kind == DatabaseKind::RocksDB => (),
-			// No version file. `ParityDB` did not previously have a version defined.
-			// We handle this as a `0 -> 1` migration.
-			None if db_kind == DatabaseKind::ParityDB =>
-				migrate_from_version_0_to_1(db_path, db_kind)?,
-			None => unreachable!(),
+		let mut version = get_db_version(db_path)?;
+		loop {
+			let next = match version {
+				// Reached the version we were asked for.
+				Some(v) if v == target_version => break,
+				// 0 -> 1 migration
+				Some(0) => migrate_from_version_0_to_1(db_path, db_kind)?,
+				// 1 -> 2 migration
+				Some(1) => migrate_from_version_1_to_2(db_path, db_kind)?,
+				// 2 -> 3 migration
+				Some(2) => migrate_from_version_2_to_3(db_path, db_kind)?,
+				// 3 -> 4 migration
+				Some(3) => migrate_from_version_3_to_4(db_path, db_kind)?,
+				// Already at current version, do nothing.
+				Some(CURRENT_VERSION) => break,
+				// This is an arbitrary future version, we don't handle it.
+				Some(v) => return Err(Error::FutureVersion { current: CURRENT_VERSION, got: v }),
+				// No version file. For `RocksDB` we dont need to do anything.
+				None if db_kind == DatabaseKind::RocksDB => break,
+				// No version file. `ParityDB` did not previously have a version defined.
+				// We handle this as a `0 -> 1` migration.
+				None if db_kind == DatabaseKind::ParityDB =>
+					migrate_from_version_0_to_1(db_path, db_kind)?,
+				None => unreachable!(),
+			};
+			set_db_version(db_path, next)?;
+			version = Some(next);
 		}
 	}
 
-	update_version(db_path)
+	set_db_version(db_path, target_version)
+}
+
+/// Check that the database at the given path is at the current version, which is required to open
+/// it without upgrading it, e.g. in read-only mode.
+pub(crate) fn check_current_version(db_path: &Path) -> Result<(), Error> {
+	match get_db_version(db_path)? {
+		Some(CURRENT_VERSION) => Ok(()),
+		Some(v) if v > CURRENT_VERSION =>
+			Err(Error::FutureVersion { current: CURRENT_VERSION, got: v }),
+		got => Err(Error::OutdatedVersion { current: CURRENT_VERSION, got }),
+	}
 }
 
 /// Reads current database version from the file at given path.
@@ -89,11 +118,11 @@ fn get_db_version(path: &Path) -> Result<Option<Version>, Error> {
 	}
 }
 
-/// Writes current database version to the file.
+/// Writes the given database version to the file.
 /// Creates a new file if the version file does not exist yet.
-fn update_version(path: &Path) -> Result<(), Error> {
+fn set_db_version(path: &Path, version: Version) -> Result<(), Error> {
 	fs::create_dir_all(path)?;
-	fs::write(version_file_path(path), CURRENT_VERSION.to_string()).map_err(Into::into)
+	fs::write(version_file_path(path), version.to_string()).map_err(Into::into)
 }
 
 /// Returns the version file path.
@@ -103,41 +132,53 @@ fn version_file_path(path: &Path) -> PathBuf {
 	file_path
 }
 
-fn migrate_from_version_0_to_1(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+fn migrate_from_version_0_to_1(path: &Path, db_kind: DatabaseKind) -> Result<Version, Error> {
 	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 0 to version 1 ...");
 
 	match db_kind {
 		DatabaseKind::ParityDB => paritydb_migrate_from_version_0_to_1(path),
 		DatabaseKind::RocksDB => rocksdb_migrate_from_version_0_to_1(path),
 	}
-	.and_then(|result| {
+	.map(|()| {
 		gum::info!(target: LOG_TARGET, "Migration complete! ");
-		Ok(result)
+		1
 	})
 }
 
-fn migrate_from_version_1_to_2(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+fn migrate_from_version_1_to_2(path: &Path, db_kind: DatabaseKind) -> Result<Version, Error> {
 	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 1 to version 2 ...");
 
 	match db_kind {
 		DatabaseKind::ParityDB => paritydb_migrate_from_version_1_to_2(path),
 		DatabaseKind::RocksDB => rocksdb_migrate_from_version_1_to_2(path),
 	}
-	.and_then(|result| {
+	.map(|()| {
 		gum::info!(target: LOG_TARGET, "Migration complete! ");
-		Ok(result)
+		2
 	})
 }
 
-fn migrate_from_version_2_to_3(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+fn migrate_from_version_2_to_3(path: &Path, db_kind: DatabaseKind) -> Result<Version, Error> {
 	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 2 to version 3 ...");
 	match db_kind {
 		DatabaseKind::ParityDB => paritydb_migrate_from_version_2_to_3(path),
 		DatabaseKind::RocksDB => rocksdb_migrate_from_version_2_to_3(path),
 	}
-	.and_then(|result| {
+	.map(|()| {
+		gum::info!(target: LOG_TARGET, "Migration complete! ");
+		3
+	})
+}
+
+fn migrate_from_version_3_to_4(path: &Path, db_kind: DatabaseKind) -> Result<Version, Error> {
+	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 3 to version 4 ...");
+	match db_kind {
+		DatabaseKind::ParityDB => paritydb_migrate_from_version_3_to_4(path),
+		DatabaseKind::RocksDB => rocksdb_migrate_from_version_3_to_4(path),
+	}
+	.map(|()| {
 		gum::info!(target: LOG_TARGET, "Migration complete! ");
-		Ok(result)
+		4
 	})
 }
 
@@ -188,6 +229,22 @@ fn rocksdb_migrate_from_version_2_to_3(path: &Path) -> Result<(), Error> {
 	Ok(())
 }
 
+/// Migration from version 3 to version 4:
+/// * the number of columns has changed from 5 to 6;
+fn rocksdb_migrate_from_version_3_to_4(path: &Path) -> Result<(), Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let db_path = path
+		.to_str()
+		.ok_or_else(|| super::other_io_error("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(super::columns::v3::NUM_COLUMNS);
+	let mut db = Database::open(&db_cfg, db_path)?;
+
+	db.add_column()?;
+
+	Ok(())
+}
+
 // This currently clears columns which had their configs altered between versions.
 // The columns to be changed are constrained by the `allowed_columns` vector.
 fn paritydb_fix_columns(
@@ -278,13 +335,24 @@ pub(crate) fn paritydb_version_3_config(path: &Path) -> parity_db::Options {
 	options
 }
 
+/// Database configuration for version 4.
+pub(crate) fn paritydb_version_4_config(path: &Path) -> parity_db::Options {
+	let mut options =
+		parity_db::Options::with_columns(&path, super::columns::v4::NUM_COLUMNS as u8);
+	for i in columns::v4::ORDERED_COL {
+		options.columns[*i as usize].btree_index = true;
+	}
+
+	options
+}
+
 /// Database configuration for version 0. This is useful just for testing.
 #[cfg(test)]
 pub(crate) fn paritydb_version_0_config(path: &Path) -> parity_db::Options {
 	let mut options =
 		parity_db::Options::with_columns(&path, super::columns::v1::NUM_COLUMNS as u8);
-	options.columns[super::columns::v3::COL_AVAILABILITY_META as usize].btree_index = true;
-	options.columns[super::columns::v3::COL_CHAIN_SELECTION_DATA as usize].btree_index = true;
+	options.columns[super::columns::v4::COL_AVAILABILITY_META as usize].btree_index = true;
+	options.columns[super::columns::v4::COL_CHAIN_SELECTION_DATA as usize].btree_index = true;
 
 	options
 }
@@ -299,7 +367,7 @@ fn paritydb_migrate_from_version_0_to_1(path: &Path) -> Result<(), Error> {
 	paritydb_fix_columns(
 		path,
 		paritydb_version_1_config(path),
-		vec![super::columns::v3::COL_DISPUTE_COORDINATOR_DATA],
+		vec![super::columns::v4::COL_DISPUTE_COORDINATOR_DATA],
 	)?;
 
 	Ok(())
@@ -325,10 +393,24 @@ fn paritydb_migrate_from_version_2_to_3(path: &Path) -> Result<(), Error> {
 	Ok(())
 }
 
+/// Migration from version 3 to version 4:
+/// - add a new, ordered column for the collator scores
+fn paritydb_migrate_from_version_3_to_4(path: &Path) -> Result<(), Error> {
+	let mut options = paritydb_version_3_config(path);
+
+	parity_db::Db::add_column(
+		&mut options,
+		parity_db::ColumnOptions { btree_index: true, ..Default::default() },
+	)
+	.map_err(|e| other_io_error(format!("Error adding COL_COLLATOR_SCORES_DATA {:?}", e)))?;
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::{
-		columns::{v2::COL_SESSION_WINDOW_DATA, v3::*},
+		columns::{v2::COL_SESSION_WINDOW_DATA, v4::*},
 		*,
 	};
 
@@ -348,7 +430,7 @@ mod tests {
 			.unwrap();
 		}
 
-		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		try_upgrade_db(&path, DatabaseKind::ParityDB, 1).unwrap();
 
 		let db = Db::open(&paritydb_version_1_config(&path)).unwrap();
 		assert_eq!(db.get(COL_DISPUTE_COORDINATOR_DATA as u8, b"1234").unwrap(), None);
@@ -382,7 +464,7 @@ mod tests {
 			assert_eq!(db.num_columns(), columns::v1::NUM_COLUMNS as u8);
 		}
 
-		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		try_upgrade_db(&path, DatabaseKind::ParityDB, 2).unwrap();
 
 		let db = Db::open(&paritydb_version_2_config(&path)).unwrap();
 
@@ -436,7 +518,7 @@ mod tests {
 			.unwrap();
 		}
 
-		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB).unwrap();
+		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB, 2).unwrap();
 
 		let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
 		let db = Database::open(&db_cfg, db_path).unwrap();
@@ -492,7 +574,7 @@ mod tests {
 			assert_eq!(db.num_columns(), columns::v2::NUM_COLUMNS as u8);
 		}
 
-		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		try_upgrade_db(&path, DatabaseKind::ParityDB, 3).unwrap();
 
 		let db = Db::open(&paritydb_version_3_config(&path)).unwrap();
 
@@ -514,11 +596,104 @@ mod tests {
 		// We need to properly set db version for upgrade to work.
 		fs::write(version_file_path(db_dir.path()), "2").expect("Failed to write DB version");
 
-		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB).unwrap();
+		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB, 3).unwrap();
 
 		let db_cfg = DatabaseConfig::with_columns(super::columns::v3::NUM_COLUMNS);
 		let db = Database::open(&db_cfg, db_path).unwrap();
 
 		assert_eq!(db.num_columns(), super::columns::v3::NUM_COLUMNS);
 	}
+
+	#[test]
+	fn test_paritydb_migrate_3_to_4() {
+		use parity_db::Db;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(path), "3").expect("Failed to write DB version");
+
+		{
+			let db = Db::open_or_create(&paritydb_version_3_config(&path)).unwrap();
+
+			db.commit(vec![(
+				COL_DISPUTE_COORDINATOR_DATA as u8,
+				b"1234".to_vec(),
+				Some(b"somevalue".to_vec()),
+			)])
+			.unwrap();
+
+			assert_eq!(db.num_columns(), columns::v3::NUM_COLUMNS as u8);
+		}
+
+		try_upgrade_db(&path, DatabaseKind::ParityDB, 4).unwrap();
+
+		let db = Db::open(&paritydb_version_4_config(&path)).unwrap();
+
+		assert_eq!(db.num_columns(), columns::v4::NUM_COLUMNS as u8);
+		assert_eq!(
+			db.get(COL_DISPUTE_COORDINATOR_DATA as u8, b"1234").unwrap(),
+			Some("somevalue".as_bytes().to_vec())
+		);
+
+		// Test we can write the new column.
+		db.commit(vec![(
+			COL_COLLATOR_SCORES_DATA as u8,
+			b"1337".to_vec(),
+			Some(b"0xdeadb00b".to_vec()),
+		)])
+		.unwrap();
+
+		// Read back data from new column.
+		assert_eq!(
+			db.get(COL_COLLATOR_SCORES_DATA as u8, b"1337").unwrap(),
+			Some("0xdeadb00b".as_bytes().to_vec())
+		);
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_3_to_4() {
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v3::NUM_COLUMNS);
+		{
+			let db = Database::open(&db_cfg, db_path).unwrap();
+			assert_eq!(db.num_columns(), super::columns::v3::NUM_COLUMNS as u32);
+		}
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(db_dir.path()), "3").expect("Failed to write DB version");
+
+		try_upgrade_db(&db_dir.path(), DatabaseKind::RocksDB, 4).unwrap();
+
+		let db_cfg = DatabaseConfig::with_columns(super::columns::v4::NUM_COLUMNS);
+		let db = Database::open(&db_cfg, db_path).unwrap();
+
+		assert_eq!(db.num_columns(), super::columns::v4::NUM_COLUMNS);
+	}
+
+	#[test]
+	fn test_paritydb_migrate_1_to_current() {
+		use parity_db::Db;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+
+		// We need to properly set db version for upgrade to work.
+		fs::write(version_file_path(path), "1").expect("Failed to write DB version");
+
+		{
+			let db = Db::open_or_create(&paritydb_version_1_config(&path)).unwrap();
+			assert_eq!(db.num_columns(), columns::v1::NUM_COLUMNS as u8);
+		}
+
+		try_upgrade_db(&path, DatabaseKind::ParityDB, CURRENT_VERSION).unwrap();
+
+		assert_eq!(get_db_version(path).unwrap(), Some(CURRENT_VERSION));
+		let db = Db::open(&paritydb_version_4_config(&path)).unwrap();
+		assert_eq!(db.num_columns(), columns::v4::NUM_COLUMNS as u8);
+	}
 }
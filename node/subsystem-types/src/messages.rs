@@ -32,7 +32,9 @@ use polkadot_node_network_protocol::{
 	self as net_protocol, peer_set::PeerSet, request_response::Requests, PeerId,
 };
 use polkadot_node_primitives::{
-	approval::{BlockApprovalMeta, IndirectAssignmentCert, IndirectSignedApprovalVote},
+	approval::{
+		BlockApprovalMeta, CandidateBitfield, IndirectAssignmentCert, IndirectSignedApprovalVoteV2,
+	},
 	AvailableData, BabeEpoch, BlockWeight, CandidateVotes, CollationGenerationConfig,
 	CollationSecondedSignal, DisputeMessage, DisputeStatus, ErasureChunk, PoV,
 	SignedDisputeStatement, SignedFullStatement, ValidationResult,
@@ -50,6 +52,7 @@ use polkadot_statement_table::v2::Misbehavior;
 use std::{
 	collections::{BTreeMap, HashMap, HashSet},
 	sync::Arc,
+	time::Duration,
 };
 
 /// Network events as transmitted to other subsystems, wrapped in their message types.
@@ -157,11 +160,11 @@ pub enum CandidateValidationMessage {
 #[derive(Debug, derive_more::From)]
 pub enum CollatorProtocolMessage {
 	/// Signal to the collator protocol that it should connect to validators with the expectation
-	/// of collating on the given para. This is only expected to be called once, early on, if at all,
-	/// and only by the Collation Generation subsystem. As such, it will overwrite the value of
-	/// the previous signal.
+	/// of collating on the given para. This is only expected to be called once per para, early on,
+	/// if at all, and only by the Collation Generation subsystem. Every signal adds a para to the
+	/// ones the collator protocol is collating on.
 	///
-	/// This should be sent before any `DistributeCollation` message.
+	/// This should be sent before any `DistributeCollation` message for the para.
 	CollateOn(ParaId),
 	/// Provide a collation to distribute to validators with an optional result sender.
 	///
@@ -263,6 +266,37 @@ pub enum DisputeCoordinatorMessage {
 		/// The block to vote on, might be base in case there is no better.
 		tx: oneshot::Sender<(BlockNumber, Hash)>,
 	},
+	/// Get the votes on the given candidates, along with the validators controlled by this node
+	/// which voted on them. Candidates without votes are omitted.
+	///
+	/// This is meant for inspecting the state of the subsystem, not for use by other subsystems.
+	InspectCandidateVotes(
+		Vec<(SessionIndex, CandidateHash)>,
+		oneshot::Sender<
+			Vec<(SessionIndex, CandidateHash, CandidateVotes, Vec<(ValidatorIndex, ValidatorId)>)>,
+		>,
+	),
+	/// Get the number of occupied spam slots per session and validator. Validators without any
+	/// occupied slots are omitted.
+	///
+	/// This is meant for inspecting the state of the subsystem, not for use by other subsystems.
+	InspectSpamSlots(oneshot::Sender<Vec<(SessionIndex, ValidatorIndex, u32)>>),
+	/// Get the running and queued dispute participations.
+	///
+	/// This is meant for inspecting the state of the subsystem, not for use by other subsystems.
+	InspectParticipationQueue(oneshot::Sender<ParticipationQueueContents>),
+}
+
+/// The dispute participations as reported by `DisputeCoordinatorMessage::InspectParticipationQueue`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParticipationQueueContents {
+	/// The candidates currently being participated in.
+	pub running: Vec<CandidateHash>,
+	/// The priority queue, in the order of participation.
+	pub priority: Vec<(SessionIndex, CandidateHash)>,
+	/// The best-effort queue, in the order of participation. It is only served once the priority
+	/// queue is empty.
+	pub best_effort: Vec<(SessionIndex, CandidateHash)>,
 }
 
 /// The result of `DisputeCoordinatorMessage::ImportStatements`.
@@ -370,6 +404,61 @@ pub enum NetworkBridgeTxMessage {
 		/// The peer set we want the connection on.
 		peer_set: PeerSet,
 	},
+	/// Get the traffic exchanged with the peers which sent us the most data, at most `limit` of
+	/// them, ordered by the number of bytes received from them.
+	///
+	/// This is meant for inspecting the state of the subsystem, not for use by other subsystems.
+	InspectPeerTraffic(usize, oneshot::Sender<Vec<PeerTraffic>>),
+}
+
+/// The number of messages and bytes of some kind exchanged with a peer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Traffic {
+	/// The number of messages.
+	pub messages: u64,
+	/// The number of bytes, as put on the wire.
+	pub bytes: u64,
+}
+
+impl Traffic {
+	/// Account for one more message of `bytes` bytes.
+	pub fn note(&mut self, bytes: usize) {
+		self.messages = self.messages.saturating_add(1);
+		self.bytes = self.bytes.saturating_add(bytes as u64);
+	}
+
+	/// Add up the traffic of `self` and `other`.
+	pub fn saturating_add(self, other: Traffic) -> Traffic {
+		Traffic {
+			messages: self.messages.saturating_add(other.messages),
+			bytes: self.bytes.saturating_add(other.bytes),
+		}
+	}
+}
+
+/// The traffic of one kind of message exchanged with a peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageTraffic {
+	/// The kind of the messages, e.g. `approval-distribution/assignments` for notifications or
+	/// `request/chunk-fetching-v1` for requests.
+	pub kind: &'static str,
+	/// The traffic received from the peer.
+	pub received: Traffic,
+	/// The traffic sent to the peer.
+	pub sent: Traffic,
+}
+
+/// The traffic exchanged with a peer, as reported by `NetworkBridgeTxMessage::InspectPeerTraffic`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerTraffic {
+	/// The peer.
+	pub peer: PeerId,
+	/// The total traffic received from the peer.
+	pub received: Traffic,
+	/// The total traffic sent to the peer.
+	pub sent: Traffic,
+	/// The traffic per kind of message, ordered by the number of bytes received.
+	pub messages: Vec<MessageTraffic>,
 }
 
 /// Availability Distribution Message.
@@ -722,8 +811,10 @@ pub enum ProvisionerMessage {
 /// Message to the Collation Generation subsystem.
 #[derive(Debug)]
 pub enum CollationGenerationMessage {
-	/// Initialize the collation generation subsystem
-	Initialize(CollationGenerationConfig),
+	/// Initialize the collation generation subsystem with one config per para to collate on.
+	///
+	/// May be sent more than once to add paras; configs for already initialized paras are ignored.
+	Initialize(Vec<CollationGenerationConfig>),
 }
 
 /// The result type of [`ApprovalVotingMessage::CheckAndImportAssignment`] request.
@@ -818,17 +909,18 @@ pub struct HighestApprovedAncestorBlock {
 #[derive(Debug)]
 pub enum ApprovalVotingMessage {
 	/// Check if the assignment is valid and can be accepted by our view of the protocol.
+	/// The assignment claims all candidates of the block set in the bitfield.
 	/// Should not be sent unless the block hash is known.
 	CheckAndImportAssignment(
 		IndirectAssignmentCert,
-		CandidateIndex,
+		CandidateBitfield,
 		oneshot::Sender<AssignmentCheckResult>,
 	),
 	/// Check if the approval vote is valid and can be accepted by our view of the
-	/// protocol.
+	/// protocol. The vote may cover multiple candidates of the same block.
 	///
 	/// Should not be sent unless the block hash within the indirect vote is known.
-	CheckAndImportApproval(IndirectSignedApprovalVote, oneshot::Sender<ApprovalCheckResult>),
+	CheckAndImportApproval(IndirectSignedApprovalVoteV2, oneshot::Sender<ApprovalCheckResult>),
 	/// Returns the highest possible ancestor hash of the provided block hash which is
 	/// acceptable to vote on finality for.
 	/// The `BlockNumber` provided is the number of the block's ancestor which is the
@@ -840,11 +932,14 @@ pub enum ApprovalVotingMessage {
 
 	/// Retrieve all available approval signatures for a candidate from approval-voting.
 	///
+	/// A signature may cover further candidates of the same block, all candidates signed are
+	/// returned along with it, in the order they were signed in.
+	///
 	/// This message involves a linear search for candidates on each relay chain fork and also
 	/// requires calling into `approval-distribution`: Calls should be infrequent and bounded.
 	GetApprovalSignaturesForCandidate(
 		CandidateHash,
-		oneshot::Sender<HashMap<ValidatorIndex, ValidatorSignature>>,
+		oneshot::Sender<HashMap<ValidatorIndex, (Vec<CandidateHash>, ValidatorSignature)>>,
 	),
 }
 
@@ -856,19 +951,22 @@ pub enum ApprovalDistributionMessage {
 	NewBlocks(Vec<BlockApprovalMeta>),
 	/// Distribute an assignment cert from the local validator. The cert is assumed
 	/// to be valid, relevant, and for the given relay-parent and validator index.
-	DistributeAssignment(IndirectAssignmentCert, CandidateIndex),
+	/// It claims all candidates of the block set in the bitfield.
+	DistributeAssignment(IndirectAssignmentCert, CandidateBitfield),
 	/// Distribute an approval vote for the local validator. The approval vote is assumed to be
-	/// valid, relevant, and the corresponding approval already issued.
+	/// valid, relevant, and the corresponding approvals already issued.
 	/// If not, the subsystem is free to drop the message.
-	DistributeApproval(IndirectSignedApprovalVote),
+	DistributeApproval(IndirectSignedApprovalVoteV2),
 	/// An update from the network bridge.
 	#[from]
 	NetworkBridgeUpdate(NetworkBridgeEvent<net_protocol::ApprovalDistributionMessage>),
 
 	/// Get all approval signatures for all chains a candidate appeared in.
+	///
+	/// Each signature is returned with the block and the indices of all candidates it covers.
 	GetApprovalSignatures(
 		HashSet<(Hash, CandidateIndex)>,
-		oneshot::Sender<HashMap<ValidatorIndex, ValidatorSignature>>,
+		oneshot::Sender<HashMap<ValidatorIndex, (Hash, Vec<CandidateIndex>, ValidatorSignature)>>,
 	),
 	/// Approval checking lag update measured in blocks.
 	ApprovalCheckingLagUpdate(BlockNumber),
@@ -880,4 +978,47 @@ pub enum GossipSupportMessage {
 	/// Dummy constructor, so we can receive networking events.
 	#[from]
 	NetworkBridgeUpdate(NetworkBridgeEvent<net_protocol::GossipSupportNetworkMessage>),
+	/// Peers connecting and disconnecting on the collation peer set.
+	///
+	/// `NetworkBridgeUpdate` only covers the validation peer set.
+	CollationNetworkBridgeUpdate(NetworkBridgeEvent<net_protocol::GossipSupportNetworkMessage>),
+	/// Get the connectivity to the authorities of the past, present and future sessions.
+	///
+	/// This is meant for inspecting the state of the subsystem, not for use by other subsystems.
+	InspectConnectivity(oneshot::Sender<ConnectivityReport>),
+}
+
+/// The connectivity to an authority, as seen by the gossip support subsystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorityConnectivity {
+	/// The authority.
+	pub authority: AuthorityDiscoveryId,
+	/// Whether authority discovery knew addresses of the authority on the last connection
+	/// request.
+	pub resolved: bool,
+	/// The peer sets we are connected to the authority on.
+	pub connected: Vec<PeerSet>,
+	/// Whether the authority is one of our neighbors in the gossip grid of the current session.
+	pub grid_neighbor: bool,
+}
+
+impl AuthorityConnectivity {
+	/// Whether we are connected to the authority on the validation peer set, which is the one
+	/// gossip is sent on.
+	pub fn is_reachable(&self) -> bool {
+		self.connected.contains(&PeerSet::Validation)
+	}
+}
+
+/// The report returned by `GossipSupportMessage::InspectConnectivity`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectivityReport {
+	/// The last session we issued a connection request for.
+	pub session: Option<SessionIndex>,
+	/// If the last connection request failed to resolve too many authorities, the time until it
+	/// gets reissued at the earliest.
+	pub reconnect_in: Option<Duration>,
+	/// The authorities we want to be connected to. Grid neighbors come first, and unreachable
+	/// authorities before reachable ones.
+	pub authorities: Vec<AuthorityConnectivity>,
 }
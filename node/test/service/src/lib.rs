@@ -76,17 +76,20 @@ pub fn new_full(
 ) -> Result<NewFull, Error> {
 	polkadot_service::new_full(
 		config,
-		is_collator,
-		None,
-		true,
-		None,
-		None,
-		worker_program_path,
-		false,
-		polkadot_service::RealOverseerGen,
-		None,
-		None,
-		None,
+		polkadot_service::NewFullParams {
+			is_collator,
+			grandpa_pause: None,
+			enable_beefy: true,
+			jaeger_agent: None,
+			telemetry_worker_handle: None,
+			program_path: worker_program_path,
+			subsystems: Default::default(),
+			overseer_enable_anyways: false,
+			overseer_gen: polkadot_service::RealOverseerGen,
+			overseer_message_channel_capacity_override: None,
+			malus_finality_delay: None,
+			hwbench: None,
+		},
 	)
 }
 
@@ -231,7 +234,7 @@ pub fn run_collator_node(
 	let config = node_config(storage_update_func, tokio_handle, key, boot_nodes, false);
 	let multiaddr = config.network.listen_addresses[0].clone();
 	let NewFull { task_manager, client, network, rpc_handlers, overseer_handle, .. } =
-		new_full(config, IsCollator::Yes(collator_pair), None)
+		new_full(config, IsCollator::Yes(collator_pair.into()), None)
 			.expect("could not create Polkadot test service");
 
 	let overseer_handle = overseer_handle.expect("test node must have an overseer handle");
@@ -257,7 +260,7 @@ pub struct PolkadotTestNode {
 
 impl PolkadotTestNode {
 	/// Send a sudo call to this node.
-	async fn send_sudo(
+	pub async fn send_sudo(
 		&self,
 		call: impl Into<polkadot_test_runtime::RuntimeCall>,
 		caller: Sr25519Keyring,
@@ -334,7 +337,7 @@ impl PolkadotTestNode {
 		let config = CollationGenerationConfig { key: collator_key, collator, para_id };
 
 		self.overseer_handle
-			.send_msg(CollationGenerationMessage::Initialize(config), "Collator")
+			.send_msg(CollationGenerationMessage::Initialize(vec![config]), "Collator")
 			.await;
 
 		self.overseer_handle
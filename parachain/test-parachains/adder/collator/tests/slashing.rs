@@ -0,0 +1,274 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Integration test that ensures that a pending slash for an included candidate of the adder
+//! parachain is reported by the validators, exactly once, and ends up as an offence on chain.
+
+const PUPPET_EXE: &str = env!("CARGO_BIN_EXE_adder_collator_puppet_worker");
+
+/// Number of blocks a pending slash stays in place without the validators submitting a report
+/// for it again.
+///
+/// The dispute coordinator only resubmits reports after `SLASHING_REPORT_RESUBMIT_INTERVAL`
+/// (600) blocks, which is more than the test can wait for, so this only covers the start of the
+/// window. Every block in it gives the validators the chance to report the slash again.
+const DEDUP_WINDOW_BLOCKS: usize = 20;
+
+use frame_support::{storage::storage_prefix, Blake2_128Concat, StorageHasher, Twox64Concat};
+use futures::StreamExt;
+use parity_scale_codec::{Decode, Encode};
+use polkadot_primitives::{
+	runtime_api::ParachainHost,
+	slashing::{PendingSlashes, SlashingOffenceKind},
+	CandidateEvent, CandidateHash, Hash, Id as ParaId, SessionIndex, ValidatorIndex,
+};
+use polkadot_test_runtime::{Runtime, RuntimeEvent};
+use polkadot_test_service::PolkadotTestNode;
+use prometheus_endpoint::Registry;
+use sc_client_api::{BlockchainEvents, StorageProvider};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use std::net::{Ipv4Addr, TcpListener};
+
+#[substrate_test_utils::test(flavor = "multi_thread")]
+async fn unapplied_slash_is_reported_once_and_applied() {
+	use sp_keyring::AccountKeyring::*;
+
+	let mut builder = sc_cli::LoggerBuilder::new("");
+	builder.with_colors(false);
+	builder.init().expect("Set up logger");
+
+	let para_id = ParaId::from(100);
+
+	let mut alice_config = polkadot_test_service::node_config(
+		|| {},
+		tokio::runtime::Handle::current(),
+		Alice,
+		Vec::new(),
+		true,
+	);
+	alice_config.prometheus_config =
+		Some(polkadot_test_service::test_prometheus_config(free_port()));
+	let alice_registry = alice_config.prometheus_registry().cloned().expect("enabled above");
+
+	// start alice
+	let alice = polkadot_test_service::run_validator_node(alice_config, Some(PUPPET_EXE.into()));
+
+	let mut bob_config = polkadot_test_service::node_config(
+		|| {},
+		tokio::runtime::Handle::current(),
+		Bob,
+		vec![alice.addr.clone()],
+		true,
+	);
+	bob_config.prometheus_config = Some(polkadot_test_service::test_prometheus_config(free_port()));
+	let bob_registry = bob_config.prometheus_registry().cloned().expect("enabled above");
+
+	// start bob
+	let bob = polkadot_test_service::run_validator_node(bob_config, Some(PUPPET_EXE.into()));
+
+	let collator = test_parachain_adder_collator::Collator::new();
+
+	// register parachain
+	alice
+		.register_parachain(para_id, collator.validation_code().to_vec(), collator.genesis_head())
+		.await
+		.unwrap();
+
+	// run the collator node
+	let mut charlie = polkadot_test_service::run_collator_node(
+		tokio::runtime::Handle::current(),
+		Charlie,
+		|| {},
+		vec![alice.addr.clone(), bob.addr.clone()],
+		collator.collator_key(),
+	);
+
+	charlie
+		.register_collator(
+			collator.collator_key(),
+			para_id,
+			collator.create_collation_function(charlie.task_manager.spawn_handle()),
+		)
+		.await;
+
+	// The validators need to have seen the candidate included to report a slash for it.
+	let (session_index, candidate_hash) = wait_for_included_candidate(&alice, para_id).await;
+
+	// There are no disputes on honest candidates, so put a pending slash in place directly.
+	let best_hash = alice.client.info().best_hash;
+	let validator_id = alice.client.runtime_api().validators(best_hash).unwrap()[0].clone();
+	let pending = PendingSlashes {
+		keys: [(ValidatorIndex(0), validator_id)].into_iter().collect(),
+		// Losing a dispute against a valid candidate doesn't disable the validator, so the chain
+		// keeps going at full speed.
+		kind: SlashingOffenceKind::AgainstValid,
+	};
+	let set_storage = || frame_system::Call::<Runtime>::set_storage {
+		items: vec![(unapplied_slashes_key(session_index, candidate_hash), pending.encode())],
+	};
+	// Nonces `0` and `1` were used up by registering the parachain.
+	alice.send_sudo(set_storage(), Alice, 2).await.unwrap();
+
+	wait_for_unapplied_slashes(&alice, |unapplied| !unapplied.is_empty()).await;
+	wait_for_unapplied_slashes(&alice, |unapplied| unapplied.is_empty()).await;
+
+	let alice_submitted = slashing_reports(&alice_registry, "submitted");
+	let bob_submitted = slashing_reports(&bob_registry, "submitted");
+	assert!(alice_submitted <= 1, "Alice submitted the report {} times", alice_submitted);
+	assert!(bob_submitted <= 1, "Bob submitted the report {} times", bob_submitted);
+	assert!(alice_submitted + bob_submitted >= 1, "The slash was applied without a report");
+	assert_eq!(offences(&alice), 1);
+
+	// Put the same slash in place again. The offence is known by now, so the runtime rejects the
+	// reports as duplicates and the slash stays pending.
+	alice.send_sudo(set_storage(), Alice, 3).await.unwrap();
+
+	wait_for_unapplied_slashes(&alice, |unapplied| !unapplied.is_empty()).await;
+	alice.wait_for_blocks(DEDUP_WINDOW_BLOCKS).await;
+
+	let best_hash = alice.client.info().best_hash;
+	assert!(
+		!alice.client.runtime_api().unapplied_slashes(best_hash).unwrap().is_empty(),
+		"The slash was applied twice",
+	);
+
+	// Each validator tried to report the slash once, and then left it alone for the whole window.
+	let alice_duplicates = slashing_reports(&alice_registry, "duplicate");
+	let bob_duplicates = slashing_reports(&bob_registry, "duplicate");
+	assert!(alice_duplicates <= 1, "Alice reported the slash again {} times", alice_duplicates);
+	assert!(bob_duplicates <= 1, "Bob reported the slash again {} times", bob_duplicates);
+	assert!(alice_duplicates + bob_duplicates >= 1, "The slash was not reported again");
+	assert_eq!(slashing_reports(&alice_registry, "submitted"), alice_submitted);
+	assert_eq!(slashing_reports(&bob_registry, "submitted"), bob_submitted);
+	assert_eq!(offences(&alice), 1);
+}
+
+/// A port on the loopback interface no other process listens on.
+fn free_port() -> u16 {
+	TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+		.and_then(|listener| listener.local_addr())
+		.expect("The OS hands out a free port")
+		.port()
+}
+
+/// Wait for a candidate of the given parachain to be included on the best chain of `node`.
+async fn wait_for_included_candidate(
+	node: &PolkadotTestNode,
+	para_id: ParaId,
+) -> (SessionIndex, CandidateHash) {
+	let mut import_notification_stream = node.client.import_notification_stream();
+
+	while let Some(notification) = import_notification_stream.next().await {
+		if !notification.is_new_best {
+			continue
+		}
+
+		let runtime_api = node.client.runtime_api();
+		let included =
+			runtime_api.candidate_events(notification.hash).unwrap().into_iter().find_map(
+				|event| match event {
+					CandidateEvent::CandidateIncluded(receipt, ..)
+						if receipt.descriptor.para_id == para_id =>
+						Some(receipt.hash()),
+					_ => None,
+				},
+			);
+
+		if let Some(candidate_hash) = included {
+			let session_index =
+				runtime_api.session_index_for_child(notification.header.parent_hash).unwrap();
+			return (session_index, candidate_hash)
+		}
+	}
+
+	panic!("Import notification stream ended before a candidate got included")
+}
+
+/// Wait for a new best block of `node` whose unapplied slashes satisfy `condition`.
+async fn wait_for_unapplied_slashes(
+	node: &PolkadotTestNode,
+	condition: impl Fn(&[(SessionIndex, CandidateHash, PendingSlashes)]) -> bool,
+) {
+	let mut import_notification_stream = node.client.import_notification_stream();
+
+	while let Some(notification) = import_notification_stream.next().await {
+		if notification.is_new_best &&
+			condition(&node.client.runtime_api().unapplied_slashes(notification.hash).unwrap())
+		{
+			return
+		}
+	}
+
+	panic!("Import notification stream ended before the unapplied slashes changed")
+}
+
+/// The storage key of a pending slash in the slashing pallet.
+fn unapplied_slashes_key(session_index: SessionIndex, candidate_hash: CandidateHash) -> Vec<u8> {
+	let mut key = storage_prefix(b"ParasSlashing", b"UnappliedSlashes").to_vec();
+	key.extend(Twox64Concat::hash(&session_index.encode()));
+	key.extend(Blake2_128Concat::hash(&candidate_hash.encode()));
+	key
+}
+
+/// Number of slashing reports the dispute coordinator of a node handed to the runtime, with the
+/// given outcome.
+fn slashing_reports(registry: &Registry, outcome: &str) -> u64 {
+	registry
+		.gather()
+		.iter()
+		.filter(|family| family.get_name() == "polkadot_parachain_dispute_slashing_reports")
+		.flat_map(|family| family.get_metric())
+		.filter(|metric| {
+			metric
+				.get_label()
+				.iter()
+				.any(|label| label.get_name() == "outcome" && label.get_value() == outcome)
+		})
+		.map(|metric| metric.get_counter().get_value() as u64)
+		.sum()
+}
+
+/// Number of offences reported on the best chain of `node`.
+fn offences(node: &PolkadotTestNode) -> usize {
+	let events_key = StorageKey(storage_prefix(b"System", b"Events").to_vec());
+
+	(1..=node.client.info().best_number)
+		.map(|number| {
+			let hash = node.client.hash(number).unwrap().expect("block is on the best chain");
+			let events = node
+				.client
+				.storage(hash, &events_key)
+				.unwrap()
+				.map(|events| {
+					Vec::<frame_system::EventRecord<RuntimeEvent, Hash>>::decode(&mut &events.0[..])
+						.unwrap()
+				})
+				.unwrap_or_default();
+
+			events
+				.into_iter()
+				.filter(|record| {
+					matches!(
+						record.event,
+						RuntimeEvent::Offences(pallet_offences::Event::Offence { .. })
+					)
+				})
+				.count()
+		})
+		.sum()
+}
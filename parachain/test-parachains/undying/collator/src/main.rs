@@ -58,16 +58,22 @@ fn main() -> Result<()> {
 
 				let full_node = polkadot_service::build_full(
 					config,
-					polkadot_service::IsCollator::Yes(collator.collator_key()),
-					None,
-					false,
-					None,
-					None,
-					false,
-					polkadot_service::RealOverseerGen,
-					None,
-					None,
-					None,
+					polkadot_service::NewFullParams {
+						is_collator: polkadot_service::IsCollator::Yes(
+							collator.collator_key().into(),
+						),
+						grandpa_pause: None,
+						enable_beefy: false,
+						jaeger_agent: None,
+						telemetry_worker_handle: None,
+						program_path: None,
+						subsystems: Default::default(),
+						overseer_enable_anyways: false,
+						overseer_gen: polkadot_service::RealOverseerGen,
+						overseer_message_channel_capacity_override: None,
+						malus_finality_delay: None,
+						hwbench: None,
+					},
 				)
 				.map_err(|e| e.to_string())?;
 				let mut overseer_handle = full_node
@@ -92,7 +98,7 @@ fn main() -> Result<()> {
 					para_id,
 				};
 				overseer_handle
-					.send_msg(CollationGenerationMessage::Initialize(config), "Collator")
+					.send_msg(CollationGenerationMessage::Initialize(vec![config]), "Collator")
 					.await;
 
 				overseer_handle
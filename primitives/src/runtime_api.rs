@@ -123,6 +123,7 @@ use crate::{
 use parity_scale_codec::{Decode, Encode};
 use polkadot_core_primitives as pcp;
 use polkadot_parachain::primitives as ppp;
+use sp_arithmetic::FixedU128;
 use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
 sp_api::decl_runtime_apis! {
@@ -240,5 +241,25 @@ sp_api::decl_runtime_apis! {
 			dispute_proof: vstaging::slashing::DisputeProof,
 			key_ownership_proof: vstaging::slashing::OpaqueKeyOwnershipProof,
 		) -> Option<()>;
+
+		/***** Staging *****/
+
+		/// Get the number the base delivery fee of downward messages to the given para is
+		/// multiplied by. Rises while the downward message queue of the para is congested.
+		/// NOTE: This function is only available since parachain host version 6.
+		#[api_version(6)]
+		fn dmp_delivery_fee_factor(recipient: ppp::Id) -> FixedU128;
+
+		/// Get the number the base delivery fee of upward messages sent by the given para is
+		/// multiplied by. Rises while the upward message queue of the para is congested.
+		/// NOTE: This function is only available since parachain host version 6.
+		#[api_version(6)]
+		fn ump_delivery_fee_factor(sender: ppp::Id) -> FixedU128;
+
+		/// Get the number the base delivery fee of messages sent over the given HRMP channel is
+		/// multiplied by. Rises while the channel is congested.
+		/// NOTE: This function is only available since parachain host version 6.
+		#[api_version(6)]
+		fn hrmp_delivery_fee_factor(channel: vstaging::HrmpChannelId) -> FixedU128;
 	}
 }
@@ -1060,6 +1060,27 @@ impl ApprovalVote {
 	}
 }
 
+/// A vote of approval on multiple candidates included by the same relay chain block.
+///
+/// The candidate hashes are expected in the order of their candidate index within the block.
+#[derive(Clone, RuntimeDebug)]
+pub struct ApprovalVoteMultipleCandidates<'a>(pub &'a [CandidateHash]);
+
+impl<'a> ApprovalVoteMultipleCandidates<'a> {
+	/// Yields the signing payload for this approval vote.
+	///
+	/// A vote on a single candidate signs the same payload as [`ApprovalVote`].
+	pub fn signing_payload(&self, session_index: SessionIndex) -> Vec<u8> {
+		const MAGIC: [u8; 4] = *b"APPR";
+
+		if let [candidate_hash] = self.0 {
+			return ApprovalVote(*candidate_hash).signing_payload(session_index)
+		}
+
+		(MAGIC, self.0, session_index).encode()
+	}
+}
+
 /// Custom validity errors used in Polkadot while validating transactions.
 #[repr(u8)]
 pub enum ValidityError {
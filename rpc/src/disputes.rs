@@ -0,0 +1,301 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unsafe RPCs for inspecting the state of the dispute coordinator.
+//!
+//! All of them are answered by the dispute coordinator subsystem, so they are only available on
+//! nodes which run the parachain subsystems, i.e. validators and collators.
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+};
+use polkadot_node_primitives::{CandidateVotes, DisputeStatus, Timestamp};
+use polkadot_node_subsystem::messages::{DisputeCoordinatorMessage, ParticipationQueueContents};
+use polkadot_primitives::{CandidateHash, Hash, SessionIndex, ValidatorId, ValidatorIndex};
+use serde::{Deserialize, Serialize};
+
+use crate::subsystems::SubsystemRequests;
+
+/// The status of a dispute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DisputeStatusInfo {
+	/// The dispute is active and unconcluded.
+	Active,
+	/// The dispute has been confirmed, but is not concluded.
+	Confirmed,
+	/// The dispute has been concluded in favor of the candidate.
+	#[serde(rename_all = "camelCase")]
+	ConcludedFor {
+		/// Since when, in seconds since the unix epoch.
+		since: Timestamp,
+	},
+	/// The dispute has been concluded against the candidate.
+	#[serde(rename_all = "camelCase")]
+	ConcludedAgainst {
+		/// Since when, in seconds since the unix epoch.
+		since: Timestamp,
+	},
+}
+
+impl From<DisputeStatus> for DisputeStatusInfo {
+	fn from(status: DisputeStatus) -> Self {
+		match status {
+			DisputeStatus::Active => Self::Active,
+			DisputeStatus::Confirmed => Self::Confirmed,
+			DisputeStatus::ConcludedFor(since) => Self::ConcludedFor { since },
+			DisputeStatus::ConcludedAgainst(since) => Self::ConcludedAgainst { since },
+		}
+	}
+}
+
+/// A dispute known to the dispute coordinator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisputeInfo {
+	/// The session of the disputed candidate.
+	pub session: SessionIndex,
+	/// The hash of the disputed candidate.
+	pub candidate_hash: Hash,
+	/// The status of the dispute.
+	pub status: DisputeStatusInfo,
+}
+
+/// A vote on a candidate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteInfo {
+	/// The index of the validator in the session.
+	pub validator_index: ValidatorIndex,
+	/// The kind of the statement, e.g. `Explicit` or `BackingSeconded`.
+	pub kind: String,
+}
+
+/// A vote on a candidate cast with one of the keys of this node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnVoteInfo {
+	/// The index of the validator in the session.
+	pub validator_index: ValidatorIndex,
+	/// The key the vote was cast with.
+	pub validator_id: ValidatorId,
+	/// Whether the vote is for the validity of the candidate.
+	pub valid: bool,
+}
+
+/// The votes on a candidate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateVotesInfo {
+	/// The session of the candidate.
+	pub session: SessionIndex,
+	/// The hash of the candidate.
+	pub candidate_hash: Hash,
+	/// The votes for the validity of the candidate, ordered by validator index.
+	pub valid: Vec<VoteInfo>,
+	/// The votes against the validity of the candidate, ordered by validator index.
+	pub invalid: Vec<VoteInfo>,
+	/// The votes cast with the keys of this node. Empty if the session is not known anymore.
+	pub own_votes: Vec<OwnVoteInfo>,
+}
+
+/// The occupied spam slots of a validator in a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpamSlotsInfo {
+	/// The session.
+	pub session: SessionIndex,
+	/// The index of the validator in the session.
+	pub validator_index: ValidatorIndex,
+	/// The number of unconfirmed disputes the validator voted in.
+	pub occupied: u32,
+}
+
+/// A queued dispute participation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedParticipationInfo {
+	/// The session of the disputed candidate.
+	pub session: SessionIndex,
+	/// The hash of the disputed candidate.
+	pub candidate_hash: Hash,
+}
+
+/// The running and queued dispute participations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipationQueueInfo {
+	/// The candidates currently being participated in.
+	pub running: Vec<Hash>,
+	/// The priority queue, in the order of participation.
+	pub priority: Vec<QueuedParticipationInfo>,
+	/// The best-effort queue, in the order of participation.
+	pub best_effort: Vec<QueuedParticipationInfo>,
+}
+
+/// Dispute coordinator inspection RPC methods.
+#[rpc(server)]
+pub trait DisputesApi {
+	/// The disputes of recent sessions, including concluded ones.
+	#[method(name = "disputes_recent")]
+	async fn recent(&self) -> RpcResult<Vec<DisputeInfo>>;
+
+	/// The disputes which are not concluded, or only concluded recently.
+	#[method(name = "disputes_active")]
+	async fn active(&self) -> RpcResult<Vec<DisputeInfo>>;
+
+	/// The votes on a candidate. Returns `None` if there are no votes on it.
+	#[method(name = "disputes_candidateVotes")]
+	async fn candidate_votes(
+		&self,
+		session: SessionIndex,
+		candidate_hash: Hash,
+	) -> RpcResult<Option<CandidateVotesInfo>>;
+
+	/// The occupied spam slots, per session and validator.
+	#[method(name = "disputes_spamSlots")]
+	async fn spam_slots(&self) -> RpcResult<Vec<SpamSlotsInfo>>;
+
+	/// The running and queued dispute participations.
+	#[method(name = "disputes_participationQueue")]
+	async fn participation_queue(&self) -> RpcResult<ParticipationQueueInfo>;
+}
+
+/// Implements the [`DisputesApiServer`] RPC trait by querying the dispute coordinator.
+pub struct Disputes {
+	requests: SubsystemRequests,
+}
+
+impl Disputes {
+	/// Create a new `Disputes` instance.
+	pub fn new(requests: SubsystemRequests) -> Self {
+		Self { requests }
+	}
+
+	async fn request<R>(
+		&self,
+		message: impl FnOnce(oneshot::Sender<R>) -> DisputeCoordinatorMessage,
+	) -> RpcResult<R> {
+		self.requests.request("dispute coordinator", "disputes-rpc", message).await
+	}
+}
+
+#[async_trait]
+impl DisputesApiServer for Disputes {
+	async fn recent(&self) -> RpcResult<Vec<DisputeInfo>> {
+		let disputes = self.request(DisputeCoordinatorMessage::RecentDisputes).await?;
+		Ok(disputes.into_iter().map(dispute_info).collect())
+	}
+
+	async fn active(&self) -> RpcResult<Vec<DisputeInfo>> {
+		let disputes = self.request(DisputeCoordinatorMessage::ActiveDisputes).await?;
+		Ok(disputes.into_iter().map(dispute_info).collect())
+	}
+
+	async fn candidate_votes(
+		&self,
+		session: SessionIndex,
+		candidate_hash: Hash,
+	) -> RpcResult<Option<CandidateVotesInfo>> {
+		let query = vec![(session, CandidateHash(candidate_hash))];
+		let votes = self
+			.request(|tx| DisputeCoordinatorMessage::InspectCandidateVotes(query, tx))
+			.await?;
+
+		Ok(votes.into_iter().next().map(|(session, candidate_hash, votes, own_votes)| {
+			candidate_votes_info(session, candidate_hash, votes, own_votes)
+		}))
+	}
+
+	async fn spam_slots(&self) -> RpcResult<Vec<SpamSlotsInfo>> {
+		let slots = self.request(DisputeCoordinatorMessage::InspectSpamSlots).await?;
+		Ok(slots
+			.into_iter()
+			.map(|(session, validator_index, occupied)| SpamSlotsInfo {
+				session,
+				validator_index,
+				occupied,
+			})
+			.collect())
+	}
+
+	async fn participation_queue(&self) -> RpcResult<ParticipationQueueInfo> {
+		let ParticipationQueueContents { running, priority, best_effort } =
+			self.request(DisputeCoordinatorMessage::InspectParticipationQueue).await?;
+
+		let queued = |queue: Vec<(SessionIndex, CandidateHash)>| {
+			queue
+				.into_iter()
+				.map(|(session, candidate_hash)| QueuedParticipationInfo {
+					session,
+					candidate_hash: candidate_hash.0,
+				})
+				.collect()
+		};
+		Ok(ParticipationQueueInfo {
+			running: running.into_iter().map(|candidate_hash| candidate_hash.0).collect(),
+			priority: queued(priority),
+			best_effort: queued(best_effort),
+		})
+	}
+}
+
+fn dispute_info(
+	(session, candidate_hash, status): (SessionIndex, CandidateHash, DisputeStatus),
+) -> DisputeInfo {
+	DisputeInfo { session, candidate_hash: candidate_hash.0, status: status.into() }
+}
+
+fn candidate_votes_info(
+	session: SessionIndex,
+	candidate_hash: CandidateHash,
+	votes: CandidateVotes,
+	own_votes: Vec<(ValidatorIndex, ValidatorId)>,
+) -> CandidateVotesInfo {
+	let own_votes = own_votes
+		.into_iter()
+		.map(|(validator_index, validator_id)| OwnVoteInfo {
+			validator_index,
+			validator_id,
+			valid: votes.valid.raw().contains_key(&validator_index),
+		})
+		.collect();
+
+	CandidateVotesInfo {
+		session,
+		candidate_hash: candidate_hash.0,
+		valid: votes
+			.valid
+			.raw()
+			.iter()
+			.map(|(validator_index, (kind, _))| VoteInfo {
+				validator_index: *validator_index,
+				kind: format!("{:?}", kind),
+			})
+			.collect(),
+		invalid: votes
+			.invalid
+			.iter()
+			.map(|(validator_index, (kind, _))| VoteInfo {
+				validator_index: *validator_index,
+				kind: format!("{:?}", kind),
+			})
+			.collect(),
+		own_votes,
+	}
+}
@@ -0,0 +1,155 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unsafe RPCs for inspecting the connectivity to other validators.
+//!
+//! They are answered by the gossip support subsystem, which only connects to validators on
+//! nodes which are authorities themselves.
+
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+};
+use polkadot_node_network_protocol::peer_set::PeerSet;
+use polkadot_node_subsystem::messages::{
+	AuthorityConnectivity, ConnectivityReport, GossipSupportMessage,
+};
+use polkadot_primitives::{AuthorityDiscoveryId, SessionIndex};
+use serde::{Deserialize, Serialize};
+
+use crate::subsystems::SubsystemRequests;
+
+/// A peer set of the parachain protocols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PeerSetInfo {
+	/// The peer set used for gossip among validators.
+	Validation,
+	/// The peer set used for communicating with collators.
+	Collation,
+}
+
+impl From<PeerSet> for PeerSetInfo {
+	fn from(peer_set: PeerSet) -> Self {
+		match peer_set {
+			PeerSet::Validation => Self::Validation,
+			PeerSet::Collation => Self::Collation,
+		}
+	}
+}
+
+/// The connectivity to an authority.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorityConnectivityInfo {
+	/// The authority discovery key of the authority.
+	pub authority: AuthorityDiscoveryId,
+	/// Whether authority discovery knew addresses of the authority on the last connection
+	/// request.
+	pub resolved: bool,
+	/// The peer sets we are connected to the authority on.
+	pub connected: Vec<PeerSetInfo>,
+	/// Whether the authority is one of our neighbors in the gossip grid.
+	pub grid_neighbor: bool,
+}
+
+impl From<AuthorityConnectivity> for AuthorityConnectivityInfo {
+	fn from(connectivity: AuthorityConnectivity) -> Self {
+		Self {
+			authority: connectivity.authority,
+			resolved: connectivity.resolved,
+			connected: connectivity.connected.into_iter().map(Into::into).collect(),
+			grid_neighbor: connectivity.grid_neighbor,
+		}
+	}
+}
+
+/// The connectivity to all authorities of the past, present and future sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityInfo {
+	/// The last session a connection request was issued for.
+	pub session: Option<SessionIndex>,
+	/// If too many authorities could not be resolved, the number of milliseconds until the
+	/// connection request is reissued at the earliest.
+	pub reconnect_in_ms: Option<u64>,
+	/// The connectivity per authority, grid neighbors and unreachable authorities first.
+	pub authorities: Vec<AuthorityConnectivityInfo>,
+}
+
+impl From<ConnectivityReport> for ConnectivityInfo {
+	fn from(report: ConnectivityReport) -> Self {
+		Self {
+			session: report.session,
+			reconnect_in_ms: report.reconnect_in.map(|d| d.as_millis() as u64),
+			authorities: report.authorities.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Gossip support inspection RPC methods.
+#[rpc(server)]
+pub trait GossipSupportApi {
+	/// The connectivity to all authorities we want to be connected to.
+	#[method(name = "gossipSupport_connectivity")]
+	async fn connectivity(&self) -> RpcResult<ConnectivityInfo>;
+
+	/// Our neighbors in the gossip grid we are not connected to on the validation peer set.
+	///
+	/// Approval checking relies on gossip reaching us through them.
+	#[method(name = "gossipSupport_unreachableGridNeighbors")]
+	async fn unreachable_grid_neighbors(&self) -> RpcResult<Vec<AuthorityConnectivityInfo>>;
+}
+
+/// Implements the [`GossipSupportApiServer`] RPC trait by querying the gossip support subsystem.
+pub struct GossipSupport {
+	requests: SubsystemRequests,
+}
+
+impl GossipSupport {
+	/// Create a new `GossipSupport` instance.
+	pub fn new(requests: SubsystemRequests) -> Self {
+		Self { requests }
+	}
+
+	async fn report(&self) -> RpcResult<ConnectivityReport> {
+		self.requests
+			.request(
+				"gossip support subsystem",
+				"gossip-support-rpc",
+				GossipSupportMessage::InspectConnectivity,
+			)
+			.await
+	}
+}
+
+#[async_trait]
+impl GossipSupportApiServer for GossipSupport {
+	async fn connectivity(&self) -> RpcResult<ConnectivityInfo> {
+		Ok(self.report().await?.into())
+	}
+
+	async fn unreachable_grid_neighbors(&self) -> RpcResult<Vec<AuthorityConnectivityInfo>> {
+		let report = self.report().await?;
+
+		Ok(report
+			.authorities
+			.into_iter()
+			.filter(|a| a.grid_neighbor && !a.is_reachable())
+			.map(Into::into)
+			.collect())
+	}
+}
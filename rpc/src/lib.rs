@@ -36,6 +36,13 @@ use sp_consensus_babe::BabeApi;
 use sp_keystore::KeystorePtr;
 use txpool_api::TransactionPool;
 
+pub mod disputes;
+pub mod gossip_support;
+pub mod network_bridge;
+pub mod subsystems;
+
+pub use subsystems::SubsystemsDeps;
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = RpcModule<()>;
 
@@ -89,6 +96,8 @@ pub struct FullDeps<C, P, SC, B> {
 	pub grandpa: GrandpaDeps<B>,
 	/// BEEFY specific dependencies.
 	pub beefy: BeefyDeps,
+	/// Dependencies of the RPCs answered by the parachain subsystems.
+	pub subsystems: SubsystemsDeps,
 }
 
 /// Instantiate all RPC extensions.
@@ -114,18 +123,31 @@ where
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::StateBackend<sp_runtime::traits::HashFor<Block>>,
 {
+	use disputes::{Disputes, DisputesApiServer};
 	use frame_rpc_system::{System, SystemApiServer};
+	use gossip_support::{GossipSupport, GossipSupportApiServer};
 	use mmr_rpc::{Mmr, MmrApiServer};
+	use network_bridge::{NetworkBridge, NetworkBridgeApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 	use sc_consensus_babe_rpc::{Babe, BabeApiServer};
 	use sc_consensus_beefy_rpc::{Beefy, BeefyApiServer};
 	use sc_consensus_grandpa_rpc::{Grandpa, GrandpaApiServer};
 	use sc_sync_state_rpc::{SyncState, SyncStateApiServer};
 	use substrate_state_trie_migration_rpc::{StateMigration, StateMigrationApiServer};
+	use subsystems::SubsystemRequests;
 
 	let mut io = RpcModule::new(());
-	let FullDeps { client, pool, select_chain, chain_spec, deny_unsafe, babe, grandpa, beefy } =
-		deps;
+	let FullDeps {
+		client,
+		pool,
+		select_chain,
+		chain_spec,
+		deny_unsafe,
+		babe,
+		grandpa,
+		beefy,
+		subsystems,
+	} = deps;
 	let BabeDeps { babe_worker_handle, keystore } = babe;
 	let GrandpaDeps {
 		shared_voter_state,
@@ -166,5 +188,10 @@ where
 		.into_rpc(),
 	)?;
 
+	let requests = SubsystemRequests::new(subsystems, deny_unsafe);
+	io.merge(Disputes::new(requests.clone()).into_rpc())?;
+	io.merge(NetworkBridge::new(requests.clone()).into_rpc())?;
+	io.merge(GossipSupport::new(requests).into_rpc())?;
+
 	Ok(io)
 }
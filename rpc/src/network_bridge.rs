@@ -0,0 +1,129 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unsafe RPCs for inspecting the traffic exchanged with peers on the parachain protocols.
+//!
+//! They are answered by the network bridge, so they are only available on nodes which run the
+//! parachain subsystems.
+
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+};
+use polkadot_node_subsystem::messages::{
+	MessageTraffic, NetworkBridgeTxMessage, PeerTraffic, Traffic,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::subsystems::SubsystemRequests;
+
+/// The number of peers returned if no limit is given.
+const DEFAULT_PEER_LIMIT: u32 = 25;
+
+/// The number of messages and bytes exchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficInfo {
+	/// The number of messages.
+	pub messages: u64,
+	/// The number of bytes, as put on the wire.
+	pub bytes: u64,
+}
+
+impl From<Traffic> for TrafficInfo {
+	fn from(traffic: Traffic) -> Self {
+		Self { messages: traffic.messages, bytes: traffic.bytes }
+	}
+}
+
+/// The traffic of one kind of message exchanged with a peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageTrafficInfo {
+	/// The kind of the messages, e.g. `approval-distribution/assignments`.
+	pub kind: String,
+	/// The traffic received from the peer.
+	pub received: TrafficInfo,
+	/// The traffic sent to the peer.
+	pub sent: TrafficInfo,
+}
+
+/// The traffic exchanged with a peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerTrafficInfo {
+	/// The peer id, base58 encoded.
+	pub peer: String,
+	/// The total traffic received from the peer.
+	pub received: TrafficInfo,
+	/// The total traffic sent to the peer.
+	pub sent: TrafficInfo,
+	/// The traffic per kind of message, ordered by the number of bytes received.
+	pub messages: Vec<MessageTrafficInfo>,
+}
+
+/// Network bridge inspection RPC methods.
+#[rpc(server)]
+pub trait NetworkBridgeApi {
+	/// The traffic exchanged with the peers which sent us the most data, ordered by the number of
+	/// bytes received from them. At most `limit` peers are returned, 25 by default.
+	#[method(name = "networkBridge_peerTraffic")]
+	async fn peer_traffic(&self, limit: Option<u32>) -> RpcResult<Vec<PeerTrafficInfo>>;
+}
+
+/// Implements the [`NetworkBridgeApiServer`] RPC trait by querying the network bridge.
+pub struct NetworkBridge {
+	requests: SubsystemRequests,
+}
+
+impl NetworkBridge {
+	/// Create a new `NetworkBridge` instance.
+	pub fn new(requests: SubsystemRequests) -> Self {
+		Self { requests }
+	}
+}
+
+#[async_trait]
+impl NetworkBridgeApiServer for NetworkBridge {
+	async fn peer_traffic(&self, limit: Option<u32>) -> RpcResult<Vec<PeerTrafficInfo>> {
+		let limit = limit.unwrap_or(DEFAULT_PEER_LIMIT) as usize;
+		let peers = self
+			.requests
+			.request("network bridge", "network-bridge-rpc", |tx| {
+				NetworkBridgeTxMessage::InspectPeerTraffic(limit, tx)
+			})
+			.await?;
+
+		Ok(peers.into_iter().map(peer_traffic_info).collect())
+	}
+}
+
+fn peer_traffic_info(traffic: PeerTraffic) -> PeerTrafficInfo {
+	PeerTrafficInfo {
+		peer: traffic.peer.to_base58(),
+		received: traffic.received.into(),
+		sent: traffic.sent.into(),
+		messages: traffic
+			.messages
+			.into_iter()
+			.map(|MessageTraffic { kind, received, sent }| MessageTrafficInfo {
+				kind: kind.to_owned(),
+				received: received.into(),
+				sent: sent.into(),
+			})
+			.collect(),
+	}
+}
@@ -0,0 +1,85 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Requests of the unsafe RPCs answered by the parachain subsystems.
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::RpcResult,
+	types::error::{CallError, ErrorObject},
+};
+use polkadot_node_subsystem::{messages::AllMessages, Handle};
+use sc_rpc::DenyUnsafe;
+
+/// The error codes of the RPCs answered by the parachain subsystems.
+pub mod error {
+	/// The node does not run the parachain subsystems, e.g. because it is a light client.
+	pub const SUBSYSTEMS_NOT_RUNNING: i32 = 9000;
+	/// The subsystem dropped the request without answering it, e.g. because the node is
+	/// shutting down.
+	pub const NO_ANSWER: i32 = 9001;
+}
+
+/// Dependencies for the RPCs answered by the parachain subsystems.
+pub struct SubsystemsDeps {
+	/// A handle to the overseer, if the node runs the parachain subsystems.
+	pub overseer_handle: Option<Handle>,
+}
+
+/// Sends the requests of unsafe RPCs to the parachain subsystems.
+#[derive(Clone)]
+pub struct SubsystemRequests {
+	overseer_handle: Option<Handle>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl SubsystemRequests {
+	/// Create a new `SubsystemRequests` instance. Without an overseer handle, all requests fail.
+	pub fn new(deps: SubsystemsDeps, deny_unsafe: DenyUnsafe) -> Self {
+		Self { overseer_handle: deps.overseer_handle, deny_unsafe }
+	}
+
+	/// Send the message built by `message` to `subsystem` and wait for its answer.
+	///
+	/// Fails if unsafe RPCs are denied, see [`error`] for the other failures.
+	pub async fn request<M, R>(
+		&self,
+		subsystem: &'static str,
+		origin: &'static str,
+		message: impl FnOnce(oneshot::Sender<R>) -> M,
+	) -> RpcResult<R>
+	where
+		M: Into<AllMessages>,
+	{
+		self.deny_unsafe.check_if_safe()?;
+
+		let mut overseer_handle = self.overseer_handle.clone().ok_or_else(|| {
+			call_error(
+				error::SUBSYSTEMS_NOT_RUNNING,
+				"the node does not run the parachain subsystems",
+			)
+		})?;
+
+		let (tx, rx) = oneshot::channel();
+		overseer_handle.send_msg(message(tx), origin).await;
+		rx.await
+			.map_err(|_| call_error(error::NO_ANSWER, &format!("the {} did not answer", subsystem)))
+	}
+}
+
+fn call_error(code: i32, message: &str) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(code, message, None::<()>)).into()
+}
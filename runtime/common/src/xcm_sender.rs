@@ -55,8 +55,8 @@ impl<T: Get<MultiAssets>> PriceForParachainDelivery for ConstantPrice<T> {
 /// The formula for the fee is based on the sum of a base fee plus a message length fee, multiplied
 /// by a specified factor. In mathematical form, it is `F * (B + encoded_msg_len * M)`.
 pub struct ExponentialPrice<A, B, M, F>(sp_std::marker::PhantomData<(A, B, M, F)>);
-impl<A: Get<AssetId>, B: Get<u128>, M: Get<u128>, F: FeeTracker> PriceForParachainDelivery
-	for ExponentialPrice<A, B, M, F>
+impl<A: Get<AssetId>, B: Get<u128>, M: Get<u128>, F: FeeTracker<Id = ParaId>>
+	PriceForParachainDelivery for ExponentialPrice<A, B, M, F>
 {
 	fn price_for_parachain_delivery(para: ParaId, msg: &Xcm<()>) -> MultiAssets {
 		let msg_fee = (msg.encoded_size() as u128).saturating_mul(M::get());
@@ -123,6 +123,8 @@ mod tests {
 
 	struct TestFeeTracker;
 	impl FeeTracker for TestFeeTracker {
+		type Id = ParaId;
+
 		fn get_fee_factor(_: ParaId) -> FixedU128 {
 			FixedU128::from_rational(101, 100)
 		}
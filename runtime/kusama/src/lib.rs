@@ -1562,6 +1562,7 @@ pub mod migrations {
 		init_state_migration::InitMigrate,
 		pallet_society::migrations::MigrateToV2<Runtime, (), past_payouts::PastPayouts>,
 		pallet_im_online::migration::v1::Migration<Runtime>,
+		parachains_hrmp::migration::v1::MigrateToV1<Runtime>,
 	);
 
 	/// Migrations that set `StorageVersion`s we missed to set.
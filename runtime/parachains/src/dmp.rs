@@ -61,9 +61,9 @@ pub use pallet::*;
 #[cfg(test)]
 mod tests;
 
-const THRESHOLD_FACTOR: u32 = 2;
-const EXPONENTIAL_FEE_BASE: FixedU128 = FixedU128::from_rational(105, 100); // 1.05
-const MESSAGE_SIZE_FEE_BASE: FixedU128 = FixedU128::from_rational(1, 1000); // 0.001
+pub(crate) const THRESHOLD_FACTOR: u32 = 2;
+pub(crate) const EXPONENTIAL_FEE_BASE: FixedU128 = FixedU128::from_rational(105, 100); // 1.05
+pub(crate) const MESSAGE_SIZE_FEE_BASE: FixedU128 = FixedU128::from_rational(1, 1000); // 0.001
 
 /// An error sending a downward message.
 #[cfg_attr(test, derive(Debug))]
@@ -344,6 +344,8 @@ impl<T: Config> Pallet<T> {
 }
 
 impl<T: Config> FeeTracker for Pallet<T> {
+	type Id = ParaId;
+
 	fn get_fee_factor(para: ParaId) -> FixedU128 {
 		DeliveryFeeFactor::<T>::get(para)
 	}
@@ -16,18 +16,24 @@
 
 use crate::{
 	configuration::{self, HostConfiguration},
-	dmp, ensure_parachain, initializer, paras,
+	dmp::{self, EXPONENTIAL_FEE_BASE, MESSAGE_SIZE_FEE_BASE, THRESHOLD_FACTOR},
+	ensure_parachain, initializer, paras, FeeTracker,
+};
+use frame_support::{
+	dispatch::Pays, pallet_prelude::*, traits::ReservableCurrency, DefaultNoBound,
 };
-use frame_support::{pallet_prelude::*, traits::ReservableCurrency, DefaultNoBound};
 use frame_system::pallet_prelude::*;
 use parity_scale_codec::{Decode, Encode};
 use polkadot_parachain::primitives::HorizontalMessages;
 use primitives::{
 	Balance, Hash, HrmpChannelId, Id as ParaId, InboundHrmpMessage, OutboundHrmpMessage,
-	SessionIndex,
+	SessionIndex, LOWEST_PUBLIC_ID,
 };
 use scale_info::TypeInfo;
-use sp_runtime::traits::{AccountIdConversion, BlakeTwo256, Hash as HashT, UniqueSaturatedInto};
+use sp_runtime::{
+	traits::{AccountIdConversion, BlakeTwo256, Hash as HashT, UniqueSaturatedInto},
+	FixedU128, Saturating,
+};
 use sp_std::{
 	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
 	fmt, mem,
@@ -50,6 +56,8 @@ pub(crate) mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migration;
+
 pub trait WeightInfo {
 	fn hrmp_init_open_channel() -> Weight;
 	fn hrmp_accept_open_channel() -> Weight;
@@ -60,6 +68,11 @@ pub trait WeightInfo {
 	fn hrmp_cancel_open_request(c: u32) -> Weight;
 	fn clean_open_channel_requests(c: u32) -> Weight;
 	fn force_open_hrmp_channel(c: u32) -> Weight;
+	fn hrmp_request_channel_update() -> Weight;
+	fn hrmp_accept_channel_update() -> Weight;
+	fn hrmp_cancel_channel_update(c: u32) -> Weight;
+	fn force_process_hrmp_update(c: u32) -> Weight;
+	fn establish_system_channels() -> Weight;
 }
 
 /// A weight info that is only suitable for testing.
@@ -93,6 +106,21 @@ impl WeightInfo for TestWeightInfo {
 	fn force_open_hrmp_channel(_: u32) -> Weight {
 		Weight::MAX
 	}
+	fn hrmp_request_channel_update() -> Weight {
+		Weight::MAX
+	}
+	fn hrmp_accept_channel_update() -> Weight {
+		Weight::MAX
+	}
+	fn hrmp_cancel_channel_update(_: u32) -> Weight {
+		Weight::MAX
+	}
+	fn force_process_hrmp_update(_: u32) -> Weight {
+		Weight::MAX
+	}
+	fn establish_system_channels() -> Weight {
+		Weight::MAX
+	}
 }
 
 /// A description of a request to open an HRMP channel.
@@ -111,6 +139,28 @@ pub struct HrmpOpenChannelRequest {
 	pub max_capacity: u32,
 	/// The maximum total size of the messages that can be pending in the channel at once.
 	pub max_total_size: u32,
+	/// The amount that the recipient supplied when confirming this request. Zero while the
+	/// request is not confirmed.
+	pub recipient_deposit: Balance,
+}
+
+/// A description of a request to update the parameters of an open HRMP channel.
+#[derive(Encode, Decode, TypeInfo)]
+pub struct HrmpChannelUpdateRequest {
+	/// The participant of the channel that made the request.
+	pub initiator: ParaId,
+	/// Indicates if this request was confirmed by the other participant of the channel.
+	pub confirmed: bool,
+	/// The maximum number of messages that can be pending in the channel at once.
+	pub max_capacity: u32,
+	/// The maximum message size that could be put into the channel.
+	pub max_message_size: u32,
+	/// The maximum total size of the messages that can be pending in the channel at once.
+	pub max_total_size: u32,
+	/// The deposit the sender holds for the channel once the update is enacted.
+	pub sender_deposit: Balance,
+	/// The deposit the recipient holds for the channel once the update is enacted.
+	pub recipient_deposit: Balance,
 }
 
 /// A metadata of an HRMP channel.
@@ -234,8 +284,12 @@ impl fmt::Debug for OutboundHrmpAcceptanceErr {
 pub mod pallet {
 	use super::*;
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
@@ -276,6 +330,19 @@ pub mod pallet {
 		/// An HRMP channel was opened via Root origin.
 		/// `[sender, recipient, proposed_max_capacity, proposed_max_message_size]`
 		HrmpChannelForceOpened(ParaId, ParaId, u32, u32),
+		/// An update of the parameters of an HRMP channel was requested.
+		/// `[by_parachain, channel_id, proposed_max_capacity, proposed_max_message_size]`
+		ChannelUpdateRequested(ParaId, HrmpChannelId, u32, u32),
+		/// An HRMP channel update request was accepted. `[by_parachain, channel_id]`
+		ChannelUpdateAccepted(ParaId, HrmpChannelId),
+		/// An HRMP channel update request was canceled. `[by_parachain, channel_id]`
+		ChannelUpdateCanceled(ParaId, HrmpChannelId),
+		/// The parameters of an HRMP channel were updated.
+		/// `[channel_id, max_capacity, max_message_size]`
+		ChannelUpdated(HrmpChannelId, u32, u32),
+		/// A deposit-free HRMP channel between system parachains was opened.
+		/// `[sender, recipient, proposed_max_capacity, proposed_max_message_size]`
+		HrmpSystemChannelOpened(ParaId, ParaId, u32, u32),
 	}
 
 	#[pallet::error]
@@ -318,6 +385,35 @@ pub mod pallet {
 		OpenHrmpChannelAlreadyConfirmed,
 		/// The provided witness data is wrong.
 		WrongWitness,
+		/// The origin tries to update a channel where it is neither the sender nor the recipient.
+		UpdateHrmpChannelUnauthorized,
+		/// The channel to be updated doesn't exist.
+		UpdateHrmpChannelDoesntExist,
+		/// The channel to be updated is about to be closed.
+		UpdateHrmpChannelCloseUnderway,
+		/// There is already a request to update the same channel.
+		UpdateHrmpChannelAlreadyRequested,
+		/// The requested capacity is zero.
+		UpdateHrmpChannelZeroCapacity,
+		/// The requested capacity exceeds the global limit.
+		UpdateHrmpChannelCapacityExceedsLimit,
+		/// The requested maximum message size is 0.
+		UpdateHrmpChannelZeroMessageSize,
+		/// The requested maximum message size exceeds the global limit.
+		UpdateHrmpChannelMessageSizeExceedsLimit,
+		/// The channel update request doesn't exist.
+		HrmpChannelUpdateDoesntExist,
+		/// The origin tries to accept a channel update request it made itself, or one for a
+		/// channel where it is neither the sender nor the recipient.
+		AcceptHrmpChannelUpdateUnauthorized,
+		/// The channel update request is already confirmed.
+		AcceptHrmpChannelUpdateAlreadyConfirmed,
+		/// Canceling is requested by neither the sender nor recipient of the channel.
+		CancelHrmpChannelUpdateUnauthorized,
+		/// Cannot cancel a channel update request because it is already confirmed.
+		CancelHrmpChannelUpdateAlreadyConfirmed,
+		/// Deposit-free channels can only be established between system parachains.
+		EstablishHrmpChannelNotSystemChain,
 	}
 
 	/// The set of pending HRMP open channel requests.
@@ -365,6 +461,22 @@ pub mod pallet {
 	pub type HrmpCloseChannelRequestsList<T: Config> =
 		StorageValue<_, Vec<HrmpChannelId>, ValueQuery>;
 
+	/// The set of pending requests to update the parameters of open HRMP channels. Confirmed
+	/// requests are enacted during the session change.
+	///
+	/// The set is accompanied by a list for iteration.
+	///
+	/// Invariants:
+	/// - There are no channels that exists in list but not in the set and vice versa.
+	/// - Each channel in the set is present in `HrmpChannels`.
+	#[pallet::storage]
+	pub type HrmpChannelUpdateRequests<T: Config> =
+		StorageMap<_, Twox64Concat, HrmpChannelId, HrmpChannelUpdateRequest>;
+
+	#[pallet::storage]
+	pub type HrmpChannelUpdateRequestsList<T: Config> =
+		StorageValue<_, Vec<HrmpChannelId>, ValueQuery>;
+
 	/// The HRMP watermark associated with each para.
 	/// Invariant:
 	/// - each para `P` used here as a key should satisfy `Paras::is_valid_para(P)` within a session.
@@ -421,6 +533,20 @@ pub mod pallet {
 	pub type HrmpChannelDigests<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, Vec<(T::BlockNumber, Vec<ParaId>)>, ValueQuery>;
 
+	/// Initialization value for the delivery fee factor of a channel.
+	#[pallet::type_value]
+	pub fn InitialFactor() -> FixedU128 {
+		FixedU128::from_u32(1)
+	}
+
+	/// The number to multiply the base delivery fee of messages sent over a channel by.
+	///
+	/// Rises while the channel is congested and decays back to one otherwise. Removed with the
+	/// channel.
+	#[pallet::storage]
+	pub(crate) type DeliveryFeeFactor<T: Config> =
+		StorageMap<_, Twox64Concat, HrmpChannelId, FixedU128, ValueQuery, InitialFactor>;
+
 	/// Preopen the given HRMP channels.
 	///
 	/// The values in the tuple corresponds to
@@ -541,8 +667,7 @@ pub mod pallet {
 		#[pallet::weight(<T as Config>::WeightInfo::force_process_hrmp_open(*_channels))]
 		pub fn force_process_hrmp_open(origin: OriginFor<T>, _channels: u32) -> DispatchResult {
 			ensure_root(origin)?;
-			let host_config = configuration::Pallet::<T>::config();
-			Self::process_hrmp_open_channel_requests(&host_config);
+			Self::process_hrmp_open_channel_requests();
 			Ok(())
 		}
 
@@ -628,11 +753,167 @@ pub mod pallet {
 
 			Ok(Some(<T as Config>::WeightInfo::force_open_hrmp_channel(cancel_request)).into())
 		}
+
+		/// Request an update of the parameters of an open channel. The origin must be either the
+		/// sender or the recipient of the channel.
+		///
+		/// - `proposed_max_capacity` - specifies how many messages can be in the channel at once.
+		/// - `proposed_max_message_size` - specifies the maximum size of the messages.
+		///
+		/// These numbers are a subject to the relay-chain configuration limits. The deposits held
+		/// for the channel are brought in line with the current configuration as well: the origin
+		/// tops its deposit up right away, while a surplus is refunded once the update is enacted.
+		///
+		/// The update has to be accepted by the other participant of the channel and is only
+		/// enacted on a session change. The messages pending in the channel are kept, and the update
+		/// is postponed for as long as they would not fit into the updated channel.
+		#[pallet::call_index(8)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_request_channel_update())]
+		pub fn hrmp_request_channel_update(
+			origin: OriginFor<T>,
+			channel_id: HrmpChannelId,
+			proposed_max_capacity: u32,
+			proposed_max_message_size: u32,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			Self::request_channel_update(
+				origin,
+				channel_id.clone(),
+				proposed_max_capacity,
+				proposed_max_message_size,
+			)?;
+			Self::deposit_event(Event::ChannelUpdateRequested(
+				origin,
+				channel_id,
+				proposed_max_capacity,
+				proposed_max_message_size,
+			));
+			Ok(())
+		}
+
+		/// Accept a pending request to update the parameters of a channel. The origin must be the
+		/// participant of the channel that did not make the request.
+		///
+		/// The update will be enacted only on the next session boundary.
+		#[pallet::call_index(9)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_accept_channel_update())]
+		pub fn hrmp_accept_channel_update(
+			origin: OriginFor<T>,
+			channel_id: HrmpChannelId,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			Self::accept_channel_update(origin, channel_id.clone())?;
+			Self::deposit_event(Event::ChannelUpdateAccepted(origin, channel_id));
+			Ok(())
+		}
+
+		/// This cancels a pending channel update request. It can be canceled by either of the
+		/// sender or the recipient of the channel. The origin must be either of those.
+		///
+		/// The cancellation happens immediately. It is not possible to cancel the request if it is
+		/// already accepted.
+		///
+		/// Total number of update requests (i.e. `HrmpChannelUpdateRequestsList`) must be provided
+		/// as witness data.
+		#[pallet::call_index(10)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_cancel_channel_update(*update_requests))]
+		pub fn hrmp_cancel_channel_update(
+			origin: OriginFor<T>,
+			channel_id: HrmpChannelId,
+			update_requests: u32,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			ensure!(
+				HrmpChannelUpdateRequestsList::<T>::decode_len().unwrap_or_default() as u32 <=
+					update_requests,
+				Error::<T>::WrongWitness
+			);
+			Self::cancel_channel_update(origin, channel_id.clone())?;
+			Self::deposit_event(Event::ChannelUpdateCanceled(origin, channel_id));
+			Ok(())
+		}
+
+		/// Force process HRMP channel update requests.
+		///
+		/// If there are pending HRMP channel update requests, you can use this function to process
+		/// all of those requests immediately.
+		///
+		/// Total number of channel update requests must be provided as witness data of weighing.
+		#[pallet::call_index(11)]
+		#[pallet::weight(<T as Config>::WeightInfo::force_process_hrmp_update(*_channels))]
+		pub fn force_process_hrmp_update(origin: OriginFor<T>, _channels: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::process_hrmp_channel_update_requests();
+			Ok(())
+		}
+
+		/// Open the channels from `para_a` to `para_b` and back, given that both are system
+		/// parachains. The origin can be any signed account, and the transaction is free if it
+		/// succeeds.
+		///
+		/// No deposits are required from either party, and the `max_capacity` and
+		/// `max_message_size` of the channels are the maximums allowed by the configuration.
+		///
+		/// A direction which is already open, or for which an accepted open request exists, is
+		/// skipped. A pending open request is canceled and replaced. The channels are opened on
+		/// the next session change.
+		#[pallet::call_index(12)]
+		#[pallet::weight(<T as Config>::WeightInfo::establish_system_channels())]
+		pub fn establish_system_channels(
+			origin: OriginFor<T>,
+			para_a: ParaId,
+			para_b: ParaId,
+		) -> DispatchResultWithPostInfo {
+			let _caller = ensure_signed(origin)?;
+			ensure!(
+				Self::is_system_chain(para_a) && Self::is_system_chain(para_b),
+				Error::<T>::EstablishHrmpChannelNotSystemChain,
+			);
+			ensure!(para_a != para_b, Error::<T>::OpenHrmpChannelToSelf);
+
+			let to_open = [(para_a, para_b), (para_b, para_a)]
+				.into_iter()
+				.map(|(sender, recipient)| HrmpChannelId { sender, recipient })
+				.filter(|channel_id| {
+					!HrmpChannels::<T>::contains_key(channel_id) &&
+						!HrmpOpenChannelRequests::<T>::get(channel_id)
+							.map_or(false, |request| request.confirmed)
+				})
+				.collect::<Vec<_>>();
+			ensure!(!to_open.is_empty(), Error::<T>::OpenHrmpChannelAlreadyExists);
+
+			let config = <configuration::Pallet<T>>::config();
+			let max_capacity = config.hrmp_channel_max_capacity;
+			let max_message_size = config.hrmp_channel_max_message_size;
+			for channel_id in to_open {
+				let (sender, recipient) = (channel_id.sender, channel_id.recipient);
+				if HrmpOpenChannelRequests::<T>::contains_key(&channel_id) {
+					Self::cancel_open_request(sender, channel_id)?;
+				}
+
+				Self::do_init_open_channel(
+					&config,
+					sender,
+					recipient,
+					max_capacity,
+					max_message_size,
+					0,
+				)?;
+				Self::do_accept_open_channel(&config, recipient, sender, 0)?;
+				Self::deposit_event(Event::HrmpSystemChannelOpened(
+					sender,
+					recipient,
+					max_capacity,
+					max_message_size,
+				));
+			}
+
+			Ok(Pays::No.into())
+		}
 	}
 }
 
 fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32, u32)]) {
-	let host_config = configuration::Pallet::<T>::config();
 	for &(sender, recipient, max_capacity, max_message_size) in preopen_hrmp_channels {
 		if let Err(err) =
 			preopen_hrmp_channel::<T>(sender, recipient, max_capacity, max_message_size)
@@ -640,7 +921,7 @@ fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32,
 			panic!("failed to initialize the genesis storage: {:?}", err);
 		}
 	}
-	<Pallet<T>>::process_hrmp_open_channel_requests(&host_config);
+	<Pallet<T>>::process_hrmp_open_channel_requests();
 }
 
 fn preopen_hrmp_channel<T: Config>(
@@ -666,27 +947,28 @@ impl<T: Config> Pallet<T> {
 
 	/// Called by the initializer to note that a new session has started.
 	pub(crate) fn initializer_on_new_session(
-		notification: &initializer::SessionChangeNotification<T::BlockNumber>,
+		_notification: &initializer::SessionChangeNotification<T::BlockNumber>,
 		outgoing_paras: &[ParaId],
 	) -> Weight {
-		let w1 = Self::perform_outgoing_para_cleanup(&notification.prev_config, outgoing_paras);
-		Self::process_hrmp_open_channel_requests(&notification.prev_config);
+		let w1 = Self::perform_outgoing_para_cleanup(outgoing_paras);
+		Self::process_hrmp_open_channel_requests();
 		Self::process_hrmp_close_channel_requests();
+		let update_requests =
+			HrmpChannelUpdateRequestsList::<T>::decode_len().unwrap_or_default() as u32;
+		Self::process_hrmp_channel_update_requests();
 		w1.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_open(
 			outgoing_paras.len() as u32
 		))
 		.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_close(
 			outgoing_paras.len() as u32
 		))
+		.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_update(update_requests))
 	}
 
 	/// Iterate over all paras that were noted for offboarding and remove all the data
 	/// associated with them.
-	fn perform_outgoing_para_cleanup(
-		config: &HostConfiguration<T::BlockNumber>,
-		outgoing: &[ParaId],
-	) -> Weight {
-		let mut w = Self::clean_open_channel_requests(config, outgoing);
+	fn perform_outgoing_para_cleanup(outgoing: &[ParaId]) -> Weight {
+		let mut w = Self::clean_open_channel_requests(outgoing);
 		for outgoing_para in outgoing {
 			Self::clean_hrmp_after_outgoing(outgoing_para);
 
@@ -707,10 +989,7 @@ impl<T: Config> Pallet<T> {
 	// Go over the HRMP open channel requests and remove all in which offboarding paras participate.
 	//
 	// This will also perform the refunds for the counterparty if it doesn't offboard.
-	pub(crate) fn clean_open_channel_requests(
-		config: &HostConfiguration<T::BlockNumber>,
-		outgoing: &[ParaId],
-	) -> Weight {
+	pub(crate) fn clean_open_channel_requests(outgoing: &[ParaId]) -> Weight {
 		// First collect all the channel ids of the open requests in which there is at least one
 		// party presents in the outgoing list.
 		//
@@ -741,16 +1020,15 @@ impl<T: Config> Pallet<T> {
 				);
 			}
 
-			// If the request was confirmed, then it means it was confirmed in the finished session.
-			// Therefore, the config's hrmp_recipient_deposit represents the actual value of the
-			// deposit.
+			// If the request was confirmed, then the recipient holds the deposit it supplied when
+			// confirming, which is recorded in the request.
 			//
 			// We still want to refund the deposit only if the para is not being offboarded.
 			if req_data.confirmed {
 				if !outgoing.contains(&req_id.recipient) {
 					T::Currency::unreserve(
 						&req_id.recipient.into_account_truncating(),
-						config.hrmp_recipient_deposit.unique_saturated_into(),
+						req_data.recipient_deposit.unique_saturated_into(),
 					);
 				}
 				Self::decrease_accepted_channel_request_count(req_id.recipient);
@@ -784,7 +1062,7 @@ impl<T: Config> Pallet<T> {
 	///
 	/// - prune the stale requests
 	/// - enact the confirmed requests
-	fn process_hrmp_open_channel_requests(config: &HostConfiguration<T::BlockNumber>) {
+	fn process_hrmp_open_channel_requests() {
 		let mut open_req_channels = HrmpOpenChannelRequestsList::<T>::get();
 		if open_req_channels.is_empty() {
 			return
@@ -813,7 +1091,7 @@ impl<T: Config> Pallet<T> {
 						&channel_id,
 						HrmpChannel {
 							sender_deposit: request.sender_deposit,
-							recipient_deposit: config.hrmp_recipient_deposit,
+							recipient_deposit: request.recipient_deposit,
 							max_capacity: request.max_capacity,
 							max_total_size: request.max_total_size,
 							max_message_size: request.max_message_size,
@@ -855,13 +1133,81 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// Iterate over all channel update requests and enact the confirmed ones.
+	///
+	/// A confirmed update is postponed if the messages pending in the channel would not fit into
+	/// the updated channel, i.e. until the recipient has processed enough of them.
+	fn process_hrmp_channel_update_requests() {
+		let mut update_req_channels = HrmpChannelUpdateRequestsList::<T>::get();
+		if update_req_channels.is_empty() {
+			return
+		}
+
+		update_req_channels.retain(|channel_id| {
+			let request = HrmpChannelUpdateRequests::<T>::get(channel_id).expect(
+				"can't be `None` due to the invariant that the list contains the same items as the set; qed",
+			);
+			if !request.confirmed {
+				return true
+			}
+
+			let mut channel = HrmpChannels::<T>::get(channel_id).expect(
+				"can't be `None` due to the invariant that update requests are removed with their channel; qed",
+			);
+			if channel.msg_count > request.max_capacity ||
+				channel.total_size > request.max_total_size
+			{
+				return true
+			}
+
+			// The top-ups were reserved when the request was made and accepted, only the
+			// surplus of a deposit that got smaller is left to be returned.
+			T::Currency::unreserve(
+				&channel_id.sender.into_account_truncating(),
+				channel
+					.sender_deposit
+					.saturating_sub(request.sender_deposit)
+					.unique_saturated_into(),
+			);
+			T::Currency::unreserve(
+				&channel_id.recipient.into_account_truncating(),
+				channel
+					.recipient_deposit
+					.saturating_sub(request.recipient_deposit)
+					.unique_saturated_into(),
+			);
+
+			channel.max_capacity = request.max_capacity;
+			channel.max_total_size = request.max_total_size;
+			channel.max_message_size = request.max_message_size;
+			channel.sender_deposit = request.sender_deposit;
+			channel.recipient_deposit = request.recipient_deposit;
+			HrmpChannels::<T>::insert(channel_id, channel);
+			HrmpChannelUpdateRequests::<T>::remove(channel_id);
+
+			Self::deposit_event(Event::ChannelUpdated(
+				channel_id.clone(),
+				request.max_capacity,
+				request.max_message_size,
+			));
+			false
+		});
+
+		HrmpChannelUpdateRequestsList::<T>::put(update_req_channels);
+	}
+
 	/// Close and remove the designated HRMP channel.
 	///
-	/// This includes returning the deposits.
+	/// This includes returning the deposits, as well as the deposit top-ups of a pending update
+	/// request for the channel.
 	///
 	/// This function is idempotent, meaning that after the first application it should have no
 	/// effect (i.e. it won't return the deposits twice).
 	fn close_hrmp_channel(channel_id: &HrmpChannelId) {
+		if let Some(channel) = HrmpChannels::<T>::get(channel_id) {
+			Self::remove_channel_update_request(channel_id, &channel);
+		}
+
 		if let Some(HrmpChannel { sender_deposit, recipient_deposit, .. }) =
 			HrmpChannels::<T>::take(channel_id)
 		{
@@ -876,6 +1222,7 @@ impl<T: Config> Pallet<T> {
 		}
 
 		HrmpChannelContents::<T>::remove(channel_id);
+		DeliveryFeeFactor::<T>::remove(channel_id);
 
 		HrmpEgressChannelsIndex::<T>::mutate(&channel_id.sender, |v| {
 			if let Ok(i) = v.binary_search(&channel_id.recipient) {
@@ -1048,14 +1395,18 @@ impl<T: Config> Pallet<T> {
 			}
 
 			// update the channel metadata.
-			HrmpChannels::<T>::mutate(&channel_id, |channel| {
-				if let Some(ref mut channel) = channel {
+			let congested = HrmpChannels::<T>::mutate(&channel_id, |channel| {
+				channel.as_mut().map(|channel| {
 					channel.msg_count -= pruned_cnt as u32;
 					channel.total_size -= pruned_size as u32;
-				}
+					Self::is_congested(channel)
+				})
 			});
+			if congested == Some(false) {
+				Self::decrement_fee_factor(&channel_id);
+			}
 
-			weight += T::DbWeight::get().reads_writes(2, 2);
+			weight += T::DbWeight::get().reads_writes(3, 3);
 		}
 
 		HrmpWatermarks::<T>::insert(&recipient, new_hrmp_watermark);
@@ -1089,6 +1440,13 @@ impl<T: Config> Pallet<T> {
 			channel.msg_count += 1;
 			channel.total_size += inbound.data.len() as u32;
 
+			if Self::is_congested(&channel) {
+				let message_size_factor =
+					FixedU128::from_u32(inbound.data.len().saturating_div(1024) as u32)
+						.saturating_mul(MESSAGE_SIZE_FEE_BASE);
+				Self::increment_fee_factor(&channel_id, message_size_factor);
+			}
+
 			// compute the new MQC head of the channel
 			let prev_head = channel.mqc_head.unwrap_or(Default::default());
 			let new_head = BlakeTwo256::hash_of(&(
@@ -1125,12 +1483,49 @@ impl<T: Config> Pallet<T> {
 			}
 			HrmpChannelDigests::<T>::insert(&channel_id.recipient, recipient_digest);
 
-			weight += T::DbWeight::get().reads_writes(2, 2);
+			weight += T::DbWeight::get().reads_writes(3, 3);
 		}
 
 		weight
 	}
 
+	/// Whether the messages pending in `channel` take up more than a `THRESHOLD_FACTOR`th of its
+	/// capacity, either by count or by size.
+	fn is_congested(channel: &HrmpChannel) -> bool {
+		channel.msg_count > channel.max_capacity / THRESHOLD_FACTOR ||
+			channel.total_size > channel.max_total_size / THRESHOLD_FACTOR
+	}
+
+	/// Raise the delivery fee factor of a channel by a multiplicative factor and store the
+	/// resulting value.
+	///
+	/// Returns the new delivery fee factor after the increment.
+	pub(crate) fn increment_fee_factor(
+		channel_id: &HrmpChannelId,
+		message_size_factor: FixedU128,
+	) -> FixedU128 {
+		DeliveryFeeFactor::<T>::mutate(channel_id, |f| {
+			*f = f.saturating_mul(EXPONENTIAL_FEE_BASE + message_size_factor);
+			*f
+		})
+	}
+
+	/// Reduce the delivery fee factor of a channel by a multiplicative factor and store the
+	/// resulting value, without going below the initial value of 1.
+	///
+	/// The factor is removed from storage once it is back at its initial value, so that idle
+	/// channels don't occupy any storage for it.
+	///
+	/// Returns the new delivery fee factor after the decrement.
+	pub(crate) fn decrement_fee_factor(channel_id: &HrmpChannelId) -> FixedU128 {
+		DeliveryFeeFactor::<T>::mutate_exists(channel_id, |f| {
+			let initial = InitialFactor::get();
+			let new = initial.max(f.unwrap_or(initial) / EXPONENTIAL_FEE_BASE);
+			*f = (new > initial).then_some(new);
+			new
+		})
+	}
+
 	/// Initiate opening a channel from a parachain to a given recipient with given channel
 	/// parameters.
 	///
@@ -1141,6 +1536,26 @@ impl<T: Config> Pallet<T> {
 		recipient: ParaId,
 		proposed_max_capacity: u32,
 		proposed_max_message_size: u32,
+	) -> DispatchResult {
+		let config = <configuration::Pallet<T>>::config();
+		Self::do_init_open_channel(
+			&config,
+			origin,
+			recipient,
+			proposed_max_capacity,
+			proposed_max_message_size,
+			config.hrmp_sender_deposit,
+		)
+	}
+
+	/// Initiate opening a channel, reserving `sender_deposit` from the sender.
+	fn do_init_open_channel(
+		config: &HostConfiguration<T::BlockNumber>,
+		origin: ParaId,
+		recipient: ParaId,
+		proposed_max_capacity: u32,
+		proposed_max_message_size: u32,
+		sender_deposit: Balance,
 	) -> DispatchResult {
 		ensure!(origin != recipient, Error::<T>::OpenHrmpChannelToSelf);
 		ensure!(
@@ -1148,7 +1563,6 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::OpenHrmpChannelInvalidRecipient,
 		);
 
-		let config = <configuration::Pallet<T>>::config();
 		ensure!(proposed_max_capacity > 0, Error::<T>::OpenHrmpChannelZeroCapacity);
 		ensure!(
 			proposed_max_capacity <= config.hrmp_channel_max_capacity,
@@ -1184,7 +1598,7 @@ impl<T: Config> Pallet<T> {
 
 		T::Currency::reserve(
 			&origin.into_account_truncating(),
-			config.hrmp_sender_deposit.unique_saturated_into(),
+			sender_deposit.unique_saturated_into(),
 		)?;
 
 		// mutating storage directly now -- shall not bail henceforth.
@@ -1195,10 +1609,11 @@ impl<T: Config> Pallet<T> {
 			HrmpOpenChannelRequest {
 				confirmed: false,
 				_age: 0,
-				sender_deposit: config.hrmp_sender_deposit,
+				sender_deposit,
 				max_capacity: proposed_max_capacity,
 				max_message_size: proposed_max_message_size,
 				max_total_size: config.hrmp_channel_max_total_size,
+				recipient_deposit: 0,
 			},
 		);
 		HrmpOpenChannelRequestsList::<T>::append(channel_id);
@@ -1215,7 +1630,7 @@ impl<T: Config> Pallet<T> {
 			.encode()
 		};
 		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Pallet<T>>::queue_downward_message(&config, recipient, notification_bytes)
+			<dmp::Pallet<T>>::queue_downward_message(config, recipient, notification_bytes)
 		{
 			// this should never happen unless the max downward message size is configured to an
 			// jokingly small number.
@@ -1234,6 +1649,17 @@ impl<T: Config> Pallet<T> {
 	/// Basically the same as [`hrmp_accept_open_channel`](Pallet::hrmp_accept_open_channel) but
 	/// intended for calling directly from other pallets rather than dispatched.
 	pub fn accept_open_channel(origin: ParaId, sender: ParaId) -> DispatchResult {
+		let config = <configuration::Pallet<T>>::config();
+		Self::do_accept_open_channel(&config, origin, sender, config.hrmp_recipient_deposit)
+	}
+
+	/// Accept a pending open channel request, reserving `recipient_deposit` from the recipient.
+	fn do_accept_open_channel(
+		config: &HostConfiguration<T::BlockNumber>,
+		origin: ParaId,
+		sender: ParaId,
+		recipient_deposit: Balance,
+	) -> DispatchResult {
 		let channel_id = HrmpChannelId { sender, recipient: origin };
 		let mut channel_req = HrmpOpenChannelRequests::<T>::get(&channel_id)
 			.ok_or(Error::<T>::AcceptHrmpChannelDoesntExist)?;
@@ -1241,7 +1667,6 @@ impl<T: Config> Pallet<T> {
 
 		// check if by accepting this open channel request, this parachain would exceed the
 		// number of inbound channels.
-		let config = <configuration::Pallet<T>>::config();
 		let channel_num_limit = if <paras::Pallet<T>>::is_parathread(origin) {
 			config.hrmp_max_parathread_inbound_channels
 		} else {
@@ -1256,12 +1681,13 @@ impl<T: Config> Pallet<T> {
 
 		T::Currency::reserve(
 			&origin.into_account_truncating(),
-			config.hrmp_recipient_deposit.unique_saturated_into(),
+			recipient_deposit.unique_saturated_into(),
 		)?;
 
 		// persist the updated open channel request and then increment the number of accepted
 		// channels.
 		channel_req.confirmed = true;
+		channel_req.recipient_deposit = recipient_deposit;
 		HrmpOpenChannelRequests::<T>::insert(&channel_id, channel_req);
 		HrmpAcceptedChannelRequestCount::<T>::insert(&origin, accepted_cnt + 1);
 
@@ -1272,7 +1698,7 @@ impl<T: Config> Pallet<T> {
 			VersionedXcm::from(xcm).encode()
 		};
 		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Pallet<T>>::queue_downward_message(&config, sender, notification_bytes)
+			<dmp::Pallet<T>>::queue_downward_message(config, sender, notification_bytes)
 		{
 			// this should never happen unless the max downward message size is configured to an
 			// jokingly small number.
@@ -1364,6 +1790,148 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Request an update of the parameters of an open channel.
+	///
+	/// Basically the same as [`hrmp_request_channel_update`](Pallet::hrmp_request_channel_update)
+	/// but intended for calling directly from other pallets rather than dispatched.
+	pub fn request_channel_update(
+		origin: ParaId,
+		channel_id: HrmpChannelId,
+		proposed_max_capacity: u32,
+		proposed_max_message_size: u32,
+	) -> DispatchResult {
+		ensure!(channel_id.is_participant(origin), Error::<T>::UpdateHrmpChannelUnauthorized);
+		let channel =
+			HrmpChannels::<T>::get(&channel_id).ok_or(Error::<T>::UpdateHrmpChannelDoesntExist)?;
+		ensure!(
+			HrmpCloseChannelRequests::<T>::get(&channel_id).is_none(),
+			Error::<T>::UpdateHrmpChannelCloseUnderway,
+		);
+		ensure!(
+			HrmpChannelUpdateRequests::<T>::get(&channel_id).is_none(),
+			Error::<T>::UpdateHrmpChannelAlreadyRequested,
+		);
+
+		let config = <configuration::Pallet<T>>::config();
+		ensure!(proposed_max_capacity > 0, Error::<T>::UpdateHrmpChannelZeroCapacity);
+		ensure!(
+			proposed_max_capacity <= config.hrmp_channel_max_capacity,
+			Error::<T>::UpdateHrmpChannelCapacityExceedsLimit,
+		);
+		ensure!(proposed_max_message_size > 0, Error::<T>::UpdateHrmpChannelZeroMessageSize);
+		ensure!(
+			proposed_max_message_size <= config.hrmp_channel_max_message_size,
+			Error::<T>::UpdateHrmpChannelMessageSizeExceedsLimit,
+		);
+
+		let request = HrmpChannelUpdateRequest {
+			initiator: origin,
+			confirmed: false,
+			max_capacity: proposed_max_capacity,
+			max_message_size: proposed_max_message_size,
+			max_total_size: config.hrmp_channel_max_total_size,
+			sender_deposit: config.hrmp_sender_deposit,
+			recipient_deposit: config.hrmp_recipient_deposit,
+		};
+
+		T::Currency::reserve(
+			&origin.into_account_truncating(),
+			Self::deposit_top_up(&channel_id, &channel, &request, origin).unique_saturated_into(),
+		)?;
+
+		HrmpChannelUpdateRequests::<T>::insert(&channel_id, request);
+		HrmpChannelUpdateRequestsList::<T>::append(channel_id);
+
+		Ok(())
+	}
+
+	/// Accept a pending request to update the parameters of a channel.
+	///
+	/// Basically the same as [`hrmp_accept_channel_update`](Pallet::hrmp_accept_channel_update)
+	/// but intended for calling directly from other pallets rather than dispatched.
+	pub fn accept_channel_update(origin: ParaId, channel_id: HrmpChannelId) -> DispatchResult {
+		let mut request = HrmpChannelUpdateRequests::<T>::get(&channel_id)
+			.ok_or(Error::<T>::HrmpChannelUpdateDoesntExist)?;
+		ensure!(
+			channel_id.is_participant(origin) && origin != request.initiator,
+			Error::<T>::AcceptHrmpChannelUpdateUnauthorized,
+		);
+		ensure!(!request.confirmed, Error::<T>::AcceptHrmpChannelUpdateAlreadyConfirmed);
+
+		let channel = HrmpChannels::<T>::get(&channel_id).expect(
+			"can't be `None` due to the invariant that update requests are removed with their channel; qed",
+		);
+		T::Currency::reserve(
+			&origin.into_account_truncating(),
+			Self::deposit_top_up(&channel_id, &channel, &request, origin).unique_saturated_into(),
+		)?;
+
+		request.confirmed = true;
+		HrmpChannelUpdateRequests::<T>::insert(&channel_id, request);
+
+		Ok(())
+	}
+
+	fn cancel_channel_update(origin: ParaId, channel_id: HrmpChannelId) -> DispatchResult {
+		ensure!(channel_id.is_participant(origin), Error::<T>::CancelHrmpChannelUpdateUnauthorized);
+
+		let request = HrmpChannelUpdateRequests::<T>::get(&channel_id)
+			.ok_or(Error::<T>::HrmpChannelUpdateDoesntExist)?;
+		ensure!(!request.confirmed, Error::<T>::CancelHrmpChannelUpdateAlreadyConfirmed);
+
+		let channel = HrmpChannels::<T>::get(&channel_id).expect(
+			"can't be `None` due to the invariant that update requests are removed with their channel; qed",
+		);
+		Self::remove_channel_update_request(&channel_id, &channel);
+
+		Ok(())
+	}
+
+	/// Whether `para` is a system parachain, i.e. one with an ID below [`LOWEST_PUBLIC_ID`], which
+	/// can only be registered by governance.
+	fn is_system_chain(para: ParaId) -> bool {
+		para < LOWEST_PUBLIC_ID
+	}
+
+	/// The amount by which the deposit `para` holds for `channel` has to grow for `request`.
+	fn deposit_top_up(
+		channel_id: &HrmpChannelId,
+		channel: &HrmpChannel,
+		request: &HrmpChannelUpdateRequest,
+		para: ParaId,
+	) -> Balance {
+		if para == channel_id.sender {
+			request.sender_deposit.saturating_sub(channel.sender_deposit)
+		} else {
+			request.recipient_deposit.saturating_sub(channel.recipient_deposit)
+		}
+	}
+
+	/// Remove the pending update request for the given channel, if any, returning the deposit
+	/// top-ups reserved for it so far.
+	fn remove_channel_update_request(channel_id: &HrmpChannelId, channel: &HrmpChannel) {
+		let request = match HrmpChannelUpdateRequests::<T>::take(channel_id) {
+			Some(request) => request,
+			None => return,
+		};
+		HrmpChannelUpdateRequestsList::<T>::mutate(|update_req_channels| {
+			if let Some(pos) = update_req_channels.iter().position(|x| x == channel_id) {
+				update_req_channels.swap_remove(pos);
+			}
+		});
+
+		// The initiator tops up when making the request, the other participant when accepting it.
+		for para in [channel_id.sender, channel_id.recipient] {
+			if para == request.initiator || request.confirmed {
+				T::Currency::unreserve(
+					&para.into_account_truncating(),
+					Self::deposit_top_up(channel_id, channel, &request, para)
+						.unique_saturated_into(),
+				);
+			}
+		}
+	}
+
 	/// Returns the list of MQC heads for the inbound channels of the given recipient para paired
 	/// with the sender para ids. This vector is sorted ascending by the para id and doesn't contain
 	/// multiple entries with the same sender.
@@ -1489,6 +2057,16 @@ impl<T: Config> Pallet<T> {
 			HrmpCloseChannelRequestsList::<T>::get().into_iter().collect::<BTreeSet<_>>(),
 		);
 
+		assert_eq!(
+			HrmpChannelUpdateRequests::<T>::iter().map(|(k, _)| k).collect::<BTreeSet<_>>(),
+			HrmpChannelUpdateRequestsList::<T>::get().into_iter().collect::<BTreeSet<_>>(),
+		);
+		// Only open channels can be updated, and only by their participants.
+		for (channel_id, request) in HrmpChannelUpdateRequests::<T>::iter() {
+			assert!(HrmpChannels::<T>::contains_key(&channel_id));
+			assert!(channel_id.is_participant(request.initiator));
+		}
+
 		// A HRMP watermark can be None for an onboarded parachain. However, an offboarded parachain
 		// cannot have an HRMP watermark: it should've been cleanup.
 		assert_contains_only_onboarded(
@@ -1578,3 +2156,11 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 }
+
+impl<T: Config> FeeTracker for Pallet<T> {
+	type Id = HrmpChannelId;
+
+	fn get_fee_factor(channel_id: HrmpChannelId) -> FixedU128 {
+		DeliveryFeeFactor::<T>::get(channel_id)
+	}
+}
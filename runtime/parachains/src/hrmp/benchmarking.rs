@@ -106,7 +106,7 @@ where
 		return output
 	}
 
-	Hrmp::<T>::process_hrmp_open_channel_requests(&Configuration::<T>::config());
+	Hrmp::<T>::process_hrmp_open_channel_requests();
 	if matches!(until, ParachainSetupStep::Established) {
 		return output
 	}
@@ -121,6 +121,59 @@ where
 	output
 }
 
+/// Requests an update of the parameters of the established channel from `sender` to `recipient`
+/// on behalf of the sender. The request is accepted by the recipient if `accept` is set.
+fn request_channel_update<T: Config>(
+	[(sender, sender_origin), (recipient, recipient_origin)]: [(ParaId, crate::Origin); 2],
+	accept: bool,
+) where
+	<T as frame_system::Config>::RuntimeOrigin: From<crate::Origin>,
+{
+	let config = Configuration::<T>::config();
+	let channel_id = HrmpChannelId { sender, recipient };
+
+	assert_ok!(Hrmp::<T>::hrmp_request_channel_update(
+		sender_origin.into(),
+		channel_id.clone(),
+		config.hrmp_channel_max_capacity,
+		config.hrmp_channel_max_message_size,
+	));
+	if accept {
+		assert_ok!(Hrmp::<T>::hrmp_accept_channel_update(recipient_origin.into(), channel_id));
+	}
+}
+
+/// Changes the deposits required for channels right away.
+///
+/// Updates of the channels opened before then have to top up or return the difference, which is
+/// the worst case of the channel update calls.
+fn set_hrmp_deposits<T: Config>(sender_deposit: Balance, recipient_deposit: Balance) {
+	assert_ok!(Configuration::<T>::set_hrmp_sender_deposit(
+		frame_system::RawOrigin::Root.into(),
+		sender_deposit
+	));
+	assert_ok!(Configuration::<T>::set_hrmp_recipient_deposit(
+		frame_system::RawOrigin::Root.into(),
+		recipient_deposit
+	));
+	Configuration::<T>::initializer_on_new_session(&Shared::<T>::scheduled_session());
+}
+
+/// Doubles the deposits required for channels, and makes sure the given parachains can afford to
+/// top up their deposits.
+fn raise_hrmp_deposits<T: Config>(paras: &[ParaId]) {
+	let config = Configuration::<T>::config();
+	let sender_deposit = config.hrmp_sender_deposit.saturating_mul(2);
+	let recipient_deposit = config.hrmp_recipient_deposit.saturating_mul(2);
+	set_hrmp_deposits::<T>(sender_deposit, recipient_deposit);
+
+	let ed = T::Currency::minimum_balance();
+	let top_up: BalanceOf<T> = sender_deposit.max(recipient_deposit).unique_saturated_into();
+	for para in paras {
+		T::Currency::make_free_balance_be(&para.into_account_truncating(), top_up + ed);
+	}
+}
+
 /// Prefix value for account generation. These numbers are used as seeds to create distinct (para)
 /// accounts.
 ///
@@ -292,9 +345,8 @@ frame_benchmarking::benchmarks! {
 
 		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, c);
 		let outgoing = (0..c).map(|id| (id + PREFIX_1).into()).collect::<Vec<ParaId>>();
-		let config = Configuration::<T>::config();
 	}: {
-		Hrmp::<T>::clean_open_channel_requests(&config, &outgoing);
+		Hrmp::<T>::clean_open_channel_requests(&outgoing);
 	} verify {
 		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, 0);
 	}
@@ -351,6 +403,105 @@ frame_benchmarking::benchmarks! {
 			Event::<T>::HrmpChannelForceOpened(sender_id, recipient_id, capacity, message_size).into()
 		);
 	}
+
+	hrmp_request_channel_update {
+		let [(sender, sender_origin), (recipient, _)] =
+			establish_para_connection::<T>(1, 2, ParachainSetupStep::Established);
+		raise_hrmp_deposits::<T>(&[sender]);
+		let channel_id = HrmpChannelId { sender, recipient };
+
+		let capacity = Configuration::<T>::config().hrmp_channel_max_capacity;
+		let message_size = Configuration::<T>::config().hrmp_channel_max_message_size;
+	}: _(sender_origin, channel_id.clone(), capacity, message_size)
+	verify {
+		assert_last_event::<T>(
+			Event::<T>::ChannelUpdateRequested(sender, channel_id, capacity, message_size).into()
+		);
+	}
+
+	hrmp_accept_channel_update {
+		let paras = establish_para_connection::<T>(1, 2, ParachainSetupStep::Established);
+		let [(sender, _), (recipient, recipient_origin)] = paras.clone();
+		raise_hrmp_deposits::<T>(&[sender, recipient]);
+		request_channel_update::<T>(paras, false);
+		let channel_id = HrmpChannelId { sender, recipient };
+	}: _(recipient_origin, channel_id.clone())
+	verify {
+		assert_last_event::<T>(Event::<T>::ChannelUpdateAccepted(recipient, channel_id).into());
+	}
+
+	hrmp_cancel_channel_update {
+		// number of items already existing in the `HrmpChannelUpdateRequestsList`, other than the
+		// one that we remove.
+		let c in 0 .. MAX_UNIQUE_CHANNELS;
+
+		let mut channels = (0 .. c)
+			.map(|id| establish_para_connection::<T>(PREFIX_0 + id, PREFIX_1 + id, ParachainSetupStep::Established))
+			.collect::<Vec<_>>();
+		let paras = establish_para_connection::<T>(1, 2, ParachainSetupStep::Established);
+		let [(sender, sender_origin), (recipient, _)] = paras.clone();
+		channels.push(paras);
+
+		// the canceled request returns the top-up of the sender.
+		raise_hrmp_deposits::<T>(&channels.iter().map(|[(sender, _), _]| *sender).collect::<Vec<_>>());
+		for paras in channels {
+			request_channel_update::<T>(paras, false);
+		}
+		assert_eq!(HrmpChannelUpdateRequestsList::<T>::decode_len().unwrap_or_default() as u32, c + 1);
+		let channel_id = HrmpChannelId { sender, recipient };
+	}: _(sender_origin, channel_id, c + 1)
+	verify {
+		assert_eq!(HrmpChannelUpdateRequestsList::<T>::decode_len().unwrap_or_default() as u32, c);
+	}
+
+	force_process_hrmp_update {
+		// number of channels that need to be processed. Worse case is an N-M relation: unique
+		// sender and recipients for all channels.
+		let c in 0 .. MAX_UNIQUE_CHANNELS;
+
+		let channels = (0 .. c)
+			.map(|id| establish_para_connection::<T>(PREFIX_0 + id, PREFIX_1 + id, ParachainSetupStep::Established))
+			.collect::<Vec<_>>();
+
+		// the updates return the surplus of the deposits to both participants.
+		let config = Configuration::<T>::config();
+		set_hrmp_deposits::<T>(config.hrmp_sender_deposit / 2, config.hrmp_recipient_deposit / 2);
+		for paras in channels {
+			request_channel_update::<T>(paras, true);
+		}
+
+		assert_eq!(HrmpChannelUpdateRequestsList::<T>::decode_len().unwrap_or_default() as u32, c);
+	}: _(frame_system::Origin::<T>::Root, c)
+	verify {
+		assert_eq!(HrmpChannelUpdateRequestsList::<T>::decode_len().unwrap_or_default() as u32, 0);
+	}
+
+	establish_system_channels {
+		let para_a: ParaId = 1000u32.into();
+		let para_b: ParaId = 1001u32.into();
+
+		// the worst case replaces a pending request in both directions, each of which was charged
+		// the regular deposit.
+		let ed = T::Currency::minimum_balance();
+		let deposit: BalanceOf<T> = Configuration::<T>::config().hrmp_sender_deposit.unique_saturated_into();
+		register_parachain_with_balance::<T>(para_a, deposit + ed);
+		register_parachain_with_balance::<T>(para_b, deposit + ed);
+		let capacity = Configuration::<T>::config().hrmp_channel_max_capacity;
+		let message_size = Configuration::<T>::config().hrmp_channel_max_message_size;
+		assert_ok!(Hrmp::<T>::init_open_channel(para_a, para_b, capacity, message_size));
+		assert_ok!(Hrmp::<T>::init_open_channel(para_b, para_a, capacity, message_size));
+
+		let caller: T::AccountId = frame_benchmarking::whitelisted_caller();
+	}: _(frame_system::RawOrigin::Signed(caller), para_a, para_b)
+	verify {
+		assert_last_event::<T>(
+			Event::<T>::HrmpSystemChannelOpened(para_b, para_a, capacity, message_size).into()
+		);
+		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, 2);
+		// both pending requests were canceled, returning their deposits.
+		assert_eq!(T::Currency::reserved_balance(&para_a.into_account_truncating()), 0u32.into());
+		assert_eq!(T::Currency::reserved_balance(&para_b.into_account_truncating()), 0u32.into());
+	}
 }
 
 frame_benchmarking::impl_benchmark_test_suite!(
@@ -0,0 +1,155 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migration(s) related to the HRMP pallet.
+
+use frame_support::traits::StorageVersion;
+
+pub mod v1 {
+	use super::*;
+	use crate::{
+		configuration,
+		hrmp::{Config, HrmpOpenChannelRequest, HrmpOpenChannelRequests, Pallet},
+	};
+	use frame_support::{pallet_prelude::*, traits::OnRuntimeUpgrade, weights::Weight};
+	use primitives::{Balance, SessionIndex};
+	#[cfg(feature = "try-runtime")]
+	use sp_std::prelude::*;
+
+	/// A request to open an HRMP channel, before the recipient deposit was recorded in it.
+	#[derive(Encode, Decode)]
+	pub struct V0HrmpOpenChannelRequest {
+		pub confirmed: bool,
+		pub _age: SessionIndex,
+		pub sender_deposit: Balance,
+		pub max_message_size: u32,
+		pub max_capacity: u32,
+		pub max_total_size: u32,
+	}
+
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight: Weight = Weight::zero();
+
+			if StorageVersion::get::<Pallet<T>>() < 1 {
+				log::info!(target: "runtime::hrmp", "Migrating HRMP storage to v1");
+				weight += migrate_to_v1::<T>();
+				StorageVersion::new(1).put::<Pallet<T>>();
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			} else {
+				log::info!(target: "runtime::hrmp", "HRMP storage up to date - no need for migration");
+			}
+
+			weight
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == 0,
+				"Storage version should be less than `1` before the migration",
+			);
+			let requests = HrmpOpenChannelRequests::<T>::iter_keys().count() as u32;
+			Ok(requests.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+			log::trace!(target: "runtime::hrmp", "Running post_upgrade()");
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() >= 1,
+				"Storage version should be `1` after the migration"
+			);
+			let requests = u32::decode(&mut &state[..])
+				.map_err(|_| "Failed to decode the number of open channel requests")?;
+			ensure!(
+				HrmpOpenChannelRequests::<T>::iter().count() as u32 == requests,
+				"All open channel requests should be migrated"
+			);
+			Ok(())
+		}
+	}
+
+	/// Records the recipient deposit in the open channel requests. Confirmed requests were
+	/// charged the recipient deposit of the configuration.
+	pub fn migrate_to_v1<T: Config>() -> Weight {
+		let recipient_deposit = <configuration::Pallet<T>>::config().hrmp_recipient_deposit;
+
+		let mut translated = 0u64;
+		HrmpOpenChannelRequests::<T>::translate::<V0HrmpOpenChannelRequest, _>(|_, request| {
+			translated += 1;
+			Some(HrmpOpenChannelRequest {
+				confirmed: request.confirmed,
+				_age: request._age,
+				sender_deposit: request.sender_deposit,
+				max_message_size: request.max_message_size,
+				max_capacity: request.max_capacity,
+				max_total_size: request.max_total_size,
+				recipient_deposit: if request.confirmed { recipient_deposit } else { 0 },
+			})
+		});
+
+		T::DbWeight::get().reads_writes(translated + 1, translated)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::v1::{migrate_to_v1, V0HrmpOpenChannelRequest};
+	use crate::{
+		hrmp::{Config, HrmpOpenChannelRequests, Pallet},
+		mock::{new_test_ext, MockGenesisConfig, Test},
+	};
+	use frame_support::{storage_alias, Twox64Concat};
+	use primitives::{HrmpChannelId, Id as ParaId};
+
+	#[storage_alias]
+	type V0HrmpOpenChannelRequests<T: Config> =
+		StorageMap<Pallet<T>, Twox64Concat, HrmpChannelId, V0HrmpOpenChannelRequest>;
+
+	#[test]
+	fn migrate_to_v1_records_recipient_deposits() {
+		let mut genesis = MockGenesisConfig::default();
+		genesis.configuration.config.hrmp_recipient_deposit = 15;
+
+		new_test_ext(genesis).execute_with(|| {
+			let pending = HrmpChannelId { sender: ParaId::from(32), recipient: ParaId::from(64) };
+			let confirmed = HrmpChannelId { sender: ParaId::from(64), recipient: ParaId::from(32) };
+			for (channel_id, confirmed) in [(&pending, false), (&confirmed, true)] {
+				V0HrmpOpenChannelRequests::<Test>::insert(
+					channel_id,
+					V0HrmpOpenChannelRequest {
+						confirmed,
+						_age: 0,
+						sender_deposit: 20,
+						max_message_size: 8,
+						max_capacity: 2,
+						max_total_size: 16,
+					},
+				);
+			}
+
+			migrate_to_v1::<Test>();
+
+			let pending = HrmpOpenChannelRequests::<Test>::get(&pending).unwrap();
+			assert_eq!((pending.sender_deposit, pending.recipient_deposit), (20, 0));
+			let confirmed = HrmpOpenChannelRequests::<Test>::get(&confirmed).unwrap();
+			assert_eq!((confirmed.sender_deposit, confirmed.recipient_deposit), (20, 15));
+			assert_eq!((confirmed.max_capacity, confirmed.max_message_size), (2, 8));
+		});
+	}
+}
@@ -648,6 +648,44 @@ fn no_dangling_open_requests() {
 	});
 }
 
+#[test]
+fn channel_keeps_recipient_deposit_of_request() {
+	let para_a = 32.into();
+	let para_b = 64.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()), 95);
+
+		// The deposit changes before the channel is opened.
+		let mut config = Configuration::config();
+		config.hrmp_recipient_deposit = 25;
+		crate::configuration::Pallet::<Test>::force_set_active_config(config);
+
+		// The channel holds the deposit reserved when accepting the request.
+		run_to_block(6, Some(vec![6]));
+		let channel =
+			HrmpChannels::<Test>::get(&HrmpChannelId { sender: para_a, recipient: para_b })
+				.unwrap();
+		assert_eq!(channel.recipient_deposit, 15);
+
+		Hrmp::close_channel(para_b, HrmpChannelId { sender: para_a, recipient: para_b }).unwrap();
+		run_to_block(8, Some(vec![8]));
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()),
+			110
+		);
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
 #[test]
 fn cancel_pending_open_channel_request() {
 	let para_a = 32.into();
@@ -724,3 +762,445 @@ fn watermark_maxed_out_at_relay_parent() {
 		Hrmp::assert_storage_consistency_exhaustive();
 	});
 }
+
+#[test]
+fn delivery_fee_factor_rises_under_congestion_and_decays_afterwards() {
+	let para_a = 32.into();
+	let para_b = 64.into();
+	let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+
+	let send_message = |data: Vec<u8>| {
+		let msgs: HorizontalMessages =
+			vec![OutboundHrmpMessage { recipient: para_b, data }].try_into().unwrap();
+		let _ = Hrmp::queue_outbound_hrmp(para_a, msgs);
+	};
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_channel_max_capacity = 8;
+	genesis.hrmp_channel_max_message_size = 2048;
+	genesis.hrmp_channel_max_total_size = 8192;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 8, 2048).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+
+		let initial = InitialFactor::get();
+		assert_eq!(Hrmp::get_fee_factor(channel_id.clone()), initial);
+
+		// B does not process any messages. Up to half of the capacity the fee stays put.
+		for block in 6..10 {
+			run_to_block(block, None);
+			send_message(vec![1]);
+			assert_eq!(Hrmp::get_fee_factor(channel_id.clone()), initial);
+		}
+
+		// Beyond, every message raises it.
+		let mut expected = initial;
+		for block in 10..13 {
+			run_to_block(block, None);
+			send_message(vec![1]);
+			expected = expected.saturating_mul(EXPONENTIAL_FEE_BASE);
+			assert_eq!(Hrmp::get_fee_factor(channel_id.clone()), expected);
+		}
+
+		// Large messages raise it by an additional 0.001 per KB.
+		run_to_block(13, None);
+		send_message(vec![0; 2048]);
+		expected = expected
+			.saturating_mul(EXPONENTIAL_FEE_BASE + MESSAGE_SIZE_FEE_BASE * FixedU128::from_u32(2));
+		assert_eq!(Hrmp::get_fee_factor(channel_id.clone()), expected);
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		// B catches up, after which every message it processes lowers the fee again, down to the
+		// initial value.
+		run_to_block(14, None);
+		let _ = Hrmp::prune_hrmp(para_b, 13);
+		expected = expected / EXPONENTIAL_FEE_BASE;
+		assert_eq!(Hrmp::get_fee_factor(channel_id.clone()), expected);
+
+		for block in 15..25 {
+			send_message(vec![1]);
+			run_to_block(block, None);
+			let _ = Hrmp::prune_hrmp(para_b, block - 1);
+			expected = initial.max(expected / EXPONENTIAL_FEE_BASE);
+			assert_eq!(Hrmp::get_fee_factor(channel_id.clone()), expected);
+		}
+		assert_eq!(expected, initial);
+		assert!(!DeliveryFeeFactor::<Test>::contains_key(&channel_id));
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn update_channel_works() {
+	let para_a = 1.into();
+	let para_b = 3.into();
+	let para_c = 5.into();
+	let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+		register_parachain(para_c);
+
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+
+		assert_noop!(
+			Hrmp::request_channel_update(para_c, channel_id.clone(), 1, 4),
+			Error::<Test>::UpdateHrmpChannelUnauthorized,
+		);
+		assert_noop!(
+			Hrmp::request_channel_update(
+				para_b,
+				HrmpChannelId { sender: para_b, recipient: para_a },
+				1,
+				4
+			),
+			Error::<Test>::UpdateHrmpChannelDoesntExist,
+		);
+		assert_noop!(
+			Hrmp::request_channel_update(para_b, channel_id.clone(), 0, 4),
+			Error::<Test>::UpdateHrmpChannelZeroCapacity,
+		);
+		assert_noop!(
+			Hrmp::request_channel_update(para_b, channel_id.clone(), 3, 4),
+			Error::<Test>::UpdateHrmpChannelCapacityExceedsLimit,
+		);
+		assert_noop!(
+			Hrmp::request_channel_update(para_b, channel_id.clone(), 1, 0),
+			Error::<Test>::UpdateHrmpChannelZeroMessageSize,
+		);
+		assert_noop!(
+			Hrmp::request_channel_update(para_b, channel_id.clone(), 1, 9),
+			Error::<Test>::UpdateHrmpChannelMessageSizeExceedsLimit,
+		);
+
+		// The recipient asks for a smaller channel.
+		let para_b_origin: crate::Origin = 3.into();
+		Hrmp::hrmp_request_channel_update(para_b_origin.into(), channel_id.clone(), 1, 4).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::ChannelUpdateRequested(para_b, channel_id.clone(), 1, 4))));
+
+		assert_noop!(
+			Hrmp::request_channel_update(para_a, channel_id.clone(), 2, 8),
+			Error::<Test>::UpdateHrmpChannelAlreadyRequested,
+		);
+		assert_noop!(
+			Hrmp::accept_channel_update(para_b, channel_id.clone()),
+			Error::<Test>::AcceptHrmpChannelUpdateUnauthorized,
+		);
+		assert_noop!(
+			Hrmp::accept_channel_update(para_c, channel_id.clone()),
+			Error::<Test>::AcceptHrmpChannelUpdateUnauthorized,
+		);
+
+		let para_a_origin: crate::Origin = 1.into();
+		Hrmp::hrmp_accept_channel_update(para_a_origin.into(), channel_id.clone()).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::ChannelUpdateAccepted(para_a, channel_id.clone()))));
+		assert_noop!(
+			Hrmp::accept_channel_update(para_a, channel_id.clone()),
+			Error::<Test>::AcceptHrmpChannelUpdateAlreadyConfirmed,
+		);
+		assert_noop!(
+			Hrmp::cancel_channel_update(para_a, channel_id.clone()),
+			Error::<Test>::CancelHrmpChannelUpdateAlreadyConfirmed,
+		);
+
+		// The update is enacted only on the session change.
+		run_to_block(7, None);
+		assert_eq!(HrmpChannels::<Test>::get(&channel_id).unwrap().max_capacity, 2);
+
+		run_to_block(8, Some(vec![8]));
+		let channel = HrmpChannels::<Test>::get(&channel_id).unwrap();
+		assert_eq!(channel.max_capacity, 1);
+		assert_eq!(channel.max_message_size, 4);
+		assert!(HrmpChannelUpdateRequests::<Test>::get(&channel_id).is_none());
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::ChannelUpdated(channel_id.clone(), 1, 4))));
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn update_channel_tops_up_and_refunds_deposits() {
+	let para_a = 32.into();
+	let para_b = 64.into();
+	let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+	let free_balance =
+		|para: ParaId| <Test as Config>::Currency::free_balance(&para.into_account_truncating());
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+		assert_eq!(free_balance(para_a), 80);
+		assert_eq!(free_balance(para_b), 95);
+
+		// The deposits have changed since the channel was opened.
+		let mut config = Configuration::config();
+		config.hrmp_sender_deposit = 30;
+		config.hrmp_recipient_deposit = 10;
+		crate::configuration::Pallet::<Test>::force_set_active_config(config);
+
+		// The top-up of the initiator is reserved right away and returned on cancellation.
+		Hrmp::request_channel_update(para_a, channel_id.clone(), 2, 8).unwrap();
+		assert_eq!(free_balance(para_a), 70);
+		Hrmp::cancel_channel_update(para_b, channel_id.clone()).unwrap();
+		assert_eq!(free_balance(para_a), 80);
+		assert!(HrmpChannelUpdateRequests::<Test>::get(&channel_id).is_none());
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		// The surplus of the recipient is only refunded once the update is enacted.
+		Hrmp::request_channel_update(para_a, channel_id.clone(), 2, 8).unwrap();
+		Hrmp::accept_channel_update(para_b, channel_id.clone()).unwrap();
+		assert_eq!(free_balance(para_a), 70);
+		assert_eq!(free_balance(para_b), 95);
+		run_to_block(8, Some(vec![8]));
+		assert_eq!(free_balance(para_a), 70);
+		assert_eq!(free_balance(para_b), 100);
+
+		let channel = HrmpChannels::<Test>::get(&channel_id).unwrap();
+		assert_eq!(channel.sender_deposit, 30);
+		assert_eq!(channel.recipient_deposit, 10);
+
+		// Closing the channel returns the updated deposits.
+		Hrmp::close_channel(para_b, channel_id.clone()).unwrap();
+		run_to_block(10, Some(vec![10]));
+		assert_eq!(free_balance(para_a), 100);
+		assert_eq!(free_balance(para_b), 110);
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn update_channel_keeps_pending_messages() {
+	let para_a = 32.into();
+	let para_b = 64.into();
+	let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+
+	let send_message = |data: Vec<u8>| {
+		let msgs: HorizontalMessages =
+			vec![OutboundHrmpMessage { recipient: para_b, data }].try_into().unwrap();
+		assert!(Hrmp::check_outbound_hrmp(&Configuration::config(), para_a, &msgs).is_ok());
+		let _ = Hrmp::queue_outbound_hrmp(para_a, msgs);
+	};
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+
+		// A fills the channel, B does not process any of the messages yet.
+		run_to_block(6, Some(vec![6]));
+		send_message(b"knock".to_vec());
+		run_to_block(7, None);
+		send_message(b"knock".to_vec());
+		let mqc_head = HrmpChannels::<Test>::get(&channel_id).unwrap().mqc_head;
+
+		Hrmp::request_channel_update(para_a, channel_id.clone(), 1, 8).unwrap();
+		Hrmp::accept_channel_update(para_b, channel_id.clone()).unwrap();
+
+		// The pending messages would not fit into the updated channel, so the update is postponed.
+		run_to_block(8, Some(vec![8]));
+		assert_eq!(HrmpChannels::<Test>::get(&channel_id).unwrap().max_capacity, 2);
+		assert!(HrmpChannelUpdateRequests::<Test>::get(&channel_id).is_some());
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		// Once B processes the first message, the other one fits.
+		let _ = Hrmp::prune_hrmp(para_b, 6);
+		run_to_block(10, Some(vec![10]));
+
+		let channel = HrmpChannels::<Test>::get(&channel_id).unwrap();
+		assert_eq!(channel.max_capacity, 1);
+		assert_eq!(channel.msg_count, 1);
+		assert_eq!(channel.mqc_head, mqc_head);
+		assert_eq!(
+			HrmpChannelContents::<Test>::get(&channel_id),
+			vec![InboundHrmpMessage { sent_at: 7, data: b"knock".to_vec() }],
+		);
+		assert!(HrmpChannelUpdateRequests::<Test>::get(&channel_id).is_none());
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn closing_channel_drops_update_request() {
+	let para_a = 32.into();
+	let para_b = 64.into();
+	let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+
+		let mut config = Configuration::config();
+		config.hrmp_recipient_deposit = 25;
+		crate::configuration::Pallet::<Test>::force_set_active_config(config);
+
+		Hrmp::request_channel_update(para_b, channel_id.clone(), 1, 8).unwrap();
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()), 85);
+
+		// No updates of channels that are about to be closed.
+		Hrmp::close_channel(para_a, channel_id.clone()).unwrap();
+		assert_noop!(
+			Hrmp::request_channel_update(para_a, channel_id.clone(), 1, 8),
+			Error::<Test>::UpdateHrmpChannelCloseUnderway,
+		);
+
+		// The pending request is dropped with the channel, returning the top-up as well.
+		run_to_block(8, Some(vec![8]));
+		assert!(!channel_exists(para_a, para_b));
+		assert!(HrmpChannelUpdateRequests::<Test>::get(&channel_id).is_none());
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()),
+			110
+		);
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn establish_system_channels_works() {
+	let para_a = 1000.into();
+	let para_b = 1001.into();
+	let a_to_b = HrmpChannelId { sender: para_a, recipient: para_b };
+	let b_to_a = HrmpChannelId { sender: para_b, recipient: para_a };
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		// System chains don't need any funds.
+		register_parachain_with_balance(para_a, 0);
+		register_parachain_with_balance(para_b, 0);
+		run_to_block(5, Some(vec![4, 5]));
+
+		Hrmp::establish_system_channels(RuntimeOrigin::signed(1), para_a, para_b).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+		for (sender, recipient) in [(para_a, para_b), (para_b, para_a)] {
+			assert!(System::events().iter().any(|record| record.event ==
+				MockEvent::Hrmp(Event::HrmpSystemChannelOpened(sender, recipient, 2, 8))));
+		}
+
+		// The channels are opened on the session change, in both directions.
+		run_to_block(6, Some(vec![6]));
+		for channel_id in [&a_to_b, &b_to_a] {
+			let channel = HrmpChannels::<Test>::get(channel_id).unwrap();
+			assert_eq!(channel.max_capacity, 2);
+			assert_eq!(channel.max_message_size, 8);
+			assert_eq!(channel.sender_deposit, 0);
+			assert_eq!(channel.recipient_deposit, 0);
+		}
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		assert_noop!(
+			Hrmp::establish_system_channels(RuntimeOrigin::signed(1), para_b, para_a),
+			Error::<Test>::OpenHrmpChannelAlreadyExists,
+		);
+
+		// Closing them is free as well.
+		Hrmp::close_channel(para_a, a_to_b.clone()).unwrap();
+		run_to_block(7, Some(vec![7]));
+		assert!(!channel_exists(para_a, para_b));
+		assert!(channel_exists(para_b, para_a));
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn establish_system_channels_replaces_pending_requests() {
+	let para_a = 1000.into();
+	let para_b = 1001.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+		run_to_block(5, Some(vec![4, 5]));
+
+		// A pending request from A to B is replaced, B to A is already accepted and kept. The
+		// regular requests are charged the deposits of the configuration.
+		Hrmp::init_open_channel(para_a, para_b, 1, 4).unwrap();
+		Hrmp::init_open_channel(para_b, para_a, 1, 4).unwrap();
+		Hrmp::accept_open_channel(para_a, para_b).unwrap();
+
+		Hrmp::establish_system_channels(RuntimeOrigin::signed(1), para_a, para_b).unwrap();
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::HrmpSystemChannelOpened(para_a, para_b, 2, 8))));
+		assert!(!System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::HrmpSystemChannelOpened(para_b, para_a, 2, 8))));
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		run_to_block(6, Some(vec![6]));
+		let a_to_b =
+			HrmpChannels::<Test>::get(&HrmpChannelId { sender: para_a, recipient: para_b })
+				.unwrap();
+		assert_eq!((a_to_b.max_capacity, a_to_b.max_message_size), (2, 8));
+		assert_eq!((a_to_b.sender_deposit, a_to_b.recipient_deposit), (0, 0));
+		let b_to_a =
+			HrmpChannels::<Test>::get(&HrmpChannelId { sender: para_b, recipient: para_a })
+				.unwrap();
+		assert_eq!((b_to_a.max_capacity, b_to_a.max_message_size), (1, 4));
+		assert_eq!((b_to_a.sender_deposit, b_to_a.recipient_deposit), (100, 100));
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()),
+			900
+		);
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()),
+			900
+		);
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn establish_system_channels_rejects_non_system_chains() {
+	let system = 1000.into();
+	let public = 2000.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(system);
+		register_parachain(public);
+		run_to_block(5, Some(vec![4, 5]));
+
+		assert_noop!(
+			Hrmp::establish_system_channels(RuntimeOrigin::signed(1), system, public),
+			Error::<Test>::EstablishHrmpChannelNotSystemChain,
+		);
+		assert_noop!(
+			Hrmp::establish_system_channels(RuntimeOrigin::signed(1), public, system),
+			Error::<Test>::EstablishHrmpChannelNotSystemChain,
+		);
+		assert_noop!(
+			Hrmp::establish_system_channels(RuntimeOrigin::signed(1), system, system),
+			Error::<Test>::OpenHrmpChannelToSelf,
+		);
+		assert_noop!(
+			Hrmp::establish_system_channels(RuntimeOrigin::root(), system, system),
+			sp_runtime::DispatchError::BadOrigin,
+		);
+	});
+}
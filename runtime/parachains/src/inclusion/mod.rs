@@ -22,9 +22,11 @@
 
 use crate::{
 	configuration::{self, HostConfiguration},
-	disputes, dmp, hrmp, paras,
+	disputes,
+	dmp::{self, EXPONENTIAL_FEE_BASE, MESSAGE_SIZE_FEE_BASE, THRESHOLD_FACTOR},
+	hrmp, paras,
 	scheduler::CoreAssignment,
-	shared,
+	shared, FeeTracker,
 };
 use bitvec::{order::Lsb0 as BitOrderLsb0, vec::BitVec};
 use frame_support::{
@@ -43,7 +45,7 @@ use primitives::{
 	ValidityAttestation,
 };
 use scale_info::TypeInfo;
-use sp_runtime::{traits::One, DispatchError, SaturatedConversion, Saturating};
+use sp_runtime::{traits::One, DispatchError, FixedU128, SaturatedConversion, Saturating};
 #[cfg(feature = "std")]
 use sp_std::fmt;
 use sp_std::{collections::btree_set::BTreeSet, prelude::*};
@@ -377,6 +379,20 @@ pub mod pallet {
 	pub(crate) type PendingAvailabilityCommitments<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, CandidateCommitments>;
 
+	/// Initialization value for the delivery fee factor of an upward message queue.
+	#[pallet::type_value]
+	pub fn InitialFactor() -> FixedU128 {
+		FixedU128::from_u32(1)
+	}
+
+	/// The number to multiply the base delivery fee of upward messages sent by a para by.
+	///
+	/// Rises while the upward message queue of the para is congested and decays back to one
+	/// otherwise.
+	#[pallet::storage]
+	pub(crate) type UpwardDeliveryFeeFactor<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, FixedU128, ValueQuery, InitialFactor>;
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {}
 }
@@ -1004,15 +1020,65 @@ impl<T: Config> Pallet<T> {
 			return Weight::zero()
 		}
 
-		T::MessageQueue::enqueue_messages(
-			messages.into_iter(),
-			AggregateMessageOrigin::Ump(UmpQueueId::Para(para)),
-		);
+		let message_size_factors: Vec<_> = messages
+			.iter()
+			.map(|msg| {
+				FixedU128::from_u32(msg.len().saturating_div(1024) as u32)
+					.saturating_mul(MESSAGE_SIZE_FEE_BASE)
+			})
+			.collect();
+
+		let origin = AggregateMessageOrigin::Ump(UmpQueueId::Para(para));
+		T::MessageQueue::enqueue_messages(messages.into_iter(), origin.clone());
+
+		let fp = T::MessageQueue::footprint(origin);
+		if Self::is_ump_congested(&<configuration::Pallet<T>>::config(), fp.count, fp.size) {
+			for message_size_factor in message_size_factors {
+				Self::increment_ump_fee_factor(para, message_size_factor);
+			}
+		}
+
 		let weight = <T as Config>::WeightInfo::receive_upward_messages(count);
 		Self::deposit_event(Event::UpwardMessagesReceived { from: para, count });
 		weight
 	}
 
+	/// Whether an upward message queue holding `count` messages of `size` bytes in total is filled
+	/// beyond a `THRESHOLD_FACTOR`th of its capacity, either by count or by size.
+	fn is_ump_congested(config: &HostConfiguration<T::BlockNumber>, count: u64, size: u64) -> bool {
+		count > (config.max_upward_queue_count / THRESHOLD_FACTOR) as u64 ||
+			size > (config.max_upward_queue_size / THRESHOLD_FACTOR) as u64
+	}
+
+	/// Raise the upward delivery fee factor of a para by a multiplicative factor and store the
+	/// resulting value.
+	///
+	/// Returns the new delivery fee factor after the increment.
+	pub(crate) fn increment_ump_fee_factor(
+		para: ParaId,
+		message_size_factor: FixedU128,
+	) -> FixedU128 {
+		UpwardDeliveryFeeFactor::<T>::mutate(para, |f| {
+			*f = f.saturating_mul(EXPONENTIAL_FEE_BASE + message_size_factor);
+			*f
+		})
+	}
+
+	/// Reduce the upward delivery fee factor of a para by a multiplicative factor and store the
+	/// resulting value, without going below the initial value of 1.
+	///
+	/// The factor is removed from storage once it is back at its initial value.
+	///
+	/// Returns the new delivery fee factor after the decrement.
+	pub(crate) fn decrement_ump_fee_factor(para: ParaId) -> FixedU128 {
+		UpwardDeliveryFeeFactor::<T>::mutate_exists(para, |f| {
+			let initial = InitialFactor::get();
+			let new = initial.max(f.unwrap_or(initial) / EXPONENTIAL_FEE_BASE);
+			*f = (new > initial).then_some(new);
+			new
+		})
+	}
+
 	/// Cleans up all paras pending availability that the predicate returns true for.
 	///
 	/// The predicate accepts the index of the core and the block number the core has been occupied
@@ -1157,6 +1223,18 @@ impl<T: Config> OnQueueChanged<AggregateMessageOrigin> for Pallet<T> {
 		let remaining_size = config.max_upward_queue_size.saturating_sub(size);
 		well_known_keys::relay_dispatch_queue_remaining_capacity(para)
 			.set((remaining_count, remaining_size));
+
+		if !Self::is_ump_congested(&config, count.into(), size.into()) {
+			Self::decrement_ump_fee_factor(para);
+		}
+	}
+}
+
+impl<T: Config> FeeTracker for Pallet<T> {
+	type Id = ParaId;
+
+	fn get_fee_factor(para: ParaId) -> FixedU128 {
+		UpwardDeliveryFeeFactor::<T>::get(para)
 	}
 }
 
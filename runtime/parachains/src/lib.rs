@@ -30,6 +30,7 @@ pub mod hrmp;
 pub mod inclusion;
 pub mod initializer;
 pub mod metrics;
+pub mod on_demand;
 pub mod origin;
 pub mod paras;
 pub mod paras_inherent;
@@ -56,7 +57,10 @@ use sp_runtime::{DispatchResult, FixedU128};
 
 /// Trait for tracking message delivery fees on a transport protocol.
 pub trait FeeTracker {
-	fn get_fee_factor(para: ParaId) -> FixedU128;
+	/// What the fee factor is tracked for, e.g. the recipient of a message or a channel.
+	type Id;
+
+	fn get_fee_factor(id: Self::Id) -> FixedU128;
 }
 
 /// Schedule a para to be initialized at the start of the next session with the given genesis data.
@@ -19,7 +19,7 @@
 use crate::{
 	configuration, disputes, dmp, hrmp,
 	inclusion::{self, AggregateMessageOrigin, UmpQueueId},
-	initializer, origin, paras,
+	initializer, on_demand, origin, paras,
 	paras::ParaKind,
 	paras_inherent, scheduler, session_info, shared, ParaId,
 };
@@ -43,7 +43,7 @@ use sp_io::TestExternalities;
 use sp_runtime::{
 	traits::{AccountIdConversion, BlakeTwo256, IdentityLookup},
 	transaction_validity::TransactionPriority,
-	Permill,
+	Perbill, Permill,
 };
 use std::{cell::RefCell, collections::HashMap};
 
@@ -68,6 +68,7 @@ frame_support::construct_runtime!(
 		Initializer: initializer,
 		Dmp: dmp,
 		Hrmp: hrmp,
+		OnDemand: on_demand,
 		ParachainsOrigin: origin,
 		SessionInfo: session_info,
 		Disputes: disputes,
@@ -228,6 +229,21 @@ impl crate::hrmp::Config for Test {
 	type WeightInfo = crate::hrmp::TestWeightInfo;
 }
 
+parameter_types! {
+	pub const OnDemandBaseFee: Balance = 100;
+	pub const OnDemandFeeVariability: Perbill = Perbill::from_percent(10);
+	pub const OnDemandTargetQueueUtilization: Perbill = Perbill::from_percent(50);
+}
+
+impl crate::on_demand::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = pallet_balances::Pallet<Test>;
+	type BaseFee = OnDemandBaseFee;
+	type FeeVariability = OnDemandFeeVariability;
+	type TargetQueueUtilization = OnDemandTargetQueueUtilization;
+	type WeightInfo = crate::on_demand::TestWeightInfo;
+}
+
 impl crate::disputes::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type RewardValidators = Self;
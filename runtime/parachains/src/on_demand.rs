@@ -0,0 +1,258 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The on-demand pallet lets anyone order blocks for parathreads.
+//!
+//! An order is paid for at the spot price and, once paid, enqueues a claim for the parathread
+//! into the parathread queue of the scheduler. The payment is burned.
+//!
+//! The spot price is the `BaseFee` multiplied by the spot traffic. At the beginning of every block
+//! the spot traffic is adjusted to the occupancy of the parathread queue: it rises while the queue
+//! is more occupied than `TargetQueueUtilization` and decays back to one while it is less occupied,
+//! each in proportion to the distance from the target and to `FeeVariability`.
+
+use crate::{
+	configuration, paras,
+	scheduler::{self, ParathreadClaimError},
+};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{Currency, ExistenceRequirement, WithdrawReasons},
+};
+use frame_system::pallet_prelude::*;
+use primitives::{CollatorId, Id as ParaId, ParathreadClaim};
+use sp_runtime::{
+	traits::{One, SaturatedConversion, Saturating, Zero},
+	FixedPointNumber, FixedU128, Perbill,
+};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub trait WeightInfo {
+	fn place_order_allow_death() -> Weight;
+	fn place_order_keep_alive() -> Weight;
+	fn on_initialize() -> Weight;
+}
+
+/// A weight info that is only suitable for testing.
+pub struct TestWeightInfo;
+
+impl WeightInfo for TestWeightInfo {
+	fn place_order_allow_death() -> Weight {
+		Weight::MAX
+	}
+	fn place_order_keep_alive() -> Weight {
+		Weight::MAX
+	}
+	fn on_initialize() -> Weight {
+		Weight::zero()
+	}
+}
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + configuration::Config + paras::Config + scheduler::Config
+	{
+		/// The outer event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency orders are paid with. Payments are burned.
+		type Currency: Currency<Self::AccountId>;
+
+		/// The price of an order while the parathread queue is not busier than targeted.
+		#[pallet::constant]
+		type BaseFee: Get<BalanceOf<Self>>;
+
+		/// How strongly the spot traffic reacts to the parathread queue being busier or quieter
+		/// than targeted, per block.
+		#[pallet::constant]
+		type FeeVariability: Get<Perbill>;
+
+		/// The occupancy of the parathread queue above which the spot price starts to rise.
+		#[pallet::constant]
+		type TargetQueueUtilization: Get<Perbill>;
+
+		/// Something that provides the weight of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// The lowest, and initial, spot traffic.
+	#[pallet::type_value]
+	pub fn MinimumTraffic() -> FixedU128 {
+		FixedU128::one()
+	}
+
+	/// The factor the `BaseFee` is multiplied by to get the spot price.
+	#[pallet::storage]
+	pub(crate) type SpotTraffic<T: Config> = StorageValue<_, FixedU128, ValueQuery, MinimumTraffic>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A block was ordered for a parathread and its claim was queued.
+		OrderPlaced { para_id: ParaId, spot_price: BalanceOf<T>, ordered_by: T::AccountId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The para is not a parathread.
+		NotParathread,
+		/// The parathread queue is full.
+		QueueFull,
+		/// The parathread already has a claim in the queue or on a core.
+		AlreadyClaimed,
+		/// The spot price is higher than the amount the origin is willing to pay.
+		SpotPriceHigherThanMaxAmount,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_now: T::BlockNumber) -> Weight {
+			let (queued, capacity) = scheduler::Pallet::<T>::parathread_queue_occupancy();
+			SpotTraffic::<T>::mutate(|traffic| {
+				*traffic = Self::next_spot_traffic(*traffic, queued, capacity)
+			});
+
+			<T as Config>::WeightInfo::on_initialize()
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Order a block for the parathread `para_id`, to be authored by `collator`.
+		///
+		/// The order is paid for at the current spot price, which must not exceed `max_amount`.
+		/// The payment may bring the balance of the origin below the existential deposit, reaping
+		/// the account.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::place_order_allow_death())]
+		pub fn place_order_allow_death(
+			origin: OriginFor<T>,
+			max_amount: BalanceOf<T>,
+			para_id: ParaId,
+			collator: CollatorId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_place_order(
+				who,
+				max_amount,
+				para_id,
+				collator,
+				ExistenceRequirement::AllowDeath,
+			)
+		}
+
+		/// Same as [`Self::place_order_allow_death`], but fails instead of bringing the balance of
+		/// the origin below the existential deposit.
+		#[pallet::call_index(1)]
+		#[pallet::weight(<T as Config>::WeightInfo::place_order_keep_alive())]
+		pub fn place_order_keep_alive(
+			origin: OriginFor<T>,
+			max_amount: BalanceOf<T>,
+			para_id: ParaId,
+			collator: CollatorId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_place_order(
+				who,
+				max_amount,
+				para_id,
+				collator,
+				ExistenceRequirement::KeepAlive,
+			)
+		}
+	}
+}
+
+impl<T> From<ParathreadClaimError> for Error<T> {
+	fn from(error: ParathreadClaimError) -> Self {
+		match error {
+			ParathreadClaimError::NotParathread => Error::NotParathread,
+			ParathreadClaimError::QueueFull => Error::QueueFull,
+			ParathreadClaimError::AlreadyClaimed => Error::AlreadyClaimed,
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The price of an order placed in the current block.
+	pub fn spot_price() -> BalanceOf<T> {
+		let base_fee: u128 = T::BaseFee::get().saturated_into();
+		SpotTraffic::<T>::get().saturating_mul_int(base_fee).saturated_into()
+	}
+
+	/// The spot traffic following `traffic`, given that the parathread queue holds `queued` of
+	/// `capacity` claims.
+	pub(crate) fn next_spot_traffic(traffic: FixedU128, queued: u32, capacity: u32) -> FixedU128 {
+		let utilization = if capacity.is_zero() {
+			FixedU128::zero()
+		} else {
+			FixedU128::from_rational(queued.min(capacity).into(), capacity.into())
+		};
+		let target = FixedU128::from(T::TargetQueueUtilization::get());
+		let variability = FixedU128::from(T::FeeVariability::get());
+
+		let factor = if utilization > target {
+			FixedU128::one().saturating_add(variability.saturating_mul(utilization - target))
+		} else {
+			FixedU128::one().saturating_sub(variability.saturating_mul(target - utilization))
+		};
+
+		traffic.saturating_mul(factor).max(MinimumTraffic::get())
+	}
+
+	fn do_place_order(
+		ordered_by: T::AccountId,
+		max_amount: BalanceOf<T>,
+		para_id: ParaId,
+		collator: CollatorId,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		let spot_price = Self::spot_price();
+		ensure!(spot_price <= max_amount, Error::<T>::SpotPriceHigherThanMaxAmount);
+
+		let payment = T::Currency::withdraw(
+			&ordered_by,
+			spot_price,
+			WithdrawReasons::FEE,
+			existence_requirement,
+		)?;
+		// Dropping the imbalance burns the payment.
+		drop(payment);
+
+		scheduler::Pallet::<T>::try_add_parathread_claim(ParathreadClaim(para_id, collator))
+			.map_err(Error::<T>::from)?;
+
+		Self::deposit_event(Event::OrderPlaced { para_id, spot_price, ordered_by });
+		Ok(())
+	}
+}
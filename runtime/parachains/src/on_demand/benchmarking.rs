@@ -0,0 +1,125 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+	configuration::{HostConfiguration, Pallet as Configuration},
+	on_demand::{Pallet as OnDemand, *},
+	paras::{Pallet as Paras, ParaKind, ParachainsCache},
+	scheduler::Pallet as Scheduler,
+};
+use frame_benchmarking::whitelisted_caller;
+use frame_support::traits::Hooks;
+use frame_system::RawOrigin;
+use sp_core::sr25519;
+
+fn register_parathread<T: Config>(id: ParaId) {
+	let mut parachains = ParachainsCache::new();
+	Paras::<T>::initialize_para_now(
+		&mut parachains,
+		id,
+		&crate::paras::ParaGenesisArgs {
+			para_kind: ParaKind::Parathread,
+			genesis_head: vec![1].into(),
+			validation_code: vec![1].into(),
+		},
+	);
+}
+
+fn collator() -> CollatorId {
+	CollatorId::from(sr25519::Public::from_raw([42u8; 32]))
+}
+
+/// Fill the parathread queue up to one free slot, so that the competing claims of the ordered
+/// parathread have to be searched for among as many claims as possible. Returns the para which
+/// the order should be placed for.
+fn prepare_order<T: Config>() -> ParaId {
+	Configuration::<T>::force_set_active_config(HostConfiguration {
+		parathread_cores: 5,
+		scheduling_lookahead: 2,
+		..Configuration::<T>::config()
+	});
+
+	let (_, capacity) = Scheduler::<T>::parathread_queue_occupancy();
+	for i in 1..capacity {
+		let id = ParaId::from(i);
+		register_parathread::<T>(id);
+		Scheduler::<T>::try_add_parathread_claim(ParathreadClaim(id, collator()))
+			.expect("the queue is not full; qed");
+	}
+
+	let para_id = ParaId::from(capacity);
+	register_parathread::<T>(para_id);
+	para_id
+}
+
+fn fund<T: Config>(who: &T::AccountId) -> BalanceOf<T> {
+	let max_amount = OnDemand::<T>::spot_price();
+	T::Currency::make_free_balance_be(
+		who,
+		max_amount
+			.saturating_mul(10u32.into())
+			.saturating_add(T::Currency::minimum_balance()),
+	);
+	max_amount
+}
+
+fn assert_last_event<T: Config>(generic_event: <T as Config>::RuntimeEvent) {
+	let events = frame_system::Pallet::<T>::events();
+	let system_event: <T as frame_system::Config>::RuntimeEvent = generic_event.into();
+	// compare to the last event record
+	let frame_system::EventRecord { event, .. } = &events[events.len() - 1];
+	assert_eq!(event, &system_event);
+}
+
+frame_benchmarking::benchmarks! {
+	place_order_allow_death {
+		let caller: T::AccountId = whitelisted_caller();
+		let para_id = prepare_order::<T>();
+		let max_amount = fund::<T>(&caller);
+	}: _(RawOrigin::Signed(caller.clone()), max_amount, para_id, collator())
+	verify {
+		assert_last_event::<T>(
+			Event::<T>::OrderPlaced { para_id, spot_price: max_amount, ordered_by: caller }.into()
+		);
+	}
+
+	place_order_keep_alive {
+		let caller: T::AccountId = whitelisted_caller();
+		let para_id = prepare_order::<T>();
+		let max_amount = fund::<T>(&caller);
+	}: _(RawOrigin::Signed(caller.clone()), max_amount, para_id, collator())
+	verify {
+		assert_last_event::<T>(
+			Event::<T>::OrderPlaced { para_id, spot_price: max_amount, ordered_by: caller }.into()
+		);
+	}
+
+	on_initialize {
+		// a busy queue raises the spot traffic.
+		let _ = prepare_order::<T>();
+		let traffic = SpotTraffic::<T>::get();
+	}: {
+		OnDemand::<T>::on_initialize(1u32.into());
+	} verify {
+		assert!(SpotTraffic::<T>::get() > traffic);
+	}
+}
+
+frame_benchmarking::impl_benchmark_test_suite!(
+	OnDemand,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
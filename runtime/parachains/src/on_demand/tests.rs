@@ -0,0 +1,326 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use keyring::Sr25519Keyring;
+use primitives::{BlockNumber, SessionIndex, ValidationCode, ValidatorId};
+
+use crate::{
+	configuration::HostConfiguration,
+	initializer::SessionChangeNotification,
+	mock::{
+		assert_last_event, new_test_ext, AccountId, Balances, MockGenesisConfig, OnDemand,
+		OnDemandBaseFee, Paras, ParasShared, RuntimeEvent, RuntimeOrigin, Scheduler, System, Test,
+	},
+	paras::{ParaGenesisArgs, ParaKind},
+};
+
+const ALICE: AccountId = 1;
+const BOB: AccountId = 2;
+
+fn schedule_blank_para(id: ParaId, parakind: ParaKind) {
+	let validation_code: ValidationCode = vec![1, 2, 3].into();
+	assert_ok!(Paras::schedule_para_initialize(
+		id,
+		ParaGenesisArgs {
+			genesis_head: Vec::new().into(),
+			validation_code: validation_code.clone(),
+			para_kind: parakind,
+		}
+	));
+
+	assert_ok!(Paras::add_trusted_validation_code(RuntimeOrigin::root(), validation_code));
+}
+
+fn run_to_block(
+	to: BlockNumber,
+	new_session: impl Fn(BlockNumber) -> Option<SessionChangeNotification<BlockNumber>>,
+) {
+	while System::block_number() < to {
+		let b = System::block_number();
+
+		Scheduler::initializer_finalize();
+		Paras::initializer_finalize(b);
+
+		if let Some(mut notification) = new_session(b + 1) {
+			// We will make every session change trigger an action queue.
+			if notification.session_index == SessionIndex::default() {
+				notification.session_index = ParasShared::scheduled_session();
+			}
+			Paras::initializer_on_new_session(&notification);
+			Scheduler::initializer_on_new_session(&notification);
+		}
+
+		System::on_finalize(b);
+
+		System::on_initialize(b + 1);
+		System::set_block_number(b + 1);
+
+		Paras::initializer_initialize(b + 1);
+		Scheduler::initializer_initialize(b + 1);
+		OnDemand::on_initialize(b + 1);
+
+		// In the real runtime this is expected to be called by the `InclusionInherent` pallet.
+		Scheduler::clear();
+		Scheduler::schedule(Vec::new(), b + 1);
+	}
+}
+
+fn default_config() -> HostConfiguration<BlockNumber> {
+	HostConfiguration {
+		parathread_cores: 3,
+		group_rotation_frequency: 10,
+		chain_availability_period: 3,
+		thread_availability_period: 5,
+		scheduling_lookahead: 2,
+		parathread_retries: 1,
+		pvf_checking_enabled: false,
+		minimum_validation_upgrade_delay: 6,
+		..Default::default()
+	}
+}
+
+fn genesis_config() -> MockGenesisConfig {
+	MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: default_config(),
+			..Default::default()
+		},
+		..Default::default()
+	}
+}
+
+/// Register a parachain and `threads` parathreads, and start a session to activate them.
+fn setup_paras(threads: u32) -> (ParaId, Vec<ParaId>) {
+	let chain = ParaId::from(1_u32);
+	let threads: Vec<_> = (0..threads).map(|i| ParaId::from(100 + i)).collect();
+
+	schedule_blank_para(chain, ParaKind::Parachain);
+	for thread in &threads {
+		schedule_blank_para(*thread, ParaKind::Parathread);
+	}
+
+	run_to_block(1, |number| match number {
+		1 => Some(SessionChangeNotification {
+			new_config: default_config(),
+			validators: vec![
+				ValidatorId::from(Sr25519Keyring::Alice.public()),
+				ValidatorId::from(Sr25519Keyring::Bob.public()),
+				ValidatorId::from(Sr25519Keyring::Charlie.public()),
+				ValidatorId::from(Sr25519Keyring::Dave.public()),
+			],
+			..Default::default()
+		}),
+		_ => None,
+	});
+
+	(chain, threads)
+}
+
+fn collator() -> CollatorId {
+	CollatorId::from(Sr25519Keyring::Alice.public())
+}
+
+fn place_order(who: AccountId, para_id: ParaId) -> DispatchResult {
+	OnDemand::place_order_allow_death(
+		RuntimeOrigin::signed(who),
+		OnDemand::spot_price(),
+		para_id,
+		collator(),
+	)
+}
+
+#[test]
+fn place_order_queues_claim_and_burns_payment() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let (_, threads) = setup_paras(1);
+		let thread = threads[0];
+		Balances::make_free_balance_be(&ALICE, 1_000);
+		let issuance = Balances::total_issuance();
+
+		assert_eq!(OnDemand::spot_price(), OnDemandBaseFee::get());
+		assert_ok!(place_order(ALICE, thread));
+
+		assert_eq!(Balances::free_balance(ALICE), 900);
+		assert_eq!(Balances::total_issuance(), issuance - 100);
+		assert_eq!(Scheduler::parathread_queue_occupancy(), (1, 6));
+		assert_last_event(RuntimeEvent::OnDemand(Event::OrderPlaced {
+			para_id: thread,
+			spot_price: 100,
+			ordered_by: ALICE,
+		}));
+
+		// The claim is assigned to a parathread core in the next block.
+		run_to_block(2, |_| None);
+
+		assert_eq!(Scheduler::parathread_queue_occupancy(), (0, 6));
+		assert!(Scheduler::scheduled().iter().any(|assignment| assignment.para_id == thread));
+	});
+}
+
+#[test]
+fn place_order_keep_alive_does_not_reap_the_origin() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let (_, threads) = setup_paras(2);
+		Balances::make_free_balance_be(&ALICE, 100);
+		Balances::make_free_balance_be(&BOB, 100);
+
+		assert!(OnDemand::place_order_keep_alive(
+			RuntimeOrigin::signed(ALICE),
+			100,
+			threads[0],
+			collator(),
+		)
+		.is_err());
+		assert_eq!(Balances::free_balance(ALICE), 100);
+		assert_eq!(Scheduler::parathread_queue_occupancy(), (0, 6));
+
+		assert_ok!(OnDemand::place_order_allow_death(
+			RuntimeOrigin::signed(BOB),
+			100,
+			threads[1],
+			collator(),
+		));
+		assert!(!System::account_exists(&BOB));
+		assert_eq!(Scheduler::parathread_queue_occupancy(), (1, 6));
+	});
+}
+
+#[test]
+fn place_order_fails_when_spot_price_exceeds_max_amount() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let (_, threads) = setup_paras(1);
+		Balances::make_free_balance_be(&ALICE, 1_000);
+
+		assert_noop!(
+			OnDemand::place_order_allow_death(
+				RuntimeOrigin::signed(ALICE),
+				99,
+				threads[0],
+				collator(),
+			),
+			Error::<Test>::SpotPriceHigherThanMaxAmount,
+		);
+	});
+}
+
+#[test]
+fn place_order_fails_for_paras_which_are_not_parathreads() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let (chain, _) = setup_paras(1);
+		Balances::make_free_balance_be(&ALICE, 1_000);
+
+		assert_noop!(place_order(ALICE, chain), Error::<Test>::NotParathread);
+		assert_noop!(place_order(ALICE, ParaId::from(42_u32)), Error::<Test>::NotParathread);
+	});
+}
+
+#[test]
+fn place_order_fails_for_claimed_parathreads() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let (_, threads) = setup_paras(1);
+		Balances::make_free_balance_be(&ALICE, 1_000);
+
+		assert_ok!(place_order(ALICE, threads[0]));
+		assert_noop!(place_order(ALICE, threads[0]), Error::<Test>::AlreadyClaimed);
+
+		// Still claimed once the claim is assigned to a core.
+		run_to_block(2, |_| None);
+		assert_noop!(place_order(ALICE, threads[0]), Error::<Test>::AlreadyClaimed);
+	});
+}
+
+#[test]
+fn place_order_fails_when_queue_is_full() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let (_, threads) = setup_paras(7);
+		Balances::make_free_balance_be(&ALICE, 10_000);
+
+		for thread in &threads[..6] {
+			assert_ok!(place_order(ALICE, *thread));
+		}
+		assert_eq!(Scheduler::parathread_queue_occupancy(), (6, 6));
+
+		assert_noop!(place_order(ALICE, threads[6]), Error::<Test>::QueueFull);
+	});
+}
+
+#[test]
+fn spot_price_rises_with_queue_occupancy() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		let (_, threads) = setup_paras(6);
+		Balances::make_free_balance_be(&ALICE, 10_000);
+
+		// At the target utilization the price stays put.
+		for thread in &threads[..3] {
+			assert_ok!(place_order(ALICE, *thread));
+		}
+		OnDemand::on_initialize(2);
+		assert_eq!(OnDemand::spot_price(), 100);
+
+		// A full queue raises it by half the variability.
+		for thread in &threads[3..] {
+			assert_ok!(place_order(ALICE, *thread));
+		}
+		OnDemand::on_initialize(3);
+		assert_eq!(OnDemand::spot_price(), 105);
+		OnDemand::on_initialize(4);
+		assert_eq!(OnDemand::spot_price(), 110);
+
+		assert_noop!(
+			OnDemand::place_order_allow_death(
+				RuntimeOrigin::signed(ALICE),
+				109,
+				threads[0],
+				collator(),
+			),
+			Error::<Test>::SpotPriceHigherThanMaxAmount,
+		);
+	});
+}
+
+#[test]
+fn spot_price_decays_to_base_fee_when_idle() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		setup_paras(1);
+		SpotTraffic::<Test>::put(FixedU128::from_rational(3, 2));
+
+		let mut price = OnDemand::spot_price();
+		assert_eq!(price, 150);
+		for block in 2..20 {
+			OnDemand::on_initialize(block);
+			let next = OnDemand::spot_price();
+			assert!(next < price || next == OnDemandBaseFee::get());
+			price = next;
+		}
+
+		// Never below the base fee.
+		assert_eq!(price, OnDemandBaseFee::get());
+		assert_eq!(SpotTraffic::<Test>::get(), FixedU128::one());
+	});
+}
+
+#[test]
+fn queue_without_capacity_counts_as_idle() {
+	new_test_ext(genesis_config()).execute_with(|| {
+		assert_eq!(
+			OnDemand::next_spot_traffic(FixedU128::from_u32(2), 0, 0),
+			FixedU128::from_rational(19, 10),
+		);
+	});
+}
@@ -15,3 +15,22 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Put implementations of functions from staging APIs here.
+
+use crate::{dmp, hrmp, inclusion, FeeTracker};
+use primitives::{vstaging::HrmpChannelId, Id as ParaId};
+use sp_runtime::FixedU128;
+
+/// Implementation for the `dmp_delivery_fee_factor` function of the runtime API.
+pub fn dmp_delivery_fee_factor<T: dmp::Config>(recipient: ParaId) -> FixedU128 {
+	<dmp::Pallet<T>>::get_fee_factor(recipient)
+}
+
+/// Implementation for the `ump_delivery_fee_factor` function of the runtime API.
+pub fn ump_delivery_fee_factor<T: inclusion::Config>(sender: ParaId) -> FixedU128 {
+	<inclusion::Pallet<T>>::get_fee_factor(sender)
+}
+
+/// Implementation for the `hrmp_delivery_fee_factor` function of the runtime API.
+pub fn hrmp_delivery_fee_factor<T: hrmp::Config>(channel: HrmpChannelId) -> FixedU128 {
+	<hrmp::Pallet<T>>::get_fee_factor(channel)
+}
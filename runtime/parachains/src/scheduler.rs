@@ -98,6 +98,17 @@ impl Default for ParathreadClaimQueue {
 	}
 }
 
+/// Reasons a parathread claim might not be added to the queue.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParathreadClaimError {
+	/// The claim does not correspond to any live parathread.
+	NotParathread,
+	/// The queue is full.
+	QueueFull,
+	/// There is a competing claim in the queue or currently assigned to a core.
+	AlreadyClaimed,
+}
+
 /// Reasons a core might be freed
 #[derive(Clone, Copy)]
 pub enum FreedReason {
@@ -347,16 +358,21 @@ impl<T: Config> Pallet<T> {
 	/// Fails if the claim does not correspond to any live parathread.
 	#[allow(unused)]
 	pub fn add_parathread_claim(claim: ParathreadClaim) {
+		let _ = Self::try_add_parathread_claim(claim);
+	}
+
+	/// Like [`Self::add_parathread_claim`], but returns why the claim was not added.
+	pub fn try_add_parathread_claim(claim: ParathreadClaim) -> Result<(), ParathreadClaimError> {
 		if !<paras::Pallet<T>>::is_parathread(claim.0) {
-			return
+			return Err(ParathreadClaimError::NotParathread)
 		}
 
 		let config = <configuration::Pallet<T>>::config();
-		let queue_max_size = config.parathread_cores * config.scheduling_lookahead;
+		let queue_max_size = Self::parathread_queue_capacity(&config);
 
 		ParathreadQueue::<T>::mutate(|queue| {
 			if queue.queue.len() >= queue_max_size as usize {
-				return
+				return Err(ParathreadClaimError::QueueFull)
 			}
 
 			let para_id = claim.0;
@@ -371,14 +387,26 @@ impl<T: Config> Pallet<T> {
 				});
 
 			if competes_with_another {
-				return
+				return Err(ParathreadClaimError::AlreadyClaimed)
 			}
 
 			let entry = ParathreadEntry { claim, retries: 0 };
 			queue.enqueue_entry(entry, config.parathread_cores);
+			Ok(())
 		})
 	}
 
+	/// The number of claims in the parathread queue and the number of claims it can hold.
+	pub(crate) fn parathread_queue_occupancy() -> (u32, u32) {
+		let config = <configuration::Pallet<T>>::config();
+		(ParathreadQueue::<T>::get().queue.len() as u32, Self::parathread_queue_capacity(&config))
+	}
+
+	/// The number of claims the parathread queue can hold under `config`.
+	fn parathread_queue_capacity(config: &configuration::HostConfiguration<T::BlockNumber>) -> u32 {
+		config.parathread_cores * config.scheduling_lookahead
+	}
+
 	/// Free unassigned cores. Provide a list of cores that should be considered newly-freed along with the reason
 	/// for them being freed. The list is assumed to be sorted in ascending order by core index.
 	pub(crate) fn free_cores(just_freed_cores: impl IntoIterator<Item = (CoreIndex, FreedReason)>) {
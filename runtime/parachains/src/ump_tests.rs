@@ -659,3 +659,63 @@ fn cannot_enqueue_ump_while_offboarding() {
 		assert!(!Paras::is_valid_para(para));
 	});
 }
+
+#[test]
+fn delivery_fee_factor_rises_under_congestion_and_decays_afterwards() {
+	use crate::{
+		dmp::{EXPONENTIAL_FEE_BASE, MESSAGE_SIZE_FEE_BASE},
+		inclusion::{InitialFactor, UpwardDeliveryFeeFactor},
+		FeeTracker,
+	};
+	use sp_runtime::FixedU128;
+
+	let a = ParaId::from(228);
+	let msg = 1000u32.encode();
+	let genesis = GenesisConfigBuilder {
+		max_upward_message_size: 2048,
+		max_upward_queue_count: 8,
+		max_upward_queue_size: 8192,
+		..Default::default()
+	};
+
+	new_test_ext(genesis.build()).execute_with(|| {
+		let initial = InitialFactor::get();
+
+		// Up to half of the queue capacity the fee stays put.
+		for _ in 0..4 {
+			queue_upward_msg(a, msg.clone());
+			assert_eq!(ParaInclusion::get_fee_factor(a), initial);
+		}
+
+		// Beyond, every message raises it.
+		let mut expected = initial;
+		for _ in 0..3 {
+			queue_upward_msg(a, msg.clone());
+			expected = expected.saturating_mul(EXPONENTIAL_FEE_BASE);
+			assert_eq!(ParaInclusion::get_fee_factor(a), expected);
+		}
+
+		// Large messages raise it by an additional 0.001 per KB.
+		queue_upward_msg(a, vec![0; 2048]);
+		expected = expected
+			.saturating_mul(EXPONENTIAL_FEE_BASE + MESSAGE_SIZE_FEE_BASE * FixedU128::from_u32(2));
+		assert_eq!(ParaInclusion::get_fee_factor(a), expected);
+
+		// Once the queue is processed, the fee decays with every change of the queue until it
+		// is back at the initial value.
+		MessageQueue::service_queues(Weight::max_value());
+		let mut previous = ParaInclusion::get_fee_factor(a);
+		assert!(previous < expected);
+
+		for _ in 0..10 {
+			queue_upward_msg(a, msg.clone());
+			MessageQueue::service_queues(Weight::max_value());
+
+			let factor = ParaInclusion::get_fee_factor(a);
+			assert!(factor < previous || factor == initial);
+			previous = factor;
+		}
+		assert_eq!(previous, initial);
+		assert!(!UpwardDeliveryFeeFactor::<Test>::contains_key(a));
+	});
+}
@@ -289,4 +289,69 @@ impl<T: frame_system::Config> runtime_parachains::hrmp::WeightInfo for WeightInf
 			.saturating_add(T::DbWeight::get().reads(14))
 			.saturating_add(T::DbWeight::get().writes(8))
 	}
+	// NOT BENCHMARKED. The weights of the channel update calls below are conservative estimates,
+	// well above what their benchmarks are expected to measure, until this file is regenerated
+	// with `benchmark pallet` on the reference hardware. The storage accesses are counted from
+	// the benchmarked worst cases.
+
+	/// Reads `Hrmp::HrmpChannels`, `Hrmp::HrmpCloseChannelRequests`,
+	/// `Hrmp::HrmpChannelUpdateRequests`, `Configuration::ActiveConfig`, `System::Account` and
+	/// `Hrmp::HrmpChannelUpdateRequestsList`, writes the last two and the update request.
+	fn hrmp_request_channel_update() -> Weight {
+		Weight::from_parts(100_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 10_000))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Reads `Hrmp::HrmpChannelUpdateRequests`, `Hrmp::HrmpChannels` and `System::Account`,
+	/// writes the update request and the account.
+	fn hrmp_accept_channel_update() -> Weight {
+		Weight::from_parts(100_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 10_000))
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	/// Reads `Hrmp::HrmpChannelUpdateRequests`, `Hrmp::HrmpChannelUpdateRequestsList`,
+	/// `Hrmp::HrmpChannels` and `System::Account`, writes all but the channel.
+	/// The range of component `c` is `[0, 128]`.
+	fn hrmp_cancel_channel_update(c: u32, ) -> Weight {
+		Weight::from_parts(100_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 10_000))
+			.saturating_add(Weight::from_parts(1_000_000, 0).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(Weight::from_parts(0, 100).saturating_mul(c.into()))
+	}
+	/// Reads and writes `Hrmp::HrmpChannelUpdateRequestsList`, and per update
+	/// `Hrmp::HrmpChannelUpdateRequests`, `Hrmp::HrmpChannels` and the `System::Account` of both
+	/// participants.
+	/// The range of component `c` is `[0, 128]`.
+	fn force_process_hrmp_update(c: u32, ) -> Weight {
+		Weight::from_parts(20_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 2_000))
+			.saturating_add(Weight::from_parts(100_000_000, 0).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads((4_u64).saturating_mul(c.into())))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(c.into())))
+			.saturating_add(Weight::from_parts(0, 10_000).saturating_mul(c.into()))
+	}
+	// NOT BENCHMARKED. The weight of `establish_system_channels` is a conservative estimate, well
+	// above what its benchmark is expected to measure, until this file is regenerated with
+	// `benchmark pallet` on the reference hardware. The call is free and any signed origin can
+	// make it, so it must not be under-estimated. The storage accesses are counted from the
+	// benchmarked worst case, in which a pending open request is canceled in both directions.
+
+	/// Reads `Paras::ParaLifecycles`, `Configuration::ActiveConfig`,
+	/// `Hrmp::HrmpOpenChannelRequestsList` and, for both directions, `Hrmp::HrmpChannels`,
+	/// `Hrmp::HrmpOpenChannelRequests`, `Hrmp::HrmpOpenChannelRequestCount`, `System::Account`,
+	/// `Hrmp::HrmpEgressChannelsIndex`, `Hrmp::HrmpIngressChannelsIndex`,
+	/// `Hrmp::HrmpAcceptedChannelRequestCount`, `Dmp::DownwardMessageQueues` and
+	/// `Dmp::DownwardMessageQueueHeads`.
+	fn establish_system_channels() -> Weight {
+		Weight::from_parts(400_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 20_000))
+			.saturating_add(T::DbWeight::get().reads(22))
+			.saturating_add(T::DbWeight::get().writes(15))
+	}
 }
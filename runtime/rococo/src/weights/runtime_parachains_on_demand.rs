@@ -0,0 +1,59 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights for `runtime_parachains::on_demand`
+//!
+//! NOT BENCHMARKED. These are conservative estimates, well above what the benchmarks in
+//! `runtime_parachains::on_demand::benchmarking` are expected to measure, until this file is
+//! generated with `benchmark pallet` on the reference hardware. The storage accesses are counted
+//! from the benchmarked worst cases.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::Weight};
+use core::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::on_demand`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> runtime_parachains::on_demand::WeightInfo for WeightInfo<T> {
+	/// Reads `OnDemand::SpotTraffic`, `Balances::TotalIssuance`, `Paras::ParaLifecycles`,
+	/// `Configuration::ActiveConfig`, `ParaScheduler::ParathreadQueue` and
+	/// `ParaScheduler::ParathreadClaimIndex`, writes the last two and `Balances::TotalIssuance`.
+	fn place_order_allow_death() -> Weight {
+		Weight::from_parts(150_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 20_000))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Same storage accesses as `place_order_allow_death`.
+	fn place_order_keep_alive() -> Weight {
+		Weight::from_parts(150_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 20_000))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Reads `Configuration::ActiveConfig`, `ParaScheduler::ParathreadQueue` and
+	/// `OnDemand::SpotTraffic`, writes the last one.
+	fn on_initialize() -> Weight {
+		Weight::from_parts(50_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 10_000))
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+}
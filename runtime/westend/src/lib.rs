@@ -1326,7 +1326,10 @@ pub mod migrations {
 	}
 
 	/// Unreleased migrations. Add new ones here:
-	pub type Unreleased = (pallet_im_online::migration::v1::Migration<Runtime>,);
+	pub type Unreleased = (
+		pallet_im_online::migration::v1::Migration<Runtime>,
+		parachains_hrmp::migration::v1::MigrateToV1<Runtime>,
+	);
 }
 
 /// Helpers to configure all migrations.